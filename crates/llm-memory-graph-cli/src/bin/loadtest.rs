@@ -0,0 +1,538 @@
+//! Load and soak testing for LLM Memory Graph
+//!
+//! Drives a mixed workload against an embedded database or a running gRPC
+//! server and reports latency histograms and achieved throughput. New
+//! sessions start at a fixed rate; each adds a number of prompt/response
+//! pairs and, between writes, reads its session back so that reads make up
+//! the requested share of operations. Sessions that would exceed the
+//! concurrency limit are dropped rather than queued, so an overloaded target
+//! shows up as a shortfall against the requested rate.
+//!
+//! Built only with the `loadtest` feature:
+//!
+//! ```bash
+//! cargo run --release -p llm-memory-graph-cli --features loadtest --bin loadtest -- \
+//!     --duration-secs 600 --sessions-per-sec 50 --read-ratio 0.7
+//!
+//! # Against a server
+//! cargo run --release -p llm-memory-graph-cli --features loadtest --bin loadtest -- \
+//!     --target grpc --address http://localhost:50051
+//! ```
+
+use anyhow::{bail, Result};
+use clap::{Parser, ValueEnum};
+use colored::Colorize;
+use comfy_table::Table;
+use hdrhistogram::Histogram;
+use llm_memory_graph::{engine::AsyncMemoryGraph, Config, NodeId, SessionId, TokenUsage};
+use llm_memory_graph_client::MemoryGraphClient;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+use tokio::time::MissedTickBehavior;
+
+/// Highest latency the histograms track, in microseconds
+const MAX_LATENCY_US: u64 = 60_000_000;
+
+/// LLM Memory Graph load tester
+#[derive(Parser)]
+#[command(name = "loadtest")]
+#[command(author, version, about, long_about = None)]
+struct Args {
+    /// What to drive the workload against
+    #[arg(long, value_enum, default_value_t = TargetKind::Embedded)]
+    target: TargetKind,
+
+    /// Database directory for the embedded target
+    #[arg(short, long, default_value = "./loadtest-data")]
+    db_path: PathBuf,
+
+    /// Server address for the gRPC target
+    #[arg(short, long, default_value = "http://localhost:50051")]
+    address: String,
+
+    /// How long to keep starting sessions
+    #[arg(long, default_value_t = 30)]
+    duration_secs: u64,
+
+    /// New sessions started per second
+    #[arg(long, default_value_t = 10.0)]
+    sessions_per_sec: f64,
+
+    /// Prompt/response pairs added to each session
+    #[arg(long, default_value_t = 5)]
+    prompts_per_session: usize,
+
+    /// Share of operations that are reads, from 0 up to (not including) 1
+    #[arg(long, default_value_t = 0.5)]
+    read_ratio: f64,
+
+    /// Size of each prompt in bytes
+    #[arg(long, default_value_t = 256)]
+    prompt_bytes: usize,
+
+    /// Size of each response in bytes
+    #[arg(long, default_value_t = 1024)]
+    response_bytes: usize,
+
+    /// Sessions in flight at once
+    #[arg(short, long, default_value_t = 64)]
+    concurrency: usize,
+
+    /// Seconds between progress lines; 0 disables them
+    #[arg(long, default_value_t = 10)]
+    report_interval_secs: u64,
+
+    /// Print the final report as JSON
+    #[arg(long)]
+    json: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+enum TargetKind {
+    /// An `AsyncMemoryGraph` opened in this process
+    Embedded,
+    /// A remote server, through `MemoryGraphClient`
+    Grpc,
+}
+
+/// The system under test
+enum Target {
+    Embedded(Box<AsyncMemoryGraph>),
+    Grpc(MemoryGraphClient),
+}
+
+impl Target {
+    async fn open(args: &Args) -> Result<Self> {
+        Ok(match args.target {
+            TargetKind::Embedded => Self::Embedded(Box::new(
+                AsyncMemoryGraph::open(Config::new(&args.db_path)).await?,
+            )),
+            TargetKind::Grpc => Self::Grpc(MemoryGraphClient::connect(args.address.clone()).await?),
+        })
+    }
+
+    async fn create_session(&self) -> Result<String> {
+        Ok(match self {
+            Self::Embedded(graph) => graph.create_session().await?.id.to_string(),
+            Self::Grpc(client) => client.create_session(HashMap::new()).await?,
+        })
+    }
+
+    async fn add_prompt(&self, session_id: &str, content: String) -> Result<String> {
+        Ok(match self {
+            Self::Embedded(graph) => {
+                let session_id = SessionId::from(session_id.parse::<uuid::Uuid>()?);
+                graph
+                    .add_prompt(session_id, content, None)
+                    .await?
+                    .to_string()
+            }
+            Self::Grpc(client) => {
+                client
+                    .add_prompt(session_id.to_string(), content, None)
+                    .await?
+                    .id
+            }
+        })
+    }
+
+    async fn add_response(&self, prompt_id: &str, content: String) -> Result<()> {
+        match self {
+            Self::Embedded(graph) => {
+                let prompt_id = NodeId::from(prompt_id.parse::<uuid::Uuid>()?);
+                graph
+                    .add_response(prompt_id, content, TokenUsage::new(64, 256), None)
+                    .await?;
+            }
+            Self::Grpc(client) => {
+                client
+                    .add_response(prompt_id.to_string(), content, None, None)
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Read back the nodes of a session
+    async fn read_session(&self, session_id: &str) -> Result<usize> {
+        Ok(match self {
+            Self::Embedded(graph) => {
+                let session_id = SessionId::from(session_id.parse::<uuid::Uuid>()?);
+                graph.get_session_nodes(&session_id).await?.len()
+            }
+            Self::Grpc(client) => client
+                .query(Some(session_id.to_string()), None, 100, 0)
+                .await?
+                .nodes
+                .len(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+enum Operation {
+    CreateSession,
+    AddPrompt,
+    AddResponse,
+    ReadSession,
+}
+
+impl Operation {
+    const ALL: [Self; 4] = [
+        Self::CreateSession,
+        Self::AddPrompt,
+        Self::AddResponse,
+        Self::ReadSession,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::CreateSession => "create_session",
+            Self::AddPrompt => "add_prompt",
+            Self::AddResponse => "add_response",
+            Self::ReadSession => "read_session",
+        }
+    }
+}
+
+/// Latencies and failures collected while the workload runs
+struct Recorder {
+    latencies: Mutex<HashMap<Operation, Histogram<u64>>>,
+    errors: Mutex<HashMap<Operation, u64>>,
+    sessions_completed: AtomicU64,
+    sessions_dropped: AtomicU64,
+}
+
+impl Recorder {
+    fn new() -> Self {
+        let histograms = Operation::ALL
+            .into_iter()
+            .map(|op| {
+                let histogram = Histogram::new_with_bounds(1, MAX_LATENCY_US, 3)
+                    .expect("valid histogram bounds");
+                (op, histogram)
+            })
+            .collect();
+        Self {
+            latencies: Mutex::new(histograms),
+            errors: Mutex::new(HashMap::new()),
+            sessions_completed: AtomicU64::new(0),
+            sessions_dropped: AtomicU64::new(0),
+        }
+    }
+
+    /// Run `future`, recording its latency if it succeeds and its failure
+    /// otherwise
+    async fn time<T>(
+        &self,
+        op: Operation,
+        future: impl std::future::Future<Output = Result<T>>,
+    ) -> Option<T> {
+        let start = Instant::now();
+        match future.await {
+            Ok(value) => {
+                let micros = u64::try_from(start.elapsed().as_micros()).unwrap_or(u64::MAX);
+                self.latencies
+                    .lock()
+                    .unwrap()
+                    .get_mut(&op)
+                    .expect("histogram for every operation")
+                    .saturating_record(micros.max(1));
+                Some(value)
+            }
+            Err(e) => {
+                log_error(op, &e);
+                *self.errors.lock().unwrap().entry(op).or_default() += 1;
+                None
+            }
+        }
+    }
+
+    fn total_operations(&self) -> u64 {
+        self.latencies
+            .lock()
+            .unwrap()
+            .values()
+            .map(Histogram::len)
+            .sum()
+    }
+
+    fn report(&self, elapsed: Duration, args: &Args) -> Report {
+        let latencies = self.latencies.lock().unwrap();
+        let errors = self.errors.lock().unwrap();
+        let seconds = elapsed.as_secs_f64();
+        let operations = Operation::ALL
+            .into_iter()
+            .map(|op| {
+                let histogram = &latencies[&op];
+                OperationReport {
+                    operation: op.name(),
+                    count: histogram.len(),
+                    errors: errors.get(&op).copied().unwrap_or(0),
+                    ops_per_sec: histogram.len() as f64 / seconds,
+                    mean_us: histogram.mean(),
+                    p50_us: histogram.value_at_quantile(0.50),
+                    p90_us: histogram.value_at_quantile(0.90),
+                    p99_us: histogram.value_at_quantile(0.99),
+                    p999_us: histogram.value_at_quantile(0.999),
+                    max_us: histogram.max(),
+                }
+            })
+            .collect::<Vec<_>>();
+        let total: u64 = operations.iter().map(|op| op.count).sum();
+        let completed = self.sessions_completed.load(Ordering::Relaxed);
+        Report {
+            target: args.target,
+            elapsed_secs: seconds,
+            requested_sessions_per_sec: args.sessions_per_sec,
+            achieved_sessions_per_sec: completed as f64 / seconds,
+            sessions_completed: completed,
+            sessions_dropped: self.sessions_dropped.load(Ordering::Relaxed),
+            operations_per_sec: total as f64 / seconds,
+            operations,
+        }
+    }
+}
+
+fn log_error(op: Operation, error: &anyhow::Error) {
+    // Only the first line, so a failing target does not flood the terminal
+    let message = error.to_string();
+    let first = message.lines().next().unwrap_or_default();
+    eprintln!("{} {}: {}", "error".red().bold(), op.name(), first);
+}
+
+#[derive(serde::Serialize)]
+struct Report {
+    target: TargetKind,
+    elapsed_secs: f64,
+    requested_sessions_per_sec: f64,
+    achieved_sessions_per_sec: f64,
+    sessions_completed: u64,
+    sessions_dropped: u64,
+    operations_per_sec: f64,
+    operations: Vec<OperationReport>,
+}
+
+#[derive(serde::Serialize)]
+struct OperationReport {
+    operation: &'static str,
+    count: u64,
+    errors: u64,
+    ops_per_sec: f64,
+    mean_us: f64,
+    p50_us: u64,
+    p90_us: u64,
+    p99_us: u64,
+    p999_us: u64,
+    max_us: u64,
+}
+
+impl Report {
+    fn print(&self) {
+        println!("\n{}", "Load Test Results".bold().green());
+        println!("{}", "=================".green());
+        println!("{:28} {:.1}s", "Elapsed:", self.elapsed_secs);
+        println!(
+            "{:28} {:.2} (requested {:.2})",
+            "Sessions/sec:", self.achieved_sessions_per_sec, self.requested_sessions_per_sec
+        );
+        println!("{:28} {}", "Sessions completed:", self.sessions_completed);
+        if self.sessions_dropped > 0 {
+            println!(
+                "{:28} {}",
+                "Sessions dropped:",
+                self.sessions_dropped.to_string().yellow()
+            );
+        }
+        println!("{:28} {:.1}", "Operations/sec:", self.operations_per_sec);
+
+        let mut table = Table::new();
+        table.set_header(vec![
+            "operation",
+            "count",
+            "errors",
+            "ops/sec",
+            "mean",
+            "p50",
+            "p90",
+            "p99",
+            "p99.9",
+            "max",
+        ]);
+        for op in &self.operations {
+            table.add_row(vec![
+                op.operation.to_string(),
+                op.count.to_string(),
+                op.errors.to_string(),
+                format!("{:.1}", op.ops_per_sec),
+                format_latency(op.mean_us as u64),
+                format_latency(op.p50_us),
+                format_latency(op.p90_us),
+                format_latency(op.p99_us),
+                format_latency(op.p999_us),
+                format_latency(op.max_us),
+            ]);
+        }
+        println!("\n{table}");
+    }
+}
+
+fn format_latency(micros: u64) -> String {
+    if micros >= 1_000_000 {
+        format!("{:.2}s", micros as f64 / 1_000_000.0)
+    } else if micros >= 1_000 {
+        format!("{:.2}ms", micros as f64 / 1_000.0)
+    } else {
+        format!("{micros}µs")
+    }
+}
+
+/// Deterministic filler text of exactly `bytes` bytes, prefixed with `tag`
+/// so that payloads are distinct
+fn payload(tag: &str, bytes: usize) -> String {
+    const FILLER: &str = "the quick brown fox jumps over the lazy dog ";
+    let mut text = String::with_capacity(bytes);
+    text.push_str(tag);
+    text.push(' ');
+    while text.len() < bytes {
+        text.push_str(FILLER);
+    }
+    text.truncate(bytes);
+    text
+}
+
+/// Run one session's worth of the workload
+async fn run_session(target: &Target, recorder: &Recorder, args: &Args, session_number: u64) {
+    let Some(session_id) = recorder
+        .time(Operation::CreateSession, target.create_session())
+        .await
+    else {
+        return;
+    };
+
+    // Reads owed per write, carried between writes so the mix converges on
+    // `read_ratio` without randomness
+    let reads_per_write = args.read_ratio / (1.0 - args.read_ratio);
+    let mut owed_reads = 0.0;
+
+    for turn in 0..args.prompts_per_session {
+        let tag = format!("s{session_number}t{turn}");
+        let Some(prompt_id) = recorder
+            .time(
+                Operation::AddPrompt,
+                target.add_prompt(&session_id, payload(&tag, args.prompt_bytes)),
+            )
+            .await
+        else {
+            continue;
+        };
+        recorder
+            .time(
+                Operation::AddResponse,
+                target.add_response(&prompt_id, payload(&tag, args.response_bytes)),
+            )
+            .await;
+
+        owed_reads += 2.0 * reads_per_write;
+        while owed_reads >= 1.0 {
+            recorder
+                .time(Operation::ReadSession, target.read_session(&session_id))
+                .await;
+            owed_reads -= 1.0;
+        }
+    }
+    recorder.sessions_completed.fetch_add(1, Ordering::Relaxed);
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let args = Arc::new(Args::parse());
+    if !(0.0..1.0).contains(&args.read_ratio) {
+        bail!("--read-ratio must be at least 0 and less than 1");
+    }
+    if !(args.sessions_per_sec > 0.0 && args.sessions_per_sec.is_finite()) {
+        bail!("--sessions-per-sec must be positive");
+    }
+    if args.concurrency == 0 {
+        bail!("--concurrency must be at least 1");
+    }
+
+    let target = Arc::new(Target::open(&args).await?);
+    let recorder = Arc::new(Recorder::new());
+    let permits = Arc::new(Semaphore::new(args.concurrency));
+
+    if !args.json {
+        println!(
+            "{} {} sessions/sec for {}s against {:?} ({} prompts per session, read ratio {})",
+            "Running".yellow(),
+            args.sessions_per_sec,
+            args.duration_secs,
+            args.target,
+            args.prompts_per_session,
+            args.read_ratio
+        );
+    }
+
+    let started = Instant::now();
+    let deadline = started + Duration::from_secs(args.duration_secs);
+    let mut ticks = tokio::time::interval(Duration::from_secs_f64(1.0 / args.sessions_per_sec));
+    ticks.set_missed_tick_behavior(MissedTickBehavior::Burst);
+    let show_progress = args.report_interval_secs > 0 && !args.json;
+    let period = Duration::from_secs(args.report_interval_secs.max(1));
+    let mut progress = tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+    let mut last_operations = 0;
+    let mut sessions = tokio::task::JoinSet::new();
+    let mut session_number = 0;
+
+    while Instant::now() < deadline {
+        tokio::select! {
+            _ = ticks.tick() => {
+                let Ok(permit) = Arc::clone(&permits).try_acquire_owned() else {
+                    recorder.sessions_dropped.fetch_add(1, Ordering::Relaxed);
+                    continue;
+                };
+                let (target, recorder, args) =
+                    (Arc::clone(&target), Arc::clone(&recorder), Arc::clone(&args));
+                session_number += 1;
+                let number = session_number;
+                sessions.spawn(async move {
+                    run_session(&target, &recorder, &args, number).await;
+                    drop(permit);
+                });
+            }
+            _ = progress.tick(), if show_progress => {
+                let total = recorder.total_operations();
+                println!(
+                    "[{:>5}s] {:>8.1} ops/sec, {} sessions completed, {} dropped, {} in flight",
+                    started.elapsed().as_secs(),
+                    (total - last_operations) as f64 / period.as_secs_f64(),
+                    recorder.sessions_completed.load(Ordering::Relaxed),
+                    recorder.sessions_dropped.load(Ordering::Relaxed),
+                    sessions.len()
+                );
+                last_operations = total;
+            }
+            Some(_) = sessions.join_next() => {}
+        }
+    }
+
+    // Let sessions already started finish, so their latencies are counted
+    while sessions.join_next().await.is_some() {}
+
+    let report = recorder.report(started.elapsed(), &args);
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        report.print();
+    }
+
+    if let Target::Embedded(graph) = target.as_ref() {
+        graph.flush().await?;
+    }
+    Ok(())
+}
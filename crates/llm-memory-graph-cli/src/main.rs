@@ -1,17 +1,41 @@
 //! Command-line interface for LLM Memory Graph management
 //!
 //! This tool provides commands for managing and querying the memory graph database:
-//! - Database inspection and statistics
+//! - Database inspection and statistics, with distribution histograms
 //! - Node queries
-//! - Data export
+//! - Cursor-paged listings of sessions, nodes, edges, templates and agents
+//! - Encrypted, signed session export and import
+//! - `.lmg` archives: session exports, whole-database backups, restores and
+//!   offline inspection
+//! - Aliases mapping external keys to sessions and nodes
+//! - Legal holds protecting sessions from deletion
+//! - Namespace management
+//! - Materialized views
+//! - Embedding backfill
+//! - Database diffs for verifying backups and migrations
+//! - Snapshot shipping to read replicas
 //! - Performance diagnostics
 
 use anyhow::Result;
 use clap::{Parser, Subcommand};
 use colored::Colorize;
+use llm_memory_graph::archive::{ArchiveManifest, ArchiveReader, ARCHIVE_EXTENSION};
+use llm_memory_graph::diff::RecordDiff;
+use llm_memory_graph::distribution::{DistributionOptions, GraphDistributions, Histogram};
+use llm_memory_graph::engine::{HttpEmbedder, LegalHold, PatternExtractor};
+use llm_memory_graph::export::{ExportOptions, SessionExport};
+use llm_memory_graph::pagination::{EdgeDirection, Page, PageCursor, DEFAULT_PAGE_SIZE};
+use llm_memory_graph::shipping::{
+    list_manifests, verify_snapshot, DirectoryStore, SnapshotManifest, SnapshotReplica,
+    SnapshotShipper, DEFAULT_SNAPSHOT_RETENTION,
+};
+use llm_memory_graph::storage::{SledBackend, StorageBackend, DEFAULT_NAMESPACE};
 use llm_memory_graph::{engine::AsyncMemoryGraph, Config};
-use llm_memory_graph_types::{NodeId, SessionId};
-use std::path::PathBuf;
+use llm_memory_graph_types::{AliasTarget, Node, NodeId, NodeType, SessionId};
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
 use uuid::Uuid;
 
 /// LLM Memory Graph CLI - Database management and query tool
@@ -23,6 +47,10 @@ struct Cli {
     #[arg(short, long, default_value = "./data")]
     db_path: PathBuf,
 
+    /// Namespace (logical graph) within the database
+    #[arg(short, long, default_value = DEFAULT_NAMESPACE)]
+    namespace: String,
+
     /// Output format (text, json)
     #[arg(short, long, default_value = "text")]
     format: OutputFormat,
@@ -52,28 +80,83 @@ impl std::str::FromStr for OutputFormat {
 #[derive(Subcommand)]
 enum Commands {
     /// Show database statistics
-    Stats,
+    Stats {
+        /// Also show distributions: nodes per session, tokens per response,
+        /// node degrees and sessions per day
+        #[arg(long)]
+        distributions: bool,
+
+        /// Fraction of sessions scanned for the distributions, in (0, 1]
+        #[arg(long, default_value_t = 1.0, requires = "distributions")]
+        sample_rate: f64,
+    },
 
     /// Get session details
     Session {
-        /// Session ID (UUID format)
+        /// Session ID (UUID format) or alias
         session_id: String,
     },
 
     /// Get node details
     Node {
-        /// Node ID (UUID format)
+        /// Node ID (UUID format) or alias
         node_id: String,
     },
 
-    /// Export session data
+    /// List sessions, a session's nodes, a node's edges, templates or agents
+    /// one page at a time
+    List {
+        #[command(subcommand)]
+        listing: Listing,
+    },
+
+    /// List custom nodes in a session
+    Custom {
+        /// Session ID (UUID format) or alias
+        session_id: String,
+
+        /// Only list nodes of this custom type
+        #[arg(short = 't', long)]
+        type_name: Option<String>,
+    },
+
+    /// Export a session with a manifest of its records
+    ///
+    /// Writes an `.lmg` archive when the output path ends in `.lmg`, and a
+    /// JSON export otherwise.
     Export {
-        /// Session ID (UUID format)
+        /// Session ID (UUID format) or alias
         session_id: String,
 
         /// Output file path
         #[arg(short, long)]
         output: PathBuf,
+
+        #[command(flatten)]
+        keys: ExportKeys,
+    },
+
+    /// Verify a session export or `.lmg` archive and import it
+    Import {
+        /// Export or archive file path
+        input: PathBuf,
+
+        #[command(flatten)]
+        keys: ExportKeys,
+    },
+
+    /// Write a backup of the whole database to an `.lmg` archive
+    Backup {
+        /// Output file path
+        #[arg(short, long)]
+        output: PathBuf,
+    },
+
+    /// Show an `.lmg` archive's manifest and verify its blocks, without
+    /// opening a database
+    InspectArchive {
+        /// Archive file path
+        input: PathBuf,
     },
 
     /// Flush database to disk
@@ -81,35 +164,464 @@ enum Commands {
 
     /// Verify database integrity
     Verify,
+
+    /// Rebuild secondary indexes and views from the stored nodes and edges
+    Reindex,
+
+    /// Set, resolve, remove or list aliases for sessions and nodes
+    Alias {
+        #[command(subcommand)]
+        action: Option<AliasAction>,
+    },
+
+    /// List, place or release legal holds on sessions
+    Holds {
+        #[command(subcommand)]
+        action: Option<HoldAction>,
+    },
+
+    /// List or manage namespaces
+    Namespaces {
+        #[command(subcommand)]
+        action: Option<NamespaceAction>,
+    },
+
+    /// List, show or rebuild materialized views
+    Views {
+        #[command(subcommand)]
+        action: Option<ViewAction>,
+    },
+
+    /// Compute or inspect node embeddings
+    Embeddings {
+        #[command(subcommand)]
+        action: EmbeddingAction,
+    },
+
+    /// Extract or query entities and facts mentioned in responses
+    Knowledge {
+        #[command(subcommand)]
+        action: KnowledgeAction,
+    },
+
+    /// Ship snapshots of this database to a directory, or ingest them into a
+    /// read replica
+    Snapshots {
+        #[command(subcommand)]
+        action: SnapshotAction,
+    },
+
+    /// Compare two databases, such as a database and its backup, node by
+    /// node and edge by edge
+    DiffDb {
+        /// Database directory to compare from
+        path_a: PathBuf,
+
+        /// Database directory to compare with
+        path_b: PathBuf,
+    },
+}
+
+/// Position and size of one page of a listing
+#[derive(clap::Args)]
+struct PageArgs {
+    /// Most items to list
+    #[arg(short, long, default_value_t = DEFAULT_PAGE_SIZE)]
+    limit: usize,
+
+    /// Continue from the cursor printed with the previous page
+    #[arg(short, long)]
+    cursor: Option<String>,
+}
+
+impl PageArgs {
+    fn cursor(&self) -> Result<Option<PageCursor>> {
+        Ok(self.cursor.as_deref().map(str::parse).transpose()?)
+    }
+}
+
+#[derive(Subcommand)]
+enum Listing {
+    /// Sessions, oldest first
+    Sessions {
+        #[command(flatten)]
+        page: PageArgs,
+    },
+
+    /// Nodes of a session, oldest first
+    Nodes {
+        /// Session ID (UUID format) or alias
+        session_id: String,
+
+        #[command(flatten)]
+        page: PageArgs,
+    },
+
+    /// Edges from a node, or to it with --incoming
+    Edges {
+        /// Node ID (UUID format) or alias
+        node_id: String,
+
+        /// List edges to the node rather than from it
+        #[arg(long)]
+        incoming: bool,
+
+        #[command(flatten)]
+        page: PageArgs,
+    },
+
+    /// Prompt templates, oldest first
+    Templates {
+        #[command(flatten)]
+        page: PageArgs,
+    },
+
+    /// Agents, oldest first
+    Agents {
+        #[command(flatten)]
+        page: PageArgs,
+    },
+}
+
+/// Key files used to seal and open session exports
+#[derive(clap::Args)]
+struct ExportKeys {
+    /// File holding a 256-bit AES key, as 64 hex digits or 32 raw bytes
+    #[arg(long)]
+    encryption_key_file: Option<PathBuf>,
+
+    /// File holding the HMAC key that signs the manifest
+    #[arg(long)]
+    signing_key_file: Option<PathBuf>,
+}
+
+impl ExportKeys {
+    fn options(&self) -> Result<ExportOptions> {
+        let mut options = ExportOptions::new();
+        if let Some(path) = &self.encryption_key_file {
+            let bytes = std::fs::read(path)?;
+            let text = String::from_utf8_lossy(&bytes);
+            let text = text.trim();
+            let key: Vec<u8> = if text.len() == 64 && text.chars().all(|c| c.is_ascii_hexdigit()) {
+                (0..64)
+                    .step_by(2)
+                    .map(|i| u8::from_str_radix(&text[i..i + 2], 16))
+                    .collect::<std::result::Result<_, _>>()?
+            } else {
+                bytes
+            };
+            let key: [u8; 32] = key.try_into().map_err(|_| {
+                anyhow::anyhow!("{} must hold 64 hex digits or 32 bytes", path.display())
+            })?;
+            options = options.with_encryption_key(key);
+        }
+        if let Some(path) = &self.signing_key_file {
+            options = options.with_signing_key(std::fs::read(path)?);
+        }
+        Ok(options)
+    }
+}
+
+#[derive(Subcommand)]
+enum NamespaceAction {
+    /// List namespaces with their statistics (default)
+    List,
+
+    /// Delete a namespace and everything in it
+    Drop {
+        /// Namespace to delete
+        name: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum HoldAction {
+    /// List held sessions, oldest hold first (default)
+    List,
+
+    /// Place a legal hold on a session
+    Place {
+        /// Session ID (UUID format) or alias
+        session_id: String,
+
+        /// Why the session is held, such as a case reference
+        #[arg(short, long)]
+        reason: String,
+    },
+
+    /// Release the legal hold on a session
+    Release {
+        /// Session ID (UUID format) or alias
+        session_id: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum AliasAction {
+    /// List aliases, optionally only those with a prefix (default)
+    List {
+        /// Only list aliases starting with this prefix
+        #[arg(default_value = "")]
+        prefix: String,
+    },
+
+    /// Point an alias at a session, or at a node with --node
+    Set {
+        /// External key, such as "ext:conv-42"
+        alias: String,
+
+        /// Session or node ID (UUID format)
+        target: String,
+
+        /// Treat the target as a node ID rather than a session ID
+        #[arg(long)]
+        node: bool,
+    },
+
+    /// Show what an alias points at
+    Resolve {
+        /// Alias to look up
+        alias: String,
+    },
+
+    /// Remove an alias
+    Remove {
+        /// Alias to remove
+        alias: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum ViewAction {
+    /// List the defined views (default)
+    List,
+
+    /// Show every group of a view with its totals
+    Show {
+        /// View name
+        name: String,
+    },
+
+    /// Recompute every view from the stored nodes
+    Rebuild,
+}
+
+#[derive(Subcommand)]
+enum EmbeddingAction {
+    /// Embed every prompt and response without an embedding from the model
+    Backfill {
+        /// Base URL of an OpenAI-compatible embeddings API
+        #[arg(long)]
+        url: String,
+
+        /// Embedding model name
+        #[arg(long)]
+        model: String,
+
+        /// Environment variable holding the API key, if the API needs one
+        #[arg(long)]
+        api_key_env: Option<String>,
+    },
+
+    /// Show the stored embedding of a node
+    Show {
+        /// Node ID (UUID format) or alias
+        node_id: String,
+    },
+
+    /// List prompts and responses nearly identical to an older node
+    Duplicates {
+        /// Lowest cosine similarity counted as a duplicate
+        #[arg(long, default_value_t = 0.95)]
+        threshold: f32,
+    },
+}
+
+#[derive(Subcommand)]
+enum KnowledgeAction {
+    /// Extract entities matching patterns, and the sentences mentioning them,
+    /// from every response of a session
+    Extract {
+        /// Session ID (UUID format) or alias
+        session_id: String,
+
+        /// Entity kind and regular expression, as KIND=PATTERN (repeatable)
+        #[arg(long = "pattern", required = true)]
+        patterns: Vec<String>,
+    },
+
+    /// Show everything known about an entity
+    About {
+        /// Entity name, compared case-insensitively
+        name: String,
+    },
+
+    /// List extracted entities
+    Entities,
+
+    /// Record a subject's predicate, superseding its current value
+    Assert {
+        /// What the fact is about
+        subject: String,
+        /// Which attribute of the subject
+        predicate: String,
+        /// The new value
+        value: String,
+    },
+
+    /// Show every value asserted for a subject's predicate, newest first
+    History {
+        /// What the fact is about
+        subject: String,
+        /// Which attribute of the subject
+        predicate: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum SnapshotAction {
+    /// Write a snapshot archive of the database to a directory
+    Ship {
+        /// Directory receiving the archives
+        dir: PathBuf,
+
+        /// Keep shipping every this many seconds until interrupted
+        #[arg(long)]
+        every_secs: Option<u64>,
+
+        /// Number of archives to keep
+        #[arg(long, default_value_t = DEFAULT_SNAPSHOT_RETENTION)]
+        keep: usize,
+    },
+
+    /// Replace the database's contents with the newest archive in a directory
+    Ingest {
+        /// Directory holding the archives
+        dir: PathBuf,
+
+        /// Keep checking for new archives every this many seconds until
+        /// interrupted
+        #[arg(long)]
+        every_secs: Option<u64>,
+    },
+
+    /// Check every archive in a directory against its manifest
+    Verify {
+        /// Directory holding the archives
+        dir: PathBuf,
+    },
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
     let cli = Cli::parse();
 
+    // Namespace management works on the whole database rather than one graph
+    if let Commands::Namespaces { action } = &cli.command {
+        return handle_namespaces(&cli.db_path, &cli.format, action.as_ref());
+    }
+
+    // Diffs open two databases of their own
+    if let Commands::DiffDb { path_a, path_b } = &cli.command {
+        return handle_diff_db(&cli.format, &cli.namespace, path_a, path_b).await;
+    }
+
+    // Archives are read without a database
+    if let Commands::InspectArchive { input } = &cli.command {
+        return handle_inspect_archive(&cli.format, input);
+    }
+
     // Open database
     let config = Config::new(cli.db_path.to_str().unwrap());
-    let graph = AsyncMemoryGraph::open(config).await?;
+    let graph = AsyncMemoryGraph::open_namespace(config, &cli.namespace).await?;
 
     match cli.command {
-        Commands::Stats => handle_stats(&graph, &cli.format).await?,
+        Commands::Stats {
+            distributions,
+            sample_rate,
+        } => {
+            let options =
+                distributions.then(|| DistributionOptions::new().with_sample_rate(sample_rate));
+            handle_stats(&graph, &cli.format, options).await?
+        }
         Commands::Session { session_id } => {
             handle_session(&graph, &cli.format, &session_id).await?
         }
         Commands::Node { node_id } => handle_node(&graph, &cli.format, &node_id).await?,
+        Commands::List { listing } => handle_list(&graph, &cli.format, listing).await?,
+        Commands::Custom {
+            session_id,
+            type_name,
+        } => handle_custom(&graph, &cli.format, &session_id, type_name).await?,
         Commands::Export {
             session_id,
             output,
-        } => handle_export(&graph, &session_id, &output).await?,
+            keys,
+        } => handle_export(&graph, &session_id, &output, &keys).await?,
+        Commands::Import { input, keys } => handle_import(&graph, &input, &keys).await?,
+        Commands::Backup { output } => handle_backup(&graph, &output).await?,
         Commands::Flush => handle_flush(&graph).await?,
         Commands::Verify => handle_verify(&graph).await?,
+        Commands::Reindex => handle_reindex(&graph, &cli.format).await?,
+        Commands::Alias { action } => handle_alias(&graph, &cli.format, action).await?,
+        Commands::Holds { action } => handle_holds(&graph, &cli.format, action).await?,
+        Commands::Views { action } => handle_views(&graph, &cli.format, action).await?,
+        Commands::Embeddings { action } => handle_embeddings(&graph, &cli.format, action).await?,
+        Commands::Knowledge { action } => handle_knowledge(&graph, &cli.format, action).await?,
+        Commands::Snapshots { action } => {
+            handle_snapshots(Arc::new(graph), &cli.format, action).await?
+        }
+        Commands::Namespaces { .. } | Commands::DiffDb { .. } | Commands::InspectArchive { .. } => {
+            unreachable!("handled before opening the graph")
+        }
     }
 
     Ok(())
 }
 
-async fn handle_stats(graph: &AsyncMemoryGraph, format: &OutputFormat) -> Result<()> {
+/// Parse a session ID, or look it up as an alias if it is not a UUID
+async fn parse_session_id(graph: &AsyncMemoryGraph, value: &str) -> Result<SessionId> {
+    if let Ok(uuid) = Uuid::parse_str(value) {
+        return Ok(SessionId::from(uuid));
+    }
+    match graph.resolve_alias(value).await? {
+        Some(AliasTarget::Session(session_id)) => Ok(session_id),
+        Some(AliasTarget::Node(_)) => anyhow::bail!("alias {value} points at a node"),
+        None => anyhow::bail!("{value} is neither a session ID nor an alias"),
+    }
+}
+
+/// Parse a node ID, or look it up as an alias if it is not a UUID
+///
+/// An alias of a session resolves to the session's node.
+async fn parse_node_id(graph: &AsyncMemoryGraph, value: &str) -> Result<NodeId> {
+    if let Ok(uuid) = Uuid::parse_str(value) {
+        return Ok(NodeId::from(uuid));
+    }
+    match graph.resolve_alias(value).await? {
+        Some(AliasTarget::Node(node_id)) => Ok(node_id),
+        Some(AliasTarget::Session(session_id)) => Ok(graph.get_session(session_id).await?.node_id),
+        None => anyhow::bail!("{value} is neither a node ID nor an alias"),
+    }
+}
+
+async fn handle_stats(
+    graph: &AsyncMemoryGraph,
+    format: &OutputFormat,
+    distributions: Option<DistributionOptions>,
+) -> Result<()> {
     let stats = graph.stats().await?;
+    let distributions = match distributions {
+        Some(options) => {
+            let mut progress = ProgressBar::new("Scanning sessions");
+            let distributions = graph
+                .distributions(&options, |done, total| progress.update(done, total))
+                .await?;
+            progress.finish();
+            Some(distributions)
+        }
+        None => None,
+    };
 
     match format {
         OutputFormat::Json => {
@@ -117,32 +629,236 @@ async fn handle_stats(graph: &AsyncMemoryGraph, format: &OutputFormat) -> Result
                 "node_count": stats.node_count,
                 "edge_count": stats.edge_count,
                 "session_count": stats.session_count,
+                "storage_bytes": stats.storage_bytes,
+                "trees": stats
+                    .trees
+                    .iter()
+                    .map(|tree| (tree.name.clone(), tree.entries.into()))
+                    .collect::<serde_json::Map<_, _>>(),
+                "last_flush_ms": stats
+                    .last_flush_duration
+                    .map(|duration| duration.as_secs_f64() * 1000.0),
+                "pending_writes": stats.pending_writes,
+                "compression": {
+                    "nodes_compressed": stats.compression.nodes_compressed,
+                    "bytes_before": stats.compression.bytes_before,
+                    "bytes_after": stats.compression.bytes_after,
+                    "ratio": stats.compression.ratio(),
+                },
+                "cache": stats.cache.as_ref().map(|cache| serde_json::json!({
+                    "node_entries": cache.node_cache_size,
+                    "edge_entries": cache.edge_cache_size,
+                    "node_hit_rate": cache.node_hit_rate(),
+                    "edge_hit_rate": cache.edge_hit_rate(),
+                })),
+                "distributions": distributions,
             });
             println!("{}", serde_json::to_string_pretty(&stats_json)?);
         }
         OutputFormat::Text => {
             println!("{}", "Database Statistics".bold().green());
             println!("{}", "===================".green());
-            println!("{:20} {}", "Total Nodes:", stats.node_count.to_string().cyan());
-            println!("{:20} {}", "Total Edges:", stats.edge_count.to_string().cyan());
+            println!(
+                "{:20} {}",
+                "Total Nodes:",
+                stats.node_count.to_string().cyan()
+            );
+            println!(
+                "{:20} {}",
+                "Total Edges:",
+                stats.edge_count.to_string().cyan()
+            );
             println!(
                 "{:20} {}",
                 "Total Sessions:",
                 stats.session_count.to_string().cyan()
             );
+            println!(
+                "{:20} {}",
+                "Storage Bytes:",
+                stats.storage_bytes.to_string().cyan()
+            );
+            println!(
+                "{:20} {}",
+                "Pending Writes:",
+                stats.pending_writes.to_string().cyan()
+            );
+            if let Some(duration) = stats.last_flush_duration {
+                println!("{:20} {}", "Last Flush:", format!("{duration:?}").cyan());
+            }
+            if stats.compression.nodes_compressed > 0 {
+                println!(
+                    "{:20} {}",
+                    "Compression Ratio:",
+                    format!(
+                        "{:.2} ({} nodes)",
+                        stats.compression.ratio(),
+                        stats.compression.nodes_compressed
+                    )
+                    .cyan()
+                );
+            }
+            if let Some(cache) = &stats.cache {
+                println!(
+                    "{:20} {}",
+                    "Node Cache Hits:",
+                    format!("{:.1}%", cache.node_hit_rate() * 100.0).cyan()
+                );
+                println!(
+                    "{:20} {}",
+                    "Edge Cache Hits:",
+                    format!("{:.1}%", cache.edge_hit_rate() * 100.0).cyan()
+                );
+            }
+
+            println!("\n{}", "Trees".bold());
+            for tree in &stats.trees {
+                println!("  {:20} {}", tree.name, tree.entries.to_string().cyan());
+            }
+
+            if let Some(distributions) = &distributions {
+                print_distributions(distributions);
+            }
         }
     }
 
     Ok(())
 }
 
+/// Print the distributions shown by `stats --distributions`
+fn print_distributions(distributions: &GraphDistributions) {
+    println!(
+        "\n{} ({} of {} sessions scanned)",
+        "Distributions".bold(),
+        distributions.sessions_scanned,
+        distributions.sessions_total
+    );
+
+    println!("\n{}", "Nodes per Session".bold());
+    print_histogram(&distributions.nodes_per_session);
+
+    println!("\n{}", "Edges per Node".bold());
+    print_histogram(&distributions.node_degree);
+
+    let tokens = &distributions.tokens_per_response;
+    println!("\n{}", "Tokens per Response".bold());
+    if tokens.count == 0 {
+        println!("  {}", "No responses".dimmed());
+    } else {
+        for (label, value) in [
+            ("min", tokens.min),
+            ("p50", tokens.p50),
+            ("p90", tokens.p90),
+            ("p99", tokens.p99),
+            ("max", tokens.max),
+        ] {
+            println!("  {:20} {}", label, value.to_string().cyan());
+        }
+        println!("  {:20} {}", "mean", format!("{:.1}", tokens.mean).cyan());
+    }
+
+    println!("\n{}", "Sessions per Day".bold());
+    let busiest = distributions
+        .sessions_per_day
+        .values()
+        .copied()
+        .max()
+        .unwrap_or(0);
+    for (day, count) in &distributions.sessions_per_day {
+        println!(
+            "  {:20} {:>8} {}",
+            day.to_string(),
+            count.to_string().cyan(),
+            bar(*count as u64, busiest as u64)
+        );
+    }
+}
+
+/// Print one line per histogram bucket, with a bar scaled to the fullest
+fn print_histogram(histogram: &Histogram) {
+    if histogram.total() == 0 {
+        println!("  {}", "No data".dimmed());
+        return;
+    }
+    let fullest = histogram
+        .buckets
+        .iter()
+        .map(|bucket| bucket.count)
+        .max()
+        .unwrap_or(0);
+    // Leading empty buckets only push the interesting ones down
+    for bucket in histogram
+        .buckets
+        .iter()
+        .skip_while(|bucket| bucket.count == 0)
+    {
+        let range = if bucket.min == bucket.max {
+            bucket.min.to_string()
+        } else {
+            format!("{}-{}", bucket.min, bucket.max)
+        };
+        println!(
+            "  {:20} {:>8} {}",
+            range,
+            bucket.count.to_string().cyan(),
+            bar(bucket.count, fullest)
+        );
+    }
+}
+
+/// Bar of up to 40 characters showing `value` relative to `max`
+fn bar(value: u64, max: u64) -> String {
+    const WIDTH: u64 = 40;
+    let len = (value * WIDTH).checked_div(max).unwrap_or(0);
+    "█".repeat(len as usize).green().to_string()
+}
+
+/// Progress bar drawn on stderr while a long scan runs
+///
+/// Draws nothing when stderr is not a terminal, so piped JSON output stays
+/// clean.
+struct ProgressBar {
+    label: &'static str,
+    visible: bool,
+}
+
+impl ProgressBar {
+    fn new(label: &'static str) -> Self {
+        Self {
+            label,
+            visible: std::io::stderr().is_terminal(),
+        }
+    }
+
+    fn update(&mut self, done: usize, total: usize) {
+        if !self.visible || total == 0 {
+            return;
+        }
+        const WIDTH: usize = 30;
+        let filled = done * WIDTH / total;
+        eprint!(
+            "\r{} [{}{}] {}/{}",
+            self.label,
+            "#".repeat(filled),
+            " ".repeat(WIDTH - filled),
+            done,
+            total
+        );
+    }
+
+    fn finish(self) {
+        if self.visible {
+            eprint!("\r\x1b[2K");
+        }
+    }
+}
+
 async fn handle_session(
     graph: &AsyncMemoryGraph,
     format: &OutputFormat,
     session_id_str: &str,
 ) -> Result<()> {
-    let uuid = Uuid::parse_str(session_id_str)?;
-    let session_id = SessionId::from(uuid);
+    let session_id = parse_session_id(graph, session_id_str).await?;
     let session = graph.get_session(session_id).await?;
 
     // Get nodes in the session
@@ -157,6 +873,10 @@ async fn handle_session(
         OutputFormat::Text => {
             println!("{}", format!("Session: {}", session.id).bold().green());
             println!("{}", "====================".green());
+            if let Some(title) = &session.title {
+                println!("{:15} {}", "Title:", title);
+            }
+            println!("{:15} {:?}", "Status:", session.status);
             println!(
                 "{:15} {}",
                 "Created:",
@@ -187,8 +907,7 @@ async fn handle_node(
     format: &OutputFormat,
     node_id_str: &str,
 ) -> Result<()> {
-    let uuid = Uuid::parse_str(node_id_str)?;
-    let node_id = NodeId::from(uuid);
+    let node_id = parse_node_id(graph, node_id_str).await?;
     let node_opt = graph.get_node(&node_id).await?;
 
     match node_opt {
@@ -200,6 +919,9 @@ async fn handle_node(
                 println!("{}", format!("Node: {}", node_id).bold().green());
                 println!("{}", "====================".green());
                 println!("{:15} {:?}", "Type:", node.node_type());
+                if let Some(custom_type) = node.custom_type() {
+                    println!("{:15} {}", "Custom Type:", custom_type.cyan());
+                }
                 println!("\n{}", "Details:".bold());
                 println!("{}", serde_json::to_string_pretty(&node)?);
             }
@@ -213,28 +935,313 @@ async fn handle_node(
     Ok(())
 }
 
+async fn handle_list(
+    graph: &AsyncMemoryGraph,
+    format: &OutputFormat,
+    listing: Listing,
+) -> Result<()> {
+    match listing {
+        Listing::Sessions { page } => {
+            let sessions = graph
+                .list_sessions_page(page.cursor()?.as_ref(), page.limit)
+                .await?;
+            print_page(format, "Sessions", &sessions, |session| {
+                let hold = if LegalHold::from_session(session).is_some() {
+                    format!(" {}", "[hold]".red())
+                } else {
+                    String::new()
+                };
+                format!(
+                    "{} {}{} {}",
+                    session.id.to_string().cyan(),
+                    session.created_at.format("%Y-%m-%d %H:%M:%S"),
+                    hold,
+                    session.title.as_deref().unwrap_or("")
+                )
+            })?;
+        }
+        Listing::Nodes { session_id, page } => {
+            let session_id = parse_session_id(graph, &session_id).await?;
+            let nodes = graph
+                .get_session_nodes_page(&session_id, page.cursor()?.as_ref(), page.limit)
+                .await?;
+            print_page(
+                format,
+                &format!("Nodes in session {}", session_id),
+                &nodes,
+                |node| {
+                    format!(
+                        "{} {:16} {}",
+                        node.id().to_string().cyan(),
+                        format!("{:?}", node.node_type()),
+                        node.created_at().format("%Y-%m-%d %H:%M:%S")
+                    )
+                },
+            )?;
+        }
+        Listing::Edges {
+            node_id,
+            incoming,
+            page,
+        } => {
+            let node_id = parse_node_id(graph, &node_id).await?;
+            let direction = if incoming {
+                EdgeDirection::Incoming
+            } else {
+                EdgeDirection::Outgoing
+            };
+            let edges = graph
+                .get_edges_page(&node_id, direction, page.cursor()?.as_ref(), page.limit)
+                .await?;
+            print_page(
+                format,
+                &format!("Edges of node {}", node_id),
+                &edges,
+                |edge| {
+                    format!(
+                        "{} {} -> {} {:?}",
+                        edge.id.to_string().cyan(),
+                        edge.from,
+                        edge.to,
+                        edge.edge_type
+                    )
+                },
+            )?;
+        }
+        Listing::Templates { page } => {
+            let templates = graph
+                .list_templates_page(page.cursor()?.as_ref(), page.limit)
+                .await?;
+            print_page(format, "Templates", &templates, |template| {
+                format!(
+                    "{} {} v{}",
+                    template.id.to_string().cyan(),
+                    template.name,
+                    template.version
+                )
+            })?;
+        }
+        Listing::Agents { page } => {
+            let agents = graph
+                .list_agents_page(page.cursor()?.as_ref(), page.limit)
+                .await?;
+            print_page(format, "Agents", &agents, |agent| {
+                format!(
+                    "{} {} ({})",
+                    agent.id.to_string().cyan(),
+                    agent.name,
+                    agent.role
+                )
+            })?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Print one page of a listing, followed by the cursor of the next page
+fn print_page<T: serde::Serialize>(
+    format: &OutputFormat,
+    title: &str,
+    page: &Page<T>,
+    line: impl Fn(&T) -> String,
+) -> Result<()> {
+    match format {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(page)?),
+        OutputFormat::Text => {
+            println!("{}", title.bold().green());
+            println!("{}", "====================".green());
+            for item in &page.items {
+                println!("{}", line(item));
+            }
+            if let Some(cursor) = &page.next_cursor {
+                println!("\n{:15} {}", "Next cursor:", cursor);
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn handle_custom(
+    graph: &AsyncMemoryGraph,
+    format: &OutputFormat,
+    session_id_str: &str,
+    type_name: Option<String>,
+) -> Result<()> {
+    let session_id = parse_session_id(graph, session_id_str).await?;
+
+    let mut query = graph
+        .query()
+        .session(session_id)
+        .node_type(NodeType::Custom);
+    if let Some(type_name) = type_name {
+        query = query.custom_type(type_name);
+    }
+    let nodes = query.execute().await?;
+
+    match format {
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(&nodes)?);
+        }
+        OutputFormat::Text => {
+            println!(
+                "{}",
+                format!("Custom nodes in session {}", session_id)
+                    .bold()
+                    .green()
+            );
+            println!("{}", "====================".green());
+            for node in &nodes {
+                if let Node::Custom(custom) = node {
+                    println!(
+                        "{} {:20} {}",
+                        custom.id.to_string().cyan(),
+                        custom.type_name,
+                        custom.created_at.format("%Y-%m-%d %H:%M:%S")
+                    );
+                }
+            }
+            println!("\n{:15} {}", "Total:", nodes.len());
+        }
+    }
+
+    Ok(())
+}
+
 async fn handle_export(
     graph: &AsyncMemoryGraph,
     session_id_str: &str,
     output: &PathBuf,
+    keys: &ExportKeys,
 ) -> Result<()> {
-    let uuid = Uuid::parse_str(session_id_str)?;
-    let session_id = SessionId::from(uuid);
-    let session = graph.get_session(session_id).await?;
+    let session_id = parse_session_id(graph, session_id_str).await?;
 
-    // Export session as JSON
-    let json = serde_json::to_string_pretty(&session)?;
+    if output
+        .extension()
+        .is_some_and(|extension| extension == ARCHIVE_EXTENSION)
+    {
+        if keys.encryption_key_file.is_some() || keys.signing_key_file.is_some() {
+            anyhow::bail!("encrypted or signed exports are written as JSON, not .lmg archives");
+        }
+        let archive = ArchiveReader::new(graph.export_session_archive(session_id).await?)?;
+        std::fs::write(output, archive.as_bytes())?;
+        println!(
+            "{} Session archived to: {} ({} nodes, {} edges)",
+            "✓".green().bold(),
+            output.display().to_string().cyan(),
+            archive.manifest().node_count,
+            archive.manifest().edge_count
+        );
+        return Ok(());
+    }
+
+    // Export the session's records with their manifest as JSON
+    let export = graph.export_session(session_id, &keys.options()?).await?;
+    let json = serde_json::to_string_pretty(&export)?;
     std::fs::write(output, json)?;
 
     println!(
-        "{} Session exported to: {}",
+        "{} Session exported to: {} ({} nodes, {} edges{}{})",
+        "✓".green().bold(),
+        output.display().to_string().cyan(),
+        export.manifest.node_count,
+        export.manifest.edge_count,
+        if export.manifest.encryption.is_some() {
+            ", encrypted"
+        } else {
+            ""
+        },
+        if export.manifest.signature.is_some() {
+            ", signed"
+        } else {
+            ""
+        }
+    );
+
+    Ok(())
+}
+
+async fn handle_import(graph: &AsyncMemoryGraph, input: &Path, keys: &ExportKeys) -> Result<()> {
+    let data = std::fs::read(input)?;
+    if ArchiveReader::is_archive(&data) {
+        let manifest = graph.import_archive(&ArchiveReader::new(data)?).await?;
+        println!(
+            "{} Imported {:?} archive ({} nodes, {} edges)",
+            "✓".green().bold(),
+            manifest.kind,
+            manifest.node_count,
+            manifest.edge_count
+        );
+        return Ok(());
+    }
+
+    let export: SessionExport = serde_json::from_slice(&data)?;
+    let session_id = graph.import_session(&export, &keys.options()?).await?;
+
+    println!(
+        "{} Imported session {} ({} nodes, {} edges)",
         "✓".green().bold(),
-        output.display().to_string().cyan()
+        session_id.to_string().cyan(),
+        export.manifest.node_count,
+        export.manifest.edge_count
     );
 
     Ok(())
 }
 
+async fn handle_backup(graph: &AsyncMemoryGraph, output: &Path) -> Result<()> {
+    let manifest = graph.backup(output).await?;
+    println!(
+        "{} Database backed up to: {} ({} nodes, {} edges)",
+        "✓".green().bold(),
+        output.display().to_string().cyan(),
+        manifest.node_count,
+        manifest.edge_count
+    );
+    Ok(())
+}
+
+fn handle_inspect_archive(format: &OutputFormat, input: &Path) -> Result<()> {
+    let archive = ArchiveReader::open(input)?;
+    let verified = archive.verify();
+
+    match format {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(archive.manifest())?),
+        OutputFormat::Text => print_archive_manifest(archive.manifest(), archive.size()),
+    }
+
+    verified?;
+    if matches!(format, OutputFormat::Text) {
+        println!("{} All blocks verified", "✓".green().bold());
+    }
+    Ok(())
+}
+
+fn print_archive_manifest(manifest: &ArchiveManifest, size: u64) {
+    println!("{}", "Archive".bold().green());
+    println!("{}", "=======".green());
+    println!("{:12} {:?}", "Kind:", manifest.kind);
+    println!(
+        "{:12} {}",
+        "Created:",
+        manifest.created_at.format("%Y-%m-%d %H:%M:%S")
+    );
+    if let Some(session_id) = manifest.session_id {
+        println!("{:12} {}", "Session:", session_id.to_string().cyan());
+    }
+    if let Some(cursor) = manifest.cursor {
+        println!("{:12} {}", "Cursor:", cursor);
+    }
+    println!("{:12} {}", "Nodes:", manifest.node_count);
+    println!("{:12} {}", "Edges:", manifest.edge_count);
+    println!("{:12} {}", "Blocks:", manifest.blocks.len());
+    println!("{:12} {}", "Compression:", manifest.compression);
+    println!("{:12} {} bytes", "Size:", size);
+    for (key, value) in &manifest.metadata {
+        println!("{:12} {}", format!("{key}:"), value);
+    }
+}
+
 async fn handle_flush(graph: &AsyncMemoryGraph) -> Result<()> {
     println!("{}", "Flushing database to disk...".yellow());
     graph.flush().await?;
@@ -259,3 +1266,616 @@ async fn handle_verify(graph: &AsyncMemoryGraph) -> Result<()> {
 
     Ok(())
 }
+
+async fn handle_reindex(graph: &AsyncMemoryGraph, format: &OutputFormat) -> Result<()> {
+    if matches!(format, OutputFormat::Text) {
+        println!("{}", "Rebuilding indexes...".yellow());
+    }
+    let report = graph.rebuild_indexes().await?;
+
+    match format {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+        OutputFormat::Text => {
+            println!(
+                "{} Replayed {} nodes and {} edges",
+                "✓".green().bold(),
+                report.nodes_scanned,
+                report.edges_scanned
+            );
+            println!(
+                "{} Wrote {} session, {} content and {} edge index entries",
+                "✓".green().bold(),
+                report.session_entries,
+                report.content_entries,
+                report.edge_entries
+            );
+            println!(
+                "{} Recounted {} view members and {} usage buckets",
+                "✓".green().bold(),
+                report.view_members,
+                report.usage_buckets
+            );
+        }
+    }
+
+    Ok(())
+}
+
+async fn handle_views(
+    graph: &AsyncMemoryGraph,
+    format: &OutputFormat,
+    action: Option<ViewAction>,
+) -> Result<()> {
+    match action.unwrap_or(ViewAction::List) {
+        ViewAction::List => {
+            let views = graph.views().await?;
+            match format {
+                OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&views)?),
+                OutputFormat::Text => {
+                    println!("{}", "Views".bold().green());
+                    println!("{}", "=====".green());
+                    for view in &views {
+                        let node_type = view
+                            .node_type
+                            .as_ref()
+                            .map_or_else(|| "all nodes".to_string(), |t| format!("{t:?} nodes"));
+                        println!(
+                            "{:20} {} grouped by {:?}",
+                            view.name.cyan(),
+                            node_type,
+                            view.group_by
+                        );
+                    }
+                }
+            }
+        }
+        ViewAction::Show { name } => {
+            let rows = graph.view_rows(&name).await?;
+            match format {
+                OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&rows)?),
+                OutputFormat::Text => {
+                    println!("{}", format!("View: {}", name).bold().green());
+                    println!("{}", "====================".green());
+                    for row in &rows {
+                        println!(
+                            "{:40} {} nodes, {} tokens ({} prompt, {} completion)",
+                            row.group.join(" / ").cyan(),
+                            row.totals.count,
+                            row.totals.total_tokens,
+                            row.totals.prompt_tokens,
+                            row.totals.completion_tokens
+                        );
+                    }
+                }
+            }
+        }
+        ViewAction::Rebuild => {
+            println!("{}", "Rebuilding views...".yellow());
+            let counted = graph.rebuild_views().await?;
+            println!(
+                "{} Rebuilt views over {} nodes",
+                "✓".green().bold(),
+                counted
+            );
+        }
+    }
+
+    Ok(())
+}
+
+async fn handle_embeddings(
+    graph: &AsyncMemoryGraph,
+    format: &OutputFormat,
+    action: EmbeddingAction,
+) -> Result<()> {
+    match action {
+        EmbeddingAction::Backfill {
+            url,
+            model,
+            api_key_env,
+        } => {
+            let mut embedder = HttpEmbedder::new(url, model)?;
+            if let Some(var) = api_key_env {
+                embedder = embedder.with_api_key(std::env::var(&var)?);
+            }
+            graph.set_embedder(Some(Arc::new(embedder)));
+
+            println!("{}", "Backfilling embeddings...".yellow());
+            let queued = graph.backfill_embeddings().await?;
+            graph.wait_for_embeddings().await;
+            let stats = graph.embedding_stats().unwrap_or_default();
+            println!(
+                "{} Embedded {} of {} nodes",
+                "✓".green().bold(),
+                stats.embedded,
+                queued
+            );
+            if stats.failed > 0 {
+                println!("{} {} nodes failed", "✗".red().bold(), stats.failed);
+            }
+        }
+        EmbeddingAction::Show { node_id } => {
+            let node_id = parse_node_id(graph, &node_id).await?;
+            let embedding = graph
+                .get_embedding(&node_id)
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("node {node_id} has no embedding"))?;
+            match format {
+                OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&embedding)?),
+                OutputFormat::Text => {
+                    println!("{}", "Embedding".bold().green());
+                    println!("{}", "=========".green());
+                    println!("{:12} {}", "Node:", embedding.node_id.to_string().cyan());
+                    println!("{:12} {}", "Model:", embedding.model);
+                    println!("{:12} {}", "Dimensions:", embedding.vector.len());
+                    println!("{:12} {}", "Created:", embedding.created_at);
+                }
+            }
+        }
+        EmbeddingAction::Duplicates { threshold } => {
+            let duplicates = graph.find_near_duplicates(threshold).await?;
+            match format {
+                OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&duplicates)?),
+                OutputFormat::Text => {
+                    println!("{}", "Near-duplicates".bold().green());
+                    println!("{}", "===============".green());
+                    for duplicate in &duplicates {
+                        println!(
+                            "{} repeats {} ({:?}, similarity {:.3})",
+                            duplicate.node_id.to_string().cyan(),
+                            duplicate.original_id,
+                            duplicate.node_type,
+                            duplicate.similarity
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+async fn handle_knowledge(
+    graph: &AsyncMemoryGraph,
+    format: &OutputFormat,
+    action: KnowledgeAction,
+) -> Result<()> {
+    match action {
+        KnowledgeAction::Extract {
+            session_id,
+            patterns,
+        } => {
+            let session_id = parse_session_id(graph, &session_id).await?;
+            let mut extractor = PatternExtractor::new();
+            for pattern in &patterns {
+                let (kind, regex) = pattern
+                    .split_once('=')
+                    .ok_or_else(|| anyhow::anyhow!("expected KIND=PATTERN, got {pattern}"))?;
+                extractor = extractor.with_pattern(kind, regex)?;
+            }
+
+            let report = graph
+                .extract_session_knowledge(&extractor, session_id)
+                .await?;
+            match format {
+                OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+                OutputFormat::Text => println!(
+                    "{} Read {} responses: {} new entities, {} new facts, {} mentions",
+                    "✓".green().bold(),
+                    report.responses,
+                    report.entities_created,
+                    report.facts_created,
+                    report.mentions
+                ),
+            }
+        }
+        KnowledgeAction::About { name } => {
+            let known = graph.knowledge_about(&name).await?;
+            if known.is_empty() {
+                anyhow::bail!("no entity called {name}");
+            }
+            match format {
+                OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&known)?),
+                OutputFormat::Text => {
+                    for entity in &known {
+                        println!(
+                            "{} ({})",
+                            entity.entity.name.bold().green(),
+                            entity.entity.kind
+                        );
+                        println!(
+                            "{:12} {}",
+                            "Node:",
+                            entity.entity.node_id.to_string().cyan()
+                        );
+                        println!("{:12} {}", "Responses:", entity.responses.len());
+                        for fact in &entity.facts {
+                            println!("  - {}", fact.statement);
+                        }
+                    }
+                }
+            }
+        }
+        KnowledgeAction::Entities => {
+            let entities = graph.list_entities().await?;
+            match format {
+                OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&entities)?),
+                OutputFormat::Text => {
+                    println!("{}", "Entities".bold().green());
+                    println!("{}", "========".green());
+                    for entity in &entities {
+                        println!(
+                            "{:16} {} {}",
+                            entity.kind,
+                            entity.name,
+                            entity.node_id.to_string().cyan()
+                        );
+                    }
+                }
+            }
+        }
+        KnowledgeAction::Assert {
+            subject,
+            predicate,
+            value,
+        } => {
+            let fact = graph.assert_fact(&subject, &predicate, &value).await?;
+            match format {
+                OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&fact)?),
+                OutputFormat::Text => println!(
+                    "{} {} {}",
+                    "✓".green().bold(),
+                    fact.statement,
+                    fact.node_id.to_string().cyan()
+                ),
+            }
+        }
+        KnowledgeAction::History { subject, predicate } => {
+            let history = graph.fact_history(&subject, &predicate).await?;
+            match format {
+                OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&history)?),
+                OutputFormat::Text => {
+                    for fact in &history {
+                        let until = fact
+                            .valid_until
+                            .map_or_else(|| "now".to_string(), |until| until.to_rfc3339());
+                        println!(
+                            "{} {} to {}: {}",
+                            fact.node_id.to_string().cyan(),
+                            fact.valid_from.to_rfc3339(),
+                            until,
+                            fact.value.as_deref().unwrap_or(&fact.statement)
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+async fn handle_alias(
+    graph: &AsyncMemoryGraph,
+    format: &OutputFormat,
+    action: Option<AliasAction>,
+) -> Result<()> {
+    let action = action.unwrap_or(AliasAction::List {
+        prefix: String::new(),
+    });
+    match action {
+        AliasAction::List { prefix } => {
+            let aliases = graph.list_aliases(&prefix).await?;
+            match format {
+                OutputFormat::Json => {
+                    let json: Vec<_> = aliases
+                        .iter()
+                        .map(|(alias, target)| serde_json::json!({ "alias": alias, "target": target }))
+                        .collect();
+                    println!("{}", serde_json::to_string_pretty(&json)?);
+                }
+                OutputFormat::Text => {
+                    println!("{}", "Aliases".bold().green());
+                    println!("{}", "=======".green());
+                    for (alias, target) in &aliases {
+                        println!("{:30} {}", alias.cyan(), target);
+                    }
+                }
+            }
+        }
+        AliasAction::Set {
+            alias,
+            target,
+            node,
+        } => {
+            let uuid = Uuid::parse_str(&target)?;
+            let target = if node {
+                AliasTarget::Node(NodeId::from(uuid))
+            } else {
+                AliasTarget::Session(SessionId::from(uuid))
+            };
+            let previous = graph.set_alias(&alias, target).await?;
+            println!("{} {} -> {}", "✓".green().bold(), alias.cyan(), target);
+            if let Some(previous) = previous {
+                println!("  (previously {})", previous);
+            }
+        }
+        AliasAction::Resolve { alias } => match graph.resolve_alias(&alias).await? {
+            Some(target) => match format {
+                OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&target)?),
+                OutputFormat::Text => println!("{} -> {}", alias.cyan(), target),
+            },
+            None => anyhow::bail!("alias {alias} is not set"),
+        },
+        AliasAction::Remove { alias } => match graph.remove_alias(&alias).await? {
+            Some(target) => {
+                println!(
+                    "{} Removed {} (was {})",
+                    "✓".green().bold(),
+                    alias.cyan(),
+                    target
+                )
+            }
+            None => println!("{} Alias {} is not set", "!".yellow().bold(), alias),
+        },
+    }
+
+    Ok(())
+}
+
+async fn handle_holds(
+    graph: &AsyncMemoryGraph,
+    format: &OutputFormat,
+    action: Option<HoldAction>,
+) -> Result<()> {
+    match action.unwrap_or(HoldAction::List) {
+        HoldAction::List => {
+            let holds = graph.legal_holds().await?;
+            match format {
+                OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&holds)?),
+                OutputFormat::Text => {
+                    println!("{}", "Legal Holds".bold().green());
+                    println!("{}", "===========".green());
+                    for hold in &holds {
+                        println!(
+                            "{} {} {}",
+                            hold.session_id.to_string().cyan(),
+                            hold.placed_at.format("%Y-%m-%d %H:%M:%S"),
+                            hold.reason
+                        );
+                    }
+                }
+            }
+        }
+        HoldAction::Place { session_id, reason } => {
+            let session_id = parse_session_id(graph, &session_id).await?;
+            let hold = graph.place_hold(session_id, reason).await?;
+            match format {
+                OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&hold)?),
+                OutputFormat::Text => println!(
+                    "{} Placed legal hold on {}: {}",
+                    "✓".green().bold(),
+                    session_id.to_string().cyan(),
+                    hold.reason
+                ),
+            }
+        }
+        HoldAction::Release { session_id } => {
+            let session_id = parse_session_id(graph, &session_id).await?;
+            match graph.release_hold(session_id).await? {
+                Some(hold) => println!(
+                    "{} Released legal hold on {} (was: {})",
+                    "✓".green().bold(),
+                    session_id.to_string().cyan(),
+                    hold.reason
+                ),
+                None => println!(
+                    "{} Session {} is not under legal hold",
+                    "!".yellow().bold(),
+                    session_id
+                ),
+            }
+        }
+    }
+
+    Ok(())
+}
+
+async fn handle_snapshots(
+    graph: Arc<AsyncMemoryGraph>,
+    format: &OutputFormat,
+    action: SnapshotAction,
+) -> Result<()> {
+    match action {
+        SnapshotAction::Ship {
+            dir,
+            every_secs,
+            keep,
+        } => {
+            let store = Arc::new(DirectoryStore::new(dir)?);
+            let shipper = Arc::new(SnapshotShipper::new(graph, store).with_retention(keep));
+            if let Some(secs) = every_secs {
+                println!("{}", "Shipping snapshots, Ctrl-C to stop...".yellow());
+                let handle = shipper.start(Duration::from_secs(secs));
+                tokio::signal::ctrl_c().await?;
+                handle.stop().await;
+            } else {
+                let manifest = shipper.ship().await?;
+                print_manifest(format, &manifest)?;
+            }
+        }
+        SnapshotAction::Ingest { dir, every_secs } => {
+            let store = Arc::new(DirectoryStore::new(dir)?);
+            let replica = Arc::new(SnapshotReplica::new(graph, store));
+            if let Some(secs) = every_secs {
+                println!("{}", "Ingesting snapshots, Ctrl-C to stop...".yellow());
+                let handle = Arc::clone(&replica).start(Duration::from_secs(secs));
+                tokio::signal::ctrl_c().await?;
+                handle.stop().await;
+            } else if let Some(manifest) = replica.ingest().await? {
+                print_manifest(format, &manifest)?;
+            } else {
+                println!("{} No new snapshot", "!".yellow().bold());
+            }
+        }
+        SnapshotAction::Verify { dir } => {
+            let store = DirectoryStore::new(dir)?;
+            let mut failed = 0;
+            for manifest in list_manifests(&store).await? {
+                match verify_snapshot(&store, &manifest).await {
+                    Ok(()) => println!("{} {}", "✓".green().bold(), manifest.name),
+                    Err(e) => {
+                        failed += 1;
+                        println!("{} {}: {}", "✗".red().bold(), manifest.name, e);
+                    }
+                }
+            }
+            if failed > 0 {
+                anyhow::bail!("{failed} snapshots failed verification");
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn print_manifest(format: &OutputFormat, manifest: &SnapshotManifest) -> Result<()> {
+    match format {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(manifest)?),
+        OutputFormat::Text => {
+            println!("{} {}", "✓".green().bold(), manifest.name.cyan());
+            println!("{:12} {}", "Taken:", manifest.taken_at);
+            println!("{:12} {}", "Nodes:", manifest.node_count);
+            println!("{:12} {}", "Edges:", manifest.edge_count);
+            println!("{:12} {} bytes", "Size:", manifest.size);
+        }
+    }
+    Ok(())
+}
+
+async fn handle_diff_db(
+    format: &OutputFormat,
+    namespace: &str,
+    path_a: &Path,
+    path_b: &Path,
+) -> Result<()> {
+    let open = |path: &Path| {
+        let config = Config::new(path).with_read_only(true);
+        AsyncMemoryGraph::open_namespace(config, namespace)
+    };
+    let a = open(path_a).await?;
+    let b = open(path_b).await?;
+    let diff = a.diff(&b).await?;
+
+    match format {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&diff)?),
+        OutputFormat::Text => {
+            println!("{}", "Database Diff".bold().green());
+            println!("{}", "=============".green());
+            for (label, records) in [
+                ("Nodes", diff_lines(&diff.nodes)),
+                ("Edges", diff_lines(&diff.edges)),
+            ] {
+                println!("{}", label.bold());
+                for line in records {
+                    println!("  {line}");
+                }
+            }
+            if diff.is_empty() {
+                println!("{} Databases hold the same records", "✓".green().bold());
+            }
+        }
+    }
+
+    if diff.is_empty() {
+        Ok(())
+    } else {
+        anyhow::bail!("databases differ")
+    }
+}
+
+/// Summary and per-record lines of one part of a diff
+fn diff_lines<Id: std::fmt::Display>(records: &RecordDiff<Id>) -> Vec<String> {
+    let mut lines = vec![format!(
+        "{} added, {} removed, {} changed, {} unchanged",
+        records.added.len(),
+        records.removed.len(),
+        records.changed.len(),
+        records.unchanged
+    )];
+    lines.extend(
+        records
+            .added
+            .iter()
+            .map(|id| format!("{} {id}", "+".green())),
+    );
+    lines.extend(
+        records
+            .removed
+            .iter()
+            .map(|id| format!("{} {id}", "-".red())),
+    );
+    lines.extend(
+        records
+            .changed
+            .iter()
+            .map(|id| format!("{} {id}", "~".yellow())),
+    );
+    lines
+}
+
+fn handle_namespaces(
+    db_path: &Path,
+    format: &OutputFormat,
+    action: Option<&NamespaceAction>,
+) -> Result<()> {
+    let backend = SledBackend::open(db_path)?;
+
+    if let Some(NamespaceAction::Drop { name }) = action {
+        if backend.drop_namespace(name)? {
+            println!("{} Dropped namespace {}", "✓".green().bold(), name.cyan());
+        } else {
+            println!("{} Namespace {} does not exist", "!".yellow().bold(), name);
+        }
+        return Ok(());
+    }
+
+    let mut namespaces = Vec::new();
+    for name in backend.list_namespaces() {
+        let stats = backend.in_namespace(&name)?.stats()?;
+        namespaces.push((name, stats));
+    }
+
+    match format {
+        OutputFormat::Json => {
+            let json: Vec<_> = namespaces
+                .iter()
+                .map(|(name, stats)| {
+                    serde_json::json!({
+                        "namespace": name,
+                        "node_count": stats.node_count,
+                        "edge_count": stats.edge_count,
+                        "session_count": stats.session_count,
+                    })
+                })
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&json)?);
+        }
+        OutputFormat::Text => {
+            println!("{}", "Namespaces".bold().green());
+            println!("{}", "==========".green());
+            for (name, stats) in &namespaces {
+                println!(
+                    "{:20} {} nodes, {} edges, {} sessions",
+                    name.cyan(),
+                    stats.node_count,
+                    stats.edge_count,
+                    stats.session_count
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
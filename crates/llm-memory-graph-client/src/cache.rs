@@ -0,0 +1,348 @@
+//! Local cache of sessions and nodes, invalidated by the event stream
+//!
+//! A client built with [`ClientBuilder::cache`](crate::ClientBuilder::cache)
+//! keeps the sessions and nodes it reads, so read-heavy UIs that fetch the
+//! same items over and over make one round-trip per item instead of one per
+//! read. A background task subscribes to the server's event stream and evicts
+//! every session or node an event names, so entries never outlive a change
+//! made by any client.
+//!
+//! The cache only serves reads while that subscription is open. When the
+//! stream fails, events may have been missed, so the cache is emptied and
+//! reads go to the server until the task has subscribed again. A read that
+//! races with an invalidation is not cached, which keeps a stale response from
+//! being stored after the event that made it stale.
+
+use crate::client::{proto, MemoryGraphClient};
+use crate::events::{EventFilter, GraphEvent};
+use futures::StreamExt;
+use llm_memory_graph_types::Node;
+use moka::future::Cache;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock, Weak};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+
+/// Size and lifetime of cached entries
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheConfig {
+    /// Most sessions and most nodes kept, each
+    pub max_entries: u64,
+    /// Drop entries this long after they were cached, even without an event
+    /// (`None` = keep until evicted)
+    pub ttl: Option<Duration>,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            max_entries: 10_000,
+            ttl: Some(Duration::from_mins(5)),
+        }
+    }
+}
+
+impl CacheConfig {
+    /// Set the most sessions and most nodes kept
+    #[must_use]
+    pub fn max_entries(mut self, max_entries: u64) -> Self {
+        self.max_entries = max_entries;
+        self
+    }
+
+    /// Set how long entries are kept (`None` = until evicted)
+    #[must_use]
+    pub fn ttl(mut self, ttl: Option<Duration>) -> Self {
+        self.ttl = ttl;
+        self
+    }
+}
+
+/// Counters of a client's cache
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// Reads answered from the cache
+    pub hits: u64,
+    /// Reads sent to the server
+    pub misses: u64,
+    /// Entries evicted because of an event or a local write
+    pub invalidations: u64,
+    /// Whether the event subscription is open, so that the cache serves reads
+    pub live: bool,
+}
+
+/// Cached sessions and nodes shared by the clones of one client
+pub(crate) struct ClientCache {
+    sessions: Cache<String, proto::Session>,
+    nodes: Cache<String, Node>,
+    live: AtomicBool,
+    /// Bumped on every invalidation, so reads that raced with one are not
+    /// cached
+    generation: AtomicU64,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    invalidations: AtomicU64,
+    task: OnceLock<JoinHandle<()>>,
+}
+
+impl ClientCache {
+    pub(crate) fn new(config: &CacheConfig) -> Self {
+        Self {
+            sessions: build_cache(config),
+            nodes: build_cache(config),
+            live: AtomicBool::new(false),
+            generation: AtomicU64::new(0),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            invalidations: AtomicU64::new(0),
+            task: OnceLock::new(),
+        }
+    }
+
+    /// Start the task that keeps the cache subscribed to events
+    ///
+    /// `client` must not itself hold the cache; the task stops once the cache
+    /// is dropped.
+    pub(crate) fn spawn_invalidation(self: &Arc<Self>, client: MemoryGraphClient) {
+        let task = tokio::spawn(invalidate_from_events(client, Arc::downgrade(self)));
+        if self.task.set(task).is_err() {
+            warn!("Cache invalidation task started twice");
+        }
+    }
+
+    pub(crate) fn generation(&self) -> u64 {
+        self.generation.load(Ordering::Acquire)
+    }
+
+    pub(crate) async fn session(&self, session_id: &str) -> Option<proto::Session> {
+        let session = if self.is_live() {
+            self.sessions.get(session_id).await
+        } else {
+            None
+        };
+        self.record(session)
+    }
+
+    pub(crate) async fn node(&self, node_id: &str) -> Option<Node> {
+        let node = if self.is_live() {
+            self.nodes.get(node_id).await
+        } else {
+            None
+        };
+        self.record(node)
+    }
+
+    /// Cache a session read at `generation`, unless it may be stale
+    pub(crate) async fn insert_session(&self, generation: u64, session: proto::Session) {
+        if self.is_current(generation) {
+            self.sessions.insert(session.id.clone(), session).await;
+        }
+    }
+
+    /// Cache a node read at `generation`, unless it may be stale
+    pub(crate) async fn insert_node(&self, generation: u64, node_id: String, node: Node) {
+        if self.is_current(generation) {
+            self.nodes.insert(node_id, node).await;
+        }
+    }
+
+    pub(crate) async fn invalidate_session(&self, session_id: &str) {
+        self.bump();
+        self.sessions.invalidate(session_id).await;
+    }
+
+    pub(crate) async fn invalidate_node(&self, node_id: &str) {
+        self.bump();
+        self.nodes.invalidate(node_id).await;
+    }
+
+    pub(crate) fn clear(&self) {
+        self.bump();
+        self.sessions.invalidate_all();
+        self.nodes.invalidate_all();
+    }
+
+    /// Evict what `event` names, or everything if it names nothing
+    async fn apply(&self, event: &GraphEvent) {
+        let mut named = false;
+        if let Some(node_id) = event.payload["node_id"].as_str() {
+            self.invalidate_node(node_id).await;
+            named = true;
+        }
+        if let Some(session_id) = event.payload["session_id"].as_str() {
+            self.invalidate_session(session_id).await;
+            named = true;
+        }
+        if !named {
+            debug!(
+                "Event {} ({:?}) names no session or node, clearing the cache",
+                event.id, event.event_type
+            );
+            self.clear();
+        }
+    }
+
+    fn set_live(&self, live: bool) {
+        if !live {
+            self.clear();
+        }
+        self.live.store(live, Ordering::Release);
+    }
+
+    pub(crate) fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            invalidations: self.invalidations.load(Ordering::Relaxed),
+            live: self.is_live(),
+        }
+    }
+
+    fn is_live(&self) -> bool {
+        self.live.load(Ordering::Acquire)
+    }
+
+    fn is_current(&self, generation: u64) -> bool {
+        self.is_live() && self.generation() == generation
+    }
+
+    fn bump(&self) {
+        self.generation.fetch_add(1, Ordering::AcqRel);
+        self.invalidations.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn record<T>(&self, found: Option<T>) -> Option<T> {
+        let counter = if found.is_some() {
+            &self.hits
+        } else {
+            &self.misses
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        found
+    }
+}
+
+impl Drop for ClientCache {
+    fn drop(&mut self) {
+        if let Some(task) = self.task.get() {
+            task.abort();
+        }
+    }
+}
+
+fn build_cache<V: Clone + Send + Sync + 'static>(config: &CacheConfig) -> Cache<String, V> {
+    let builder = Cache::builder().max_capacity(config.max_entries);
+    match config.ttl {
+        Some(ttl) => builder.time_to_live(ttl).build(),
+        None => builder.build(),
+    }
+}
+
+/// Keep `cache` subscribed to every event, resubscribing after failures
+async fn invalidate_from_events(client: MemoryGraphClient, cache: Weak<ClientCache>) {
+    let retry = client.retry_config();
+    let mut failures = 0;
+    loop {
+        match client.subscribe_events(EventFilter::new()).await {
+            Ok(mut events) => {
+                let Some(live) = cache.upgrade() else { return };
+                live.set_live(true);
+                drop(live);
+                failures = 0;
+
+                while let Some(event) = events.next().await {
+                    let Some(cache) = cache.upgrade() else { return };
+                    match event {
+                        Ok(event) => cache.apply(&event).await,
+                        Err(crate::ClientError::Status(status)) => {
+                            warn!("Cache event stream failed: {}", status);
+                            break;
+                        }
+                        Err(e) => {
+                            warn!("Unreadable event, clearing the cache: {}", e);
+                            cache.clear();
+                        }
+                    }
+                }
+            }
+            Err(e) => debug!("Cache could not subscribe to events: {}", e),
+        }
+
+        let Some(stale) = cache.upgrade() else { return };
+        stale.set_live(false);
+        drop(stale);
+        failures += 1;
+        tokio::time::sleep(retry.backoff(failures)).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::EventType;
+    use chrono::Utc;
+
+    fn event(payload: serde_json::Value) -> GraphEvent {
+        GraphEvent {
+            id: "e-1".to_string(),
+            event_type: EventType::NodeUpdated,
+            timestamp: Utc::now(),
+            payload,
+        }
+    }
+
+    fn session(id: &str) -> proto::Session {
+        proto::Session {
+            id: id.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cache_invalidation() {
+        let cache = ClientCache::new(&CacheConfig::default());
+
+        // Nothing is cached or served until the subscription is open
+        cache
+            .insert_session(cache.generation(), session("s-1"))
+            .await;
+        assert_eq!(cache.session("s-1").await, None);
+
+        cache.set_live(true);
+        cache
+            .insert_session(cache.generation(), session("s-1"))
+            .await;
+        cache
+            .insert_session(cache.generation(), session("s-2"))
+            .await;
+        assert_eq!(cache.session("s-1").await, Some(session("s-1")));
+
+        cache
+            .apply(&event(serde_json::json!({ "session_id": "s-1" })))
+            .await;
+        assert_eq!(cache.session("s-1").await, None);
+        assert!(cache.session("s-2").await.is_some());
+
+        // A read that raced with an invalidation is not cached
+        let generation = cache.generation();
+        cache.invalidate_node("n-1").await;
+        cache.insert_session(generation, session("s-1")).await;
+        assert_eq!(cache.session("s-1").await, None);
+
+        // An event that names nothing clears everything
+        cache.apply(&event(serde_json::Value::Null)).await;
+        assert_eq!(cache.session("s-2").await, None);
+
+        cache
+            .insert_session(cache.generation(), session("s-3"))
+            .await;
+        cache.set_live(false);
+        assert_eq!(cache.session("s-3").await, None);
+
+        let stats = cache.stats();
+        assert_eq!(stats.hits, 2);
+        assert_eq!(stats.misses, 5);
+        assert!(!stats.live);
+    }
+}
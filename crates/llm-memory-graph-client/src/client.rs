@@ -1,24 +1,236 @@
 //! Client implementation for the LLM Memory Graph service
 
+use crate::cache::{CacheConfig, CacheStats, ClientCache};
+use crate::config::{ClientConfig, RetryConfig};
+use crate::convert::{self, proto_to_node};
 use crate::error::{ClientError, Result};
+use crate::events::{EventFilter, GraphEvent};
+use crate::queue::{self, Delivery, OfflineQueue, OfflineQueueConfig, QueueStats, QueuedWrite};
+use futures::{Stream, StreamExt};
+use llm_memory_graph_types::{AliasTarget, Node};
 use std::collections::HashMap;
-use tonic::transport::Channel;
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tonic::transport::{Channel, Endpoint};
+use tonic::Streaming;
+use tracing::debug;
+use uuid::Uuid;
 
-// Include generated proto code
+/// Generated protobuf and gRPC types
+#[allow(missing_docs, clippy::all, clippy::pedantic)]
 pub mod proto {
     tonic::include_proto!("llm.memory.graph.v1");
 }
 
 use proto::memory_graph_service_client::MemoryGraphServiceClient;
 
+type ServiceClient = MemoryGraphServiceClient<Channel>;
+
+/// One page of a listing
+///
+/// Pass `next_cursor` back to the same listing method for the next page.
+/// Cursors are opaque and stay valid while the server keeps writing, so
+/// paging neither repeats nor skips items.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Page<T> {
+    /// Items in this page
+    pub items: Vec<T>,
+    /// Cursor for the next page, or `None` if this is the last page
+    pub next_cursor: Option<String>,
+}
+
+impl<T> Page<T> {
+    fn new(items: Vec<T>, next_cursor: String) -> Self {
+        Self {
+            items,
+            next_cursor: (!next_cursor.is_empty()).then_some(next_cursor),
+        }
+    }
+}
+
 /// High-level client for the LLM Memory Graph service
+///
+/// Cloning is cheap: clones share the same channel pool. Channels reconnect
+/// automatically after the server goes away, and idempotent calls are retried
+/// according to the configured [`RetryConfig`]. A client built with a
+/// [`cache`](ClientBuilder::cache) or an
+/// [`offline_queue`](ClientBuilder::offline_queue) shares them across clones
+/// as well.
 #[derive(Clone)]
 pub struct MemoryGraphClient {
-    client: MemoryGraphServiceClient<Channel>,
+    channels: Arc<[ServiceClient]>,
+    next: Arc<AtomicUsize>,
+    deadline: Option<Duration>,
+    retry: Arc<RetryConfig>,
+    cache: Option<Arc<ClientCache>>,
+    queue: Option<Arc<OfflineQueue>>,
+}
+
+/// Builder for [`MemoryGraphClient`]
+#[derive(Debug, Clone, Default)]
+pub struct ClientBuilder {
+    config: ClientConfig,
+}
+
+impl ClientBuilder {
+    /// Server address, e.g. `http://localhost:50051`
+    #[must_use]
+    pub fn address(mut self, address: impl Into<String>) -> Self {
+        self.config.address = address.into();
+        self
+    }
+
+    /// Timeout for establishing each connection
+    #[must_use]
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.config.connect_timeout = timeout;
+        self
+    }
+
+    /// Default deadline for every call
+    #[must_use]
+    pub fn deadline(mut self, deadline: Duration) -> Self {
+        self.config.deadline = Some(deadline);
+        self
+    }
+
+    /// Let calls run without a deadline
+    #[must_use]
+    pub fn no_deadline(mut self) -> Self {
+        self.config.deadline = None;
+        self
+    }
+
+    /// Spread calls round-robin across `size` channels
+    #[must_use]
+    pub fn pool_size(mut self, size: usize) -> Self {
+        self.config.pool_size = size;
+        self
+    }
+
+    /// Connect on first use instead of in [`build`](Self::build)
+    #[must_use]
+    pub fn lazy(mut self, lazy: bool) -> Self {
+        self.config.lazy = lazy;
+        self
+    }
+
+    /// HTTP/2 keep-alive ping interval (`None` disables pings)
+    #[must_use]
+    pub fn keep_alive_interval(mut self, interval: Option<Duration>) -> Self {
+        self.config.keep_alive_interval = interval;
+        self
+    }
+
+    /// Retry policy for idempotent calls
+    #[must_use]
+    pub fn retry(mut self, retry: RetryConfig) -> Self {
+        self.config.retry = retry;
+        self
+    }
+
+    /// Maximum attempts per idempotent call, including the first
+    #[must_use]
+    pub fn max_attempts(mut self, attempts: u32) -> Self {
+        self.config.retry.max_attempts = attempts;
+        self
+    }
+
+    /// Cache sessions and nodes locally, invalidated by the event stream
+    ///
+    /// See [`cache`](crate::cache) for how entries are kept consistent.
+    #[must_use]
+    pub fn cache(mut self, cache: CacheConfig) -> Self {
+        self.config.cache = Some(cache);
+        self
+    }
+
+    /// Queue writes on disk while the server is unreachable and replay them
+    /// once it is back
+    ///
+    /// See [`queue`](crate::queue) for which writes are queued and how.
+    #[must_use]
+    pub fn offline_queue(mut self, queue: OfflineQueueConfig) -> Self {
+        self.config.offline_queue = Some(queue);
+        self
+    }
+
+    /// Replace the whole configuration
+    #[must_use]
+    pub fn config(mut self, config: ClientConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Create the client
+    ///
+    /// Unless the builder is [`lazy`](Self::lazy), every channel in the pool is
+    /// connected before this returns.
+    pub async fn build(self) -> Result<MemoryGraphClient> {
+        let config = self.config;
+        if config.pool_size == 0 {
+            return Err(ClientError::InvalidArgument(
+                "pool size must be at least 1".to_string(),
+            ));
+        }
+        if config.retry.max_attempts == 0 {
+            return Err(ClientError::InvalidArgument(
+                "max attempts must be at least 1".to_string(),
+            ));
+        }
+
+        let mut endpoint = Endpoint::from_shared(config.address.clone())
+            .map_err(|e| ClientError::Connection(format!("{}: {}", config.address, e)))?
+            .connect_timeout(config.connect_timeout)
+            .keep_alive_while_idle(true);
+        if let Some(interval) = config.keep_alive_interval {
+            endpoint = endpoint.http2_keep_alive_interval(interval);
+        }
+
+        let mut channels = Vec::with_capacity(config.pool_size);
+        for _ in 0..config.pool_size {
+            let channel = if config.lazy {
+                endpoint.connect_lazy()
+            } else {
+                endpoint.connect().await?
+            };
+            channels.push(MemoryGraphServiceClient::new(channel));
+        }
+
+        let mut client = MemoryGraphClient {
+            channels: channels.into(),
+            next: Arc::new(AtomicUsize::new(0)),
+            deadline: config.deadline,
+            retry: Arc::new(config.retry),
+            cache: None,
+            queue: None,
+        };
+        if let Some(queue_config) = &config.offline_queue {
+            let queue = Arc::new(OfflineQueue::open(queue_config)?);
+            queue.spawn_replay(client.clone());
+            client.queue = Some(queue);
+        }
+        if let Some(cache_config) = &config.cache {
+            let cache = Arc::new(ClientCache::new(cache_config));
+            cache.spawn_invalidation(MemoryGraphClient {
+                queue: None,
+                ..client.clone()
+            });
+            client.cache = Some(cache);
+        }
+        Ok(client)
+    }
 }
 
 impl MemoryGraphClient {
-    /// Connect to the Memory Graph service
+    /// Start configuring a client
+    pub fn builder() -> ClientBuilder {
+        ClientBuilder::default()
+    }
+
+    /// Connect to the Memory Graph service with default settings
     pub async fn connect<D>(addr: D) -> Result<Self>
     where
         D: TryInto<tonic::transport::Endpoint>,
@@ -28,58 +240,393 @@ impl MemoryGraphClient {
             .try_into()
             .map_err(|e| ClientError::Connection(format!("{:?}", e)))?;
 
-        let client = MemoryGraphServiceClient::connect(endpoint).await?;
+        Self::builder()
+            .address(endpoint.uri().to_string())
+            .build()
+            .await
+    }
+
+    /// A handle that applies `deadline` to every call instead of the default
+    #[must_use]
+    pub fn with_deadline(&self, deadline: Duration) -> Self {
+        Self {
+            deadline: Some(deadline),
+            ..self.clone()
+        }
+    }
+
+    /// Number of channels in the pool
+    pub fn pool_size(&self) -> usize {
+        self.channels.len()
+    }
+
+    /// Counters of the local cache, if the client has one
+    pub fn cache_stats(&self) -> Option<CacheStats> {
+        self.cache.as_ref().map(|cache| cache.stats())
+    }
+
+    /// Drop every entry of the local cache, if the client has one
+    pub fn clear_cache(&self) {
+        if let Some(cache) = &self.cache {
+            cache.clear();
+        }
+    }
+
+    /// Counters of the offline queue, if the client has one
+    pub fn queue_stats(&self) -> Option<QueueStats> {
+        self.queue.as_ref().map(|queue| queue.stats())
+    }
+
+    /// Replay queued writes now instead of waiting for the background task
+    ///
+    /// Fails with the first error that shows the server is still unreachable.
+    /// Does nothing if the client has no offline queue.
+    pub async fn flush_queue(&self) -> Result<()> {
+        match &self.queue {
+            Some(queue) => queue.replay(self).await,
+            None => Ok(()),
+        }
+    }
 
-        Ok(Self { client })
+    pub(crate) fn retry_config(&self) -> Arc<RetryConfig> {
+        Arc::clone(&self.retry)
+    }
+
+    fn next_channel(&self) -> ServiceClient {
+        let index = self.next.fetch_add(1, Ordering::Relaxed) % self.channels.len();
+        self.channels[index].clone()
+    }
+
+    /// Run a unary call with the deadline and, if `idempotent`, retries applied
+    async fn unary<M, T, F, Fut>(&self, message: M, idempotent: bool, call: F) -> Result<T>
+    where
+        M: Clone,
+        F: Fn(ServiceClient, tonic::Request<M>) -> Fut,
+        Fut: Future<Output = std::result::Result<tonic::Response<T>, tonic::Status>>,
+    {
+        self.invoke(message, idempotent, true, call).await
+    }
+
+    /// Open a server stream, retrying transient failures to open it
+    ///
+    /// The deadline bounds only the wait for the stream to open; once open, a
+    /// stream runs until the server ends it or the caller drops it.
+    async fn open_stream<M, T, F, Fut>(&self, message: M, call: F) -> Result<Streaming<T>>
+    where
+        M: Clone,
+        F: Fn(ServiceClient, tonic::Request<M>) -> Fut,
+        Fut: Future<Output = std::result::Result<tonic::Response<Streaming<T>>, tonic::Status>>,
+    {
+        self.invoke(message, true, false, call).await
+    }
+
+    async fn invoke<M, T, F, Fut>(
+        &self,
+        message: M,
+        idempotent: bool,
+        propagate_deadline: bool,
+        call: F,
+    ) -> Result<T>
+    where
+        M: Clone,
+        F: Fn(ServiceClient, tonic::Request<M>) -> Fut,
+        Fut: Future<Output = std::result::Result<tonic::Response<T>, tonic::Status>>,
+    {
+        let max_attempts = if idempotent {
+            self.retry.max_attempts.max(1)
+        } else {
+            1
+        };
+
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+
+            let mut request = tonic::Request::new(message.clone());
+            let response = match self.deadline {
+                Some(deadline) => {
+                    if propagate_deadline {
+                        request.set_timeout(deadline);
+                    }
+                    match tokio::time::timeout(deadline, call(self.next_channel(), request)).await {
+                        Ok(response) => response,
+                        Err(_) => Err(tonic::Status::deadline_exceeded(format!(
+                            "no response within {}ms",
+                            deadline.as_millis()
+                        ))),
+                    }
+                }
+                None => call(self.next_channel(), request).await,
+            };
+
+            match response {
+                Ok(response) => return Ok(response.into_inner()),
+                Err(status) if attempt < max_attempts && self.retry.is_retryable(status.code()) => {
+                    let backoff = self.retry.backoff(attempt);
+                    debug!(
+                        "Attempt {} failed with {:?}, retrying in {}ms",
+                        attempt,
+                        status.code(),
+                        backoff.as_millis()
+                    );
+                    tokio::time::sleep(backoff).await;
+                }
+                Err(status) => return Err(status.into()),
+            }
+        }
     }
 
     /// Create a new session
     pub async fn create_session(&self, metadata: HashMap<String, String>) -> Result<String> {
         let request = proto::CreateSessionRequest { metadata };
-        let response = self.client.clone().create_session(request).await?;
-        Ok(response.into_inner().id)
+        let session = self
+            .unary(request, false, |mut c, r| async move {
+                c.create_session(r).await
+            })
+            .await?;
+        Ok(session.id)
     }
 
     /// Get a session by ID
+    ///
+    /// Served from the local cache when the client has one.
     pub async fn get_session(&self, session_id: String) -> Result<proto::Session> {
-        let request = proto::GetSessionRequest {
-            session_id,
+        let Some(cache) = &self.cache else {
+            return self.fetch_session(session_id).await;
         };
-        let response = self.client.clone().get_session(request).await?;
-        Ok(response.into_inner())
+        if let Some(session) = cache.session(&session_id).await {
+            return Ok(session);
+        }
+        let generation = cache.generation();
+        let session = self.fetch_session(session_id).await?;
+        cache.insert_session(generation, session.clone()).await;
+        Ok(session)
+    }
+
+    async fn fetch_session(&self, session_id: String) -> Result<proto::Session> {
+        let request = proto::GetSessionRequest { session_id };
+        self.unary(
+            request,
+            true,
+            |mut c, r| async move { c.get_session(r).await },
+        )
+        .await
     }
 
     /// Delete a session
     pub async fn delete_session(&self, session_id: String) -> Result<()> {
+        if let Some(cache) = &self.cache {
+            cache.invalidate_session(&session_id).await;
+        }
         let request = proto::DeleteSessionRequest { session_id };
-        self.client.clone().delete_session(request).await?;
-        Ok(())
+        self.unary(request, false, |mut c, r| async move {
+            c.delete_session(r).await
+        })
+        .await
+    }
+
+    /// Get a node by ID
+    ///
+    /// Served from the local cache when the client has one.
+    pub async fn get_node(&self, node_id: String) -> Result<Node> {
+        let Some(cache) = &self.cache else {
+            return self.fetch_node(node_id).await;
+        };
+        if let Some(node) = cache.node(&node_id).await {
+            return Ok(node);
+        }
+        let generation = cache.generation();
+        let node = self.fetch_node(node_id.clone()).await?;
+        cache.insert_node(generation, node_id, node.clone()).await;
+        Ok(node)
+    }
+
+    async fn fetch_node(&self, node_id: String) -> Result<Node> {
+        let request = proto::GetNodeRequest { node_id };
+        let node = self
+            .unary(request, true, |mut c, r| async move { c.get_node(r).await })
+            .await?;
+        proto_to_node(node)
     }
 
     /// List sessions
+    #[deprecated(note = "offsets shift as sessions are written; use `list_sessions_page`")]
     pub async fn list_sessions(&self, limit: i32, offset: i32) -> Result<Vec<proto::Session>> {
-        let request = proto::ListSessionsRequest { limit, offset };
-        let response = self.client.clone().list_sessions(request).await?;
-        Ok(response.into_inner().sessions)
+        #[allow(deprecated)]
+        let request = proto::ListSessionsRequest {
+            limit,
+            offset,
+            cursor: String::new(),
+        };
+        let response = self
+            .unary(
+                request,
+                true,
+                |mut c, r| async move { c.list_sessions(r).await },
+            )
+            .await?;
+        Ok(response.sessions)
+    }
+
+    /// List up to `limit` sessions after `cursor`, oldest first
+    ///
+    /// A `limit` of 0 asks for the server's default page size.
+    pub async fn list_sessions_page(
+        &self,
+        cursor: Option<String>,
+        limit: i32,
+    ) -> Result<Page<proto::Session>> {
+        #[allow(deprecated)]
+        let request = proto::ListSessionsRequest {
+            limit,
+            offset: 0,
+            cursor: cursor.unwrap_or_default(),
+        };
+        let response = self
+            .unary(
+                request,
+                true,
+                |mut c, r| async move { c.list_sessions(r).await },
+            )
+            .await?;
+        Ok(Page::new(response.sessions, response.next_cursor))
+    }
+
+    /// List up to `limit` nodes of a session after `cursor`, oldest first
+    pub async fn list_session_nodes(
+        &self,
+        session_id: String,
+        cursor: Option<String>,
+        limit: i32,
+    ) -> Result<Page<Node>> {
+        let request = proto::ListSessionNodesRequest {
+            session_id,
+            limit,
+            cursor: cursor.unwrap_or_default(),
+        };
+        let response = self
+            .unary(request, true, |mut c, r| async move {
+                c.list_session_nodes(r).await
+            })
+            .await?;
+        let nodes = response
+            .nodes
+            .into_iter()
+            .map(proto_to_node)
+            .collect::<Result<_>>()?;
+        Ok(Page::new(nodes, response.next_cursor))
+    }
+
+    /// List up to `limit` edges from or to a node after `cursor`
+    ///
+    /// `direction` must be outgoing or incoming.
+    pub async fn list_edges(
+        &self,
+        node_id: String,
+        direction: proto::EdgeDirection,
+        cursor: Option<String>,
+        limit: i32,
+    ) -> Result<Page<proto::Edge>> {
+        let request = proto::GetEdgesRequest {
+            node_id,
+            direction: Some(direction as i32),
+            r#type: None,
+            limit,
+            cursor: cursor.unwrap_or_default(),
+        };
+        let response = self
+            .unary(
+                request,
+                true,
+                |mut c, r| async move { c.get_edges(r).await },
+            )
+            .await?;
+        Ok(Page::new(response.edges, response.next_cursor))
+    }
+
+    /// List up to `limit` prompt templates after `cursor`, oldest first
+    pub async fn list_templates(
+        &self,
+        cursor: Option<String>,
+        limit: i32,
+    ) -> Result<Page<proto::TemplateNode>> {
+        let request = proto::ListTemplatesRequest {
+            limit,
+            cursor: cursor.unwrap_or_default(),
+        };
+        let response = self
+            .unary(request, true, |mut c, r| async move {
+                c.list_templates(r).await
+            })
+            .await?;
+        Ok(Page::new(response.templates, response.next_cursor))
+    }
+
+    /// List up to `limit` agents after `cursor`, oldest first
+    pub async fn list_agents(
+        &self,
+        cursor: Option<String>,
+        limit: i32,
+    ) -> Result<Page<proto::AgentNode>> {
+        let request = proto::ListAgentsRequest {
+            limit,
+            cursor: cursor.unwrap_or_default(),
+        };
+        let response = self
+            .unary(
+                request,
+                true,
+                |mut c, r| async move { c.list_agents(r).await },
+            )
+            .await?;
+        Ok(Page::new(response.agents, response.next_cursor))
     }
 
     /// Add a prompt
+    ///
+    /// The request carries a fresh idempotency key, so it is retried like an
+    /// idempotent call: a retry returns the prompt stored by the first attempt.
     pub async fn add_prompt(
         &self,
         session_id: String,
         content: String,
         metadata: Option<proto::PromptMetadata>,
     ) -> Result<proto::PromptNode> {
-        let request = proto::AddPromptRequest {
-            session_id,
-            content,
-            metadata,
-        };
-        let response = self.client.clone().add_prompt(request).await?;
-        Ok(response.into_inner())
+        self.send_prompt(prompt_request(session_id, content, metadata))
+            .await
+    }
+
+    /// Add a prompt, queueing it if the server is unreachable
+    ///
+    /// Without an [`offline_queue`](ClientBuilder::offline_queue) this is
+    /// [`add_prompt`](Self::add_prompt). With one, the prompt is queued when
+    /// the server cannot be reached or earlier writes are still queued.
+    pub async fn add_prompt_or_queue(
+        &self,
+        session_id: String,
+        content: String,
+        metadata: Option<proto::PromptMetadata>,
+    ) -> Result<Delivery<proto::PromptNode>> {
+        let request = prompt_request(session_id, content, metadata);
+        self.send_or_queue(
+            QueuedWrite::AddPrompt(request.clone()),
+            self.send_prompt(request),
+        )
+        .await
+    }
+
+    async fn send_prompt(&self, request: proto::AddPromptRequest) -> Result<proto::PromptNode> {
+        self.unary(
+            request,
+            true,
+            |mut c, r| async move { c.add_prompt(r).await },
+        )
+        .await
     }
 
     /// Add a response
+    ///
+    /// Retried like [`add_prompt`](Self::add_prompt).
     pub async fn add_response(
         &self,
         prompt_id: String,
@@ -87,14 +634,133 @@ impl MemoryGraphClient {
         token_usage: Option<proto::TokenUsage>,
         metadata: Option<proto::ResponseMetadata>,
     ) -> Result<proto::ResponseNode> {
-        let request = proto::AddResponseRequest {
-            prompt_id,
-            content,
-            token_usage,
-            metadata,
+        self.send_response(response_request(prompt_id, content, token_usage, metadata))
+            .await
+    }
+
+    /// Add a response, queueing it if the server is unreachable
+    ///
+    /// Queued like [`add_prompt_or_queue`](Self::add_prompt_or_queue).
+    pub async fn add_response_or_queue(
+        &self,
+        prompt_id: String,
+        content: String,
+        token_usage: Option<proto::TokenUsage>,
+        metadata: Option<proto::ResponseMetadata>,
+    ) -> Result<Delivery<proto::ResponseNode>> {
+        let request = response_request(prompt_id, content, token_usage, metadata);
+        self.send_or_queue(
+            QueuedWrite::AddResponse(request.clone()),
+            self.send_response(request),
+        )
+        .await
+    }
+
+    async fn send_response(
+        &self,
+        request: proto::AddResponseRequest,
+    ) -> Result<proto::ResponseNode> {
+        self.unary(
+            request,
+            true,
+            |mut c, r| async move { c.add_response(r).await },
+        )
+        .await
+    }
+
+    /// Send `write` unless writes are queued, queueing it if the server turns
+    /// out to be unreachable
+    async fn send_or_queue<T>(
+        &self,
+        write: QueuedWrite,
+        send: impl Future<Output = Result<T>>,
+    ) -> Result<Delivery<T>> {
+        let Some(queue) = &self.queue else {
+            return send.await.map(Delivery::Sent);
         };
-        let response = self.client.clone().add_response(request).await?;
-        Ok(response.into_inner())
+        if queue.depth() > 0 {
+            queue.push(write)?;
+            return Ok(Delivery::Queued);
+        }
+        match send.await {
+            Ok(reply) => Ok(Delivery::Sent(reply)),
+            Err(e) if queue::is_offline(&e, &self.retry) => {
+                debug!("Server unreachable, queueing write: {}", e);
+                queue.push(write)?;
+                Ok(Delivery::Queued)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Deliver a write taken from the offline queue
+    pub(crate) async fn send_queued(&self, write: QueuedWrite) -> Result<()> {
+        match write {
+            QueuedWrite::AddPrompt(request) => self.send_prompt(request).await.map(drop),
+            QueuedWrite::AddResponse(request) => self.send_response(request).await.map(drop),
+        }
+    }
+
+    /// Point an external key at a session or node
+    ///
+    /// Setting an alias that exists repoints it.
+    pub async fn set_alias(&self, alias: String, target: AliasTarget) -> Result<()> {
+        let request = proto::SetAliasRequest {
+            alias,
+            target: Some(convert::alias_target_to_proto(target)),
+        };
+        self.unary(
+            request,
+            true,
+            |mut c, r| async move { c.set_alias(r).await },
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// What an alias points at, or `None` if it is not set
+    pub async fn resolve_alias(&self, alias: String) -> Result<Option<AliasTarget>> {
+        let request = proto::ResolveAliasRequest { alias };
+        let result = self
+            .unary(
+                request,
+                true,
+                |mut c, r| async move { c.resolve_alias(r).await },
+            )
+            .await;
+        match result {
+            Ok(alias) => Ok(Some(convert::proto_to_alias(alias)?.1)),
+            Err(ClientError::Status(status)) if status.code() == tonic::Code::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Remove an alias
+    pub async fn remove_alias(&self, alias: String) -> Result<()> {
+        let request = proto::RemoveAliasRequest { alias };
+        self.unary(
+            request,
+            true,
+            |mut c, r| async move { c.remove_alias(r).await },
+        )
+        .await
+    }
+
+    /// Every alias starting with `prefix`, with its target
+    pub async fn list_aliases(&self, prefix: String) -> Result<Vec<(String, AliasTarget)>> {
+        let request = proto::ListAliasesRequest { prefix };
+        let response = self
+            .unary(
+                request,
+                true,
+                |mut c, r| async move { c.list_aliases(r).await },
+            )
+            .await?;
+        response
+            .aliases
+            .into_iter()
+            .map(convert::proto_to_alias)
+            .collect()
     }
 
     /// Query nodes
@@ -114,22 +780,99 @@ impl MemoryGraphClient {
             after: None,
             before: None,
         };
-        let response = self.client.clone().query(request).await?;
-        Ok(response.into_inner())
+        self.unary(request, true, |mut c, r| async move { c.query(r).await })
+            .await
+    }
+
+    /// Stream the nodes matching a query
+    ///
+    /// Nodes are converted to [`Node`] as they arrive; a node that cannot be
+    /// converted yields an error item without ending the stream.
+    pub async fn query_stream(
+        &self,
+        request: proto::QueryRequest,
+    ) -> Result<impl Stream<Item = Result<Node>> + Send + 'static> {
+        let stream = self
+            .open_stream(request, |mut c, r| async move { c.stream_query(r).await })
+            .await?;
+        Ok(stream.map(|item| item.map_err(ClientError::from).and_then(proto_to_node)))
+    }
+
+    /// Subscribe to graph events matching `filter`
+    pub async fn subscribe_events(
+        &self,
+        filter: EventFilter,
+    ) -> Result<impl Stream<Item = Result<GraphEvent>> + Send + 'static> {
+        let stream = self
+            .open_stream(filter.into_request(), |mut c, r| async move {
+                c.stream_events(r).await
+            })
+            .await?;
+        Ok(stream.map(|item| {
+            item.map_err(ClientError::from)
+                .and_then(GraphEvent::try_from)
+        }))
+    }
+
+    /// Stream records to the server's bulk loader
+    ///
+    /// Build records with [`convert::node_to_import_record`] and
+    /// [`convert::edge_to_import_record`]. The returned stream yields a
+    /// progress ack after every batch the server writes; the last ack has
+    /// `done` set and summarizes the import. Imports are not retried, since a
+    /// consumed stream cannot be replayed.
+    pub async fn import(
+        &self,
+        records: impl Stream<Item = proto::ImportRecord> + Send + 'static,
+    ) -> Result<impl Stream<Item = Result<proto::ImportProgress>> + Send + 'static> {
+        let stream = self
+            .next_channel()
+            .import_nodes(tonic::Request::new(records))
+            .await?
+            .into_inner();
+        Ok(stream.map(|item| item.map_err(ClientError::from)))
     }
 
     /// Get service health
     pub async fn health(&self) -> Result<proto::HealthResponse> {
-        let request = tonic::Request::new(());
-        let response = self.client.clone().health(request).await?;
-        Ok(response.into_inner())
+        self.unary((), true, |mut c, r| async move { c.health(r).await })
+            .await
     }
 
     /// Get service metrics
     pub async fn metrics(&self) -> Result<proto::MetricsResponse> {
-        let request = tonic::Request::new(());
-        let response = self.client.clone().get_metrics(request).await?;
-        Ok(response.into_inner())
+        self.unary((), true, |mut c, r| async move { c.get_metrics(r).await })
+            .await
+    }
+}
+
+/// A prompt request with a fresh idempotency key
+fn prompt_request(
+    session_id: String,
+    content: String,
+    metadata: Option<proto::PromptMetadata>,
+) -> proto::AddPromptRequest {
+    proto::AddPromptRequest {
+        session_id,
+        content,
+        metadata,
+        idempotency_key: Some(Uuid::new_v4().to_string()),
+    }
+}
+
+/// A response request with a fresh idempotency key
+fn response_request(
+    prompt_id: String,
+    content: String,
+    token_usage: Option<proto::TokenUsage>,
+    metadata: Option<proto::ResponseMetadata>,
+) -> proto::AddResponseRequest {
+    proto::AddResponseRequest {
+        prompt_id,
+        content,
+        token_usage,
+        metadata,
+        idempotency_key: Some(Uuid::new_v4().to_string()),
     }
 }
 
@@ -137,9 +880,263 @@ impl MemoryGraphClient {
 mod tests {
     use super::*;
 
+    use std::sync::atomic::AtomicU32;
+
+    async fn lazy_client(retry: RetryConfig) -> MemoryGraphClient {
+        // Nothing listens on port 1; lazy channels only fail once used
+        MemoryGraphClient::builder()
+            .address("http://127.0.0.1:1")
+            .lazy(true)
+            .pool_size(3)
+            .retry(retry)
+            .build()
+            .await
+            .unwrap()
+    }
+
+    fn fast_retry() -> RetryConfig {
+        RetryConfig {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(5),
+            ..RetryConfig::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_builder_validates_config() {
+        let result = MemoryGraphClient::builder()
+            .lazy(true)
+            .pool_size(0)
+            .build()
+            .await;
+        assert!(matches!(result, Err(ClientError::InvalidArgument(_))));
+
+        let result = MemoryGraphClient::builder()
+            .lazy(true)
+            .max_attempts(0)
+            .build()
+            .await;
+        assert!(matches!(result, Err(ClientError::InvalidArgument(_))));
+
+        let result = MemoryGraphClient::builder()
+            .address("not a uri")
+            .lazy(true)
+            .build()
+            .await;
+        assert!(matches!(result, Err(ClientError::Connection(_))));
+    }
+
+    #[tokio::test]
+    async fn test_eager_connect_fails_without_server() {
+        let result = MemoryGraphClient::builder()
+            .address("http://127.0.0.1:1")
+            .connect_timeout(Duration::from_millis(200))
+            .build()
+            .await;
+        assert!(matches!(result, Err(ClientError::Transport(_))));
+    }
+
     #[test]
-    fn test_client_construction() {
-        // Verify types compile
-        let _client: Option<MemoryGraphClient> = None;
+    fn test_page_next_cursor() {
+        let last = Page::new(vec![1, 2], String::new());
+        assert_eq!(last.next_cursor, None);
+        let page = Page::new(vec![1, 2], "AAAA".to_string());
+        assert_eq!(page.next_cursor.as_deref(), Some("AAAA"));
+    }
+
+    #[tokio::test]
+    async fn test_pool_round_robin() {
+        let client = lazy_client(fast_retry()).await;
+        assert_eq!(client.pool_size(), 3);
+
+        let start = client.next.load(Ordering::Relaxed);
+        for _ in 0..7 {
+            client.next_channel();
+        }
+        assert_eq!(client.next.load(Ordering::Relaxed) - start, 7);
+    }
+
+    #[tokio::test]
+    async fn test_idempotent_calls_are_retried() {
+        let client = lazy_client(fast_retry()).await;
+        let attempts = AtomicU32::new(0);
+
+        let result: Result<()> = client
+            .unary((), true, |_, _| {
+                attempts.fetch_add(1, Ordering::SeqCst);
+                async { Err(tonic::Status::unavailable("down")) }
+            })
+            .await;
+
+        assert!(
+            matches!(result, Err(ClientError::Status(s)) if s.code() == tonic::Code::Unavailable)
+        );
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_non_idempotent_and_permanent_errors_not_retried() {
+        let client = lazy_client(fast_retry()).await;
+        let attempts = AtomicU32::new(0);
+
+        let _: Result<()> = client
+            .unary((), false, |_, _| {
+                attempts.fetch_add(1, Ordering::SeqCst);
+                async { Err(tonic::Status::unavailable("down")) }
+            })
+            .await;
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+
+        let _: Result<()> = client
+            .unary((), true, |_, _| {
+                attempts.fetch_add(1, Ordering::SeqCst);
+                async { Err(tonic::Status::invalid_argument("bad")) }
+            })
+            .await;
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_retry_recovers_after_transient_failure() {
+        let client = lazy_client(fast_retry()).await;
+        let attempts = AtomicU32::new(0);
+
+        let result = client
+            .unary((), true, |_, _| {
+                let attempt = attempts.fetch_add(1, Ordering::SeqCst);
+                async move {
+                    if attempt == 0 {
+                        Err(tonic::Status::unavailable("restarting"))
+                    } else {
+                        Ok(tonic::Response::new(42))
+                    }
+                }
+            })
+            .await;
+        assert_eq!(result.unwrap(), 42);
+    }
+
+    #[tokio::test]
+    async fn test_deadline_is_enforced() {
+        let client = lazy_client(RetryConfig::disabled())
+            .await
+            .with_deadline(Duration::from_millis(20));
+
+        let result: Result<()> = client
+            .unary((), true, |_, request| {
+                assert!(request.metadata().get("grpc-timeout").is_some());
+                async {
+                    tokio::time::sleep(Duration::from_secs(5)).await;
+                    Ok(tonic::Response::new(()))
+                }
+            })
+            .await;
+        assert!(
+            matches!(result, Err(ClientError::Status(s)) if s.code() == tonic::Code::DeadlineExceeded)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stream_open_does_not_propagate_deadline() {
+        let client = lazy_client(RetryConfig::disabled())
+            .await
+            .with_deadline(Duration::from_millis(20));
+
+        let result: Result<()> = client
+            .invoke((), true, false, |_, request| {
+                assert!(request.metadata().get("grpc-timeout").is_none());
+                async { Ok(tonic::Response::new(())) }
+            })
+            .await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_streams_fail_to_open_without_server() {
+        let client = lazy_client(fast_retry()).await;
+
+        let result = client.query_stream(proto::QueryRequest::default()).await;
+        assert!(
+            matches!(result, Err(ClientError::Status(s)) if s.code() == tonic::Code::Unavailable)
+        );
+
+        let result = client.subscribe_events(EventFilter::new()).await;
+        assert!(
+            matches!(result, Err(ClientError::Status(s)) if s.code() == tonic::Code::Unavailable)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_server_surfaces_unavailable() {
+        let client = lazy_client(fast_retry()).await;
+        let result = client.health().await;
+        assert!(
+            matches!(result, Err(ClientError::Status(s)) if s.code() == tonic::Code::Unavailable)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cache_bypassed_without_event_stream() {
+        let client = MemoryGraphClient::builder()
+            .address("http://127.0.0.1:1")
+            .lazy(true)
+            .retry(fast_retry())
+            .cache(CacheConfig::default())
+            .build()
+            .await
+            .unwrap();
+        assert_eq!(lazy_client(fast_retry()).await.cache_stats(), None);
+
+        // With no event stream the cache cannot stay consistent, so every
+        // read goes to the server
+        assert!(client.get_session("s-1".to_string()).await.is_err());
+        assert!(client.get_session("s-1".to_string()).await.is_err());
+        let stats = client.cache_stats().unwrap();
+        assert_eq!(stats.hits, 0);
+        assert_eq!(stats.misses, 2);
+        assert!(!stats.live);
+    }
+
+    #[tokio::test]
+    async fn test_writes_queued_while_offline() {
+        let dir = tempfile::tempdir().unwrap();
+        let client = MemoryGraphClient::builder()
+            .address("http://127.0.0.1:1")
+            .lazy(true)
+            .retry(fast_retry())
+            .offline_queue(OfflineQueueConfig::new(dir.path()))
+            .build()
+            .await
+            .unwrap();
+
+        let delivery = client
+            .add_prompt_or_queue("s-1".to_string(), "hello".to_string(), None)
+            .await
+            .unwrap();
+        assert_eq!(delivery, Delivery::Queued);
+        let delivery = client
+            .add_response_or_queue("p-1".to_string(), "hi".to_string(), None, None)
+            .await
+            .unwrap();
+        assert_eq!(delivery.sent(), None);
+
+        assert!(client.flush_queue().await.is_err());
+        let stats = client.queue_stats().unwrap();
+        assert_eq!(stats.depth, 2);
+        assert_eq!(stats.enqueued, 2);
+        assert_eq!(stats.replayed, 0);
+        drop(client);
+
+        // Queued writes outlive the client
+        let client = MemoryGraphClient::builder()
+            .address("http://127.0.0.1:1")
+            .lazy(true)
+            .retry(RetryConfig::disabled())
+            .offline_queue(OfflineQueueConfig::new(dir.path()))
+            .build()
+            .await
+            .unwrap();
+        assert_eq!(client.queue_stats().unwrap().depth, 2);
     }
 }
@@ -0,0 +1,161 @@
+//! Client configuration: deadlines, retries and connection pooling
+
+use crate::cache::CacheConfig;
+use crate::queue::OfflineQueueConfig;
+use rand::Rng;
+use std::time::Duration;
+use tonic::Code;
+
+/// Retry behaviour for idempotent RPCs
+///
+/// Non-idempotent calls (creating sessions, nodes, edges, ...) are never
+/// retried, since the server may have applied them before failing. Prompts and
+/// responses are sent with an idempotency key, so they are retried too.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryConfig {
+    /// Total attempts per call, including the first one
+    pub max_attempts: u32,
+    /// Backoff before the first retry
+    pub initial_backoff: Duration,
+    /// Upper bound on the backoff between attempts
+    pub max_backoff: Duration,
+    /// Backoff growth factor per attempt
+    pub multiplier: f64,
+    /// Randomise each backoff so that clients do not retry in lockstep
+    pub jitter: bool,
+    /// Status codes that are considered transient
+    pub retryable_codes: Vec<Code>,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(5),
+            multiplier: 2.0,
+            jitter: true,
+            retryable_codes: vec![
+                Code::Unavailable,
+                Code::DeadlineExceeded,
+                Code::ResourceExhausted,
+            ],
+        }
+    }
+}
+
+impl RetryConfig {
+    /// Disable retries entirely
+    pub fn disabled() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    /// Whether a failed call with this status code may be retried
+    pub fn is_retryable(&self, code: Code) -> bool {
+        self.retryable_codes.contains(&code)
+    }
+
+    /// Backoff to wait after the given (1-based) failed attempt
+    ///
+    /// With jitter enabled the delay is drawn uniformly from the upper half of
+    /// the exponential backoff, so it never collapses to zero.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let exponent = i32::try_from(attempt.saturating_sub(1)).unwrap_or(i32::MAX);
+        let factor = self.multiplier.powi(exponent);
+        let capped = if factor.is_finite()
+            && self.initial_backoff.as_secs_f64() * factor < self.max_backoff.as_secs_f64()
+        {
+            self.initial_backoff.mul_f64(factor)
+        } else {
+            self.max_backoff
+        };
+
+        if self.jitter {
+            capped.mul_f64(rand::thread_rng().gen_range(0.5..=1.0))
+        } else {
+            capped
+        }
+    }
+}
+
+/// Connection and call settings for [`MemoryGraphClient`](crate::MemoryGraphClient)
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClientConfig {
+    /// Server address, e.g. `http://localhost:50051`
+    pub address: String,
+    /// Timeout for establishing a connection
+    pub connect_timeout: Duration,
+    /// Default deadline applied to every call (`None` = no deadline)
+    pub deadline: Option<Duration>,
+    /// Number of channels (HTTP/2 connections) to spread calls across
+    pub pool_size: usize,
+    /// Connect on first use instead of during `build()`
+    pub lazy: bool,
+    /// Interval for HTTP/2 keep-alive pings used to detect dead connections
+    pub keep_alive_interval: Option<Duration>,
+    /// Retry policy for idempotent calls
+    pub retry: RetryConfig,
+    /// Local cache of sessions and nodes (`None` = no cache)
+    pub cache: Option<CacheConfig>,
+    /// Queue writes on disk while the server is unreachable (`None` = fail
+    /// them instead)
+    pub offline_queue: Option<OfflineQueueConfig>,
+}
+
+impl Default for ClientConfig {
+    fn default() -> Self {
+        Self {
+            address: "http://localhost:50051".to_string(),
+            connect_timeout: Duration::from_secs(10),
+            deadline: Some(Duration::from_secs(30)),
+            pool_size: 1,
+            lazy: false,
+            keep_alive_interval: Some(Duration::from_secs(30)),
+            retry: RetryConfig::default(),
+            cache: None,
+            offline_queue: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_without_jitter_is_exponential_and_capped() {
+        let retry = RetryConfig {
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_millis(350),
+            jitter: false,
+            ..RetryConfig::default()
+        };
+
+        assert_eq!(retry.backoff(1), Duration::from_millis(100));
+        assert_eq!(retry.backoff(2), Duration::from_millis(200));
+        assert_eq!(retry.backoff(3), Duration::from_millis(350));
+        assert_eq!(retry.backoff(30), Duration::from_millis(350));
+    }
+
+    #[test]
+    fn test_backoff_jitter_stays_in_upper_half() {
+        let retry = RetryConfig::default();
+        for _ in 0..100 {
+            let delay = retry.backoff(2);
+            assert!(delay >= Duration::from_millis(100));
+            assert!(delay <= Duration::from_millis(200));
+        }
+    }
+
+    #[test]
+    fn test_retryable_codes() {
+        let retry = RetryConfig::default();
+        assert!(retry.is_retryable(Code::Unavailable));
+        assert!(!retry.is_retryable(Code::InvalidArgument));
+        assert!(!retry.is_retryable(Code::NotFound));
+        assert_eq!(RetryConfig::disabled().max_attempts, 1);
+    }
+}
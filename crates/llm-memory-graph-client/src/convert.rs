@@ -0,0 +1,817 @@
+//! Conversions between protobuf messages and `llm-memory-graph-types`
+//!
+//! The wire format carries a subset of each node's fields. Converting a node to
+//! protobuf and back preserves everything the wire format carries; fields it
+//! does not carry (template descriptions, agent configuration, ...) come back
+//! with their defaults.
+
+use crate::client::proto;
+use crate::error::{ClientError, Result};
+use chrono::{DateTime, Utc};
+use llm_memory_graph_types::{
+    AgentId, AgentNode, AgentStatus, AliasTarget, ConversationSession, Edge, EdgeId, EdgeType,
+    Node, NodeId, PromptMetadata, PromptNode, PromptTemplate, Properties, ResponseMetadata,
+    ResponseNode, SessionId, TemplateId, TokenUsage, ToolInvocation, VariableSpec,
+};
+use prost_types::Timestamp;
+use uuid::Uuid;
+
+/// Metadata key used to carry an agent's model on the wire
+const AGENT_MODEL_KEY: &str = "model";
+
+fn conversion(message: impl Into<String>) -> ClientError {
+    ClientError::Conversion(message.into())
+}
+
+// ============================================================================
+// Scalars
+// ============================================================================
+
+/// Convert chrono `DateTime` to protobuf `Timestamp`
+pub fn datetime_to_proto(dt: DateTime<Utc>) -> Timestamp {
+    Timestamp {
+        seconds: dt.timestamp(),
+        nanos: i32::try_from(dt.timestamp_subsec_nanos()).unwrap_or(i32::MAX),
+    }
+}
+
+/// Convert protobuf `Timestamp` to chrono `DateTime`
+pub fn proto_to_datetime(ts: &Timestamp) -> Result<DateTime<Utc>> {
+    let nanos = u32::try_from(ts.nanos).map_err(|_| conversion("negative timestamp nanos"))?;
+    DateTime::from_timestamp(ts.seconds, nanos)
+        .ok_or_else(|| conversion(format!("timestamp out of range: {}s", ts.seconds)))
+}
+
+/// Convert a required protobuf `Timestamp` field to chrono `DateTime`
+pub fn optional_proto_to_datetime(ts: Option<Timestamp>) -> Result<DateTime<Utc>> {
+    match ts {
+        Some(timestamp) => proto_to_datetime(&timestamp),
+        None => Err(conversion("missing timestamp")),
+    }
+}
+
+fn parse_uuid(id: &str) -> Result<Uuid> {
+    Uuid::parse_str(id).map_err(|e| conversion(format!("invalid id {:?}: {}", id, e)))
+}
+
+fn parse_json(field: &str, json: &str) -> Result<serde_json::Value> {
+    serde_json::from_str(json).map_err(|e| conversion(format!("invalid JSON in {}: {}", field, e)))
+}
+
+fn to_i64(value: u64) -> i64 {
+    i64::try_from(value).unwrap_or(i64::MAX)
+}
+
+fn from_i64<T: TryFrom<i64>>(field: &str, value: i64) -> Result<T> {
+    T::try_from(value).map_err(|_| conversion(format!("{} out of range: {}", field, value)))
+}
+
+/// Parse a node ID
+pub fn parse_node_id(id: &str) -> Result<NodeId> {
+    parse_uuid(id).map(NodeId::from_uuid)
+}
+
+/// Parse a session ID
+pub fn parse_session_id(id: &str) -> Result<SessionId> {
+    parse_uuid(id).map(SessionId::from_uuid)
+}
+
+// ============================================================================
+// Metadata
+// ============================================================================
+
+/// Convert internal `PromptMetadata` to protobuf
+pub fn prompt_metadata_to_proto(metadata: PromptMetadata) -> proto::PromptMetadata {
+    proto::PromptMetadata {
+        model: metadata.model,
+        temperature: f64::from(metadata.temperature),
+        max_tokens: metadata
+            .max_tokens
+            .map(|t| i32::try_from(t).unwrap_or(i32::MAX)),
+        tools_available: metadata.tools_available,
+        custom: metadata.custom,
+    }
+}
+
+/// Convert protobuf `PromptMetadata` to internal
+#[allow(clippy::cast_possible_truncation)]
+pub fn proto_to_prompt_metadata(metadata: proto::PromptMetadata) -> Result<PromptMetadata> {
+    Ok(PromptMetadata {
+        model: metadata.model,
+        temperature: metadata.temperature as f32,
+        max_tokens: metadata
+            .max_tokens
+            .map(|t| usize::try_from(t).map_err(|_| conversion("negative max_tokens")))
+            .transpose()?,
+        tools_available: metadata.tools_available,
+        custom: metadata.custom,
+    })
+}
+
+/// Convert internal `ResponseMetadata` to protobuf
+pub fn response_metadata_to_proto(metadata: ResponseMetadata) -> proto::ResponseMetadata {
+    proto::ResponseMetadata {
+        model: metadata.model,
+        finish_reason: metadata.finish_reason,
+        latency_ms: to_i64(metadata.latency_ms),
+        custom: metadata.custom,
+    }
+}
+
+/// Convert protobuf `ResponseMetadata` to internal
+pub fn proto_to_response_metadata(metadata: proto::ResponseMetadata) -> Result<ResponseMetadata> {
+    Ok(ResponseMetadata {
+        model: metadata.model,
+        finish_reason: metadata.finish_reason,
+        latency_ms: from_i64("latency_ms", metadata.latency_ms)?,
+        custom: metadata.custom,
+    })
+}
+
+/// Convert internal `TokenUsage` to protobuf
+pub fn token_usage_to_proto(usage: TokenUsage) -> proto::TokenUsage {
+    proto::TokenUsage {
+        prompt_tokens: i64::from(usage.prompt_tokens),
+        completion_tokens: i64::from(usage.completion_tokens),
+        total_tokens: i64::from(usage.total_tokens),
+    }
+}
+
+/// Convert protobuf `TokenUsage` to internal
+pub fn proto_to_token_usage(usage: &proto::TokenUsage) -> Result<TokenUsage> {
+    Ok(TokenUsage {
+        prompt_tokens: from_i64("prompt_tokens", usage.prompt_tokens)?,
+        completion_tokens: from_i64("completion_tokens", usage.completion_tokens)?,
+        total_tokens: from_i64("total_tokens", usage.total_tokens)?,
+    })
+}
+
+/// Convert internal `AgentStatus` to its wire name
+pub fn agent_status_to_proto(status: &AgentStatus) -> String {
+    match status {
+        AgentStatus::Active => "active",
+        AgentStatus::Idle => "idle",
+        AgentStatus::Busy => "busy",
+        AgentStatus::Paused => "paused",
+        AgentStatus::Terminated => "terminated",
+    }
+    .to_string()
+}
+
+/// Parse an `AgentStatus` from its wire name
+pub fn proto_to_agent_status(status: &str) -> Result<AgentStatus> {
+    match status {
+        "active" => Ok(AgentStatus::Active),
+        "idle" => Ok(AgentStatus::Idle),
+        "busy" => Ok(AgentStatus::Busy),
+        "paused" => Ok(AgentStatus::Paused),
+        "terminated" => Ok(AgentStatus::Terminated),
+        other => Err(conversion(format!("unknown agent status: {}", other))),
+    }
+}
+
+/// Convert internal `VariableSpec` to protobuf
+pub fn variable_spec_to_proto(spec: VariableSpec) -> proto::VariableSpec {
+    proto::VariableSpec {
+        name: spec.name,
+        type_hint: spec.type_hint,
+        required: spec.required,
+        default_value: spec.default,
+        validation_pattern: spec.validation_pattern,
+        description: spec.description,
+    }
+}
+
+/// Convert protobuf `VariableSpec` to internal
+pub fn proto_to_variable_spec(spec: proto::VariableSpec) -> VariableSpec {
+    VariableSpec {
+        name: spec.name,
+        type_hint: spec.type_hint,
+        required: spec.required,
+        default: spec.default_value,
+        validation_pattern: spec.validation_pattern,
+        description: spec.description,
+    }
+}
+
+// ============================================================================
+// Nodes
+// ============================================================================
+
+/// Convert internal `PromptNode` to protobuf
+pub fn prompt_node_to_proto(prompt: PromptNode) -> proto::PromptNode {
+    proto::PromptNode {
+        id: prompt.id.to_string(),
+        session_id: prompt.session_id.to_string(),
+        content: prompt.content,
+        timestamp: Some(datetime_to_proto(prompt.timestamp)),
+        metadata: Some(prompt_metadata_to_proto(prompt.metadata)),
+    }
+}
+
+/// Convert protobuf `PromptNode` to internal
+pub fn proto_to_prompt_node(prompt: proto::PromptNode) -> Result<PromptNode> {
+    Ok(PromptNode {
+        id: parse_node_id(&prompt.id)?,
+        session_id: parse_session_id(&prompt.session_id)?,
+        timestamp: optional_proto_to_datetime(prompt.timestamp)?,
+        template_id: None,
+        content: prompt.content,
+        variables: std::collections::HashMap::new(),
+        metadata: prompt
+            .metadata
+            .map(proto_to_prompt_metadata)
+            .transpose()?
+            .unwrap_or_default(),
+        // Properties are not carried over gRPC
+        properties: Properties::new(),
+    })
+}
+
+/// Convert internal `ResponseNode` to protobuf
+pub fn response_node_to_proto(response: ResponseNode) -> proto::ResponseNode {
+    proto::ResponseNode {
+        id: response.id.to_string(),
+        prompt_id: response.prompt_id.to_string(),
+        content: response.content,
+        timestamp: Some(datetime_to_proto(response.timestamp)),
+        token_usage: Some(token_usage_to_proto(response.usage)),
+        metadata: Some(response_metadata_to_proto(response.metadata)),
+    }
+}
+
+/// Convert protobuf `ResponseNode` to internal
+pub fn proto_to_response_node(response: proto::ResponseNode) -> Result<ResponseNode> {
+    Ok(ResponseNode {
+        id: parse_node_id(&response.id)?,
+        prompt_id: parse_node_id(&response.prompt_id)?,
+        timestamp: optional_proto_to_datetime(response.timestamp)?,
+        content: response.content,
+        usage: response
+            .token_usage
+            .as_ref()
+            .map(proto_to_token_usage)
+            .transpose()?
+            .unwrap_or(TokenUsage::new(0, 0)),
+        metadata: response
+            .metadata
+            .map(proto_to_response_metadata)
+            .transpose()?
+            .unwrap_or_default(),
+        properties: Properties::new(),
+    })
+}
+
+/// Convert internal `ToolInvocation` to protobuf
+pub fn tool_invocation_to_proto(tool: ToolInvocation) -> proto::ToolInvocationNode {
+    proto::ToolInvocationNode {
+        status: tool.status().to_string(),
+        id: tool.id.to_string(),
+        response_id: tool.response_id.to_string(),
+        tool_name: tool.tool_name,
+        parameters: tool.parameters.to_string(),
+        result: tool.result.map(|r| r.to_string()),
+        error: tool.error,
+        duration_ms: to_i64(tool.duration_ms),
+        retry_count: i32::try_from(tool.retry_count).unwrap_or(i32::MAX),
+        timestamp: Some(datetime_to_proto(tool.timestamp)),
+        metadata: tool.metadata,
+    }
+}
+
+/// Convert protobuf `ToolInvocationNode` to internal
+pub fn proto_to_tool_invocation(tool: proto::ToolInvocationNode) -> Result<ToolInvocation> {
+    Ok(ToolInvocation {
+        id: parse_node_id(&tool.id)?,
+        response_id: parse_node_id(&tool.response_id)?,
+        parameters: parse_json("parameters", &tool.parameters)?,
+        result: tool
+            .result
+            .as_deref()
+            .map(|r| parse_json("result", r))
+            .transpose()?,
+        tool_name: tool.tool_name,
+        error: tool.error,
+        duration_ms: from_i64("duration_ms", tool.duration_ms)?,
+        timestamp: optional_proto_to_datetime(tool.timestamp)?,
+        success: tool.status == "success",
+        retry_count: u32::try_from(tool.retry_count)
+            .map_err(|_| conversion("negative retry_count"))?,
+        metadata: tool.metadata,
+        properties: Properties::new(),
+    })
+}
+
+/// Convert internal `AgentNode` to protobuf
+///
+/// The agent's model travels in the metadata map under `"model"`.
+pub fn agent_node_to_proto(agent: AgentNode) -> proto::AgentNode {
+    let mut metadata = std::collections::HashMap::new();
+    metadata.insert(AGENT_MODEL_KEY.to_string(), agent.model);
+    proto::AgentNode {
+        id: agent.id.to_string(),
+        name: agent.name,
+        role: agent.role,
+        capabilities: agent.capabilities,
+        status: agent_status_to_proto(&agent.status),
+        created_at: Some(datetime_to_proto(agent.created_at)),
+        metadata,
+    }
+}
+
+/// Convert protobuf `AgentNode` to internal
+///
+/// `node_id` is the ID of the enclosing graph node.
+pub fn proto_to_agent_node(node_id: NodeId, agent: proto::AgentNode) -> Result<AgentNode> {
+    let created_at = optional_proto_to_datetime(agent.created_at)?;
+    let mut metadata = agent.metadata;
+    Ok(AgentNode {
+        id: AgentId::from_uuid(parse_uuid(&agent.id)?),
+        node_id,
+        name: agent.name,
+        role: agent.role,
+        capabilities: agent.capabilities,
+        model: metadata.remove(AGENT_MODEL_KEY).unwrap_or_default(),
+        created_at,
+        last_active: created_at,
+        status: proto_to_agent_status(&agent.status)?,
+        config: llm_memory_graph_types::AgentConfig::default(),
+        metrics: llm_memory_graph_types::AgentMetrics::default(),
+        tags: Vec::new(),
+        properties: Properties::new(),
+    })
+}
+
+/// Convert internal `PromptTemplate` to protobuf
+pub fn template_to_proto(template: PromptTemplate) -> proto::TemplateNode {
+    proto::TemplateNode {
+        id: template.id.to_string(),
+        name: template.name,
+        template_text: template.template,
+        variables: template
+            .variables
+            .into_iter()
+            .map(variable_spec_to_proto)
+            .collect(),
+        version: template.version.to_string(),
+        usage_count: to_i64(template.usage_count),
+        created_at: Some(datetime_to_proto(template.created_at)),
+        metadata: template.metadata,
+    }
+}
+
+/// Convert protobuf `TemplateNode` to internal
+///
+/// `node_id` is the ID of the enclosing graph node.
+pub fn proto_to_template(node_id: NodeId, template: proto::TemplateNode) -> Result<PromptTemplate> {
+    let created_at = optional_proto_to_datetime(template.created_at)?;
+    Ok(PromptTemplate {
+        id: TemplateId::from_uuid(parse_uuid(&template.id)?),
+        node_id,
+        version: template.version.parse().map_err(conversion)?,
+        name: template.name,
+        description: String::new(),
+        template: template.template_text,
+        variables: template
+            .variables
+            .into_iter()
+            .map(proto_to_variable_spec)
+            .collect(),
+        parent_id: None,
+        created_at,
+        updated_at: created_at,
+        author: String::new(),
+        usage_count: from_i64("usage_count", template.usage_count)?,
+        tags: Vec::new(),
+        metadata: template.metadata,
+        properties: Properties::new(),
+    })
+}
+
+/// Convert an internal `Node` to protobuf
+///
+/// # Errors
+/// Session and custom nodes have no payload in the wire format and cannot be
+/// converted.
+pub fn node_to_proto(node: Node) -> Result<proto::Node> {
+    use proto::node::NodeData;
+
+    let (id, node_type, created_at, data) = match node {
+        Node::Prompt(prompt) => (
+            prompt.id,
+            proto::NodeType::Prompt,
+            prompt.timestamp,
+            NodeData::Prompt(prompt_node_to_proto(prompt)),
+        ),
+        Node::Response(response) => (
+            response.id,
+            proto::NodeType::Response,
+            response.timestamp,
+            NodeData::Response(response_node_to_proto(response)),
+        ),
+        Node::ToolInvocation(tool) => (
+            tool.id,
+            proto::NodeType::ToolInvocation,
+            tool.timestamp,
+            NodeData::ToolInvocation(tool_invocation_to_proto(tool)),
+        ),
+        Node::Agent(agent) => (
+            agent.node_id,
+            proto::NodeType::Agent,
+            agent.created_at,
+            NodeData::Agent(agent_node_to_proto(agent)),
+        ),
+        Node::Template(template) => (
+            template.node_id,
+            proto::NodeType::Template,
+            template.created_at,
+            NodeData::Template(template_to_proto(template)),
+        ),
+        Node::Session(session) => {
+            return Err(conversion(format!(
+                "session node {} has no protobuf representation",
+                session.id
+            )))
+        }
+        Node::Custom(custom) => {
+            return Err(conversion(format!(
+                "custom node {} ({}) has no protobuf representation",
+                custom.id, custom.type_name
+            )))
+        }
+    };
+
+    Ok(proto::Node {
+        id: id.to_string(),
+        r#type: node_type.into(),
+        created_at: Some(datetime_to_proto(created_at)),
+        node_data: Some(data),
+    })
+}
+
+/// Convert a protobuf `Node` to internal
+pub fn proto_to_node(node: proto::Node) -> Result<Node> {
+    use proto::node::NodeData;
+
+    match node.node_data {
+        Some(NodeData::Prompt(prompt)) => proto_to_prompt_node(prompt).map(Node::Prompt),
+        Some(NodeData::Response(response)) => proto_to_response_node(response).map(Node::Response),
+        Some(NodeData::ToolInvocation(tool)) => {
+            proto_to_tool_invocation(tool).map(Node::ToolInvocation)
+        }
+        Some(NodeData::Agent(agent)) => {
+            proto_to_agent_node(parse_node_id(&node.id)?, agent).map(Node::Agent)
+        }
+        Some(NodeData::Template(template)) => {
+            proto_to_template(parse_node_id(&node.id)?, template).map(Node::Template)
+        }
+        None => Err(conversion(format!("node {} has no payload", node.id))),
+    }
+}
+
+// ============================================================================
+// Sessions and edges
+// ============================================================================
+
+/// Convert an internal `ConversationSession` to protobuf
+pub fn session_to_proto(session: ConversationSession) -> proto::Session {
+    proto::Session {
+        id: session.id.to_string(),
+        created_at: Some(datetime_to_proto(session.created_at)),
+        updated_at: Some(datetime_to_proto(session.updated_at)),
+        metadata: session.metadata,
+        is_active: session.status.is_open(),
+    }
+}
+
+/// Convert an internal `EdgeType` to protobuf
+pub fn edge_type_to_proto(edge_type: &EdgeType) -> proto::EdgeType {
+    match edge_type {
+        EdgeType::PartOf => proto::EdgeType::BelongsTo,
+        EdgeType::RespondsTo => proto::EdgeType::RespondsTo,
+        EdgeType::Follows => proto::EdgeType::Follows,
+        EdgeType::Invokes => proto::EdgeType::Invokes,
+        EdgeType::HandledBy => proto::EdgeType::HandledBy,
+        EdgeType::Instantiates => proto::EdgeType::Instantiates,
+        EdgeType::Inherits => proto::EdgeType::Inherits,
+        EdgeType::TransfersTo => proto::EdgeType::TransfersTo,
+        EdgeType::References => proto::EdgeType::References,
+        EdgeType::MentionedIn => proto::EdgeType::MentionedIn,
+        EdgeType::Supersedes => proto::EdgeType::Supersedes,
+        EdgeType::PreviousVersionOf => proto::EdgeType::PreviousVersionOf,
+    }
+}
+
+/// Convert a protobuf edge type to internal
+pub fn proto_to_edge_type(edge_type: i32) -> Result<EdgeType> {
+    match proto::EdgeType::try_from(edge_type) {
+        Ok(proto::EdgeType::BelongsTo) => Ok(EdgeType::PartOf),
+        Ok(proto::EdgeType::RespondsTo) => Ok(EdgeType::RespondsTo),
+        Ok(proto::EdgeType::Follows) => Ok(EdgeType::Follows),
+        Ok(proto::EdgeType::Invokes) => Ok(EdgeType::Invokes),
+        Ok(proto::EdgeType::HandledBy) => Ok(EdgeType::HandledBy),
+        Ok(proto::EdgeType::Instantiates) => Ok(EdgeType::Instantiates),
+        Ok(proto::EdgeType::Inherits) => Ok(EdgeType::Inherits),
+        Ok(proto::EdgeType::TransfersTo) => Ok(EdgeType::TransfersTo),
+        Ok(proto::EdgeType::References) => Ok(EdgeType::References),
+        Ok(proto::EdgeType::MentionedIn) => Ok(EdgeType::MentionedIn),
+        Ok(proto::EdgeType::Supersedes) => Ok(EdgeType::Supersedes),
+        Ok(proto::EdgeType::PreviousVersionOf) => Ok(EdgeType::PreviousVersionOf),
+        _ => Err(conversion(format!("invalid edge type: {}", edge_type))),
+    }
+}
+
+/// Convert an internal `Edge` to protobuf
+///
+/// Typed attributes are not carried by the wire format.
+pub fn edge_to_proto(edge: Edge) -> proto::Edge {
+    proto::Edge {
+        id: edge.id.to_string(),
+        from_node_id: edge.from.to_string(),
+        to_node_id: edge.to.to_string(),
+        r#type: edge_type_to_proto(&edge.edge_type).into(),
+        created_at: Some(datetime_to_proto(edge.created_at)),
+        properties: edge.properties,
+    }
+}
+
+/// Convert a protobuf `Edge` to internal
+pub fn proto_to_edge(edge: proto::Edge) -> Result<Edge> {
+    Ok(Edge {
+        id: EdgeId::from_bytes(*parse_uuid(&edge.id)?.as_bytes()),
+        from: parse_node_id(&edge.from_node_id)?,
+        to: parse_node_id(&edge.to_node_id)?,
+        edge_type: proto_to_edge_type(edge.r#type)?,
+        created_at: optional_proto_to_datetime(edge.created_at)?,
+        properties: edge.properties,
+        attributes: Properties::new(),
+    })
+}
+
+// ============================================================================
+// Aliases
+// ============================================================================
+
+/// Convert an alias target to the target of a set-alias request
+pub fn alias_target_to_proto(target: AliasTarget) -> proto::set_alias_request::Target {
+    match target {
+        AliasTarget::Session(id) => proto::set_alias_request::Target::SessionId(id.to_string()),
+        AliasTarget::Node(id) => proto::set_alias_request::Target::NodeId(id.to_string()),
+    }
+}
+
+/// Convert a protobuf alias to its key and target
+pub fn proto_to_alias(alias: proto::Alias) -> Result<(String, AliasTarget)> {
+    let target = match alias.target {
+        Some(proto::alias::Target::SessionId(id)) => AliasTarget::Session(parse_session_id(&id)?),
+        Some(proto::alias::Target::NodeId(id)) => AliasTarget::Node(parse_node_id(&id)?),
+        None => return Err(conversion(format!("alias {} has no target", alias.alias))),
+    };
+    Ok((alias.alias, target))
+}
+
+// ============================================================================
+// Imports
+// ============================================================================
+
+/// Wrap `node` in a record for
+/// [`MemoryGraphClient::import`](crate::MemoryGraphClient::import)
+///
+/// Unlike [`node_to_proto`], the record carries every field of the node.
+pub fn node_to_import_record(node: &Node) -> Result<proto::ImportRecord> {
+    Ok(proto::ImportRecord {
+        json: format!("{{\"node\":{}}}", serde_json::to_string(node)?),
+    })
+}
+
+/// Wrap `edge` in a record for
+/// [`MemoryGraphClient::import`](crate::MemoryGraphClient::import)
+pub fn edge_to_import_record(edge: &Edge) -> Result<proto::ImportRecord> {
+    Ok(proto::ImportRecord {
+        json: format!("{{\"edge\":{}}}", serde_json::to_string(edge)?),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(node: Node) -> Node {
+        proto_to_node(node_to_proto(node).unwrap()).unwrap()
+    }
+
+    fn assert_same(a: &Node, b: &Node) {
+        assert_eq!(
+            serde_json::to_value(a).unwrap(),
+            serde_json::to_value(b).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_timestamp_round_trip() {
+        let now = Utc::now();
+        assert_eq!(proto_to_datetime(&datetime_to_proto(now)).unwrap(), now);
+        assert!(optional_proto_to_datetime(None).is_err());
+        assert!(proto_to_datetime(&Timestamp {
+            seconds: 0,
+            nanos: -1
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn test_prompt_round_trip() {
+        let metadata = PromptMetadata {
+            model: "gpt-4".to_string(),
+            temperature: 0.25,
+            max_tokens: Some(512),
+            tools_available: vec!["search".to_string()],
+            custom: [("team".to_string(), "infra".to_string())].into(),
+        };
+        let prompt = Node::Prompt(PromptNode::with_metadata(
+            SessionId::new(),
+            "Hello".to_string(),
+            metadata,
+        ));
+        assert_same(&prompt, &round_trip(prompt.clone()));
+    }
+
+    #[test]
+    fn test_response_round_trip() {
+        let response = Node::Response(ResponseNode::new(
+            NodeId::new(),
+            "Hi there".to_string(),
+            TokenUsage::new(12, 34),
+        ));
+        assert_same(&response, &round_trip(response.clone()));
+    }
+
+    #[test]
+    fn test_tool_invocation_round_trip() {
+        let mut tool = ToolInvocation::new(
+            NodeId::new(),
+            "calculator".to_string(),
+            serde_json::json!({"op": "add", "args": [1, 2]}),
+        );
+        tool.mark_success(serde_json::json!({"value": 3}), 42);
+        tool.add_metadata("host".to_string(), "worker-1".to_string());
+        let tool = Node::ToolInvocation(tool);
+        assert_same(&tool, &round_trip(tool.clone()));
+
+        let mut failed =
+            ToolInvocation::new(NodeId::new(), "http".to_string(), serde_json::json!({}));
+        failed.mark_failed("timeout".to_string(), 5000);
+        let failed = Node::ToolInvocation(failed);
+        assert_same(&failed, &round_trip(failed.clone()));
+    }
+
+    #[test]
+    fn test_agent_round_trip_preserves_wire_fields() {
+        let mut agent = AgentNode::new(
+            "researcher".to_string(),
+            "research".to_string(),
+            vec!["search".to_string(), "summarize".to_string()],
+        );
+        agent.model = "claude-3".to_string();
+        agent.set_status(AgentStatus::Busy);
+
+        let Node::Agent(back) = round_trip(Node::Agent(agent.clone())) else {
+            panic!("expected agent node");
+        };
+        assert_eq!(back.id, agent.id);
+        assert_eq!(back.node_id, agent.node_id);
+        assert_eq!(back.name, agent.name);
+        assert_eq!(back.role, agent.role);
+        assert_eq!(back.capabilities, agent.capabilities);
+        assert_eq!(back.model, agent.model);
+        assert_eq!(back.status, AgentStatus::Busy);
+        assert_eq!(back.created_at, agent.created_at);
+    }
+
+    #[test]
+    fn test_template_round_trip_preserves_wire_fields() {
+        let variables = vec![VariableSpec::new(
+            "topic".to_string(),
+            "String".to_string(),
+            true,
+            "Topic to explain".to_string(),
+        )];
+        let mut template = PromptTemplate::new(
+            "explain".to_string(),
+            "Explain {{topic}}".to_string(),
+            variables,
+        );
+        template.usage_count = 7;
+        template
+            .metadata
+            .insert("owner".to_string(), "docs".to_string());
+
+        let Node::Template(back) = round_trip(Node::Template(template.clone())) else {
+            panic!("expected template node");
+        };
+        assert_eq!(back.id, template.id);
+        assert_eq!(back.node_id, template.node_id);
+        assert_eq!(back.name, template.name);
+        assert_eq!(back.template, template.template);
+        assert_eq!(back.version, template.version);
+        assert_eq!(back.usage_count, 7);
+        assert_eq!(back.metadata, template.metadata);
+        assert_eq!(back.variables.len(), 1);
+        assert_eq!(back.variables[0].name, "topic");
+        assert!(back.variables[0].required);
+    }
+
+    #[test]
+    fn test_invalid_messages_are_rejected() {
+        let session = Node::Session(llm_memory_graph_types::ConversationSession::new());
+        assert!(matches!(
+            node_to_proto(session),
+            Err(ClientError::Conversion(_))
+        ));
+
+        let empty = proto::Node {
+            id: NodeId::new().to_string(),
+            ..Default::default()
+        };
+        assert!(matches!(
+            proto_to_node(empty),
+            Err(ClientError::Conversion(_))
+        ));
+
+        let prompt = proto::Node {
+            node_data: Some(proto::node::NodeData::Prompt(proto::PromptNode {
+                id: "not-a-uuid".to_string(),
+                ..Default::default()
+            })),
+            ..Default::default()
+        };
+        assert!(matches!(
+            proto_to_node(prompt),
+            Err(ClientError::Conversion(_))
+        ));
+    }
+
+    #[test]
+    fn test_edge_round_trip() {
+        let mut edge = Edge::new(NodeId::new(), NodeId::new(), EdgeType::PartOf);
+        edge.properties
+            .insert("weight".to_string(), "1".to_string());
+        let proto_edge = edge_to_proto(edge.clone());
+        assert_eq!(proto_edge.r#type, proto::EdgeType::BelongsTo as i32);
+        assert_eq!(
+            serde_json::to_value(proto_to_edge(proto_edge).unwrap()).unwrap(),
+            serde_json::to_value(&edge).unwrap()
+        );
+        assert!(proto_to_edge_type(0).is_err());
+
+        let session = ConversationSession::new();
+        let proto_session = session_to_proto(session.clone());
+        assert_eq!(proto_session.id, session.id.to_string());
+        assert!(proto_session.is_active);
+    }
+
+    #[test]
+    fn test_alias_conversion() {
+        let target = AliasTarget::Session(SessionId::new());
+        let proto::set_alias_request::Target::SessionId(id) = alias_target_to_proto(target) else {
+            panic!("expected a session target");
+        };
+        let alias = proto::Alias {
+            alias: "ext:conv-42".to_string(),
+            target: Some(proto::alias::Target::SessionId(id)),
+        };
+        assert_eq!(
+            proto_to_alias(alias).unwrap(),
+            ("ext:conv-42".to_string(), target)
+        );
+
+        let empty = proto::Alias {
+            alias: "ext:conv-42".to_string(),
+            target: None,
+        };
+        assert!(matches!(
+            proto_to_alias(empty),
+            Err(ClientError::Conversion(_))
+        ));
+    }
+
+    #[test]
+    fn test_import_records() {
+        let session = llm_memory_graph_types::ConversationSession::new();
+        let record = node_to_import_record(&Node::Session(session.clone())).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&record.json).unwrap();
+        assert_eq!(
+            value["node"]["Session"]["id"],
+            serde_json::json!(session.id.to_string())
+        );
+
+        let edge = Edge::new(
+            session.node_id,
+            NodeId::new(),
+            llm_memory_graph_types::EdgeType::Follows,
+        );
+        let record = edge_to_import_record(&edge).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&record.json).unwrap();
+        assert_eq!(value["edge"]["id"], serde_json::json!(edge.id.to_string()));
+    }
+}
@@ -1,5 +1,6 @@
 //! Error types for the client
 
+use llm_memory_graph_types::ErrorCode;
 use thiserror::Error;
 
 /// Client error types
@@ -21,6 +22,10 @@ pub enum ClientError {
     #[error("Serialization error: {0}")]
     Serialization(String),
 
+    /// Message could not be converted between protobuf and internal types
+    #[error("Conversion error: {0}")]
+    Conversion(String),
+
     /// Invalid argument
     #[error("Invalid argument: {0}")]
     InvalidArgument(String),
@@ -33,6 +38,10 @@ pub enum ClientError {
     #[error("Resource already exists: {0}")]
     AlreadyExists(String),
 
+    /// The offline queue could not be read or written, or is full
+    #[error("Offline queue error: {0}")]
+    Queue(String),
+
     /// Internal error
     #[error("Internal error: {0}")]
     Internal(String),
@@ -45,6 +54,23 @@ pub enum ClientError {
 /// Result type for client operations
 pub type Result<T> = std::result::Result<T, ClientError>;
 
+impl ClientError {
+    /// Stable category of the error, decoded from the gRPC status for
+    /// errors returned by the server
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            ClientError::Connection(_) | ClientError::Transport(_) => ErrorCode::Unavailable,
+            ClientError::Status(status) => ErrorCode::from_grpc_code(status.code() as i32),
+            ClientError::Serialization(_) | ClientError::Conversion(_) => ErrorCode::Serialization,
+            ClientError::InvalidArgument(_) => ErrorCode::InvalidInput,
+            ClientError::NotFound(_) => ErrorCode::NotFound,
+            ClientError::AlreadyExists(_) => ErrorCode::Conflict,
+            ClientError::Queue(_) => ErrorCode::Storage,
+            ClientError::Internal(_) | ClientError::Other(_) => ErrorCode::Internal,
+        }
+    }
+}
+
 impl From<serde_json::Error> for ClientError {
     fn from(err: serde_json::Error) -> Self {
         ClientError::Serialization(err.to_string())
@@ -0,0 +1,145 @@
+//! Typed graph events received from the event stream
+
+use crate::client::proto;
+use crate::convert::optional_proto_to_datetime;
+use crate::error::{ClientError, Result};
+use chrono::{DateTime, Utc};
+
+pub use proto::EventType;
+
+/// Selects which events [`subscribe_events`](crate::MemoryGraphClient::subscribe_events) delivers
+///
+/// An empty filter delivers every event of every session.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EventFilter {
+    /// Only deliver events for this session
+    pub session_id: Option<String>,
+    /// Only deliver these event types (empty = all types)
+    pub event_types: Vec<EventType>,
+}
+
+impl EventFilter {
+    /// Create a filter that matches every event
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Restrict the filter to one session
+    #[must_use]
+    pub fn session(mut self, session_id: impl Into<String>) -> Self {
+        self.session_id = Some(session_id.into());
+        self
+    }
+
+    /// Add an event type to deliver
+    #[must_use]
+    pub fn event_type(mut self, event_type: EventType) -> Self {
+        self.event_types.push(event_type);
+        self
+    }
+
+    pub(crate) fn into_request(self) -> proto::StreamEventsRequest {
+        proto::StreamEventsRequest {
+            session_id: self.session_id,
+            event_types: self.event_types.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+/// An event emitted by the graph
+#[derive(Debug, Clone, PartialEq)]
+pub struct GraphEvent {
+    /// Event ID
+    pub id: String,
+    /// Kind of event
+    pub event_type: EventType,
+    /// When the event occurred
+    pub timestamp: DateTime<Utc>,
+    /// Event payload
+    pub payload: serde_json::Value,
+}
+
+impl TryFrom<proto::Event> for GraphEvent {
+    type Error = ClientError;
+
+    fn try_from(event: proto::Event) -> Result<Self> {
+        let event_type = EventType::try_from(event.r#type).map_err(|_| {
+            ClientError::Conversion(format!("unknown event type: {}", event.r#type))
+        })?;
+        let payload = if event.payload.is_empty() {
+            serde_json::Value::Null
+        } else {
+            serde_json::from_str(&event.payload).map_err(|e| {
+                ClientError::Conversion(format!("invalid event payload for {}: {}", event.id, e))
+            })?
+        };
+
+        Ok(Self {
+            id: event.id,
+            event_type,
+            timestamp: optional_proto_to_datetime(event.timestamp)?,
+            payload,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::convert::datetime_to_proto;
+
+    #[test]
+    fn test_filter_builds_request() {
+        let request = EventFilter::new()
+            .session("s-1")
+            .event_type(EventType::NodeCreated)
+            .event_type(EventType::SessionClosed)
+            .into_request();
+
+        assert_eq!(request.session_id.as_deref(), Some("s-1"));
+        assert_eq!(
+            request.event_types,
+            vec![
+                EventType::NodeCreated as i32,
+                EventType::SessionClosed as i32
+            ]
+        );
+        assert!(EventFilter::new().into_request().event_types.is_empty());
+    }
+
+    #[test]
+    fn test_event_conversion() {
+        let now = Utc::now();
+        let event = GraphEvent::try_from(proto::Event {
+            id: "e-1".to_string(),
+            r#type: EventType::NodeCreated.into(),
+            timestamp: Some(datetime_to_proto(now)),
+            payload: r#"{"node_id":"n-1"}"#.to_string(),
+        })
+        .unwrap();
+
+        assert_eq!(event.event_type, EventType::NodeCreated);
+        assert_eq!(event.timestamp, now);
+        assert_eq!(event.payload["node_id"], "n-1");
+
+        let bad_payload = proto::Event {
+            payload: "{not json".to_string(),
+            timestamp: Some(datetime_to_proto(now)),
+            ..Default::default()
+        };
+        assert!(matches!(
+            GraphEvent::try_from(bad_payload),
+            Err(ClientError::Conversion(_))
+        ));
+
+        let bad_type = proto::Event {
+            r#type: 99,
+            timestamp: Some(datetime_to_proto(now)),
+            ..Default::default()
+        };
+        assert!(matches!(
+            GraphEvent::try_from(bad_type),
+            Err(ClientError::Conversion(_))
+        ));
+    }
+}
@@ -8,28 +8,35 @@
 //! - **Async/await**: Full async support with tokio
 //! - **Type-safe**: Strongly typed API using llm-memory-graph-types
 //! - **Streaming**: Support for streaming queries and events
-//! - **Connection pooling**: Efficient connection management
+//! - **Connection pooling**: Round-robin over multiple channels with automatic reconnection
+//! - **Resilience**: Per-call deadlines and jittered retries for idempotent RPCs
+//! - **Caching**: Optional local cache of sessions and nodes, invalidated by the event stream
+//! - **Offline mode**: Optional file-backed queue of writes, replayed once the server is reachable
 //! - **Error handling**: Comprehensive error types
 //!
 //! # Example
 //!
 //! ```no_run
 //! use llm_memory_graph_client::MemoryGraphClient;
-//! use llm_memory_graph_types::{PromptNode, PromptMetadata};
+//! use std::time::Duration;
 //!
 //! #[tokio::main]
 //! async fn main() -> Result<(), Box<dyn std::error::Error>> {
-//!     let client = MemoryGraphClient::connect("http://localhost:50051").await?;
+//!     let client = MemoryGraphClient::builder()
+//!         .address("http://localhost:50051")
+//!         .deadline(Duration::from_secs(5))
+//!         .max_attempts(4)
+//!         .pool_size(4)
+//!         .build()
+//!         .await?;
 //!
 //!     // Create a session
-//!     let session = client.create_session(Default::default()).await?;
+//!     let session_id = client.create_session(Default::default()).await?;
 //!
 //!     // Add a prompt
-//!     let prompt = client.add_prompt(
-//!         session.id.clone(),
-//!         "What is the capital of France?".to_string(),
-//!         PromptMetadata::default()
-//!     ).await?;
+//!     let prompt = client
+//!         .add_prompt(session_id, "What is the capital of France?".to_string(), None)
+//!         .await?;
 //!
 //!     Ok(())
 //! }
@@ -42,13 +49,26 @@
 #![allow(clippy::module_name_repetitions)]
 #![allow(clippy::missing_errors_doc)]
 #![allow(clippy::missing_panics_doc)]
+#![allow(clippy::must_use_candidate)]
+#![allow(clippy::result_large_err)]
+#![allow(clippy::return_self_not_must_use)]
+#![allow(clippy::uninlined_format_args)]
 
+pub mod cache;
 pub mod client;
+pub mod config;
+pub mod convert;
 pub mod error;
+pub mod events;
+pub mod queue;
 
 // Re-export main types
-pub use client::MemoryGraphClient;
+pub use cache::{CacheConfig, CacheStats};
+pub use client::{ClientBuilder, MemoryGraphClient, Page};
+pub use config::{ClientConfig, RetryConfig};
 pub use error::{ClientError, Result};
+pub use events::{EventFilter, EventType, GraphEvent};
+pub use queue::{Delivery, OfflineQueueConfig, QueueStats, QueuedWrite};
 
 // Re-export types from llm-memory-graph-types
 pub use llm_memory_graph_types::*;
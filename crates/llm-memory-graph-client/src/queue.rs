@@ -0,0 +1,450 @@
+//! File-backed queue of writes made while the server is unreachable
+//!
+//! Agents at the edge lose connectivity for minutes or hours at a time. A
+//! client built with [`ClientBuilder::offline_queue`](crate::ClientBuilder::offline_queue)
+//! keeps working through an outage: [`add_prompt_or_queue`] and
+//! [`add_response_or_queue`] append a write they cannot deliver to a log on
+//! disk and return [`Delivery::Queued`], and a background task replays the
+//! log in order once the server answers again.
+//!
+//! Every queued write carries the idempotency key it was first sent with, so
+//! a write the server applied before the connection dropped, or one replayed
+//! again after the client crashed mid-replay, is applied only once. Writes
+//! the server rejects outright (for instance because their session was
+//! deleted meanwhile) are dropped with a warning rather than blocking the
+//! queue, and counted in [`QueueStats::rejected`].
+//!
+//! Once a write is queued, later writes are queued behind it even if the
+//! server is back, so the server sees writes in the order they were made.
+//! Since a queued prompt has no ID yet, a response to it can only be added
+//! after the queue has delivered it.
+//!
+//! [`add_prompt_or_queue`]: crate::MemoryGraphClient::add_prompt_or_queue
+//! [`add_response_or_queue`]: crate::MemoryGraphClient::add_response_or_queue
+
+use crate::client::{proto, MemoryGraphClient};
+use crate::config::RetryConfig;
+use crate::error::{ClientError, Result};
+use prost::Message;
+use std::collections::VecDeque;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock, Weak};
+use tokio::sync::Notify;
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+/// Log of queued writes, in the queue directory
+const LOG_FILE: &str = "writes.log";
+
+/// Number of writes at the head of the log already delivered
+const POSITION_FILE: &str = "writes.pos";
+
+/// Where queued writes are kept and how many may pile up
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OfflineQueueConfig {
+    /// Directory holding the queue files, created if missing
+    pub dir: PathBuf,
+    /// Most writes queued at once; further writes fail while the queue is full
+    pub max_depth: usize,
+}
+
+impl OfflineQueueConfig {
+    /// Queue writes in `dir`
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            max_depth: 100_000,
+        }
+    }
+
+    /// Set the most writes queued at once
+    #[must_use]
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+}
+
+/// What happened to a write made through the offline queue
+#[derive(Debug, Clone, PartialEq)]
+pub enum Delivery<T> {
+    /// The server applied the write and returned this
+    Sent(T),
+    /// The write was queued and will be replayed when the server is reachable
+    Queued,
+}
+
+impl<T> Delivery<T> {
+    /// The server's reply, if the write was delivered right away
+    pub fn sent(self) -> Option<T> {
+        match self {
+            Self::Sent(reply) => Some(reply),
+            Self::Queued => None,
+        }
+    }
+}
+
+/// A write that can wait in the offline queue
+#[derive(Clone, PartialEq, prost::Oneof)]
+pub enum QueuedWrite {
+    /// Add a prompt to a session
+    #[prost(message, tag = "1")]
+    AddPrompt(proto::AddPromptRequest),
+    /// Add a response to a prompt
+    #[prost(message, tag = "2")]
+    AddResponse(proto::AddResponseRequest),
+}
+
+/// One entry of the queue log
+#[derive(Clone, PartialEq, prost::Message)]
+struct QueueRecord {
+    #[prost(oneof = "QueuedWrite", tags = "1, 2")]
+    write: Option<QueuedWrite>,
+}
+
+/// Counters of a client's offline queue
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct QueueStats {
+    /// Writes waiting to be replayed
+    pub depth: usize,
+    /// Writes queued since the client was built
+    pub enqueued: u64,
+    /// Queued writes the server has since applied
+    pub replayed: u64,
+    /// Queued writes the server refused and that were dropped
+    pub rejected: u64,
+}
+
+/// Whether `err` means the server could not be reached, rather than that it
+/// refused the call
+pub(crate) fn is_offline(err: &ClientError, retry: &RetryConfig) -> bool {
+    match err {
+        ClientError::Connection(_) | ClientError::Transport(_) => true,
+        ClientError::Status(status) => retry.is_retryable(status.code()),
+        _ => false,
+    }
+}
+
+/// Writes waiting for the server, shared by the clones of one client
+pub(crate) struct OfflineQueue {
+    dir: PathBuf,
+    max_depth: usize,
+    state: Mutex<QueueState>,
+    /// Held while replaying, so a write is never delivered by two replays
+    replaying: tokio::sync::Mutex<()>,
+    wake: Arc<Notify>,
+    enqueued: AtomicU64,
+    replayed: AtomicU64,
+    rejected: AtomicU64,
+    task: OnceLock<JoinHandle<()>>,
+}
+
+struct QueueState {
+    log: File,
+    pending: VecDeque<QueuedWrite>,
+    /// Records at the head of the log that have been delivered
+    delivered: u64,
+}
+
+impl OfflineQueue {
+    /// Open the queue in `config.dir`, picking up writes left by an earlier run
+    pub(crate) fn open(config: &OfflineQueueConfig) -> Result<Self> {
+        fs::create_dir_all(&config.dir).map_err(|e| queue_error(&config.dir, &e))?;
+        let log_path = config.dir.join(LOG_FILE);
+        let bytes = match fs::read(&log_path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(queue_error(&log_path, &e)),
+        };
+
+        let mut pending = VecDeque::new();
+        let mut buf = bytes.as_slice();
+        let mut valid_len = 0;
+        while !buf.is_empty() {
+            match QueueRecord::decode_length_delimited(&mut buf) {
+                Ok(QueueRecord { write: Some(write) }) => pending.push_back(write),
+                Ok(QueueRecord { write: None }) => {}
+                Err(e) => {
+                    // A crash while appending leaves a partial record at the
+                    // end; the write it held was never acknowledged
+                    warn!("Discarding truncated offline queue record: {}", e);
+                    break;
+                }
+            }
+            valid_len = bytes.len() - buf.len();
+        }
+
+        let position_path = config.dir.join(POSITION_FILE);
+        let delivered = fs::read_to_string(&position_path)
+            .ok()
+            .and_then(|s| s.trim().parse::<u64>().ok())
+            .unwrap_or(0);
+        let skip = usize::try_from(delivered)
+            .unwrap_or(usize::MAX)
+            .min(pending.len());
+        pending.drain(..skip);
+
+        let log = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&log_path)
+            .map_err(|e| queue_error(&log_path, &e))?;
+        log.set_len(valid_len as u64)
+            .map_err(|e| queue_error(&log_path, &e))?;
+        if !pending.is_empty() {
+            info!(
+                "Offline queue in {} holds {} writes to replay",
+                config.dir.display(),
+                pending.len()
+            );
+        }
+
+        let queue = Self {
+            dir: config.dir.clone(),
+            max_depth: config.max_depth,
+            state: Mutex::new(QueueState {
+                log,
+                pending,
+                delivered: skip as u64,
+            }),
+            replaying: tokio::sync::Mutex::new(()),
+            wake: Arc::new(Notify::new()),
+            enqueued: AtomicU64::new(0),
+            replayed: AtomicU64::new(0),
+            rejected: AtomicU64::new(0),
+            task: OnceLock::new(),
+        };
+        queue.compact(&mut queue.state.lock().unwrap())?;
+        Ok(queue)
+    }
+
+    /// Start the task that replays queued writes
+    ///
+    /// `client` must not itself hold the queue; the task stops once the queue
+    /// is dropped.
+    pub(crate) fn spawn_replay(self: &Arc<Self>, client: MemoryGraphClient) {
+        let task = tokio::spawn(replay_queued(
+            client,
+            Arc::downgrade(self),
+            Arc::clone(&self.wake),
+        ));
+        if self.task.set(task).is_err() {
+            warn!("Offline queue replay task started twice");
+        }
+    }
+
+    pub(crate) fn depth(&self) -> usize {
+        self.state.lock().unwrap().pending.len()
+    }
+
+    /// Append `write` to the log
+    pub(crate) fn push(&self, write: QueuedWrite) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        if state.pending.len() >= self.max_depth {
+            return Err(ClientError::Queue(format!(
+                "offline queue is full ({} writes)",
+                self.max_depth
+            )));
+        }
+
+        let record = QueueRecord {
+            write: Some(write.clone()),
+        };
+        let log_path = self.dir.join(LOG_FILE);
+        state
+            .log
+            .write_all(&record.encode_length_delimited_to_vec())
+            .and_then(|()| state.log.sync_data())
+            .map_err(|e| queue_error(&log_path, &e))?;
+        state.pending.push_back(write);
+        drop(state);
+
+        self.enqueued.fetch_add(1, Ordering::Relaxed);
+        self.wake.notify_one();
+        Ok(())
+    }
+
+    fn front(&self) -> Option<QueuedWrite> {
+        self.state.lock().unwrap().pending.front().cloned()
+    }
+
+    /// Remove the write at the head of the queue once it has been handled
+    fn pop(&self) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        if state.pending.pop_front().is_some() {
+            state.delivered += 1;
+        }
+        self.compact(&mut state)
+    }
+
+    /// Record how far the log has been delivered, emptying it once all of it
+    /// has
+    fn compact(&self, state: &mut QueueState) -> Result<()> {
+        let position_path = self.dir.join(POSITION_FILE);
+        if state.pending.is_empty() {
+            let log_path = self.dir.join(LOG_FILE);
+            state
+                .log
+                .set_len(0)
+                .and_then(|()| state.log.sync_data())
+                .map_err(|e| queue_error(&log_path, &e))?;
+            state.delivered = 0;
+            return match fs::remove_file(&position_path) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                    Err(queue_error(&position_path, &e))
+                }
+                _ => Ok(()),
+            };
+        }
+
+        let temp_path = position_path.with_extension("tmp");
+        fs::write(&temp_path, state.delivered.to_string())
+            .and_then(|()| fs::rename(&temp_path, &position_path))
+            .map_err(|e| queue_error(&position_path, &e))
+    }
+
+    /// Deliver queued writes in order until the queue is empty
+    ///
+    /// Stops at the first write that fails because the server is
+    /// unreachable, returning that error.
+    pub(crate) async fn replay(&self, client: &MemoryGraphClient) -> Result<()> {
+        let _replaying = self.replaying.lock().await;
+        while let Some(write) = self.front() {
+            match client.send_queued(write).await {
+                Ok(()) => {
+                    self.replayed.fetch_add(1, Ordering::Relaxed);
+                }
+                Err(e) if is_offline(&e, &client.retry_config()) => return Err(e),
+                Err(e) => {
+                    warn!("Dropping queued write refused by the server: {}", e);
+                    self.rejected.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+            self.pop()?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn stats(&self) -> QueueStats {
+        QueueStats {
+            depth: self.depth(),
+            enqueued: self.enqueued.load(Ordering::Relaxed),
+            replayed: self.replayed.load(Ordering::Relaxed),
+            rejected: self.rejected.load(Ordering::Relaxed),
+        }
+    }
+}
+
+impl Drop for OfflineQueue {
+    fn drop(&mut self) {
+        if let Some(task) = self.task.get() {
+            task.abort();
+        }
+    }
+}
+
+fn queue_error(path: &Path, err: &std::io::Error) -> ClientError {
+    ClientError::Queue(format!("{}: {}", path.display(), err))
+}
+
+/// Replay queued writes whenever there are some, backing off while the
+/// server stays unreachable
+async fn replay_queued(client: MemoryGraphClient, queue: Weak<OfflineQueue>, wake: Arc<Notify>) {
+    let retry = client.retry_config();
+    let mut failures = 0;
+    loop {
+        let Some(live) = queue.upgrade() else { return };
+        let result = live.replay(&client).await;
+        drop(live);
+
+        match result {
+            Ok(()) => {
+                failures = 0;
+                wake.notified().await;
+            }
+            Err(e) => {
+                failures += 1;
+                debug!("Offline queue replay paused: {}", e);
+                tokio::time::sleep(retry.backoff(failures)).await;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn prompt(content: &str) -> QueuedWrite {
+        QueuedWrite::AddPrompt(proto::AddPromptRequest {
+            session_id: "s-1".to_string(),
+            content: content.to_string(),
+            metadata: None,
+            idempotency_key: Some(content.to_string()),
+        })
+    }
+
+    #[test]
+    fn test_queue_survives_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = OfflineQueueConfig::new(dir.path()).max_depth(3);
+
+        let queue = OfflineQueue::open(&config).unwrap();
+        queue.push(prompt("a")).unwrap();
+        queue.push(prompt("b")).unwrap();
+        queue.push(prompt("c")).unwrap();
+        assert!(matches!(
+            queue.push(prompt("d")),
+            Err(ClientError::Queue(_))
+        ));
+        queue.pop().unwrap();
+        drop(queue);
+
+        // A partial record left by a crash mid-append is discarded
+        let mut log = OpenOptions::new()
+            .append(true)
+            .open(dir.path().join(LOG_FILE))
+            .unwrap();
+        log.write_all(&[0x7f, 0x0a]).unwrap();
+
+        let queue = OfflineQueue::open(&config).unwrap();
+        assert_eq!(queue.depth(), 2);
+        assert_eq!(queue.front(), Some(prompt("b")));
+        queue.push(prompt("d")).unwrap();
+        queue.pop().unwrap();
+        drop(queue);
+
+        let queue = OfflineQueue::open(&config).unwrap();
+        assert_eq!(queue.front(), Some(prompt("c")));
+        queue.pop().unwrap();
+        assert_eq!(queue.front(), Some(prompt("d")));
+        queue.pop().unwrap();
+        assert_eq!(queue.depth(), 0);
+        assert_eq!(
+            fs::metadata(dir.path().join(LOG_FILE)).unwrap().len(),
+            0,
+            "a drained queue empties its log"
+        );
+        assert!(!dir.path().join(POSITION_FILE).exists());
+    }
+
+    #[test]
+    fn test_is_offline() {
+        let retry = RetryConfig::default();
+        assert!(is_offline(
+            &ClientError::Status(tonic::Status::unavailable("down")),
+            &retry
+        ));
+        assert!(is_offline(
+            &ClientError::Connection("refused".into()),
+            &retry
+        ));
+        assert!(!is_offline(
+            &ClientError::Status(tonic::Status::invalid_argument("bad")),
+            &retry
+        ));
+    }
+}
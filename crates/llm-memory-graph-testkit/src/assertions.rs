@@ -0,0 +1,127 @@
+//! Assertions about the graph held by a [`MockServer`]
+//!
+//! The assertions panic with a description of what the graph holds instead,
+//! and report the caller's location like `assert!` does. Ids can be given as
+//! typed ids or as the strings the client returns.
+
+use crate::server::MockServer;
+use llm_memory_graph_types::{Edge, EdgeType, Node};
+
+/// Assert that an edge of `edge_type` goes from `from` to `to`, returning it
+#[track_caller]
+pub fn assert_edge_exists(
+    server: &MockServer,
+    from: impl ToString,
+    to: impl ToString,
+    edge_type: EdgeType,
+) -> Edge {
+    let (from, to) = (from.to_string(), to.to_string());
+    let edges = server.edges();
+    if let Some(edge) = edges.iter().find(|edge| {
+        edge.edge_type == edge_type && edge.from.to_string() == from && edge.to.to_string() == to
+    }) {
+        return edge.clone();
+    }
+
+    let touching: Vec<String> = edges
+        .iter()
+        .filter(|edge| edge.from.to_string() == from || edge.to.to_string() == to)
+        .map(describe)
+        .collect();
+    panic!(
+        "expected a {edge_type:?} edge {from} -> {to}; edges from {from} or to {to}: {touching:#?}"
+    );
+}
+
+/// Assert that no edge of `edge_type` goes from `from` to `to`
+#[track_caller]
+pub fn assert_no_edge(
+    server: &MockServer,
+    from: impl ToString,
+    to: impl ToString,
+    edge_type: EdgeType,
+) {
+    let (from, to) = (from.to_string(), to.to_string());
+    if let Some(edge) = server.edges().iter().find(|edge| {
+        edge.edge_type == edge_type && edge.from.to_string() == from && edge.to.to_string() == to
+    }) {
+        panic!(
+            "expected no {edge_type:?} edge {from} -> {to}, found {}",
+            describe(edge)
+        );
+    }
+}
+
+/// Assert that the prompts and responses of a session, oldest first, have
+/// exactly the contents `expected`
+#[track_caller]
+pub fn assert_thread_order(server: &MockServer, session_id: impl ToString, expected: &[&str]) {
+    let session_id = session_id.to_string();
+    let Some(session) = server
+        .sessions()
+        .into_iter()
+        .find(|session| session.id.to_string() == session_id)
+    else {
+        panic!("session {session_id} does not exist");
+    };
+
+    let thread: Vec<String> = server
+        .thread(&session.id)
+        .into_iter()
+        .filter_map(|node| match node {
+            Node::Prompt(prompt) => Some(prompt.content),
+            Node::Response(response) => Some(response.content),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        thread, expected,
+        "thread of session {session_id} is out of order"
+    );
+}
+
+fn describe(edge: &Edge) -> String {
+    format!("{:?} {} -> {}", edge.edge_type, edge.from, edge.to)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::ConversationBuilder;
+
+    #[tokio::test]
+    async fn test_assertions_on_seeded_conversation() {
+        let server = MockServer::start().await.unwrap();
+        let conversation = ConversationBuilder::new()
+            .turn("First", "One")
+            .turn("Second", "Two")
+            .build();
+        server.seed(&conversation);
+
+        let prompt = &conversation.prompts[1];
+        let response = &conversation.responses[1];
+        assert_edge_exists(&server, response.id, prompt.id, EdgeType::RespondsTo);
+        assert_edge_exists(
+            &server,
+            prompt.id,
+            conversation.session.node_id,
+            EdgeType::PartOf,
+        );
+        assert_no_edge(&server, prompt.id, response.id, EdgeType::RespondsTo);
+        assert_thread_order(
+            &server,
+            conversation.session.id,
+            &["First", "One", "Second", "Two"],
+        );
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "out of order")]
+    async fn test_assert_thread_order_fails() {
+        let server = MockServer::start().await.unwrap();
+        let conversation = ConversationBuilder::new().turn("Hi", "Hello").build();
+        server.seed(&conversation);
+
+        assert_thread_order(&server, conversation.session.id, &["Hello", "Hi"]);
+    }
+}
@@ -0,0 +1,396 @@
+//! Builders for sessions, prompts, responses and agents
+//!
+//! Every builder starts from sensible defaults, so a test only spells out the
+//! fields it cares about:
+//!
+//! ```
+//! use llm_memory_graph_testkit::fixtures::{PromptBuilder, ResponseBuilder, SessionBuilder};
+//!
+//! let session = SessionBuilder::new().title("Support chat").build();
+//! let prompt = PromptBuilder::new(session.id, "Where is my order?").build();
+//! let response = ResponseBuilder::new(prompt.id, "It shipped yesterday.")
+//!     .tokens(12, 6)
+//!     .build();
+//! assert_eq!(response.usage.total_tokens, 18);
+//! ```
+//!
+//! [`ConversationBuilder`] strings them together into a whole conversation,
+//! with timestamps one second apart so its thread order is unambiguous.
+
+use chrono::{DateTime, Duration, Utc};
+use llm_memory_graph_types::NodeId;
+use llm_memory_graph_types::{
+    AgentNode, ConversationSession, Node, PromptMetadata, PromptNode, ResponseMetadata,
+    ResponseNode, SessionId, TokenUsage,
+};
+use std::collections::HashMap;
+
+/// Model recorded on prompts and responses unless a builder sets another
+pub const DEFAULT_MODEL: &str = "test-model";
+
+/// Builds a [`ConversationSession`]
+#[derive(Debug, Clone, Default)]
+pub struct SessionBuilder {
+    session: ConversationSession,
+}
+
+impl SessionBuilder {
+    /// Start an open session with no metadata
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the session's title
+    #[must_use]
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.session.title = Some(title.into());
+        self
+    }
+
+    /// Add a metadata entry
+    #[must_use]
+    pub fn metadata(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.session.metadata.insert(key.into(), value.into());
+        self
+    }
+
+    /// Add a tag
+    #[must_use]
+    pub fn tag(mut self, tag: impl Into<String>) -> Self {
+        self.session.add_tag(tag.into());
+        self
+    }
+
+    /// Set when the session was created
+    #[must_use]
+    pub fn created_at(mut self, at: DateTime<Utc>) -> Self {
+        self.session.created_at = at;
+        self.session.updated_at = at;
+        self
+    }
+
+    /// Finish the session
+    pub fn build(self) -> ConversationSession {
+        self.session
+    }
+}
+
+/// Builds a [`PromptNode`]
+#[derive(Debug, Clone)]
+pub struct PromptBuilder {
+    prompt: PromptNode,
+}
+
+impl PromptBuilder {
+    /// Start a prompt in `session_id` sent to [`DEFAULT_MODEL`]
+    pub fn new(session_id: SessionId, content: impl Into<String>) -> Self {
+        let metadata = PromptMetadata {
+            model: DEFAULT_MODEL.to_string(),
+            ..PromptMetadata::default()
+        };
+        Self {
+            prompt: PromptNode::with_metadata(session_id, content.into(), metadata),
+        }
+    }
+
+    /// Set the model the prompt was sent to
+    #[must_use]
+    pub fn model(mut self, model: impl Into<String>) -> Self {
+        self.prompt.metadata.model = model.into();
+        self
+    }
+
+    /// Set the sampling temperature
+    #[must_use]
+    pub fn temperature(mut self, temperature: f32) -> Self {
+        self.prompt.metadata.temperature = temperature;
+        self
+    }
+
+    /// Add a custom metadata entry
+    #[must_use]
+    pub fn metadata(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.prompt.metadata.custom.insert(key.into(), value.into());
+        self
+    }
+
+    /// Set when the prompt was sent
+    #[must_use]
+    pub fn at(mut self, at: DateTime<Utc>) -> Self {
+        self.prompt.timestamp = at;
+        self
+    }
+
+    /// Finish the prompt
+    pub fn build(self) -> PromptNode {
+        self.prompt
+    }
+}
+
+/// Builds a [`ResponseNode`]
+#[derive(Debug, Clone)]
+pub struct ResponseBuilder {
+    response: ResponseNode,
+}
+
+impl ResponseBuilder {
+    /// Start a response to `prompt_id` from [`DEFAULT_MODEL`], using no tokens
+    pub fn new(prompt_id: NodeId, content: impl Into<String>) -> Self {
+        let metadata = ResponseMetadata {
+            model: DEFAULT_MODEL.to_string(),
+            finish_reason: "stop".to_string(),
+            ..ResponseMetadata::default()
+        };
+        Self {
+            response: ResponseNode::with_metadata(
+                prompt_id,
+                content.into(),
+                TokenUsage::new(0, 0),
+                metadata,
+            ),
+        }
+    }
+
+    /// Set the model that answered
+    #[must_use]
+    pub fn model(mut self, model: impl Into<String>) -> Self {
+        self.response.metadata.model = model.into();
+        self
+    }
+
+    /// Set the prompt and completion tokens used
+    #[must_use]
+    pub fn tokens(mut self, prompt_tokens: u32, completion_tokens: u32) -> Self {
+        self.response.usage = TokenUsage::new(prompt_tokens, completion_tokens);
+        self
+    }
+
+    /// Set how long the model took to answer
+    #[must_use]
+    pub fn latency_ms(mut self, latency_ms: u64) -> Self {
+        self.response.metadata.latency_ms = latency_ms;
+        self
+    }
+
+    /// Set why the model stopped
+    #[must_use]
+    pub fn finish_reason(mut self, reason: impl Into<String>) -> Self {
+        self.response.metadata.finish_reason = reason.into();
+        self
+    }
+
+    /// Set when the response arrived
+    #[must_use]
+    pub fn at(mut self, at: DateTime<Utc>) -> Self {
+        self.response.timestamp = at;
+        self
+    }
+
+    /// Finish the response
+    pub fn build(self) -> ResponseNode {
+        self.response
+    }
+}
+
+/// Builds an [`AgentNode`]
+#[derive(Debug, Clone)]
+pub struct AgentBuilder {
+    agent: AgentNode,
+}
+
+impl AgentBuilder {
+    /// Start an idle agent called `name` with the `assistant` role
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            agent: AgentNode::new(name.into(), "assistant".to_string(), Vec::new()),
+        }
+    }
+
+    /// Set the agent's role
+    #[must_use]
+    pub fn role(mut self, role: impl Into<String>) -> Self {
+        self.agent.role = role.into();
+        self
+    }
+
+    /// Add a capability
+    #[must_use]
+    pub fn capability(mut self, capability: impl Into<String>) -> Self {
+        self.agent.add_capability(capability.into());
+        self
+    }
+
+    /// Set the model the agent runs on
+    #[must_use]
+    pub fn model(mut self, model: impl Into<String>) -> Self {
+        self.agent.model = model.into();
+        self
+    }
+
+    /// Add a tag
+    #[must_use]
+    pub fn tag(mut self, tag: impl Into<String>) -> Self {
+        self.agent.tags.push(tag.into());
+        self
+    }
+
+    /// Finish the agent
+    pub fn build(self) -> AgentNode {
+        self.agent
+    }
+}
+
+/// A session with its prompts and responses, built by [`ConversationBuilder`]
+#[derive(Debug, Clone)]
+pub struct Conversation {
+    /// The session
+    pub session: ConversationSession,
+    /// The prompts, in the order they were sent
+    pub prompts: Vec<PromptNode>,
+    /// The responses, in the order they arrived
+    pub responses: Vec<ResponseNode>,
+}
+
+impl Conversation {
+    /// The prompts and responses, oldest first
+    pub fn nodes(&self) -> Vec<Node> {
+        let mut nodes: Vec<Node> = self
+            .prompts
+            .iter()
+            .cloned()
+            .map(Node::Prompt)
+            .chain(self.responses.iter().cloned().map(Node::Response))
+            .collect();
+        nodes.sort_by_key(Node::created_at);
+        nodes
+    }
+
+    /// The content of the prompts and responses, oldest first
+    pub fn thread(&self) -> Vec<String> {
+        self.nodes()
+            .into_iter()
+            .map(|node| match node {
+                Node::Prompt(prompt) => prompt.content,
+                Node::Response(response) => response.content,
+                _ => unreachable!("conversations only hold prompts and responses"),
+            })
+            .collect()
+    }
+}
+
+/// Builds a [`Conversation`] turn by turn
+///
+/// ```
+/// use llm_memory_graph_testkit::fixtures::ConversationBuilder;
+///
+/// let conversation = ConversationBuilder::new()
+///     .turn("Hi", "Hello! How can I help?")
+///     .prompt("Are you there?")
+///     .build();
+/// assert_eq!(conversation.prompts.len(), 2);
+/// assert_eq!(conversation.thread(), ["Hi", "Hello! How can I help?", "Are you there?"]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ConversationBuilder {
+    session: SessionBuilder,
+    model: String,
+    turns: Vec<(String, Option<String>)>,
+    start: DateTime<Utc>,
+}
+
+impl Default for ConversationBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ConversationBuilder {
+    /// Start an empty conversation beginning now
+    pub fn new() -> Self {
+        Self {
+            session: SessionBuilder::new(),
+            model: DEFAULT_MODEL.to_string(),
+            turns: Vec::new(),
+            start: Utc::now(),
+        }
+    }
+
+    /// Add a session metadata entry
+    #[must_use]
+    pub fn metadata(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.session = self.session.metadata(key, value);
+        self
+    }
+
+    /// Set the model of every prompt and response
+    #[must_use]
+    pub fn model(mut self, model: impl Into<String>) -> Self {
+        self.model = model.into();
+        self
+    }
+
+    /// Set when the session starts; each message follows one second later
+    #[must_use]
+    pub fn starting_at(mut self, at: DateTime<Utc>) -> Self {
+        self.start = at;
+        self
+    }
+
+    /// Add a prompt and its response
+    #[must_use]
+    pub fn turn(mut self, prompt: impl Into<String>, response: impl Into<String>) -> Self {
+        self.turns.push((prompt.into(), Some(response.into())));
+        self
+    }
+
+    /// Add a prompt left unanswered
+    #[must_use]
+    pub fn prompt(mut self, prompt: impl Into<String>) -> Self {
+        self.turns.push((prompt.into(), None));
+        self
+    }
+
+    /// Build the conversation
+    pub fn build(self) -> Conversation {
+        let session = self.session.created_at(self.start).build();
+        let mut prompts = Vec::new();
+        let mut responses = Vec::new();
+        let mut at = self.start;
+
+        for (prompt, response) in self.turns {
+            at += Duration::seconds(1);
+            let prompt = PromptBuilder::new(session.id, prompt)
+                .model(&self.model)
+                .at(at)
+                .build();
+            if let Some(response) = response {
+                at += Duration::seconds(1);
+                responses.push(
+                    ResponseBuilder::new(prompt.id, response)
+                        .model(&self.model)
+                        .at(at)
+                        .build(),
+                );
+            }
+            prompts.push(prompt);
+        }
+
+        Conversation {
+            session,
+            prompts,
+            responses,
+        }
+    }
+}
+
+/// Metadata map from key-value pairs, for APIs that take a `HashMap`
+pub fn metadata<K, V>(pairs: impl IntoIterator<Item = (K, V)>) -> HashMap<String, String>
+where
+    K: Into<String>,
+    V: Into<String>,
+{
+    pairs
+        .into_iter()
+        .map(|(key, value)| (key.into(), value.into()))
+        .collect()
+}
@@ -0,0 +1,61 @@
+//! Test support for applications built on LLM Memory Graph
+//!
+//! This crate lets integration tests exercise a real
+//! [`MemoryGraphClient`](llm_memory_graph_client::MemoryGraphClient) without
+//! running the service.
+//!
+//! # Features
+//!
+//! - **Mock server**: An in-process gRPC server with an in-memory graph, see [`MockServer`]
+//! - **Fixtures**: Builders for sessions, prompts, responses, agents and whole conversations
+//! - **Assertions**: Checks for edges and thread order, with readable failures
+//! - **Fault injection**: Make every call fail with a chosen status code
+//!
+//! # Example
+//!
+//! ```
+//! use llm_memory_graph_testkit::{assert_edge_exists, assert_thread_order, MockServer};
+//! use llm_memory_graph_types::EdgeType;
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!     let server = MockServer::start().await?;
+//!     let client = server.client().await?;
+//!
+//!     let session_id = client.create_session(Default::default()).await?;
+//!     let prompt = client
+//!         .add_prompt(session_id.clone(), "What is the capital of France?".to_string(), None)
+//!         .await?;
+//!     let response = client
+//!         .add_response(prompt.id.clone(), "Paris.".to_string(), None, None)
+//!         .await?;
+//!
+//!     assert_edge_exists(&server, &response.id, &prompt.id, EdgeType::RespondsTo);
+//!     assert_thread_order(&server, &session_id, &["What is the capital of France?", "Paris."]);
+//!     Ok(())
+//! }
+//! ```
+
+#![deny(missing_docs)]
+#![deny(unsafe_code)]
+#![warn(clippy::all)]
+#![warn(clippy::pedantic)]
+#![allow(clippy::module_name_repetitions)]
+#![allow(clippy::missing_errors_doc)]
+#![allow(clippy::missing_panics_doc)]
+#![allow(clippy::must_use_candidate)]
+#![allow(clippy::needless_pass_by_value)]
+#![allow(clippy::result_large_err)]
+#![allow(clippy::return_self_not_must_use)]
+#![allow(clippy::uninlined_format_args)]
+
+pub mod assertions;
+pub mod fixtures;
+pub mod server;
+
+// Re-export main types
+pub use assertions::{assert_edge_exists, assert_no_edge, assert_thread_order};
+pub use fixtures::{
+    AgentBuilder, Conversation, ConversationBuilder, PromptBuilder, ResponseBuilder, SessionBuilder,
+};
+pub use server::MockServer;
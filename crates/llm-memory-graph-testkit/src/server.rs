@@ -0,0 +1,1390 @@
+//! In-process mock of the Memory Graph gRPC service
+//!
+//! [`MockServer`] listens on a local port and answers every RPC of the
+//! service from an in-memory graph, so applications can exercise their real
+//! [`MemoryGraphClient`] without a storage engine. Adding a prompt links it
+//! to its session, and adding a response links it to its prompt, just as the
+//! graph does; every write is published on the event stream.
+//!
+//! Tests can seed the graph directly with [`MockServer::seed`] and friends,
+//! inspect it with [`MockServer::nodes`] and [`MockServer::edges`], and make
+//! the server fail every call with [`MockServer::fail_with`].
+
+use crate::fixtures::Conversation;
+use async_stream::try_stream;
+use chrono::Utc;
+use futures::{Stream, StreamExt};
+use llm_memory_graph_client::client::proto;
+use llm_memory_graph_client::client::proto::memory_graph_service_server::{
+    MemoryGraphService, MemoryGraphServiceServer,
+};
+use llm_memory_graph_client::convert::{
+    agent_node_to_proto, datetime_to_proto, edge_to_proto, node_to_proto, parse_node_id,
+    parse_session_id, proto_to_datetime, proto_to_edge, proto_to_edge_type, proto_to_node,
+    proto_to_prompt_metadata, proto_to_response_metadata, proto_to_template, proto_to_token_usage,
+    proto_to_tool_invocation, session_to_proto, template_to_proto,
+};
+use llm_memory_graph_client::{ClientError, MemoryGraphClient};
+use llm_memory_graph_types::{
+    AliasTarget, ConversationSession, Edge, EdgeId, EdgeType, Node, NodeId, NodeType, PromptNode,
+    ResponseNode, SessionId, TokenUsage,
+};
+use std::collections::{BTreeMap, HashMap};
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Instant;
+use tokio::net::TcpListener;
+use tokio::sync::{broadcast, oneshot};
+use tokio::task::JoinHandle;
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::{Code, Request, Response, Status, Streaming};
+use uuid::Uuid;
+
+/// Page size used when a listing request does not ask for one
+const DEFAULT_PAGE_SIZE: usize = 100;
+
+/// Events buffered for subscribers that fall behind
+const EVENT_BUFFER: usize = 1024;
+
+type ServiceStream<T> = Pin<Box<dyn Stream<Item = Result<T, Status>> + Send>>;
+
+/// A mock Memory Graph server running on a local port
+///
+/// The server stops when dropped.
+pub struct MockServer {
+    address: SocketAddr,
+    state: Arc<MockState>,
+    shutdown: Option<oneshot::Sender<()>>,
+    task: JoinHandle<()>,
+}
+
+impl MockServer {
+    /// Start a server on a free local port
+    pub async fn start() -> std::io::Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let address = listener.local_addr()?;
+        let state = Arc::new(MockState::new());
+        let service = MemoryGraphServiceServer::new(MockService {
+            state: Arc::clone(&state),
+        });
+
+        let (shutdown, stopped) = oneshot::channel::<()>();
+        let task = tokio::spawn(async move {
+            let result = tonic::transport::Server::builder()
+                .add_service(service)
+                .serve_with_incoming_shutdown(TcpListenerStream::new(listener), async {
+                    stopped.await.ok();
+                })
+                .await;
+            if let Err(e) = result {
+                eprintln!("mock memory graph server failed: {e}");
+            }
+        });
+
+        Ok(Self {
+            address,
+            state,
+            shutdown: Some(shutdown),
+            task,
+        })
+    }
+
+    /// Address to connect to, such as `http://127.0.0.1:50123`
+    pub fn address(&self) -> String {
+        format!("http://{}", self.address)
+    }
+
+    /// A client connected to this server, without retries
+    pub async fn client(&self) -> llm_memory_graph_client::Result<MemoryGraphClient> {
+        MemoryGraphClient::builder()
+            .address(self.address())
+            .retry(llm_memory_graph_client::RetryConfig::disabled())
+            .build()
+            .await
+    }
+
+    /// Make every call fail with `code`, or answer normally again with `None`
+    pub fn fail_with(&self, code: Option<Code>) {
+        *self.state.failure.lock().unwrap() = code;
+    }
+
+    /// Add a session
+    pub fn insert_session(&self, session: ConversationSession) {
+        self.state.graph().sessions.push(session);
+    }
+
+    /// Add a node, linked to its session or prompt like the graph links it
+    pub fn insert_node(&self, node: Node) {
+        self.state.graph().add_node(node);
+    }
+
+    /// Add an edge
+    pub fn insert_edge(&self, edge: Edge) {
+        self.state.graph().edges.push(edge);
+    }
+
+    /// Add a conversation built with
+    /// [`ConversationBuilder`](crate::fixtures::ConversationBuilder)
+    pub fn seed(&self, conversation: &Conversation) {
+        let mut graph = self.state.graph();
+        graph.sessions.push(conversation.session.clone());
+        for node in conversation.nodes() {
+            graph.add_node(node);
+        }
+    }
+
+    /// Every session, in the order they were added
+    pub fn sessions(&self) -> Vec<ConversationSession> {
+        self.state.graph().sessions.clone()
+    }
+
+    /// Every node, in the order they were added
+    pub fn nodes(&self) -> Vec<Node> {
+        self.state.graph().nodes.clone()
+    }
+
+    /// Every edge, in the order they were added
+    pub fn edges(&self) -> Vec<Edge> {
+        self.state.graph().edges.clone()
+    }
+
+    /// The prompts and responses of a session, oldest first
+    pub fn thread(&self, session_id: &SessionId) -> Vec<Node> {
+        let graph = self.state.graph();
+        let mut thread: Vec<Node> = graph
+            .nodes
+            .iter()
+            .filter(|node| matches!(node, Node::Prompt(_) | Node::Response(_)))
+            .filter(|node| graph.session_of(node) == Some(*session_id))
+            .cloned()
+            .collect();
+        thread.sort_by_key(Node::created_at);
+        thread
+    }
+
+    /// Stop the server and wait for it to shut down
+    pub async fn shutdown(mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            shutdown.send(()).ok();
+        }
+        (&mut self.task).await.ok();
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            shutdown.send(()).ok();
+        }
+    }
+}
+
+/// State shared by the server and its handle
+struct MockState {
+    graph: Mutex<Graph>,
+    events: broadcast::Sender<proto::Event>,
+    failure: Mutex<Option<Code>>,
+    started: Instant,
+}
+
+impl MockState {
+    fn new() -> Self {
+        Self {
+            graph: Mutex::new(Graph::default()),
+            events: broadcast::channel(EVENT_BUFFER).0,
+            failure: Mutex::new(None),
+            started: Instant::now(),
+        }
+    }
+
+    fn graph(&self) -> MutexGuard<'_, Graph> {
+        self.graph.lock().unwrap()
+    }
+
+    /// Fail the call if the test asked for failures
+    fn check(&self) -> Result<(), Status> {
+        match *self.failure.lock().unwrap() {
+            Some(code) => Err(Status::new(code, "failure injected by the mock server")),
+            None => Ok(()),
+        }
+    }
+
+    fn emit(&self, event_type: proto::EventType, payload: serde_json::Value) {
+        // Sending only fails when nobody is subscribed
+        let _ = self.events.send(proto::Event {
+            id: Uuid::new_v4().to_string(),
+            r#type: event_type.into(),
+            timestamp: Some(datetime_to_proto(Utc::now())),
+            payload: payload.to_string(),
+        });
+    }
+
+    fn emit_node(
+        &self,
+        event_type: proto::EventType,
+        node_id: NodeId,
+        session_id: Option<SessionId>,
+    ) {
+        let mut payload = serde_json::json!({ "node_id": node_id.to_string() });
+        if let Some(session_id) = session_id {
+            payload["session_id"] = session_id.to_string().into();
+        }
+        self.emit(event_type, payload);
+    }
+
+    fn emit_edge(&self, event_type: proto::EventType, edge: &Edge) {
+        self.emit(
+            event_type,
+            serde_json::json!({
+                "edge_id": edge.id.to_string(),
+                "from": edge.from.to_string(),
+                "to": edge.to.to_string(),
+            }),
+        );
+    }
+}
+
+/// The mock's in-memory graph
+#[derive(Default)]
+struct Graph {
+    sessions: Vec<ConversationSession>,
+    nodes: Vec<Node>,
+    edges: Vec<Edge>,
+    aliases: BTreeMap<String, AliasTarget>,
+    /// Nodes and edges created by requests carrying an idempotency key
+    node_keys: HashMap<String, NodeId>,
+    edge_keys: HashMap<String, EdgeId>,
+}
+
+impl Graph {
+    fn session(&self, id: &SessionId) -> Option<&ConversationSession> {
+        self.sessions.iter().find(|session| session.id == *id)
+    }
+
+    fn node(&self, id: NodeId) -> Option<&Node> {
+        self.nodes.iter().find(|node| node.id() == id)
+    }
+
+    /// Session a prompt, response or tool invocation belongs to
+    fn session_of(&self, node: &Node) -> Option<SessionId> {
+        match node {
+            Node::Prompt(prompt) => Some(prompt.session_id),
+            Node::Response(response) => self
+                .node(response.prompt_id)
+                .and_then(|prompt| self.session_of(prompt)),
+            Node::ToolInvocation(tool) => self
+                .node(tool.response_id)
+                .and_then(|response| self.session_of(response)),
+            _ => None,
+        }
+    }
+
+    /// Store `node` and the edge linking it into its conversation
+    fn add_node(&mut self, node: Node) -> Option<Edge> {
+        let edge = match &node {
+            Node::Prompt(prompt) => self
+                .session(&prompt.session_id)
+                .map(|session| Edge::new(prompt.id, session.node_id, EdgeType::PartOf)),
+            Node::Response(response) => Some(Edge::new(
+                response.id,
+                response.prompt_id,
+                EdgeType::RespondsTo,
+            )),
+            Node::ToolInvocation(tool) => {
+                Some(Edge::new(tool.response_id, tool.id, EdgeType::Invokes))
+            }
+            _ => None,
+        };
+        self.nodes.push(node);
+        if let Some(edge) = &edge {
+            self.edges.push(edge.clone());
+        }
+        edge
+    }
+
+    fn remove_node(&mut self, id: NodeId) -> Option<Node> {
+        let index = self.nodes.iter().position(|node| node.id() == id)?;
+        self.edges.retain(|edge| edge.from != id && edge.to != id);
+        Some(self.nodes.remove(index))
+    }
+}
+
+fn invalid(err: ClientError) -> Status {
+    Status::invalid_argument(err.to_string())
+}
+
+fn node_id(id: &str) -> Result<NodeId, Status> {
+    parse_node_id(id).map_err(invalid)
+}
+
+fn session_id(id: &str) -> Result<SessionId, Status> {
+    parse_session_id(id).map_err(invalid)
+}
+
+fn to_proto(node: Node) -> Result<proto::Node, Status> {
+    node_to_proto(node).map_err(|e| Status::internal(e.to_string()))
+}
+
+fn count(len: usize) -> i64 {
+    i64::try_from(len).unwrap_or(i64::MAX)
+}
+
+/// The page of `items` after the item whose key is `cursor`
+///
+/// The mock's cursors are simply the key of the last item of a page.
+fn page<T>(
+    items: Vec<T>,
+    key: impl Fn(&T) -> String,
+    cursor: &str,
+    limit: i32,
+) -> Result<(Vec<T>, String), Status> {
+    let start = if cursor.is_empty() {
+        0
+    } else {
+        items
+            .iter()
+            .position(|item| key(item) == cursor)
+            .map(|position| position + 1)
+            .ok_or_else(|| Status::invalid_argument(format!("unknown cursor {cursor:?}")))?
+    };
+    let limit = usize::try_from(limit)
+        .ok()
+        .filter(|&limit| limit > 0)
+        .unwrap_or(DEFAULT_PAGE_SIZE);
+
+    let mut items: Vec<T> = items.into_iter().skip(start).collect();
+    let next_cursor = if items.len() > limit {
+        items.truncate(limit);
+        items.last().map(&key).unwrap_or_default()
+    } else {
+        String::new()
+    };
+    Ok((items, next_cursor))
+}
+
+struct MockService {
+    state: Arc<MockState>,
+}
+
+impl MockService {
+    fn query_nodes(&self, req: &proto::QueryRequest) -> Result<(Vec<Node>, i64), Status> {
+        if !req.filters.is_empty() {
+            return Err(Status::unimplemented(
+                "the mock server does not support query filters",
+            ));
+        }
+        let session_id = req.session_id.as_deref().map(session_id).transpose()?;
+        let node_type = req
+            .node_type
+            .map(|node_type| match proto::NodeType::try_from(node_type) {
+                Ok(proto::NodeType::Prompt) => Ok(NodeType::Prompt),
+                Ok(proto::NodeType::Response) => Ok(NodeType::Response),
+                Ok(proto::NodeType::ToolInvocation) => Ok(NodeType::ToolInvocation),
+                Ok(proto::NodeType::Agent) => Ok(NodeType::Agent),
+                Ok(proto::NodeType::Template) => Ok(NodeType::Template),
+                _ => Err(Status::invalid_argument(format!(
+                    "invalid node type: {node_type}"
+                ))),
+            })
+            .transpose()?;
+        let after = req
+            .after
+            .as_ref()
+            .map(proto_to_datetime)
+            .transpose()
+            .map_err(invalid)?;
+        let before = req
+            .before
+            .as_ref()
+            .map(proto_to_datetime)
+            .transpose()
+            .map_err(invalid)?;
+
+        let graph = self.state.graph();
+        let matches: Vec<Node> = graph
+            .nodes
+            .iter()
+            .filter(|node| session_id.is_none() || graph.session_of(node) == session_id)
+            .filter(|node| {
+                node_type
+                    .as_ref()
+                    .is_none_or(|node_type| node.node_type() == *node_type)
+            })
+            .filter(|node| after.is_none_or(|after| node.created_at() >= after))
+            .filter(|node| before.is_none_or(|before| node.created_at() < before))
+            .cloned()
+            .collect();
+        let total = count(matches.len());
+        let offset = usize::try_from(req.offset).unwrap_or(0);
+        let limit = usize::try_from(req.limit)
+            .ok()
+            .filter(|&limit| limit > 0)
+            .unwrap_or(usize::MAX);
+        Ok((
+            matches.into_iter().skip(offset).take(limit).collect(),
+            total,
+        ))
+    }
+}
+
+#[tonic::async_trait]
+impl MemoryGraphService for MockService {
+    type ImportNodesStream = ServiceStream<proto::ImportProgress>;
+    type StreamQueryStream = ServiceStream<proto::Node>;
+    type StreamEventsStream = ServiceStream<proto::Event>;
+    type SubscribeToSessionStream = ServiceStream<proto::SessionEvent>;
+
+    // ========================================================================
+    // Sessions
+    // ========================================================================
+
+    async fn create_session(
+        &self,
+        request: Request<proto::CreateSessionRequest>,
+    ) -> Result<Response<proto::Session>, Status> {
+        self.state.check()?;
+        let session = ConversationSession::with_metadata(request.into_inner().metadata);
+        self.state.graph().sessions.push(session.clone());
+        self.state.emit(
+            proto::EventType::SessionCreated,
+            serde_json::json!({ "session_id": session.id.to_string() }),
+        );
+        Ok(Response::new(session_to_proto(session)))
+    }
+
+    async fn get_session(
+        &self,
+        request: Request<proto::GetSessionRequest>,
+    ) -> Result<Response<proto::Session>, Status> {
+        self.state.check()?;
+        let id = session_id(&request.into_inner().session_id)?;
+        let session = self
+            .state
+            .graph()
+            .session(&id)
+            .cloned()
+            .ok_or_else(|| Status::not_found(format!("Session not found: {id}")))?;
+        Ok(Response::new(session_to_proto(session)))
+    }
+
+    async fn delete_session(
+        &self,
+        request: Request<proto::DeleteSessionRequest>,
+    ) -> Result<Response<()>, Status> {
+        self.state.check()?;
+        let id = session_id(&request.into_inner().session_id)?;
+        {
+            let mut graph = self.state.graph();
+            let index = graph
+                .sessions
+                .iter()
+                .position(|session| session.id == id)
+                .ok_or_else(|| Status::not_found(format!("Session not found: {id}")))?;
+            let session = graph.sessions.remove(index);
+            let doomed: Vec<NodeId> = graph
+                .nodes
+                .iter()
+                .filter(|node| graph.session_of(node) == Some(id))
+                .map(Node::id)
+                .collect();
+            for node in doomed {
+                graph.remove_node(node);
+            }
+            graph
+                .edges
+                .retain(|edge| edge.from != session.node_id && edge.to != session.node_id);
+        }
+        self.state.emit(
+            proto::EventType::SessionClosed,
+            serde_json::json!({ "session_id": id.to_string() }),
+        );
+        Ok(Response::new(()))
+    }
+
+    async fn list_sessions(
+        &self,
+        request: Request<proto::ListSessionsRequest>,
+    ) -> Result<Response<proto::ListSessionsResponse>, Status> {
+        self.state.check()?;
+        let req = request.into_inner();
+        let sessions = self.state.graph().sessions.clone();
+        let total_count = count(sessions.len());
+        let (sessions, next_cursor) = page(
+            sessions,
+            |session| session.id.to_string(),
+            &req.cursor,
+            req.limit,
+        )?;
+        Ok(Response::new(proto::ListSessionsResponse {
+            sessions: sessions.into_iter().map(session_to_proto).collect(),
+            total_count,
+            next_cursor,
+        }))
+    }
+
+    async fn list_session_nodes(
+        &self,
+        request: Request<proto::ListSessionNodesRequest>,
+    ) -> Result<Response<proto::ListSessionNodesResponse>, Status> {
+        self.state.check()?;
+        let req = request.into_inner();
+        let id = session_id(&req.session_id)?;
+        let nodes: Vec<Node> = {
+            let graph = self.state.graph();
+            if graph.session(&id).is_none() {
+                return Err(Status::not_found(format!("Session not found: {id}")));
+            }
+            graph
+                .nodes
+                .iter()
+                .filter(|node| graph.session_of(node) == Some(id))
+                .cloned()
+                .collect()
+        };
+        let (nodes, next_cursor) =
+            page(nodes, |node| node.id().to_string(), &req.cursor, req.limit)?;
+        Ok(Response::new(proto::ListSessionNodesResponse {
+            nodes: nodes.into_iter().map(to_proto).collect::<Result<_, _>>()?,
+            next_cursor,
+        }))
+    }
+
+    // ========================================================================
+    // Nodes
+    // ========================================================================
+
+    async fn create_node(
+        &self,
+        request: Request<proto::CreateNodeRequest>,
+    ) -> Result<Response<proto::Node>, Status> {
+        self.state.check()?;
+        let node = request
+            .into_inner()
+            .node
+            .ok_or_else(|| Status::invalid_argument("node is required"))?;
+        let node = proto_to_node(node).map_err(invalid)?;
+        let id = node.id();
+        let session_id = {
+            let mut graph = self.state.graph();
+            if graph.node(id).is_some() {
+                return Err(Status::already_exists(format!("Node already exists: {id}")));
+            }
+            graph.add_node(node.clone());
+            graph.session_of(&node)
+        };
+        self.state
+            .emit_node(proto::EventType::NodeCreated, id, session_id);
+        Ok(Response::new(to_proto(node)?))
+    }
+
+    async fn get_node(
+        &self,
+        request: Request<proto::GetNodeRequest>,
+    ) -> Result<Response<proto::Node>, Status> {
+        self.state.check()?;
+        let id = node_id(&request.into_inner().node_id)?;
+        let node = self
+            .state
+            .graph()
+            .node(id)
+            .cloned()
+            .ok_or_else(|| Status::not_found(format!("Node not found: {id}")))?;
+        Ok(Response::new(to_proto(node)?))
+    }
+
+    async fn update_node(
+        &self,
+        request: Request<proto::UpdateNodeRequest>,
+    ) -> Result<Response<proto::Node>, Status> {
+        self.state.check()?;
+        let node = request
+            .into_inner()
+            .node
+            .ok_or_else(|| Status::invalid_argument("node is required"))?;
+        let node = proto_to_node(node).map_err(invalid)?;
+        let id = node.id();
+        let session_id = {
+            let mut graph = self.state.graph();
+            let slot = graph
+                .nodes
+                .iter_mut()
+                .find(|existing| existing.id() == id)
+                .ok_or_else(|| Status::not_found(format!("Node not found: {id}")))?;
+            *slot = node.clone();
+            graph.session_of(&node)
+        };
+        self.state
+            .emit_node(proto::EventType::NodeUpdated, id, session_id);
+        Ok(Response::new(to_proto(node)?))
+    }
+
+    async fn delete_node(
+        &self,
+        request: Request<proto::DeleteNodeRequest>,
+    ) -> Result<Response<()>, Status> {
+        self.state.check()?;
+        let id = node_id(&request.into_inner().node_id)?;
+        let session_id = {
+            let mut graph = self.state.graph();
+            let session_id = graph.node(id).and_then(|node| graph.session_of(node));
+            graph
+                .remove_node(id)
+                .ok_or_else(|| Status::not_found(format!("Node not found: {id}")))?;
+            session_id
+        };
+        self.state
+            .emit_node(proto::EventType::NodeDeleted, id, session_id);
+        Ok(Response::new(()))
+    }
+
+    async fn batch_create_nodes(
+        &self,
+        request: Request<proto::BatchCreateNodesRequest>,
+    ) -> Result<Response<proto::BatchCreateNodesResponse>, Status> {
+        let mut nodes = Vec::new();
+        for node in request.into_inner().nodes {
+            let created = self
+                .create_node(Request::new(proto::CreateNodeRequest { node: Some(node) }))
+                .await?;
+            nodes.push(created.into_inner());
+        }
+        Ok(Response::new(proto::BatchCreateNodesResponse {
+            created_count: i32::try_from(nodes.len()).unwrap_or(i32::MAX),
+            nodes,
+        }))
+    }
+
+    async fn batch_get_nodes(
+        &self,
+        request: Request<proto::BatchGetNodesRequest>,
+    ) -> Result<Response<proto::BatchGetNodesResponse>, Status> {
+        self.state.check()?;
+        let ids = request
+            .into_inner()
+            .node_ids
+            .iter()
+            .map(|id| node_id(id))
+            .collect::<Result<Vec<_>, _>>()?;
+        let nodes: Vec<Node> = {
+            let graph = self.state.graph();
+            ids.into_iter()
+                .filter_map(|id| graph.node(id).cloned())
+                .collect()
+        };
+        Ok(Response::new(proto::BatchGetNodesResponse {
+            nodes: nodes.into_iter().map(to_proto).collect::<Result<_, _>>()?,
+        }))
+    }
+
+    async fn import_nodes(
+        &self,
+        request: Request<Streaming<proto::ImportRecord>>,
+    ) -> Result<Response<Self::ImportNodesStream>, Status> {
+        self.state.check()?;
+        let started = Instant::now();
+        let mut records = request.into_inner();
+        let (mut nodes_loaded, mut edges_loaded) = (0, 0);
+        while let Some(record) = records.next().await {
+            let mut record: serde_json::Value = serde_json::from_str(&record?.json)
+                .map_err(|e| Status::invalid_argument(format!("invalid import record: {e}")))?;
+            let mut graph = self.state.graph();
+            if let Some(node) = record.get_mut("node") {
+                let node: Node = serde_json::from_value(node.take())
+                    .map_err(|e| Status::invalid_argument(format!("invalid node: {e}")))?;
+                match node {
+                    Node::Session(session) => graph.sessions.push(session),
+                    node => graph.nodes.push(node),
+                }
+                nodes_loaded += 1;
+            } else if let Some(edge) = record.get_mut("edge") {
+                let edge: Edge = serde_json::from_value(edge.take())
+                    .map_err(|e| Status::invalid_argument(format!("invalid edge: {e}")))?;
+                graph.edges.push(edge);
+                edges_loaded += 1;
+            } else {
+                return Err(Status::invalid_argument(
+                    "import record is neither a node nor an edge",
+                ));
+            }
+        }
+
+        let progress = proto::ImportProgress {
+            nodes_loaded,
+            edges_loaded,
+            batches: 1,
+            responses_reindexed: 0,
+            duration_ms: i64::try_from(started.elapsed().as_millis()).unwrap_or(i64::MAX),
+            done: true,
+        };
+        Ok(Response::new(Box::pin(tokio_stream::once(Ok(progress)))))
+    }
+
+    // ========================================================================
+    // Edges
+    // ========================================================================
+
+    async fn create_edge(
+        &self,
+        request: Request<proto::CreateEdgeRequest>,
+    ) -> Result<Response<proto::Edge>, Status> {
+        self.state.check()?;
+        let req = request.into_inner();
+        let mut edge = req
+            .edge
+            .ok_or_else(|| Status::invalid_argument("edge is required"))?;
+        if edge.id.is_empty() {
+            edge.id = Uuid::new_v4().to_string();
+        }
+        if edge.created_at.is_none() {
+            edge.created_at = Some(datetime_to_proto(Utc::now()));
+        }
+        let edge = proto_to_edge(edge).map_err(invalid)?;
+
+        let edge = {
+            let mut graph = self.state.graph();
+            let replay = req
+                .idempotency_key
+                .as_ref()
+                .and_then(|key| graph.edge_keys.get(key))
+                .and_then(|id| graph.edges.iter().find(|edge| edge.id == *id));
+            if let Some(existing) = replay {
+                return Ok(Response::new(edge_to_proto(existing.clone())));
+            }
+            for id in [edge.from, edge.to] {
+                if graph.node(id).is_none() && !graph.sessions.iter().any(|s| s.node_id == id) {
+                    return Err(Status::not_found(format!("Node not found: {id}")));
+                }
+            }
+            if let Some(key) = req.idempotency_key {
+                graph.edge_keys.insert(key, edge.id);
+            }
+            graph.edges.push(edge.clone());
+            edge
+        };
+        self.state.emit_edge(proto::EventType::EdgeCreated, &edge);
+        Ok(Response::new(edge_to_proto(edge)))
+    }
+
+    async fn get_edges(
+        &self,
+        request: Request<proto::GetEdgesRequest>,
+    ) -> Result<Response<proto::GetEdgesResponse>, Status> {
+        self.state.check()?;
+        let req = request.into_inner();
+        let id = node_id(&req.node_id)?;
+        let direction = req
+            .direction
+            .and_then(|direction| proto::EdgeDirection::try_from(direction).ok())
+            .unwrap_or(proto::EdgeDirection::Both);
+        let edge_type = req
+            .r#type
+            .map(proto_to_edge_type)
+            .transpose()
+            .map_err(invalid)?;
+
+        let edges: Vec<Edge> = self
+            .state
+            .graph()
+            .edges
+            .iter()
+            .filter(|edge| match direction {
+                proto::EdgeDirection::Outgoing => edge.from == id,
+                proto::EdgeDirection::Incoming => edge.to == id,
+                _ => edge.from == id || edge.to == id,
+            })
+            .filter(|edge| {
+                edge_type
+                    .as_ref()
+                    .is_none_or(|edge_type| edge.edge_type == *edge_type)
+            })
+            .cloned()
+            .collect();
+        let (edges, next_cursor) = if req.limit > 0 || !req.cursor.is_empty() {
+            page(edges, |edge| edge.id.to_string(), &req.cursor, req.limit)?
+        } else {
+            (edges, String::new())
+        };
+        Ok(Response::new(proto::GetEdgesResponse {
+            edges: edges.into_iter().map(edge_to_proto).collect(),
+            next_cursor,
+        }))
+    }
+
+    async fn delete_edge(
+        &self,
+        request: Request<proto::DeleteEdgeRequest>,
+    ) -> Result<Response<()>, Status> {
+        self.state.check()?;
+        let id = request.into_inner().edge_id;
+        let edge = {
+            let mut graph = self.state.graph();
+            let index = graph
+                .edges
+                .iter()
+                .position(|edge| edge.id.to_string() == id)
+                .ok_or_else(|| Status::not_found(format!("Edge not found: {id}")))?;
+            graph.edges.remove(index)
+        };
+        self.state.emit_edge(proto::EventType::EdgeDeleted, &edge);
+        Ok(Response::new(()))
+    }
+
+    // ========================================================================
+    // Queries
+    // ========================================================================
+
+    async fn query(
+        &self,
+        request: Request<proto::QueryRequest>,
+    ) -> Result<Response<proto::QueryResponse>, Status> {
+        self.state.check()?;
+        let (nodes, total_count) = self.query_nodes(&request.into_inner())?;
+        Ok(Response::new(proto::QueryResponse {
+            nodes: nodes.into_iter().map(to_proto).collect::<Result<_, _>>()?,
+            total_count,
+        }))
+    }
+
+    async fn stream_query(
+        &self,
+        request: Request<proto::QueryRequest>,
+    ) -> Result<Response<Self::StreamQueryStream>, Status> {
+        self.state.check()?;
+        let (nodes, _) = self.query_nodes(&request.into_inner())?;
+        let nodes: Vec<_> = nodes.into_iter().map(to_proto).collect();
+        Ok(Response::new(Box::pin(tokio_stream::iter(nodes))))
+    }
+
+    // ========================================================================
+    // Prompts and responses
+    // ========================================================================
+
+    async fn add_prompt(
+        &self,
+        request: Request<proto::AddPromptRequest>,
+    ) -> Result<Response<proto::PromptNode>, Status> {
+        self.state.check()?;
+        let req = request.into_inner();
+        let id = session_id(&req.session_id)?;
+        let prompt = {
+            let mut graph = self.state.graph();
+            let replay = req
+                .idempotency_key
+                .as_ref()
+                .and_then(|key| graph.node_keys.get(key))
+                .and_then(|id| graph.node(*id));
+            if let Some(Node::Prompt(prompt)) = replay {
+                return Ok(Response::new(
+                    llm_memory_graph_client::convert::prompt_node_to_proto(prompt.clone()),
+                ));
+            }
+            if graph.session(&id).is_none() {
+                return Err(Status::not_found(format!("Session not found: {id}")));
+            }
+
+            let mut prompt = PromptNode::new(id, req.content);
+            if let Some(metadata) = req.metadata {
+                prompt.metadata = proto_to_prompt_metadata(metadata).map_err(invalid)?;
+            }
+            if let Some(key) = req.idempotency_key {
+                graph.node_keys.insert(key, prompt.id);
+            }
+            graph.add_node(Node::Prompt(prompt.clone()));
+            prompt
+        };
+        self.state
+            .emit_node(proto::EventType::NodeCreated, prompt.id, Some(id));
+        Ok(Response::new(
+            llm_memory_graph_client::convert::prompt_node_to_proto(prompt),
+        ))
+    }
+
+    async fn add_response(
+        &self,
+        request: Request<proto::AddResponseRequest>,
+    ) -> Result<Response<proto::ResponseNode>, Status> {
+        self.state.check()?;
+        let req = request.into_inner();
+        let prompt_id = node_id(&req.prompt_id)?;
+        let (response, session_id) = {
+            let mut graph = self.state.graph();
+            let replay = req
+                .idempotency_key
+                .as_ref()
+                .and_then(|key| graph.node_keys.get(key))
+                .and_then(|id| graph.node(*id));
+            if let Some(Node::Response(response)) = replay {
+                return Ok(Response::new(
+                    llm_memory_graph_client::convert::response_node_to_proto(response.clone()),
+                ));
+            }
+            let Some(prompt @ Node::Prompt(_)) = graph.node(prompt_id) else {
+                return Err(Status::not_found(format!("Prompt not found: {prompt_id}")));
+            };
+            let session_id = graph.session_of(prompt);
+
+            let usage = match &req.token_usage {
+                Some(usage) => proto_to_token_usage(usage).map_err(invalid)?,
+                None => TokenUsage::new(0, 0),
+            };
+            let mut response = ResponseNode::new(prompt_id, req.content, usage);
+            if let Some(metadata) = req.metadata {
+                response.metadata = proto_to_response_metadata(metadata).map_err(invalid)?;
+            }
+            if let Some(key) = req.idempotency_key {
+                graph.node_keys.insert(key, response.id);
+            }
+            graph.add_node(Node::Response(response.clone()));
+            (response, session_id)
+        };
+        self.state
+            .emit_node(proto::EventType::NodeCreated, response.id, session_id);
+        Ok(Response::new(
+            llm_memory_graph_client::convert::response_node_to_proto(response),
+        ))
+    }
+
+    async fn add_tool_invocation(
+        &self,
+        request: Request<proto::AddToolInvocationRequest>,
+    ) -> Result<Response<proto::ToolInvocationNode>, Status> {
+        self.state.check()?;
+        let tool = request
+            .into_inner()
+            .tool_invocation
+            .ok_or_else(|| Status::invalid_argument("tool_invocation is required"))?;
+        let tool = proto_to_tool_invocation(tool).map_err(invalid)?;
+        let session_id = {
+            let mut graph = self.state.graph();
+            if !matches!(graph.node(tool.response_id), Some(Node::Response(_))) {
+                return Err(Status::not_found(format!(
+                    "Response not found: {}",
+                    tool.response_id
+                )));
+            }
+            let node = Node::ToolInvocation(tool.clone());
+            graph.add_node(node.clone());
+            graph.session_of(&node)
+        };
+        self.state
+            .emit_node(proto::EventType::NodeCreated, tool.id, session_id);
+        Ok(Response::new(
+            llm_memory_graph_client::convert::tool_invocation_to_proto(tool),
+        ))
+    }
+
+    // ========================================================================
+    // Templates and agents
+    // ========================================================================
+
+    async fn create_template(
+        &self,
+        request: Request<proto::CreateTemplateRequest>,
+    ) -> Result<Response<proto::TemplateNode>, Status> {
+        self.state.check()?;
+        let mut template = request
+            .into_inner()
+            .template
+            .ok_or_else(|| Status::invalid_argument("template is required"))?;
+        if template.id.is_empty() {
+            template.id = Uuid::new_v4().to_string();
+        }
+        if template.created_at.is_none() {
+            template.created_at = Some(datetime_to_proto(Utc::now()));
+        }
+        let template = proto_to_template(NodeId::new(), template).map_err(invalid)?;
+        self.state
+            .graph()
+            .add_node(Node::Template(template.clone()));
+        self.state
+            .emit_node(proto::EventType::NodeCreated, template.node_id, None);
+        Ok(Response::new(template_to_proto(template)))
+    }
+
+    async fn instantiate_template(
+        &self,
+        request: Request<proto::InstantiateTemplateRequest>,
+    ) -> Result<Response<proto::PromptNode>, Status> {
+        self.state.check()?;
+        let req = request.into_inner();
+        let session = session_id(&req.session_id)?;
+        let prompt = {
+            let mut graph = self.state.graph();
+            if graph.session(&session).is_none() {
+                return Err(Status::not_found(format!("Session not found: {session}")));
+            }
+            let template = graph
+                .nodes
+                .iter_mut()
+                .find_map(|node| match node {
+                    Node::Template(template)
+                        if template.id.to_string() == req.template_id
+                            || template.node_id.to_string() == req.template_id =>
+                    {
+                        Some(template)
+                    }
+                    _ => None,
+                })
+                .ok_or_else(|| {
+                    Status::not_found(format!("Template not found: {}", req.template_id))
+                })?;
+            let content = template
+                .instantiate(&req.variable_values)
+                .map_err(Status::invalid_argument)?;
+            template.usage_count += 1;
+            let template_node = template.node_id;
+
+            let mut prompt = PromptNode::new(session, content);
+            prompt.template_id = Some(template.id);
+            prompt.variables = req.variable_values;
+            graph.add_node(Node::Prompt(prompt.clone()));
+            graph
+                .edges
+                .push(Edge::new(prompt.id, template_node, EdgeType::Instantiates));
+            prompt
+        };
+        self.state
+            .emit_node(proto::EventType::NodeCreated, prompt.id, Some(session));
+        Ok(Response::new(
+            llm_memory_graph_client::convert::prompt_node_to_proto(prompt),
+        ))
+    }
+
+    async fn list_templates(
+        &self,
+        request: Request<proto::ListTemplatesRequest>,
+    ) -> Result<Response<proto::ListTemplatesResponse>, Status> {
+        self.state.check()?;
+        let req = request.into_inner();
+        let templates: Vec<_> = self
+            .state
+            .graph()
+            .nodes
+            .iter()
+            .filter_map(|node| match node {
+                Node::Template(template) => Some(template.clone()),
+                _ => None,
+            })
+            .collect();
+        let (templates, next_cursor) = page(
+            templates,
+            |template| template.id.to_string(),
+            &req.cursor,
+            req.limit,
+        )?;
+        Ok(Response::new(proto::ListTemplatesResponse {
+            templates: templates.into_iter().map(template_to_proto).collect(),
+            next_cursor,
+        }))
+    }
+
+    async fn list_agents(
+        &self,
+        request: Request<proto::ListAgentsRequest>,
+    ) -> Result<Response<proto::ListAgentsResponse>, Status> {
+        self.state.check()?;
+        let req = request.into_inner();
+        let agents: Vec<_> = self
+            .state
+            .graph()
+            .nodes
+            .iter()
+            .filter_map(|node| match node {
+                Node::Agent(agent) => Some(agent.clone()),
+                _ => None,
+            })
+            .collect();
+        let (agents, next_cursor) =
+            page(agents, |agent| agent.id.to_string(), &req.cursor, req.limit)?;
+        Ok(Response::new(proto::ListAgentsResponse {
+            agents: agents.into_iter().map(agent_node_to_proto).collect(),
+            next_cursor,
+        }))
+    }
+
+    // ========================================================================
+    // Aliases
+    // ========================================================================
+
+    async fn set_alias(
+        &self,
+        request: Request<proto::SetAliasRequest>,
+    ) -> Result<Response<proto::Alias>, Status> {
+        self.state.check()?;
+        let req = request.into_inner();
+        let target = match req.target {
+            Some(proto::set_alias_request::Target::SessionId(id)) => {
+                AliasTarget::Session(session_id(&id)?)
+            }
+            Some(proto::set_alias_request::Target::NodeId(id)) => AliasTarget::Node(node_id(&id)?),
+            None => return Err(Status::invalid_argument("alias target is required")),
+        };
+        let mut graph = self.state.graph();
+        let exists = match target {
+            AliasTarget::Session(id) => graph.session(&id).is_some(),
+            AliasTarget::Node(id) => graph.node(id).is_some(),
+        };
+        if !exists {
+            return Err(Status::not_found(format!(
+                "Alias target not found: {target:?}"
+            )));
+        }
+        graph.aliases.insert(req.alias.clone(), target);
+        Ok(Response::new(alias_to_proto(req.alias, target)))
+    }
+
+    async fn resolve_alias(
+        &self,
+        request: Request<proto::ResolveAliasRequest>,
+    ) -> Result<Response<proto::Alias>, Status> {
+        self.state.check()?;
+        let alias = request.into_inner().alias;
+        let target = self
+            .state
+            .graph()
+            .aliases
+            .get(&alias)
+            .copied()
+            .ok_or_else(|| Status::not_found(format!("Alias not set: {alias}")))?;
+        Ok(Response::new(alias_to_proto(alias, target)))
+    }
+
+    async fn remove_alias(
+        &self,
+        request: Request<proto::RemoveAliasRequest>,
+    ) -> Result<Response<()>, Status> {
+        self.state.check()?;
+        self.state
+            .graph()
+            .aliases
+            .remove(&request.into_inner().alias);
+        Ok(Response::new(()))
+    }
+
+    async fn list_aliases(
+        &self,
+        request: Request<proto::ListAliasesRequest>,
+    ) -> Result<Response<proto::ListAliasesResponse>, Status> {
+        self.state.check()?;
+        let prefix = request.into_inner().prefix;
+        let aliases = self
+            .state
+            .graph()
+            .aliases
+            .iter()
+            .filter(|(alias, _)| alias.starts_with(&prefix))
+            .map(|(alias, target)| alias_to_proto(alias.clone(), *target))
+            .collect();
+        Ok(Response::new(proto::ListAliasesResponse { aliases }))
+    }
+
+    // ========================================================================
+    // Events
+    // ========================================================================
+
+    async fn stream_events(
+        &self,
+        request: Request<proto::StreamEventsRequest>,
+    ) -> Result<Response<Self::StreamEventsStream>, Status> {
+        self.state.check()?;
+        let req = request.into_inner();
+        let mut events = self.state.events.subscribe();
+        let stream = try_stream! {
+            loop {
+                let event = match events.recv().await {
+                    Ok(event) => event,
+                    Err(broadcast::error::RecvError::Closed) => break,
+                    Err(broadcast::error::RecvError::Lagged(missed)) => {
+                        Err(Status::data_loss(format!("subscriber missed {missed} events")))?
+                    }
+                };
+                let wanted_type =
+                    req.event_types.is_empty() || req.event_types.contains(&event.r#type);
+                if wanted_type && in_session(&event, req.session_id.as_deref()) {
+                    yield event;
+                }
+            }
+        };
+        Ok(Response::new(Box::pin(stream)))
+    }
+
+    async fn subscribe_to_session(
+        &self,
+        request: Request<proto::SubscribeRequest>,
+    ) -> Result<Response<Self::SubscribeToSessionStream>, Status> {
+        self.state.check()?;
+        let session_id = request.into_inner().session_id;
+        let mut events = self.state.events.subscribe();
+        let stream = try_stream! {
+            loop {
+                let event = match events.recv().await {
+                    Ok(event) => event,
+                    Err(broadcast::error::RecvError::Closed) => break,
+                    Err(broadcast::error::RecvError::Lagged(missed)) => {
+                        Err(Status::data_loss(format!("subscriber missed {missed} events")))?
+                    }
+                };
+                if in_session(&event, Some(&session_id)) {
+                    yield proto::SessionEvent {
+                        event: Some(event),
+                        session_id: session_id.clone(),
+                    };
+                }
+            }
+        };
+        Ok(Response::new(Box::pin(stream)))
+    }
+
+    // ========================================================================
+    // Health and metrics
+    // ========================================================================
+
+    async fn health(
+        &self,
+        _request: Request<()>,
+    ) -> Result<Response<proto::HealthResponse>, Status> {
+        self.state.check()?;
+        Ok(Response::new(proto::HealthResponse {
+            status: proto::health_response::ServingStatus::Serving.into(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            uptime_seconds: i64::try_from(self.state.started.elapsed().as_secs())
+                .unwrap_or(i64::MAX),
+            components: Vec::new(),
+        }))
+    }
+
+    async fn get_metrics(
+        &self,
+        _request: Request<()>,
+    ) -> Result<Response<proto::MetricsResponse>, Status> {
+        self.state.check()?;
+        let graph = self.state.graph();
+        Ok(Response::new(proto::MetricsResponse {
+            total_nodes: count(graph.nodes.len()),
+            total_edges: count(graph.edges.len()),
+            total_sessions: count(graph.sessions.len()),
+            active_sessions: count(
+                graph
+                    .sessions
+                    .iter()
+                    .filter(|session| session.status.is_open())
+                    .count(),
+            ),
+            avg_write_latency_ms: 0.0,
+            avg_read_latency_ms: 0.0,
+            requests_per_second: 0,
+        }))
+    }
+}
+
+fn alias_to_proto(alias: String, target: AliasTarget) -> proto::Alias {
+    let target = match target {
+        AliasTarget::Session(id) => proto::alias::Target::SessionId(id.to_string()),
+        AliasTarget::Node(id) => proto::alias::Target::NodeId(id.to_string()),
+    };
+    proto::Alias {
+        alias,
+        target: Some(target),
+    }
+}
+
+/// Whether `event` concerns `session_id`, or any session if it is `None`
+fn in_session(event: &proto::Event, session_id: Option<&str>) -> bool {
+    let Some(session_id) = session_id else {
+        return true;
+    };
+    serde_json::from_str::<serde_json::Value>(&event.payload)
+        .is_ok_and(|payload| payload["session_id"] == session_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::ConversationBuilder;
+    use llm_memory_graph_client::{CacheConfig, EventFilter};
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_client_round_trip() {
+        let server = MockServer::start().await.unwrap();
+        let client = server.client().await.unwrap();
+
+        let session_id = client.create_session(HashMap::new()).await.unwrap();
+        let prompt = client
+            .add_prompt(session_id.clone(), "What is Rust?".to_string(), None)
+            .await
+            .unwrap();
+        let response = client
+            .add_response(prompt.id.clone(), "A language.".to_string(), None, None)
+            .await
+            .unwrap();
+
+        let edges = server.edges();
+        assert_eq!(edges.len(), 2);
+        assert_eq!(edges[1].edge_type, EdgeType::RespondsTo);
+        assert_eq!(edges[1].from.to_string(), response.id);
+
+        let page = client
+            .list_session_nodes(session_id.clone(), None, 1)
+            .await
+            .unwrap();
+        assert_eq!(page.items.len(), 1);
+        let rest = client
+            .list_session_nodes(session_id, page.next_cursor, 1)
+            .await
+            .unwrap();
+        assert_eq!(rest.items[0].id().to_string(), response.id);
+        assert_eq!(rest.next_cursor, None);
+
+        server.fail_with(Some(Code::Unavailable));
+        assert!(client.health().await.is_err());
+        server.fail_with(None);
+        assert!(client.health().await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_events_invalidate_client_cache() {
+        let server = MockServer::start().await.unwrap();
+        let conversation = ConversationBuilder::new().turn("Hi", "Hello!").build();
+        server.seed(&conversation);
+
+        let writer = server.client().await.unwrap();
+        let mut events = writer.subscribe_events(EventFilter::new()).await.unwrap();
+        let reader = MemoryGraphClient::builder()
+            .address(server.address())
+            .cache(CacheConfig::default())
+            .build()
+            .await
+            .unwrap();
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while !reader.cache_stats().unwrap().live {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .unwrap();
+
+        let session_id = conversation.session.id.to_string();
+        reader.get_session(session_id.clone()).await.unwrap();
+        reader.get_session(session_id.clone()).await.unwrap();
+        assert_eq!(reader.cache_stats().unwrap().hits, 1);
+
+        writer
+            .add_prompt(session_id.clone(), "Again".to_string(), None)
+            .await
+            .unwrap();
+        let event = events.next().await.unwrap().unwrap();
+        assert_eq!(event.payload["session_id"], session_id.as_str());
+
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while reader.cache_stats().unwrap().invalidations == 0 {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .unwrap();
+        reader.get_session(session_id).await.unwrap();
+        assert_eq!(reader.cache_stats().unwrap().misses, 2);
+    }
+}
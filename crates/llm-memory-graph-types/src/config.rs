@@ -1,20 +1,191 @@
 //! Configuration for the memory graph
+//!
+//! A [`Config`] is usually built in code, but it can also be read from a TOML
+//! file with [`Config::from_file`] and from `LMG_*` environment variables with
+//! [`Config::from_env`]. The layers combine with [`Config::merge_env`], so
+//! environment variables override the file, which overrides the defaults:
+//!
+//! ```no_run
+//! use llm_memory_graph_types::Config;
+//!
+//! # fn main() -> llm_memory_graph_types::Result<()> {
+//! let config = Config::from_file("graph.toml")?.merge_env()?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! A file sets any subset of the fields, using their names as keys:
+//!
+//! ```toml
+//! path = "/var/lib/memory-graph"
+//! cache_size_mb = 512
+//! serialization_format = "messagepack"
+//! audit_log = true
+//!
+//! [compression]
+//! algorithm = "zstd"
+//! min_bytes = 4096
+//! node_types = ["Response"]
+//!
+//! [maintenance]
+//! trash_retention_ms = 86400000
+//!
+//! [[maintenance.retention_rules]]
+//! name = "ephemeral"
+//! tag = "ephemeral"
+//! retain_ms = 86400000
+//!
+//! [[maintenance.retention_rules]]
+//! name = "enterprise"
+//! metadata = { customer_tier = "enterprise" }
+//! retain_ms = 63072000000
+//! archive = true
+//!
+//! [cold_storage]
+//! path = "/mnt/archive/memory-graph-cold"
+//! compression_level = 19
+//!
+//! [slow_ops]
+//! query_ms = 250
+//!
+//! [observatory]
+//! enabled = true
+//! batch_size = 50
+//!
+//! [integrations.registry]
+//! url = "https://registry.example.com"
+//! api_key = "..."
+//! ```
+//!
+//! | Variable | Field |
+//! |----------|-------|
+//! | `LMG_DB_PATH` | `path` |
+//! | `LMG_NAMESPACE` | `namespace` |
+//! | `LMG_CACHE_SIZE_MB` | `cache_size_mb` |
+//! | `LMG_ENABLE_WAL` | `enable_wal` |
+//! | `LMG_COMPRESSION_LEVEL` | `compression_level` |
+//! | `LMG_COMPRESSION` | `compression.algorithm` |
+//! | `LMG_COMPRESSION_MIN_BYTES` | `compression.min_bytes` |
+//! | `LMG_FLUSH_INTERVAL_MS` | `flush_interval_ms` |
+//! | `LMG_SERIALIZATION_FORMAT` | `serialization_format` |
+//! | `LMG_READ_CONSISTENCY` | `read_consistency` |
+//! | `LMG_LOG_LEVEL` | `log_level` |
+//! | `LMG_LOG_CONTENT` | `log_content` |
+//! | `LMG_AUDIT_LOG` | `audit_log` |
+//! | `LMG_CHANGE_CAPTURE` | `change_capture` |
+//! | `LMG_READ_ONLY` | `read_only` |
+//! | `LMG_WAIT_FOR_LOCK_MS` | `wait_for_lock_ms` |
+//! | `LMG_DEDUPE_PROMPTS` | `dedupe_prompts` |
+//! | `LMG_IDEMPOTENCY_TTL_MS` | `idempotency_ttl_ms` |
+//! | `LMG_ID_SEED` | `id_seed` |
+//! | `LMG_PURGE_INTERVAL_MS` | `maintenance.purge_interval_ms` |
+//! | `LMG_TRASH_RETENTION_MS` | `maintenance.trash_retention_ms` |
+//! | `LMG_COMPACTION_INTERVAL_MS` | `maintenance.compaction_interval_ms` |
+//! | `LMG_GC_INTERVAL_MS` | `maintenance.gc_interval_ms` |
+//! | `LMG_ARCHIVE_INTERVAL_MS` | `maintenance.archive_interval_ms` |
+//! | `LMG_ARCHIVE_AFTER_MS` | `maintenance.archive_after_ms` |
+//! | `LMG_RETENTION_INTERVAL_MS` | `maintenance.retention_interval_ms` |
+//! | `LMG_REFERENCE_HALF_LIFE_MS` | `maintenance.reference_half_life_ms` |
+//! | `LMG_REFERENCE_FLOOR` | `maintenance.reference_floor` |
+//! | `LMG_REFERENCE_PRUNE_INTERVAL_MS` | `maintenance.reference_prune_interval_ms` |
+//! | `LMG_COLD_TIER_INTERVAL_MS` | `maintenance.cold_tier_interval_ms` |
+//! | `LMG_COLD_AFTER_MS` | `maintenance.cold_after_ms` |
+//! | `LMG_COLD_PATH` | `cold_storage.path` |
+//! | `LMG_COLD_COMPRESSION` | `cold_storage.compression` |
+//! | `LMG_COLD_COMPRESSION_LEVEL` | `cold_storage.compression_level` |
+//! | `LMG_SLOW_STORAGE_MS` | `slow_ops.storage_ms` |
+//! | `LMG_SLOW_QUERY_MS` | `slow_ops.query_ms` |
+//! | `LMG_SLOW_TRAVERSAL_MS` | `slow_ops.traversal_ms` |
+//! | `LMG_OBSERVATORY_ENABLED` | `observatory.enabled` |
+//! | `LMG_OBSERVATORY_BATCH_SIZE` | `observatory.batch_size` |
+//! | `LMG_OBSERVATORY_FLUSH_INTERVAL_MS` | `observatory.flush_interval_ms` |
+//! | `LMG_OBSERVATORY_METRICS` | `observatory.enable_metrics` |
+//! | `LMG_OBSERVATORY_QUEUE_CAPACITY` | `observatory.queue_capacity` |
+//! | `LMG_REGISTRY_URL`, `LMG_REGISTRY_API_KEY` | `integrations.registry` |
+//! | `LMG_VAULT_URL`, `LMG_VAULT_API_KEY` | `integrations.vault` |
+//!
+//! Booleans accept `true`/`false`, `1`/`0`, `yes`/`no` and `on`/`off`.
 
-use std::path::PathBuf;
+use crate::error::{Error, Result};
+use crate::ingest::IngestValidation;
+use crate::nodes::{ConversationSession, NodeType};
+use crate::schema::GraphSchema;
+use crate::views::ViewDefinition;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Prefix of the environment variables read by [`Config::from_env`]
+pub const ENV_PREFIX: &str = "LMG_";
 
 /// Configuration for `MemoryGraph`
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+#[allow(clippy::struct_excessive_bools)] // independent feature switches
 pub struct Config {
     /// Path to the database directory
     pub path: PathBuf,
+    /// Logical graph within the database; `None` opens the default namespace
+    pub namespace: Option<String>,
     /// Cache size in megabytes
     pub cache_size_mb: usize,
     /// Enable write-ahead logging for durability
     pub enable_wal: bool,
     /// Compression level (0-9, 0 = no compression)
     pub compression_level: u8,
+    /// Which nodes are compressed in storage, and with what
+    pub compression: CompressionPolicy,
     /// Flush interval in milliseconds (0 = sync every write)
     pub flush_interval_ms: u64,
+    /// Encoding of stored nodes and edges; must match the format the
+    /// database was written with
+    pub serialization_format: SerializationFormat,
+    /// Consistency of reads that do not ask for one
+    pub read_consistency: ReadConsistency,
+    /// Level every mutation is logged at through `tracing`
+    pub log_level: LogLevel,
+    /// Include the stored node or edge in mutation logs; for debugging only,
+    /// as it writes prompt and response text to the logs
+    pub log_content: bool,
+    /// Record every mutation in the append-only audit log
+    pub audit_log: bool,
+    /// Record node and edge changes for change data capture consumers
+    pub change_capture: bool,
+    /// Reject every write made through the graph, as on a replication follower
+    pub read_only: bool,
+    /// How long to wait for another process to close the database before
+    /// failing with [`Error::DatabaseLocked`], in milliseconds; `None` fails
+    /// at once
+    pub wait_for_lock_ms: Option<u64>,
+    /// Reuse an identical prompt already in the session instead of storing a copy
+    pub dedupe_prompts: bool,
+    /// How long an idempotency key keeps returning the result of its first
+    /// write, in milliseconds
+    pub idempotency_ttl_ms: u64,
+    /// Generate IDs deterministically from this seed instead of at random,
+    /// see [`SeededIds`](crate::SeededIds)
+    pub id_seed: Option<u64>,
+    /// Data quality checks applied to prompts and responses before they are
+    /// stored
+    pub ingest: IngestValidation,
+    /// Materialized views defined, or redefined, when the graph opens
+    pub views: Vec<ViewDefinition>,
+    /// Background maintenance schedule
+    pub maintenance: MaintenanceConfig,
+    /// Where and how nodes and edges moved to the cold tier are stored
+    pub cold_storage: ColdStorageConfig,
+    /// Durations above which operations are logged as slow
+    pub slow_ops: SlowOpConfig,
+    /// Observatory event publishing settings
+    pub observatory: ObservatorySettings,
+    /// Connections to external services
+    pub integrations: IntegrationSettings,
+    /// Structural rules checked on write (acyclic FOLLOWS and INHERITS edges
+    /// by default)
+    #[serde(skip)]
+    pub schema: GraphSchema,
 }
 
 impl Config {
@@ -23,13 +194,368 @@ impl Config {
     pub fn new<P: Into<PathBuf>>(path: P) -> Self {
         Self {
             path: path.into(),
-            cache_size_mb: 100,
-            enable_wal: true,
-            compression_level: 3,
-            flush_interval_ms: 1000,
+            ..Self::default()
+        }
+    }
+
+    /// Read a configuration from a TOML file
+    ///
+    /// Fields missing from the file keep their defaults.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::ConfigError`] if the file cannot be read, is not valid
+    /// TOML, contains an unknown key or fails [`validate`](Self::validate).
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| Error::ConfigError(format!("cannot read {}: {e}", path.display())))?;
+        let config: Self = toml::from_str(&text)
+            .map_err(|e| Error::ConfigError(format!("{}: {e}", path.display())))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Read a configuration from `LMG_*` environment variables
+    ///
+    /// Variables that are not set keep their defaults. See the
+    /// [module documentation](self) for the variable names.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::ConfigError`] if a variable cannot be parsed or the
+    /// result fails [`validate`](Self::validate).
+    pub fn from_env() -> Result<Self> {
+        Self::default().merge_env()
+    }
+
+    /// Override fields with the `LMG_*` environment variables that are set
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::ConfigError`] if a variable cannot be parsed or the
+    /// result fails [`validate`](Self::validate).
+    pub fn merge_env(self) -> Result<Self> {
+        self.merge_vars(|name| std::env::var(name).ok())
+    }
+
+    fn merge_vars(mut self, lookup: impl Fn(&str) -> Option<String>) -> Result<Self> {
+        let env = EnvVars { lookup };
+
+        if let Some(path) = env.string("DB_PATH") {
+            self.path = path.into();
+        }
+        if let Some(namespace) = env.string("NAMESPACE") {
+            self.namespace = Some(namespace);
+        }
+        env.set(
+            &mut self.cache_size_mb,
+            "CACHE_SIZE_MB",
+            "a size in megabytes",
+        )?;
+        env.flag(&mut self.enable_wal, "ENABLE_WAL")?;
+        env.set(
+            &mut self.compression_level,
+            "COMPRESSION_LEVEL",
+            "a level from 0 to 9",
+        )?;
+        env.set(
+            &mut self.compression.algorithm,
+            "COMPRESSION",
+            "none, zstd or lz4",
+        )?;
+        env.set(
+            &mut self.compression.min_bytes,
+            "COMPRESSION_MIN_BYTES",
+            "a size in bytes",
+        )?;
+        env.set(
+            &mut self.flush_interval_ms,
+            "FLUSH_INTERVAL_MS",
+            "milliseconds",
+        )?;
+        env.set(
+            &mut self.serialization_format,
+            "SERIALIZATION_FORMAT",
+            "json, messagepack or bincode",
+        )?;
+        env.set(
+            &mut self.read_consistency,
+            "READ_CONSISTENCY",
+            "cache_ok, storage_only or require_flushed",
+        )?;
+        env.set(
+            &mut self.log_level,
+            "LOG_LEVEL",
+            "off, error, warn, info, debug or trace",
+        )?;
+        env.flag(&mut self.log_content, "LOG_CONTENT")?;
+        env.flag(&mut self.audit_log, "AUDIT_LOG")?;
+        env.flag(&mut self.change_capture, "CHANGE_CAPTURE")?;
+        env.flag(&mut self.read_only, "READ_ONLY")?;
+        if env.string("WAIT_FOR_LOCK_MS").is_some() {
+            let mut timeout_ms = 0;
+            env.set(&mut timeout_ms, "WAIT_FOR_LOCK_MS", "milliseconds")?;
+            self.wait_for_lock_ms = Some(timeout_ms);
+        }
+        env.flag(&mut self.dedupe_prompts, "DEDUPE_PROMPTS")?;
+        env.set(
+            &mut self.idempotency_ttl_ms,
+            "IDEMPOTENCY_TTL_MS",
+            "milliseconds",
+        )?;
+        if env.string("ID_SEED").is_some() {
+            let mut seed = 0;
+            env.set(&mut seed, "ID_SEED", "an unsigned integer")?;
+            self.id_seed = Some(seed);
+        }
+        self.merge_section_vars(&env)?;
+        self.validate()?;
+        Ok(self)
+    }
+
+    /// Override maintenance and cold storage fields from environment
+    /// variables
+    fn merge_maintenance_vars<F: Fn(&str) -> Option<String>>(
+        &mut self,
+        env: &EnvVars<F>,
+    ) -> Result<()> {
+        let maintenance = &mut self.maintenance;
+        env.set(
+            &mut maintenance.purge_interval_ms,
+            "PURGE_INTERVAL_MS",
+            "milliseconds",
+        )?;
+        env.set(
+            &mut maintenance.trash_retention_ms,
+            "TRASH_RETENTION_MS",
+            "milliseconds",
+        )?;
+        env.set(
+            &mut maintenance.compaction_interval_ms,
+            "COMPACTION_INTERVAL_MS",
+            "milliseconds",
+        )?;
+        env.set(
+            &mut maintenance.gc_interval_ms,
+            "GC_INTERVAL_MS",
+            "milliseconds",
+        )?;
+        env.set(
+            &mut maintenance.archive_interval_ms,
+            "ARCHIVE_INTERVAL_MS",
+            "milliseconds",
+        )?;
+        env.set(
+            &mut maintenance.archive_after_ms,
+            "ARCHIVE_AFTER_MS",
+            "milliseconds",
+        )?;
+        env.set(
+            &mut maintenance.retention_interval_ms,
+            "RETENTION_INTERVAL_MS",
+            "milliseconds",
+        )?;
+        env.set(
+            &mut maintenance.reference_half_life_ms,
+            "REFERENCE_HALF_LIFE_MS",
+            "milliseconds",
+        )?;
+        env.set(
+            &mut maintenance.reference_floor,
+            "REFERENCE_FLOOR",
+            "a relevance from 0.0 to 1.0",
+        )?;
+        env.set(
+            &mut maintenance.reference_prune_interval_ms,
+            "REFERENCE_PRUNE_INTERVAL_MS",
+            "milliseconds",
+        )?;
+        env.set(
+            &mut maintenance.cold_tier_interval_ms,
+            "COLD_TIER_INTERVAL_MS",
+            "milliseconds",
+        )?;
+        env.set(
+            &mut maintenance.cold_after_ms,
+            "COLD_AFTER_MS",
+            "milliseconds",
+        )?;
+
+        let cold_storage = &mut self.cold_storage;
+        if let Some(path) = env.string("COLD_PATH") {
+            cold_storage.path = Some(path.into());
+        }
+        env.set(
+            &mut cold_storage.compression,
+            "COLD_COMPRESSION",
+            "none, zstd or lz4",
+        )?;
+        env.set(
+            &mut cold_storage.compression_level,
+            "COLD_COMPRESSION_LEVEL",
+            "a level from 0 to 22",
+        )?;
+        Ok(())
+    }
+
+    /// Override fields of the nested sections from environment variables
+    fn merge_section_vars<F: Fn(&str) -> Option<String>>(
+        &mut self,
+        env: &EnvVars<F>,
+    ) -> Result<()> {
+        self.merge_maintenance_vars(env)?;
+
+        let slow_ops = &mut self.slow_ops;
+        env.set(&mut slow_ops.storage_ms, "SLOW_STORAGE_MS", "milliseconds")?;
+        env.set(&mut slow_ops.query_ms, "SLOW_QUERY_MS", "milliseconds")?;
+        env.set(
+            &mut slow_ops.traversal_ms,
+            "SLOW_TRAVERSAL_MS",
+            "milliseconds",
+        )?;
+
+        let observatory = &mut self.observatory;
+        env.flag(&mut observatory.enabled, "OBSERVATORY_ENABLED")?;
+        env.set(
+            &mut observatory.batch_size,
+            "OBSERVATORY_BATCH_SIZE",
+            "a number of events",
+        )?;
+        env.set(
+            &mut observatory.flush_interval_ms,
+            "OBSERVATORY_FLUSH_INTERVAL_MS",
+            "milliseconds",
+        )?;
+        env.flag(&mut observatory.enable_metrics, "OBSERVATORY_METRICS")?;
+        env.set(
+            &mut observatory.queue_capacity,
+            "OBSERVATORY_QUEUE_CAPACITY",
+            "a number of events",
+        )?;
+
+        env.service(&mut self.integrations.registry, "REGISTRY")?;
+        env.service(&mut self.integrations.vault, "VAULT")?;
+
+        Ok(())
+    }
+
+    /// Check that every field holds a usable value
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::ConfigError`] listing every problem found.
+    pub fn validate(&self) -> Result<()> {
+        let mut problems = Vec::new();
+
+        if self.path.as_os_str().is_empty() {
+            problems.push("path must not be empty".to_string());
+        }
+        if self.namespace.as_deref().is_some_and(str::is_empty) {
+            problems.push("namespace must not be empty; omit it to use the default".to_string());
+        }
+        if self.cache_size_mb == 0 {
+            problems.push("cache_size_mb must be at least 1".to_string());
+        }
+        if self.compression_level > 9 {
+            problems.push(format!(
+                "compression_level must be between 0 and 9, got {}",
+                self.compression_level
+            ));
+        }
+        if !(0.0..=1.0).contains(&self.maintenance.jitter) {
+            problems.push(format!(
+                "maintenance.jitter must be between 0.0 and 1.0, got {}",
+                self.maintenance.jitter
+            ));
+        }
+        if !(0.0..=1.0).contains(&self.maintenance.reference_floor) {
+            problems.push(format!(
+                "maintenance.reference_floor must be between 0.0 and 1.0, got {}",
+                self.maintenance.reference_floor
+            ));
+        }
+        if self.cold_storage.compression_level > MAX_COLD_COMPRESSION_LEVEL {
+            problems.push(format!(
+                "cold_storage.compression_level must be between 0 and {MAX_COLD_COMPRESSION_LEVEL}, got {}",
+                self.cold_storage.compression_level
+            ));
+        }
+        if self.cold_storage.path.as_deref() == Some(self.path.as_path()) {
+            problems.push(
+                "cold_storage.path must differ from path; omit it to keep the cold tier in the same database"
+                    .to_string(),
+            );
+        }
+        if self.observatory.batch_size == 0 {
+            problems.push("observatory.batch_size must be at least 1".to_string());
+        }
+        if self.observatory.queue_capacity == 0 {
+            problems.push("observatory.queue_capacity must be at least 1".to_string());
+        }
+        for (name, service) in [
+            ("registry", &self.integrations.registry),
+            ("vault", &self.integrations.vault),
+        ] {
+            let Some(service) = service else { continue };
+            if !(service.url.starts_with("http://") || service.url.starts_with("https://")) {
+                problems.push(format!(
+                    "integrations.{name}.url must start with http:// or https://, got {:?}",
+                    service.url
+                ));
+            }
+            if service.timeout_secs == Some(0) {
+                problems.push(format!(
+                    "integrations.{name}.timeout_secs must be at least 1"
+                ));
+            }
+        }
+        let rules = &self.maintenance.retention_rules;
+        for (i, rule) in rules.iter().enumerate() {
+            if rule.name.is_empty() {
+                problems.push(format!(
+                    "maintenance.retention_rules[{i}].name must not be empty"
+                ));
+            } else if rules[..i].iter().any(|other| other.name == rule.name) {
+                problems.push(format!(
+                    "maintenance.retention_rules[{i}]: duplicate rule name {:?}",
+                    rule.name
+                ));
+            }
+        }
+        for (i, view) in self.views.iter().enumerate() {
+            if let Err(e) = view.validate() {
+                problems.push(format!("views[{i}]: {e}"));
+            } else if self.views[..i].iter().any(|other| other.name == view.name) {
+                problems.push(format!("views[{i}]: duplicate view name {:?}", view.name));
+            }
+        }
+        if self
+            .integrations
+            .vault
+            .as_ref()
+            .is_some_and(|vault| vault.api_key.as_deref().unwrap_or_default().is_empty())
+        {
+            problems.push("integrations.vault.api_key is required".to_string());
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::ConfigError(format!(
+                "invalid configuration: {}",
+                problems.join("; ")
+            )))
         }
     }
 
+    /// Open the named namespace instead of the default one
+    #[must_use]
+    pub fn with_namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.namespace = Some(namespace.into());
+        self
+    }
+
     /// Set the cache size
     #[must_use]
     pub fn with_cache_size(mut self, size_mb: usize) -> Self {
@@ -51,35 +577,907 @@ impl Config {
         self
     }
 
+    /// Set which nodes are compressed in storage
+    ///
+    /// Zstd uses [`compression_level`](Self::compression_level); a level of
+    /// 0 turns compression off.
+    #[must_use]
+    pub fn with_compression_policy(mut self, policy: CompressionPolicy) -> Self {
+        self.compression = policy;
+        self
+    }
+
     /// Set flush interval in milliseconds
     #[must_use]
     pub const fn with_flush_interval(mut self, interval_ms: u64) -> Self {
         self.flush_interval_ms = interval_ms;
         self
     }
+
+    /// Set the encoding of stored nodes and edges
+    #[must_use]
+    pub const fn with_serialization_format(mut self, format: SerializationFormat) -> Self {
+        self.serialization_format = format;
+        self
+    }
+
+    /// Set the consistency of reads that do not ask for one
+    #[must_use]
+    pub const fn with_read_consistency(mut self, consistency: ReadConsistency) -> Self {
+        self.read_consistency = consistency;
+        self
+    }
+
+    /// Set the level mutations are logged at
+    #[must_use]
+    pub const fn with_log_level(mut self, level: LogLevel) -> Self {
+        self.log_level = level;
+        self
+    }
+
+    /// Include or leave out node and edge contents in mutation logs
+    #[must_use]
+    pub const fn with_log_content(mut self, enable: bool) -> Self {
+        self.log_content = enable;
+        self
+    }
+
+    /// Enable or disable the audit log
+    #[must_use]
+    pub const fn with_audit_log(mut self, enable: bool) -> Self {
+        self.audit_log = enable;
+        self
+    }
+
+    /// Enable or disable change data capture
+    #[must_use]
+    pub const fn with_change_capture(mut self, enable: bool) -> Self {
+        self.change_capture = enable;
+        self
+    }
+
+    /// Open the graph read-only
+    #[must_use]
+    pub const fn with_read_only(mut self, enable: bool) -> Self {
+        self.read_only = enable;
+        self
+    }
+
+    /// Enable or disable prompt deduplication
+    #[must_use]
+    pub const fn with_dedupe_prompts(mut self, enable: bool) -> Self {
+        self.dedupe_prompts = enable;
+        self
+    }
+
+    /// Set how long idempotency keys are remembered
+    #[must_use]
+    pub const fn with_idempotency_ttl(mut self, ttl_ms: u64) -> Self {
+        self.idempotency_ttl_ms = ttl_ms;
+        self
+    }
+
+    /// Generate IDs deterministically from `seed`, for reproducible tests
+    /// and pipelines
+    #[must_use]
+    pub const fn with_id_seed(mut self, seed: u64) -> Self {
+        self.id_seed = Some(seed);
+        self
+    }
+
+    /// Set the thresholds above which operations are logged as slow
+    #[must_use]
+    pub fn with_slow_ops(mut self, slow_ops: SlowOpConfig) -> Self {
+        self.slow_ops = slow_ops;
+        self
+    }
+
+    /// Set the background maintenance schedule
+    #[must_use]
+    pub fn with_maintenance(mut self, maintenance: MaintenanceConfig) -> Self {
+        self.maintenance = maintenance;
+        self
+    }
+
+    /// Set where and how the cold tier is stored
+    #[must_use]
+    pub fn with_cold_storage(mut self, cold_storage: ColdStorageConfig) -> Self {
+        self.cold_storage = cold_storage;
+        self
+    }
+
+    /// Set the Observatory publishing settings
+    #[must_use]
+    pub const fn with_observatory(mut self, observatory: ObservatorySettings) -> Self {
+        self.observatory = observatory;
+        self
+    }
+
+    /// Set the external service connections
+    #[must_use]
+    pub fn with_integrations(mut self, integrations: IntegrationSettings) -> Self {
+        self.integrations = integrations;
+        self
+    }
+
+    /// Wait up to `timeout_ms` for the database lock when another process
+    /// holds it
+    #[must_use]
+    pub const fn with_wait_for_lock(mut self, timeout_ms: u64) -> Self {
+        self.wait_for_lock_ms = Some(timeout_ms);
+        self
+    }
+
+    /// Set the data quality checks applied to prompts and responses
+    #[must_use]
+    pub fn with_ingest_validation(mut self, ingest: IngestValidation) -> Self {
+        self.ingest = ingest;
+        self
+    }
+
+    /// Define a materialized view when the graph opens
+    #[must_use]
+    pub fn with_view(mut self, view: ViewDefinition) -> Self {
+        self.views.push(view);
+        self
+    }
+
+    /// Set the structural rules checked on write
+    #[must_use]
+    pub fn with_schema(mut self, schema: GraphSchema) -> Self {
+        self.schema = schema;
+        self
+    }
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
             path: PathBuf::from("./data/graph.db"),
+            namespace: None,
             cache_size_mb: 100,
             enable_wal: true,
             compression_level: 3,
+            compression: CompressionPolicy::default(),
             flush_interval_ms: 1000,
+            serialization_format: SerializationFormat::default(),
+            read_consistency: ReadConsistency::default(),
+            log_level: LogLevel::default(),
+            log_content: false,
+            audit_log: false,
+            change_capture: false,
+            read_only: false,
+            wait_for_lock_ms: None,
+            dedupe_prompts: false,
+            idempotency_ttl_ms: 24 * 60 * 60 * 1000,
+            id_seed: None,
+            ingest: IngestValidation::default(),
+            views: Vec::new(),
+            maintenance: MaintenanceConfig::default(),
+            cold_storage: ColdStorageConfig::default(),
+            slow_ops: SlowOpConfig::default(),
+            observatory: ObservatorySettings::default(),
+            integrations: IntegrationSettings::default(),
+            schema: GraphSchema::dag(),
+        }
+    }
+}
+
+/// Encoding of nodes and edges in storage
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SerializationFormat {
+    /// JSON format (human-readable, slower)
+    Json,
+    /// `MessagePack` format (binary, faster)
+    #[default]
+    #[serde(alias = "msgpack")]
+    MessagePack,
+    /// Bincode format (binary, fastest; cannot store JSON values such as
+    /// properties, tool parameters or custom payloads)
+    Bincode,
+}
+
+impl FromStr for SerializationFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "json" => Ok(Self::Json),
+            "messagepack" | "msgpack" => Ok(Self::MessagePack),
+            "bincode" => Ok(Self::Bincode),
+            other => Err(Error::ConfigError(format!(
+                "unknown serialization format {other:?}"
+            ))),
+        }
+    }
+}
+
+/// How fresh and durable the data a read returns must be
+///
+/// Stronger levels trade latency for visibility: [`CacheOk`](Self::CacheOk)
+/// answers from the read cache when it can, [`StorageOnly`](Self::StorageOnly)
+/// always reads storage, and [`RequireFlushed`](Self::RequireFlushed) also
+/// flushes pending writes first, so everything it returns would survive a
+/// crash.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReadConsistency {
+    /// Serve reads from the cache when possible (lowest latency)
+    #[default]
+    CacheOk,
+    /// Bypass the cache and read storage
+    StorageOnly,
+    /// Flush pending writes to disk, then read storage
+    RequireFlushed,
+}
+
+impl FromStr for ReadConsistency {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().replace('-', "_").as_str() {
+            "cache_ok" => Ok(Self::CacheOk),
+            "storage_only" => Ok(Self::StorageOnly),
+            "require_flushed" => Ok(Self::RequireFlushed),
+            other => Err(Error::ConfigError(format!(
+                "unknown read consistency {other:?}"
+            ))),
+        }
+    }
+}
+
+/// Level of the `tracing` events a graph emits for its mutations
+///
+/// Events are only written when the subscriber also enables the level;
+/// [`Off`](Self::Off) skips them altogether.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    /// Log no mutations
+    Off,
+    /// Log at `ERROR`
+    Error,
+    /// Log at `WARN`
+    Warn,
+    /// Log at `INFO`
+    Info,
+    /// Log at `DEBUG`
+    #[default]
+    Debug,
+    /// Log at `TRACE`
+    Trace,
+}
+
+impl FromStr for LogLevel {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "off" | "none" => Ok(Self::Off),
+            "error" => Ok(Self::Error),
+            "warn" | "warning" => Ok(Self::Warn),
+            "info" => Ok(Self::Info),
+            "debug" => Ok(Self::Debug),
+            "trace" => Ok(Self::Trace),
+            other => Err(Error::ConfigError(format!("unknown log level {other:?}"))),
         }
     }
 }
 
+/// Algorithm nodes are compressed with in storage
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CompressionAlgorithm {
+    /// Store nodes uncompressed
+    #[default]
+    None,
+    /// Zstandard (better ratio)
+    Zstd,
+    /// LZ4 (faster)
+    Lz4,
+}
+
+impl FromStr for CompressionAlgorithm {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "none" => Ok(Self::None),
+            "zstd" => Ok(Self::Zstd),
+            "lz4" => Ok(Self::Lz4),
+            other => Err(Error::ConfigError(format!(
+                "unknown compression algorithm {other:?}"
+            ))),
+        }
+    }
+}
+
+/// Which nodes are compressed in storage
+///
+/// Compression is transparent: compressed and uncompressed nodes can be
+/// mixed in one database and are read back alike, so the policy can change
+/// between runs. Edges are never compressed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CompressionPolicy {
+    /// Algorithm to compress with; `None` stores every node uncompressed
+    pub algorithm: CompressionAlgorithm,
+    /// Smallest encoded node compressed, in bytes
+    pub min_bytes: usize,
+    /// Types of node compressed; empty compresses every type
+    pub node_types: Vec<NodeType>,
+}
+
+impl CompressionPolicy {
+    /// Compress nodes of every type with `algorithm` once they reach the
+    /// default size
+    pub fn new(algorithm: CompressionAlgorithm) -> Self {
+        Self {
+            algorithm,
+            ..Self::default()
+        }
+    }
+
+    /// Compress only nodes of at least `min_bytes` once encoded
+    #[must_use]
+    pub const fn with_min_bytes(mut self, min_bytes: usize) -> Self {
+        self.min_bytes = min_bytes;
+        self
+    }
+
+    /// Compress nodes of `node_type`, and no other type not also added
+    #[must_use]
+    pub fn with_node_type(mut self, node_type: NodeType) -> Self {
+        self.node_types.push(node_type);
+        self
+    }
+
+    /// Whether a node of `node_type` encoded in `len` bytes is compressed
+    pub fn applies_to(&self, node_type: &NodeType, len: usize) -> bool {
+        self.algorithm != CompressionAlgorithm::None
+            && len >= self.min_bytes
+            && (self.node_types.is_empty() || self.node_types.contains(node_type))
+    }
+}
+
+impl Default for CompressionPolicy {
+    fn default() -> Self {
+        Self {
+            algorithm: CompressionAlgorithm::None,
+            min_bytes: 1024,
+            node_types: Vec::new(),
+        }
+    }
+}
+
+/// Schedule of the background maintenance tasks
+///
+/// Every interval is in milliseconds; an interval of 0 disables the task.
+/// Periodic flushing uses [`Config::flush_interval_ms`].
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MaintenanceConfig {
+    /// How often cache statistics are published to the Observatory
+    pub cache_stats_interval_ms: u64,
+    /// How often the trash is pruned
+    pub purge_interval_ms: u64,
+    /// How long deleted nodes stay in the trash before pruning removes them
+    pub trash_retention_ms: u64,
+    /// How often dangling index entries are compacted away
+    pub compaction_interval_ms: u64,
+    /// How often orphaned nodes and dangling edges are garbage collected
+    pub gc_interval_ms: u64,
+    /// How often idle sessions are archived (requires an archiver)
+    pub archive_interval_ms: u64,
+    /// Archive sessions not updated for this long
+    pub archive_after_ms: u64,
+    /// How often the retention rules are applied
+    pub retention_interval_ms: u64,
+    /// Retention rules, in priority order; the first rule matching a session
+    /// decides how long it is kept
+    pub retention_rules: Vec<RetentionRule>,
+    /// Time for the relevance of a REFERENCES edge to halve; 0 disables decay
+    pub reference_half_life_ms: u64,
+    /// Decayed relevance below which REFERENCES edges are pruned
+    pub reference_floor: f32,
+    /// How often decayed REFERENCES edges are pruned (requires a half-life)
+    pub reference_prune_interval_ms: u64,
+    /// How often old nodes and edges are moved to the cold tier
+    pub cold_tier_interval_ms: u64,
+    /// Move nodes and edges created longer ago than this to the cold tier;
+    /// 0 keeps everything in the hot tier
+    pub cold_after_ms: u64,
+    /// Random spread applied to every interval, as a fraction (0.0-1.0)
+    pub jitter: f64,
+}
+
+impl Default for MaintenanceConfig {
+    fn default() -> Self {
+        const HOUR_MS: u64 = 60 * 60 * 1000;
+        Self {
+            cache_stats_interval_ms: 60 * 1000,
+            purge_interval_ms: HOUR_MS,
+            trash_retention_ms: 7 * 24 * HOUR_MS,
+            compaction_interval_ms: 24 * HOUR_MS,
+            gc_interval_ms: 24 * HOUR_MS,
+            archive_interval_ms: 24 * HOUR_MS,
+            archive_after_ms: 30 * 24 * HOUR_MS,
+            retention_interval_ms: HOUR_MS,
+            retention_rules: Vec::new(),
+            reference_half_life_ms: 0,
+            reference_floor: 0.05,
+            reference_prune_interval_ms: 24 * HOUR_MS,
+            cold_tier_interval_ms: 24 * HOUR_MS,
+            cold_after_ms: 0,
+            jitter: 0.1,
+        }
+    }
+}
+
+impl MaintenanceConfig {
+    /// Default schedule
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the cache statistics publication interval
+    #[must_use]
+    pub const fn with_cache_stats_interval(mut self, interval_ms: u64) -> Self {
+        self.cache_stats_interval_ms = interval_ms;
+        self
+    }
+
+    /// Set the trash pruning interval and how long trashed nodes are kept
+    #[must_use]
+    pub const fn with_trash_retention(mut self, interval_ms: u64, retention_ms: u64) -> Self {
+        self.purge_interval_ms = interval_ms;
+        self.trash_retention_ms = retention_ms;
+        self
+    }
+
+    /// Set the index compaction interval
+    #[must_use]
+    pub const fn with_compaction_interval(mut self, interval_ms: u64) -> Self {
+        self.compaction_interval_ms = interval_ms;
+        self
+    }
+
+    /// Set the garbage collection interval
+    #[must_use]
+    pub const fn with_gc_interval(mut self, interval_ms: u64) -> Self {
+        self.gc_interval_ms = interval_ms;
+        self
+    }
+
+    /// Set the archival interval and the idle age at which sessions are archived
+    #[must_use]
+    pub const fn with_archival(mut self, interval_ms: u64, archive_after_ms: u64) -> Self {
+        self.archive_interval_ms = interval_ms;
+        self.archive_after_ms = archive_after_ms;
+        self
+    }
+
+    /// Set how often the retention rules are applied
+    #[must_use]
+    pub const fn with_retention_interval(mut self, interval_ms: u64) -> Self {
+        self.retention_interval_ms = interval_ms;
+        self
+    }
+
+    /// Add a retention rule after the existing ones
+    #[must_use]
+    pub fn with_retention_rule(mut self, rule: RetentionRule) -> Self {
+        self.retention_rules.push(rule);
+        self
+    }
+
+    /// Decay the relevance of REFERENCES edges with `half_life_ms`, pruning
+    /// those that fall below `floor`
+    #[must_use]
+    pub const fn with_reference_decay(mut self, half_life_ms: u64, floor: f32) -> Self {
+        self.reference_half_life_ms = half_life_ms;
+        self.reference_floor = floor;
+        self
+    }
+
+    /// Set how often decayed REFERENCES edges are pruned
+    #[must_use]
+    pub const fn with_reference_prune_interval(mut self, interval_ms: u64) -> Self {
+        self.reference_prune_interval_ms = interval_ms;
+        self
+    }
+
+    /// Set the cold tiering interval and the age at which nodes and edges
+    /// move to the cold tier
+    #[must_use]
+    pub const fn with_cold_tiering(mut self, interval_ms: u64, cold_after_ms: u64) -> Self {
+        self.cold_tier_interval_ms = interval_ms;
+        self.cold_after_ms = cold_after_ms;
+        self
+    }
+
+    /// Set the interval jitter (clamped to 0.0-1.0)
+    #[must_use]
+    pub fn with_jitter(mut self, jitter: f64) -> Self {
+        self.jitter = jitter.clamp(0.0, 1.0);
+        self
+    }
+
+    /// The first retention rule matching `session`
+    #[must_use]
+    pub fn retention_rule_for(&self, session: &ConversationSession) -> Option<&RetentionRule> {
+        self.retention_rules
+            .iter()
+            .find(|rule| rule.matches(session))
+    }
+}
+
+/// How long sessions with a given tag or metadata are kept
+///
+/// A rule matches a session that carries its `tag` (if set) and every one of
+/// its `metadata` entries; a rule with neither matches every session. Once a
+/// matching session has been idle for `retain_ms` it is deleted, or with
+/// `archive` archived first (which needs an archiver; without one the session
+/// is kept).
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RetentionRule {
+    /// Name reported when the rule removes a session
+    pub name: String,
+    /// Tag the session must carry
+    #[serde(default)]
+    pub tag: Option<String>,
+    /// Metadata entries the session must have
+    #[serde(default)]
+    pub metadata: HashMap<String, String>,
+    /// How long a matching session is kept after its last activity
+    pub retain_ms: u64,
+    /// Archive the session before deleting it
+    #[serde(default)]
+    pub archive: bool,
+}
+
+impl RetentionRule {
+    /// Rule matching every session, keeping it for `retain_ms`
+    #[must_use]
+    pub fn new(name: impl Into<String>, retain_ms: u64) -> Self {
+        Self {
+            name: name.into(),
+            tag: None,
+            metadata: HashMap::new(),
+            retain_ms,
+            archive: false,
+        }
+    }
+
+    /// Only match sessions tagged `tag`
+    #[must_use]
+    pub fn with_tag(mut self, tag: impl Into<String>) -> Self {
+        self.tag = Some(tag.into());
+        self
+    }
+
+    /// Only match sessions whose metadata maps `key` to `value`
+    #[must_use]
+    pub fn with_metadata(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.metadata.insert(key.into(), value.into());
+        self
+    }
+
+    /// Archive matching sessions before deleting them
+    #[must_use]
+    pub const fn archived(mut self) -> Self {
+        self.archive = true;
+        self
+    }
+
+    /// Whether the rule applies to `session`
+    #[must_use]
+    pub fn matches(&self, session: &ConversationSession) -> bool {
+        self.tag
+            .as_ref()
+            .is_none_or(|tag| session.tags.contains(tag))
+            && self
+                .metadata
+                .iter()
+                .all(|(key, value)| session.metadata.get(key) == Some(value))
+    }
+}
+
+/// Highest accepted [`ColdStorageConfig::compression_level`], zstd's maximum
+pub const MAX_COLD_COMPRESSION_LEVEL: u8 = 22;
+
+/// Storage of the cold tier
+///
+/// Nodes and edges older than [`MaintenanceConfig::cold_after_ms`] are moved
+/// out of the hot trees, which every write touches, into cold trees that
+/// reads fall back to. The cold tier lives in the same database unless given
+/// a path of its own, such as on a larger, slower disk, and compresses every
+/// node it holds, by default harder than the hot tier does.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ColdStorageConfig {
+    /// Directory of a separate database holding the cold tier; `None` keeps
+    /// it in the main database
+    pub path: Option<PathBuf>,
+    /// Algorithm cold nodes are compressed with
+    pub compression: CompressionAlgorithm,
+    /// Compression level (0 = no compression; up to 22 for zstd)
+    pub compression_level: u8,
+}
+
+impl Default for ColdStorageConfig {
+    fn default() -> Self {
+        Self {
+            path: None,
+            compression: CompressionAlgorithm::Zstd,
+            compression_level: 19,
+        }
+    }
+}
+
+impl ColdStorageConfig {
+    /// Cold tier in the main database, compressed with zstd at level 19
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keep the cold tier in a separate database at `path`
+    #[must_use]
+    pub fn with_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.path = Some(path.into());
+        self
+    }
+
+    /// Compress cold nodes with `algorithm` at `level`
+    #[must_use]
+    pub const fn with_compression(mut self, algorithm: CompressionAlgorithm, level: u8) -> Self {
+        self.compression = algorithm;
+        self.compression_level = level;
+        self
+    }
+}
+
+/// Durations above which operations are logged as slow
+///
+/// Every threshold is in milliseconds; a threshold of 0 stops logging that
+/// kind of operation.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SlowOpConfig {
+    /// Single reads and writes of nodes and edges
+    pub storage_ms: u64,
+    /// Query builder executions
+    pub query_ms: u64,
+    /// Traversals and path searches
+    pub traversal_ms: u64,
+    /// How many of the most recent slow operations are kept for inspection
+    pub capacity: usize,
+}
+
+impl Default for SlowOpConfig {
+    fn default() -> Self {
+        Self {
+            storage_ms: 100,
+            query_ms: 1000,
+            traversal_ms: 1000,
+            capacity: 100,
+        }
+    }
+}
+
+impl SlowOpConfig {
+    /// Default thresholds
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Log no operation as slow
+    #[must_use]
+    pub const fn disabled() -> Self {
+        Self {
+            storage_ms: 0,
+            query_ms: 0,
+            traversal_ms: 0,
+            capacity: 0,
+        }
+    }
+
+    /// Set the storage operation threshold
+    #[must_use]
+    pub const fn with_storage_threshold(mut self, threshold_ms: u64) -> Self {
+        self.storage_ms = threshold_ms;
+        self
+    }
+
+    /// Set the query threshold
+    #[must_use]
+    pub const fn with_query_threshold(mut self, threshold_ms: u64) -> Self {
+        self.query_ms = threshold_ms;
+        self
+    }
+
+    /// Set the traversal threshold
+    #[must_use]
+    pub const fn with_traversal_threshold(mut self, threshold_ms: u64) -> Self {
+        self.traversal_ms = threshold_ms;
+        self
+    }
+
+    /// Set how many recent slow operations are kept
+    #[must_use]
+    pub const fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self
+    }
+}
+
+/// Observatory publishing settings
+///
+/// The engine turns these into its `ObservatoryConfig`; publisher-specific
+/// options are set there.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ObservatorySettings {
+    /// Publish events
+    pub enabled: bool,
+    /// Events published per batch
+    pub batch_size: usize,
+    /// Flush interval in milliseconds
+    pub flush_interval_ms: u64,
+    /// Collect metrics
+    pub enable_metrics: bool,
+    /// Maximum number of events waiting to be published
+    pub queue_capacity: usize,
+}
+
+impl Default for ObservatorySettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            batch_size: 100,
+            flush_interval_ms: 1000,
+            enable_metrics: true,
+            queue_capacity: 10_000,
+        }
+    }
+}
+
+/// Connections to the external services the graph integrates with
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct IntegrationSettings {
+    /// LLM-Registry service
+    pub registry: Option<ServiceSettings>,
+    /// Data-Vault service
+    pub vault: Option<ServiceSettings>,
+}
+
+/// Address and credentials of an external service
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ServiceSettings {
+    /// Base URL
+    pub url: String,
+    /// API key for authentication
+    #[serde(default)]
+    pub api_key: Option<String>,
+    /// Request timeout in seconds; `None` keeps the client's default
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+}
+
+impl ServiceSettings {
+    /// Settings for the service at `url`
+    #[must_use]
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            api_key: None,
+            timeout_secs: None,
+        }
+    }
+
+    /// Set the API key
+    #[must_use]
+    pub fn with_api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.api_key = Some(api_key.into());
+        self
+    }
+
+    /// Set the request timeout in seconds
+    #[must_use]
+    pub const fn with_timeout(mut self, timeout_secs: u64) -> Self {
+        self.timeout_secs = Some(timeout_secs);
+        self
+    }
+}
+
+/// `LMG_*` variables looked up through `lookup`
+struct EnvVars<F> {
+    lookup: F,
+}
+
+impl<F: Fn(&str) -> Option<String>> EnvVars<F> {
+    fn string(&self, key: &str) -> Option<String> {
+        (self.lookup)(&format!("{ENV_PREFIX}{key}"))
+    }
+
+    fn set<T>(&self, field: &mut T, key: &str, expected: &str) -> Result<()>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        if let Some(value) = self.string(key) {
+            *field = value.trim().parse().map_err(|e| {
+                Error::ConfigError(format!(
+                    "{ENV_PREFIX}{key}: expected {expected}, got {value:?} ({e})"
+                ))
+            })?;
+        }
+        Ok(())
+    }
+
+    fn flag(&self, field: &mut bool, key: &str) -> Result<()> {
+        if let Some(value) = self.string(key) {
+            *field = match value.trim().to_ascii_lowercase().as_str() {
+                "true" | "1" | "yes" | "on" => true,
+                "false" | "0" | "no" | "off" => false,
+                _ => {
+                    return Err(Error::ConfigError(format!(
+                        "{ENV_PREFIX}{key}: expected true or false, got {value:?}"
+                    )))
+                }
+            };
+        }
+        Ok(())
+    }
+
+    fn service(&self, field: &mut Option<ServiceSettings>, name: &str) -> Result<()> {
+        if let Some(url) = self.string(&format!("{name}_URL")) {
+            match field {
+                Some(service) => service.url = url,
+                None => *field = Some(ServiceSettings::new(url)),
+            }
+        }
+        if let Some(api_key) = self.string(&format!("{name}_API_KEY")) {
+            let Some(service) = field else {
+                return Err(Error::ConfigError(format!(
+                    "{ENV_PREFIX}{name}_API_KEY is set but no {} URL is configured; \
+                     set {ENV_PREFIX}{name}_URL as well",
+                    name.to_ascii_lowercase()
+                )));
+            };
+            service.api_key = Some(api_key);
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::views::ViewGroup;
+    use std::collections::HashMap;
+
+    fn vars(pairs: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let vars: HashMap<String, String> = pairs
+            .iter()
+            .map(|(key, value)| ((*key).to_string(), (*value).to_string()))
+            .collect();
+        move |key| vars.get(key).cloned()
+    }
 
     #[test]
     fn test_default_config() {
         let config = Config::default();
         assert_eq!(config.cache_size_mb, 100);
         assert!(config.enable_wal);
+        assert!(config.validate().is_ok());
     }
 
     #[test]
@@ -88,12 +1486,67 @@ mod tests {
             .with_cache_size(200)
             .with_wal(false)
             .with_compression(5)
-            .with_flush_interval(2000);
+            .with_flush_interval(2000)
+            .with_audit_log(true)
+            .with_dedupe_prompts(true);
 
         assert_eq!(config.cache_size_mb, 200);
         assert!(!config.enable_wal);
         assert_eq!(config.compression_level, 5);
         assert_eq!(config.flush_interval_ms, 2000);
+        assert!(config.audit_log);
+        assert!(config.dedupe_prompts);
+    }
+
+    #[test]
+    fn test_maintenance_config() {
+        let maintenance = MaintenanceConfig::new()
+            .with_cache_stats_interval(0)
+            .with_trash_retention(1000, 5000)
+            .with_reference_decay(86_400_000, 0.1)
+            .with_jitter(2.0);
+        let config = Config::default().with_maintenance(maintenance.clone());
+
+        assert_eq!(config.maintenance, maintenance);
+        assert_eq!(maintenance.cache_stats_interval_ms, 0);
+        assert_eq!(maintenance.purge_interval_ms, 1000);
+        assert_eq!(maintenance.trash_retention_ms, 5000);
+        assert_eq!(maintenance.reference_half_life_ms, 86_400_000);
+        assert!((maintenance.reference_floor - 0.1).abs() < f32::EPSILON);
+        assert!((maintenance.jitter - 1.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_retention_rules() {
+        let maintenance = MaintenanceConfig::new()
+            .with_retention_rule(RetentionRule::new("ephemeral", 1000).with_tag("ephemeral"))
+            .with_retention_rule(
+                RetentionRule::new("enterprise", 2000).with_metadata("customer_tier", "enterprise"),
+            );
+
+        let mut session = ConversationSession::new();
+        assert!(maintenance.retention_rule_for(&session).is_none());
+        session
+            .metadata
+            .insert("customer_tier".to_string(), "enterprise".to_string());
+        assert_eq!(
+            maintenance.retention_rule_for(&session).unwrap().name,
+            "enterprise"
+        );
+        session.add_tag("ephemeral".to_string());
+        assert_eq!(
+            maintenance.retention_rule_for(&session).unwrap().name,
+            "ephemeral"
+        );
+        assert!(RetentionRule::new("all", 0).matches(&ConversationSession::new()));
+
+        let config = Config::default()
+            .with_maintenance(maintenance.with_retention_rule(RetentionRule::new("ephemeral", 5)));
+        assert!(config
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("duplicate rule name \"ephemeral\""));
     }
 
     #[test]
@@ -101,4 +1554,188 @@ mod tests {
         let config = Config::default().with_compression(15);
         assert_eq!(config.compression_level, 9);
     }
+
+    #[test]
+    fn test_from_file() {
+        let dir = std::env::temp_dir().join(format!("lmg-config-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("graph.toml");
+        std::fs::write(
+            &file,
+            r#"
+            path = "/srv/graph"
+            cache_size_mb = 512
+            serialization_format = "json"
+
+            [maintenance]
+            trash_retention_ms = 1000
+
+            [[maintenance.retention_rules]]
+            name = "enterprise"
+            metadata = { customer_tier = "enterprise" }
+            retain_ms = 63072000000
+            archive = true
+
+            [observatory]
+            enabled = true
+
+            [integrations.registry]
+            url = "https://registry.example.com"
+
+            [compression]
+            algorithm = "lz4"
+            node_types = ["Response"]
+
+            [ingest]
+            max_content_bytes = 4096
+            reject_control_chars = true
+
+            [[views]]
+            name = "daily_prompts"
+            node_type = "Prompt"
+            group_by = ["day", { property = "channel" }]
+            "#,
+        )
+        .unwrap();
+
+        let config = Config::from_file(&file).unwrap();
+        assert_eq!(config.path, PathBuf::from("/srv/graph"));
+        assert_eq!(config.cache_size_mb, 512);
+        assert_eq!(config.serialization_format, SerializationFormat::Json);
+        assert_eq!(config.maintenance.trash_retention_ms, 1000);
+        assert_eq!(
+            config.maintenance.retention_rules,
+            [RetentionRule::new("enterprise", 63_072_000_000)
+                .with_metadata("customer_tier", "enterprise")
+                .archived()]
+        );
+        assert_eq!(
+            config.maintenance.purge_interval_ms,
+            MaintenanceConfig::default().purge_interval_ms
+        );
+        assert!(config.observatory.enabled);
+        assert_eq!(config.observatory.batch_size, 100);
+        assert_eq!(
+            config.integrations.registry,
+            Some(ServiceSettings::new("https://registry.example.com"))
+        );
+        assert_eq!(config.schema, GraphSchema::dag());
+        assert_eq!(
+            config.compression,
+            CompressionPolicy::new(CompressionAlgorithm::Lz4)
+                .with_node_type(crate::NodeType::Response)
+        );
+        assert_eq!(
+            config.ingest,
+            IngestValidation::new()
+                .with_max_content_bytes(4096)
+                .with_reject_control_chars(true)
+        );
+        assert_eq!(
+            config.views,
+            vec![ViewDefinition::new("daily_prompts")
+                .of_type(crate::NodeType::Prompt)
+                .group_by(ViewGroup::Day)
+                .group_by(ViewGroup::Property("channel".to_string()))]
+        );
+
+        // Environment variables override the file
+        let config = config
+            .merge_vars(vars(&[
+                ("LMG_CACHE_SIZE_MB", "64"),
+                ("LMG_AUDIT_LOG", "yes"),
+                ("LMG_REGISTRY_API_KEY", "secret"),
+                ("LMG_WAIT_FOR_LOCK_MS", "5000"),
+            ]))
+            .unwrap();
+        assert_eq!(config.cache_size_mb, 64);
+        assert_eq!(config.wait_for_lock_ms, Some(5000));
+        assert!(config.audit_log);
+        assert_eq!(config.path, PathBuf::from("/srv/graph"));
+        assert_eq!(
+            config.integrations.registry.unwrap().api_key.as_deref(),
+            Some("secret")
+        );
+
+        std::fs::write(&file, "cache_size = 10").unwrap();
+        let err = Config::from_file(&file).unwrap_err().to_string();
+        assert!(err.contains("cache_size"), "{err}");
+        assert!(Config::from_file(dir.join("missing.toml")).is_err());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_env_vars() {
+        let config = Config::default()
+            .merge_vars(vars(&[
+                ("LMG_DB_PATH", "/tmp/graph"),
+                ("LMG_SERIALIZATION_FORMAT", "Bincode"),
+                ("LMG_TRASH_RETENTION_MS", "60000"),
+                ("LMG_SLOW_QUERY_MS", "250"),
+                ("LMG_OBSERVATORY_ENABLED", "on"),
+                ("LMG_VAULT_URL", "http://vault:9000"),
+                ("LMG_VAULT_API_KEY", "key"),
+                ("LMG_ID_SEED", "42"),
+                ("LMG_COMPRESSION", "ZSTD"),
+                ("LMG_READ_CONSISTENCY", "require-flushed"),
+                ("LMG_LOG_LEVEL", "Info"),
+                ("LMG_REFERENCE_HALF_LIFE_MS", "3600000"),
+                ("LMG_COLD_AFTER_MS", "86400000"),
+                ("LMG_COLD_PATH", "/mnt/cold"),
+            ]))
+            .unwrap();
+        assert_eq!(config.path, PathBuf::from("/tmp/graph"));
+        assert_eq!(config.id_seed, Some(42));
+        assert_eq!(config.compression.algorithm, CompressionAlgorithm::Zstd);
+        assert_eq!(config.serialization_format, SerializationFormat::Bincode);
+        assert_eq!(config.read_consistency, ReadConsistency::RequireFlushed);
+        assert_eq!(config.log_level, LogLevel::Info);
+        assert!(!config.log_content);
+        assert_eq!(config.maintenance.trash_retention_ms, 60_000);
+        assert_eq!(config.maintenance.reference_half_life_ms, 3_600_000);
+        assert_eq!(config.maintenance.cold_after_ms, 86_400_000);
+        assert_eq!(config.cold_storage.path, Some(PathBuf::from("/mnt/cold")));
+        assert_eq!(config.cold_storage.compression_level, 19);
+        assert_eq!(config.slow_ops.query_ms, 250);
+        assert_eq!(config.slow_ops.storage_ms, 100);
+        assert!(config.observatory.enabled);
+        assert_eq!(
+            config.integrations.vault,
+            Some(ServiceSettings::new("http://vault:9000").with_api_key("key"))
+        );
+
+        let err = Config::default()
+            .merge_vars(vars(&[("LMG_CACHE_SIZE_MB", "lots")]))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("LMG_CACHE_SIZE_MB"), "{err}");
+        assert!(Config::default()
+            .merge_vars(vars(&[("LMG_READ_ONLY", "maybe")]))
+            .is_err());
+        assert!(Config::default()
+            .merge_vars(vars(&[("LMG_REGISTRY_API_KEY", "orphan")]))
+            .is_err());
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut config = Config::default().with_cache_size(0);
+        config.compression_level = 12;
+        config.integrations.vault = Some(ServiceSettings::new("vault:9000"));
+        config.cold_storage = ColdStorageConfig::new()
+            .with_path(config.path.clone())
+            .with_compression(CompressionAlgorithm::Zstd, 30);
+        config.views = vec![
+            ViewDefinition::session_usage(),
+            ViewDefinition::session_usage(),
+        ];
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("cache_size_mb"), "{err}");
+        assert!(err.contains("compression_level"), "{err}");
+        assert!(err.contains("integrations.vault.url"), "{err}");
+        assert!(err.contains("integrations.vault.api_key"), "{err}");
+        assert!(err.contains("duplicate view name"), "{err}");
+        assert!(err.contains("cold_storage.compression_level"), "{err}");
+        assert!(err.contains("cold_storage.path"), "{err}");
+    }
 }
@@ -3,11 +3,21 @@
 //! This module provides strongly-typed edge definitions with property validation
 //! for enterprise-grade graph operations.
 
+use super::nodes::Properties;
 use super::{EdgeId, NodeId};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+/// Attribute holding an edge's traversal weight
+pub const WEIGHT_ATTRIBUTE: &str = "weight";
+
+/// Attribute holding an edge's relevance score
+pub const RELEVANCE_ATTRIBUTE: &str = "relevance";
+
+/// Weight of edges that do not set one
+pub const DEFAULT_EDGE_WEIGHT: f64 = 1.0;
+
 /// Types of edges that can connect nodes
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum EdgeType {
@@ -19,7 +29,7 @@ pub enum EdgeType {
     HandledBy,
     /// Links a prompt to the session it belongs to (Prompt → Session)
     PartOf,
-    /// Links a response to the tools it invoked (Response → ToolInvocation)
+    /// Links a response to the tools it invoked (Response → `ToolInvocation`)
     Invokes,
     /// Links a response to the agent it handed off to (Response → Agent)
     TransfersTo,
@@ -27,8 +37,16 @@ pub enum EdgeType {
     Instantiates,
     /// Links a template to its parent template (Template → Template)
     Inherits,
-    /// Links a prompt to external context sources (Prompt → ExternalContext)
+    /// Links a prompt to external context sources (Prompt → `ExternalContext`)
     References,
+    /// Links an extracted entity or fact to where it was mentioned
+    /// (Entity → Response, Entity → Fact, Fact → Response)
+    MentionedIn,
+    /// Links a fact to the older fact it replaces (Fact → Fact)
+    Supersedes,
+    /// Links an earlier version of an edited prompt or response to the
+    /// version that replaced it (Version → Version, Version → Prompt/Response)
+    PreviousVersionOf,
 }
 
 // ===== Edge Property Structs =====
@@ -90,8 +108,7 @@ impl InstantiatesProperties {
         let instantiation_time = props
             .get("instantiation_time")
             .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
-            .map(|dt| dt.with_timezone(&Utc))
-            .unwrap_or_else(Utc::now);
+            .map_or_else(Utc::now, |dt| dt.with_timezone(&Utc));
 
         Ok(Self {
             template_version,
@@ -169,7 +186,7 @@ impl InheritsProperties {
     }
 }
 
-/// Properties for INVOKES edge (Response → ToolInvocation)
+/// Properties for INVOKES edge (Response → `ToolInvocation`)
 ///
 /// Tracks tool invocations made during response generation, including
 /// execution order and success status.
@@ -230,12 +247,17 @@ impl InvokesProperties {
     }
 }
 
-/// Priority level for agent handoffs
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+/// Priority level for agent handoffs and context references
+///
+/// Levels order from [`Low`](Self::Low) to [`Critical`](Self::Critical).
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
 pub enum Priority {
     /// Low priority - can be handled later
     Low,
     /// Normal priority - standard handling
+    #[default]
     Normal,
     /// High priority - expedited handling
     High,
@@ -263,12 +285,12 @@ impl std::str::FromStr for Priority {
             "normal" => Ok(Priority::Normal),
             "high" => Ok(Priority::High),
             "critical" => Ok(Priority::Critical),
-            _ => Err(format!("Invalid priority: {}", s)),
+            _ => Err(format!("Invalid priority: {s}")),
         }
     }
 }
 
-/// Properties for TRANSFERS_TO edge (Response → Agent)
+/// Properties for `TRANSFERS_TO` edge (Response → Agent)
 ///
 /// Tracks agent handoffs, including the reason for transfer and
 /// conversation context.
@@ -363,12 +385,12 @@ impl std::str::FromStr for ContextType {
             "database" => Ok(ContextType::Database),
             "vector_search" | "vectorsearch" => Ok(ContextType::VectorSearch),
             "memory" => Ok(ContextType::Memory),
-            _ => Err(format!("Invalid context type: {}", s)),
+            _ => Err(format!("Invalid context type: {s}")),
         }
     }
 }
 
-/// Properties for REFERENCES edge (Prompt → ExternalContext)
+/// Properties for REFERENCES edge (Prompt → `ExternalContext`)
 ///
 /// Tracks external context sources used in prompt generation, including
 /// relevance scores and specific content references.
@@ -380,10 +402,13 @@ pub struct ReferencesProperties {
     pub relevance_score: f32,
     /// Optional identifier for specific chunk or section
     pub chunk_id: Option<String>,
+    /// How important the context is when space runs short
+    #[serde(default)]
+    pub priority: Priority,
 }
 
 impl ReferencesProperties {
-    /// Create new reference properties
+    /// Create new reference properties with [`Priority::Normal`]
     pub fn new(context_type: ContextType, relevance_score: f32, chunk_id: Option<String>) -> Self {
         // Clamp relevance score between 0.0 and 1.0
         let relevance_score = relevance_score.clamp(0.0, 1.0);
@@ -392,9 +417,17 @@ impl ReferencesProperties {
             context_type,
             relevance_score,
             chunk_id,
+            priority: Priority::Normal,
         }
     }
 
+    /// Set the priority of the context
+    #[must_use]
+    pub fn with_priority(mut self, priority: Priority) -> Self {
+        self.priority = priority;
+        self
+    }
+
     /// Convert to property map for storage
     pub fn to_properties(&self) -> HashMap<String, String> {
         let mut props = HashMap::new();
@@ -406,6 +439,7 @@ impl ReferencesProperties {
         if let Some(ref chunk_id) = self.chunk_id {
             props.insert("chunk_id".to_string(), chunk_id.clone());
         }
+        props.insert("priority".to_string(), self.priority.to_string());
         props
     }
 
@@ -424,10 +458,16 @@ impl ReferencesProperties {
 
         let chunk_id = props.get("chunk_id").cloned();
 
+        let priority = props
+            .get("priority")
+            .and_then(|s| s.parse().ok())
+            .unwrap_or_default();
+
         Ok(Self {
             context_type,
             relevance_score,
             chunk_id,
+            priority,
         })
     }
 }
@@ -447,6 +487,12 @@ pub struct Edge {
     pub created_at: DateTime<Utc>,
     /// Additional properties for this edge
     pub properties: HashMap<String, String>,
+    /// User-defined typed properties
+    ///
+    /// Unlike [`properties`](Self::properties), which holds the string
+    /// properties of typed relationships, values here may be any JSON value.
+    #[serde(default)]
+    pub attributes: Properties,
 }
 
 impl Edge {
@@ -460,6 +506,7 @@ impl Edge {
             edge_type,
             created_at: Utc::now(),
             properties: HashMap::new(),
+            attributes: Properties::new(),
         }
     }
 
@@ -478,6 +525,7 @@ impl Edge {
             edge_type,
             created_at: Utc::now(),
             properties,
+            attributes: Properties::new(),
         }
     }
 
@@ -492,6 +540,86 @@ impl Edge {
         self.properties.get(key)
     }
 
+    /// A user-defined typed property
+    #[must_use]
+    pub fn attribute(&self, key: &str) -> Option<&serde_json::Value> {
+        self.attributes.get(key)
+    }
+
+    /// Cost of following the edge in weighted traversals
+    ///
+    /// Reads the [`WEIGHT_ATTRIBUTE`] attribute; edges without a finite,
+    /// non-negative weight cost [`DEFAULT_EDGE_WEIGHT`].
+    #[must_use]
+    pub fn weight(&self) -> f64 {
+        self.attribute(WEIGHT_ATTRIBUTE)
+            .and_then(serde_json::Value::as_f64)
+            .filter(|weight| weight.is_finite() && *weight >= 0.0)
+            .unwrap_or(DEFAULT_EDGE_WEIGHT)
+    }
+
+    /// How relevant the target is to the source, from 0.0 to 1.0
+    ///
+    /// Reads the [`RELEVANCE_ATTRIBUTE`] attribute, falling back to the
+    /// relevance score of a REFERENCES edge.
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation)] // clamped to 0.0..=1.0 first
+    pub fn relevance(&self) -> Option<f32> {
+        self.attribute(RELEVANCE_ATTRIBUTE)
+            .and_then(serde_json::Value::as_f64)
+            .filter(|relevance| relevance.is_finite())
+            .map(|relevance| relevance.clamp(0.0, 1.0) as f32)
+            .or_else(|| {
+                self.get_references_properties()
+                    .map(|props| props.relevance_score)
+            })
+    }
+
+    /// [`relevance`](Self::relevance) at `at`, halved for every `half_life_ms`
+    /// since the edge was created
+    ///
+    /// A half-life of 0 disables decay. Edges without a relevance have none.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)] // ages far beyond 2^52 ms are not a concern
+    #[allow(clippy::cast_possible_truncation)] // the decay factor is within 0.0..=1.0
+    pub fn decayed_relevance(&self, half_life_ms: u64, at: DateTime<Utc>) -> Option<f32> {
+        let relevance = self.relevance()?;
+        if half_life_ms == 0 {
+            return Some(relevance);
+        }
+        let age_ms = (at - self.created_at).num_milliseconds().max(0);
+        let half_lives = age_ms as f64 / half_life_ms as f64;
+        Some(relevance * 0.5_f64.powf(half_lives) as f32)
+    }
+
+    /// Priority of a REFERENCES or `TRANSFERS_TO` edge
+    ///
+    /// Edges without a valid priority property are [`Priority::Normal`].
+    #[must_use]
+    pub fn priority(&self) -> Priority {
+        self.get_property("priority")
+            .and_then(|priority| priority.parse().ok())
+            .unwrap_or_default()
+    }
+
+    /// Set the traversal weight
+    #[must_use]
+    pub fn with_weight(mut self, weight: f64) -> Self {
+        self.attributes
+            .insert(WEIGHT_ATTRIBUTE.to_string(), serde_json::json!(weight));
+        self
+    }
+
+    /// Set the relevance score, clamped to 0.0..=1.0
+    #[must_use]
+    pub fn with_relevance(mut self, relevance: f32) -> Self {
+        self.attributes.insert(
+            RELEVANCE_ATTRIBUTE.to_string(),
+            serde_json::json!(relevance.clamp(0.0, 1.0)),
+        );
+        self
+    }
+
     // ===== Strongly-Typed Edge Builders =====
 
     /// Create an INSTANTIATES edge with typed properties
@@ -501,7 +629,7 @@ impl Edge {
     /// # Examples
     ///
     /// ```
-    /// use llm_memory_graph::types::{Edge, NodeId, InstantiatesProperties};
+    /// use llm_memory_graph_types::{Edge, NodeId, InstantiatesProperties};
     /// use std::collections::HashMap;
     ///
     /// let prompt_id = NodeId::new();
@@ -513,6 +641,7 @@ impl Edge {
     /// let edge = Edge::instantiates(prompt_id, template_id, properties);
     /// ```
     #[must_use]
+    #[allow(clippy::needless_pass_by_value)] // built inline by callers
     pub fn instantiates(from: NodeId, to: NodeId, properties: InstantiatesProperties) -> Self {
         Self::with_properties(from, to, EdgeType::Instantiates, properties.to_properties())
     }
@@ -524,7 +653,7 @@ impl Edge {
     /// # Examples
     ///
     /// ```
-    /// use llm_memory_graph::types::{Edge, NodeId, InheritsProperties};
+    /// use llm_memory_graph_types::{Edge, NodeId, InheritsProperties};
     ///
     /// let child_id = NodeId::new();
     /// let parent_id = NodeId::new();
@@ -537,6 +666,7 @@ impl Edge {
     /// let edge = Edge::inherits(child_id, parent_id, properties);
     /// ```
     #[must_use]
+    #[allow(clippy::needless_pass_by_value)] // built inline by callers
     pub fn inherits(from: NodeId, to: NodeId, properties: InheritsProperties) -> Self {
         Self::with_properties(from, to, EdgeType::Inherits, properties.to_properties())
     }
@@ -548,7 +678,7 @@ impl Edge {
     /// # Examples
     ///
     /// ```
-    /// use llm_memory_graph::types::{Edge, NodeId, InvokesProperties};
+    /// use llm_memory_graph_types::{Edge, NodeId, InvokesProperties};
     ///
     /// let response_id = NodeId::new();
     /// let tool_id = NodeId::new();
@@ -557,18 +687,19 @@ impl Edge {
     /// let edge = Edge::invokes(response_id, tool_id, properties);
     /// ```
     #[must_use]
+    #[allow(clippy::needless_pass_by_value)] // built inline by callers
     pub fn invokes(from: NodeId, to: NodeId, properties: InvokesProperties) -> Self {
         Self::with_properties(from, to, EdgeType::Invokes, properties.to_properties())
     }
 
-    /// Create a TRANSFERS_TO edge with typed properties
+    /// Create a `TRANSFERS_TO` edge with typed properties
     ///
     /// Links a response to the agent it transfers control to.
     ///
     /// # Examples
     ///
     /// ```
-    /// use llm_memory_graph::types::{Edge, NodeId, TransfersToProperties, Priority};
+    /// use llm_memory_graph_types::{Edge, NodeId, TransfersToProperties, Priority};
     ///
     /// let response_id = NodeId::new();
     /// let agent_id = NodeId::new();
@@ -581,6 +712,7 @@ impl Edge {
     /// let edge = Edge::transfers_to(response_id, agent_id, properties);
     /// ```
     #[must_use]
+    #[allow(clippy::needless_pass_by_value)] // built inline by callers
     pub fn transfers_to(from: NodeId, to: NodeId, properties: TransfersToProperties) -> Self {
         Self::with_properties(from, to, EdgeType::TransfersTo, properties.to_properties())
     }
@@ -592,7 +724,7 @@ impl Edge {
     /// # Examples
     ///
     /// ```
-    /// use llm_memory_graph::types::{Edge, NodeId, ReferencesProperties, ContextType};
+    /// use llm_memory_graph_types::{Edge, NodeId, ReferencesProperties, ContextType};
     ///
     /// let prompt_id = NodeId::new();
     /// let context_id = NodeId::new();
@@ -605,6 +737,7 @@ impl Edge {
     /// let edge = Edge::references(prompt_id, context_id, properties);
     /// ```
     #[must_use]
+    #[allow(clippy::needless_pass_by_value)] // built inline by callers
     pub fn references(from: NodeId, to: NodeId, properties: ReferencesProperties) -> Self {
         Self::with_properties(from, to, EdgeType::References, properties.to_properties())
     }
@@ -644,9 +777,9 @@ impl Edge {
         InvokesProperties::from_properties(&self.properties).ok()
     }
 
-    /// Extract TRANSFERS_TO properties from edge
+    /// Extract `TRANSFERS_TO` properties from edge
     ///
-    /// Returns None if edge is not of type TransfersTo or properties are invalid.
+    /// Returns None if edge is not of type `TransfersTo` or properties are invalid.
     #[must_use]
     pub fn get_transfers_to_properties(&self) -> Option<TransfersToProperties> {
         if self.edge_type != EdgeType::TransfersTo {
@@ -668,6 +801,7 @@ impl Edge {
 }
 
 #[cfg(test)]
+#[allow(clippy::float_cmp)] // expected values are stored and read back exactly
 mod tests {
     use super::*;
 
@@ -690,6 +824,41 @@ mod tests {
         assert_eq!(edge.get_property("latency_ms"), Some(&"150".to_string()));
     }
 
+    #[test]
+    fn test_edge_weight_and_relevance() {
+        let edge = Edge::new(NodeId::new(), NodeId::new(), EdgeType::Follows);
+        assert_eq!(edge.weight(), DEFAULT_EDGE_WEIGHT);
+        assert_eq!(edge.relevance(), None);
+
+        let edge = edge.with_weight(2.5).with_relevance(1.5);
+        assert_eq!(edge.weight(), 2.5);
+        assert_eq!(edge.relevance(), Some(1.0));
+        assert_eq!(edge.clone().with_weight(-1.0).weight(), DEFAULT_EDGE_WEIGHT);
+
+        let reference = Edge::references(
+            NodeId::new(),
+            NodeId::new(),
+            ReferencesProperties::new(ContextType::Document, 0.4, None),
+        );
+        assert_eq!(reference.relevance(), Some(0.4));
+        assert_eq!(reference.with_relevance(0.9).relevance(), Some(0.9));
+    }
+
+    #[test]
+    fn test_decayed_relevance() {
+        let edge =
+            Edge::new(NodeId::new(), NodeId::new(), EdgeType::References).with_relevance(0.8);
+        let day_ms = 24 * 60 * 60 * 1000;
+        let later = edge.created_at + chrono::Duration::days(2);
+        assert_eq!(edge.decayed_relevance(0, later), Some(0.8));
+        let decayed = edge.decayed_relevance(day_ms, later).unwrap();
+        assert!((decayed - 0.2).abs() < 1e-6);
+        let earlier = edge.created_at - chrono::Duration::days(1);
+        assert_eq!(edge.decayed_relevance(day_ms, earlier), Some(0.8));
+        let unscored = Edge::new(NodeId::new(), NodeId::new(), EdgeType::References);
+        assert_eq!(unscored.decayed_relevance(day_ms, later), None);
+    }
+
     #[test]
     fn test_edge_with_properties() {
         let from = NodeId::new();
@@ -1017,6 +1186,24 @@ mod tests {
         assert_eq!(restored.chunk_id, Some("doc_123".to_string()));
     }
 
+    #[test]
+    fn test_references_properties_priority() {
+        let props = ReferencesProperties::new(ContextType::Memory, 0.5, None)
+            .with_priority(Priority::Critical);
+        let edge = Edge::references(NodeId::new(), NodeId::new(), props);
+        assert_eq!(edge.priority(), Priority::Critical);
+        assert_eq!(
+            edge.get_references_properties().unwrap().priority,
+            Priority::Critical
+        );
+
+        // Edges stored before priorities existed are normal priority
+        let mut legacy = edge.clone();
+        legacy.properties.remove("priority");
+        assert_eq!(legacy.priority(), Priority::Normal);
+        assert!(Priority::Critical > Priority::High && Priority::Normal > Priority::Low);
+    }
+
     #[test]
     fn test_references_edge_builder() {
         let prompt_id = NodeId::new();
@@ -1,10 +1,88 @@
 //! Error types for LLM Memory Graph
-
+//!
+//! Every [`Error`] has a stable [`ErrorCode`] for programmatic handling; match
+//! on the code rather than on the message, which may change. Each code maps
+//! to a gRPC status and an HTTP status:
+//!
+//! | Code | gRPC status | HTTP status |
+//! |------|-------------|-------------|
+//! | `not_found` | `NOT_FOUND` (5) | 404 |
+//! | `conflict` | `ALREADY_EXISTS` (6) | 409 |
+//! | `invalid_input` | `INVALID_ARGUMENT` (3) | 400 |
+//! | `rejected` | `FAILED_PRECONDITION` (9) | 422 |
+//! | `unsupported` | `UNIMPLEMENTED` (12) | 501 |
+//! | `timeout` | `DEADLINE_EXCEEDED` (4) | 504 |
+//! | `cancelled` | `CANCELLED` (1) | 499 |
+//! | `unavailable` | `UNAVAILABLE` (14) | 503 |
+//! | `corruption` | `DATA_LOSS` (15) | 500 |
+//! | `serialization` | `INTERNAL` (13) | 500 |
+//! | `storage` | `INTERNAL` (13) | 500 |
+//! | `config` | `INTERNAL` (13) | 500 |
+//! | `internal` | `INTERNAL` (13) | 500 |
+//!
+//! [`ResultExt::context`] wraps an error in a description of what was being
+//! done; the wrapped error stays reachable through
+//! [`std::error::Error::source`] and keeps its code.
+
+use crate::ingest::IngestViolation;
+use serde::{Deserialize, Serialize};
+use std::fmt::{self, Write};
 use thiserror::Error;
 
 /// Main error type for LLM Memory Graph
 #[derive(Debug, Error)]
 pub enum Error {
+    /// An entity of the given kind does not exist
+    #[error("{kind} not found: {id}")]
+    NotFound {
+        /// Kind of entity, such as `"node"` or `"session"`
+        kind: &'static str,
+        /// ID or name that was looked up
+        id: String,
+    },
+
+    /// The operation conflicts with the current state of the graph
+    #[error("Conflict: {0}")]
+    Conflict(String),
+
+    /// A stored record could not be read back
+    #[error("Corrupt record in {tree} at key {key}: {reason}")]
+    Corruption {
+        /// Storage tree holding the record
+        tree: String,
+        /// Key of the record, hex-encoded if binary
+        key: String,
+        /// What was wrong with it
+        reason: String,
+    },
+
+    /// An export was altered, is incomplete, or was sealed with other keys
+    #[error("Export failed verification: {0}")]
+    ExportVerification(String),
+
+    /// The database is open in another process, or elsewhere in this one
+    #[error("Database is locked by process {pid} since {since}")]
+    DatabaseLocked {
+        /// ID of the process holding the lock, 0 if unknown
+        pid: u32,
+        /// When the lock was taken
+        since: chrono::DateTime<chrono::Utc>,
+    },
+
+    /// The backend or build does not support the operation
+    #[error("Unsupported: {0}")]
+    Unsupported(String),
+
+    /// An error with a description of the operation that failed
+    #[error("{context}: {source}")]
+    Context {
+        /// What was being done
+        context: String,
+        /// The underlying error
+        #[source]
+        source: Box<Error>,
+    },
+
     /// Node not found
     #[error("Node not found: {0}")]
     NodeNotFound(String),
@@ -45,6 +123,15 @@ pub enum Error {
     #[error("Validation error: {0}")]
     ValidationError(String),
 
+    /// Content rejected by ingest validation
+    #[error("Invalid {field}: {violation}")]
+    InvalidIngest {
+        /// What was checked, such as `"prompt content"`
+        field: &'static str,
+        /// The rule it broke
+        violation: IngestViolation,
+    },
+
     /// Serialization error
     #[error("Serialization error: {0}")]
     SerializationError(String),
@@ -77,6 +164,20 @@ pub enum Error {
     #[error("Operation timed out after {0}ms")]
     Timeout(u64),
 
+    /// An operation ran past its deadline or was cancelled before finishing
+    #[error("{operation} {reason} after {elapsed_ms}ms{}", progress_note(.completed.as_ref()))]
+    Interrupted {
+        /// Operation that was stopped, such as `"build_subgraph"`
+        operation: String,
+        /// Whether it timed out or was cancelled
+        reason: InterruptReason,
+        /// How long it had been running
+        elapsed_ms: u64,
+        /// Items, such as nodes visited, finished before it stopped, for
+        /// operations that make progress in steps
+        completed: Option<u64>,
+    },
+
     /// Plugin error
     #[error("Plugin error: {0}")]
     PluginError(String),
@@ -110,6 +211,281 @@ pub enum Error {
     Other(String),
 }
 
+/// Stable, machine-readable category of an [`Error`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    /// A node, edge, session or other entity does not exist
+    NotFound,
+    /// The entity already exists or the write conflicts with stored state
+    Conflict,
+    /// The request was malformed or failed validation
+    InvalidInput,
+    /// A plugin or rule rejected an otherwise valid request
+    Rejected,
+    /// The backend or build does not support the operation
+    Unsupported,
+    /// The operation did not finish in time
+    Timeout,
+    /// The caller cancelled the operation
+    Cancelled,
+    /// An external service could not be reached
+    Unavailable,
+    /// Stored data is damaged
+    Corruption,
+    /// A value could not be encoded or decoded
+    Serialization,
+    /// The storage engine failed
+    Storage,
+    /// The configuration is invalid
+    Config,
+    /// Any other failure
+    Internal,
+}
+
+impl ErrorCode {
+    /// The code as it appears in logs and API responses
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::NotFound => "not_found",
+            Self::Conflict => "conflict",
+            Self::InvalidInput => "invalid_input",
+            Self::Rejected => "rejected",
+            Self::Unsupported => "unsupported",
+            Self::Timeout => "timeout",
+            Self::Cancelled => "cancelled",
+            Self::Unavailable => "unavailable",
+            Self::Corruption => "corruption",
+            Self::Serialization => "serialization",
+            Self::Storage => "storage",
+            Self::Config => "config",
+            Self::Internal => "internal",
+        }
+    }
+
+    /// Canonical gRPC status code number for this code
+    pub const fn grpc_code(self) -> i32 {
+        match self {
+            Self::Cancelled => 1,
+            Self::InvalidInput => 3,
+            Self::Timeout => 4,
+            Self::NotFound => 5,
+            Self::Conflict => 6,
+            Self::Rejected => 9,
+            Self::Unsupported => 12,
+            Self::Unavailable => 14,
+            Self::Corruption => 15,
+            Self::Serialization | Self::Storage | Self::Config | Self::Internal => 13,
+        }
+    }
+
+    /// Code for a gRPC status code number, the reverse of
+    /// [`grpc_code`](Self::grpc_code); `INTERNAL` and unknown numbers map to
+    /// [`Internal`](Self::Internal)
+    pub const fn from_grpc_code(code: i32) -> Self {
+        match code {
+            1 => Self::Cancelled,
+            3 | 11 => Self::InvalidInput,
+            4 => Self::Timeout,
+            5 => Self::NotFound,
+            6 | 10 => Self::Conflict,
+            9 => Self::Rejected,
+            12 => Self::Unsupported,
+            14 => Self::Unavailable,
+            15 => Self::Corruption,
+            _ => Self::Internal,
+        }
+    }
+
+    /// HTTP status for this code
+    pub const fn http_status(self) -> u16 {
+        match self {
+            Self::InvalidInput => 400,
+            Self::NotFound => 404,
+            Self::Conflict => 409,
+            Self::Rejected => 422,
+            Self::Unsupported => 501,
+            Self::Unavailable => 503,
+            Self::Timeout => 504,
+            Self::Cancelled => 499,
+            Self::Corruption
+            | Self::Serialization
+            | Self::Storage
+            | Self::Config
+            | Self::Internal => 500,
+        }
+    }
+
+    /// Whether retrying the same operation later may succeed
+    pub const fn is_retryable(self) -> bool {
+        matches!(self, Self::Timeout | Self::Unavailable)
+    }
+}
+
+/// Why an [`Error::Interrupted`] operation stopped
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InterruptReason {
+    /// Its timeout elapsed
+    TimedOut,
+    /// Its cancellation token was cancelled
+    Cancelled,
+}
+
+impl fmt::Display for InterruptReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::TimedOut => "timed out",
+            Self::Cancelled => "was cancelled",
+        })
+    }
+}
+
+/// Suffix of an [`Error::Interrupted`] message describing partial progress
+fn progress_note(completed: Option<&u64>) -> String {
+    completed.map_or_else(String::new, |completed| {
+        format!(" ({completed} items completed)")
+    })
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Error {
+    /// An entity of the given kind does not exist
+    pub fn not_found(kind: &'static str, id: impl fmt::Display) -> Self {
+        Self::NotFound {
+            kind,
+            id: id.to_string(),
+        }
+    }
+
+    /// Content submitted to the graph broke an ingest rule
+    pub fn invalid_ingest(field: &'static str, violation: IngestViolation) -> Self {
+        Self::InvalidIngest { field, violation }
+    }
+
+    /// A stored record could not be read back; binary keys are hex-encoded
+    pub fn corruption(tree: impl Into<String>, key: &[u8], reason: impl fmt::Display) -> Self {
+        let key = match std::str::from_utf8(key) {
+            Ok(key) if !key.chars().any(char::is_control) => key.to_string(),
+            _ => key.iter().fold(String::new(), |mut hex, byte| {
+                let _ = write!(hex, "{byte:02x}");
+                hex
+            }),
+        };
+        Self::Corruption {
+            tree: tree.into(),
+            key,
+            reason: reason.to_string(),
+        }
+    }
+
+    /// `operation` stopped for `reason` after `elapsed`, having finished
+    /// `completed` items if it makes progress in steps
+    pub fn interrupted(
+        operation: impl Into<String>,
+        reason: InterruptReason,
+        elapsed: std::time::Duration,
+        completed: Option<u64>,
+    ) -> Self {
+        Self::Interrupted {
+            operation: operation.into(),
+            reason,
+            elapsed_ms: u64::try_from(elapsed.as_millis()).unwrap_or(u64::MAX),
+            completed,
+        }
+    }
+
+    /// Wrap the error in a description of what was being done
+    #[must_use]
+    pub fn context(self, context: impl Into<String>) -> Self {
+        Self::Context {
+            context: context.into(),
+            source: Box::new(self),
+        }
+    }
+
+    /// Stable category of the error; wrapped errors keep their code
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::Context { source, .. } => source.code(),
+            Self::NotFound { .. }
+            | Self::NodeNotFound(_)
+            | Self::EdgeNotFound(_)
+            | Self::SessionNotFound(_)
+            | Self::TemplateNotFound(_)
+            | Self::AgentNotFound(_) => ErrorCode::NotFound,
+            Self::Conflict(_) | Self::NodeAlreadyExists(_) | Self::EdgeAlreadyExists(_) => {
+                ErrorCode::Conflict
+            }
+            Self::InvalidNodeType(_)
+            | Self::InvalidEdgeType(_)
+            | Self::ValidationError(_)
+            | Self::InvalidIngest { .. }
+            | Self::QueryError(_) => ErrorCode::InvalidInput,
+            Self::PluginError(_) => ErrorCode::Rejected,
+            Self::Unsupported(_) => ErrorCode::Unsupported,
+            Self::Timeout(_)
+            | Self::Interrupted {
+                reason: InterruptReason::TimedOut,
+                ..
+            } => ErrorCode::Timeout,
+            Self::Interrupted {
+                reason: InterruptReason::Cancelled,
+                ..
+            } => ErrorCode::Cancelled,
+            Self::IntegrationError(_) | Self::GrpcError(_) | Self::DatabaseLocked { .. } => {
+                ErrorCode::Unavailable
+            }
+            Self::Corruption { .. } | Self::ExportVerification(_) => ErrorCode::Corruption,
+            Self::SerializationError(_) | Self::DeserializationError(_) => ErrorCode::Serialization,
+            Self::Storage(_) | Self::StorageError(_) | Self::IoError(_) => ErrorCode::Storage,
+            Self::ConfigError(_) => ErrorCode::Config,
+            Self::RuntimeError(_)
+            | Self::MigrationError(_)
+            | Self::TraversalError(_)
+            | Self::PrometheusError(_)
+            | Self::Other(_) => ErrorCode::Internal,
+        }
+    }
+
+    /// Whether the error means the looked-up entity does not exist
+    pub fn is_not_found(&self) -> bool {
+        self.code() == ErrorCode::NotFound
+    }
+
+    /// The innermost error, beneath any [`context`](Self::context) wrappers
+    pub fn root_cause(&self) -> &Self {
+        match self {
+            Self::Context { source, .. } => source.root_cause(),
+            other => other,
+        }
+    }
+}
+
+/// Adds context to the error of a [`Result`]
+pub trait ResultExt<T> {
+    /// Wrap the error, if any, in a description of what was being done
+    fn context(self, context: impl Into<String>) -> Result<T>;
+
+    /// As [`context`](Self::context), building the description only on error
+    fn with_context<C: Into<String>>(self, context: impl FnOnce() -> C) -> Result<T>;
+}
+
+impl<T> ResultExt<T> for Result<T> {
+    fn context(self, context: impl Into<String>) -> Result<T> {
+        self.map_err(|err| err.context(context))
+    }
+
+    fn with_context<C: Into<String>>(self, context: impl FnOnce() -> C) -> Result<T> {
+        self.map_err(|err| err.context(context()))
+    }
+}
+
 impl From<std::io::Error> for Error {
     fn from(err: std::io::Error) -> Self {
         Error::IoError(err.to_string())
@@ -165,3 +541,73 @@ impl From<prometheus::Error> for Error {
 
 /// Result type for LLM Memory Graph operations
 pub type Result<T> = std::result::Result<T, Error>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error as _;
+
+    #[test]
+    fn test_codes_and_statuses() {
+        let err = Error::not_found("session", "abc");
+        assert_eq!(err.to_string(), "session not found: abc");
+        assert_eq!(err.code(), ErrorCode::NotFound);
+        assert_eq!(err.code().grpc_code(), 5);
+        assert_eq!(err.code().http_status(), 404);
+        assert!(Error::NodeNotFound("x".to_string()).is_not_found());
+
+        let err = Error::corruption("nodes", &[0xab, 0x01], "truncated");
+        assert_eq!(err.code().as_str(), "corruption");
+        assert!(err.to_string().contains("at key ab01"));
+        assert_eq!(ErrorCode::Unsupported.http_status(), 501);
+        assert!(ErrorCode::Timeout.is_retryable());
+        for code in [
+            ErrorCode::NotFound,
+            ErrorCode::Conflict,
+            ErrorCode::Corruption,
+        ] {
+            assert_eq!(ErrorCode::from_grpc_code(code.grpc_code()), code);
+        }
+        assert_eq!(
+            serde_json::to_string(&ErrorCode::InvalidInput).unwrap(),
+            "\"invalid_input\""
+        );
+    }
+
+    #[test]
+    fn test_interrupted() {
+        let err = Error::interrupted(
+            "build_subgraph",
+            InterruptReason::TimedOut,
+            std::time::Duration::from_millis(250),
+            Some(42),
+        );
+        assert_eq!(
+            err.to_string(),
+            "build_subgraph timed out after 250ms (42 items completed)"
+        );
+        assert_eq!(err.code(), ErrorCode::Timeout);
+
+        let err = Error::interrupted(
+            "query",
+            InterruptReason::Cancelled,
+            std::time::Duration::ZERO,
+            None,
+        );
+        assert_eq!(err.to_string(), "query was cancelled after 0ms");
+        assert_eq!(err.code(), ErrorCode::Cancelled);
+        assert_eq!(err.code().http_status(), 499);
+        assert!(!err.code().is_retryable());
+        assert_eq!(ErrorCode::from_grpc_code(1), ErrorCode::Cancelled);
+    }
+
+    #[test]
+    fn test_context_keeps_code_and_source() {
+        let result: Result<()> = Err(Error::Conflict("key reused".to_string()));
+        let err = result.context("adding prompt").unwrap_err();
+        assert_eq!(err.to_string(), "adding prompt: Conflict: key reused");
+        assert_eq!(err.code(), ErrorCode::Conflict);
+        assert!(err.source().is_some());
+        assert!(matches!(err.root_cause(), Error::Conflict(_)));
+    }
+}
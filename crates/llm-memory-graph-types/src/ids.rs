@@ -2,7 +2,8 @@
 
 use serde::{Deserialize, Serialize};
 use std::fmt;
-use uuid::Uuid;
+use std::sync::atomic::{AtomicU64, Ordering};
+use uuid::{Builder, Uuid};
 
 /// Unique identifier for a node in the graph
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -238,6 +239,152 @@ impl From<Uuid> for AgentId {
     }
 }
 
+/// Longest accepted alias, in bytes
+pub const MAX_ALIAS_LEN: usize = 256;
+
+/// What an external key, such as an application's own conversation ID,
+/// refers to in the graph
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "id", rename_all = "snake_case")]
+pub enum AliasTarget {
+    /// A session
+    Session(SessionId),
+    /// A node
+    Node(NodeId),
+}
+
+impl AliasTarget {
+    /// The session, if this alias names one
+    #[must_use]
+    pub const fn session_id(&self) -> Option<SessionId> {
+        match self {
+            Self::Session(id) => Some(*id),
+            Self::Node(_) => None,
+        }
+    }
+
+    /// The node, if this alias names one
+    #[must_use]
+    pub const fn node_id(&self) -> Option<NodeId> {
+        match self {
+            Self::Node(id) => Some(*id),
+            Self::Session(_) => None,
+        }
+    }
+}
+
+impl From<SessionId> for AliasTarget {
+    fn from(id: SessionId) -> Self {
+        Self::Session(id)
+    }
+}
+
+impl From<NodeId> for AliasTarget {
+    fn from(id: NodeId) -> Self {
+        Self::Node(id)
+    }
+}
+
+impl fmt::Display for AliasTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Session(id) => write!(f, "session {id}"),
+            Self::Node(id) => write!(f, "node {id}"),
+        }
+    }
+}
+
+/// Check that `alias` can be stored
+///
+/// Aliases are 1 to [`MAX_ALIAS_LEN`] bytes without control characters.
+///
+/// # Errors
+///
+/// Returns [`Error::ValidationError`](crate::Error::ValidationError) for any
+/// other alias.
+pub fn validate_alias(alias: &str) -> crate::Result<()> {
+    if alias.is_empty() || alias.len() > MAX_ALIAS_LEN || alias.chars().any(char::is_control) {
+        return Err(crate::Error::ValidationError(format!(
+            "alias must be 1 to {MAX_ALIAS_LEN} bytes without control characters, got {alias:?}"
+        )));
+    }
+    Ok(())
+}
+
+/// Source of the UUIDs behind newly created IDs
+///
+/// The graph draws the IDs of the sessions, nodes and edges it creates from
+/// its generator; [`RandomIds`] is the default and [`SeededIds`] makes runs
+/// reproducible.
+pub trait IdGenerator: Send + Sync {
+    /// The next UUID
+    fn next_uuid(&self) -> Uuid;
+
+    /// The next node ID
+    fn node_id(&self) -> NodeId {
+        NodeId::from_uuid(self.next_uuid())
+    }
+
+    /// The next edge ID
+    fn edge_id(&self) -> EdgeId {
+        EdgeId::from_bytes(*self.next_uuid().as_bytes())
+    }
+
+    /// The next session ID
+    fn session_id(&self) -> SessionId {
+        SessionId::from_uuid(self.next_uuid())
+    }
+}
+
+/// Random version 4 UUIDs, as returned by `NodeId::new` and friends
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RandomIds;
+
+impl IdGenerator for RandomIds {
+    fn next_uuid(&self) -> Uuid {
+        Uuid::new_v4()
+    }
+}
+
+/// Deterministic UUIDs built from a seed and a counter
+///
+/// Each UUID is a version 8 UUID holding the seed and the number of IDs
+/// generated before it, so a generator with the same seed produces the same
+/// sequence on every run. Two generators with the same seed produce the same
+/// IDs, so use one per graph, and only for graphs built from scratch.
+#[derive(Debug, Default)]
+pub struct SeededIds {
+    seed: u64,
+    counter: AtomicU64,
+}
+
+impl SeededIds {
+    /// Generator whose sequence is determined by `seed`
+    #[must_use]
+    pub const fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            counter: AtomicU64::new(0),
+        }
+    }
+
+    /// Seed this generator was created with
+    #[must_use]
+    pub const fn seed(&self) -> u64 {
+        self.seed
+    }
+}
+
+impl IdGenerator for SeededIds {
+    fn next_uuid(&self) -> Uuid {
+        let count = self.counter.fetch_add(1, Ordering::Relaxed);
+        let mut bytes = [0; 16];
+        bytes[..8].copy_from_slice(&self.seed.to_be_bytes());
+        bytes[8..].copy_from_slice(&count.to_be_bytes());
+        Builder::from_custom_bytes(bytes).into_uuid()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -292,4 +439,32 @@ mod tests {
         let display = format!("{id}");
         assert!(!display.is_empty());
     }
+
+    #[test]
+    fn test_seeded_ids_are_reproducible() {
+        let first = SeededIds::new(42);
+        let second = SeededIds::new(42);
+        let ids: Vec<NodeId> = (0..3).map(|_| first.node_id()).collect();
+        assert_eq!(ids, (0..3).map(|_| second.node_id()).collect::<Vec<_>>());
+        assert_ne!(ids[0], ids[1]);
+        assert_eq!(ids[0].as_uuid().get_version_num(), 8);
+        assert_ne!(SeededIds::new(7).node_id(), ids[0]);
+    }
+
+    #[test]
+    fn test_alias_target() {
+        let session_id = SessionId::new();
+        let target = AliasTarget::from(session_id);
+        assert_eq!(target.session_id(), Some(session_id));
+        assert_eq!(target.node_id(), None);
+
+        let json = serde_json::to_value(target).unwrap();
+        assert_eq!(json["kind"], "session");
+        assert_eq!(serde_json::from_value::<AliasTarget>(json).unwrap(), target);
+
+        validate_alias("ext:conv-42").unwrap();
+        assert!(validate_alias("").is_err());
+        assert!(validate_alias("a\nb").is_err());
+        assert!(validate_alias(&"x".repeat(MAX_ALIAS_LEN + 1)).is_err());
+    }
 }
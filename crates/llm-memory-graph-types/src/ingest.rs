@@ -0,0 +1,292 @@
+//! Data quality checks applied to prompts and responses before they are
+//! stored
+//!
+//! [`IngestValidation`] is set with
+//! [`Config::with_ingest_validation`](crate::Config::with_ingest_validation)
+//! or the `[ingest]` section of a config file. Every check is off by default.
+//! A failed check is reported as [`Error::InvalidIngest`] carrying an
+//! [`IngestViolation`], so callers can tell which rule was broken without
+//! parsing the message.
+
+use crate::{Error, Result, TokenUsage};
+use serde::{Deserialize, Serialize};
+
+/// Which ingest checks run, and their limits
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct IngestValidation {
+    /// Longest accepted content, in bytes
+    pub max_content_bytes: Option<usize>,
+    /// Strip byte order marks, U+FFFD replacement characters left by lossy
+    /// decoding and Unicode noncharacters from content before it is checked
+    pub sanitize_text: bool,
+    /// Reject content containing control characters other than tab, line
+    /// feed and carriage return
+    pub reject_control_chars: bool,
+    /// Most custom metadata entries accepted on a prompt or response
+    pub max_metadata_entries: Option<usize>,
+    /// Reject token usage whose total is not the sum of its parts, or whose
+    /// completion exceeds the prompt's `max_tokens`
+    pub check_token_usage: bool,
+}
+
+/// A rule broken by content submitted to the graph
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum IngestViolation {
+    /// The content is longer than allowed
+    #[error("{length} bytes exceeds the limit of {max}")]
+    ContentTooLong {
+        /// Length of the content in bytes
+        length: usize,
+        /// Configured limit
+        max: usize,
+    },
+    /// The content contains a control character
+    #[error("control character U+{:04X} at byte {offset}", u32::from(*character))]
+    ControlCharacter {
+        /// The character found
+        character: char,
+        /// Byte offset of the character in the content
+        offset: usize,
+    },
+    /// There are more custom metadata entries than allowed
+    #[error("{count} metadata entries exceeds the limit of {max}")]
+    TooManyMetadataEntries {
+        /// Number of entries submitted
+        count: usize,
+        /// Configured limit
+        max: usize,
+    },
+    /// The completion used more tokens than the prompt allowed
+    #[error(
+        "{completion_tokens} completion tokens exceeds the prompt's max_tokens of {max_tokens}"
+    )]
+    CompletionOverMaxTokens {
+        /// Completion tokens reported
+        completion_tokens: u32,
+        /// `max_tokens` of the prompt
+        max_tokens: usize,
+    },
+    /// The total token count is not the sum of prompt and completion tokens
+    #[error("total of {total_tokens} tokens is not {prompt_tokens} prompt + {completion_tokens} completion tokens")]
+    InconsistentTokenTotal {
+        /// Prompt tokens reported
+        prompt_tokens: u32,
+        /// Completion tokens reported
+        completion_tokens: u32,
+        /// Total tokens reported
+        total_tokens: u32,
+    },
+}
+
+impl IngestValidation {
+    /// No checks, the default
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reject content longer than `max` bytes
+    #[must_use]
+    pub const fn with_max_content_bytes(mut self, max: usize) -> Self {
+        self.max_content_bytes = Some(max);
+        self
+    }
+
+    /// Strip invisible and invalid characters from content
+    #[must_use]
+    pub const fn with_sanitize_text(mut self, enable: bool) -> Self {
+        self.sanitize_text = enable;
+        self
+    }
+
+    /// Reject content containing control characters
+    #[must_use]
+    pub const fn with_reject_control_chars(mut self, enable: bool) -> Self {
+        self.reject_control_chars = enable;
+        self
+    }
+
+    /// Reject more than `max` custom metadata entries
+    #[must_use]
+    pub const fn with_max_metadata_entries(mut self, max: usize) -> Self {
+        self.max_metadata_entries = Some(max);
+        self
+    }
+
+    /// Check reported token usage for consistency
+    #[must_use]
+    pub const fn with_token_usage_checks(mut self, enable: bool) -> Self {
+        self.check_token_usage = enable;
+        self
+    }
+
+    /// Sanitize `content` if enabled, then check it
+    ///
+    /// `field` names the content in the error, such as `"prompt content"`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidIngest`] if the content is too long or
+    /// contains a control character.
+    pub fn check_content(&self, field: &'static str, content: &mut String) -> Result<()> {
+        if self.sanitize_text && content.chars().any(is_unwanted) {
+            content.retain(|c| !is_unwanted(c));
+        }
+        if let Some(max) = self.max_content_bytes {
+            if content.len() > max {
+                return Err(Error::invalid_ingest(
+                    field,
+                    IngestViolation::ContentTooLong {
+                        length: content.len(),
+                        max,
+                    },
+                ));
+            }
+        }
+        if self.reject_control_chars {
+            if let Some((offset, character)) = content
+                .char_indices()
+                .find(|(_, c)| c.is_control() && !matches!(c, '\t' | '\n' | '\r'))
+            {
+                return Err(Error::invalid_ingest(
+                    field,
+                    IngestViolation::ControlCharacter { character, offset },
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Check the number of custom metadata entries
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidIngest`] if there are too many.
+    pub fn check_metadata(&self, field: &'static str, count: usize) -> Result<()> {
+        match self.max_metadata_entries {
+            Some(max) if count > max => Err(Error::invalid_ingest(
+                field,
+                IngestViolation::TooManyMetadataEntries { count, max },
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Check a response's token usage against itself and the `max_tokens` of
+    /// its prompt
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidIngest`] if the usage is inconsistent.
+    pub fn check_token_usage(&self, usage: &TokenUsage, max_tokens: Option<usize>) -> Result<()> {
+        if !self.check_token_usage {
+            return Ok(());
+        }
+        if usage.prompt_tokens.checked_add(usage.completion_tokens) != Some(usage.total_tokens) {
+            return Err(Error::invalid_ingest(
+                "token usage",
+                IngestViolation::InconsistentTokenTotal {
+                    prompt_tokens: usage.prompt_tokens,
+                    completion_tokens: usage.completion_tokens,
+                    total_tokens: usage.total_tokens,
+                },
+            ));
+        }
+        if let Some(max_tokens) = max_tokens {
+            if usage.completion_tokens as usize > max_tokens {
+                return Err(Error::invalid_ingest(
+                    "token usage",
+                    IngestViolation::CompletionOverMaxTokens {
+                        completion_tokens: usage.completion_tokens,
+                        max_tokens,
+                    },
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Characters removed by [`IngestValidation::sanitize_text`]
+fn is_unwanted(c: char) -> bool {
+    let code = u32::from(c);
+    c == '\u{FEFF}'
+        || c == char::REPLACEMENT_CHARACTER
+        || (0xFDD0..=0xFDEF).contains(&code)
+        || code & 0xFFFE == 0xFFFE
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_content() {
+        let rules = IngestValidation::new()
+            .with_sanitize_text(true)
+            .with_reject_control_chars(true)
+            .with_max_content_bytes(8);
+
+        let mut content = "\u{FEFF}Hi\u{FFFD}\n".to_string();
+        rules.check_content("content", &mut content).unwrap();
+        assert_eq!(content, "Hi\n");
+
+        let err = rules
+            .check_content("content", &mut "a\u{0}b".to_string())
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            Error::InvalidIngest {
+                violation: IngestViolation::ControlCharacter {
+                    character: '\0',
+                    offset: 1
+                },
+                ..
+            }
+        ));
+        assert_eq!(err.code(), crate::ErrorCode::InvalidInput);
+
+        let err = rules
+            .check_content("content", &mut "too long!".to_string())
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid content: 9 bytes exceeds the limit of 8"
+        );
+
+        // Nothing is checked by default
+        IngestValidation::default()
+            .check_content("content", &mut "\u{0}".repeat(100))
+            .unwrap();
+    }
+
+    #[test]
+    fn test_check_token_usage_and_metadata() {
+        let rules = IngestValidation::new()
+            .with_token_usage_checks(true)
+            .with_max_metadata_entries(2);
+
+        rules
+            .check_token_usage(&TokenUsage::new(10, 20), Some(20))
+            .unwrap();
+        assert!(matches!(
+            rules.check_token_usage(&TokenUsage::new(10, 21), Some(20)),
+            Err(Error::InvalidIngest {
+                violation: IngestViolation::CompletionOverMaxTokens { .. },
+                ..
+            })
+        ));
+        let mut usage = TokenUsage::new(10, 20);
+        usage.total_tokens = 5;
+        assert!(matches!(
+            rules.check_token_usage(&usage, None),
+            Err(Error::InvalidIngest {
+                violation: IngestViolation::InconsistentTokenTotal { .. },
+                ..
+            })
+        ));
+
+        rules.check_metadata("metadata", 2).unwrap();
+        assert!(rules.check_metadata("metadata", 3).is_err());
+    }
+}
@@ -6,7 +6,8 @@
 //!
 //! # Features
 //!
-//! - **Zero heavy dependencies**: Only serde, uuid, chrono, and thiserror
+//! - **Zero heavy dependencies**: Only serde, uuid, chrono, thiserror, and toml for
+//!   configuration files
 //! - **Type safety**: Strong typing for all graph entities
 //! - **Serialization**: Full serde support for all types
 //! - **Documentation**: Comprehensive docs for all public APIs
@@ -14,15 +15,13 @@
 //! # Example
 //!
 //! ```rust
-//! use llm_memory_graph_types::{NodeType, PromptNode, PromptMetadata};
+//! use llm_memory_graph_types::{Node, NodeType, PromptNode, SessionId};
 //!
-//! let prompt = PromptNode {
-//!     id: "prompt-123".to_string(),
-//!     content: "What is the capital of France?".to_string(),
-//!     metadata: PromptMetadata::default(),
-//!     session_id: Some("session-1".to_string()),
-//!     created_at: chrono::Utc::now(),
-//! };
+//! let session_id = SessionId::new();
+//! let prompt = PromptNode::new(session_id, "What is the capital of France?".to_string());
+//!
+//! let node = Node::Prompt(prompt);
+//! assert_eq!(node.node_type(), NodeType::Prompt);
 //! ```
 
 #![deny(missing_docs)]
@@ -38,20 +37,34 @@ pub mod config;
 pub mod edges;
 pub mod error;
 pub mod ids;
+pub mod ingest;
 pub mod nodes;
+pub mod schema;
 pub mod utils;
+pub mod views;
 
 // Re-export main types
-pub use config::Config;
+pub use config::{
+    ColdStorageConfig, CompressionAlgorithm, CompressionPolicy, Config, IntegrationSettings,
+    LogLevel, MaintenanceConfig, ObservatorySettings, ReadConsistency, RetentionRule,
+    SerializationFormat, ServiceSettings, SlowOpConfig, ENV_PREFIX, MAX_COLD_COMPRESSION_LEVEL,
+};
 pub use edges::{
     ContextType, Edge, EdgeType, InheritsProperties, InstantiatesProperties, InvokesProperties,
-    Priority, ReferencesProperties, TransfersToProperties,
+    Priority, ReferencesProperties, TransfersToProperties, DEFAULT_EDGE_WEIGHT,
+    RELEVANCE_ATTRIBUTE, WEIGHT_ATTRIBUTE,
+};
+pub use error::{Error, ErrorCode, InterruptReason, Result, ResultExt};
+pub use ids::{
+    validate_alias, AgentId, AliasTarget, EdgeId, IdGenerator, NodeId, RandomIds, SeededIds,
+    SessionId, TemplateId, MAX_ALIAS_LEN,
 };
-pub use error::{Error, Result};
-pub use ids::{AgentId, EdgeId, NodeId, SessionId, TemplateId};
+pub use ingest::{IngestValidation, IngestViolation};
 pub use nodes::{
-    AgentConfig, AgentMetrics, AgentNode, AgentStatus, ConversationSession, Node, NodeType,
-    PromptMetadata, PromptNode, PromptTemplate, ResponseMetadata, ResponseNode, TokenUsage,
-    ToolInvocation, VariableSpec, Version, VersionLevel,
+    AgentConfig, AgentMetrics, AgentNode, AgentStatus, ConversationSession, CustomNode, Node,
+    NodeType, PromptMetadata, PromptNode, PromptTemplate, Properties, ResponseMetadata,
+    ResponseNode, SessionStatus, TokenUsage, ToolInvocation, VariableSpec, Version, VersionLevel,
 };
+pub use schema::{GraphSchema, SchemaRule, ValidationReport, SELECTED_RESPONSE_PROPERTY};
 pub use utils::*;
+pub use views::{ViewDefinition, ViewGroup, ViewRow, ViewTotals};
@@ -6,6 +6,12 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fmt;
 
+/// Arbitrary JSON properties attached to a node or edge
+///
+/// Lets applications store domain-specific data on graph elements without
+/// changing the node types.
+pub type Properties = HashMap<String, serde_json::Value>;
+
 /// Enum representing different node types in the graph
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum NodeType {
@@ -21,6 +27,8 @@ pub enum NodeType {
     Agent,
     /// A versioned prompt template
     Template,
+    /// A node of an application-defined type, see [`CustomNode`]
+    Custom,
 }
 
 /// Generic node wrapper that contains any node type
@@ -38,6 +46,8 @@ pub enum Node {
     Agent(AgentNode),
     /// Template node
     Template(PromptTemplate),
+    /// Application-defined node
+    Custom(CustomNode),
 }
 
 impl Node {
@@ -51,6 +61,7 @@ impl Node {
             Node::ToolInvocation(t) => t.id,
             Node::Agent(a) => a.node_id,
             Node::Template(t) => t.node_id,
+            Node::Custom(c) => c.id,
         }
     }
 
@@ -64,6 +75,64 @@ impl Node {
             Node::ToolInvocation(_) => NodeType::ToolInvocation,
             Node::Agent(_) => NodeType::Agent,
             Node::Template(_) => NodeType::Template,
+            Node::Custom(_) => NodeType::Custom,
+        }
+    }
+
+    /// When the node was created, or for prompts, responses and tool
+    /// invocations, when it happened
+    #[must_use]
+    pub fn created_at(&self) -> DateTime<Utc> {
+        match self {
+            Node::Prompt(p) => p.timestamp,
+            Node::Response(r) => r.timestamp,
+            Node::Session(s) => s.created_at,
+            Node::ToolInvocation(t) => t.timestamp,
+            Node::Agent(a) => a.created_at,
+            Node::Template(t) => t.created_at,
+            Node::Custom(c) => c.created_at,
+        }
+    }
+
+    /// User-defined properties of the node
+    #[must_use]
+    pub fn properties(&self) -> &Properties {
+        match self {
+            Node::Prompt(p) => &p.properties,
+            Node::Response(r) => &r.properties,
+            Node::Session(s) => &s.properties,
+            Node::ToolInvocation(t) => &t.properties,
+            Node::Agent(a) => &a.properties,
+            Node::Template(t) => &t.properties,
+            Node::Custom(c) => &c.properties,
+        }
+    }
+
+    /// Mutable access to the user-defined properties of the node
+    pub fn properties_mut(&mut self) -> &mut Properties {
+        match self {
+            Node::Prompt(p) => &mut p.properties,
+            Node::Response(r) => &mut r.properties,
+            Node::Session(s) => &mut s.properties,
+            Node::ToolInvocation(t) => &mut t.properties,
+            Node::Agent(a) => &mut a.properties,
+            Node::Template(t) => &mut t.properties,
+            Node::Custom(c) => &mut c.properties,
+        }
+    }
+
+    /// A user-defined property of the node
+    #[must_use]
+    pub fn property(&self, key: &str) -> Option<&serde_json::Value> {
+        self.properties().get(key)
+    }
+
+    /// Type name of a custom node
+    #[must_use]
+    pub fn custom_type(&self) -> Option<&str> {
+        match self {
+            Node::Custom(c) => Some(&c.type_name),
+            _ => None,
         }
     }
 }
@@ -83,6 +152,15 @@ pub struct ConversationSession {
     pub metadata: HashMap<String, String>,
     /// Tags for categorization
     pub tags: Vec<String>,
+    /// User-defined typed properties
+    #[serde(default)]
+    pub properties: Properties,
+    /// Human-readable title, set explicitly or generated from the first prompt
+    #[serde(default)]
+    pub title: Option<String>,
+    /// Lifecycle state
+    #[serde(default)]
+    pub status: SessionStatus,
 }
 
 impl ConversationSession {
@@ -97,6 +175,9 @@ impl ConversationSession {
             updated_at: now,
             metadata: HashMap::new(),
             tags: Vec::new(),
+            properties: Properties::new(),
+            title: None,
+            status: SessionStatus::Open,
         }
     }
 
@@ -119,6 +200,32 @@ impl ConversationSession {
     pub fn touch(&mut self) {
         self.updated_at = Utc::now();
     }
+
+    /// The title if one is set, otherwise the session ID
+    #[must_use]
+    pub fn display_name(&self) -> String {
+        self.title.clone().unwrap_or_else(|| self.id.to_string())
+    }
+}
+
+/// Lifecycle state of a session
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
+pub enum SessionStatus {
+    /// The session accepts new prompts
+    #[default]
+    Open,
+    /// The conversation has ended; no new prompts are accepted
+    Closed,
+    /// The session has been archived
+    Archived,
+}
+
+impl SessionStatus {
+    /// Check if the session accepts new prompts
+    #[must_use]
+    pub const fn is_open(&self) -> bool {
+        matches!(self, Self::Open)
+    }
 }
 
 impl Default for ConversationSession {
@@ -171,6 +278,9 @@ pub struct PromptNode {
     pub variables: HashMap<String, String>,
     /// Metadata about the prompt
     pub metadata: PromptMetadata,
+    /// User-defined typed properties
+    #[serde(default)]
+    pub properties: Properties,
 }
 
 impl PromptNode {
@@ -185,6 +295,7 @@ impl PromptNode {
             content,
             variables: HashMap::new(),
             metadata: PromptMetadata::default(),
+            properties: Properties::new(),
         }
     }
 
@@ -199,6 +310,7 @@ impl PromptNode {
             content,
             variables: HashMap::new(),
             metadata,
+            properties: Properties::new(),
         }
     }
 
@@ -218,6 +330,7 @@ impl PromptNode {
             content,
             variables,
             metadata: PromptMetadata::default(),
+            properties: Properties::new(),
         }
     }
 }
@@ -284,6 +397,9 @@ pub struct ResponseNode {
     pub usage: TokenUsage,
     /// Metadata about the response
     pub metadata: ResponseMetadata,
+    /// User-defined typed properties
+    #[serde(default)]
+    pub properties: Properties,
 }
 
 impl ResponseNode {
@@ -297,6 +413,7 @@ impl ResponseNode {
             content,
             usage,
             metadata: ResponseMetadata::default(),
+            properties: Properties::new(),
         }
     }
 
@@ -315,6 +432,7 @@ impl ResponseNode {
             content,
             usage,
             metadata,
+            properties: Properties::new(),
         }
     }
 }
@@ -344,6 +462,9 @@ pub struct ToolInvocation {
     pub retry_count: u32,
     /// Additional metadata
     pub metadata: HashMap<String, String>,
+    /// User-defined typed properties
+    #[serde(default)]
+    pub properties: Properties,
 }
 
 impl ToolInvocation {
@@ -362,6 +483,7 @@ impl ToolInvocation {
             success: false,
             retry_count: 0,
             metadata: HashMap::new(),
+            properties: Properties::new(),
         }
     }
 
@@ -513,6 +635,8 @@ impl Default for AgentMetrics {
     }
 }
 
+// Task counts and latencies stay far below 2^52
+#[allow(clippy::cast_precision_loss)]
 impl AgentMetrics {
     /// Calculate success rate as a percentage
     #[must_use]
@@ -564,6 +688,9 @@ pub struct AgentNode {
     pub metrics: AgentMetrics,
     /// Tags for categorization
     pub tags: Vec<String>,
+    /// User-defined typed properties
+    #[serde(default)]
+    pub properties: Properties,
 }
 
 impl AgentNode {
@@ -584,6 +711,7 @@ impl AgentNode {
             config: AgentConfig::default(),
             metrics: AgentMetrics::default(),
             tags: Vec::new(),
+            properties: Properties::new(),
         }
     }
 
@@ -742,7 +870,7 @@ impl std::str::FromStr for Version {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let parts: Vec<&str> = s.split('.').collect();
         if parts.len() != 3 {
-            return Err(format!("Invalid version format: {}", s));
+            return Err(format!("Invalid version format: {s}"));
         }
 
         let major = parts[0]
@@ -773,7 +901,7 @@ pub enum VersionLevel {
 /// Variable specification for template variables
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VariableSpec {
-    /// Variable name (e.g., "user_query")
+    /// Variable name (e.g., `user_query`)
     pub name: String,
     /// Type hint (e.g., "string", "number", "array")
     pub type_hint: String,
@@ -826,7 +954,7 @@ impl VariableSpec {
         if let Some(val) = value {
             if let Some(ref pattern) = self.validation_pattern {
                 let re = regex::Regex::new(pattern)
-                    .map_err(|e| format!("Invalid regex pattern: {}", e))?;
+                    .map_err(|e| format!("Invalid regex pattern: {e}"))?;
                 if !re.is_match(val) {
                     return Err(format!(
                         "Variable '{}' value '{}' does not match pattern '{}'",
@@ -871,6 +999,9 @@ pub struct PromptTemplate {
     pub tags: Vec<String>,
     /// Additional metadata
     pub metadata: HashMap<String, String>,
+    /// User-defined typed properties
+    #[serde(default)]
+    pub properties: Properties,
 }
 
 impl PromptTemplate {
@@ -893,6 +1024,7 @@ impl PromptTemplate {
             usage_count: 0,
             tags: Vec::new(),
             metadata: HashMap::new(),
+            properties: Properties::new(),
         }
     }
 
@@ -947,7 +1079,7 @@ impl PromptTemplate {
         // Replace variables in template
         let mut result = self.template.clone();
         for (key, value) in final_values {
-            let placeholder = format!("{{{{{}}}}}", key);
+            let placeholder = format!("{{{{{key}}}}}");
             result = result.replace(&placeholder, &value);
         }
 
@@ -996,7 +1128,52 @@ impl PromptTemplate {
     }
 }
 
+/// A node of an application-defined type
+///
+/// Lets downstream crates store domain entities (documents, tickets, ...) in
+/// the same graph as conversations. The type name says what the payload
+/// holds; engines can register type names and check payloads before storing.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomNode {
+    /// Unique node identifier
+    pub id: NodeId,
+    /// Application-defined type name, e.g. `document`
+    pub type_name: String,
+    /// Session the node belongs to, if any
+    pub session_id: Option<SessionId>,
+    /// When the node was created
+    pub created_at: DateTime<Utc>,
+    /// Application data
+    pub payload: serde_json::Value,
+    /// User-defined typed properties
+    #[serde(default)]
+    pub properties: Properties,
+}
+
+impl CustomNode {
+    /// Create a custom node of type `type_name`
+    #[must_use]
+    pub fn new(type_name: impl Into<String>, payload: serde_json::Value) -> Self {
+        Self {
+            id: NodeId::new(),
+            type_name: type_name.into(),
+            session_id: None,
+            created_at: Utc::now(),
+            payload,
+            properties: Properties::new(),
+        }
+    }
+
+    /// Attach the node to a session
+    #[must_use]
+    pub fn with_session(mut self, session_id: SessionId) -> Self {
+        self.session_id = Some(session_id);
+        self
+    }
+}
+
 #[cfg(test)]
+#[allow(clippy::float_cmp)] // expected values are stored and read back exactly
 mod tests {
     use super::*;
 
@@ -1015,6 +1192,22 @@ mod tests {
         assert_eq!(session.tags.len(), 1);
     }
 
+    #[test]
+    fn test_session_lifecycle_fields() {
+        let session = ConversationSession::new();
+        assert_eq!(session.status, SessionStatus::Open);
+        assert_eq!(session.display_name(), session.id.to_string());
+
+        // Sessions serialized before titles and statuses existed still deserialize
+        let mut json = serde_json::to_value(&session).unwrap();
+        let fields = json.as_object_mut().unwrap();
+        fields.remove("title");
+        fields.remove("status");
+        let old: ConversationSession = serde_json::from_value(json).unwrap();
+        assert!(old.title.is_none());
+        assert!(old.status.is_open());
+    }
+
     #[test]
     fn test_prompt_creation() {
         let session_id = SessionId::new();
@@ -1023,6 +1216,41 @@ mod tests {
         assert_eq!(prompt.content, "Test prompt");
     }
 
+    #[test]
+    fn test_node_properties() {
+        let mut node = Node::Prompt(PromptNode::new(SessionId::new(), "Test".to_string()));
+        assert!(node.properties().is_empty());
+
+        node.properties_mut()
+            .insert("score".to_string(), serde_json::json!(0.9));
+        assert_eq!(node.property("score"), Some(&serde_json::json!(0.9)));
+
+        // Nodes serialized before properties existed still deserialize
+        let mut json = serde_json::to_value(&node).unwrap();
+        json["Prompt"].as_object_mut().unwrap().remove("properties");
+        let old: Node = serde_json::from_value(json).unwrap();
+        assert!(old.properties().is_empty());
+    }
+
+    #[test]
+    fn test_custom_node() {
+        let session_id = SessionId::new();
+        let custom = CustomNode::new("ticket", serde_json::json!({"title": "Login fails"}))
+            .with_session(session_id);
+        let node = Node::Custom(custom.clone());
+
+        assert_eq!(node.id(), custom.id);
+        assert_eq!(node.node_type(), NodeType::Custom);
+        assert_eq!(node.custom_type(), Some("ticket"));
+
+        let json = serde_json::to_string(&node).unwrap();
+        let back: Node = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.custom_type(), Some("ticket"));
+        assert!(Node::Prompt(PromptNode::new(session_id, String::new()))
+            .custom_type()
+            .is_none());
+    }
+
     #[test]
     fn test_response_creation() {
         let prompt_id = NodeId::new();
@@ -0,0 +1,281 @@
+//! Structural rules checked when the graph is written
+//!
+//! A [`GraphSchema`] is a list of [`SchemaRule`]s set with
+//! [`Config::with_schema`](crate::Config::with_schema). By default only
+//! [`GraphSchema::dag`] applies, keeping FOLLOWS and INHERITS edges free of
+//! cycles; [`GraphSchema::standard`] also enforces the relationships
+//! documented on [`EdgeType`], and [`GraphSchema::new`] checks nothing.
+
+use crate::{Edge, EdgeType, Error, Node, NodeType, Result};
+
+/// Node property marking the response chosen among several for one prompt
+pub const SELECTED_RESPONSE_PROPERTY: &str = "selected";
+
+/// A structural rule
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchemaRule {
+    /// Edges of `edge_type` must start at one of `from` and end at one of `to`
+    EdgeEndpoints {
+        /// Edge type the rule applies to
+        edge_type: EdgeType,
+        /// Allowed source node types
+        from: Vec<NodeType>,
+        /// Allowed target node types
+        to: Vec<NodeType>,
+    },
+    /// Both ends of every edge must exist
+    EndpointsExist,
+    /// A prompt has at most one response with [`SELECTED_RESPONSE_PROPERTY`]
+    /// set to `true`
+    SingleSelectedResponse,
+    /// Edges of this type must not form a cycle
+    Acyclic(EdgeType),
+}
+
+impl SchemaRule {
+    /// Require edges of `edge_type` to run from `from` to `to`
+    pub fn edge_endpoints(edge_type: EdgeType, from: NodeType, to: NodeType) -> Self {
+        Self::EdgeEndpoints {
+            edge_type,
+            from: vec![from],
+            to: vec![to],
+        }
+    }
+
+    /// Check an edge given its endpoints, `None` for endpoints not in the graph
+    ///
+    /// Endpoint type rules skip missing endpoints; use
+    /// [`EndpointsExist`](Self::EndpointsExist) to reject them. Rules about
+    /// nodes or cycles need more of the graph and always pass here.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::ValidationError`] describing the violation.
+    pub fn check_edge(&self, edge: &Edge, from: Option<&Node>, to: Option<&Node>) -> Result<()> {
+        match self {
+            Self::EdgeEndpoints {
+                edge_type,
+                from: allowed_from,
+                to: allowed_to,
+            } if *edge_type == edge.edge_type => {
+                for (end, node, allowed) in
+                    [("source", from, allowed_from), ("target", to, allowed_to)]
+                {
+                    if let Some(node) = node {
+                        if !allowed.contains(&node.node_type()) {
+                            return Err(Error::ValidationError(format!(
+                                "{:?} edge {} has {} {} of type {:?}, expected one of {:?}",
+                                edge.edge_type,
+                                edge.id,
+                                end,
+                                node.id(),
+                                node.node_type(),
+                                allowed
+                            )));
+                        }
+                    }
+                }
+                Ok(())
+            }
+            Self::EndpointsExist if from.is_none() || to.is_none() => {
+                let missing = if from.is_none() { edge.from } else { edge.to };
+                Err(Error::ValidationError(format!(
+                    "{:?} edge {} points at missing node {}",
+                    edge.edge_type, edge.id, missing
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Rules the graph must satisfy
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GraphSchema {
+    rules: Vec<SchemaRule>,
+}
+
+impl GraphSchema {
+    /// A schema without rules
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// FOLLOWS and INHERITS edges must not form cycles
+    ///
+    /// The schema used when none is configured.
+    #[must_use]
+    pub fn dag() -> Self {
+        Self::new()
+            .with_rule(SchemaRule::Acyclic(EdgeType::Follows))
+            .with_rule(SchemaRule::Acyclic(EdgeType::Inherits))
+    }
+
+    /// The built-in rules: the [`dag`](Self::dag) rules, `RESPONDS_TO` runs from
+    /// a response to a prompt, INVOKES from a response to a tool invocation,
+    /// and a prompt has at most one selected response
+    #[must_use]
+    pub fn standard() -> Self {
+        Self::dag()
+            .with_rule(SchemaRule::edge_endpoints(
+                EdgeType::RespondsTo,
+                NodeType::Response,
+                NodeType::Prompt,
+            ))
+            .with_rule(SchemaRule::edge_endpoints(
+                EdgeType::Invokes,
+                NodeType::Response,
+                NodeType::ToolInvocation,
+            ))
+            .with_rule(SchemaRule::SingleSelectedResponse)
+    }
+
+    /// Add a rule
+    #[must_use]
+    pub fn with_rule(mut self, rule: SchemaRule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    /// The rules, in the order they are checked
+    #[must_use]
+    pub fn rules(&self) -> &[SchemaRule] {
+        &self.rules
+    }
+
+    /// Whether the schema has no rules
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Whether any rule looks at the endpoints of edges
+    #[must_use]
+    pub fn checks_endpoints(&self) -> bool {
+        self.rules.iter().any(|rule| {
+            matches!(
+                rule,
+                SchemaRule::EdgeEndpoints { .. } | SchemaRule::EndpointsExist
+            )
+        })
+    }
+
+    /// Edge types that must not form cycles
+    #[must_use]
+    pub fn acyclic_types(&self) -> Vec<EdgeType> {
+        self.rules
+            .iter()
+            .filter_map(|rule| match rule {
+                SchemaRule::Acyclic(edge_type) => Some(edge_type.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Whether edges of `edge_type` must not form cycles
+    #[must_use]
+    pub fn is_acyclic(&self, edge_type: &EdgeType) -> bool {
+        self.rules.contains(&SchemaRule::Acyclic(edge_type.clone()))
+    }
+
+    /// Whether selected responses are limited to one per prompt
+    #[must_use]
+    pub fn limits_selected_responses(&self) -> bool {
+        self.rules.contains(&SchemaRule::SingleSelectedResponse)
+    }
+
+    /// Check an edge against every rule
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::ValidationError`] for the first violated rule.
+    pub fn check_edge(&self, edge: &Edge, from: Option<&Node>, to: Option<&Node>) -> Result<()> {
+        self.rules
+            .iter()
+            .try_for_each(|rule| rule.check_edge(edge, from, to))
+    }
+}
+
+/// Result of checking a whole graph against a schema
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ValidationReport {
+    /// Nodes examined
+    pub nodes_checked: u64,
+    /// Edges examined
+    pub edges_checked: u64,
+    /// Description of every violation found
+    pub violations: Vec<String>,
+}
+
+impl ValidationReport {
+    /// Whether no rule was violated
+    #[must_use]
+    pub fn is_valid(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+/// Whether `node` is a response marked as selected
+#[must_use]
+pub fn is_selected_response(node: &Node) -> bool {
+    matches!(node, Node::Response(_))
+        && node.property(SELECTED_RESPONSE_PROPERTY) == Some(&serde_json::Value::Bool(true))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{NodeId, PromptNode, ResponseNode, SessionId, TokenUsage};
+
+    #[test]
+    fn test_standard_edge_rules() {
+        let prompt = Node::Prompt(PromptNode::new(SessionId::new(), "Q".to_string()));
+        let response = Node::Response(ResponseNode::new(
+            prompt.id(),
+            "A".to_string(),
+            TokenUsage::new(1, 1),
+        ));
+        let schema = GraphSchema::standard();
+
+        let valid = Edge::new(response.id(), prompt.id(), EdgeType::RespondsTo);
+        assert!(schema
+            .check_edge(&valid, Some(&response), Some(&prompt))
+            .is_ok());
+
+        let reversed = Edge::new(prompt.id(), response.id(), EdgeType::RespondsTo);
+        assert!(matches!(
+            schema.check_edge(&reversed, Some(&prompt), Some(&response)),
+            Err(Error::ValidationError(_))
+        ));
+
+        // Missing endpoints only fail when existence is required
+        let dangling = Edge::new(response.id(), NodeId::new(), EdgeType::RespondsTo);
+        assert!(schema.check_edge(&dangling, Some(&response), None).is_ok());
+        let strict = schema.with_rule(SchemaRule::EndpointsExist);
+        assert!(strict.check_edge(&dangling, Some(&response), None).is_err());
+        assert!(GraphSchema::new()
+            .check_edge(&reversed, Some(&prompt), Some(&response))
+            .is_ok());
+        assert_eq!(
+            GraphSchema::standard().acyclic_types(),
+            vec![EdgeType::Follows, EdgeType::Inherits]
+        );
+        assert!(!GraphSchema::dag().checks_endpoints());
+    }
+
+    #[test]
+    fn test_selected_response() {
+        let mut response = Node::Response(ResponseNode::new(
+            NodeId::new(),
+            "A".to_string(),
+            TokenUsage::new(1, 1),
+        ));
+        assert!(!is_selected_response(&response));
+        response.properties_mut().insert(
+            SELECTED_RESPONSE_PROPERTY.to_string(),
+            serde_json::json!(true),
+        );
+        assert!(is_selected_response(&response));
+    }
+}
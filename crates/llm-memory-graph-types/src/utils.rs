@@ -52,12 +52,9 @@ where
                 std::thread::sleep(delay);
 
                 // Calculate next delay with exponential backoff
-                delay = std::cmp::min(
-                    Duration::from_millis(
-                        (delay.as_millis() as f64 * policy.backoff_multiplier) as u64,
-                    ),
-                    policy.max_delay,
-                );
+                delay =
+                    Duration::try_from_secs_f64(delay.as_secs_f64() * policy.backoff_multiplier)
+                        .map_or(policy.max_delay, |next| next.min(policy.max_delay));
             }
         }
     }
@@ -0,0 +1,243 @@
+//! Definitions of materialized views over the graph
+//!
+//! A [`ViewDefinition`] groups the nodes of one type by a few keys, such as
+//! their session or the model and day of a response, and keeps running
+//! [`ViewTotals`] for each group. Storage updates the totals on every write,
+//! so reading a group is a single lookup instead of a scan. Views are defined
+//! with [`Config::with_view`](crate::Config::with_view) or at runtime, and
+//! stay defined in the database until dropped.
+
+use crate::{Error, Node, NodeType, Result, SessionId};
+use serde::{Deserialize, Serialize};
+
+/// One part of the key a view groups nodes by
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ViewGroup {
+    /// Session the node belongs to; nodes outside sessions are skipped
+    Session,
+    /// Model of a response; other nodes are skipped
+    Model,
+    /// UTC date the node was created, as `YYYY-MM-DD`
+    Day,
+    /// Type name of a custom node; other nodes are skipped
+    CustomType,
+    /// Value of a user-defined property; nodes without it are skipped
+    Property(String),
+}
+
+/// A materialized view: running totals of the nodes of one type, grouped
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ViewDefinition {
+    /// Unique name of the view
+    pub name: String,
+    /// Only nodes of this type are counted; `None` counts every node
+    #[serde(default)]
+    pub node_type: Option<NodeType>,
+    /// Keys the nodes are grouped by, in order; empty for one grand total
+    #[serde(default)]
+    pub group_by: Vec<ViewGroup>,
+}
+
+impl ViewDefinition {
+    /// A view of every node, grouped by nothing yet
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            node_type: None,
+            group_by: Vec::new(),
+        }
+    }
+
+    /// Count only nodes of `node_type`
+    #[must_use]
+    pub fn of_type(mut self, node_type: NodeType) -> Self {
+        self.node_type = Some(node_type);
+        self
+    }
+
+    /// Add a key to group by
+    #[must_use]
+    pub fn group_by(mut self, group: ViewGroup) -> Self {
+        self.group_by.push(group);
+        self
+    }
+
+    /// Token totals of the responses in each session, named `session_usage`
+    pub fn session_usage() -> Self {
+        Self::new("session_usage")
+            .of_type(NodeType::Response)
+            .group_by(ViewGroup::Session)
+    }
+
+    /// Token totals of the responses of each model per day, named
+    /// `model_daily_usage`
+    pub fn model_daily_usage() -> Self {
+        Self::new("model_daily_usage")
+            .of_type(NodeType::Response)
+            .group_by(ViewGroup::Model)
+            .group_by(ViewGroup::Day)
+    }
+
+    /// Check that the view can be stored
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::ValidationError`] if the name is empty or contains a
+    /// NUL character.
+    pub fn validate(&self) -> Result<()> {
+        if self.name.is_empty() || self.name.contains('\0') {
+            return Err(Error::ValidationError(format!(
+                "view name must be non-empty and free of NUL characters, got {:?}",
+                self.name
+            )));
+        }
+        Ok(())
+    }
+
+    /// Group `node` belongs to, or `None` if the view does not count it
+    ///
+    /// `session_id` is the session the node is stored under, which storage
+    /// looks up for responses.
+    pub fn group_of(&self, node: &Node, session_id: Option<SessionId>) -> Option<Vec<String>> {
+        if self
+            .node_type
+            .as_ref()
+            .is_some_and(|node_type| *node_type != node.node_type())
+        {
+            return None;
+        }
+        self.group_by
+            .iter()
+            .map(|group| match group {
+                ViewGroup::Session => session_id.map(|id| id.to_string()),
+                ViewGroup::Model => match node {
+                    Node::Response(r) => Some(r.metadata.model.clone()),
+                    _ => None,
+                },
+                ViewGroup::Day => {
+                    let timestamp = match node {
+                        Node::Prompt(p) => p.timestamp,
+                        Node::Response(r) => r.timestamp,
+                        Node::Session(s) => s.created_at,
+                        Node::ToolInvocation(t) => t.timestamp,
+                        Node::Agent(a) => a.created_at,
+                        Node::Template(t) => t.created_at,
+                        Node::Custom(c) => c.created_at,
+                    };
+                    Some(timestamp.format("%Y-%m-%d").to_string())
+                }
+                ViewGroup::CustomType => node.custom_type().map(str::to_string),
+                ViewGroup::Property(key) => node.property(key).map(|value| match value {
+                    serde_json::Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                }),
+            })
+            .collect()
+    }
+}
+
+/// Running totals of the nodes in one group of a view
+///
+/// Token counts and latency come from responses and are zero for other
+/// nodes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ViewTotals {
+    /// Number of nodes
+    pub count: u64,
+    /// Prompt tokens used
+    pub prompt_tokens: u64,
+    /// Completion tokens used
+    pub completion_tokens: u64,
+    /// Total tokens used
+    pub total_tokens: u64,
+    /// Total response latency in milliseconds
+    pub latency_ms: u64,
+}
+
+impl ViewTotals {
+    /// Contribution of one node
+    pub fn of(node: &Node) -> Self {
+        match node {
+            Node::Response(r) => Self {
+                count: 1,
+                prompt_tokens: u64::from(r.usage.prompt_tokens),
+                completion_tokens: u64::from(r.usage.completion_tokens),
+                total_tokens: u64::from(r.usage.total_tokens),
+                latency_ms: r.metadata.latency_ms,
+            },
+            _ => Self {
+                count: 1,
+                ..Self::default()
+            },
+        }
+    }
+
+    /// Add `other` to these totals
+    pub fn add(&mut self, other: &Self) {
+        self.count += other.count;
+        self.prompt_tokens += other.prompt_tokens;
+        self.completion_tokens += other.completion_tokens;
+        self.total_tokens += other.total_tokens;
+        self.latency_ms += other.latency_ms;
+    }
+
+    /// Take `other` away from these totals
+    pub fn subtract(&mut self, other: &Self) {
+        self.count = self.count.saturating_sub(other.count);
+        self.prompt_tokens = self.prompt_tokens.saturating_sub(other.prompt_tokens);
+        self.completion_tokens = self
+            .completion_tokens
+            .saturating_sub(other.completion_tokens);
+        self.total_tokens = self.total_tokens.saturating_sub(other.total_tokens);
+        self.latency_ms = self.latency_ms.saturating_sub(other.latency_ms);
+    }
+}
+
+/// Totals of one group of a view
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ViewRow {
+    /// Values of the view's group keys, in order
+    pub group: Vec<String>,
+    /// Totals of the nodes in the group
+    pub totals: ViewTotals,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{NodeId, PromptNode, ResponseNode, TokenUsage};
+
+    #[test]
+    fn test_view_groups() {
+        let session_id = SessionId::new();
+        let prompt = Node::Prompt(PromptNode::new(session_id, "Hi".to_string()));
+        let mut response =
+            ResponseNode::new(NodeId::new(), "Hello".to_string(), TokenUsage::new(3, 4));
+        response.metadata.model = "gpt-4".to_string();
+        let day = response.timestamp.format("%Y-%m-%d").to_string();
+        let response = Node::Response(response);
+
+        let usage = ViewDefinition::session_usage();
+        assert_eq!(usage.group_of(&prompt, Some(session_id)), None);
+        assert_eq!(
+            usage.group_of(&response, Some(session_id)),
+            Some(vec![session_id.to_string()])
+        );
+        assert_eq!(usage.group_of(&response, None), None);
+        assert_eq!(
+            ViewDefinition::model_daily_usage().group_of(&response, None),
+            Some(vec!["gpt-4".to_string(), day])
+        );
+
+        let mut totals = ViewTotals::of(&response);
+        assert_eq!(totals.total_tokens, 7);
+        totals.add(&ViewTotals::of(&prompt));
+        assert_eq!((totals.count, totals.total_tokens), (2, 7));
+        totals.subtract(&ViewTotals::of(&response));
+        assert_eq!(totals, ViewTotals::of(&prompt));
+
+        assert!(ViewDefinition::new("").validate().is_err());
+        assert!(ViewDefinition::new("a\0b").validate().is_err());
+    }
+}
@@ -0,0 +1,60 @@
+//! Throughput of batched versus per-record node writes
+//!
+//! Imports 10,000 prompt nodes into a Sled database, either one `store_node`
+//! call at a time or with a single `store_nodes_batch` call. Each benchmark
+//! keeps one database open and imports fresh nodes on every iteration.
+//!
+//! Run with: cargo bench -p llm-memory-graph --bench batch_writes
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+use llm_memory_graph::storage::{SledBackend, StorageBackend};
+use llm_memory_graph::{ConversationSession, Node, PromptNode};
+use std::time::Duration;
+use tempfile::tempdir;
+
+const NODE_COUNT: usize = 10_000;
+
+fn nodes() -> Vec<Node> {
+    let session = ConversationSession::new();
+    let mut nodes = vec![Node::Session(session.clone())];
+    nodes.extend(
+        (0..NODE_COUNT - 1)
+            .map(|i| Node::Prompt(PromptNode::new(session.id, format!("Prompt {i}")))),
+    );
+    nodes
+}
+
+fn bench_node_import(c: &mut Criterion) {
+    let mut group = c.benchmark_group("import_10k_nodes");
+    group.sample_size(10);
+    group.measurement_time(Duration::from_secs(30));
+    group.throughput(Throughput::Elements(NODE_COUNT as u64));
+
+    let dir = tempdir().unwrap();
+    let backend = SledBackend::open(dir.path().join("store_node")).unwrap();
+    group.bench_function("store_node", |b| {
+        b.iter_batched(
+            nodes,
+            |nodes| {
+                for node in &nodes {
+                    backend.store_node(node).unwrap();
+                }
+            },
+            BatchSize::PerIteration,
+        );
+    });
+
+    let backend = SledBackend::open(dir.path().join("store_nodes_batch")).unwrap();
+    group.bench_function("store_nodes_batch", |b| {
+        b.iter_batched(
+            nodes,
+            |nodes| backend.store_nodes_batch(&nodes).unwrap(),
+            BatchSize::PerIteration,
+        );
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_node_import);
+criterion_main!(benches);
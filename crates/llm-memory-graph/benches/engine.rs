@@ -0,0 +1,364 @@
+//! Engine benchmarks with a JSON summary for comparing commits
+//!
+//! Covers the hot paths of [`AsyncMemoryGraph`]: single and batched prompt
+//! writes, listing the nodes of 10k- and 100k-node sessions, cached and
+//! uncached node reads, and subgraph traversal.
+//!
+//! After the run, the estimates Criterion saved are collected into one JSON
+//! file, `engine-summary.json` in the Criterion directory unless
+//! `LMG_BENCH_OUTPUT` names another path. If `LMG_BENCH_BASELINE` names a
+//! summary from an earlier commit, the change in each mean is printed and the
+//! run fails when any benchmark got slower by more than
+//! `LMG_BENCH_THRESHOLD` percent (default 10).
+//!
+//! ```text
+//! cargo bench -p llm-memory-graph --bench engine
+//! cp target/criterion/engine-summary.json main.json
+//! # ...switch commits...
+//! LMG_BENCH_BASELINE=main.json cargo bench -p llm-memory-graph --bench engine
+//! ```
+
+use criterion::{criterion_group, BenchmarkId, Criterion, Throughput};
+use llm_memory_graph::engine::AsyncMemoryGraph;
+use llm_memory_graph::storage::{SledBackend, StorageBackend};
+use llm_memory_graph::{
+    Config, ConversationSession, Node, NodeId, PromptNode, SessionId, TokenUsage,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Duration;
+use tempfile::{tempdir, TempDir};
+use tokio::runtime::Runtime;
+
+/// Benchmark groups defined here, and so included in the summary
+const GROUPS: [&str; 5] = [
+    "add_prompt",
+    "batch_insert",
+    "get_session_nodes",
+    "node_reads",
+    "traversal",
+];
+
+const DEFAULT_THRESHOLD_PERCENT: f64 = 10.0;
+
+fn runtime() -> Runtime {
+    Runtime::new().unwrap()
+}
+
+/// A graph in a fresh directory
+fn open_graph(rt: &Runtime, config: impl FnOnce(Config) -> Config) -> (TempDir, AsyncMemoryGraph) {
+    let dir = tempdir().unwrap();
+    let graph = rt
+        .block_on(AsyncMemoryGraph::open(config(Config::new(dir.path()))))
+        .unwrap();
+    (dir, graph)
+}
+
+/// A database holding one session with `size` nodes, written in one batch
+fn seeded_session(size: usize) -> (TempDir, SessionId) {
+    let dir = tempdir().unwrap();
+    let session = ConversationSession::new();
+    let mut nodes = vec![Node::Session(session.clone())];
+    nodes.extend(
+        (0..size - 1).map(|i| Node::Prompt(PromptNode::new(session.id, format!("Prompt {i}")))),
+    );
+    let backend = SledBackend::open(dir.path()).unwrap();
+    backend.store_nodes_batch(&nodes).unwrap();
+    backend.flush().unwrap();
+    (dir, session.id)
+}
+
+fn bench_add_prompt(c: &mut Criterion) {
+    let rt = runtime();
+    let (_dir, graph) = open_graph(&rt, |config| config);
+    let session = rt.block_on(graph.create_session()).unwrap();
+
+    let mut group = c.benchmark_group("add_prompt");
+    group.throughput(Throughput::Elements(1));
+    group.bench_function("single", |b| {
+        b.iter(|| {
+            rt.block_on(graph.add_prompt(session.id, "What is the weather?".to_string(), None))
+                .unwrap()
+        });
+    });
+    group.finish();
+}
+
+fn bench_batch_insert(c: &mut Criterion) {
+    let rt = runtime();
+    let (_dir, graph) = open_graph(&rt, |config| config);
+    let session = rt.block_on(graph.create_session()).unwrap();
+
+    let mut group = c.benchmark_group("batch_insert");
+    group.sample_size(20);
+    for size in [10, 100, 1_000] {
+        group.throughput(Throughput::Elements(size as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(size), &size, |b, &size| {
+            b.iter(|| {
+                let prompts = (0..size)
+                    .map(|i| (session.id, format!("Prompt {i}")))
+                    .collect();
+                rt.block_on(graph.add_prompts_batch(prompts)).unwrap()
+            });
+        });
+    }
+    group.finish();
+}
+
+fn bench_get_session_nodes(c: &mut Criterion) {
+    let rt = runtime();
+
+    let mut group = c.benchmark_group("get_session_nodes");
+    group.sample_size(10);
+    group.measurement_time(Duration::from_secs(20));
+    for size in [10_000, 100_000] {
+        let (dir, session_id) = seeded_session(size);
+        let graph = rt
+            .block_on(AsyncMemoryGraph::open(Config::new(dir.path())))
+            .unwrap();
+        group.throughput(Throughput::Elements(size as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(size), &size, |b, &size| {
+            b.iter(|| {
+                let nodes = rt.block_on(graph.get_session_nodes(&session_id)).unwrap();
+                assert_eq!(nodes.len(), size);
+            });
+        });
+    }
+    group.finish();
+}
+
+fn bench_node_reads(c: &mut Criterion) {
+    const READ_SET: usize = 10_000;
+    let rt = runtime();
+    // 1 MB of cache holds about 1,000 nodes, so cycling through 10,000
+    // distinct nodes misses on nearly every read
+    let (_dir, graph) = open_graph(&rt, |config| config.with_cache_size(1));
+    let session = rt.block_on(graph.create_session()).unwrap();
+    let prompts = (0..READ_SET)
+        .map(|i| (session.id, format!("Prompt {i}")))
+        .collect();
+    let ids: Vec<NodeId> = rt.block_on(graph.add_prompts_batch(prompts)).unwrap();
+
+    let mut group = c.benchmark_group("node_reads");
+    group.throughput(Throughput::Elements(1));
+    group.bench_function("cache_hit", |b| {
+        rt.block_on(graph.get_node(&ids[0])).unwrap();
+        b.iter(|| rt.block_on(graph.get_node(&ids[0])).unwrap());
+    });
+    group.bench_function("cache_miss", |b| {
+        let mut next = ids.iter().cycle();
+        b.iter(|| rt.block_on(graph.get_node(next.next().unwrap())).unwrap());
+    });
+    group.finish();
+}
+
+fn bench_traversal(c: &mut Criterion) {
+    let rt = runtime();
+    let (_dir, graph) = open_graph(&rt, |config| config);
+    let session = rt.block_on(graph.create_session()).unwrap();
+    for i in 0..1_000 {
+        let prompt = rt
+            .block_on(graph.add_prompt(session.id, format!("Prompt {i}"), None))
+            .unwrap();
+        rt.block_on(graph.add_response(
+            prompt,
+            format!("Response {i}"),
+            TokenUsage::new(10, 20),
+            None,
+        ))
+        .unwrap();
+    }
+    let root = session.node_id;
+
+    let mut group = c.benchmark_group("traversal");
+    group.sample_size(20);
+    for depth in [2, 8] {
+        group.bench_with_input(
+            BenchmarkId::new("subgraph_depth", depth),
+            &depth,
+            |b, &depth| {
+                b.iter(|| {
+                    rt.block_on(graph.traversal().max_depth(depth).build_subgraph(root))
+                        .unwrap()
+                });
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_add_prompt,
+    bench_batch_insert,
+    bench_get_session_nodes,
+    bench_node_reads,
+    bench_traversal
+);
+
+/// Results of one run, as written to the summary file
+#[derive(Debug, Serialize, Deserialize)]
+struct Summary {
+    /// Commit the benchmarks ran against, if known
+    commit: Option<String>,
+    /// Estimates keyed by benchmark id, such as `batch_insert/100`
+    benchmarks: BTreeMap<String, Estimate>,
+}
+
+/// Time per iteration of one benchmark, in nanoseconds
+#[derive(Debug, Serialize, Deserialize)]
+struct Estimate {
+    mean_ns: f64,
+    median_ns: f64,
+    std_dev_ns: f64,
+}
+
+/// The parts of Criterion's `benchmark.json` the summary uses
+#[derive(Deserialize)]
+struct CriterionBenchmark {
+    full_id: String,
+}
+
+/// The parts of Criterion's `estimates.json` the summary uses
+#[derive(Deserialize)]
+struct CriterionEstimates {
+    mean: PointEstimate,
+    median: PointEstimate,
+    std_dev: PointEstimate,
+}
+
+#[derive(Deserialize)]
+struct PointEstimate {
+    point_estimate: f64,
+}
+
+/// Where Criterion saves its results, resolved the way Criterion does
+fn criterion_dir() -> PathBuf {
+    if let Some(home) = std::env::var_os("CRITERION_HOME") {
+        return PathBuf::from(home);
+    }
+    std::env::var_os("CARGO_TARGET_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| Path::new(env!("CARGO_MANIFEST_DIR")).join("../../target"))
+        .join("criterion")
+}
+
+fn current_commit() -> Option<String> {
+    let output = Command::new("git")
+        .args(["rev-parse", "HEAD"])
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Add the latest results saved under `dir` to `benchmarks`
+fn collect_estimates(dir: &Path, benchmarks: &mut BTreeMap<String, Estimate>) {
+    let latest = dir.join("new");
+    if let (Ok(benchmark), Ok(estimates)) = (
+        std::fs::read(latest.join("benchmark.json")),
+        std::fs::read(latest.join("estimates.json")),
+    ) {
+        if let (Ok(benchmark), Ok(estimates)) = (
+            serde_json::from_slice::<CriterionBenchmark>(&benchmark),
+            serde_json::from_slice::<CriterionEstimates>(&estimates),
+        ) {
+            benchmarks.insert(
+                benchmark.full_id,
+                Estimate {
+                    mean_ns: estimates.mean.point_estimate,
+                    median_ns: estimates.median.point_estimate,
+                    std_dev_ns: estimates.std_dev.point_estimate,
+                },
+            );
+        }
+        return;
+    }
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() && path.file_name().is_some_and(|name| name != "report") {
+            collect_estimates(&path, benchmarks);
+        }
+    }
+}
+
+/// Print the change in each mean against `baseline`; returns the ids of
+/// benchmarks that got slower by more than `threshold` percent
+fn compare(baseline: &Summary, current: &Summary, threshold: f64) -> Vec<String> {
+    println!(
+        "\nChange against {}:",
+        baseline.commit.as_deref().unwrap_or("baseline")
+    );
+    let mut regressions = Vec::new();
+    for (id, estimate) in &current.benchmarks {
+        let Some(before) = baseline.benchmarks.get(id) else {
+            println!("  {id:<40} new");
+            continue;
+        };
+        let change = (estimate.mean_ns - before.mean_ns) / before.mean_ns * 100.0;
+        println!("  {id:<40} {change:+7.2}%");
+        if change > threshold {
+            regressions.push(id.clone());
+        }
+    }
+    regressions
+}
+
+fn write_summary() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = criterion_dir();
+    let mut benchmarks = BTreeMap::new();
+    for group in GROUPS {
+        collect_estimates(&dir.join(group), &mut benchmarks);
+    }
+    let summary = Summary {
+        commit: current_commit(),
+        benchmarks,
+    };
+
+    let output = std::env::var_os("LMG_BENCH_OUTPUT")
+        .map_or_else(|| dir.join("engine-summary.json"), PathBuf::from);
+    if let Some(parent) = output.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&output, serde_json::to_vec_pretty(&summary)?)?;
+    println!("Wrote benchmark summary to {}", output.display());
+
+    if let Some(baseline) = std::env::var_os("LMG_BENCH_BASELINE") {
+        let baseline: Summary = serde_json::from_slice(&std::fs::read(baseline)?)?;
+        let threshold = match std::env::var("LMG_BENCH_THRESHOLD") {
+            Ok(value) => value.parse()?,
+            Err(_) => DEFAULT_THRESHOLD_PERCENT,
+        };
+        let regressions = compare(&baseline, &summary, threshold);
+        if !regressions.is_empty() {
+            return Err(format!(
+                "{} benchmark(s) slower by more than {threshold}%: {}",
+                regressions.len(),
+                regressions.join(", ")
+            )
+            .into());
+        }
+    }
+    Ok(())
+}
+
+fn main() {
+    benches();
+    Criterion::default().configure_from_args().final_summary();
+
+    // `cargo test --benches` runs each benchmark once without saving results
+    if std::env::args().any(|arg| arg == "--bench") {
+        if let Err(e) = write_summary() {
+            eprintln!("error: {e}");
+            std::process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,49 @@
+//! Decode arbitrary bytes as a stored node and edge in every format
+//!
+//! Decoding may fail, but must not panic; anything that decodes must encode
+//! again and decode to the same value.
+//!
+//! ```text
+//! cargo +nightly fuzz run deserialize_record
+//! ```
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use llm_memory_graph::storage::{SerializationFormat, Serializer};
+
+const FORMATS: [SerializationFormat; 3] = [
+    SerializationFormat::Json,
+    SerializationFormat::MessagePack,
+    SerializationFormat::Bincode,
+];
+
+fuzz_target!(|data: &[u8]| {
+    for format in FORMATS {
+        let serializer = Serializer::new(format);
+
+        if let Ok(node) = serializer.deserialize_node(data) {
+            if let Ok(bytes) = serializer.serialize_node(&node) {
+                let again = serializer
+                    .deserialize_node(&bytes)
+                    .expect("re-encoded node must decode");
+                assert_eq!(
+                    serde_json::to_value(&node).unwrap(),
+                    serde_json::to_value(&again).unwrap()
+                );
+            }
+        }
+
+        if let Ok(edge) = serializer.deserialize_edge(data) {
+            if let Ok(bytes) = serializer.serialize_edge(&edge) {
+                let again = serializer
+                    .deserialize_edge(&bytes)
+                    .expect("re-encoded edge must decode");
+                assert_eq!(
+                    serde_json::to_value(&edge).unwrap(),
+                    serde_json::to_value(&again).unwrap()
+                );
+            }
+        }
+    }
+});
@@ -0,0 +1,419 @@
+//! Scrubbed copies of graph data for sharing outside the organisation
+//!
+//! An [`Anonymizer`] rewrites nodes and edges so they can leave the system
+//! that produced them:
+//!
+//! - values stored under identifier keys (`user_id`, `email`, ... in
+//!   metadata, properties and JSON payloads) are replaced by a salted hash, so
+//!   records of the same user still group together
+//! - every other piece of text is passed through [`RedactionRule`]s that
+//!   replace emails, phone numbers, card numbers and similar PII
+//!
+//! Ids, timestamps, token counts and edges are kept, so the copy has the same
+//! shape as the original.
+//! [`AsyncMemoryGraph::anonymize_session`](crate::engine::AsyncMemoryGraph::anonymize_session)
+//! and [`anonymize_graph`](crate::engine::AsyncMemoryGraph::anonymize_graph)
+//! write the copy into a separate graph, which can then be exported with the
+//! usual tools.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use llm_memory_graph::anonymize::{Anonymizer, RedactionRule};
+//! use llm_memory_graph::engine::AsyncMemoryGraph;
+//! use llm_memory_graph::Config;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let graph = AsyncMemoryGraph::open(Config::new("./data/graph.db")).await?;
+//! let shared = AsyncMemoryGraph::open(Config::new("./data/shared.db")).await?;
+//!
+//! let anonymizer = Anonymizer::new("per-dataset secret")
+//!     .with_identifier_key("tenant")
+//!     .with_rule(RedactionRule::new("order", r"\bORD-\d{6}\b", "[ORDER]")?);
+//! let report = graph.anonymize_graph(&anonymizer, &shared).await?;
+//! println!("{} redactions in {} nodes", report.redactions, report.nodes_copied);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{Edge, Error, Node, Properties, Result};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// Keys whose values are hashed rather than redacted, compared
+/// case-insensitively
+pub const DEFAULT_IDENTIFIER_KEYS: &[&str] = &[
+    "user_id",
+    "user",
+    "username",
+    "email",
+    "customer_id",
+    "account_id",
+    "ip",
+    "ip_address",
+];
+
+/// Prefix of hashed identifiers
+pub const HASHED_PREFIX: &str = "anon_";
+
+/// A pattern replaced wherever it occurs in text
+#[derive(Debug, Clone)]
+pub struct RedactionRule {
+    name: String,
+    pattern: Regex,
+    replacement: String,
+}
+
+impl RedactionRule {
+    /// Replace matches of the regular expression `pattern` with `replacement`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `pattern` is not a valid regular expression.
+    pub fn new(
+        name: impl Into<String>,
+        pattern: &str,
+        replacement: impl Into<String>,
+    ) -> Result<Self> {
+        let name = name.into();
+        let pattern = Regex::new(pattern).map_err(|e| {
+            Error::ValidationError(format!("invalid redaction pattern for {name}: {e}"))
+        })?;
+        Ok(Self {
+            name,
+            pattern,
+            replacement: replacement.into(),
+        })
+    }
+
+    /// Rules for emails, card numbers, US social security numbers, phone
+    /// numbers and IPv4 addresses
+    pub fn defaults() -> Vec<Self> {
+        [
+            (
+                "email",
+                r"(?i)\b[A-Z0-9._%+-]+@[A-Z0-9.-]+\.[A-Z]{2,}\b",
+                "[EMAIL]",
+            ),
+            ("card", r"\b(?:\d{4}[ -]?){3}\d{1,4}\b", "[CARD]"),
+            ("ssn", r"\b\d{3}-\d{2}-\d{4}\b", "[SSN]"),
+            (
+                "phone",
+                r"(?:\+\d{1,3}[ .-]?)?(?:\(\d{3}\)|\b\d{3})[ .-]?\d{3}[ .-]?\d{4}\b",
+                "[PHONE]",
+            ),
+            ("ip", r"\b(?:\d{1,3}\.){3}\d{1,3}\b", "[IP]"),
+        ]
+        .into_iter()
+        .map(|(name, pattern, replacement)| {
+            Self::new(name, pattern, replacement).expect("built-in redaction patterns are valid")
+        })
+        .collect()
+    }
+
+    /// Name of the rule
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Whether the pattern occurs in `text`
+    pub fn is_match(&self, text: &str) -> bool {
+        self.pattern.is_match(text)
+    }
+}
+
+/// Counts from an anonymization run
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AnonymizationReport {
+    /// Nodes written to the copy
+    pub nodes_copied: usize,
+    /// Edges written to the copy
+    pub edges_copied: usize,
+    /// Identifier values replaced by hashes
+    pub identifiers_hashed: usize,
+    /// Matches replaced by redaction rules
+    pub redactions: usize,
+}
+
+/// Hashes identifiers and redacts PII in nodes and edges
+#[derive(Debug, Clone)]
+pub struct Anonymizer {
+    salt: String,
+    identifier_keys: Vec<String>,
+    rules: Vec<RedactionRule>,
+}
+
+impl Anonymizer {
+    /// Anonymizer hashing with `salt`, with the [`DEFAULT_IDENTIFIER_KEYS`]
+    /// and [default rules](RedactionRule::defaults)
+    ///
+    /// Use a salt that is kept private and differs between datasets, so
+    /// hashes cannot be matched against known identifiers or across releases.
+    pub fn new(salt: impl Into<String>) -> Self {
+        Self {
+            salt: salt.into(),
+            identifier_keys: DEFAULT_IDENTIFIER_KEYS
+                .iter()
+                .map(|key| (*key).to_string())
+                .collect(),
+            rules: RedactionRule::defaults(),
+        }
+    }
+
+    /// Also hash values stored under `key`
+    pub fn with_identifier_key(mut self, key: impl Into<String>) -> Self {
+        self.identifier_keys.push(key.into().to_ascii_lowercase());
+        self
+    }
+
+    /// Add a redaction rule, applied after those already configured
+    pub fn with_rule(mut self, rule: RedactionRule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    /// Replace the redaction rules
+    pub fn with_rules(mut self, rules: Vec<RedactionRule>) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    /// Salted hash standing in for `value`
+    pub fn hash_identifier(&self, value: &str) -> String {
+        let digest = Sha256::new()
+            .chain_update(self.salt.as_bytes())
+            .chain_update([0])
+            .chain_update(value.as_bytes())
+            .finalize();
+        digest[..8]
+            .iter()
+            .fold(HASHED_PREFIX.to_string(), |mut out, byte| {
+                let _ = write!(out, "{byte:02x}");
+                out
+            })
+    }
+
+    /// `text` with every redaction rule applied
+    pub fn redact(&self, text: &str) -> String {
+        let mut report = AnonymizationReport::default();
+        self.redact_counted(text, &mut report)
+    }
+
+    /// Anonymized copy of `node`
+    pub fn anonymize_node(&self, node: &Node) -> Node {
+        let mut report = AnonymizationReport::default();
+        self.scrub_node(node, &mut report)
+    }
+
+    /// Anonymized copy of `edge`
+    pub fn anonymize_edge(&self, edge: &Edge) -> Edge {
+        let mut report = AnonymizationReport::default();
+        self.scrub_edge(edge, &mut report)
+    }
+
+    pub(crate) fn scrub_node(&self, node: &Node, report: &mut AnonymizationReport) -> Node {
+        let mut node = node.clone();
+        match &mut node {
+            Node::Session(session) => {
+                self.scrub_map(&mut session.metadata, report);
+                self.scrub_properties(&mut session.properties, report);
+                if let Some(title) = &mut session.title {
+                    *title = self.redact_counted(title, report);
+                }
+            }
+            Node::Prompt(prompt) => {
+                prompt.content = self.redact_counted(&prompt.content, report);
+                self.scrub_map(&mut prompt.metadata.custom, report);
+                self.scrub_map(&mut prompt.variables, report);
+                self.scrub_properties(&mut prompt.properties, report);
+            }
+            Node::Response(response) => {
+                response.content = self.redact_counted(&response.content, report);
+                self.scrub_map(&mut response.metadata.custom, report);
+                self.scrub_properties(&mut response.properties, report);
+            }
+            Node::ToolInvocation(tool) => {
+                self.scrub_value(&mut tool.parameters, report);
+                if let Some(result) = &mut tool.result {
+                    self.scrub_value(result, report);
+                }
+                if let Some(error) = &mut tool.error {
+                    *error = self.redact_counted(error, report);
+                }
+                self.scrub_map(&mut tool.metadata, report);
+                self.scrub_properties(&mut tool.properties, report);
+            }
+            Node::Agent(agent) => self.scrub_properties(&mut agent.properties, report),
+            Node::Template(template) => {
+                template.author = self.hash_counted(&template.author, report);
+                self.scrub_map(&mut template.metadata, report);
+                self.scrub_properties(&mut template.properties, report);
+            }
+            Node::Custom(custom) => {
+                self.scrub_value(&mut custom.payload, report);
+                self.scrub_properties(&mut custom.properties, report);
+            }
+        }
+        node
+    }
+
+    pub(crate) fn scrub_edge(&self, edge: &Edge, report: &mut AnonymizationReport) -> Edge {
+        let mut edge = edge.clone();
+        self.scrub_map(&mut edge.properties, report);
+        self.scrub_properties(&mut edge.attributes, report);
+        edge
+    }
+
+    fn is_identifier(&self, key: &str) -> bool {
+        self.identifier_keys
+            .iter()
+            .any(|identifier| identifier.eq_ignore_ascii_case(key))
+    }
+
+    fn hash_counted(&self, value: &str, report: &mut AnonymizationReport) -> String {
+        report.identifiers_hashed += 1;
+        self.hash_identifier(value)
+    }
+
+    fn redact_counted(&self, text: &str, report: &mut AnonymizationReport) -> String {
+        let mut text = text.to_string();
+        for rule in &self.rules {
+            let matches = rule.pattern.find_iter(&text).count();
+            if matches > 0 {
+                report.redactions += matches;
+                text = rule
+                    .pattern
+                    .replace_all(&text, rule.replacement.as_str())
+                    .into_owned();
+            }
+        }
+        text
+    }
+
+    fn scrub_map(&self, map: &mut HashMap<String, String>, report: &mut AnonymizationReport) {
+        for (key, value) in map.iter_mut() {
+            *value = if self.is_identifier(key) {
+                self.hash_counted(value, report)
+            } else {
+                self.redact_counted(value, report)
+            };
+        }
+    }
+
+    fn scrub_properties(&self, properties: &mut Properties, report: &mut AnonymizationReport) {
+        for (key, value) in properties.iter_mut() {
+            self.scrub_entry(key, value, report);
+        }
+    }
+
+    fn scrub_entry(&self, key: &str, value: &mut Value, report: &mut AnonymizationReport) {
+        match value {
+            Value::String(s) if self.is_identifier(key) => *s = self.hash_counted(s, report),
+            Value::Number(n) if self.is_identifier(key) => {
+                *value = Value::String(self.hash_counted(&n.to_string(), report));
+            }
+            _ => self.scrub_value(value, report),
+        }
+    }
+
+    fn scrub_value(&self, value: &mut Value, report: &mut AnonymizationReport) {
+        match value {
+            Value::String(s) => *s = self.redact_counted(s, report),
+            Value::Array(items) => {
+                for item in items {
+                    self.scrub_value(item, report);
+                }
+            }
+            Value::Object(map) => {
+                for (key, value) in map.iter_mut() {
+                    self.scrub_entry(key, value, report);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ConversationSession, PromptNode, ToolInvocation};
+
+    #[test]
+    fn test_redact_defaults() {
+        let anonymizer = Anonymizer::new("salt");
+        assert_eq!(
+            anonymizer.redact(
+                "Mail jane.doe@example.com or call (555) 123-4567 from 10.0.0.12, \
+                 card 4111 1111 1111 1111, SSN 123-45-6789."
+            ),
+            "Mail [EMAIL] or call [PHONE] from [IP], card [CARD], SSN [SSN]."
+        );
+        assert_eq!(anonymizer.redact("Order 42 shipped"), "Order 42 shipped");
+
+        let custom = Anonymizer::new("salt")
+            .with_rules(Vec::new())
+            .with_rule(RedactionRule::new("order", r"ORD-\d+", "[ORDER]").unwrap());
+        assert_eq!(custom.redact("ORD-991 for a@b.io"), "[ORDER] for a@b.io");
+        assert!(RedactionRule::new("bad", "(", "").is_err());
+    }
+
+    #[test]
+    fn test_anonymize_nodes() {
+        let anonymizer = Anonymizer::new("salt").with_identifier_key("tenant");
+        let mut report = AnonymizationReport::default();
+
+        let mut session = ConversationSession::new();
+        session
+            .metadata
+            .insert("user_id".to_string(), "u-123".to_string());
+        session
+            .metadata
+            .insert("Tenant".to_string(), "acme".to_string());
+        let Node::Session(scrubbed) =
+            anonymizer.scrub_node(&Node::Session(session.clone()), &mut report)
+        else {
+            unreachable!()
+        };
+        assert_eq!(scrubbed.id, session.id);
+        assert_eq!(
+            scrubbed.metadata["user_id"],
+            anonymizer.hash_identifier("u-123")
+        );
+        assert!(scrubbed.metadata["Tenant"].starts_with(HASHED_PREFIX));
+        assert_ne!(
+            scrubbed.metadata["user_id"],
+            Anonymizer::new("other salt").hash_identifier("u-123")
+        );
+
+        let prompt = PromptNode::new(session.id, "I am bob@corp.com".to_string());
+        let Node::Prompt(scrubbed) = anonymizer.scrub_node(&Node::Prompt(prompt), &mut report)
+        else {
+            unreachable!()
+        };
+        assert_eq!(scrubbed.content, "I am [EMAIL]");
+
+        let tool = ToolInvocation::new(
+            scrubbed.id,
+            "lookup".to_string(),
+            serde_json::json!({"email": "bob@corp.com", "query": ["call 555-123-4567"]}),
+        );
+        let Node::ToolInvocation(scrubbed) =
+            anonymizer.scrub_node(&Node::ToolInvocation(tool), &mut report)
+        else {
+            unreachable!()
+        };
+        assert!(scrubbed.parameters["email"]
+            .as_str()
+            .unwrap()
+            .starts_with(HASHED_PREFIX));
+        assert_eq!(scrubbed.parameters["query"][0], "call [PHONE]");
+
+        assert_eq!(report.identifiers_hashed, 3);
+        assert_eq!(report.redactions, 2);
+    }
+}
@@ -0,0 +1,577 @@
+//! The `.lmg` archive format
+//!
+//! Session exports, backups, vault archival and snapshot shipping all write
+//! graph records into the same versioned container, so any of them can be
+//! inspected, verified or imported with the same tools. An archive is laid
+//! out as:
+//!
+//! | Part | Size | Contents |
+//! |------|------|----------|
+//! | Magic | 4 bytes | `LMGA` |
+//! | Format version | 2 bytes | little-endian, [`ARCHIVE_FORMAT_VERSION`] |
+//! | Reserved | 2 bytes | zero |
+//! | Manifest length | 4 bytes | little-endian |
+//! | Manifest | variable | JSON [`ArchiveManifest`] |
+//! | Record blocks | variable | zstd-compressed JSON lines of [`ArchiveRecord`]s |
+//! | Checksum | 32 bytes | SHA-256 of everything before it |
+//!
+//! The manifest lists the length, record count and SHA-256 of every block, so
+//! a single block can be checked and decoded without the rest.
+//!
+//! [`ArchiveWriter`] builds archives; [`ArchiveReader`] reads them without a
+//! database, which makes it suitable for inspecting archives offline.
+//! [`AsyncMemoryGraph::import_archive`](crate::engine::AsyncMemoryGraph::import_archive)
+//! writes an archive's records into a graph.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use llm_memory_graph::archive::ArchiveReader;
+//!
+//! # fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let reader = ArchiveReader::open("backup.lmg")?;
+//! let manifest = reader.manifest();
+//! println!(
+//!     "{:?} archive from {}: {} nodes, {} edges in {} blocks",
+//!     manifest.kind,
+//!     manifest.created_at,
+//!     manifest.node_count,
+//!     manifest.edge_count,
+//!     manifest.blocks.len()
+//! );
+//! reader.verify()?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::export::sha256_hex;
+use crate::{Edge, Error, Node, Result, SessionId};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Version of the container layout written by this release
+pub const ARCHIVE_FORMAT_VERSION: u16 = 1;
+
+/// Bytes every archive starts with
+pub const ARCHIVE_MAGIC: &[u8; 4] = b"LMGA";
+
+/// File extension of archives
+pub const ARCHIVE_EXTENSION: &str = "lmg";
+
+/// Default number of records per block
+pub const DEFAULT_BLOCK_RECORDS: usize = 1_000;
+
+/// Compression of record blocks, as recorded in the manifest
+const ZSTD: &str = "zstd";
+
+/// zstd level used for record blocks
+const BLOCK_COMPRESSION_LEVEL: i32 = 3;
+
+/// Bytes before the manifest
+const HEADER_LEN: usize = 12;
+
+/// Bytes of the trailing checksum
+const CHECKSUM_LEN: usize = 32;
+
+/// What an archive was written for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArchiveKind {
+    /// A session exported for transfer
+    Export,
+    /// A copy of a whole graph
+    Backup,
+    /// A session archived to a vault before deletion
+    VaultArchive,
+    /// A snapshot shipped to read replicas
+    Snapshot,
+}
+
+/// One record block of an archive
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchiveBlock {
+    /// Records in the block
+    pub records: usize,
+    /// Compressed length in bytes
+    pub length: u64,
+    /// SHA-256 of the compressed block, as hex
+    pub sha256: String,
+}
+
+/// What an archive holds
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchiveManifest {
+    /// What the archive was written for
+    pub kind: ArchiveKind,
+    /// When the archive was written
+    pub created_at: DateTime<Utc>,
+    /// Session the archive holds, for session exports and vault archives
+    pub session_id: Option<SessionId>,
+    /// Last captured change the records reflect, if change capture is on
+    pub cursor: Option<u64>,
+    /// Node records
+    pub node_count: usize,
+    /// Edge records
+    pub edge_count: usize,
+    /// Compression of the record blocks, `zstd`
+    pub compression: String,
+    /// Record blocks, in order
+    pub blocks: Vec<ArchiveBlock>,
+    /// Free-form details from the writer
+    #[serde(default)]
+    pub metadata: BTreeMap<String, String>,
+}
+
+/// One record of an archive
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ArchiveRecord {
+    /// A node
+    Node(Box<Node>),
+    /// An edge
+    Edge(Edge),
+}
+
+/// Builds an archive in memory
+pub struct ArchiveWriter {
+    manifest: ArchiveManifest,
+    block_records: usize,
+    block: Vec<u8>,
+    block_len: usize,
+    blocks: Vec<Vec<u8>>,
+}
+
+impl ArchiveWriter {
+    /// Start an archive of `kind`
+    pub fn new(kind: ArchiveKind) -> Self {
+        Self {
+            manifest: ArchiveManifest {
+                kind,
+                created_at: Utc::now(),
+                session_id: None,
+                cursor: None,
+                node_count: 0,
+                edge_count: 0,
+                compression: ZSTD.to_string(),
+                blocks: Vec::new(),
+                metadata: BTreeMap::new(),
+            },
+            block_records: DEFAULT_BLOCK_RECORDS,
+            block: Vec::new(),
+            block_len: 0,
+            blocks: Vec::new(),
+        }
+    }
+
+    /// Record the session the archive holds
+    pub fn with_session(mut self, session_id: SessionId) -> Self {
+        self.manifest.session_id = Some(session_id);
+        self
+    }
+
+    /// Record the change cursor the records reflect
+    pub fn with_cursor(mut self, cursor: Option<u64>) -> Self {
+        self.manifest.cursor = cursor;
+        self
+    }
+
+    /// Record the time the records were read, instead of the time the writer
+    /// was created
+    pub fn with_created_at(mut self, created_at: DateTime<Utc>) -> Self {
+        self.manifest.created_at = created_at;
+        self
+    }
+
+    /// Add a free-form detail to the manifest
+    pub fn with_metadata(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.manifest.metadata.insert(key.into(), value.into());
+        self
+    }
+
+    /// Set the number of records per block
+    pub fn with_block_records(mut self, block_records: usize) -> Self {
+        self.block_records = block_records.max(1);
+        self
+    }
+
+    /// Append a node record
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the node cannot be serialized or compressed.
+    pub fn push_node(&mut self, node: &Node) -> Result<()> {
+        self.push(&ArchiveRecord::Node(Box::new(node.clone())))?;
+        self.manifest.node_count += 1;
+        Ok(())
+    }
+
+    /// Append an edge record
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the edge cannot be serialized or compressed.
+    pub fn push_edge(&mut self, edge: &Edge) -> Result<()> {
+        self.push(&ArchiveRecord::Edge(edge.clone()))?;
+        self.manifest.edge_count += 1;
+        Ok(())
+    }
+
+    /// Append every node, then every edge
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a record cannot be serialized or compressed.
+    pub fn push_all(&mut self, nodes: &[Node], edges: &[Edge]) -> Result<()> {
+        for node in nodes {
+            self.push_node(node)?;
+        }
+        for edge in edges {
+            self.push_edge(edge)?;
+        }
+        Ok(())
+    }
+
+    fn push(&mut self, record: &ArchiveRecord) -> Result<()> {
+        serde_json::to_writer(&mut self.block, record)
+            .map_err(|e| Error::SerializationError(e.to_string()))?;
+        self.block.push(b'\n');
+        self.block_len += 1;
+        if self.block_len >= self.block_records {
+            self.seal_block()?;
+        }
+        Ok(())
+    }
+
+    fn seal_block(&mut self) -> Result<()> {
+        if self.block_len == 0 {
+            return Ok(());
+        }
+        let compressed = zstd::encode_all(self.block.as_slice(), BLOCK_COMPRESSION_LEVEL)?;
+        self.manifest.blocks.push(ArchiveBlock {
+            records: self.block_len,
+            length: compressed.len() as u64,
+            sha256: sha256_hex(&compressed),
+        });
+        self.blocks.push(compressed);
+        self.block.clear();
+        self.block_len = 0;
+        Ok(())
+    }
+
+    /// The finished archive
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the last block cannot be compressed or the
+    /// manifest serialized.
+    pub fn finish(mut self) -> Result<Vec<u8>> {
+        self.seal_block()?;
+        let manifest = serde_json::to_vec(&self.manifest)?;
+        let manifest_len = u32::try_from(manifest.len())
+            .map_err(|_| Error::SerializationError("archive manifest too large".to_string()))?;
+
+        let blocks_len: usize = self.blocks.iter().map(Vec::len).sum();
+        let mut archive =
+            Vec::with_capacity(HEADER_LEN + manifest.len() + blocks_len + CHECKSUM_LEN);
+        archive.extend_from_slice(ARCHIVE_MAGIC);
+        archive.extend_from_slice(&ARCHIVE_FORMAT_VERSION.to_le_bytes());
+        archive.extend_from_slice(&0u16.to_le_bytes());
+        archive.extend_from_slice(&manifest_len.to_le_bytes());
+        archive.extend_from_slice(&manifest);
+        for block in &self.blocks {
+            archive.extend_from_slice(block);
+        }
+        let checksum = Sha256::digest(&archive);
+        archive.extend_from_slice(&checksum);
+        Ok(archive)
+    }
+}
+
+/// Reads an archive without a database
+///
+/// Creating a reader checks the header and the whole-archive checksum and
+/// parses the manifest; blocks are decompressed and checked as they are read.
+pub struct ArchiveReader {
+    data: Vec<u8>,
+    manifest: ArchiveManifest,
+    /// Offset of each block, followed by the end of the last one
+    offsets: Vec<usize>,
+}
+
+fn corrupt(part: &str, reason: impl std::fmt::Display) -> Error {
+    Error::corruption("archive", part.as_bytes(), reason)
+}
+
+impl ArchiveReader {
+    /// Whether `data` starts like an archive
+    pub fn is_archive(data: &[u8]) -> bool {
+        data.starts_with(ARCHIVE_MAGIC)
+    }
+
+    /// Read the archive in `data`
+    ///
+    /// # Errors
+    ///
+    /// Returns a corruption error if `data` is not an archive of a supported
+    /// version, its checksum does not match, or its manifest does not
+    /// describe its blocks.
+    pub fn new(data: Vec<u8>) -> Result<Self> {
+        if data.len() < HEADER_LEN + CHECKSUM_LEN || !Self::is_archive(&data) {
+            return Err(corrupt("header", "not an .lmg archive"));
+        }
+        let version = u16::from_le_bytes([data[4], data[5]]);
+        if version != ARCHIVE_FORMAT_VERSION {
+            return Err(corrupt(
+                "header",
+                format!("unsupported archive format version {version}"),
+            ));
+        }
+
+        let body_end = data.len() - CHECKSUM_LEN;
+        if Sha256::digest(&data[..body_end])[..] != data[body_end..] {
+            return Err(corrupt("checksum", "archive checksum does not match"));
+        }
+
+        let manifest_len = u32::from_le_bytes([data[8], data[9], data[10], data[11]]) as usize;
+        let manifest_end = HEADER_LEN
+            .checked_add(manifest_len)
+            .filter(|end| *end <= body_end)
+            .ok_or_else(|| corrupt("manifest", "manifest runs past the end of the archive"))?;
+        let manifest: ArchiveManifest = serde_json::from_slice(&data[HEADER_LEN..manifest_end])
+            .map_err(|e| corrupt("manifest", e))?;
+        if manifest.compression != ZSTD {
+            return Err(corrupt(
+                "manifest",
+                format!("unsupported compression {}", manifest.compression),
+            ));
+        }
+
+        let mut offsets = Vec::with_capacity(manifest.blocks.len() + 1);
+        let mut offset = manifest_end;
+        offsets.push(offset);
+        for block in &manifest.blocks {
+            offset = usize::try_from(block.length)
+                .ok()
+                .and_then(|length| offset.checked_add(length))
+                .ok_or_else(|| corrupt("manifest", "block lengths overflow"))?;
+            offsets.push(offset);
+        }
+        if offset != body_end {
+            return Err(corrupt(
+                "manifest",
+                format!("blocks end at byte {offset}, archive body ends at {body_end}"),
+            ));
+        }
+        let records: usize = manifest.blocks.iter().map(|block| block.records).sum();
+        if records != manifest.node_count + manifest.edge_count {
+            return Err(corrupt(
+                "manifest",
+                format!(
+                    "blocks hold {records} records, manifest lists {} nodes and {} edges",
+                    manifest.node_count, manifest.edge_count
+                ),
+            ));
+        }
+
+        Ok(Self {
+            data,
+            manifest,
+            offsets,
+        })
+    }
+
+    /// Read the archive stored at `path`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or is not a valid archive.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Self::new(std::fs::read(path)?)
+    }
+
+    /// What the archive holds
+    pub fn manifest(&self) -> &ArchiveManifest {
+        &self.manifest
+    }
+
+    /// Size of the archive in bytes
+    pub fn size(&self) -> u64 {
+        self.data.len() as u64
+    }
+
+    /// The archive's bytes
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Records of the block at `index`
+    ///
+    /// # Errors
+    ///
+    /// Returns a corruption error if there is no such block, or it does not
+    /// match its checksum or record count.
+    pub fn block(&self, index: usize) -> Result<Vec<ArchiveRecord>> {
+        let part = format!("block {index}");
+        let info = self
+            .manifest
+            .blocks
+            .get(index)
+            .ok_or_else(|| corrupt(&part, "no such block"))?;
+        let compressed = &self.data[self.offsets[index]..self.offsets[index + 1]];
+        if sha256_hex(compressed) != info.sha256 {
+            return Err(corrupt(&part, "block checksum does not match"));
+        }
+
+        let lines = zstd::decode_all(compressed).map_err(|e| corrupt(&part, e))?;
+        let records = lines
+            .split(|byte| *byte == b'\n')
+            .filter(|line| !line.is_empty())
+            .map(|line| serde_json::from_slice(line).map_err(|e| corrupt(&part, e)))
+            .collect::<Result<Vec<ArchiveRecord>>>()?;
+        if records.len() != info.records {
+            return Err(corrupt(
+                &part,
+                format!(
+                    "block holds {} records, manifest says {}",
+                    records.len(),
+                    info.records
+                ),
+            ));
+        }
+        Ok(records)
+    }
+
+    /// Every node and edge in the archive
+    ///
+    /// # Errors
+    ///
+    /// Returns a corruption error if a block is damaged or the records do not
+    /// match the manifest's counts.
+    pub fn read_all(&self) -> Result<(Vec<Node>, Vec<Edge>)> {
+        let mut nodes = Vec::with_capacity(self.manifest.node_count);
+        let mut edges = Vec::with_capacity(self.manifest.edge_count);
+        for index in 0..self.manifest.blocks.len() {
+            for record in self.block(index)? {
+                match record {
+                    ArchiveRecord::Node(node) => nodes.push(*node),
+                    ArchiveRecord::Edge(edge) => edges.push(edge),
+                }
+            }
+        }
+        if nodes.len() != self.manifest.node_count || edges.len() != self.manifest.edge_count {
+            return Err(corrupt(
+                "records",
+                format!(
+                    "archive holds {} nodes and {} edges, manifest says {} and {}",
+                    nodes.len(),
+                    edges.len(),
+                    self.manifest.node_count,
+                    self.manifest.edge_count
+                ),
+            ));
+        }
+        Ok((nodes, edges))
+    }
+
+    /// Check every block, without keeping the records
+    ///
+    /// # Errors
+    ///
+    /// Returns a corruption error for the first damaged block.
+    pub fn verify(&self) -> Result<()> {
+        self.read_all().map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ConversationSession, EdgeType, PromptNode};
+
+    fn sample() -> (Vec<Node>, Vec<Edge>) {
+        let session = ConversationSession::new();
+        let prompts: Vec<PromptNode> = (0..5)
+            .map(|i| PromptNode::new(session.id, format!("Prompt {i}")))
+            .collect();
+        let edges = prompts
+            .windows(2)
+            .map(|pair| Edge::new(pair[1].id, pair[0].id, EdgeType::Follows))
+            .collect();
+        let nodes = std::iter::once(Node::Session(session))
+            .chain(prompts.into_iter().map(Node::Prompt))
+            .collect();
+        (nodes, edges)
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let (nodes, edges) = sample();
+        let mut writer = ArchiveWriter::new(ArchiveKind::Backup)
+            .with_cursor(Some(7))
+            .with_metadata("source", "test")
+            .with_block_records(4);
+        writer.push_all(&nodes, &edges).unwrap();
+        let archive = writer.finish().unwrap();
+        assert!(ArchiveReader::is_archive(&archive));
+
+        let reader = ArchiveReader::new(archive).unwrap();
+        let manifest = reader.manifest();
+        assert_eq!(manifest.kind, ArchiveKind::Backup);
+        assert_eq!(manifest.cursor, Some(7));
+        assert_eq!((manifest.node_count, manifest.edge_count), (6, 4));
+        assert_eq!(
+            manifest
+                .blocks
+                .iter()
+                .map(|b| b.records)
+                .collect::<Vec<_>>(),
+            vec![4, 4, 2]
+        );
+        assert_eq!(manifest.metadata["source"], "test");
+        assert_eq!(reader.block(2).unwrap().len(), 2);
+
+        let (read_nodes, read_edges) = reader.read_all().unwrap();
+        assert_eq!(
+            read_nodes.iter().map(Node::id).collect::<Vec<_>>(),
+            nodes.iter().map(Node::id).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            read_edges.iter().map(|e| e.id).collect::<Vec<_>>(),
+            edges.iter().map(|e| e.id).collect::<Vec<_>>()
+        );
+
+        let empty = ArchiveWriter::new(ArchiveKind::Export).finish().unwrap();
+        let reader = ArchiveReader::new(empty).unwrap();
+        assert!(reader.manifest().blocks.is_empty());
+        reader.verify().unwrap();
+    }
+
+    #[test]
+    fn test_damaged_archives() {
+        let (nodes, edges) = sample();
+        let mut writer = ArchiveWriter::new(ArchiveKind::Snapshot);
+        writer.push_all(&nodes, &edges).unwrap();
+        let archive = writer.finish().unwrap();
+
+        let is_corruption =
+            |result: Result<ArchiveReader>| matches!(result, Err(Error::Corruption { .. }));
+        assert!(is_corruption(ArchiveReader::new(
+            b"not an archive".to_vec()
+        )));
+        assert!(is_corruption(ArchiveReader::new(
+            archive[..archive.len() - 1].to_vec()
+        )));
+
+        let mut flipped = archive.clone();
+        let middle = flipped.len() / 2;
+        flipped[middle] ^= 0xff;
+        assert!(is_corruption(ArchiveReader::new(flipped)));
+
+        let mut newer = archive;
+        newer[4] = 99;
+        assert!(is_corruption(ArchiveReader::new(newer)));
+    }
+}
@@ -0,0 +1,336 @@
+//! Append-only audit trail of graph mutations
+//!
+//! When [`Config::audit_log`](crate::Config) is enabled, every mutation made
+//! through [`AsyncMemoryGraph`](crate::engine::AsyncMemoryGraph) appends an
+//! [`AuditEntry`] to a dedicated storage tree. Entries are never updated or
+//! removed, and can be queried with an [`AuditFilter`] or exported as JSON Lines
+//! for compliance reviews.
+//!
+//! The actor of an entry is, in order of preference:
+//! 1. the actor set with [`with_actor`] for the current task, typically the
+//!    authenticated caller's [`Principal::subject`](crate::auth::Principal),
+//! 2. an `"actor"` entry in the operation's metadata (session metadata or a
+//!    prompt's custom metadata),
+//! 3. [`SYSTEM_ACTOR`].
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use llm_memory_graph::audit::{with_actor, AuditFilter, AuditOperation};
+//! use llm_memory_graph::engine::AsyncMemoryGraph;
+//! use llm_memory_graph::Config;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let graph = AsyncMemoryGraph::open(Config::new("./data").with_audit_log(true)).await?;
+//!
+//! let session = with_actor("alice", graph.create_session()).await?;
+//!
+//! let entries = graph
+//!     .audit_log(&AuditFilter::new().actor("alice").operation(AuditOperation::CreateSession))
+//!     .await?;
+//! assert_eq!(entries[0].session_id, Some(session.id));
+//!
+//! graph.export_audit_log(&AuditFilter::new(), std::io::stdout()).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{NodeId, Result, SessionId};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::io::Write;
+
+/// Actor recorded when no caller identity is known
+pub const SYSTEM_ACTOR: &str = "system";
+
+/// Metadata key that names the actor of an operation
+pub const ACTOR_METADATA_KEY: &str = "actor";
+
+tokio::task_local! {
+    static ACTOR: String;
+}
+
+/// Run `future` with `actor` recorded as the actor of its mutations
+pub async fn with_actor<F: Future>(actor: impl Into<String>, future: F) -> F::Output {
+    ACTOR.scope(actor.into(), future).await
+}
+
+/// The actor set by [`with_actor`] for the current task, if any
+pub fn current_actor() -> Option<String> {
+    ACTOR.try_with(Clone::clone).ok()
+}
+
+/// Kind of audited operation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditOperation {
+    /// A session was created
+    CreateSession,
+    /// A session's title or status was changed
+    UpdateSession,
+    /// A session was merged into another and removed
+    MergeSession,
+    /// A checkpoint of a session was taken
+    CheckpointSession,
+    /// A session was rolled back to a checkpoint
+    RollbackSession,
+    /// A prompt was added
+    AddPrompt,
+    /// A response was added
+    AddResponse,
+    /// A tool invocation was added
+    AddToolInvocation,
+    /// A tool invocation was updated
+    UpdateToolInvocation,
+    /// An agent was added
+    AddAgent,
+    /// An agent was updated
+    UpdateAgent,
+    /// A template was created
+    CreateTemplate,
+    /// A template was updated
+    UpdateTemplate,
+    /// An edge was created
+    AddEdge,
+    /// A node was written directly (batch import)
+    StoreNode,
+    /// The content of a prompt or response was edited, keeping a version
+    EditContent,
+    /// A node was deleted (moved to the trash)
+    DeleteNode,
+    /// A node was restored from the trash
+    RestoreNode,
+    /// A trashed node was permanently removed
+    PurgeNode,
+    /// An orphaned node was removed by garbage collection
+    CollectNode,
+    /// A dangling edge was removed by garbage collection
+    CollectEdge,
+    /// A REFERENCES edge whose relevance decayed below the floor was removed
+    PruneEdge,
+    /// A user-defined node or edge property was set or removed
+    SetProperty,
+    /// A node of a custom type was added
+    AddCustomNode,
+    /// An alias was pointed at a session or node, or removed
+    SetAlias,
+}
+
+impl fmt::Display for AuditOperation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = serde_json::to_value(self)
+            .ok()
+            .and_then(|v| v.as_str().map(str::to_string))
+            .unwrap_or_default();
+        f.write_str(&name)
+    }
+}
+
+/// A single audit record
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// Position in the log, assigned by storage; increases with every append
+    pub sequence: u64,
+    /// When the operation happened
+    pub timestamp: DateTime<Utc>,
+    /// Who performed the operation
+    pub actor: String,
+    /// What was done
+    pub operation: AuditOperation,
+    /// Session the operation touched, if known
+    pub session_id: Option<SessionId>,
+    /// Node the operation touched, if any
+    pub node_id: Option<NodeId>,
+    /// Operation-specific details (edge type, target node, ...)
+    #[serde(default)]
+    pub details: HashMap<String, String>,
+}
+
+impl AuditEntry {
+    /// Create an entry for `operation` performed now by the current actor
+    ///
+    /// `metadata` is consulted for an [`ACTOR_METADATA_KEY`] entry when no
+    /// actor is set for the current task.
+    pub fn new(operation: AuditOperation, metadata: Option<&HashMap<String, String>>) -> Self {
+        let actor = current_actor()
+            .or_else(|| metadata.and_then(|m| m.get(ACTOR_METADATA_KEY).cloned()))
+            .unwrap_or_else(|| SYSTEM_ACTOR.to_string());
+        Self {
+            sequence: 0,
+            timestamp: Utc::now(),
+            actor,
+            operation,
+            session_id: None,
+            node_id: None,
+            details: HashMap::new(),
+        }
+    }
+
+    /// Set the session the operation touched
+    pub fn with_session(mut self, session_id: SessionId) -> Self {
+        self.session_id = Some(session_id);
+        self
+    }
+
+    /// Set the node the operation touched
+    pub fn with_node(mut self, node_id: NodeId) -> Self {
+        self.node_id = Some(node_id);
+        self
+    }
+
+    /// Add a detail
+    pub fn with_detail(mut self, key: impl Into<String>, value: impl ToString) -> Self {
+        self.details.insert(key.into(), value.to_string());
+        self
+    }
+}
+
+/// Selects audit entries; unset fields match everything
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AuditFilter {
+    /// Only entries by this actor
+    pub actor: Option<String>,
+    /// Only entries of this operation
+    pub operation: Option<AuditOperation>,
+    /// Only entries touching this session
+    pub session_id: Option<SessionId>,
+    /// Only entries touching this node
+    pub node_id: Option<NodeId>,
+    /// Only entries at or after this time
+    pub since: Option<DateTime<Utc>>,
+    /// Only entries before this time
+    pub until: Option<DateTime<Utc>>,
+    /// Return at most this many entries (oldest first)
+    pub limit: Option<usize>,
+}
+
+impl AuditFilter {
+    /// A filter matching every entry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only entries by `actor`
+    pub fn actor(mut self, actor: impl Into<String>) -> Self {
+        self.actor = Some(actor.into());
+        self
+    }
+
+    /// Only entries of `operation`
+    pub fn operation(mut self, operation: AuditOperation) -> Self {
+        self.operation = Some(operation);
+        self
+    }
+
+    /// Only entries touching `session_id`
+    pub fn session(mut self, session_id: SessionId) -> Self {
+        self.session_id = Some(session_id);
+        self
+    }
+
+    /// Only entries touching `node_id`
+    pub fn node(mut self, node_id: NodeId) -> Self {
+        self.node_id = Some(node_id);
+        self
+    }
+
+    /// Only entries in `[since, until)`
+    pub fn between(mut self, since: DateTime<Utc>, until: DateTime<Utc>) -> Self {
+        self.since = Some(since);
+        self.until = Some(until);
+        self
+    }
+
+    /// Return at most `limit` entries
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Whether `entry` passes the filter (ignores `limit`)
+    pub fn matches(&self, entry: &AuditEntry) -> bool {
+        self.actor.as_ref().is_none_or(|a| &entry.actor == a)
+            && self.operation.is_none_or(|o| entry.operation == o)
+            && self.session_id.is_none_or(|s| entry.session_id == Some(s))
+            && self.node_id.is_none_or(|n| entry.node_id == Some(n))
+            && self.since.is_none_or(|t| entry.timestamp >= t)
+            && self.until.is_none_or(|t| entry.timestamp < t)
+    }
+}
+
+/// Write entries as JSON Lines, one entry per line
+///
+/// Returns the number of entries written.
+pub fn write_jsonl<W: Write>(entries: &[AuditEntry], mut writer: W) -> Result<usize> {
+    for entry in entries {
+        serde_json::to_writer(&mut writer, entry)?;
+        writer.write_all(b"\n")?;
+    }
+    writer.flush()?;
+    Ok(entries.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_actor_resolution() {
+        let metadata: HashMap<_, _> = [(ACTOR_METADATA_KEY.to_string(), "bob".to_string())].into();
+
+        assert_eq!(
+            AuditEntry::new(AuditOperation::AddPrompt, None).actor,
+            SYSTEM_ACTOR
+        );
+        assert_eq!(
+            AuditEntry::new(AuditOperation::AddPrompt, Some(&metadata)).actor,
+            "bob"
+        );
+
+        let scoped = with_actor("alice", async {
+            AuditEntry::new(AuditOperation::AddPrompt, Some(&metadata))
+        })
+        .await;
+        assert_eq!(scoped.actor, "alice");
+        assert!(current_actor().is_none());
+    }
+
+    #[test]
+    fn test_filter_matching() {
+        let session = SessionId::new();
+        let entry = AuditEntry::new(AuditOperation::CreateSession, None).with_session(session);
+
+        assert!(AuditFilter::new().matches(&entry));
+        assert!(AuditFilter::new().session(session).matches(&entry));
+        assert!(AuditFilter::new().actor(SYSTEM_ACTOR).matches(&entry));
+        assert!(!AuditFilter::new().actor("alice").matches(&entry));
+        assert!(!AuditFilter::new()
+            .operation(AuditOperation::DeleteNode)
+            .matches(&entry));
+        assert!(!AuditFilter::new().node(NodeId::new()).matches(&entry));
+        assert!(!AuditFilter::new()
+            .between(entry.timestamp + chrono::Duration::seconds(1), Utc::now())
+            .matches(&entry));
+    }
+
+    #[test]
+    fn test_write_jsonl() {
+        let entries = vec![
+            AuditEntry::new(AuditOperation::CreateSession, None),
+            AuditEntry::new(AuditOperation::AddEdge, None).with_detail("edge_type", "Follows"),
+        ];
+        let mut out = Vec::new();
+        assert_eq!(write_jsonl(&entries, &mut out).unwrap(), 2);
+
+        let lines: Vec<AuditEntry> = String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(lines, entries);
+        assert_eq!(AuditOperation::AddEdge.to_string(), "add_edge");
+    }
+}
@@ -0,0 +1,548 @@
+//! Authentication and multi-tenant isolation for the server
+//!
+//! Callers authenticate with either an API key (`x-api-key` header) or a JWT
+//! bearer token (`authorization: Bearer <token>`). Each credential resolves to a
+//! [`Principal`] carrying the caller's [`TenantId`], and each tenant gets its own
+//! database via [`TenantGraphs`], so teams sharing one server never see each
+//! other's data. What a caller may do within its tenant is decided by the
+//! [`RbacPolicy`] from its [`Role`]s.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use llm_memory_graph::auth::{Authenticator, JwtConfig, TenantGraphs};
+//! use llm_memory_graph::Config;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let authenticator = Authenticator::new()
+//!     .with_api_key("team-a-secret", "team-a")?
+//!     .with_jwt(JwtConfig::hs256("jwt-signing-secret").with_tenant_claim("org"));
+//!
+//! let tenants = TenantGraphs::new(Config::new("./data"));
+//!
+//! // In a handler, after the interceptor has run:
+//! // let principal = llm_memory_graph::auth::principal(&request)?;
+//! // let graph = tenants.graph(&principal.tenant).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+mod rbac;
+mod tenant;
+
+pub use rbac::{Operation, RbacPlugin, RbacPolicy, Role, ROLES_METADATA_KEY};
+pub use tenant::TenantGraphs;
+
+use jsonwebtoken::{Algorithm, DecodingKey, Validation};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use tonic::metadata::MetadataMap;
+use tonic::{Request, Status};
+
+/// Header carrying an API key
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// Header carrying a bearer token
+pub const AUTHORIZATION_HEADER: &str = "authorization";
+
+/// Maximum length of a tenant ID
+const MAX_TENANT_ID_LEN: usize = 64;
+
+/// Authentication error types
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum AuthError {
+    /// No API key or bearer token was presented
+    #[error("Missing credentials")]
+    MissingCredentials,
+
+    /// The API key is not known
+    #[error("Invalid API key")]
+    InvalidApiKey,
+
+    /// The bearer token failed validation
+    #[error("Invalid token: {0}")]
+    InvalidToken(String),
+
+    /// Bearer tokens are presented but JWT validation is not configured
+    #[error("Bearer tokens are not accepted by this server")]
+    JwtDisabled,
+
+    /// The tenant ID is malformed
+    #[error("Invalid tenant ID: {0}")]
+    InvalidTenant(String),
+
+    /// The role name is not known
+    #[error("Invalid role: {0}")]
+    InvalidRole(String),
+
+    /// The caller is authenticated but its roles do not allow the operation
+    #[error("{subject} is not allowed to {operation}")]
+    PermissionDenied {
+        /// Caller identity
+        subject: String,
+        /// Denied operation
+        operation: Operation,
+    },
+}
+
+impl From<AuthError> for Status {
+    fn from(err: AuthError) -> Self {
+        match err {
+            AuthError::PermissionDenied { .. } => Status::permission_denied(err.to_string()),
+            _ => Status::unauthenticated(err.to_string()),
+        }
+    }
+}
+
+/// Identifier of a tenant sharing the server
+///
+/// Tenant IDs are 1-64 ASCII letters, digits, `-` or `_`. The restriction keeps
+/// them safe to use as directory names and storage key prefixes.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TenantId(String);
+
+impl TenantId {
+    /// Validate and create a tenant ID
+    pub fn new(id: impl Into<String>) -> Result<Self, AuthError> {
+        let id = id.into();
+        let valid = !id.is_empty()
+            && id.len() <= MAX_TENANT_ID_LEN
+            && id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if valid {
+            Ok(Self(id))
+        } else {
+            Err(AuthError::InvalidTenant(id))
+        }
+    }
+
+    /// The tenant ID as a string
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for TenantId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// An authenticated caller
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Principal {
+    /// Tenant the caller belongs to
+    pub tenant: TenantId,
+    /// Caller identity (JWT `sub`, or the API key's label)
+    pub subject: String,
+    /// Roles granted to the caller
+    pub roles: Vec<Role>,
+}
+
+/// JWT validation settings
+#[derive(Clone)]
+pub struct JwtConfig {
+    secret: Vec<u8>,
+    /// Claim holding the tenant ID
+    pub tenant_claim: String,
+    /// Claim holding the caller's roles (a string or an array of strings)
+    pub roles_claim: String,
+    /// Required `iss` claim, if any
+    pub issuer: Option<String>,
+    /// Required `aud` claim, if any
+    pub audience: Option<String>,
+}
+
+impl fmt::Debug for JwtConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JwtConfig")
+            .field("secret", &"<redacted>")
+            .field("tenant_claim", &self.tenant_claim)
+            .field("roles_claim", &self.roles_claim)
+            .field("issuer", &self.issuer)
+            .field("audience", &self.audience)
+            .finish()
+    }
+}
+
+impl JwtConfig {
+    /// Validate HS256 tokens signed with `secret`
+    ///
+    /// Tokens must carry an unexpired `exp` claim and a `tenant` claim. Roles
+    /// are read from the `roles` claim; tokens without one get [`Role::Reader`].
+    pub fn hs256(secret: impl AsRef<[u8]>) -> Self {
+        Self {
+            secret: secret.as_ref().to_vec(),
+            tenant_claim: "tenant".to_string(),
+            roles_claim: "roles".to_string(),
+            issuer: None,
+            audience: None,
+        }
+    }
+
+    /// Read the tenant ID from a different claim
+    pub fn with_tenant_claim(mut self, claim: impl Into<String>) -> Self {
+        self.tenant_claim = claim.into();
+        self
+    }
+
+    /// Read the roles from a different claim
+    pub fn with_roles_claim(mut self, claim: impl Into<String>) -> Self {
+        self.roles_claim = claim.into();
+        self
+    }
+
+    /// Require the given issuer
+    pub fn with_issuer(mut self, issuer: impl Into<String>) -> Self {
+        self.issuer = Some(issuer.into());
+        self
+    }
+
+    /// Require the given audience
+    pub fn with_audience(mut self, audience: impl Into<String>) -> Self {
+        self.audience = Some(audience.into());
+        self
+    }
+
+    fn validation(&self) -> Validation {
+        let mut validation = Validation::new(Algorithm::HS256);
+        if let Some(issuer) = &self.issuer {
+            validation.set_issuer(&[issuer]);
+        }
+        match &self.audience {
+            Some(audience) => validation.set_audience(&[audience]),
+            None => validation.validate_aud = false,
+        }
+        validation
+    }
+}
+
+#[derive(Deserialize)]
+struct Claims {
+    #[serde(default)]
+    sub: Option<String>,
+    #[serde(flatten)]
+    extra: HashMap<String, serde_json::Value>,
+}
+
+/// Validates credentials and resolves them to a [`Principal`]
+#[derive(Clone, Default)]
+pub struct Authenticator {
+    /// Principals keyed by the SHA-256 digest of their API key
+    api_keys: HashMap<[u8; 32], Principal>,
+    jwt: Option<JwtConfig>,
+}
+
+impl fmt::Debug for Authenticator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Authenticator")
+            .field("api_keys", &self.api_keys.len())
+            .field("jwt", &self.jwt)
+            .finish()
+    }
+}
+
+impl Authenticator {
+    /// Create an authenticator that accepts no credentials
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Accept `key` as a read-only credential for `tenant`
+    ///
+    /// Use [`Authenticator::with_api_key_roles`] to grant more than
+    /// [`Role::Reader`]. Only a digest of the key is kept in memory.
+    pub fn with_api_key(self, key: impl AsRef<str>, tenant: &str) -> Result<Self, AuthError> {
+        self.with_api_key_roles(key, tenant, vec![Role::Reader])
+    }
+
+    /// Accept `key` as a credential for `tenant` with the given roles
+    pub fn with_api_key_roles(
+        mut self,
+        key: impl AsRef<str>,
+        tenant: &str,
+        roles: Vec<Role>,
+    ) -> Result<Self, AuthError> {
+        let principal = Principal {
+            tenant: TenantId::new(tenant)?,
+            subject: format!("api-key:{}", tenant),
+            roles,
+        };
+        self.api_keys.insert(digest(key.as_ref()), principal);
+        Ok(self)
+    }
+
+    /// Accept JWT bearer tokens
+    pub fn with_jwt(mut self, config: JwtConfig) -> Self {
+        self.jwt = Some(config);
+        self
+    }
+
+    /// Whether any credential type is configured
+    pub fn is_enabled(&self) -> bool {
+        !self.api_keys.is_empty() || self.jwt.is_some()
+    }
+
+    /// Authenticate raw header values
+    ///
+    /// An API key takes precedence over a bearer token when both are present.
+    pub fn authenticate_credentials(
+        &self,
+        authorization: Option<&str>,
+        api_key: Option<&str>,
+    ) -> Result<Principal, AuthError> {
+        if let Some(key) = api_key {
+            return self
+                .api_keys
+                .get(&digest(key))
+                .cloned()
+                .ok_or(AuthError::InvalidApiKey);
+        }
+
+        let token = authorization
+            .and_then(|value| {
+                value
+                    .strip_prefix("Bearer ")
+                    .or_else(|| value.strip_prefix("bearer "))
+            })
+            .map(str::trim)
+            .ok_or(AuthError::MissingCredentials)?;
+        self.authenticate_jwt(token)
+    }
+
+    /// Authenticate the credentials in gRPC request metadata
+    pub fn authenticate(&self, metadata: &MetadataMap) -> Result<Principal, AuthError> {
+        let header = |name: &str| metadata.get(name).and_then(|v| v.to_str().ok());
+        self.authenticate_credentials(header(AUTHORIZATION_HEADER), header(API_KEY_HEADER))
+    }
+
+    /// Authenticate the credentials in HTTP request headers
+    pub fn authenticate_headers(&self, headers: &hyper::HeaderMap) -> Result<Principal, AuthError> {
+        let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+        self.authenticate_credentials(header(AUTHORIZATION_HEADER), header(API_KEY_HEADER))
+    }
+
+    fn authenticate_jwt(&self, token: &str) -> Result<Principal, AuthError> {
+        let config = self.jwt.as_ref().ok_or(AuthError::JwtDisabled)?;
+        let data = jsonwebtoken::decode::<Claims>(
+            token,
+            &DecodingKey::from_secret(&config.secret),
+            &config.validation(),
+        )
+        .map_err(|e| AuthError::InvalidToken(e.to_string()))?;
+
+        let tenant = data
+            .claims
+            .extra
+            .get(&config.tenant_claim)
+            .and_then(serde_json::Value::as_str)
+            .ok_or_else(|| {
+                AuthError::InvalidToken(format!("missing '{}' claim", config.tenant_claim))
+            })?;
+        let tenant = TenantId::new(tenant)?;
+        let subject = data.claims.sub.unwrap_or_else(|| format!("jwt:{}", tenant));
+
+        // Unknown role names are ignored rather than rejected, so issuers can
+        // add roles for other services to the same claim
+        let roles = match data.claims.extra.get(&config.roles_claim) {
+            None => vec![Role::Reader],
+            Some(serde_json::Value::String(role)) => role.parse().ok().into_iter().collect(),
+            Some(serde_json::Value::Array(roles)) => roles
+                .iter()
+                .filter_map(|r| r.as_str()?.parse().ok())
+                .collect(),
+            Some(_) => {
+                return Err(AuthError::InvalidToken(format!(
+                    "'{}' claim must be a string or an array",
+                    config.roles_claim
+                )))
+            }
+        };
+
+        Ok(Principal {
+            tenant,
+            subject,
+            roles,
+        })
+    }
+}
+
+fn digest(key: &str) -> [u8; 32] {
+    Sha256::digest(key.as_bytes()).into()
+}
+
+/// gRPC interceptor that rejects unauthenticated requests
+///
+/// On success the caller's [`Principal`] is stored in the request extensions,
+/// where handlers read it with [`principal`].
+#[derive(Debug, Clone)]
+pub struct AuthInterceptor {
+    authenticator: Arc<Authenticator>,
+}
+
+impl AuthInterceptor {
+    /// Create an interceptor backed by `authenticator`
+    pub fn new(authenticator: Authenticator) -> Self {
+        Self {
+            authenticator: Arc::new(authenticator),
+        }
+    }
+}
+
+impl tonic::service::Interceptor for AuthInterceptor {
+    fn call(&mut self, mut request: Request<()>) -> Result<Request<()>, Status> {
+        let principal = self.authenticator.authenticate(request.metadata())?;
+        request.extensions_mut().insert(principal);
+        Ok(request)
+    }
+}
+
+/// The authenticated caller of a request that passed [`AuthInterceptor`]
+///
+/// The error converts into an `UNAUTHENTICATED` status, so handlers can use `?`.
+pub fn principal<T>(request: &Request<T>) -> Result<&Principal, AuthError> {
+    request
+        .extensions()
+        .get::<Principal>()
+        .ok_or(AuthError::MissingCredentials)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonwebtoken::{EncodingKey, Header};
+    use tonic::service::Interceptor;
+
+    const SECRET: &str = "test-signing-secret";
+
+    fn token(claims: &serde_json::Value, secret: &str) -> String {
+        jsonwebtoken::encode(
+            &Header::new(Algorithm::HS256),
+            claims,
+            &EncodingKey::from_secret(secret.as_bytes()),
+        )
+        .unwrap()
+    }
+
+    fn exp() -> i64 {
+        chrono::Utc::now().timestamp() + 3600
+    }
+
+    #[test]
+    fn test_tenant_id_validation() {
+        assert!(TenantId::new("team-a_1").is_ok());
+        assert!(TenantId::new("").is_err());
+        assert!(TenantId::new("..").is_err());
+        assert!(TenantId::new("a/b").is_err());
+        assert!(TenantId::new("x".repeat(65)).is_err());
+    }
+
+    #[test]
+    fn test_api_key_authentication() {
+        let auth = Authenticator::new()
+            .with_api_key("key-a", "team-a")
+            .unwrap()
+            .with_api_key("key-b", "team-b")
+            .unwrap();
+
+        let principal = auth.authenticate_credentials(None, Some("key-b")).unwrap();
+        assert_eq!(principal.tenant.as_str(), "team-b");
+        assert_eq!(principal.roles, vec![Role::Reader]);
+
+        let auth = auth
+            .with_api_key_roles("key-r", "team-b", vec![Role::Reader])
+            .unwrap();
+        let principal = auth.authenticate_credentials(None, Some("key-r")).unwrap();
+        assert_eq!(principal.roles, vec![Role::Reader]);
+        assert_eq!(
+            auth.authenticate_credentials(None, Some("nope")),
+            Err(AuthError::InvalidApiKey)
+        );
+        assert_eq!(
+            auth.authenticate_credentials(None, None),
+            Err(AuthError::MissingCredentials)
+        );
+        assert!(Authenticator::new()
+            .with_api_key("k", "bad tenant")
+            .is_err());
+    }
+
+    #[test]
+    fn test_jwt_authentication() {
+        let auth = Authenticator::new().with_jwt(
+            JwtConfig::hs256(SECRET)
+                .with_tenant_claim("org")
+                .with_issuer("issuer"),
+        );
+
+        let valid = token(
+            &serde_json::json!({"sub": "alice", "org": "team-a", "iss": "issuer", "exp": exp()}),
+            SECRET,
+        );
+        let principal = auth
+            .authenticate_credentials(Some(&format!("Bearer {}", valid)), None)
+            .unwrap();
+        assert_eq!(principal.tenant.as_str(), "team-a");
+        assert_eq!(principal.subject, "alice");
+        assert_eq!(principal.roles, vec![Role::Reader]);
+
+        let with_roles = token(
+            &serde_json::json!({"org": "team-a", "iss": "issuer", "exp": exp(), "roles": ["writer", "billing"]}),
+            SECRET,
+        );
+        let principal = auth
+            .authenticate_credentials(Some(&format!("Bearer {}", with_roles)), None)
+            .unwrap();
+        assert_eq!(principal.roles, vec![Role::Writer]);
+
+        let wrong_secret = token(
+            &serde_json::json!({"org": "team-a", "iss": "issuer", "exp": exp()}),
+            "other",
+        );
+        let expired = token(
+            &serde_json::json!({"org": "team-a", "iss": "issuer", "exp": 1}),
+            SECRET,
+        );
+        let no_tenant = token(&serde_json::json!({"iss": "issuer", "exp": exp()}), SECRET);
+        let wrong_issuer = token(
+            &serde_json::json!({"org": "team-a", "iss": "mallory", "exp": exp()}),
+            SECRET,
+        );
+        for bad in [wrong_secret, expired, no_tenant, wrong_issuer] {
+            assert!(matches!(
+                auth.authenticate_credentials(Some(&format!("Bearer {}", bad)), None),
+                Err(AuthError::InvalidToken(_))
+            ));
+        }
+
+        // Bearer tokens are rejected when JWT is not configured
+        assert_eq!(
+            Authenticator::new().authenticate_credentials(Some("Bearer abc"), None),
+            Err(AuthError::JwtDisabled)
+        );
+    }
+
+    #[test]
+    fn test_interceptor_attaches_principal() {
+        let mut interceptor = AuthInterceptor::new(
+            Authenticator::new()
+                .with_api_key("key-a", "team-a")
+                .unwrap(),
+        );
+
+        let mut request = Request::new(());
+        request
+            .metadata_mut()
+            .insert(API_KEY_HEADER, "key-a".parse().unwrap());
+        let request = interceptor.call(request).unwrap();
+        assert_eq!(principal(&request).unwrap().tenant.as_str(), "team-a");
+
+        let status = interceptor.call(Request::new(())).unwrap_err();
+        assert_eq!(status.code(), tonic::Code::Unauthenticated);
+        assert!(principal(&Request::new(())).is_err());
+    }
+}
@@ -0,0 +1,342 @@
+//! Role-based access control for graph operations
+//!
+//! An [`RbacPolicy`] maps [`Role`]s to the [`Operation`]s they may perform. The
+//! gRPC service checks it on every call via [`RbacPolicy::authorize_request`]
+//! and the server's `/import` endpoint via [`RbacPolicy::authorize`]; embedded
+//! users get the same checks by registering an [`RbacPlugin`].
+
+use super::{AuthError, Principal};
+use crate::plugin::{Plugin, PluginBuilder, PluginContext, PluginError, PluginMetadata};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::str::FromStr;
+use tonic::Request;
+
+/// Plugin context metadata key holding the caller's comma-separated roles
+pub const ROLES_METADATA_KEY: &str = "roles";
+
+/// A role granted to a caller
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    /// Read sessions and nodes
+    Reader,
+    /// Read and write sessions, nodes and edges
+    Writer,
+    /// Everything, including deleting sessions and managing templates
+    Admin,
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Role::Reader => "reader",
+            Role::Writer => "writer",
+            Role::Admin => "admin",
+        })
+    }
+}
+
+impl FromStr for Role {
+    type Err = AuthError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "reader" => Ok(Role::Reader),
+            "writer" => Ok(Role::Writer),
+            "admin" => Ok(Role::Admin),
+            other => Err(AuthError::InvalidRole(other.to_string())),
+        }
+    }
+}
+
+/// A class of graph operations subject to access control
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Operation {
+    /// Read sessions, nodes and edges, run queries, subscribe to events
+    ReadNodes,
+    /// Create or update sessions, nodes and edges
+    WriteNodes,
+    /// Delete sessions, nodes and edges
+    DeleteSessions,
+    /// Create, update and delete prompt templates
+    ManageTemplates,
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Operation::ReadNodes => "read_nodes",
+            Operation::WriteNodes => "write_nodes",
+            Operation::DeleteSessions => "delete_sessions",
+            Operation::ManageTemplates => "manage_templates",
+        })
+    }
+}
+
+/// Which roles may perform which operations
+///
+/// The default policy grants readers [`Operation::ReadNodes`], writers reads
+/// and [`Operation::WriteNodes`], and admins every operation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RbacPolicy {
+    grants: HashMap<Role, HashSet<Operation>>,
+}
+
+impl Default for RbacPolicy {
+    fn default() -> Self {
+        Self::empty()
+            .grant(Role::Reader, Operation::ReadNodes)
+            .grant(Role::Writer, Operation::ReadNodes)
+            .grant(Role::Writer, Operation::WriteNodes)
+            .grant(Role::Admin, Operation::ReadNodes)
+            .grant(Role::Admin, Operation::WriteNodes)
+            .grant(Role::Admin, Operation::DeleteSessions)
+            .grant(Role::Admin, Operation::ManageTemplates)
+    }
+}
+
+impl RbacPolicy {
+    /// A policy that grants nothing
+    pub fn empty() -> Self {
+        Self {
+            grants: HashMap::new(),
+        }
+    }
+
+    /// Allow `role` to perform `operation`
+    pub fn grant(mut self, role: Role, operation: Operation) -> Self {
+        self.grants.entry(role).or_default().insert(operation);
+        self
+    }
+
+    /// Stop allowing `role` to perform `operation`
+    pub fn revoke(mut self, role: Role, operation: Operation) -> Self {
+        if let Some(operations) = self.grants.get_mut(&role) {
+            operations.remove(&operation);
+        }
+        self
+    }
+
+    /// Whether any of `roles` may perform `operation`
+    pub fn allows(&self, roles: &[Role], operation: Operation) -> bool {
+        roles.iter().any(|role| {
+            self.grants
+                .get(role)
+                .is_some_and(|operations| operations.contains(&operation))
+        })
+    }
+
+    /// Check that `principal` may perform `operation`
+    pub fn authorize(&self, principal: &Principal, operation: Operation) -> Result<(), AuthError> {
+        if self.allows(&principal.roles, operation) {
+            Ok(())
+        } else {
+            Err(AuthError::PermissionDenied {
+                subject: principal.subject.clone(),
+                operation,
+            })
+        }
+    }
+
+    /// Check that the authenticated caller of `request` may perform `operation`
+    ///
+    /// Returns the caller so handlers can go on to pick the tenant's graph.
+    pub fn authorize_request<'a, T>(
+        &self,
+        request: &'a Request<T>,
+        operation: Operation,
+    ) -> Result<&'a Principal, AuthError> {
+        let principal = super::principal(request)?;
+        self.authorize(principal, operation)?;
+        Ok(principal)
+    }
+}
+
+/// Enforces an [`RbacPolicy`] for embedded use through the plugin hooks
+///
+/// The caller's roles are read from the [`ROLES_METADATA_KEY`] entry of the
+/// plugin context; contexts without roles are denied. Creating a node whose
+/// `node_type` is `"template"` requires [`Operation::ManageTemplates`].
+pub struct RbacPlugin {
+    metadata: PluginMetadata,
+    policy: RbacPolicy,
+}
+
+impl RbacPlugin {
+    /// Create the plugin
+    pub fn new(policy: RbacPolicy) -> Self {
+        let metadata = PluginBuilder::new("rbac", "1.0.0")
+            .author(env!("CARGO_PKG_AUTHORS"))
+            .description("Role-based access control for graph operations")
+            .capability("access_control")
+            .build();
+        Self { metadata, policy }
+    }
+
+    /// The enforced policy
+    pub fn policy(&self) -> &RbacPolicy {
+        &self.policy
+    }
+
+    fn check(&self, context: &PluginContext, operation: Operation) -> Result<(), PluginError> {
+        let roles: Vec<Role> = context
+            .get_metadata(ROLES_METADATA_KEY)
+            .map(|roles| roles.split(',').filter_map(|r| r.parse().ok()).collect())
+            .unwrap_or_default();
+
+        if self.policy.allows(&roles, operation) {
+            Ok(())
+        } else {
+            Err(PluginError::HookFailed(format!(
+                "access denied: {} requires {}",
+                context.operation(),
+                operation
+            )))
+        }
+    }
+}
+
+impl Default for RbacPlugin {
+    fn default() -> Self {
+        Self::new(RbacPolicy::default())
+    }
+}
+
+#[async_trait]
+impl Plugin for RbacPlugin {
+    fn metadata(&self) -> &PluginMetadata {
+        &self.metadata
+    }
+
+    async fn before_create_node(&self, context: &PluginContext) -> Result<(), PluginError> {
+        let is_template = context
+            .data()
+            .get("node_type")
+            .and_then(|t| t.as_str())
+            .is_some_and(|t| t.eq_ignore_ascii_case("template"));
+        let operation = if is_template {
+            Operation::ManageTemplates
+        } else {
+            Operation::WriteNodes
+        };
+        self.check(context, operation)
+    }
+
+    async fn before_create_session(&self, context: &PluginContext) -> Result<(), PluginError> {
+        self.check(context, Operation::WriteNodes)
+    }
+
+    async fn before_query(&self, context: &PluginContext) -> Result<(), PluginError> {
+        self.check(context, Operation::ReadNodes)
+    }
+
+    async fn before_create_edge(&self, context: &PluginContext) -> Result<(), PluginError> {
+        self.check(context, Operation::WriteNodes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::TenantId;
+
+    fn principal(roles: Vec<Role>) -> Principal {
+        Principal {
+            tenant: TenantId::new("team-a").unwrap(),
+            subject: "alice".to_string(),
+            roles,
+        }
+    }
+
+    #[test]
+    fn test_default_policy() {
+        let policy = RbacPolicy::default();
+
+        assert!(policy.allows(&[Role::Reader], Operation::ReadNodes));
+        assert!(!policy.allows(&[Role::Reader], Operation::WriteNodes));
+        assert!(policy.allows(&[Role::Writer], Operation::WriteNodes));
+        assert!(!policy.allows(&[Role::Writer], Operation::DeleteSessions));
+        assert!(!policy.allows(&[Role::Writer], Operation::ManageTemplates));
+        assert!(policy.allows(&[Role::Admin], Operation::DeleteSessions));
+        assert!(policy.allows(&[Role::Reader, Role::Admin], Operation::ManageTemplates));
+        assert!(!policy.allows(&[], Operation::ReadNodes));
+    }
+
+    #[test]
+    fn test_custom_grants() {
+        let policy = RbacPolicy::default()
+            .grant(Role::Writer, Operation::ManageTemplates)
+            .revoke(Role::Admin, Operation::DeleteSessions);
+
+        assert!(policy.allows(&[Role::Writer], Operation::ManageTemplates));
+        assert!(!policy.allows(&[Role::Admin], Operation::DeleteSessions));
+        assert!(!RbacPolicy::empty().allows(&[Role::Admin], Operation::ReadNodes));
+    }
+
+    #[test]
+    fn test_authorize_request() {
+        let policy = RbacPolicy::default();
+
+        let mut request = Request::new(());
+        request
+            .extensions_mut()
+            .insert(principal(vec![Role::Reader]));
+        assert!(policy
+            .authorize_request(&request, Operation::ReadNodes)
+            .is_ok());
+
+        let err = policy
+            .authorize_request(&request, Operation::WriteNodes)
+            .unwrap_err();
+        assert!(matches!(err, AuthError::PermissionDenied { .. }));
+        assert_eq!(
+            tonic::Status::from(err).code(),
+            tonic::Code::PermissionDenied
+        );
+
+        let unauthenticated = policy
+            .authorize_request(&Request::new(()), Operation::ReadNodes)
+            .unwrap_err();
+        assert_eq!(
+            tonic::Status::from(unauthenticated).code(),
+            tonic::Code::Unauthenticated
+        );
+    }
+
+    #[test]
+    fn test_role_parsing() {
+        assert_eq!("Writer".parse::<Role>().unwrap(), Role::Writer);
+        assert_eq!(" admin ".parse::<Role>().unwrap(), Role::Admin);
+        assert!("root".parse::<Role>().is_err());
+        assert_eq!(Role::Reader.to_string(), "reader");
+    }
+
+    #[tokio::test]
+    async fn test_plugin_enforces_policy() {
+        let plugin = RbacPlugin::default();
+
+        let read = PluginContext::new("query", serde_json::json!({}))
+            .with_metadata(ROLES_METADATA_KEY, "reader");
+        assert!(plugin.before_query(&read).await.is_ok());
+        assert!(plugin.before_create_session(&read).await.is_err());
+
+        let prompt = PluginContext::new("create_node", serde_json::json!({"node_type": "prompt"}))
+            .with_metadata(ROLES_METADATA_KEY, "writer");
+        assert!(plugin.before_create_node(&prompt).await.is_ok());
+
+        let template =
+            PluginContext::new("create_node", serde_json::json!({"node_type": "template"}))
+                .with_metadata(ROLES_METADATA_KEY, "writer");
+        assert!(plugin.before_create_node(&template).await.is_err());
+
+        let anonymous = PluginContext::new("query", serde_json::json!({}));
+        assert!(plugin
+            .before_hook("before_query", &anonymous)
+            .await
+            .is_err());
+    }
+}
@@ -0,0 +1,119 @@
+//! Per-tenant databases
+
+use super::TenantId;
+use crate::engine::{AsyncMemoryGraph, ShutdownReport};
+use crate::{Config, Result};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// Directory under the base path that holds one database per tenant
+const TENANTS_DIR: &str = "tenants";
+
+/// Opens and caches a separate database for each tenant
+///
+/// Tenant `acme` is stored at `<base path>/tenants/acme`, with every other
+/// setting taken from the base [`Config`]. Because tenants never share a sled
+/// database, a bug in query scoping cannot leak one tenant's nodes to another.
+pub struct TenantGraphs {
+    base: Config,
+    graphs: Mutex<HashMap<TenantId, Arc<AsyncMemoryGraph>>>,
+}
+
+impl TenantGraphs {
+    /// Create a registry rooted at `base.path`
+    pub fn new(base: Config) -> Self {
+        Self {
+            base,
+            graphs: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Database directory of a tenant
+    pub fn tenant_path(&self, tenant: &TenantId) -> PathBuf {
+        self.base.path.join(TENANTS_DIR).join(tenant.as_str())
+    }
+
+    /// The tenant's graph, opened on first use
+    pub async fn graph(&self, tenant: &TenantId) -> Result<Arc<AsyncMemoryGraph>> {
+        let mut graphs = self.graphs.lock().await;
+        if let Some(graph) = graphs.get(tenant) {
+            return Ok(Arc::clone(graph));
+        }
+
+        let config = Config {
+            path: self.tenant_path(tenant),
+            ..self.base.clone()
+        };
+        let graph = Arc::new(AsyncMemoryGraph::open(config).await?);
+        graphs.insert(tenant.clone(), Arc::clone(&graph));
+        Ok(graph)
+    }
+
+    /// Tenants whose graphs are currently open
+    pub async fn open_tenants(&self) -> Vec<TenantId> {
+        let mut tenants: Vec<_> = self.graphs.lock().await.keys().cloned().collect();
+        tenants.sort();
+        tenants
+    }
+
+    /// Flush every open tenant graph
+    pub async fn flush_all(&self) -> Result<()> {
+        let graphs: Vec<_> = self.graphs.lock().await.values().cloned().collect();
+        for graph in graphs {
+            graph.flush().await?;
+        }
+        Ok(())
+    }
+
+    /// Close every open tenant graph, returning each tenant's shutdown report
+    pub async fn close_all(&self) -> Result<Vec<(TenantId, ShutdownReport)>> {
+        let graphs: Vec<_> = self
+            .graphs
+            .lock()
+            .await
+            .iter()
+            .map(|(tenant, graph)| (tenant.clone(), Arc::clone(graph)))
+            .collect();
+        let mut reports = Vec::with_capacity(graphs.len());
+        for (tenant, graph) in graphs {
+            reports.push((tenant, graph.close().await?));
+        }
+        Ok(reports)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_tenants_are_isolated() {
+        let dir = tempdir().unwrap();
+        let tenants = TenantGraphs::new(Config::new(dir.path()));
+        let team_a = TenantId::new("team-a").unwrap();
+        let team_b = TenantId::new("team-b").unwrap();
+
+        let graph_a = tenants.graph(&team_a).await.unwrap();
+        let session = graph_a.create_session().await.unwrap();
+        graph_a
+            .add_prompt(session.id, "secret plan".to_string(), None)
+            .await
+            .unwrap();
+
+        let graph_b = tenants.graph(&team_b).await.unwrap();
+        assert!(graph_b.get_session(session.id).await.is_err());
+        assert_eq!(graph_b.stats().await.unwrap().node_count, 0);
+
+        // The same tenant gets the same graph back
+        let again = tenants.graph(&team_a).await.unwrap();
+        assert!(Arc::ptr_eq(&graph_a, &again));
+        assert_eq!(tenants.open_tenants().await, vec![team_a.clone(), team_b]);
+        assert!(tenants.tenant_path(&team_a).starts_with(dir.path()));
+        tenants.flush_all().await.unwrap();
+        assert_eq!(tenants.close_all().await.unwrap().len(), 2);
+        assert!(graph_a.is_closed());
+    }
+}
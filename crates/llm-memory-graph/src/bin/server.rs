@@ -17,6 +17,35 @@
 //! - `REGISTRY_API_KEY`: LLM-Registry API key (optional)
 //! - `VAULT_URL`: Data-Vault URL (optional)
 //! - `VAULT_API_KEY`: Data-Vault API key (optional)
+//! - `AUTH_API_KEYS`: Comma-separated `key=tenant[:role|role...]` API keys (optional;
+//!   keys without roles are read-only)
+//! - `AUTH_JWT_SECRET`: HS256 secret for validating JWT bearer tokens (optional)
+//! - `AUTH_JWT_TENANT_CLAIM`: JWT claim holding the tenant ID (default: tenant)
+//! - `AUTH_JWT_ROLES_CLAIM`: JWT claim holding the caller's roles (default: roles)
+//! - `AUDIT_LOG`: Record every mutation in the audit log (default: false)
+//! - `METRICS_LABELS`: Export metrics labeled by node type and model (default: false)
+//! - `METRICS_MAX_MODEL_LABELS`: Distinct model labels before models are reported
+//!   as `other` (default: 20)
+//!
+//! When either `AUTH_API_KEYS` or `AUTH_JWT_SECRET` is set, every gRPC call and
+//! `POST /import` must authenticate, is checked against the caller's roles, and
+//! runs on the caller's tenant's database under `DB_PATH/tenants/<tenant>`.
+//! `/health`, `/healthz` and `/metrics` stay open for probes and scrapers.
+//!
+//! # Streaming import
+//!
+//! `POST /import` on the metrics port bulk loads a chunked body of
+//! newline-delimited records, `{"node": ...}` or `{"edge": ...}`, without
+//! buffering it. The response streams one JSON progress line per batch
+//! written and ends with a line whose `done` is true, or one carrying an
+//! `error`. `?batch_size=` overrides the default batch size. With
+//! authentication enabled the caller needs the `write_nodes` permission and
+//! records go to their tenant's database.
+//!
+//! ```bash
+//! curl -T records.ndjson -X POST -H 'x-api-key: ...' \
+//!     http://localhost:9090/import
+//! ```
 //!
 //! # Usage
 //!
@@ -32,6 +61,15 @@
 //! cargo run --bin server
 //! ```
 
+use llm_memory_graph::auth::{
+    AuthInterceptor, Authenticator, JwtConfig, Operation, RbacPolicy, Role, TenantGraphs,
+};
+use llm_memory_graph::engine::{ndjson_records, BulkLoadReport, DEFAULT_BULK_BATCH_SIZE};
+use llm_memory_graph::grpc::proto::memory_graph_service_server::MemoryGraphServiceServer;
+use llm_memory_graph::grpc::{MemoryGraphServiceImpl, ServiceConfig as GrpcServiceConfig};
+use llm_memory_graph::observatory::prometheus::{MetricLabels, DEFAULT_MAX_MODEL_LABELS};
+use llm_memory_graph::observatory::OPENMETRICS_CONTENT_TYPE;
+use llm_memory_graph::validation::{validate_import_records, RequestLimits, RequestValidator};
 use llm_memory_graph::{engine::AsyncMemoryGraph, observatory::PrometheusMetrics, Config};
 use prometheus::Registry;
 use std::sync::Arc;
@@ -63,6 +101,18 @@ struct ServerConfig {
     /// Data-Vault API key
     #[allow(dead_code)]
     vault_api_key: Option<String>,
+    /// API keys and the tenant each one authenticates as
+    auth_api_keys: Vec<(String, String)>,
+    /// JWT signing secret
+    auth_jwt_secret: Option<String>,
+    /// JWT claim holding the tenant ID
+    auth_jwt_tenant_claim: String,
+    /// JWT claim holding the caller's roles
+    auth_jwt_roles_claim: String,
+    /// Whether mutations are recorded in the audit log
+    audit_log: bool,
+    /// Which labeled Prometheus metrics to export
+    metric_labels: MetricLabels,
     /// Server start time for uptime calculation
     start_time: Instant,
 }
@@ -86,6 +136,28 @@ impl ServerConfig {
             registry_api_key: std::env::var("REGISTRY_API_KEY").ok(),
             vault_url: std::env::var("VAULT_URL").ok(),
             vault_api_key: std::env::var("VAULT_API_KEY").ok(),
+            auth_api_keys: std::env::var("AUTH_API_KEYS")
+                .map(|s| parse_api_keys(&s))
+                .unwrap_or_default(),
+            auth_jwt_secret: std::env::var("AUTH_JWT_SECRET").ok(),
+            auth_jwt_tenant_claim: std::env::var("AUTH_JWT_TENANT_CLAIM")
+                .unwrap_or_else(|_| "tenant".to_string()),
+            auth_jwt_roles_claim: std::env::var("AUTH_JWT_ROLES_CLAIM")
+                .unwrap_or_else(|_| "roles".to_string()),
+            audit_log: std::env::var("AUDIT_LOG")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(false),
+            metric_labels: MetricLabels {
+                enabled: std::env::var("METRICS_LABELS")
+                    .ok()
+                    .and_then(|s| s.parse().ok())
+                    .unwrap_or(false),
+                max_models: std::env::var("METRICS_MAX_MODEL_LABELS")
+                    .ok()
+                    .and_then(|s| s.parse().ok())
+                    .unwrap_or(DEFAULT_MAX_MODEL_LABELS),
+            },
             start_time: Instant::now(),
         }
     }
@@ -104,6 +176,36 @@ impl ServerConfig {
         Ok(())
     }
 
+    /// Build the request authenticator from the auth settings
+    fn authenticator(&self) -> Result<Authenticator, String> {
+        let mut authenticator = Authenticator::new();
+        for (key, grant) in &self.auth_api_keys {
+            authenticator = match grant.split_once(':') {
+                Some((tenant, roles)) => {
+                    let roles = roles
+                        .split('|')
+                        .map(str::parse::<Role>)
+                        .collect::<Result<Vec<_>, _>>()
+                        .map_err(|e| format!("AUTH_API_KEYS: {}", e))?;
+                    authenticator.with_api_key_roles(key, tenant, roles)
+                }
+                None => authenticator.with_api_key(key, grant),
+            }
+            .map_err(|e| format!("AUTH_API_KEYS: {}", e))?;
+        }
+        if let Some(ref secret) = self.auth_jwt_secret {
+            if secret.is_empty() {
+                return Err("AUTH_JWT_SECRET must not be empty".to_string());
+            }
+            authenticator = authenticator.with_jwt(
+                JwtConfig::hs256(secret)
+                    .with_tenant_claim(&self.auth_jwt_tenant_claim)
+                    .with_roles_claim(&self.auth_jwt_roles_claim),
+            );
+        }
+        Ok(authenticator)
+    }
+
     /// Get the gRPC bind address
     fn grpc_address(&self) -> String {
         format!("{}:{}", self.grpc_host, self.grpc_port)
@@ -115,6 +217,17 @@ impl ServerConfig {
     }
 }
 
+/// Parse `key=tenant` pairs separated by commas, skipping malformed entries
+fn parse_api_keys(value: &str) -> Vec<(String, String)> {
+    value
+        .split(',')
+        .filter_map(|pair| {
+            let (key, tenant) = pair.trim().split_once('=')?;
+            (!key.is_empty()).then(|| (key.to_string(), tenant.to_string()))
+        })
+        .collect()
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Initialize tracing/logging
@@ -138,6 +251,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Load and validate configuration
     let config = ServerConfig::from_env();
     config.validate()?;
+    let authenticator = config.authenticator()?;
 
     info!("Configuration:");
     info!("  Database path: {}", config.db_path);
@@ -153,26 +267,55 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     if config.vault_url.is_some() {
         info!("  Data-Vault integration: enabled");
     }
+    if config.audit_log {
+        info!("  Audit log: enabled");
+    }
+    if authenticator.is_enabled() {
+        info!(
+            "  Authentication: enabled ({} API keys, JWT {})",
+            config.auth_api_keys.len(),
+            if config.auth_jwt_secret.is_some() {
+                "enabled"
+            } else {
+                "disabled"
+            }
+        );
+    } else {
+        warn!("  Authentication: disabled, all callers share one database");
+    }
 
     // Initialize Prometheus registry
     info!("Initializing Prometheus metrics...");
     let registry = Registry::new();
     let _metrics = Arc::new(
-        PrometheusMetrics::new(&registry)
+        PrometheusMetrics::with_labels(&registry, config.metric_labels)
             .map_err(|e| format!("Failed to create Prometheus metrics: {}", e))?,
     );
     info!("Prometheus metrics initialized successfully");
 
     // Initialize memory graph with Observatory
     info!("Opening memory graph database at: {}", config.db_path);
-    let graph_config = Config::new(&config.db_path);
+    let graph_config = Config::new(&config.db_path).with_audit_log(config.audit_log);
     let graph = Arc::new(
-        AsyncMemoryGraph::open(graph_config)
+        AsyncMemoryGraph::open(graph_config.clone())
             .await
             .map_err(|e| format!("Failed to open memory graph: {}", e))?,
     );
     info!("Memory graph database opened successfully");
 
+    // Per-tenant databases and the access policy, used when auth is enabled
+    let tenants = authenticator
+        .is_enabled()
+        .then(|| Arc::new(TenantGraphs::new(graph_config.clone())));
+    let rbac_policy = Arc::new(RbacPolicy::default());
+    let importer = Importer {
+        graph: Arc::clone(&graph),
+        tenants: tenants.clone(),
+        authenticator: authenticator.clone(),
+        rbac: Arc::clone(&rbac_policy),
+        limits: RequestLimits::default(),
+    };
+
     // Get initial statistics
     match graph.stats().await {
         Ok(stats) => {
@@ -182,8 +325,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             info!("  Total sessions: {}", stats.session_count);
 
             // Update Prometheus gauges with initial values
-            _metrics.set_total_nodes(stats.node_count as i64);
-            _metrics.set_total_edges(stats.edge_count as i64);
+            _metrics.record_storage_stats(&stats);
         }
         Err(e) => {
             warn!("Could not retrieve database statistics: {}", e);
@@ -210,8 +352,18 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Spawn metrics HTTP server
     let metrics_addr = config.metrics_address();
     let registry_clone = registry.clone();
+    let health_graph = Arc::clone(&graph);
+    let exporter = Arc::clone(&_metrics);
     let _metrics_handle = tokio::spawn(async move {
-        if let Err(e) = serve_metrics(registry_clone, metrics_addr).await {
+        if let Err(e) = serve_metrics(
+            registry_clone,
+            exporter,
+            health_graph,
+            importer,
+            metrics_addr,
+        )
+        .await
+        {
             error!("Metrics server error: {}", e);
         }
     });
@@ -221,9 +373,51 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         config.metrics_port
     );
 
-    // Note: gRPC service implementation would go here
-    // For now, we create a placeholder that demonstrates the structure
-    info!("gRPC service not yet fully implemented");
+    // Spawn the gRPC server; with auth enabled every call passes the
+    // interceptor, then RBAC, and runs on the caller's tenant's database
+    let grpc_addr = config.grpc_address().parse()?;
+    let mut service = MemoryGraphServiceImpl::new(
+        Arc::clone(&graph),
+        Some(Arc::clone(&_metrics)),
+        GrpcServiceConfig {
+            host: config.grpc_host.clone(),
+            port: config.grpc_port,
+            start_time: config.start_time,
+            ..GrpcServiceConfig::default()
+        },
+    );
+    if let Some(ref tenants) = tenants {
+        service = service
+            .with_tenants(Arc::clone(tenants))
+            .with_rbac(Arc::clone(&rbac_policy));
+    }
+    let (open_service, guarded_service) = if authenticator.is_enabled() {
+        let interceptor = AuthInterceptor::new(authenticator);
+        (
+            None,
+            Some(MemoryGraphServiceServer::with_interceptor(
+                service,
+                interceptor,
+            )),
+        )
+    } else {
+        (Some(MemoryGraphServiceServer::new(service)), None)
+    };
+    let (stop_grpc, grpc_stopped) = tokio::sync::oneshot::channel::<()>();
+    let grpc_handle = tokio::spawn(async move {
+        let result = tonic::transport::Server::builder()
+            .add_optional_service(open_service)
+            .add_optional_service(guarded_service)
+            .serve_with_shutdown(grpc_addr, async {
+                let _ = grpc_stopped.await;
+            })
+            .await;
+        if let Err(e) = result {
+            error!("gRPC server error: {}", e);
+        }
+    });
+
+    info!("gRPC server started on {}", grpc_addr);
     info!("Server initialization complete");
 
     // Wait for shutdown signal
@@ -241,13 +435,33 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Graceful shutdown
     info!("Starting graceful shutdown...");
 
+    // Stop accepting gRPC calls and let in-flight ones finish
+    let _ = stop_grpc.send(());
+    if let Err(e) = grpc_handle.await {
+        error!("gRPC server task failed: {}", e);
+    }
+
     // Abort metrics server
     _metrics_handle.abort();
 
-    // Flush database
-    info!("Flushing database...");
-    if let Err(e) = graph.flush().await {
-        error!("Error flushing database: {}", e);
+    // Stop background work and flush the database
+    info!("Closing database...");
+    match graph.close().await {
+        Ok(report) if !report.is_clean() => {
+            warn!("Database closed with pending work: {:?}", report)
+        }
+        Ok(_) => {}
+        Err(e) => error!("Error closing database: {}", e),
+    }
+    if let Some(ref tenants) = tenants {
+        match tenants.close_all().await {
+            Ok(reports) => {
+                for (tenant, report) in reports.iter().filter(|(_, r)| !r.is_clean()) {
+                    warn!("Tenant {} closed with pending work: {:?}", tenant, report);
+                }
+            }
+            Err(e) => error!("Error closing tenant databases: {}", e),
+        }
     }
 
     // Log final statistics
@@ -270,9 +484,145 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
-/// Serve Prometheus metrics on a separate HTTP port
+/// Query parameters of `POST /import`
+#[derive(Debug, serde::Deserialize)]
+struct ImportParams {
+    /// Nodes or edges written per batch
+    batch_size: Option<usize>,
+}
+
+/// Where `POST /import` writes and who may call it
+#[derive(Clone)]
+struct Importer {
+    /// Database used when authentication is disabled
+    graph: Arc<AsyncMemoryGraph>,
+    /// Per-tenant databases when authentication is enabled
+    tenants: Option<Arc<TenantGraphs>>,
+    authenticator: Authenticator,
+    rbac: Arc<RbacPolicy>,
+    /// Limits each batch and record is checked against
+    limits: RequestLimits,
+}
+
+impl Importer {
+    /// Database the caller's records go to
+    async fn graph(
+        &self,
+        headers: &warp::http::HeaderMap,
+    ) -> Result<Arc<AsyncMemoryGraph>, (warp::http::StatusCode, String)> {
+        use warp::http::StatusCode;
+
+        let Some(tenants) = &self.tenants else {
+            return Ok(Arc::clone(&self.graph));
+        };
+        let principal = self
+            .authenticator
+            .authenticate_headers(headers)
+            .map_err(|e| (StatusCode::UNAUTHORIZED, e.to_string()))?;
+        self.rbac
+            .authorize(&principal, Operation::WriteNodes)
+            .map_err(|e| (StatusCode::FORBIDDEN, e.to_string()))?;
+        tenants
+            .graph(&principal.tenant)
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+    }
+
+    /// Load the records in `body`, streaming progress back as they are written
+    async fn import<S, B>(
+        self,
+        headers: warp::http::HeaderMap,
+        params: ImportParams,
+        body: S,
+    ) -> warp::http::Response<warp::hyper::Body>
+    where
+        S: futures::Stream<Item = Result<B, warp::Error>> + Send + 'static,
+        B: warp::hyper::body::Buf + Send,
+    {
+        use futures::TryStreamExt;
+
+        let graph = match self.graph(&headers).await {
+            Ok(graph) => graph,
+            Err((status, message)) => {
+                return warp::http::Response::builder()
+                    .status(status)
+                    .body(warp::hyper::Body::from(message))
+                    .unwrap_or_default()
+            }
+        };
+        let batch_size = params.batch_size.unwrap_or(DEFAULT_BULK_BATCH_SIZE);
+        let mut validator = RequestValidator::new(&self.limits);
+        validator.batch("batch_size", batch_size);
+        if let Err(invalid) = validator.finish() {
+            return warp::http::Response::builder()
+                .status(warp::http::StatusCode::BAD_REQUEST)
+                .header("content-type", "application/json")
+                .body(warp::hyper::Body::from(
+                    serde_json::to_string(&invalid).unwrap_or_default(),
+                ))
+                .unwrap_or_default();
+        }
+        let limits = self.limits;
+
+        let (progress, mut lines) = tokio::sync::mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            let chunks = body.map_ok(|mut chunk| chunk.copy_to_bytes(chunk.remaining()));
+            let result = graph
+                .import_records(
+                    validate_import_records(ndjson_records(chunks), limits),
+                    batch_size,
+                    |report| {
+                        let _ = progress.send(import_progress(report, false));
+                    },
+                )
+                .await;
+            let last = match result {
+                Ok(report) => {
+                    info!(
+                        "Imported {} nodes and {} edges in {:?}",
+                        report.nodes_loaded, report.edges_loaded, report.duration
+                    );
+                    import_progress(&report, true)
+                }
+                Err(e) => {
+                    warn!("Import failed: {}", e);
+                    serde_json::json!({ "error": e.to_string() })
+                }
+            };
+            let _ = progress.send(last);
+        });
+
+        let body = async_stream::stream! {
+            while let Some(line) = lines.recv().await {
+                yield Ok::<_, std::convert::Infallible>(format!("{line}\n"));
+            }
+        };
+        warp::http::Response::builder()
+            .header("content-type", "application/x-ndjson")
+            .body(warp::hyper::Body::wrap_stream(body))
+            .unwrap_or_default()
+    }
+}
+
+/// One progress line of a streamed import
+fn import_progress(report: &BulkLoadReport, done: bool) -> serde_json::Value {
+    serde_json::json!({
+        "nodes_loaded": report.nodes_loaded,
+        "edges_loaded": report.edges_loaded,
+        "batches": report.batches,
+        "responses_reindexed": report.responses_reindexed,
+        "duration_ms": report.duration.as_millis() as u64,
+        "done": done,
+    })
+}
+
+/// Serve Prometheus metrics, health checks and streaming imports on a
+/// separate HTTP port
 async fn serve_metrics(
     registry: Registry,
+    exporter: Arc<PrometheusMetrics>,
+    graph: Arc<AsyncMemoryGraph>,
+    importer: Importer,
     addr: ([u8; 4], u16),
 ) -> Result<(), Box<dyn std::error::Error>> {
     use warp::Filter;
@@ -286,28 +636,58 @@ async fn serve_metrics(
         }))
     });
 
-    // Metrics endpoint
-    let metrics = warp::path("metrics").map(move || {
-        use prometheus::Encoder;
-        let encoder = prometheus::TextEncoder::new();
-        let metric_families = registry.gather();
-        let mut buffer = vec![];
-
-        match encoder.encode(&metric_families, &mut buffer) {
-            Ok(()) => match String::from_utf8(buffer) {
-                Ok(text) => warp::reply::with_status(text, warp::http::StatusCode::OK),
-                Err(_) => warp::reply::with_status(
-                    "Error encoding metrics".to_string(),
-                    warp::http::StatusCode::INTERNAL_SERVER_ERROR,
-                ),
-            },
-            Err(_) => warp::reply::with_status(
-                "Error gathering metrics".to_string(),
-                warp::http::StatusCode::INTERNAL_SERVER_ERROR,
-            ),
+    // Readiness endpoint with a per-subsystem breakdown; 503 when unhealthy
+    let healthz = warp::path("healthz").then(move || {
+        let graph = Arc::clone(&graph);
+        async move {
+            let report = graph.health().await;
+            let status = if report.is_serving() {
+                warp::http::StatusCode::OK
+            } else {
+                warp::http::StatusCode::SERVICE_UNAVAILABLE
+            };
+            warp::reply::with_status(warp::reply::json(&report), status)
         }
     });
 
+    // Metrics endpoint; OpenMetrics, with latency exemplars, when the scraper
+    // asks for it
+    let metrics = warp::path("metrics")
+        .and(warp::header::optional::<String>("accept"))
+        .map(move |accept: Option<String>| {
+            let openmetrics =
+                accept.is_some_and(|accept| accept.contains("application/openmetrics-text"));
+            let encoded = if openmetrics {
+                exporter
+                    .encode_openmetrics(&registry)
+                    .map(|text| (text, OPENMETRICS_CONTENT_TYPE))
+                    .map_err(|e| e.to_string())
+            } else {
+                prometheus::TextEncoder::new()
+                    .encode_to_string(&registry.gather())
+                    .map(|text| (text, prometheus::TEXT_FORMAT))
+                    .map_err(|e| e.to_string())
+            };
+
+            match encoded {
+                Ok((text, content_type)) => warp::http::Response::builder()
+                    .header("content-type", content_type)
+                    .body(text),
+                Err(e) => warp::http::Response::builder()
+                    .status(warp::http::StatusCode::INTERNAL_SERVER_ERROR)
+                    .body(format!("Error encoding metrics: {}", e)),
+            }
+        });
+
+    // Streaming import: NDJSON records in, NDJSON progress out
+    let import = warp::path("import")
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(warp::header::headers_cloned())
+        .and(warp::query::<ImportParams>())
+        .and(warp::body::stream())
+        .then(move |headers, params, body| importer.clone().import(headers, params, body));
+
     // Root endpoint
     let root = warp::path::end().map(|| {
         warp::reply::html(
@@ -328,12 +708,14 @@ async fn serve_metrics(
     <p>Available endpoints:</p>
     <div class="endpoint"><a href="/metrics">/metrics</a> - Prometheus metrics</div>
     <div class="endpoint"><a href="/health">/health</a> - Health check</div>
+    <div class="endpoint"><a href="/healthz">/healthz</a> - Subsystem health</div>
+    <div class="endpoint">POST /import - Streaming NDJSON import</div>
 </body>
 </html>"#,
         )
     });
 
-    let routes = root.or(health).or(metrics);
+    let routes = root.or(health).or(healthz).or(metrics).or(import);
 
     info!(
         "Metrics server listening on http://{}:{}",
@@ -360,6 +742,12 @@ mod tests {
             registry_api_key: None,
             vault_url: None,
             vault_api_key: None,
+            auth_api_keys: Vec::new(),
+            auth_jwt_secret: None,
+            auth_jwt_tenant_claim: "tenant".to_string(),
+            auth_jwt_roles_claim: "roles".to_string(),
+            audit_log: false,
+            metric_labels: MetricLabels::default(),
             start_time: Instant::now(),
         };
 
@@ -393,10 +781,77 @@ mod tests {
             registry_api_key: None,
             vault_url: None,
             vault_api_key: None,
+            auth_api_keys: Vec::new(),
+            auth_jwt_secret: None,
+            auth_jwt_tenant_claim: "tenant".to_string(),
+            auth_jwt_roles_claim: "roles".to_string(),
+            audit_log: false,
+            metric_labels: MetricLabels::default(),
             start_time: Instant::now(),
         };
 
         assert_eq!(config.grpc_address(), "0.0.0.0:50051");
         assert_eq!(config.metrics_address(), ([0, 0, 0, 0], 9090));
     }
+
+    #[test]
+    fn test_server_config_authenticator() {
+        assert_eq!(
+            parse_api_keys("k1=team-a, k2=team-b,broken,=x"),
+            vec![
+                ("k1".to_string(), "team-a".to_string()),
+                ("k2".to_string(), "team-b".to_string())
+            ]
+        );
+
+        let mut config = ServerConfig {
+            db_path: "./test".to_string(),
+            grpc_host: "0.0.0.0".to_string(),
+            grpc_port: 50051,
+            metrics_port: 9090,
+            plugin_dirs: None,
+            registry_url: None,
+            registry_api_key: None,
+            vault_url: None,
+            vault_api_key: None,
+            auth_api_keys: Vec::new(),
+            auth_jwt_secret: None,
+            auth_jwt_tenant_claim: "tenant".to_string(),
+            auth_jwt_roles_claim: "roles".to_string(),
+            audit_log: false,
+            metric_labels: MetricLabels::default(),
+            start_time: Instant::now(),
+        };
+        assert!(!config.authenticator().unwrap().is_enabled());
+
+        config.auth_api_keys = parse_api_keys("k1=team-a");
+        let authenticator = config.authenticator().unwrap();
+        assert!(authenticator.is_enabled());
+        assert_eq!(
+            authenticator
+                .authenticate_credentials(None, Some("k1"))
+                .unwrap()
+                .tenant
+                .as_str(),
+            "team-a"
+        );
+
+        config.auth_api_keys = parse_api_keys("k2=team-b:reader|writer");
+        let principal = config
+            .authenticator()
+            .unwrap()
+            .authenticate_credentials(None, Some("k2"))
+            .unwrap();
+        assert_eq!(principal.roles, vec![Role::Reader, Role::Writer]);
+
+        config.auth_api_keys = parse_api_keys("k2=team-b:root");
+        assert!(config.authenticator().is_err());
+
+        config.auth_api_keys = parse_api_keys("k1=../etc");
+        assert!(config.authenticator().is_err());
+
+        config.auth_api_keys.clear();
+        config.auth_jwt_secret = Some(String::new());
+        assert!(config.authenticator().is_err());
+    }
 }
@@ -0,0 +1,140 @@
+//! Change data capture of storage mutations
+//!
+//! When [`Config::change_capture`](crate::Config) is enabled, the storage
+//! backend appends a [`ChangeRecord`] for every node and edge it creates,
+//! updates or deletes, including writes made through bulk loads and
+//! maintenance. Each record carries a cursor that increases with every change,
+//! so downstream consumers (search indexers, data warehouses) can resume from
+//! the last cursor they processed with
+//! [`AsyncMemoryGraph::changes`](crate::engine::AsyncMemoryGraph::changes)
+//! instead of subscribing to Observatory events.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use futures::StreamExt;
+//! use llm_memory_graph::engine::AsyncMemoryGraph;
+//! use llm_memory_graph::Config;
+//!
+//! # async fn example(last_cursor: Option<u64>) -> Result<(), Box<dyn std::error::Error>> {
+//! let graph = AsyncMemoryGraph::open(Config::new("./data").with_change_capture(true)).await?;
+//!
+//! let mut changes = graph.changes(last_cursor);
+//! while let Some(change) = changes.next().await {
+//!     let change = change?;
+//!     println!("{} {:?} {:?}", change.cursor, change.kind, change.entity);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{Edge, EdgeId, Node, NodeId};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Default number of records fetched at a time when streaming changes
+pub const DEFAULT_CHANGE_PAGE_SIZE: usize = 1_000;
+
+/// What happened to the entity
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    /// The entity was written for the first time
+    Created,
+    /// An existing entity was overwritten
+    Updated,
+    /// The entity was removed
+    Deleted,
+}
+
+/// The node or edge a change applies to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(tag = "type", content = "id", rename_all = "snake_case")]
+pub enum ChangeEntity {
+    /// A node
+    Node(NodeId),
+    /// An edge
+    Edge(EdgeId),
+}
+
+/// One captured mutation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangeRecord {
+    /// Position in the change log, assigned by storage; increases with every
+    /// change
+    pub cursor: u64,
+    /// When the change was captured
+    pub timestamp: DateTime<Utc>,
+    /// What happened
+    pub kind: ChangeKind,
+    /// What it happened to
+    pub entity: ChangeEntity,
+    /// The node as written, for node creates and updates
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub node: Option<Node>,
+    /// The edge as written, for edge creates and updates
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub edge: Option<Edge>,
+}
+
+impl ChangeRecord {
+    fn new(kind: ChangeKind, entity: ChangeEntity) -> Self {
+        Self {
+            cursor: 0,
+            timestamp: Utc::now(),
+            kind,
+            entity,
+            node: None,
+            edge: None,
+        }
+    }
+
+    /// A node was created or updated
+    pub fn node_written(kind: ChangeKind, node: &Node) -> Self {
+        Self {
+            node: Some(node.clone()),
+            ..Self::new(kind, ChangeEntity::Node(node.id()))
+        }
+    }
+
+    /// A node was deleted
+    pub fn node_deleted(id: NodeId) -> Self {
+        Self::new(ChangeKind::Deleted, ChangeEntity::Node(id))
+    }
+
+    /// An edge was created or updated
+    pub fn edge_written(kind: ChangeKind, edge: &Edge) -> Self {
+        Self {
+            edge: Some(edge.clone()),
+            ..Self::new(kind, ChangeEntity::Edge(edge.id))
+        }
+    }
+
+    /// An edge was deleted
+    pub fn edge_deleted(id: EdgeId) -> Self {
+        Self::new(ChangeKind::Deleted, ChangeEntity::Edge(id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ConversationSession, EdgeType};
+
+    #[test]
+    fn test_record_json() {
+        let node = Node::Session(ConversationSession::new());
+        let record = ChangeRecord::node_written(ChangeKind::Created, &node);
+        let json = serde_json::to_value(&record).unwrap();
+        assert_eq!(json["kind"], "created");
+        assert_eq!(json["entity"]["type"], "node");
+        assert!(json.get("edge").is_none());
+
+        let edge = Edge::new(NodeId::new(), NodeId::new(), EdgeType::Follows);
+        let deleted = ChangeRecord::edge_deleted(edge.id);
+        let back: ChangeRecord =
+            serde_json::from_str(&serde_json::to_string(&deleted).unwrap()).unwrap();
+        assert_eq!(back.entity, ChangeEntity::Edge(edge.id));
+        assert!(back.edge.is_none());
+    }
+}
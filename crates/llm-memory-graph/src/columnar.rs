@@ -0,0 +1,395 @@
+//! Parquet export for analytics warehouses
+//!
+//! Available with the `parquet` feature.
+//! [`AsyncMemoryGraph::export_parquet`](crate::engine::AsyncMemoryGraph::export_parquet)
+//! writes the graph as typed, Snappy-compressed Parquet that DuckDB, Spark or
+//! pandas read directly:
+//!
+//! - [`ParquetExportKind::Nodes`]: one row per node, with content, model,
+//!   token counts and latency where the node type has them
+//! - [`ParquetExportKind::Edges`]: one row per edge
+//! - [`ParquetExportKind::Turns`]: one row per prompt/response pair (prompts
+//!   without a response get one row with empty response columns), the
+//!   shape most conversation analytics start from
+//!
+//! Timestamps are microseconds since the epoch in UTC; IDs are UUID strings;
+//! user-defined properties are a JSON string column.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use llm_memory_graph::columnar::ParquetExportKind;
+//! use llm_memory_graph::engine::AsyncMemoryGraph;
+//! use llm_memory_graph::Config;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let graph = AsyncMemoryGraph::open(Config::default()).await?;
+//! let rows = graph.export_parquet(ParquetExportKind::Turns, "turns.parquet").await?;
+//! // duckdb: SELECT response_model, avg(latency_ms) FROM 'turns.parquet' GROUP BY 1
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{Edge, Error, Node, PromptNode, ResponseNode, Result};
+use arrow_array::{
+    ArrayRef, RecordBatch, StringArray, TimestampMicrosecondArray, UInt32Array, UInt64Array,
+};
+use arrow_schema::{DataType, Field, Schema, SchemaRef, TimeUnit};
+use chrono::{DateTime, Utc};
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Rows written per Parquet row group
+pub const PARQUET_BATCH_SIZE: usize = 8_192;
+
+/// Which table to export
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParquetExportKind {
+    /// Every node
+    Nodes,
+    /// Every edge
+    Edges,
+    /// Prompt/response pairs
+    Turns,
+}
+
+fn timestamp_type() -> DataType {
+    DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into()))
+}
+
+fn utf8(name: &str, nullable: bool) -> Field {
+    Field::new(name, DataType::Utf8, nullable)
+}
+
+fn timestamp(name: &str, nullable: bool) -> Field {
+    Field::new(name, timestamp_type(), nullable)
+}
+
+fn uint32(name: &str) -> Field {
+    Field::new(name, DataType::UInt32, true)
+}
+
+fn uint64(name: &str) -> Field {
+    Field::new(name, DataType::UInt64, true)
+}
+
+/// Arrow schema of the Parquet files written for `kind`
+pub fn parquet_schema(kind: ParquetExportKind) -> SchemaRef {
+    let fields = match kind {
+        ParquetExportKind::Nodes => vec![
+            utf8("id", false),
+            utf8("node_type", false),
+            utf8("session_id", true),
+            timestamp("timestamp", false),
+            utf8("content", true),
+            utf8("model", true),
+            uint32("prompt_tokens"),
+            uint32("completion_tokens"),
+            uint32("total_tokens"),
+            uint64("latency_ms"),
+            utf8("properties", false),
+        ],
+        ParquetExportKind::Edges => vec![
+            utf8("id", false),
+            utf8("from_id", false),
+            utf8("to_id", false),
+            utf8("edge_type", false),
+            timestamp("created_at", false),
+            utf8("properties", false),
+        ],
+        ParquetExportKind::Turns => vec![
+            utf8("session_id", false),
+            utf8("prompt_id", false),
+            timestamp("prompt_timestamp", false),
+            utf8("prompt", false),
+            utf8("prompt_model", false),
+            utf8("response_id", true),
+            timestamp("response_timestamp", true),
+            utf8("response", true),
+            utf8("response_model", true),
+            utf8("finish_reason", true),
+            uint32("prompt_tokens"),
+            uint32("completion_tokens"),
+            uint32("total_tokens"),
+            uint64("latency_ms"),
+        ],
+    };
+    Arc::new(Schema::new(fields))
+}
+
+/// Streams rows of one kind into a Parquet file, a row group at a time
+pub(crate) struct ParquetSink {
+    kind: ParquetExportKind,
+    writer: ArrowWriter<std::fs::File>,
+    columns: Vec<Column>,
+    pending: usize,
+    written: usize,
+}
+
+/// Values of one column waiting to be written
+enum Column {
+    Utf8(Vec<Option<String>>),
+    Timestamp(Vec<Option<i64>>),
+    UInt32(Vec<Option<u32>>),
+    UInt64(Vec<Option<u64>>),
+}
+
+impl Column {
+    fn for_type(data_type: &DataType) -> Self {
+        match data_type {
+            DataType::Utf8 => Self::Utf8(Vec::new()),
+            DataType::UInt32 => Self::UInt32(Vec::new()),
+            DataType::UInt64 => Self::UInt64(Vec::new()),
+            _ => Self::Timestamp(Vec::new()),
+        }
+    }
+
+    fn take(&mut self) -> ArrayRef {
+        match self {
+            Self::Utf8(values) => Arc::new(StringArray::from(std::mem::take(values))),
+            Self::Timestamp(values) => Arc::new(
+                TimestampMicrosecondArray::from(std::mem::take(values)).with_timezone("UTC"),
+            ),
+            Self::UInt32(values) => Arc::new(UInt32Array::from(std::mem::take(values))),
+            Self::UInt64(values) => Arc::new(UInt64Array::from(std::mem::take(values))),
+        }
+    }
+}
+
+/// One cell of a row, in schema order
+enum Value {
+    Str(Option<String>),
+    Time(Option<DateTime<Utc>>),
+    U32(Option<u32>),
+    U64(Option<u64>),
+}
+
+fn text(value: impl Into<String>) -> Value {
+    Value::Str(Some(value.into()))
+}
+
+impl ParquetSink {
+    /// Create (or truncate) the file at `path`
+    pub(crate) fn create(kind: ParquetExportKind, path: &Path) -> Result<Self> {
+        let schema = parquet_schema(kind);
+        let columns = schema
+            .fields()
+            .iter()
+            .map(|field| Column::for_type(field.data_type()))
+            .collect();
+        let properties = WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .set_max_row_group_size(PARQUET_BATCH_SIZE)
+            .build();
+        let writer = ArrowWriter::try_new(std::fs::File::create(path)?, schema, Some(properties))
+            .map_err(parquet_error)?;
+        Ok(Self {
+            kind,
+            writer,
+            columns,
+            pending: 0,
+            written: 0,
+        })
+    }
+
+    /// Add a node row
+    pub(crate) fn push_node(&mut self, node: &Node) -> Result<()> {
+        debug_assert_eq!(self.kind, ParquetExportKind::Nodes);
+        let (session_id, timestamp, content, model, usage, latency) = match node {
+            Node::Prompt(p) => (
+                Some(p.session_id.to_string()),
+                p.timestamp,
+                Some(p.content.clone()),
+                Some(p.metadata.model.clone()),
+                None,
+                None,
+            ),
+            Node::Response(r) => (
+                None,
+                r.timestamp,
+                Some(r.content.clone()),
+                Some(r.metadata.model.clone()),
+                Some(r.usage),
+                Some(r.metadata.latency_ms),
+            ),
+            Node::Session(s) => (
+                Some(s.id.to_string()),
+                s.created_at,
+                s.title.clone(),
+                None,
+                None,
+                None,
+            ),
+            Node::ToolInvocation(t) => (
+                None,
+                t.timestamp,
+                Some(t.tool_name.clone()),
+                None,
+                None,
+                Some(t.duration_ms),
+            ),
+            Node::Agent(a) => (
+                None,
+                a.created_at,
+                Some(a.name.clone()),
+                Some(a.model.clone()),
+                None,
+                None,
+            ),
+            Node::Template(t) => (
+                None,
+                t.created_at,
+                Some(t.template.clone()),
+                None,
+                None,
+                None,
+            ),
+            Node::Custom(c) => (
+                c.session_id.map(|id| id.to_string()),
+                c.created_at,
+                Some(c.payload.to_string()),
+                None,
+                None,
+                None,
+            ),
+        };
+        self.push(vec![
+            text(node.id().to_string()),
+            text(format!("{:?}", node.node_type())),
+            Value::Str(session_id),
+            Value::Time(Some(timestamp)),
+            Value::Str(content),
+            Value::Str(model),
+            Value::U32(usage.map(|u| u.prompt_tokens)),
+            Value::U32(usage.map(|u| u.completion_tokens)),
+            Value::U32(usage.map(|u| u.total_tokens)),
+            Value::U64(latency),
+            text(serde_json::to_string(node.properties())?),
+        ])
+    }
+
+    /// Add an edge row
+    pub(crate) fn push_edge(&mut self, edge: &Edge) -> Result<()> {
+        debug_assert_eq!(self.kind, ParquetExportKind::Edges);
+        self.push(vec![
+            text(edge.id.to_string()),
+            text(edge.from.to_string()),
+            text(edge.to.to_string()),
+            text(format!("{:?}", edge.edge_type)),
+            Value::Time(Some(edge.created_at)),
+            text(serde_json::to_string(&edge.attributes)?),
+        ])
+    }
+
+    /// Add a turn row; `response` is `None` for an unanswered prompt
+    pub(crate) fn push_turn(
+        &mut self,
+        prompt: &PromptNode,
+        response: Option<&ResponseNode>,
+    ) -> Result<()> {
+        debug_assert_eq!(self.kind, ParquetExportKind::Turns);
+        self.push(vec![
+            text(prompt.session_id.to_string()),
+            text(prompt.id.to_string()),
+            Value::Time(Some(prompt.timestamp)),
+            text(prompt.content.clone()),
+            text(prompt.metadata.model.clone()),
+            Value::Str(response.map(|r| r.id.to_string())),
+            Value::Time(response.map(|r| r.timestamp)),
+            Value::Str(response.map(|r| r.content.clone())),
+            Value::Str(response.map(|r| r.metadata.model.clone())),
+            Value::Str(response.map(|r| r.metadata.finish_reason.clone())),
+            Value::U32(response.map(|r| r.usage.prompt_tokens)),
+            Value::U32(response.map(|r| r.usage.completion_tokens)),
+            Value::U32(response.map(|r| r.usage.total_tokens)),
+            Value::U64(response.map(|r| r.metadata.latency_ms)),
+        ])
+    }
+
+    fn push(&mut self, row: Vec<Value>) -> Result<()> {
+        for (column, value) in self.columns.iter_mut().zip(row) {
+            match (column, value) {
+                (Column::Utf8(values), Value::Str(v)) => values.push(v),
+                (Column::Timestamp(values), Value::Time(v)) => {
+                    values.push(v.map(|t| t.timestamp_micros()));
+                }
+                (Column::UInt32(values), Value::U32(v)) => values.push(v),
+                (Column::UInt64(values), Value::U64(v)) => values.push(v),
+                _ => unreachable!("row does not match the {:?} schema", self.kind),
+            }
+        }
+        self.pending += 1;
+        if self.pending >= PARQUET_BATCH_SIZE {
+            self.write_pending()?;
+        }
+        Ok(())
+    }
+
+    fn write_pending(&mut self) -> Result<()> {
+        if self.pending == 0 {
+            return Ok(());
+        }
+        let arrays = self.columns.iter_mut().map(Column::take).collect();
+        let batch = RecordBatch::try_new(parquet_schema(self.kind), arrays)
+            .map_err(|e| Error::SerializationError(e.to_string()))?;
+        self.writer.write(&batch).map_err(parquet_error)?;
+        self.written += self.pending;
+        self.pending = 0;
+        Ok(())
+    }
+
+    /// Write the remaining rows and the file footer; returns the row count
+    pub(crate) fn finish(mut self) -> Result<usize> {
+        self.write_pending()?;
+        self.writer.close().map_err(parquet_error)?;
+        Ok(self.written)
+    }
+}
+
+fn parquet_error(e: parquet::errors::ParquetError) -> Error {
+    Error::SerializationError(e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EdgeType, NodeId, SessionId, TokenUsage};
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_turns_file() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("turns.parquet");
+        let prompt = PromptNode::new(SessionId::new(), "Hi".to_string());
+        let response = ResponseNode::new(prompt.id, "Hello".to_string(), TokenUsage::new(3, 4));
+
+        let mut sink = ParquetSink::create(ParquetExportKind::Turns, &path).unwrap();
+        sink.push_turn(&prompt, Some(&response)).unwrap();
+        sink.push_turn(&prompt, None).unwrap();
+        assert_eq!(sink.finish().unwrap(), 2);
+
+        let reader = SerializedFileReader::new(std::fs::File::open(&path).unwrap()).unwrap();
+        let metadata = reader.metadata().file_metadata();
+        assert_eq!(metadata.num_rows(), 2);
+        assert_eq!(
+            metadata.schema_descr().num_columns(),
+            parquet_schema(ParquetExportKind::Turns).fields().len()
+        );
+    }
+
+    #[test]
+    fn test_edges_file() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("edges.parquet");
+        let mut sink = ParquetSink::create(ParquetExportKind::Edges, &path).unwrap();
+        for _ in 0..3 {
+            sink.push_edge(&Edge::new(NodeId::new(), NodeId::new(), EdgeType::Follows))
+                .unwrap();
+        }
+        assert_eq!(sink.finish().unwrap(), 3);
+    }
+}
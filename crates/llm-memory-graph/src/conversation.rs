@@ -0,0 +1,467 @@
+//! Paged access to a session's conversation for chat frontends
+//!
+//! [`AsyncMemoryGraph::get_conversation_page`](crate::engine::AsyncMemoryGraph::get_conversation_page)
+//! returns a session's turns (a prompt followed by its responses) a page at a
+//! time, in chronological order. Each page carries a [`ConversationCursor`] to
+//! request the next one; cursors name a turn rather than an offset, so turns
+//! added while a client scrolls do not shift or repeat the pages it fetches.
+//!
+//! [`AsyncMemoryGraph::get_turns`](crate::engine::AsyncMemoryGraph::get_turns)
+//! returns every turn of a session at once as a [`Turn`], pairing each prompt
+//! with the response that answers it and the tools that response invoked.
+//!
+//! [`AsyncMemoryGraph::thread`](crate::engine::AsyncMemoryGraph::thread)
+//! wraps both in a [`Thread`] handle for the common chat loop: append a prompt,
+//! append the model's reply, read the last few turns back as context, or walk
+//! the whole conversation a page at a time with [`Thread::iter`].
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use llm_memory_graph::conversation::PageDirection;
+//! use llm_memory_graph::engine::AsyncMemoryGraph;
+//! use llm_memory_graph::Config;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let graph = AsyncMemoryGraph::open(Config::default()).await?;
+//! # let session = graph.create_session().await?;
+//! // The latest 20 turns, then older ones as the user scrolls up
+//! let page = graph
+//!     .get_conversation_page(session.id, None, 20, PageDirection::Backward)
+//!     .await?;
+//! if let Some(cursor) = &page.next_cursor {
+//!     let older = graph
+//!         .get_conversation_page(session.id, Some(cursor), 20, PageDirection::Backward)
+//!         .await?;
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::engine::AsyncMemoryGraph;
+use crate::storage::AsyncStorageBackend;
+use crate::transcript::TranscriptTurn;
+use crate::{
+    Error, Node, NodeId, PromptMetadata, PromptNode, ResponseMetadata, ResponseNode, Result,
+    SessionId, TokenUsage, ToolInvocation, SELECTED_RESPONSE_PROPERTY,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::str::FromStr;
+
+/// Page size used when a caller has no preference
+pub const DEFAULT_CONVERSATION_PAGE_SIZE: usize = 50;
+
+/// Which way a page extends from its cursor
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PageDirection {
+    /// Newer turns, after the cursor; without a cursor, from the first turn
+    Forward,
+    /// Older turns, before the cursor; without a cursor, up to the latest turn
+    Backward,
+}
+
+/// Position of a turn in a conversation
+///
+/// Serialized as an opaque string for use in URLs and JSON responses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub struct ConversationCursor {
+    timestamp: DateTime<Utc>,
+    prompt_id: NodeId,
+}
+
+impl ConversationCursor {
+    fn of(prompt: &PromptNode) -> Self {
+        Self {
+            timestamp: prompt.timestamp,
+            prompt_id: prompt.id,
+        }
+    }
+
+    /// Prompt of the turn the cursor points at
+    pub fn prompt_id(&self) -> NodeId {
+        self.prompt_id
+    }
+
+    fn key(&self) -> (DateTime<Utc>, [u8; 16]) {
+        (self.timestamp, self.prompt_id.to_bytes())
+    }
+}
+
+impl fmt::Display for ConversationCursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}",
+            self.timestamp.timestamp_nanos_opt().unwrap_or_default(),
+            self.prompt_id
+        )
+    }
+}
+
+impl FromStr for ConversationCursor {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || Error::ValidationError(format!("invalid conversation cursor {s:?}"));
+        let (nanos, id) = s.split_once(':').ok_or_else(invalid)?;
+        let nanos: i64 = nanos.parse().map_err(|_| invalid())?;
+        let id = uuid::Uuid::parse_str(id).map_err(|_| invalid())?;
+        Ok(Self {
+            timestamp: DateTime::from_timestamp_nanos(nanos),
+            prompt_id: NodeId::from_uuid(id),
+        })
+    }
+}
+
+impl From<ConversationCursor> for String {
+    fn from(cursor: ConversationCursor) -> Self {
+        cursor.to_string()
+    }
+}
+
+impl TryFrom<String> for ConversationCursor {
+    type Error = Error;
+
+    fn try_from(s: String) -> Result<Self> {
+        s.parse()
+    }
+}
+
+/// A prompt and the responses to it, oldest response first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConversationTurn {
+    /// Position of this turn, usable as a cursor
+    pub cursor: ConversationCursor,
+    /// The prompt
+    pub prompt: PromptNode,
+    /// Responses to the prompt
+    pub responses: Vec<ResponseNode>,
+}
+
+/// A prompt paired with its answer
+///
+/// The answer is the response marked with
+/// [`SELECTED_RESPONSE_PROPERTY`](crate::SELECTED_RESPONSE_PROPERTY), or the
+/// latest response when none is marked, as when a prompt was regenerated.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Turn {
+    /// The prompt
+    pub prompt: PromptNode,
+    /// The answer, if the prompt has any response
+    pub response: Option<ResponseNode>,
+    /// Tools invoked by the answer, oldest first
+    pub tools: Vec<ToolInvocation>,
+    /// The prompt's other responses, oldest first
+    pub alternatives: Vec<ResponseNode>,
+}
+
+impl From<TranscriptTurn> for Turn {
+    fn from(turn: TranscriptTurn) -> Self {
+        let mut responses = turn.responses;
+        let selected = responses
+            .iter()
+            .position(|r| {
+                r.response.properties.get(SELECTED_RESPONSE_PROPERTY)
+                    == Some(&serde_json::Value::Bool(true))
+            })
+            .or_else(|| responses.len().checked_sub(1));
+        let chosen = selected.map(|index| responses.remove(index));
+        Self {
+            prompt: turn.prompt,
+            tools: chosen.as_ref().map(|r| r.tools.clone()).unwrap_or_default(),
+            response: chosen.map(|r| r.response),
+            alternatives: responses.into_iter().map(|r| r.response).collect(),
+        }
+    }
+}
+
+/// One page of a conversation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConversationPage {
+    /// Turns in chronological order, whichever the direction
+    pub turns: Vec<ConversationTurn>,
+    /// Cursor for the following page in the same direction, if there is one
+    pub next_cursor: Option<ConversationCursor>,
+}
+
+impl ConversationPage {
+    /// Read one page of a session's turns from `storage`
+    pub(crate) async fn collect(
+        storage: &dyn AsyncStorageBackend,
+        session_id: &SessionId,
+        cursor: Option<&ConversationCursor>,
+        limit: usize,
+        direction: PageDirection,
+    ) -> Result<Self> {
+        let limit = limit.max(1);
+        let mut prompts = Vec::new();
+        let mut responses: HashMap<NodeId, Vec<ResponseNode>> = HashMap::new();
+        for node in storage.get_session_nodes(session_id).await? {
+            match node {
+                Node::Prompt(prompt) => prompts.push(prompt),
+                Node::Response(response) => {
+                    responses
+                        .entry(response.prompt_id)
+                        .or_default()
+                        .push(response);
+                }
+                _ => {}
+            }
+        }
+        prompts.sort_by_key(|p| ConversationCursor::of(p).key());
+
+        let after = cursor.map(ConversationCursor::key);
+        let (selected, has_more) = match direction {
+            PageDirection::Forward => {
+                let start = after.map_or(0, |after| {
+                    prompts.partition_point(|p| ConversationCursor::of(p).key() <= after)
+                });
+                let end = (start + limit).min(prompts.len());
+                (start..end, end < prompts.len())
+            }
+            PageDirection::Backward => {
+                let end = after.map_or(prompts.len(), |before| {
+                    prompts.partition_point(|p| ConversationCursor::of(p).key() < before)
+                });
+                let start = end.saturating_sub(limit);
+                (start..end, start > 0)
+            }
+        };
+
+        let turns: Vec<ConversationTurn> = prompts
+            .drain(selected)
+            .map(|prompt| {
+                let mut replies = responses.remove(&prompt.id).unwrap_or_default();
+                replies.sort_by_key(|r| r.timestamp);
+                ConversationTurn {
+                    cursor: ConversationCursor::of(&prompt),
+                    prompt,
+                    responses: replies,
+                }
+            })
+            .collect();
+
+        let next_cursor = if has_more {
+            match direction {
+                PageDirection::Forward => turns.last(),
+                PageDirection::Backward => turns.first(),
+            }
+            .map(|turn| turn.cursor)
+        } else {
+            None
+        };
+
+        Ok(Self { turns, next_cursor })
+    }
+}
+
+/// Handle on one session's conversation
+///
+/// Obtained from [`AsyncMemoryGraph::thread`]. The handle holds only the
+/// session ID; every call reads from or writes through the graph, so it always
+/// sees turns added elsewhere.
+#[derive(Clone, Copy)]
+pub struct Thread<'a> {
+    graph: &'a AsyncMemoryGraph,
+    session_id: SessionId,
+}
+
+impl<'a> Thread<'a> {
+    pub(crate) fn new(graph: &'a AsyncMemoryGraph, session_id: SessionId) -> Self {
+        Self { graph, session_id }
+    }
+
+    /// Session the thread belongs to
+    pub fn session_id(&self) -> SessionId {
+        self.session_id
+    }
+
+    /// The latest `n` turns, oldest first
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the session does not exist or storage cannot be
+    /// read.
+    pub async fn last_n_turns(&self, n: usize) -> Result<Vec<ConversationTurn>> {
+        if n == 0 {
+            return Ok(Vec::new());
+        }
+        let page = self
+            .graph
+            .get_conversation_page(self.session_id, None, n, PageDirection::Backward)
+            .await?;
+        Ok(page.turns)
+    }
+
+    /// The latest turn, if the thread has any
+    pub async fn last_turn(&self) -> Result<Option<ConversationTurn>> {
+        Ok(self.last_n_turns(1).await?.pop())
+    }
+
+    /// Add a prompt to the end of the thread
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the session does not exist, the prompt breaks an
+    /// ingest rule or storage fails.
+    pub async fn append_prompt(
+        &self,
+        content: impl Into<String>,
+        metadata: Option<PromptMetadata>,
+    ) -> Result<NodeId> {
+        self.graph
+            .add_prompt(self.session_id, content.into(), metadata)
+            .await
+    }
+
+    /// Add a response to the thread's latest prompt
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::ValidationError`] if the thread has no prompt yet, and
+    /// an error if the response breaks an ingest rule or storage fails.
+    pub async fn append_response(
+        &self,
+        content: impl Into<String>,
+        token_usage: TokenUsage,
+        metadata: Option<ResponseMetadata>,
+    ) -> Result<NodeId> {
+        let prompt_id = self
+            .last_turn()
+            .await?
+            .map(|turn| turn.prompt.id)
+            .ok_or_else(|| {
+                Error::ValidationError(format!(
+                    "session {} has no prompt to respond to",
+                    self.session_id
+                ))
+            })?;
+        self.graph
+            .add_response(prompt_id, content.into(), token_usage, metadata)
+            .await
+    }
+
+    /// Walk the thread from its first turn, loading
+    /// [`DEFAULT_CONVERSATION_PAGE_SIZE`] turns at a time
+    // Turns load asynchronously, so the walk cannot be a std `Iterator`
+    #[allow(clippy::iter_not_returning_iterator)]
+    pub fn iter(&self) -> ThreadTurns<'a> {
+        ThreadTurns {
+            thread: *self,
+            page_size: DEFAULT_CONVERSATION_PAGE_SIZE,
+            buffered: VecDeque::new(),
+            cursor: None,
+            exhausted: false,
+        }
+    }
+}
+
+/// Turns of a [`Thread`], oldest first, read from storage a page at a time
+///
+/// Call [`next`](Self::next) until it returns `None`. Turns added during the
+/// walk are included if they fall in a page that has not been read yet.
+pub struct ThreadTurns<'a> {
+    thread: Thread<'a>,
+    page_size: usize,
+    buffered: VecDeque<ConversationTurn>,
+    cursor: Option<ConversationCursor>,
+    exhausted: bool,
+}
+
+impl ThreadTurns<'_> {
+    /// Load this many turns per read (at least one)
+    pub fn with_page_size(mut self, page_size: usize) -> Self {
+        self.page_size = page_size.max(1);
+        self
+    }
+
+    /// The next turn, loading another page when the current one runs out
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the session does not exist or storage cannot be
+    /// read.
+    pub async fn next(&mut self) -> Result<Option<ConversationTurn>> {
+        if self.buffered.is_empty() && !self.exhausted {
+            let page = self
+                .thread
+                .graph
+                .get_conversation_page(
+                    self.thread.session_id,
+                    self.cursor.as_ref(),
+                    self.page_size,
+                    PageDirection::Forward,
+                )
+                .await?;
+            self.exhausted = page.next_cursor.is_none();
+            self.cursor = page.next_cursor;
+            self.buffered.extend(page.turns);
+        }
+        Ok(self.buffered.pop_front())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cursor_round_trip() {
+        let prompt = PromptNode::new(SessionId::new(), "Hi".to_string());
+        let cursor = ConversationCursor::of(&prompt);
+        let parsed: ConversationCursor = cursor.to_string().parse().unwrap();
+        assert_eq!(parsed, cursor);
+
+        let json = serde_json::to_string(&cursor).unwrap();
+        assert_eq!(json, format!("\"{cursor}\""));
+        assert!("not-a-cursor".parse::<ConversationCursor>().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_thread() {
+        let dir = tempfile::tempdir().unwrap();
+        let graph = AsyncMemoryGraph::open(crate::Config::new(dir.path()))
+            .await
+            .unwrap();
+        let session = graph.create_session().await.unwrap();
+        let thread = graph.thread(session.id);
+
+        assert!(thread.last_turn().await.unwrap().is_none());
+        assert!(thread
+            .append_response("Too early", TokenUsage::new(1, 1), None)
+            .await
+            .is_err());
+
+        for i in 0..5 {
+            thread
+                .append_prompt(format!("Prompt {i}"), None)
+                .await
+                .unwrap();
+            thread
+                .append_response(format!("Answer {i}"), TokenUsage::new(1, 1), None)
+                .await
+                .unwrap();
+        }
+
+        let last = thread.last_n_turns(2).await.unwrap();
+        let prompts: Vec<_> = last.iter().map(|t| t.prompt.content.as_str()).collect();
+        assert_eq!(prompts, vec!["Prompt 3", "Prompt 4"]);
+        assert_eq!(last[1].responses[0].content, "Answer 4");
+        assert!(thread.last_n_turns(0).await.unwrap().is_empty());
+
+        let mut turns = thread.iter().with_page_size(2);
+        let mut seen = Vec::new();
+        while let Some(turn) = turns.next().await.unwrap() {
+            seen.push(turn.prompt.content);
+        }
+        assert_eq!(seen.len(), 5);
+        assert_eq!(seen[0], "Prompt 0");
+
+        assert!(graph.thread(SessionId::new()).last_turn().await.is_err());
+    }
+}
@@ -0,0 +1,218 @@
+//! Registration and validation of custom node types
+//!
+//! Applications store their own entities (documents, tickets, ...) as
+//! [`CustomNode`]s. Each type name must first be registered with a
+//! [`CustomTypeSpec`] through
+//! [`AsyncMemoryGraph::register_custom_type`](crate::engine::AsyncMemoryGraph::register_custom_type);
+//! [`add_custom_node`](crate::engine::AsyncMemoryGraph::add_custom_node)
+//! rejects nodes of unknown types and payloads missing a required field.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use llm_memory_graph::custom::CustomTypeSpec;
+//! use llm_memory_graph::engine::AsyncMemoryGraph;
+//! use llm_memory_graph::{Config, CustomNode};
+//! use serde_json::json;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let graph = AsyncMemoryGraph::open(Config::default()).await?;
+//! graph
+//!     .register_custom_type(CustomTypeSpec::new("ticket").with_required_field("title"))
+//!     .await?;
+//!
+//! let ticket = CustomNode::new("ticket", json!({"title": "Login fails", "priority": 2}));
+//! let node_id = graph.add_custom_node(ticket).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{CustomNode, Error, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Longest accepted custom type name
+pub const MAX_TYPE_NAME_LEN: usize = 64;
+
+/// Description of a custom node type
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CustomTypeSpec {
+    /// Type name stored on each node
+    pub name: String,
+    /// What nodes of the type represent
+    #[serde(default)]
+    pub description: String,
+    /// Top-level payload fields every node of the type must set
+    #[serde(default)]
+    pub required_fields: Vec<String>,
+}
+
+impl CustomTypeSpec {
+    /// A type without required fields
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            description: String::new(),
+            required_fields: Vec::new(),
+        }
+    }
+
+    /// Describe the type
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = description.into();
+        self
+    }
+
+    /// Require `field` in every payload
+    pub fn with_required_field(mut self, field: impl Into<String>) -> Self {
+        self.required_fields.push(field.into());
+        self
+    }
+
+    /// Check a node of this type
+    ///
+    /// # Errors
+    ///
+    /// Returns a validation error if the payload is not a JSON object or lacks
+    /// a required field.
+    pub fn validate(&self, node: &CustomNode) -> Result<()> {
+        let Some(payload) = node.payload.as_object() else {
+            return Err(Error::ValidationError(format!(
+                "payload of custom node {} must be a JSON object",
+                node.id
+            )));
+        };
+        if let Some(missing) = self
+            .required_fields
+            .iter()
+            .find(|field| !payload.contains_key(field.as_str()))
+        {
+            return Err(Error::ValidationError(format!(
+                "custom node {} of type '{}' is missing required field '{}'",
+                node.id, self.name, missing
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Check that `name` can be used as a custom type name
+///
+/// Names are 1 to [`MAX_TYPE_NAME_LEN`] characters of lowercase ASCII letters,
+/// digits, `_`, `-` and `.`, starting with a letter.
+///
+/// # Errors
+///
+/// Returns [`Error::InvalidNodeType`] for any other name.
+pub fn validate_type_name(name: &str) -> Result<()> {
+    let valid = name.len() <= MAX_TYPE_NAME_LEN
+        && name.starts_with(|c: char| c.is_ascii_lowercase())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '_' | '-' | '.'));
+    if valid {
+        Ok(())
+    } else {
+        Err(Error::InvalidNodeType(format!(
+            "invalid custom type name '{name}'"
+        )))
+    }
+}
+
+/// Registered custom node types, keyed by name
+#[derive(Debug, Clone, Default)]
+pub struct CustomTypeRegistry {
+    types: HashMap<String, CustomTypeSpec>,
+}
+
+impl CustomTypeRegistry {
+    /// An empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a type, replacing any earlier spec with the same name
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidNodeType`] if the name is not valid.
+    pub fn register(&mut self, spec: CustomTypeSpec) -> Result<()> {
+        validate_type_name(&spec.name)?;
+        self.types.insert(spec.name.clone(), spec);
+        Ok(())
+    }
+
+    /// The spec registered as `name`
+    pub fn get(&self, name: &str) -> Option<&CustomTypeSpec> {
+        self.types.get(name)
+    }
+
+    /// Whether `name` is registered
+    pub fn contains(&self, name: &str) -> bool {
+        self.types.contains_key(name)
+    }
+
+    /// Registered specs, sorted by name
+    pub fn specs(&self) -> Vec<CustomTypeSpec> {
+        let mut specs: Vec<_> = self.types.values().cloned().collect();
+        specs.sort_by(|a, b| a.name.cmp(&b.name));
+        specs
+    }
+
+    /// Check a node against its registered type
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidNodeType`] if the type is not registered and a
+    /// validation error if the payload does not match the spec.
+    pub fn validate(&self, node: &CustomNode) -> Result<()> {
+        match self.types.get(&node.type_name) {
+            Some(spec) => spec.validate(node),
+            None => Err(Error::InvalidNodeType(format!(
+                "custom type '{}' is not registered",
+                node.type_name
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_type_names() {
+        for name in ["ticket", "doc.v2", "jira-issue", "a_1"] {
+            assert!(validate_type_name(name).is_ok(), "{name}");
+        }
+        for name in ["", "Ticket", "1ticket", "has space", &"x".repeat(65)] {
+            assert!(validate_type_name(name).is_err(), "{name}");
+        }
+    }
+
+    #[test]
+    fn test_registry_validation() {
+        let mut registry = CustomTypeRegistry::new();
+        registry
+            .register(CustomTypeSpec::new("ticket").with_required_field("title"))
+            .unwrap();
+        assert!(registry.register(CustomTypeSpec::new("Bad Name")).is_err());
+
+        assert!(registry
+            .validate(&CustomNode::new("ticket", json!({"title": "Bug"})))
+            .is_ok());
+        assert!(matches!(
+            registry.validate(&CustomNode::new("ticket", json!({"body": "..."}))),
+            Err(Error::ValidationError(_))
+        ));
+        assert!(matches!(
+            registry.validate(&CustomNode::new("ticket", json!("Bug"))),
+            Err(Error::ValidationError(_))
+        ));
+        assert!(matches!(
+            registry.validate(&CustomNode::new("document", json!({}))),
+            Err(Error::InvalidNodeType(_))
+        ));
+    }
+}
@@ -0,0 +1,253 @@
+//! Timeouts and cancellation for long-running operations
+//!
+//! A [`Deadline`] bounds an operation by a timeout, a [`CancellationToken`],
+//! or both. Traversals
+//! ([`AsyncGraphTraversal::deadline`](crate::query::AsyncGraphTraversal::deadline)),
+//! queries ([`AsyncQueryBuilder::deadline`](crate::query::AsyncQueryBuilder::deadline))
+//! and session reads
+//! ([`AsyncMemoryGraph::get_session_nodes_until`](crate::engine::AsyncMemoryGraph::get_session_nodes_until))
+//! check it as they go, including inside the blocking storage scans they run,
+//! and stop with [`Error::Interrupted`] saying how far they got. Any other
+//! operation can be bounded with [`Deadline::run`], which stops waiting for
+//! it.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use llm_memory_graph::deadline::{CancellationToken, Deadline};
+//! use llm_memory_graph::engine::AsyncMemoryGraph;
+//! use llm_memory_graph::{Config, Error, NodeId};
+//! use std::time::Duration;
+//!
+//! # async fn example(start: NodeId) -> Result<(), Box<dyn std::error::Error>> {
+//! let graph = AsyncMemoryGraph::open(Config::default()).await?;
+//! let token = CancellationToken::new();
+//! let deadline = Deadline::after(Duration::from_secs(2)).with_cancellation(token.clone());
+//!
+//! match graph.traversal().deadline(deadline).build_subgraph(start).await {
+//!     Ok(subgraph) => println!("{} nodes", subgraph.nodes.len()),
+//!     Err(Error::Interrupted { completed, .. }) => {
+//!         println!("gave up after visiting {completed:?} nodes")
+//!     }
+//!     Err(e) => return Err(e.into()),
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{Error, InterruptReason, Result};
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+pub use tokio_util::sync::CancellationToken;
+
+/// When an operation has to stop: after a timeout, on cancellation, or never
+///
+/// Timeouts count from when they are set, and the time reported in
+/// [`Error::Interrupted`] from when the deadline was created.
+#[derive(Debug, Clone)]
+pub struct Deadline {
+    started: Instant,
+    expires_at: Option<Instant>,
+    token: Option<CancellationToken>,
+}
+
+impl Deadline {
+    /// A deadline that never passes
+    pub fn none() -> Self {
+        Self {
+            started: Instant::now(),
+            expires_at: None,
+            token: None,
+        }
+    }
+
+    /// A deadline `timeout` from now
+    pub fn after(timeout: Duration) -> Self {
+        Self::none().with_timeout(timeout)
+    }
+
+    /// A deadline that passes when `token` is cancelled
+    pub fn cancelled_by(token: CancellationToken) -> Self {
+        Self::none().with_cancellation(token)
+    }
+
+    /// Also pass `timeout` from now; the earlier of two timeouts applies
+    ///
+    /// A timeout too long to represent, such as [`Duration::MAX`], never
+    /// passes.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        if let Some(at) = Instant::now().checked_add(timeout) {
+            self.expires_at = Some(self.expires_at.map_or(at, |current| current.min(at)));
+        }
+        self
+    }
+
+    /// Also pass when `token` is cancelled, replacing any earlier token
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.token = Some(token);
+        self
+    }
+
+    /// Whether the deadline can never pass
+    pub fn is_unbounded(&self) -> bool {
+        self.expires_at.is_none() && self.token.is_none()
+    }
+
+    /// Time left before the timeout, if there is one
+    pub fn remaining(&self) -> Option<Duration> {
+        self.expires_at
+            .map(|at| at.saturating_duration_since(Instant::now()))
+    }
+
+    /// Why an operation must stop now, or `None` if it may go on
+    pub fn interruption(&self) -> Option<InterruptReason> {
+        if self
+            .token
+            .as_ref()
+            .is_some_and(CancellationToken::is_cancelled)
+        {
+            Some(InterruptReason::Cancelled)
+        } else if self.expires_at.is_some_and(|at| Instant::now() >= at) {
+            Some(InterruptReason::TimedOut)
+        } else {
+            None
+        }
+    }
+
+    /// Fail if the deadline has passed
+    ///
+    /// Long-running loops call this between steps, passing how many items
+    /// they have finished so the error reports partial progress.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Interrupted`] if the timeout elapsed or the token was
+    /// cancelled.
+    pub fn check(&self, operation: &str, completed: Option<u64>) -> Result<()> {
+        match self.interruption() {
+            Some(reason) => Err(self.interrupted(operation, reason, completed)),
+            None => Ok(()),
+        }
+    }
+
+    /// Run `future`, giving up on it once the deadline passes
+    ///
+    /// `completed` is the progress the operation had made before the future
+    /// started, reported if it is interrupted. The future is dropped when the
+    /// deadline passes; work it handed to a blocking thread keeps running
+    /// unless that work checks the deadline itself.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Interrupted`] if the deadline passes first, and the
+    /// future's error if it fails.
+    pub async fn run<T>(
+        &self,
+        operation: &str,
+        completed: Option<u64>,
+        future: impl Future<Output = Result<T>>,
+    ) -> Result<T> {
+        if self.is_unbounded() {
+            return future.await;
+        }
+        self.check(operation, completed)?;
+
+        let expired = async {
+            match self.expires_at {
+                Some(at) => tokio::time::sleep_until(at.into()).await,
+                None => std::future::pending().await,
+            }
+        };
+        let cancelled = async {
+            match &self.token {
+                Some(token) => token.cancelled().await,
+                None => std::future::pending().await,
+            }
+        };
+        tokio::select! {
+            biased;
+            result = future => result,
+            () = cancelled => Err(self.interrupted(operation, InterruptReason::Cancelled, completed)),
+            () = expired => Err(self.interrupted(operation, InterruptReason::TimedOut, completed)),
+        }
+    }
+
+    fn interrupted(
+        &self,
+        operation: &str,
+        reason: InterruptReason,
+        completed: Option<u64>,
+    ) -> Error {
+        Error::interrupted(operation, reason, self.started.elapsed(), completed)
+    }
+}
+
+impl Default for Deadline {
+    fn default() -> Self {
+        Self::none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ErrorCode;
+
+    #[tokio::test]
+    async fn test_check_and_run() {
+        let deadline = Deadline::none();
+        assert!(deadline.is_unbounded());
+        assert!(deadline.check("op", None).is_ok());
+
+        let deadline = Deadline::after(Duration::ZERO);
+        let err = deadline.check("scan", Some(3)).unwrap_err();
+        assert_eq!(err.code(), ErrorCode::Timeout);
+        assert!(matches!(
+            err,
+            Error::Interrupted {
+                completed: Some(3),
+                ..
+            }
+        ));
+
+        let deadline = Deadline::after(Duration::from_millis(20));
+        let err = deadline
+            .run("sleep", None, async {
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                Ok(())
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), ErrorCode::Timeout);
+
+        let token = CancellationToken::new();
+        let deadline = Deadline::cancelled_by(token.clone());
+        assert_eq!(
+            deadline.run("ready", None, async { Ok(1) }).await.unwrap(),
+            1
+        );
+        token.cancel();
+        assert_eq!(deadline.interruption(), Some(InterruptReason::Cancelled));
+        let err = deadline
+            .run("ready", Some(0), async { Ok(1) })
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), ErrorCode::Cancelled);
+    }
+
+    #[tokio::test]
+    async fn test_unrepresentable_timeout_never_passes() {
+        let deadline = Deadline::after(Duration::MAX);
+        assert!(deadline.is_unbounded());
+        assert_eq!(deadline.remaining(), None);
+        assert!(deadline.check("op", None).is_ok());
+        assert_eq!(
+            deadline.run("ready", None, async { Ok(1) }).await.unwrap(),
+            1
+        );
+
+        let deadline = Deadline::after(Duration::ZERO).with_timeout(Duration::MAX);
+        assert_eq!(deadline.interruption(), Some(InterruptReason::TimedOut));
+    }
+}
@@ -0,0 +1,176 @@
+//! Differences between two graphs
+//!
+//! Migrations, restores from backup and replication should all leave a copy
+//! of the graph holding exactly the same records. [`diff_backends`] compares
+//! every node and edge of two storage backends and reports what only one side
+//! holds and what both hold with different contents;
+//! [`AsyncMemoryGraph::diff`](crate::engine::AsyncMemoryGraph::diff) does the
+//! same for two open graphs, comparing consistent snapshots of each.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use llm_memory_graph::engine::AsyncMemoryGraph;
+//! use llm_memory_graph::Config;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let primary = AsyncMemoryGraph::open(Config::new("./data").with_read_only(true)).await?;
+//! let backup = AsyncMemoryGraph::open(Config::new("./backup").with_read_only(true)).await?;
+//!
+//! let diff = primary.diff(&backup).await?;
+//! if !diff.is_empty() {
+//!     println!("{} nodes missing from the backup", diff.nodes.removed.len());
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::storage::AsyncStorageBackend;
+use crate::{EdgeId, Node, NodeId, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// Records that differ between two graphs, each list sorted by ID
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordDiff<Id> {
+    /// Only in the second graph
+    pub added: Vec<Id>,
+    /// Only in the first graph
+    pub removed: Vec<Id>,
+    /// In both graphs with different contents
+    pub changed: Vec<Id>,
+    /// In both graphs with the same contents
+    pub unchanged: usize,
+}
+
+impl<Id> Default for RecordDiff<Id> {
+    fn default() -> Self {
+        Self {
+            added: Vec::new(),
+            removed: Vec::new(),
+            changed: Vec::new(),
+            unchanged: 0,
+        }
+    }
+}
+
+impl<Id> RecordDiff<Id> {
+    /// Whether both graphs hold the same records
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Nodes and edges that differ between two graphs
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GraphDiff {
+    /// Differences among nodes, sessions included
+    pub nodes: RecordDiff<NodeId>,
+    /// Differences among edges
+    pub edges: RecordDiff<EdgeId>,
+}
+
+impl GraphDiff {
+    /// Whether both graphs hold the same nodes and edges
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty() && self.edges.is_empty()
+    }
+}
+
+/// Compare every node and edge stored in `before` with those in `after`
+///
+/// Records are compared by their serialized contents, so any difference in
+/// a field, property or attribute counts as a change.
+///
+/// # Errors
+///
+/// Returns an error if either backend cannot be read.
+pub async fn diff_backends(
+    before: &dyn AsyncStorageBackend,
+    after: &dyn AsyncStorageBackend,
+) -> Result<GraphDiff> {
+    let nodes = diff_records(
+        before.all_nodes().await?,
+        after.all_nodes().await?,
+        Node::id,
+        NodeId::to_bytes,
+    )?;
+    let edges = diff_records(
+        before.all_edges().await?,
+        after.all_edges().await?,
+        |edge| edge.id,
+        EdgeId::to_bytes,
+    )?;
+    Ok(GraphDiff { nodes, edges })
+}
+
+fn diff_records<T: Serialize, Id: Copy + Eq + Hash>(
+    before: Vec<T>,
+    after: Vec<T>,
+    id: impl Fn(&T) -> Id,
+    sort_key: impl Fn(&Id) -> [u8; 16],
+) -> Result<RecordDiff<Id>> {
+    let mut remaining: HashMap<Id, serde_json::Value> = HashMap::with_capacity(before.len());
+    for record in before {
+        remaining.insert(id(&record), serde_json::to_value(&record)?);
+    }
+
+    let mut diff = RecordDiff::default();
+    for record in after {
+        let record_id = id(&record);
+        match remaining.remove(&record_id) {
+            None => diff.added.push(record_id),
+            Some(old) if old != serde_json::to_value(&record)? => diff.changed.push(record_id),
+            Some(_) => diff.unchanged += 1,
+        }
+    }
+    diff.removed = remaining.into_keys().collect();
+
+    diff.added.sort_by_key(&sort_key);
+    diff.removed.sort_by_key(&sort_key);
+    diff.changed.sort_by_key(&sort_key);
+    Ok(diff)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::AsyncSledBackend;
+    use crate::{ConversationSession, Edge, EdgeType, PromptNode};
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_diff_backends() {
+        let dir = tempdir().unwrap();
+        let a = AsyncSledBackend::open(dir.path().join("a")).await.unwrap();
+        let b = AsyncSledBackend::open(dir.path().join("b")).await.unwrap();
+
+        let session = ConversationSession::new();
+        let kept = Node::Session(session.clone());
+        let prompt = PromptNode::new(session.id, "Hello".to_string());
+        let mut edited = prompt.clone();
+        edited.content = "Hello again".to_string();
+        let only_a = Node::Prompt(PromptNode::new(session.id, "Bye".to_string()));
+        let only_b = Node::Prompt(PromptNode::new(session.id, "Hi".to_string()));
+        let edge = Edge::new(prompt.id, only_a.id(), EdgeType::Follows);
+
+        for node in [&kept, &Node::Prompt(prompt.clone()), &only_a] {
+            a.store_node(node).await.unwrap();
+        }
+        a.store_edge(&edge).await.unwrap();
+        for node in [&kept, &Node::Prompt(edited), &only_b] {
+            b.store_node(node).await.unwrap();
+        }
+
+        let diff = diff_backends(&a, &b).await.unwrap();
+        assert_eq!(diff.nodes.added, vec![only_b.id()]);
+        assert_eq!(diff.nodes.removed, vec![only_a.id()]);
+        assert_eq!(diff.nodes.changed, vec![prompt.id]);
+        assert_eq!(diff.nodes.unchanged, 1);
+        assert_eq!(diff.edges.removed, vec![edge.id]);
+        assert!(!diff.is_empty());
+
+        assert!(diff_backends(&a, &a).await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,334 @@
+//! Distributions over the graph's contents
+//!
+//! [`StorageStats`](crate::storage::StorageStats) counts records; this module
+//! describes their shape. [`graph_distributions`] walks the sessions of a
+//! backend and reports how many nodes sessions hold, how many tokens
+//! responses use, how many edges nodes have and how many sessions were
+//! started each day.
+//!
+//! Walking every session of a large graph is slow, so the scan can be sampled
+//! with [`DistributionOptions::with_sample_rate`]: only every n-th session,
+//! oldest first, has its nodes loaded. Sessions per day always covers every
+//! session, since it only needs the session records. The scan reports its
+//! progress after every session, which the CLI turns into a progress bar.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use llm_memory_graph::distribution::DistributionOptions;
+//! use llm_memory_graph::engine::AsyncMemoryGraph;
+//! use llm_memory_graph::Config;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let graph = AsyncMemoryGraph::open(Config::default()).await?;
+//!
+//! let options = DistributionOptions::new().with_sample_rate(0.1);
+//! let distributions = graph
+//!     .distributions(&options, |scanned, total| eprintln!("{scanned}/{total}"))
+//!     .await?;
+//! println!("p90 tokens per response: {}", distributions.tokens_per_response.p90);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::storage::AsyncStorageBackend;
+use crate::{Error, Node, Result};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// How to scan the graph for [`graph_distributions`]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DistributionOptions {
+    /// Fraction of sessions whose nodes are loaded, in `(0, 1]`
+    pub sample_rate: f64,
+}
+
+impl Default for DistributionOptions {
+    fn default() -> Self {
+        Self { sample_rate: 1.0 }
+    }
+}
+
+impl DistributionOptions {
+    /// Scan every session
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load the nodes of only this fraction of sessions
+    pub fn with_sample_rate(mut self, sample_rate: f64) -> Self {
+        self.sample_rate = sample_rate;
+        self
+    }
+
+    /// Distance between sampled sessions, or an error if the sample rate is
+    /// out of range
+    fn stride(self) -> Result<usize> {
+        if !(self.sample_rate > 0.0 && self.sample_rate <= 1.0) {
+            return Err(Error::ValidationError(format!(
+                "sample rate {} is not in (0, 1]",
+                self.sample_rate
+            )));
+        }
+        Ok((1.0 / self.sample_rate).round().max(1.0) as usize)
+    }
+}
+
+/// Values counted in one histogram bucket
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistogramBucket {
+    /// Smallest value in the bucket
+    pub min: u64,
+    /// Largest value in the bucket
+    pub max: u64,
+    /// Values that fell in the bucket
+    pub count: u64,
+}
+
+/// Histogram with power-of-two buckets: 0, 1, 2-3, 4-7, 8-15 and so on
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Histogram {
+    /// Buckets from smallest to largest, up to the largest value recorded
+    pub buckets: Vec<HistogramBucket>,
+}
+
+impl Histogram {
+    /// Count one value
+    pub fn record(&mut self, value: u64) {
+        let index = (u64::BITS - value.leading_zeros()) as usize;
+        while self.buckets.len() <= index {
+            let bucket = self.buckets.len() as u32;
+            let (min, max) = match bucket {
+                0 => (0, 0),
+                _ => (1 << (bucket - 1), (1 << (bucket - 1)) * 2 - 1),
+            };
+            self.buckets.push(HistogramBucket { min, max, count: 0 });
+        }
+        self.buckets[index].count += 1;
+    }
+
+    /// Number of values recorded
+    pub fn total(&self) -> u64 {
+        self.buckets.iter().map(|bucket| bucket.count).sum()
+    }
+}
+
+/// Summary of a set of values by percentile
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Percentiles {
+    /// Number of values
+    pub count: usize,
+    /// Smallest value
+    pub min: u64,
+    /// Median
+    pub p50: u64,
+    /// 90th percentile
+    pub p90: u64,
+    /// 99th percentile
+    pub p99: u64,
+    /// Largest value
+    pub max: u64,
+    /// Arithmetic mean
+    pub mean: f64,
+}
+
+impl Percentiles {
+    /// Summarise `values`, using the nearest-rank method
+    pub fn from_values(mut values: Vec<u64>) -> Self {
+        if values.is_empty() {
+            return Self::default();
+        }
+        values.sort_unstable();
+        let rank = |percentile: f64| {
+            let index = (percentile / 100.0 * values.len() as f64).ceil() as usize;
+            values[index.clamp(1, values.len()) - 1]
+        };
+        Self {
+            count: values.len(),
+            min: values[0],
+            p50: rank(50.0),
+            p90: rank(90.0),
+            p99: rank(99.0),
+            max: values[values.len() - 1],
+            mean: values.iter().sum::<u64>() as f64 / values.len() as f64,
+        }
+    }
+}
+
+/// Shape of the graph's contents, see the [module docs](self)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GraphDistributions {
+    /// Sessions in the graph
+    pub sessions_total: usize,
+    /// Sessions whose nodes were loaded
+    pub sessions_scanned: usize,
+    /// Fraction of sessions sampled
+    pub sample_rate: f64,
+    /// Nodes per scanned session, counting the session's own node
+    pub nodes_per_session: Histogram,
+    /// Total tokens used by each response in a scanned session
+    pub tokens_per_response: Percentiles,
+    /// Edges from and to each node in a scanned session
+    pub node_degree: Histogram,
+    /// Sessions created on each day (UTC), over every session
+    pub sessions_per_day: BTreeMap<NaiveDate, usize>,
+}
+
+/// Compute the distributions of `backend`'s contents, calling `on_progress`
+/// with the number of sessions scanned so far and the number to scan after
+/// every session
+///
+/// # Errors
+///
+/// Returns [`Error::ValidationError`] if the sample rate is out of range, or
+/// an error if storage cannot be read.
+pub async fn graph_distributions(
+    backend: &dyn AsyncStorageBackend,
+    options: &DistributionOptions,
+    mut on_progress: impl FnMut(usize, usize),
+) -> Result<GraphDistributions> {
+    let stride = options.stride()?;
+    let mut sessions = backend.list_sessions().await?;
+    sessions.sort_by_key(|session| session.created_at);
+
+    let mut distributions = GraphDistributions {
+        sessions_total: sessions.len(),
+        sample_rate: options.sample_rate,
+        ..GraphDistributions::default()
+    };
+    for session in &sessions {
+        *distributions
+            .sessions_per_day
+            .entry(session.created_at.date_naive())
+            .or_default() += 1;
+    }
+
+    let sampled: Vec<_> = sessions.iter().step_by(stride).collect();
+    let mut tokens = Vec::new();
+    for (scanned, session) in sampled.iter().enumerate() {
+        let nodes = backend.get_session_nodes(&session.id).await?;
+        distributions.nodes_per_session.record(nodes.len() as u64);
+        for node in &nodes {
+            if let Node::Response(response) = node {
+                tokens.push(u64::from(response.usage.total_tokens));
+            }
+            let degree = backend.node_degree(&node.id()).await?;
+            distributions.node_degree.record(degree.total() as u64);
+        }
+        distributions.sessions_scanned = scanned + 1;
+        on_progress(scanned + 1, sampled.len());
+    }
+    distributions.tokens_per_response = Percentiles::from_values(tokens);
+
+    Ok(distributions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_histogram_buckets() {
+        let mut histogram = Histogram::default();
+        for value in [0, 1, 2, 3, 5, 9] {
+            histogram.record(value);
+        }
+        let buckets: Vec<_> = histogram
+            .buckets
+            .iter()
+            .map(|bucket| (bucket.min, bucket.max, bucket.count))
+            .collect();
+        assert_eq!(
+            buckets,
+            [(0, 0, 1), (1, 1, 1), (2, 3, 2), (4, 7, 1), (8, 15, 1)]
+        );
+        assert_eq!(histogram.total(), 6);
+    }
+
+    #[test]
+    fn test_percentiles() {
+        let percentiles = Percentiles::from_values((1..=100).rev().collect());
+        assert_eq!(
+            (
+                percentiles.min,
+                percentiles.p50,
+                percentiles.p90,
+                percentiles.p99
+            ),
+            (1, 50, 90, 99)
+        );
+        assert_eq!(percentiles.max, 100);
+        assert!((percentiles.mean - 50.5).abs() < f64::EPSILON);
+        assert_eq!(Percentiles::from_values(Vec::new()).count, 0);
+    }
+
+    #[tokio::test]
+    async fn test_graph_distributions() {
+        use crate::engine::AsyncMemoryGraph;
+        use crate::{Config, TokenUsage};
+
+        let dir = tempfile::tempdir().unwrap();
+        let graph = AsyncMemoryGraph::open(Config::new(dir.path()))
+            .await
+            .unwrap();
+        for prompts in 1..=4 {
+            let session = graph.create_session().await.unwrap();
+            for turn in 0..prompts {
+                let prompt = graph
+                    .add_prompt(session.id, format!("Prompt {turn}"), None)
+                    .await
+                    .unwrap();
+                graph
+                    .add_response(
+                        prompt,
+                        "Answer".to_string(),
+                        TokenUsage::new(10, turn * 10),
+                        None,
+                    )
+                    .await
+                    .unwrap();
+            }
+        }
+
+        let mut progress = Vec::new();
+        let all = graph
+            .distributions(&DistributionOptions::new(), |done, total| {
+                progress.push((done, total));
+            })
+            .await
+            .unwrap();
+        assert_eq!(progress, [(1, 4), (2, 4), (3, 4), (4, 4)]);
+        assert_eq!(all.sessions_scanned, 4);
+        assert_eq!(all.nodes_per_session.total(), 4);
+        assert_eq!(all.tokens_per_response.count, 10);
+        assert_eq!(all.tokens_per_response.max, 40);
+        assert_eq!(all.node_degree.total(), 24);
+        assert_eq!(all.sessions_per_day.values().sum::<usize>(), 4);
+
+        let sampled = graph
+            .distributions(&DistributionOptions::new().with_sample_rate(0.5), |_, _| {})
+            .await
+            .unwrap();
+        assert_eq!((sampled.sessions_total, sampled.sessions_scanned), (4, 2));
+        assert_eq!(sampled.sessions_per_day.values().sum::<usize>(), 4);
+    }
+
+    #[test]
+    fn test_sample_rate_range() {
+        assert_eq!(DistributionOptions::new().stride().unwrap(), 1);
+        assert_eq!(
+            DistributionOptions::new()
+                .with_sample_rate(0.25)
+                .stride()
+                .unwrap(),
+            4
+        );
+        for rate in [0.0, 1.5, f64::NAN] {
+            assert!(DistributionOptions::new()
+                .with_sample_rate(rate)
+                .stride()
+                .is_err());
+        }
+    }
+}
@@ -0,0 +1,396 @@
+//! Bulk loading for large imports
+//!
+//! [`AsyncMemoryGraph::bulk_loader`](super::AsyncMemoryGraph::bulk_loader)
+//! returns a [`BulkLoader`] that writes nodes and edges straight to storage in
+//! large sorted batches. Writes made through the loader skip the read cache,
+//! Observatory events, audit entries and prompt deduplication, which makes it
+//! suitable for migrating millions of records; normal writes through the graph
+//! are unaffected and behave as usual.
+//!
+//! Responses are indexed under the session of their prompt. A response loaded
+//! before its prompt is written immediately and indexed again by
+//! [`finish`](BulkLoader::finish) once every prompt is in storage.
+//!
+//! Remote imports arrive as a stream of [`ImportRecord`]s, one JSON object per
+//! line of an HTTP body or per message of the `ImportNodes` RPC.
+//! [`ndjson_records`] parses them from a body as it arrives and
+//! [`AsyncMemoryGraph::import_records`](super::AsyncMemoryGraph::import_records)
+//! feeds them through a loader, reporting progress after every batch.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use llm_memory_graph::engine::AsyncMemoryGraph;
+//! use llm_memory_graph::{Config, Node};
+//!
+//! # async fn example(nodes: Vec<Node>) -> Result<(), Box<dyn std::error::Error>> {
+//! let graph = AsyncMemoryGraph::open(Config::default()).await?;
+//!
+//! let mut loader = graph.bulk_loader().with_batch_size(50_000);
+//! loader.add_nodes(nodes).await?;
+//! let report = loader.finish().await?;
+//! println!("loaded {} nodes in {:?}", report.nodes_loaded, report.duration);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::storage::{AsyncStorageBackend, StorageCache};
+use crate::{Edge, Node, NodeId};
+use crate::{Error, Result};
+use futures::{Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// Default number of nodes or edges written per batch
+pub const DEFAULT_BULK_BATCH_SIZE: usize = 10_000;
+
+/// What a bulk load wrote
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BulkLoadReport {
+    /// Nodes written
+    pub nodes_loaded: u64,
+    /// Edges written
+    pub edges_loaded: u64,
+    /// Write batches issued
+    pub batches: u64,
+    /// Responses loaded before their prompt and indexed again at the end
+    pub responses_reindexed: u64,
+    /// Time from creating the loader to finishing it
+    pub duration: Duration,
+}
+
+/// One record of a streamed import
+///
+/// Serialized as `{"node": ...}` or `{"edge": ...}`, with the node or edge in
+/// the same form as a JSON export.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportRecord {
+    /// A node to load
+    Node(Node),
+    /// An edge to load
+    Edge(Edge),
+}
+
+/// Parse newline-delimited [`ImportRecord`]s from a stream of byte chunks
+///
+/// Records are yielded as soon as their line is complete, so a body of any
+/// size is parsed in constant memory. Blank lines are skipped; a line that is
+/// not a record, or a failed chunk, ends the stream with an error naming the
+/// line.
+pub fn ndjson_records<S, B, E>(chunks: S) -> impl Stream<Item = Result<ImportRecord>>
+where
+    S: Stream<Item = std::result::Result<B, E>>,
+    B: AsRef<[u8]>,
+    E: fmt::Display,
+{
+    async_stream::try_stream! {
+        futures::pin_mut!(chunks);
+        let mut buffer = Vec::new();
+        let mut line = 0;
+        while let Some(chunk) = chunks.next().await {
+            let chunk = chunk.map_err(|e| Error::IoError(format!("line {}: {e}", line + 1)))?;
+            buffer.extend_from_slice(chunk.as_ref());
+
+            let mut start = 0;
+            while let Some(len) = buffer[start..].iter().position(|byte| *byte == b'\n') {
+                line += 1;
+                if let Some(record) = parse_line(&buffer[start..start + len], line)? {
+                    yield record;
+                }
+                start += len + 1;
+            }
+            buffer.drain(..start);
+        }
+        if let Some(record) = parse_line(&buffer, line + 1)? {
+            yield record;
+        }
+    }
+}
+
+fn parse_line(line: &[u8], number: usize) -> Result<Option<ImportRecord>> {
+    if line.trim_ascii().is_empty() {
+        return Ok(None);
+    }
+    serde_json::from_slice(line)
+        .map(Some)
+        .map_err(|e| Error::DeserializationError(format!("line {number}: {e}")))
+}
+
+/// Handle for loading nodes and edges in large batches
+///
+/// Buffered records are written whenever a buffer reaches the batch size, on
+/// [`flush`](Self::flush) and on [`finish`](Self::finish). Records still
+/// buffered when the loader is dropped without finishing are discarded.
+pub struct BulkLoader<'a> {
+    backend: &'a dyn AsyncStorageBackend,
+    cache: &'a StorageCache,
+    batch_size: usize,
+    nodes: Vec<Node>,
+    edges: Vec<Edge>,
+    /// Prompts written so far, to detect responses that arrive first
+    prompts: HashSet<NodeId>,
+    /// Responses written before their prompt was known
+    unindexed: Vec<Node>,
+    report: BulkLoadReport,
+    started: Instant,
+}
+
+impl<'a> BulkLoader<'a> {
+    pub(super) fn new(backend: &'a dyn AsyncStorageBackend, cache: &'a StorageCache) -> Self {
+        Self {
+            backend,
+            cache,
+            batch_size: DEFAULT_BULK_BATCH_SIZE,
+            nodes: Vec::new(),
+            edges: Vec::new(),
+            prompts: HashSet::new(),
+            unindexed: Vec::new(),
+            report: BulkLoadReport::default(),
+            started: Instant::now(),
+        }
+    }
+
+    /// Write a batch once this many nodes or edges are buffered (at least one)
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Buffer a node, writing a batch if the buffer is full
+    pub async fn add_node(&mut self, node: Node) -> Result<()> {
+        self.nodes.push(node);
+        if self.nodes.len() >= self.batch_size {
+            self.write_nodes().await?;
+        }
+        Ok(())
+    }
+
+    /// Buffer several nodes, writing batches as buffers fill
+    pub async fn add_nodes(&mut self, nodes: impl IntoIterator<Item = Node>) -> Result<()> {
+        for node in nodes {
+            self.add_node(node).await?;
+        }
+        Ok(())
+    }
+
+    /// Buffer an edge, writing a batch if the buffer is full
+    ///
+    /// Buffered nodes are written first so that edges never precede the nodes
+    /// they were loaded with.
+    pub async fn add_edge(&mut self, edge: Edge) -> Result<()> {
+        self.edges.push(edge);
+        if self.edges.len() >= self.batch_size {
+            self.write_nodes().await?;
+            self.write_edges().await?;
+        }
+        Ok(())
+    }
+
+    /// Buffer several edges, writing batches as buffers fill
+    pub async fn add_edges(&mut self, edges: impl IntoIterator<Item = Edge>) -> Result<()> {
+        for edge in edges {
+            self.add_edge(edge).await?;
+        }
+        Ok(())
+    }
+
+    /// Write everything buffered so far
+    pub async fn flush(&mut self) -> Result<()> {
+        self.write_nodes().await?;
+        self.write_edges().await
+    }
+
+    /// Write remaining records, index responses that arrived before their
+    /// prompt and flush storage
+    pub async fn finish(mut self) -> Result<BulkLoadReport> {
+        self.flush().await?;
+
+        // Every prompt is stored now, so rewriting these resolves their session
+        let unindexed = std::mem::take(&mut self.unindexed);
+        for chunk in unindexed.chunks(self.batch_size) {
+            self.backend.store_nodes_batch(chunk).await?;
+            self.report.batches += 1;
+        }
+        self.report.responses_reindexed = unindexed.len() as u64;
+
+        self.backend.flush().await?;
+        self.report.duration = self.started.elapsed();
+        Ok(self.report)
+    }
+
+    /// Records buffered but not yet written
+    pub fn pending(&self) -> usize {
+        self.nodes.len() + self.edges.len()
+    }
+
+    /// What has been written so far, and for how long the loader has run
+    pub fn report(&self) -> BulkLoadReport {
+        BulkLoadReport {
+            duration: self.started.elapsed(),
+            ..self.report.clone()
+        }
+    }
+
+    async fn write_nodes(&mut self) -> Result<()> {
+        if self.nodes.is_empty() {
+            return Ok(());
+        }
+        let mut nodes = std::mem::take(&mut self.nodes);
+        // Sorted keys keep sled's inserts sequential
+        nodes.sort_by_key(|node| node.id().to_bytes());
+
+        for node in &nodes {
+            if let Node::Prompt(prompt) = node {
+                self.prompts.insert(prompt.id);
+            }
+        }
+        for node in &nodes {
+            if let Node::Response(response) = node {
+                if !self.prompts.contains(&response.prompt_id)
+                    && self.backend.get_node(&response.prompt_id).await?.is_none()
+                {
+                    self.unindexed.push(node.clone());
+                }
+            }
+        }
+
+        self.backend.store_nodes_batch(&nodes).await?;
+        for node in &nodes {
+            self.cache.invalidate_node(&node.id()).await;
+        }
+        self.report.nodes_loaded += nodes.len() as u64;
+        self.report.batches += 1;
+        Ok(())
+    }
+
+    async fn write_edges(&mut self) -> Result<()> {
+        if self.edges.is_empty() {
+            return Ok(());
+        }
+        let mut edges = std::mem::take(&mut self.edges);
+        edges.sort_by_key(|edge| edge.id.to_bytes());
+
+        self.backend.store_edges_batch(&edges).await?;
+        for edge in &edges {
+            self.cache.invalidate_edge(&edge.id).await;
+        }
+        self.report.edges_loaded += edges.len() as u64;
+        self.report.batches += 1;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ndjson_records, ImportRecord};
+    use crate::engine::AsyncMemoryGraph;
+    use crate::{Config, ConversationSession, EdgeType, PromptNode, ResponseNode, TokenUsage};
+    use crate::{Edge, Node};
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_bulk_load() {
+        let dir = tempdir().unwrap();
+        let graph = AsyncMemoryGraph::open(Config::new(dir.path()))
+            .await
+            .unwrap();
+
+        let session = ConversationSession::new();
+        let prompts: Vec<PromptNode> = (0..25)
+            .map(|i| PromptNode::new(session.id, format!("Prompt {i}")))
+            .collect();
+        // The last prompt's response arrives in an earlier batch than the prompt
+        let early = ResponseNode::new(prompts[24].id, "Early".to_string(), TokenUsage::new(1, 1));
+
+        let mut loader = graph.bulk_loader().with_batch_size(10);
+        loader
+            .add_node(Node::Session(session.clone()))
+            .await
+            .unwrap();
+        loader
+            .add_node(Node::Response(early.clone()))
+            .await
+            .unwrap();
+        for prompt in &prompts {
+            loader.add_node(Node::Prompt(prompt.clone())).await.unwrap();
+            loader
+                .add_edge(Edge::new(prompt.id, session.node_id, EdgeType::PartOf))
+                .await
+                .unwrap();
+        }
+        let report = loader.finish().await.unwrap();
+
+        assert_eq!(report.nodes_loaded, 27);
+        assert_eq!(report.edges_loaded, 25);
+        assert_eq!(report.responses_reindexed, 1);
+        assert_eq!(
+            graph.get_session_nodes(&session.id).await.unwrap().len(),
+            27
+        );
+        assert_eq!(
+            graph.node_degree(&session.node_id).await.unwrap().incoming,
+            25
+        );
+    }
+
+    #[tokio::test]
+    async fn test_import_ndjson_records() {
+        let dir = tempdir().unwrap();
+        let graph = AsyncMemoryGraph::open(Config::new(dir.path()))
+            .await
+            .unwrap();
+
+        let session = ConversationSession::new();
+        let mut records = vec![ImportRecord::Node(Node::Session(session.clone()))];
+        for i in 0..12 {
+            let prompt = PromptNode::new(session.id, format!("Prompt {i}"));
+            records.push(ImportRecord::Edge(Edge::new(
+                prompt.id,
+                session.node_id,
+                EdgeType::PartOf,
+            )));
+            records.push(ImportRecord::Node(Node::Prompt(prompt)));
+        }
+        let mut body = String::new();
+        for record in &records {
+            body.push_str(&serde_json::to_string(record).unwrap());
+            body.push_str("\r\n\n");
+        }
+        // Chunk boundaries fall in the middle of records
+        let chunks: Vec<_> = body
+            .as_bytes()
+            .chunks(7)
+            .map(|chunk| Ok::<_, std::io::Error>(chunk.to_vec()))
+            .collect();
+
+        let mut progress = Vec::new();
+        let report = graph
+            .import_records(ndjson_records(futures::stream::iter(chunks)), 5, |report| {
+                progress.push(report.batches);
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(report.nodes_loaded, 13);
+        assert_eq!(report.edges_loaded, 12);
+        assert!(progress.len() >= 4);
+        assert!(progress.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(
+            graph.node_degree(&session.node_id).await.unwrap().incoming,
+            12
+        );
+
+        let bad = futures::stream::iter(vec![
+            Ok::<_, std::io::Error>(b"{\"node\":".to_vec()),
+            Ok(b" 1}\n".to_vec()),
+        ]);
+        let err = graph
+            .import_records(ndjson_records(bad), 5, |_| {})
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("line 1"));
+    }
+}
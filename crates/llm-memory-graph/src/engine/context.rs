@@ -0,0 +1,145 @@
+//! Context assembly from REFERENCES edges
+//!
+//! [`AsyncMemoryGraph::assemble_context`](super::AsyncMemoryGraph::assemble_context)
+//! collects the nodes a prompt references and orders them by the
+//! [`priority`](crate::Edge::priority) and then the
+//! [`relevance`](crate::Edge::relevance) of the referencing edge, so callers
+//! filling a limited context window keep the most important material.
+//! [`Priority::Critical`] references, such as system policies, are kept even
+//! when they fall below the relevance threshold or exceed the token budget.
+//!
+//! With a [`reference_half_life_ms`](crate::MaintenanceConfig::reference_half_life_ms)
+//! configured, relevance decays with the age of the edge, halving every
+//! half-life, so stale references sink below fresh ones and eventually under
+//! the relevance threshold.
+//! [`AsyncMemoryGraph::prune_references`](super::AsyncMemoryGraph::prune_references)
+//! removes those that decay below the configured floor.
+
+use crate::{Edge, Node, Priority};
+use chrono::Utc;
+
+/// Which references to include when assembling context
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ContextOptions {
+    /// Keep at most this many references
+    pub max_items: Option<usize>,
+    /// Drop references less relevant than this
+    pub min_relevance: f32,
+    /// Keep references until their estimated tokens reach this budget
+    pub token_budget: Option<usize>,
+}
+
+impl ContextOptions {
+    /// Include every reference
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keep only the `max_items` most important references
+    pub fn max_items(mut self, max_items: usize) -> Self {
+        self.max_items = Some(max_items);
+        self
+    }
+
+    /// Drop references less relevant than `min_relevance`, unless critical
+    pub fn min_relevance(mut self, min_relevance: f32) -> Self {
+        self.min_relevance = min_relevance;
+        self
+    }
+
+    /// Keep references, most important first, while their estimated tokens
+    /// fit in `token_budget`; critical references are always kept
+    pub fn token_budget(mut self, token_budget: usize) -> Self {
+        self.token_budget = Some(token_budget);
+        self
+    }
+}
+
+/// A referenced node with the edge that references it
+#[derive(Debug, Clone)]
+pub struct ContextItem {
+    /// The referenced node
+    pub node: Node,
+    /// The REFERENCES edge
+    pub edge: Edge,
+    /// Relevance of the reference, after decay; references without one
+    /// count as 0.0
+    pub relevance: f32,
+    /// Priority of the reference
+    pub priority: Priority,
+    /// Estimated tokens of the node's text, see [`estimated_tokens`]
+    pub tokens: usize,
+}
+
+/// Rough token count of a node's text, at about four characters per token
+///
+/// Prompts, responses and templates count their text; custom nodes count
+/// their JSON payload; other nodes count as empty.
+pub fn estimated_tokens(node: &Node) -> usize {
+    let chars = match node {
+        Node::Prompt(prompt) => prompt.content.chars().count(),
+        Node::Response(response) => response.content.chars().count(),
+        Node::Template(template) => template.template.chars().count(),
+        Node::Custom(custom) => custom.payload.to_string().chars().count(),
+        Node::Session(_) | Node::ToolInvocation(_) | Node::Agent(_) => 0,
+    };
+    chars.div_ceil(4)
+}
+
+/// Order references by priority, then relevance decayed with
+/// `half_life_ms`, newest first among equals, and apply the item and
+/// relevance limits of `options`
+pub(super) fn rank_references(
+    mut edges: Vec<Edge>,
+    options: &ContextOptions,
+    half_life_ms: u64,
+) -> Vec<(Edge, f32)> {
+    edges.retain(|edge| edge.edge_type == crate::EdgeType::References);
+    let now = Utc::now();
+    let mut ranked: Vec<(Edge, f32)> = edges
+        .into_iter()
+        .map(|edge| {
+            let relevance = edge.decayed_relevance(half_life_ms, now).unwrap_or(0.0);
+            (edge, relevance)
+        })
+        .filter(|(edge, relevance)| {
+            *relevance >= options.min_relevance || edge.priority() == Priority::Critical
+        })
+        .collect();
+    ranked.sort_by(|(a, ra), (b, rb)| {
+        b.priority()
+            .cmp(&a.priority())
+            .then_with(|| rb.total_cmp(ra))
+            .then_with(|| b.created_at.cmp(&a.created_at))
+    });
+    if let Some(max_items) = options.max_items {
+        ranked.truncate(max_items);
+    }
+    ranked
+}
+
+/// Drop ranked items that no longer fit in the token budget of `options`
+///
+/// Later items that fit in what remains are still kept, so one large
+/// document does not crowd out every smaller one after it.
+pub(super) fn apply_token_budget(
+    items: Vec<ContextItem>,
+    options: &ContextOptions,
+) -> Vec<ContextItem> {
+    let Some(budget) = options.token_budget else {
+        return items;
+    };
+    let mut used = 0;
+    items
+        .into_iter()
+        .filter(|item| {
+            let fits = used + item.tokens <= budget;
+            if fits || item.priority == Priority::Critical {
+                used += item.tokens;
+                true
+            } else {
+                false
+            }
+        })
+        .collect()
+}
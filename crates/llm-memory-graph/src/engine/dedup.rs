@@ -0,0 +1,205 @@
+//! Semantic near-duplicates among prompts and responses
+//!
+//! Exact repeats within a session are caught by
+//! [`Config::dedupe_prompts`](crate::Config). Long-lived agent memories also
+//! collect the same question or answer worded slightly differently across
+//! sessions; with embeddings stored by an [`Embedder`](super::Embedder), these
+//! show up as vectors with a cosine similarity close to one.
+//!
+//! [`find_near_duplicates`](super::AsyncMemoryGraph::find_near_duplicates)
+//! reports them for the whole graph. Setting
+//! [`EmbeddingOptions::near_duplicates`](super::EmbeddingOptions) also checks
+//! each new prompt and response once it is embedded, and flags it with a
+//! [`NEAR_DUPLICATE_PROPERTY`] or moves it to the trash.
+//!
+//! Only nodes of the same type whose vectors come from the same model are
+//! compared, and the newer node of a pair is always the duplicate.
+
+use crate::storage::NodeEmbedding;
+use crate::{Node, NodeId, NodeType};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Node property recording which node a near-duplicate repeats, as
+/// `{"node_id": ..., "similarity": ...}`
+pub const NEAR_DUPLICATE_PROPERTY: &str = "near_duplicate_of";
+
+/// A node whose embedding is nearly identical to that of an older node
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NearDuplicate {
+    /// The newer node
+    pub node_id: NodeId,
+    /// The older node it repeats, the most similar one if there are several
+    pub original_id: NodeId,
+    /// Type of both nodes
+    pub node_type: NodeType,
+    /// Cosine similarity of their embeddings
+    pub similarity: f32,
+}
+
+/// What the write-time check does with a near-duplicate
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NearDuplicateAction {
+    /// Set [`NEAR_DUPLICATE_PROPERTY`] on the new node
+    Flag,
+    /// Flag the new node and move it to the trash, leaving the original as
+    /// the only copy; [`restore`](super::AsyncMemoryGraph::restore) undoes it
+    Merge,
+}
+
+/// Write-time near-duplicate check, see
+/// [`EmbeddingOptions::near_duplicates`](super::EmbeddingOptions)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NearDuplicateCheck {
+    /// Lowest cosine similarity counted as a duplicate
+    pub threshold: f32,
+    /// What to do with a duplicate
+    pub action: NearDuplicateAction,
+}
+
+impl NearDuplicateCheck {
+    /// Flag new nodes at least `threshold` similar to an existing one
+    pub fn flag(threshold: f32) -> Self {
+        Self {
+            threshold,
+            action: NearDuplicateAction::Flag,
+        }
+    }
+
+    /// Merge new nodes at least `threshold` similar to an existing one into it
+    pub fn merge(threshold: f32) -> Self {
+        Self {
+            threshold,
+            action: NearDuplicateAction::Merge,
+        }
+    }
+}
+
+/// Cosine similarity of two vectors, 0 if either is zero or their lengths
+/// differ
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() {
+        return 0.0;
+    }
+    let (mut dot, mut norm_a, mut norm_b) = (0.0f32, 0.0f32, 0.0f32);
+    for (x, y) in a.iter().zip(b) {
+        dot += x * y;
+        norm_a += x * x;
+        norm_b += y * y;
+    }
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+    dot / (norm_a.sqrt() * norm_b.sqrt())
+}
+
+/// Every node in `nodes` with an embedding at least `threshold` similar to
+/// that of an older node, oldest duplicate first
+pub(super) fn near_duplicates(
+    nodes: &[Node],
+    embeddings: Vec<NodeEmbedding>,
+    threshold: f32,
+) -> Vec<NearDuplicate> {
+    let mut vectors: HashMap<NodeId, NodeEmbedding> = embeddings
+        .into_iter()
+        .map(|embedding| (embedding.node_id, embedding))
+        .collect();
+
+    let mut candidates: Vec<_> = nodes
+        .iter()
+        .filter_map(|node| {
+            let timestamp = match node {
+                Node::Prompt(prompt) => prompt.timestamp,
+                Node::Response(response) => response.timestamp,
+                _ => return None,
+            };
+            let embedding = vectors.remove(&node.id())?;
+            Some((timestamp, node.node_type(), embedding))
+        })
+        .collect();
+    candidates.sort_by_key(|(timestamp, _, embedding)| (*timestamp, embedding.node_id.to_bytes()));
+
+    let mut duplicates = Vec::new();
+    for (i, (_, node_type, embedding)) in candidates.iter().enumerate() {
+        let best = candidates[..i]
+            .iter()
+            .filter(|(_, other_type, other)| {
+                other_type == node_type && other.model == embedding.model
+            })
+            .map(|(_, _, other)| {
+                (
+                    other.node_id,
+                    cosine_similarity(&embedding.vector, &other.vector),
+                )
+            })
+            .filter(|(_, similarity)| *similarity >= threshold)
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+        if let Some((original_id, similarity)) = best {
+            duplicates.push(NearDuplicate {
+                node_id: embedding.node_id,
+                original_id,
+                node_type: node_type.clone(),
+                similarity,
+            });
+        }
+    }
+    duplicates
+}
+
+/// Embeddings of one model that new vectors are checked against
+#[derive(Default)]
+pub(super) struct DuplicateIndex {
+    entries: Vec<(NodeId, NodeType, Vec<f32>)>,
+}
+
+impl DuplicateIndex {
+    /// Index of the embeddings of `model` belonging to `nodes`
+    pub(super) fn new(model: &str, nodes: &[Node], embeddings: Vec<NodeEmbedding>) -> Self {
+        let types: HashMap<NodeId, NodeType> = nodes
+            .iter()
+            .map(|node| (node.id(), node.node_type()))
+            .collect();
+        let entries = embeddings
+            .into_iter()
+            .filter(|embedding| embedding.model == model)
+            .filter_map(|embedding| {
+                let node_type = types.get(&embedding.node_id)?.clone();
+                Some((embedding.node_id, node_type, embedding.vector))
+            })
+            .collect();
+        Self { entries }
+    }
+
+    /// Most similar indexed node of `node_type` at least `threshold`
+    /// similar to `vector`
+    pub(super) fn best_match(
+        &self,
+        node_type: &NodeType,
+        vector: &[f32],
+        threshold: f32,
+    ) -> Option<(NodeId, f32)> {
+        self.entries
+            .iter()
+            .filter(|(_, other_type, _)| other_type == node_type)
+            .map(|(node_id, _, other)| (*node_id, cosine_similarity(vector, other)))
+            .filter(|(_, similarity)| *similarity >= threshold)
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+    }
+
+    pub(super) fn insert(&mut self, node_id: NodeId, node_type: NodeType, vector: Vec<f32>) {
+        self.entries.push((node_id, node_type, vector));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cosine_similarity() {
+        assert!((cosine_similarity(&[1.0, 0.0], &[2.0, 0.0]) - 1.0).abs() < 1e-6);
+        assert!(cosine_similarity(&[1.0, 0.0], &[0.0, 1.0]).abs() < 1e-6);
+        assert_eq!(cosine_similarity(&[0.0, 0.0], &[1.0, 0.0]), 0.0);
+        assert_eq!(cosine_similarity(&[1.0], &[1.0, 0.0]), 0.0);
+    }
+}
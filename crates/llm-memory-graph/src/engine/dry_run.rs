@@ -0,0 +1,158 @@
+//! Previewing writes without making them
+//!
+//! A [`DryRunGraph`] is a full [`AsyncMemoryGraph`] whose storage is a
+//! [`DryRunBackend`] over another graph's storage: every engine operation
+//! works and sees its own writes, but they are kept in memory and recorded in
+//! a [`Changeset`] instead of being persisted. Agent orchestration logic can
+//! be tested against production data this way, and a UI can show what an
+//! operation would do before it is run for real.
+//!
+//! The graph dereferences to [`AsyncMemoryGraph`], so it is used exactly like
+//! one. Operations are audited into [`Changeset::operations`] whatever the
+//! configuration, and mutations are not logged through `tracing`. See the
+//! [storage module](crate::storage::DryRunBackend) for what a dry run cannot
+//! do.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use llm_memory_graph::engine::AsyncMemoryGraph;
+//! use llm_memory_graph::Config;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let config = Config::new("./data");
+//! let graph = AsyncMemoryGraph::open(config.clone()).await?;
+//!
+//! let preview = graph.dry_run(config);
+//! let session = preview.create_session().await?;
+//! preview
+//!     .add_prompt(session.id, "Plan the migration".to_string(), None)
+//!     .await?;
+//!
+//! let changeset = preview.changeset();
+//! println!("{} nodes would be created", changeset.created_nodes().len());
+//! assert!(graph.get_session(session.id).await.is_err());
+//! # Ok(())
+//! # }
+//! ```
+
+use super::AsyncMemoryGraph;
+use crate::storage::{AsyncStorageBackend, Changeset, DryRunBackend};
+use crate::{Config, LogLevel};
+use std::ops::Deref;
+use std::sync::Arc;
+
+/// Graph that records writes instead of making them, see the
+/// [module docs](self)
+pub struct DryRunGraph {
+    graph: AsyncMemoryGraph,
+    storage: Arc<DryRunBackend>,
+}
+
+impl DryRunGraph {
+    /// Run a dry run over `base` with the settings in `config`
+    ///
+    /// As with [`AsyncMemoryGraph::open_with_backend`], the path, namespace
+    /// and serialization format in `config` are ignored. Read-only mode and
+    /// mutation logging are turned off, and the audit log on.
+    pub fn new(base: Arc<dyn AsyncStorageBackend>, mut config: Config) -> Self {
+        config.read_only = false;
+        config.audit_log = true;
+        config.log_level = LogLevel::Off;
+        let storage = Arc::new(DryRunBackend::new(base));
+        let graph = AsyncMemoryGraph::open_with_backend(
+            config,
+            Arc::clone(&storage) as Arc<dyn AsyncStorageBackend>,
+        );
+        Self { graph, storage }
+    }
+
+    /// The graph writes are previewed in
+    pub fn graph(&self) -> &AsyncMemoryGraph {
+        &self.graph
+    }
+
+    /// Writes made so far
+    pub fn changeset(&self) -> Changeset {
+        self.storage.changeset()
+    }
+
+    /// Writes made so far, clearing the record while the graph keeps seeing
+    /// them
+    pub fn take_changeset(&self) -> Changeset {
+        self.storage.take_changeset()
+    }
+
+    /// End the dry run, returning its writes
+    pub fn into_changeset(self) -> Changeset {
+        self.storage.take_changeset()
+    }
+}
+
+impl Deref for DryRunGraph {
+    type Target = AsyncMemoryGraph;
+
+    fn deref(&self) -> &AsyncMemoryGraph {
+        &self.graph
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::Mutation;
+    use crate::{EdgeType, Node, TokenUsage};
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_dry_run_records_without_writing() {
+        let dir = tempdir().unwrap();
+        let config = Config::new(dir.path());
+        let graph = AsyncMemoryGraph::open(config.clone()).await.unwrap();
+        let session = graph.create_session().await.unwrap();
+        let existing = graph
+            .add_prompt(session.id, "Existing".to_string(), None)
+            .await
+            .unwrap();
+
+        let preview = graph.dry_run(config);
+        let prompt = preview
+            .add_prompt(session.id, "Preview".to_string(), None)
+            .await
+            .unwrap();
+        let response = preview
+            .add_response(prompt, "Answer".to_string(), TokenUsage::new(1, 2), None)
+            .await
+            .unwrap();
+        preview.set_alias("preview", prompt).await.unwrap();
+        preview.delete_node(existing).await.unwrap();
+
+        // The dry run sees its own writes merged with the graph
+        let nodes = preview.get_session_nodes(&session.id).await.unwrap();
+        let ids: Vec<_> = nodes.iter().map(Node::id).collect();
+        assert!(ids.contains(&prompt) && ids.contains(&response));
+        assert!(!ids.contains(&existing));
+        let incoming = preview.get_incoming_edges(&prompt).await.unwrap();
+        assert!(incoming
+            .iter()
+            .any(|edge| edge.from == response && edge.edge_type == EdgeType::RespondsTo));
+
+        // The graph does not
+        assert!(graph.get_node(&prompt).await.unwrap().is_none());
+        assert!(graph.get_node(&existing).await.unwrap().is_some());
+        assert!(graph.resolve_alias("preview").await.unwrap().is_none());
+
+        let changeset = preview.take_changeset();
+        let created: Vec<_> = changeset.created_nodes().iter().map(|n| n.id()).collect();
+        assert_eq!(created, vec![prompt, response]);
+        assert!(changeset.created_edges().len() >= 2);
+        assert!(changeset.deleted_nodes().contains(&existing));
+        assert!(changeset
+            .mutations
+            .iter()
+            .any(|m| matches!(m, Mutation::SetAlias { alias, .. } if alias == "preview")));
+        assert!(!changeset.operations.is_empty());
+        assert!(preview.changeset().is_empty());
+        assert!(preview.get_node(&prompt).await.unwrap().is_some());
+    }
+}
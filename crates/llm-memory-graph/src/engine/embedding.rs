@@ -0,0 +1,584 @@
+//! Embeddings computed on write
+//!
+//! With an [`Embedder`] installed through
+//! [`AsyncMemoryGraph::set_embedder`](super::AsyncMemoryGraph::set_embedder),
+//! every prompt and response is queued for embedding once it is stored. A
+//! single background task takes texts off the queue, calls the embedder,
+//! retrying failed calls with exponential backoff, and stores the vector next
+//! to the node. Writes only wait for the embedder when the queue is full.
+//!
+//! [`HttpEmbedder`] calls an OpenAI-compatible `/embeddings` endpoint;
+//! [`HashEmbedder`] computes hashed bag-of-words vectors locally and needs no
+//! model. Any other model is plugged in by implementing [`Embedder`].
+//!
+//! Nodes stored before an embedder was installed, or embedded by a different
+//! model, are queued by
+//! [`backfill_embeddings`](super::AsyncMemoryGraph::backfill_embeddings).
+//!
+//! With [`EmbeddingOptions::near_duplicates`] set, each newly embedded node is
+//! also checked against the stored embeddings for near-duplicates, see
+//! [`NearDuplicateCheck`].
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use llm_memory_graph::engine::{AsyncMemoryGraph, HttpEmbedder};
+//! use llm_memory_graph::Config;
+//! use std::sync::Arc;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let graph = AsyncMemoryGraph::open(Config::default()).await?;
+//! let embedder = HttpEmbedder::new("http://localhost:11434/v1", "nomic-embed-text")?;
+//! graph.set_embedder(Some(Arc::new(embedder)));
+//!
+//! let session = graph.create_session().await?;
+//! let prompt_id = graph.add_prompt(session.id, "What is Rust?".to_string(), None).await?;
+//!
+//! graph.wait_for_embeddings().await;
+//! let embedding = graph.get_embedding(&prompt_id).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use super::async_memory_graph::PromptHashIndex;
+use super::dedup::{
+    DuplicateIndex, NearDuplicateAction, NearDuplicateCheck, NEAR_DUPLICATE_PROPERTY,
+};
+use crate::audit::{AuditEntry, AuditOperation};
+use crate::storage::{AsyncStorageBackend, NodeEmbedding, StorageCache};
+use crate::{Error, Node, NodeId, NodeType, Result};
+use async_trait::async_trait;
+use chrono::Utc;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot, watch, RwLock};
+
+/// Default number of texts waiting for the embedder before writes block
+pub const DEFAULT_EMBEDDING_QUEUE_CAPACITY: usize = 1_024;
+
+/// Computes embedding vectors for node text
+#[async_trait]
+pub trait Embedder: Send + Sync {
+    /// Name of the model, stored with every vector it produces
+    fn model(&self) -> &str;
+
+    /// Embedding of `text`
+    async fn embed(&self, text: &str) -> Result<Vec<f32>>;
+}
+
+/// How the embedding queue runs
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EmbeddingOptions {
+    /// Texts waiting for the embedder before writes block
+    pub queue_capacity: usize,
+    /// Calls made for one text before it is given up on
+    pub max_attempts: u32,
+    /// Delay before the first retry, doubled for each further retry
+    pub retry_backoff: Duration,
+    /// Check each newly embedded node for near-duplicates, off by default
+    pub near_duplicates: Option<NearDuplicateCheck>,
+}
+
+impl Default for EmbeddingOptions {
+    fn default() -> Self {
+        Self {
+            queue_capacity: DEFAULT_EMBEDDING_QUEUE_CAPACITY,
+            max_attempts: 3,
+            retry_backoff: Duration::from_millis(200),
+            near_duplicates: None,
+        }
+    }
+}
+
+/// Counts of the embedding queue's work since the embedder was installed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EmbeddingStats {
+    /// Texts queued
+    pub queued: u64,
+    /// Vectors stored
+    pub embedded: u64,
+    /// Texts given up on after every attempt failed
+    pub failed: u64,
+    /// Embedder calls repeated after a failure
+    pub retries: u64,
+    /// New nodes found to be near-duplicates of existing ones
+    pub near_duplicates: u64,
+}
+
+#[derive(Default)]
+struct EmbeddingCounters {
+    queued: AtomicU64,
+    embedded: AtomicU64,
+    failed: AtomicU64,
+    retries: AtomicU64,
+    near_duplicates: AtomicU64,
+}
+
+/// Graph state the embedding task writes to
+pub(super) struct EmbeddingSink {
+    pub(super) backend: Arc<dyn AsyncStorageBackend>,
+    pub(super) cache: StorageCache,
+    pub(super) prompt_hashes: Arc<RwLock<PromptHashIndex>>,
+    pub(super) audit_log: bool,
+}
+
+enum Job {
+    Embed {
+        node_id: NodeId,
+        node_type: NodeType,
+        text: String,
+    },
+    Barrier(oneshot::Sender<()>),
+}
+
+/// Text of a node that gets embedded, for prompts and responses
+pub(super) fn embedding_text(node: &Node) -> Option<&str> {
+    match node {
+        Node::Prompt(prompt) => Some(&prompt.content),
+        Node::Response(response) => Some(&response.content),
+        _ => None,
+    }
+}
+
+/// Sending side of a running embedding queue
+#[derive(Clone)]
+pub(super) struct EmbeddingQueue {
+    jobs: mpsc::Sender<Job>,
+    model: String,
+    counters: Arc<EmbeddingCounters>,
+}
+
+impl EmbeddingQueue {
+    /// Spawn the task embedding queued texts into the sink's backend
+    ///
+    /// The task holds `closing` until it exits; once it changes, texts
+    /// already queued are embedded and the task stops.
+    pub(super) fn start(
+        embedder: Arc<dyn Embedder>,
+        options: EmbeddingOptions,
+        sink: EmbeddingSink,
+        mut closing: watch::Receiver<bool>,
+    ) -> Self {
+        let (jobs, mut receiver) = mpsc::channel(options.queue_capacity.max(1));
+        let counters = Arc::new(EmbeddingCounters::default());
+        let queue = Self {
+            jobs,
+            model: embedder.model().to_string(),
+            counters: Arc::clone(&counters),
+        };
+
+        tokio::spawn(async move {
+            let mut worker = Worker {
+                embedder,
+                options,
+                sink,
+                counters,
+                duplicates: None,
+            };
+            loop {
+                tokio::select! {
+                    job = receiver.recv() => match job {
+                        Some(job) => worker.run(job).await,
+                        None => break,
+                    },
+                    _ = closing.changed() => {
+                        receiver.close();
+                        while let Some(job) = receiver.recv().await {
+                            worker.run(job).await;
+                        }
+                        break;
+                    }
+                }
+            }
+        });
+
+        queue
+    }
+
+    /// Model of the installed embedder
+    pub(super) fn model(&self) -> &str {
+        &self.model
+    }
+
+    /// Queue `text` for embedding, waiting while the queue is full
+    pub(super) async fn push(&self, node_id: NodeId, node_type: NodeType, text: String) {
+        let job = Job::Embed {
+            node_id,
+            node_type,
+            text,
+        };
+        if self.jobs.send(job).await.is_ok() {
+            self.counters.queued.fetch_add(1, Ordering::Relaxed);
+        } else {
+            tracing::debug!("Embedding queue stopped; node {} not embedded", node_id);
+        }
+    }
+
+    /// Wait until every text queued so far has been embedded or given up on
+    pub(super) async fn drain(&self) {
+        let (done, finished) = oneshot::channel();
+        if self.jobs.send(Job::Barrier(done)).await.is_ok() {
+            let _ = finished.await;
+        }
+    }
+
+    pub(super) fn stats(&self) -> EmbeddingStats {
+        EmbeddingStats {
+            queued: self.counters.queued.load(Ordering::Relaxed),
+            embedded: self.counters.embedded.load(Ordering::Relaxed),
+            failed: self.counters.failed.load(Ordering::Relaxed),
+            retries: self.counters.retries.load(Ordering::Relaxed),
+            near_duplicates: self.counters.near_duplicates.load(Ordering::Relaxed),
+        }
+    }
+}
+
+struct Worker {
+    embedder: Arc<dyn Embedder>,
+    options: EmbeddingOptions,
+    sink: EmbeddingSink,
+    counters: Arc<EmbeddingCounters>,
+    /// Embeddings checked for near-duplicates, loaded on first use
+    duplicates: Option<DuplicateIndex>,
+}
+
+impl Worker {
+    async fn run(&mut self, job: Job) {
+        match job {
+            Job::Embed {
+                node_id,
+                node_type,
+                text,
+            } => {
+                if let Some(vector) = self.embed(node_id, &text).await {
+                    if let Some(check) = self.options.near_duplicates {
+                        if let Err(e) = self
+                            .check_duplicate(check, node_id, node_type, vector)
+                            .await
+                        {
+                            tracing::warn!(
+                                "Near-duplicate check of node {} failed: {}",
+                                node_id,
+                                e
+                            );
+                        }
+                    }
+                }
+            }
+            Job::Barrier(done) => {
+                let _ = done.send(());
+            }
+        }
+    }
+
+    /// Embed and store `text`, returning the vector once stored
+    async fn embed(&self, node_id: NodeId, text: &str) -> Option<Vec<f32>> {
+        let mut backoff = self.options.retry_backoff;
+        let mut attempt = 1;
+        loop {
+            match self.embed_once(node_id, text).await {
+                Ok(vector) => {
+                    self.counters.embedded.fetch_add(1, Ordering::Relaxed);
+                    return Some(vector);
+                }
+                Err(e) if attempt >= self.options.max_attempts => {
+                    self.counters.failed.fetch_add(1, Ordering::Relaxed);
+                    tracing::warn!(
+                        "Giving up embedding node {} after {} attempts: {}",
+                        node_id,
+                        attempt,
+                        e
+                    );
+                    return None;
+                }
+                Err(e) => {
+                    tracing::debug!("Embedding node {} failed, retrying: {}", node_id, e);
+                    self.counters.retries.fetch_add(1, Ordering::Relaxed);
+                    tokio::time::sleep(backoff).await;
+                    backoff = backoff.saturating_mul(2);
+                    attempt += 1;
+                }
+            }
+        }
+    }
+
+    async fn embed_once(&self, node_id: NodeId, text: &str) -> Result<Vec<f32>> {
+        let vector = self.embedder.embed(text).await?;
+        self.sink
+            .backend
+            .store_embedding(&NodeEmbedding {
+                node_id,
+                model: self.embedder.model().to_string(),
+                vector: vector.clone(),
+                created_at: Utc::now(),
+            })
+            .await?;
+        Ok(vector)
+    }
+
+    /// Flag or merge a newly embedded node if an older node of the same type
+    /// is nearly identical
+    async fn check_duplicate(
+        &mut self,
+        check: NearDuplicateCheck,
+        node_id: NodeId,
+        node_type: NodeType,
+        vector: Vec<f32>,
+    ) -> Result<()> {
+        if self.duplicates.is_none() {
+            let nodes = self.sink.backend.all_nodes().await?;
+            let mut embeddings = self.sink.backend.all_embeddings().await?;
+            embeddings.retain(|embedding| embedding.node_id != node_id);
+            self.duplicates = Some(DuplicateIndex::new(
+                self.embedder.model(),
+                &nodes,
+                embeddings,
+            ));
+        }
+        let index = self.duplicates.get_or_insert_with(DuplicateIndex::default);
+        let best = index.best_match(&node_type, &vector, check.threshold);
+        index.insert(node_id, node_type, vector);
+        let Some((original_id, similarity)) = best else {
+            return Ok(());
+        };
+
+        let backend = &self.sink.backend;
+        // The original may have been deleted since it was indexed
+        if backend.get_node(&original_id).await?.is_none() {
+            return Ok(());
+        }
+        let Some(mut node) = backend.get_node(&node_id).await? else {
+            return Ok(());
+        };
+        self.counters
+            .near_duplicates
+            .fetch_add(1, Ordering::Relaxed);
+
+        node.properties_mut().insert(
+            NEAR_DUPLICATE_PROPERTY.to_string(),
+            serde_json::json!({ "node_id": original_id, "similarity": similarity }),
+        );
+        backend.store_node(&node).await?;
+        self.sink.cache.invalidate_node(&node_id).await;
+        self.record_audit(
+            AuditEntry::new(AuditOperation::SetProperty, None)
+                .with_node(node_id)
+                .with_detail("key", NEAR_DUPLICATE_PROPERTY),
+        )
+        .await?;
+
+        if check.action == NearDuplicateAction::Merge
+            && backend.trash_node(&node_id).await?.is_some()
+        {
+            if let Node::Prompt(prompt) = &node {
+                self.sink
+                    .prompt_hashes
+                    .write()
+                    .await
+                    .remove(&prompt.session_id);
+            }
+            self.record_audit(
+                AuditEntry::new(AuditOperation::DeleteNode, None)
+                    .with_node(node_id)
+                    .with_detail(NEAR_DUPLICATE_PROPERTY, original_id),
+            )
+            .await?;
+        }
+        Ok(())
+    }
+
+    async fn record_audit(&self, entry: AuditEntry) -> Result<()> {
+        if self.sink.audit_log {
+            self.sink.backend.append_audit_entry(entry).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Embedder calling an OpenAI-compatible embeddings API
+///
+/// Posts `{"model": ..., "input": ...}` to `{base_url}/embeddings` and reads
+/// the first vector of the response, which works with OpenAI, Azure OpenAI,
+/// Ollama, vLLM and most self-hosted embedding servers.
+pub struct HttpEmbedder {
+    client: Client,
+    url: String,
+    model: String,
+    api_key: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct EmbeddingsResponse {
+    data: Vec<EmbeddingsItem>,
+}
+
+#[derive(Deserialize)]
+struct EmbeddingsItem {
+    embedding: Vec<f32>,
+}
+
+impl HttpEmbedder {
+    /// Embedder using `model` at the API rooted at `base_url`, such as
+    /// `https://api.openai.com/v1`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the HTTP client cannot be created.
+    pub fn new(base_url: impl Into<String>, model: impl Into<String>) -> Result<Self> {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(30))
+            .connect_timeout(Duration::from_secs(10))
+            .build()
+            .map_err(|e| Error::IntegrationError(e.to_string()))?;
+        let base_url = base_url.into();
+        Ok(Self {
+            client,
+            url: format!("{}/embeddings", base_url.trim_end_matches('/')),
+            model: model.into(),
+            api_key: None,
+        })
+    }
+
+    /// Send `key` as a bearer token
+    pub fn with_api_key(mut self, key: impl Into<String>) -> Self {
+        self.api_key = Some(key.into());
+        self
+    }
+}
+
+#[async_trait]
+impl Embedder for HttpEmbedder {
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    async fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        let mut request = self.client.post(&self.url).json(&serde_json::json!({
+            "model": self.model,
+            "input": text,
+        }));
+        if let Some(key) = &self.api_key {
+            request = request.bearer_auth(key);
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| Error::IntegrationError(format!("embedding request failed: {e}")))?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::IntegrationError(format!(
+                "embedding API returned {status}: {body}"
+            )));
+        }
+
+        let body: EmbeddingsResponse = response
+            .json()
+            .await
+            .map_err(|e| Error::IntegrationError(format!("invalid embedding response: {e}")))?;
+        body.data
+            .into_iter()
+            .next()
+            .map(|item| item.embedding)
+            .ok_or_else(|| Error::IntegrationError("embedding response was empty".to_string()))
+    }
+}
+
+/// Default number of dimensions of [`HashEmbedder`] vectors
+pub const DEFAULT_HASH_DIMENSIONS: usize = 256;
+
+/// Local embedder hashing lowercased words into a fixed number of buckets
+///
+/// Vectors are normalized to unit length, so texts sharing most of their
+/// words have a cosine similarity close to one. It knows nothing about
+/// meaning, but needs no model and produces the same vectors on every
+/// platform, which makes it useful for tests and for spotting near-verbatim
+/// repeats.
+#[derive(Debug, Clone)]
+pub struct HashEmbedder {
+    dimensions: usize,
+    model: String,
+}
+
+impl HashEmbedder {
+    /// Embedder producing [`DEFAULT_HASH_DIMENSIONS`]-dimensional vectors
+    pub fn new() -> Self {
+        Self::with_dimensions(DEFAULT_HASH_DIMENSIONS)
+    }
+
+    /// Embedder producing vectors of `dimensions` components
+    pub fn with_dimensions(dimensions: usize) -> Self {
+        let dimensions = dimensions.max(1);
+        Self {
+            dimensions,
+            model: format!("hash-{dimensions}"),
+        }
+    }
+
+    /// Embedding of `text`, computed synchronously
+    pub fn vector(&self, text: &str) -> Vec<f32> {
+        let mut vector = vec![0.0f32; self.dimensions];
+        for word in text
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|word| !word.is_empty())
+        {
+            // FNV-1a, stable across platforms and releases
+            let hash = word
+                .to_lowercase()
+                .bytes()
+                .fold(0xcbf2_9ce4_8422_2325u64, |hash, byte| {
+                    (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+                });
+            vector[(hash % self.dimensions as u64) as usize] += 1.0;
+        }
+
+        let norm = vector.iter().map(|x| x * x).sum::<f32>().sqrt();
+        if norm > 0.0 {
+            for x in &mut vector {
+                *x /= norm;
+            }
+        }
+        vector
+    }
+}
+
+impl Default for HashEmbedder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl Embedder for HashEmbedder {
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    async fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        Ok(self.vector(text))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash_embedder() {
+        let embedder = HashEmbedder::with_dimensions(64);
+        assert_eq!(embedder.model(), "hash-64");
+
+        let a = embedder.vector("The quick brown fox");
+        let b = embedder.vector("the QUICK, brown fox!");
+        assert_eq!(a, b);
+        assert_eq!(a.len(), 64);
+        let norm: f32 = a.iter().map(|x| x * x).sum();
+        assert!((norm - 1.0).abs() < 1e-5);
+
+        assert!(embedder.vector("").iter().all(|x| *x == 0.0));
+    }
+}
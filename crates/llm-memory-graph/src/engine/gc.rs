@@ -0,0 +1,178 @@
+//! Garbage collection of orphaned nodes and dangling edges
+//!
+//! Hard deletes and purged trash can leave records behind: tool invocations
+//! whose response is gone, responses without a prompt, and edges pointing at
+//! nodes that no longer exist. [`AsyncMemoryGraph::gc`](super::AsyncMemoryGraph::gc)
+//! finds and removes them; the maintenance scheduler runs it as
+//! [`MaintenanceTask::CollectGarbage`](super::MaintenanceTask::CollectGarbage).
+//!
+//! A node is orphaned when the chain of owners leading to its session is
+//! broken: a prompt or session-scoped custom node whose session is missing, a
+//! response whose prompt is missing or orphaned, or a tool invocation whose
+//! response is. Agents, templates and unscoped custom nodes stand on their own
+//! and are never collected. Nodes in the trash count as present, so nothing
+//! that [`restore`](super::AsyncMemoryGraph::restore) could bring back is
+//! collected until the trash is purged.
+
+use crate::{Edge, EdgeId, Node, NodeId, SessionId};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// What a garbage collection found, and whether it was removed
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GcReport {
+    /// Nodes whose owner is missing
+    pub orphaned_nodes: Vec<NodeId>,
+    /// Edges with a missing endpoint, including edges of orphaned nodes
+    pub dangling_edges: Vec<EdgeId>,
+    /// Whether the run only reported, leaving the graph unchanged
+    pub dry_run: bool,
+}
+
+impl GcReport {
+    /// Whether the graph had nothing to collect
+    pub fn is_clean(&self) -> bool {
+        self.orphaned_nodes.is_empty() && self.dangling_edges.is_empty()
+    }
+
+    /// Number of nodes and edges found
+    pub fn total(&self) -> usize {
+        self.orphaned_nodes.len() + self.dangling_edges.len()
+    }
+}
+
+/// Find the orphaned nodes among `nodes` and the dangling edges among
+/// `edges`; `trashed` are nodes in the trash, which count as present but are
+/// never collected themselves, and `versions` are the IDs of earlier versions
+/// of edited nodes, which count as present too
+pub(crate) fn find_garbage(
+    nodes: &[Node],
+    trashed: &[Node],
+    versions: &[NodeId],
+    edges: &[Edge],
+) -> GcReport {
+    let sessions: HashSet<SessionId> = nodes
+        .iter()
+        .chain(trashed)
+        .filter_map(|node| match node {
+            Node::Session(session) => Some(session.id),
+            _ => None,
+        })
+        .collect();
+    let trashed_ids: HashSet<NodeId> = trashed.iter().map(Node::id).collect();
+
+    // Each level is resolved before the next, so a response sees whether its
+    // prompt was orphaned
+    let mut orphaned = HashSet::new();
+    let prompts = resolve_level(nodes, &trashed_ids, &mut orphaned, |node| match node {
+        Node::Prompt(prompt) => Some(sessions.contains(&prompt.session_id)),
+        Node::Custom(custom) => custom.session_id.map(|id| sessions.contains(&id)),
+        _ => None,
+    });
+    let responses = resolve_level(nodes, &trashed_ids, &mut orphaned, |node| match node {
+        Node::Response(response) => Some(prompts.contains(&response.prompt_id)),
+        _ => None,
+    });
+    resolve_level(nodes, &trashed_ids, &mut orphaned, |node| match node {
+        Node::ToolInvocation(tool) => Some(responses.contains(&tool.response_id)),
+        _ => None,
+    });
+
+    let present: HashSet<NodeId> = nodes
+        .iter()
+        .map(Node::id)
+        .filter(|id| !orphaned.contains(id))
+        .chain(trashed_ids.iter().copied())
+        .chain(versions.iter().copied())
+        .collect();
+    let dangling_edges = edges
+        .iter()
+        .filter(|edge| !present.contains(&edge.from) || !present.contains(&edge.to))
+        .map(|edge| edge.id)
+        .collect();
+    let orphaned_nodes = nodes
+        .iter()
+        .map(Node::id)
+        .filter(|id| orphaned.contains(id))
+        .collect();
+
+    GcReport {
+        orphaned_nodes,
+        dangling_edges,
+        dry_run: false,
+    }
+}
+
+/// Sort the nodes `owner_present` applies to into orphaned and alive;
+/// returns the alive ones, plus `trashed`, as candidate owners for the next
+/// level
+fn resolve_level(
+    nodes: &[Node],
+    trashed: &HashSet<NodeId>,
+    orphaned: &mut HashSet<NodeId>,
+    owner_present: impl Fn(&Node) -> Option<bool>,
+) -> HashSet<NodeId> {
+    let mut alive = trashed.clone();
+    for node in nodes {
+        match owner_present(node) {
+            Some(true) => {
+                alive.insert(node.id());
+            }
+            Some(false) => {
+                orphaned.insert(node.id());
+            }
+            None => {}
+        }
+    }
+    alive
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        AgentNode, ConversationSession, EdgeType, PromptNode, ResponseNode, TokenUsage,
+        ToolInvocation,
+    };
+
+    #[test]
+    fn test_find_garbage() {
+        let session = ConversationSession::new();
+        let prompt = PromptNode::new(session.id, "Hi".to_string());
+        let response = ResponseNode::new(prompt.id, "Hello".to_string(), TokenUsage::new(1, 1));
+        let tool = ToolInvocation::new(response.id, "t".to_string(), serde_json::json!({}));
+        let lost_prompt = PromptNode::new(SessionId::new(), "Lost".to_string());
+        let lost_response =
+            ResponseNode::new(lost_prompt.id, "Lost".to_string(), TokenUsage::new(1, 1));
+        let lost_tool =
+            ToolInvocation::new(lost_response.id, "t".to_string(), serde_json::json!({}));
+        let agent = AgentNode::new("a".to_string(), "r".to_string(), Vec::new());
+
+        let nodes = vec![
+            Node::Session(session.clone()),
+            Node::Prompt(prompt.clone()),
+            Node::ToolInvocation(tool.clone()),
+            Node::Prompt(lost_prompt.clone()),
+            Node::Response(lost_response.clone()),
+            Node::ToolInvocation(lost_tool.clone()),
+            Node::Agent(agent.clone()),
+        ];
+        // The tool's response is in the trash and may come back
+        let trashed = vec![Node::Response(response.clone())];
+        let kept = Edge::new(prompt.id, session.node_id, EdgeType::PartOf);
+        let to_trash = Edge::new(response.id, tool.id, EdgeType::Invokes);
+        let of_orphan = Edge::new(lost_response.id, lost_prompt.id, EdgeType::RespondsTo);
+        let to_missing = Edge::new(agent.node_id, NodeId::new(), EdgeType::HandledBy);
+        let edges = vec![kept, to_trash, of_orphan.clone(), to_missing.clone()];
+
+        let report = find_garbage(&nodes, &trashed, &[], &edges);
+        assert_eq!(
+            report.orphaned_nodes,
+            vec![lost_prompt.id, lost_response.id, lost_tool.id]
+        );
+        assert_eq!(report.dangling_edges, vec![of_orphan.id, to_missing.id]);
+        assert_eq!(report.total(), 5);
+
+        assert!(find_garbage(&nodes[..3], &trashed, &[], &edges[..2]).is_clean());
+    }
+}
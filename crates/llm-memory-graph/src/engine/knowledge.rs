@@ -0,0 +1,368 @@
+//! Entities and facts mined from responses
+//!
+//! An [`Extractor`] reads a response and names the entities it mentions
+//! (customers, products, people, ...) and the facts it states about them.
+//! [`AsyncMemoryGraph::extract_knowledge`](super::AsyncMemoryGraph::extract_knowledge)
+//! stores them as custom nodes of type [`ENTITY_TYPE`] and [`FACT_TYPE`]
+//! linked by [`EdgeType::MentionedIn`](crate::EdgeType::MentionedIn) edges:
+//!
+//! - entity → response, for every response mentioning the entity
+//! - fact → response, for the response stating the fact
+//! - entity → fact, for every entity the fact is about
+//!
+//! Entities are shared across the graph: one node per kind and normalized
+//! name, found again through an alias under [`ENTITY_ALIAS_PREFIX`].
+//! [`knowledge_about`](super::AsyncMemoryGraph::knowledge_about) then answers
+//! "everything we know about customer X" by following those edges.
+//!
+//! Facts can also be asserted directly as a subject, predicate and value with
+//! [`assert_fact`](super::AsyncMemoryGraph::assert_fact). Asserting a new
+//! value for a subject and predicate supersedes the current fact: the new fact
+//! is linked to it by a [`EdgeType::Supersedes`](crate::EdgeType::Supersedes)
+//! edge and the old one gets a [`VALID_UNTIL_PROPERTY`]. Recall returns only
+//! facts still valid, while
+//! [`fact_history`](super::AsyncMemoryGraph::fact_history) keeps every value.
+//!
+//! [`PatternExtractor`] finds entities with regular expressions and needs no
+//! model; anything smarter, such as an LLM prompted for JSON, is plugged in by
+//! implementing [`Extractor`].
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use llm_memory_graph::engine::{AsyncMemoryGraph, PatternExtractor};
+//! use llm_memory_graph::{Config, TokenUsage};
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let graph = AsyncMemoryGraph::open(Config::default()).await?;
+//! let session = graph.create_session().await?;
+//! let prompt_id = graph.add_prompt(session.id, "Who is late?".to_string(), None).await?;
+//! let response_id = graph
+//!     .add_response(prompt_id, "CUST-42 has not paid invoice 7.".to_string(), TokenUsage::new(5, 8), None)
+//!     .await?;
+//!
+//! let extractor = PatternExtractor::new().with_pattern("customer", r"CUST-\d+")?;
+//! graph.extract_knowledge(&extractor, response_id).await?;
+//!
+//! for known in graph.knowledge_about("cust-42").await? {
+//!     println!("{} facts about {}", known.facts.len(), known.entity.name);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{CustomNode, Error, NodeId, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// Custom type name of entity nodes
+pub const ENTITY_TYPE: &str = "entity";
+
+/// Custom type name of fact nodes
+pub const FACT_TYPE: &str = "fact";
+
+/// Prefix of the aliases pointing at entity nodes, followed by
+/// `<kind>:<normalized name>`
+pub const ENTITY_ALIAS_PREFIX: &str = "entity:";
+
+/// Prefix of the aliases pointing at the current fact for a subject and
+/// predicate, followed by `<subject>:<predicate>`
+pub const FACT_ALIAS_PREFIX: &str = "fact:";
+
+/// Fact property holding when a superseded fact stopped being valid
+pub const VALID_UNTIL_PROPERTY: &str = "valid_until";
+
+/// An entity named by an extractor
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExtractedEntity {
+    /// Name as it appears in the text
+    pub name: String,
+    /// What the entity is, e.g. `customer`
+    pub kind: String,
+}
+
+impl ExtractedEntity {
+    /// An entity of `kind` called `name`
+    pub fn new(name: impl Into<String>, kind: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            kind: kind.into(),
+        }
+    }
+}
+
+/// A fact stated by a text
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExtractedFact {
+    /// The fact in words
+    pub statement: String,
+    /// Names of the entities, from the same extraction, the fact is about
+    pub entities: Vec<String>,
+}
+
+/// Everything an extractor found in one text
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Extraction {
+    /// Entities mentioned
+    pub entities: Vec<ExtractedEntity>,
+    /// Facts stated
+    pub facts: Vec<ExtractedFact>,
+}
+
+/// Finds entities and facts in response text
+#[async_trait]
+pub trait Extractor: Send + Sync {
+    /// Entities and facts in `text`
+    async fn extract(&self, text: &str) -> Result<Extraction>;
+}
+
+/// Extractor matching entity names with regular expressions
+///
+/// Every match of a kind's pattern is an entity of that kind, and every
+/// sentence mentioning at least one entity is a fact about them.
+#[derive(Debug, Clone, Default)]
+pub struct PatternExtractor {
+    patterns: Vec<(String, Regex)>,
+}
+
+impl PatternExtractor {
+    /// An extractor without patterns, which finds nothing
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Treat matches of `pattern` as entities of `kind`
+    ///
+    /// # Errors
+    ///
+    /// Returns a validation error if `pattern` is not a valid regular
+    /// expression.
+    pub fn with_pattern(mut self, kind: impl Into<String>, pattern: &str) -> Result<Self> {
+        let regex = Regex::new(pattern).map_err(|e| {
+            Error::ValidationError(format!("invalid entity pattern {pattern:?}: {e}"))
+        })?;
+        self.patterns.push((kind.into(), regex));
+        Ok(self)
+    }
+
+    fn entities_in(&self, text: &str) -> Vec<ExtractedEntity> {
+        let mut entities: Vec<ExtractedEntity> = Vec::new();
+        for (kind, regex) in &self.patterns {
+            for found in regex.find_iter(text) {
+                let entity = ExtractedEntity::new(found.as_str().trim(), kind.clone());
+                let seen = entities.iter().any(|other| {
+                    other.kind == entity.kind
+                        && normalize_name(&other.name) == normalize_name(&entity.name)
+                });
+                if !entity.name.is_empty() && !seen {
+                    entities.push(entity);
+                }
+            }
+        }
+        entities
+    }
+}
+
+#[async_trait]
+impl Extractor for PatternExtractor {
+    async fn extract(&self, text: &str) -> Result<Extraction> {
+        let entities = self.entities_in(text);
+        let facts = sentences(text)
+            .filter_map(|sentence| {
+                let mentioned: Vec<String> = self
+                    .entities_in(sentence)
+                    .into_iter()
+                    .map(|entity| entity.name)
+                    .collect();
+                (!mentioned.is_empty()).then(|| ExtractedFact {
+                    statement: sentence.to_string(),
+                    entities: mentioned,
+                })
+            })
+            .collect();
+        Ok(Extraction { entities, facts })
+    }
+}
+
+/// Non-empty sentences of `text`, split after `.`, `!`, `?` and at line ends
+fn sentences(text: &str) -> impl Iterator<Item = &str> {
+    text.split_inclusive(['.', '!', '?', '\n'])
+        .map(str::trim)
+        .filter(|sentence| sentence.chars().any(char::is_alphanumeric))
+}
+
+/// An entity node
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Entity {
+    /// The entity's node
+    pub node_id: NodeId,
+    /// Name as first extracted
+    pub name: String,
+    /// What the entity is
+    pub kind: String,
+}
+
+/// A fact node
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Fact {
+    /// The fact's node
+    pub node_id: NodeId,
+    /// The fact in words
+    pub statement: String,
+    /// Response the fact was extracted from, if it was extracted
+    pub source: Option<NodeId>,
+    /// Subject of an asserted fact
+    pub subject: Option<String>,
+    /// Predicate of an asserted fact
+    pub predicate: Option<String>,
+    /// Value of an asserted fact
+    pub value: Option<String>,
+    /// When the fact became valid
+    pub valid_from: DateTime<Utc>,
+    /// When a newer fact superseded it
+    pub valid_until: Option<DateTime<Utc>>,
+}
+
+impl Fact {
+    /// Whether no newer fact has superseded this one
+    pub fn is_current(&self) -> bool {
+        self.valid_until.is_none()
+    }
+}
+
+/// Everything the graph knows about one entity
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EntityKnowledge {
+    /// The entity
+    pub entity: Entity,
+    /// Facts about it that have not been superseded, oldest first
+    pub facts: Vec<Fact>,
+    /// Responses mentioning it, oldest first
+    pub responses: Vec<NodeId>,
+}
+
+/// Outcome of extracting knowledge from responses
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExtractionReport {
+    /// Responses read
+    pub responses: usize,
+    /// Entities seen for the first time
+    pub entities_created: usize,
+    /// Facts stored
+    pub facts_created: usize,
+    /// MENTIONED_IN edges stored
+    pub mentions: usize,
+}
+
+impl ExtractionReport {
+    pub(super) fn absorb(&mut self, other: &Self) {
+        self.responses += other.responses;
+        self.entities_created += other.entities_created;
+        self.facts_created += other.facts_created;
+        self.mentions += other.mentions;
+    }
+}
+
+/// Name in the form entities are matched by: lowercase, with runs of
+/// whitespace collapsed
+pub(super) fn normalize_name(name: &str) -> String {
+    name.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// Alias of the entity of `kind` called `name`
+pub(super) fn entity_alias(kind: &str, name: &str) -> String {
+    format!(
+        "{ENTITY_ALIAS_PREFIX}{}:{}",
+        normalize_name(kind),
+        normalize_name(name)
+    )
+}
+
+/// Alias of the current fact for `subject` and `predicate`
+pub(super) fn fact_alias(subject: &str, predicate: &str) -> String {
+    format!(
+        "{FACT_ALIAS_PREFIX}{}:{}",
+        normalize_name(subject),
+        normalize_name(predicate)
+    )
+}
+
+/// Read an entity back from its node
+pub(super) fn entity_from_node(node: &CustomNode) -> Option<Entity> {
+    if node.type_name != ENTITY_TYPE {
+        return None;
+    }
+    Some(Entity {
+        node_id: node.id,
+        name: node.payload.get("name")?.as_str()?.to_string(),
+        kind: node.payload.get("kind")?.as_str()?.to_string(),
+    })
+}
+
+/// Read a fact back from its node
+pub(super) fn fact_from_node(node: &CustomNode) -> Option<Fact> {
+    if node.type_name != FACT_TYPE {
+        return None;
+    }
+    let payload = &node.payload;
+    let text = |field: &str| {
+        payload
+            .get(field)
+            .and_then(|v| v.as_str())
+            .map(str::to_string)
+    };
+    let time = |value: Option<&serde_json::Value>| {
+        value.and_then(|v| serde_json::from_value::<DateTime<Utc>>(v.clone()).ok())
+    };
+    Some(Fact {
+        node_id: node.id,
+        statement: text("statement")?,
+        source: payload
+            .get("source")
+            .and_then(|v| serde_json::from_value(v.clone()).ok()),
+        subject: text("subject"),
+        predicate: text("predicate"),
+        value: text("value"),
+        valid_from: time(payload.get("valid_from")).unwrap_or(node.created_at),
+        valid_until: time(node.properties.get(VALID_UNTIL_PROPERTY)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_pattern_extractor() {
+        let extractor = PatternExtractor::new()
+            .with_pattern("customer", r"CUST-\d+")
+            .unwrap();
+        let extraction = extractor
+            .extract("CUST-1 renewed. Nothing else happened!\nCUST-2 and CUST-1 merged.")
+            .await
+            .unwrap();
+
+        assert_eq!(
+            extraction.entities,
+            vec![
+                ExtractedEntity::new("CUST-1", "customer"),
+                ExtractedEntity::new("CUST-2", "customer"),
+            ]
+        );
+        assert_eq!(extraction.facts.len(), 2);
+        assert_eq!(extraction.facts[0].statement, "CUST-1 renewed.");
+        assert_eq!(extraction.facts[1].entities, vec!["CUST-2", "CUST-1"]);
+
+        assert!(PatternExtractor::new().with_pattern("bad", "(").is_err());
+        assert_eq!(
+            entity_alias("Customer", "  Acme   Corp "),
+            "entity:customer:acme corp"
+        );
+        assert_eq!(fact_alias("Acme Corp", "Plan"), "fact:acme corp:plan");
+    }
+}
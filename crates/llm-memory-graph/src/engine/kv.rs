@@ -0,0 +1,227 @@
+//! Key-value store scoped to a graph
+//!
+//! [`AsyncMemoryGraph::kv`](super::AsyncMemoryGraph::kv) returns a [`KvStore`]
+//! for small pieces of state an embedder wants to keep next to the graph, such
+//! as stream cursors, feature flags or the time a job last ran. Entries live in
+//! their own tree in the graph's database and namespace, so they are flushed
+//! and dropped together with the graph, and they never appear in queries or
+//! exports.
+//!
+//! A value set with a TTL stops being visible once the TTL has passed; expired
+//! entries stay on disk until they are overwritten, deleted or removed by
+//! [`purge_expired`](KvStore::purge_expired).
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use llm_memory_graph::engine::AsyncMemoryGraph;
+//! use llm_memory_graph::Config;
+//! use std::time::Duration;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let graph = AsyncMemoryGraph::open(Config::default()).await?;
+//! let kv = graph.kv();
+//!
+//! kv.set_json("sync/cursor", &42u64, None).await?;
+//! kv.set("lock/nightly-job", "worker-3", Some(Duration::from_mins(10)))
+//!     .await?;
+//!
+//! let cursor: Option<u64> = kv.get_json("sync/cursor").await?;
+//! assert_eq!(cursor, Some(42));
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::storage::{AsyncStorageBackend, KvEntry};
+use crate::{Error, Result};
+use chrono::Utc;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::time::Duration;
+
+/// Longest key the store accepts, in bytes
+pub const MAX_KV_KEY_LEN: usize = 1024;
+
+/// Handle for reading and writing the graph's key-value entries
+///
+/// Keys are non-empty strings of up to [`MAX_KV_KEY_LEN`] bytes; a `/`
+/// separated layout such as `"sync/cursor"` lets [`list`](Self::list) read a
+/// group of related keys by prefix.
+pub struct KvStore<'a> {
+    backend: &'a dyn AsyncStorageBackend,
+}
+
+impl<'a> KvStore<'a> {
+    pub(super) fn new(backend: &'a dyn AsyncStorageBackend) -> Self {
+        Self { backend }
+    }
+
+    /// Value stored under `key`, or `None` if it is unset or expired
+    pub async fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        Ok(self.get_entry(key).await?.map(|entry| entry.value))
+    }
+
+    /// Entry stored under `key`, or `None` if it is unset or expired
+    pub async fn get_entry(&self, key: &str) -> Result<Option<KvEntry>> {
+        validate_key(key)?;
+        let now = Utc::now();
+        Ok(self
+            .backend
+            .kv_get(key)
+            .await?
+            .filter(|entry| !entry.is_expired_at(now)))
+    }
+
+    /// Value stored under `key` decoded from JSON
+    ///
+    /// # Errors
+    ///
+    /// Returns a deserialization error if the stored value is not JSON of
+    /// type `T`.
+    pub async fn get_json<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>> {
+        self.get(key)
+            .await?
+            .map(|value| {
+                serde_json::from_slice(&value)
+                    .map_err(|e| Error::DeserializationError(e.to_string()))
+            })
+            .transpose()
+    }
+
+    /// Store `value` under `key`, expiring after `ttl` if one is given
+    ///
+    /// # Errors
+    ///
+    /// Returns a validation error if the key is empty or longer than
+    /// [`MAX_KV_KEY_LEN`], or the TTL is too large to represent.
+    pub async fn set(
+        &self,
+        key: &str,
+        value: impl Into<Vec<u8>>,
+        ttl: Option<Duration>,
+    ) -> Result<()> {
+        validate_key(key)?;
+        let updated_at = Utc::now();
+        let expires_at = ttl
+            .map(|ttl| {
+                chrono::Duration::from_std(ttl)
+                    .ok()
+                    .and_then(|ttl| updated_at.checked_add_signed(ttl))
+                    .ok_or_else(|| {
+                        Error::ValidationError(format!("TTL of {ttl:?} is out of range"))
+                    })
+            })
+            .transpose()?;
+
+        self.backend
+            .kv_set(&KvEntry {
+                key: key.to_string(),
+                value: value.into(),
+                updated_at,
+                expires_at,
+            })
+            .await
+    }
+
+    /// Store `value` under `key` as JSON, expiring after `ttl` if one is given
+    pub async fn set_json<T: Serialize + ?Sized>(
+        &self,
+        key: &str,
+        value: &T,
+        ttl: Option<Duration>,
+    ) -> Result<()> {
+        self.set(key, serde_json::to_vec(value)?, ttl).await
+    }
+
+    /// Remove the value under `key`, returning whether a live one existed
+    pub async fn delete(&self, key: &str) -> Result<bool> {
+        let live = self.get_entry(key).await?.is_some();
+        let existed = self.backend.kv_delete(key).await?;
+        Ok(existed && live)
+    }
+
+    /// Live entries whose key starts with `prefix`, sorted by key
+    ///
+    /// An empty prefix lists every live entry.
+    pub async fn list(&self, prefix: &str) -> Result<Vec<KvEntry>> {
+        let now = Utc::now();
+        let mut entries = self.backend.kv_list(prefix).await?;
+        entries.retain(|entry| !entry.is_expired_at(now));
+        Ok(entries)
+    }
+
+    /// Remove every expired entry, returning how many were removed
+    pub async fn purge_expired(&self) -> Result<usize> {
+        let now = Utc::now();
+        let mut purged = 0;
+        for entry in self.backend.kv_list("").await? {
+            if entry.is_expired_at(now) && self.backend.kv_delete(&entry.key).await? {
+                purged += 1;
+            }
+        }
+        Ok(purged)
+    }
+}
+
+fn validate_key(key: &str) -> Result<()> {
+    if key.is_empty() || key.len() > MAX_KV_KEY_LEN {
+        return Err(Error::ValidationError(format!(
+            "key-value keys must be 1 to {MAX_KV_KEY_LEN} bytes, got {} bytes",
+            key.len()
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::engine::AsyncMemoryGraph;
+    use crate::Config;
+    use std::time::Duration;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_kv_store() {
+        let dir = tempdir().unwrap();
+        let graph = AsyncMemoryGraph::open(Config::new(dir.path()))
+            .await
+            .unwrap();
+        let kv = graph.kv();
+
+        assert!(kv.get("sync/cursor").await.unwrap().is_none());
+        kv.set_json("sync/cursor", &42u64, None).await.unwrap();
+        kv.set("sync/flag", "on", None).await.unwrap();
+        kv.set("sync/lease", "worker-1", Some(Duration::ZERO))
+            .await
+            .unwrap();
+        kv.set("other", "x", Some(Duration::from_mins(10)))
+            .await
+            .unwrap();
+        assert!(kv.set("", "x", None).await.is_err());
+
+        assert_eq!(kv.get_json::<u64>("sync/cursor").await.unwrap(), Some(42));
+        assert_eq!(kv.get("sync/flag").await.unwrap(), Some(b"on".to_vec()));
+        assert!(kv.get("sync/lease").await.unwrap().is_none());
+        assert!(kv
+            .get_entry("other")
+            .await
+            .unwrap()
+            .unwrap()
+            .expires_at
+            .is_some());
+
+        let keys: Vec<_> = kv
+            .list("sync/")
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.key)
+            .collect();
+        assert_eq!(keys, vec!["sync/cursor", "sync/flag"]);
+
+        assert_eq!(kv.purge_expired().await.unwrap(), 1);
+        assert!(kv.delete("sync/flag").await.unwrap());
+        assert!(!kv.delete("sync/flag").await.unwrap());
+        assert_eq!(kv.list("").await.unwrap().len(), 2);
+    }
+}
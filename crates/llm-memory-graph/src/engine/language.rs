@@ -0,0 +1,127 @@
+//! Language of prompt and response text
+//!
+//! With a [`LanguageDetector`] installed through
+//! [`AsyncMemoryGraph::set_language_detector`](super::AsyncMemoryGraph::set_language_detector),
+//! the language of every prompt and response is detected as it is stored and
+//! recorded as an ISO 639-3 code such as `eng` or `deu` under
+//! [`LANGUAGE_METADATA_KEY`] in its custom metadata, unless the caller already
+//! set one. Queries select a language with
+//! [`AsyncQueryBuilder::language`](crate::query::AsyncQueryBuilder::language).
+//!
+//! [`WhatlangDetector`], available with the `language-detection` feature,
+//! detects languages locally from character trigrams; any closure taking the
+//! text works too, for example one that calls a hosted detection service.
+
+use crate::Node;
+
+/// Custom metadata key holding the detected language code
+pub const LANGUAGE_METADATA_KEY: &str = "language";
+
+/// Detects the language of node text
+pub trait LanguageDetector: Send + Sync {
+    /// Language code of `text`, or `None` if it cannot be told reliably
+    fn detect(&self, text: &str) -> Option<String>;
+}
+
+impl<F> LanguageDetector for F
+where
+    F: Fn(&str) -> Option<String> + Send + Sync,
+{
+    fn detect(&self, text: &str) -> Option<String> {
+        self(text)
+    }
+}
+
+/// Language recorded in a prompt's or response's metadata
+pub fn node_language(node: &Node) -> Option<&str> {
+    let custom = match node {
+        Node::Prompt(prompt) => &prompt.metadata.custom,
+        Node::Response(response) => &response.metadata.custom,
+        _ => return None,
+    };
+    custom.get(LANGUAGE_METADATA_KEY).map(String::as_str)
+}
+
+/// Default confidence [`WhatlangDetector`] needs before reporting a language
+#[cfg(feature = "language-detection")]
+pub const DEFAULT_MIN_LANGUAGE_CONFIDENCE: f64 = 0.2;
+
+/// Detects languages locally with [whatlang](https://docs.rs/whatlang)
+///
+/// Reports ISO 639-3 codes. Short texts are often ambiguous, so languages
+/// detected with less than the minimum confidence are not reported.
+#[cfg(feature = "language-detection")]
+#[derive(Debug, Clone, Copy)]
+pub struct WhatlangDetector {
+    min_confidence: f64,
+}
+
+#[cfg(feature = "language-detection")]
+impl WhatlangDetector {
+    /// Detector requiring [`DEFAULT_MIN_LANGUAGE_CONFIDENCE`]
+    pub fn new() -> Self {
+        Self {
+            min_confidence: DEFAULT_MIN_LANGUAGE_CONFIDENCE,
+        }
+    }
+
+    /// Set the confidence, between zero and one, needed to report a language
+    pub fn with_min_confidence(mut self, min_confidence: f64) -> Self {
+        self.min_confidence = min_confidence.clamp(0.0, 1.0);
+        self
+    }
+}
+
+#[cfg(feature = "language-detection")]
+impl Default for WhatlangDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "language-detection")]
+impl LanguageDetector for WhatlangDetector {
+    fn detect(&self, text: &str) -> Option<String> {
+        let info = whatlang::detect(text)?;
+        (info.confidence() >= self.min_confidence).then(|| info.lang().code().to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{PromptNode, SessionId};
+
+    #[test]
+    fn test_node_language() {
+        let mut prompt = PromptNode::new(SessionId::new(), "Hola".to_string());
+        assert_eq!(node_language(&Node::Prompt(prompt.clone())), None);
+
+        let detector = |_: &str| Some("spa".to_string());
+        let language = detector.detect(&prompt.content).unwrap();
+        prompt
+            .metadata
+            .custom
+            .insert(LANGUAGE_METADATA_KEY.to_string(), language);
+        assert_eq!(node_language(&Node::Prompt(prompt)), Some("spa"));
+    }
+
+    #[cfg(feature = "language-detection")]
+    #[test]
+    fn test_whatlang_detector() {
+        let detector = WhatlangDetector::new();
+        assert_eq!(
+            detector
+                .detect("The quick brown fox jumps over the lazy dog and runs away")
+                .as_deref(),
+            Some("eng")
+        );
+        assert_eq!(
+            detector
+                .detect("Der schnelle braune Fuchs springt über den faulen Hund")
+                .as_deref(),
+            Some("deu")
+        );
+        assert_eq!(detector.detect("42"), None);
+    }
+}
@@ -0,0 +1,92 @@
+//! Legal holds on sessions
+//!
+//! A session under a hold placed with
+//! [`place_hold`](super::AsyncMemoryGraph::place_hold) cannot lose any of its
+//! nodes until [`release_hold`](super::AsyncMemoryGraph::release_hold) is
+//! called: deleting the session or one of its nodes, rolling it back and
+//! merging it away fail with a conflict error, trash purges keep its trashed
+//! nodes, and the maintenance runner neither archives nor expires it.
+//!
+//! The hold is stored in the session's metadata under [`HOLD_REASON_KEY`] and
+//! [`HOLD_PLACED_AT_KEY`], so it survives restarts and shows up wherever
+//! sessions are listed or exported. Placing and releasing holds publish
+//! `LegalHoldPlaced` and `LegalHoldReleased` events.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use llm_memory_graph::engine::AsyncMemoryGraph;
+//! use llm_memory_graph::Config;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let graph = AsyncMemoryGraph::open(Config::default()).await?;
+//! let session = graph.create_session().await?;
+//!
+//! graph.place_hold(session.id, "Case 2024-118").await?;
+//! assert!(graph.delete_session(session.id).await.is_err());
+//!
+//! graph.release_hold(session.id).await?;
+//! graph.delete_session(session.id).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{ConversationSession, SessionId};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Session metadata key holding why the session is held
+pub const HOLD_REASON_KEY: &str = "legal_hold_reason";
+
+/// Session metadata key holding when the hold was placed, in RFC 3339
+pub const HOLD_PLACED_AT_KEY: &str = "legal_hold_placed_at";
+
+/// Longest hold reason accepted, in bytes
+pub const MAX_HOLD_REASON_LEN: usize = 1024;
+
+/// A legal hold placed on a session
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LegalHold {
+    /// The held session
+    pub session_id: SessionId,
+    /// Why the session is held, such as a case or ticket reference
+    pub reason: String,
+    /// When the hold was placed
+    pub placed_at: DateTime<Utc>,
+}
+
+impl LegalHold {
+    /// The hold recorded in `session`'s metadata, if any
+    ///
+    /// A hold whose placement time is unreadable is still reported, placed at
+    /// the session's creation, so that a damaged record never lifts it.
+    pub fn from_session(session: &ConversationSession) -> Option<Self> {
+        let reason = session.metadata.get(HOLD_REASON_KEY)?.clone();
+        let placed_at = session
+            .metadata
+            .get(HOLD_PLACED_AT_KEY)
+            .and_then(|value| DateTime::parse_from_rfc3339(value).ok())
+            .map_or(session.created_at, |at| at.with_timezone(&Utc));
+        Some(Self {
+            session_id: session.id,
+            reason,
+            placed_at,
+        })
+    }
+
+    /// Record the hold in session metadata
+    pub(super) fn write_to(&self, metadata: &mut HashMap<String, String>) {
+        metadata.insert(HOLD_REASON_KEY.to_string(), self.reason.clone());
+        metadata.insert(HOLD_PLACED_AT_KEY.to_string(), self.placed_at.to_rfc3339());
+    }
+
+    /// Remove any hold from session metadata, returning whether one was there
+    pub(super) fn clear(metadata: &mut HashMap<String, String>) -> bool {
+        let mut cleared = false;
+        for key in [HOLD_REASON_KEY, HOLD_PLACED_AT_KEY] {
+            cleared |= metadata.remove(key).is_some();
+        }
+        cleared
+    }
+}
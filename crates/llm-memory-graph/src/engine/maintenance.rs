@@ -0,0 +1,836 @@
+//! Background maintenance for an [`AsyncMemoryGraph`]
+//!
+//! A [`MaintenanceScheduler`] runs the housekeeping every long-lived graph
+//! needs on a single Tokio task: periodic flushing, cache statistics
+//! publication, trash pruning, index compaction, garbage collection, the
+//! [retention rules](crate::RetentionRule), pruning of decayed references
+//! (with a reference half-life), moving old records to the
+//! [cold tier](crate::ColdStorageConfig) (with a cold age) and (with an
+//! archiver) archival of idle
+//! sessions, sent to the archiver as [`.lmg` archives](crate::archive).
+//! The schedule comes from
+//! [`Config::maintenance`](crate::Config) and every interval is jittered so
+//! that many graphs opened at once do not run their tasks in lockstep.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use llm_memory_graph::engine::{AsyncMemoryGraph, MaintenanceScheduler};
+//! use llm_memory_graph::{Config, MaintenanceConfig};
+//! use std::sync::Arc;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let config = Config::new("./data").with_maintenance(
+//!     MaintenanceConfig::new()
+//!         .with_cache_stats_interval(30_000)
+//!         .with_jitter(0.2),
+//! );
+//! let graph = Arc::new(AsyncMemoryGraph::open(config).await?);
+//!
+//! let maintenance = MaintenanceScheduler::new(Arc::clone(&graph)).start();
+//! // ... use the graph ...
+//! maintenance.stop().await;
+//! # Ok(())
+//! # }
+//! ```
+
+use super::{AsyncMemoryGraph, LegalHold};
+use crate::archive::ArchiveKind;
+use crate::integrations::vault::{ArchiveEntry, Archiver};
+use crate::{ConversationSession, MaintenanceConfig, Node, Result};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use rand::Rng;
+use std::fmt;
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+
+/// A background maintenance task
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MaintenanceTask {
+    /// Flush pending writes to disk
+    Flush,
+    /// Publish cache statistics to the Observatory
+    PublishCacheStats,
+    /// Permanently remove nodes that outlived the trash retention
+    PurgeTrash,
+    /// Remove index entries left dangling by hard deletes
+    CompactIndexes,
+    /// Remove orphaned nodes and dangling edges
+    CollectGarbage,
+    /// Archive idle sessions and move them to the trash
+    ArchiveSessions,
+    /// Delete (or archive and delete) sessions their retention rule expired
+    ApplyRetention,
+    /// Remove REFERENCES edges whose relevance decayed below the floor
+    PruneReferences,
+    /// Move old nodes and edges to the cold storage tier
+    TierColdStorage,
+}
+
+impl fmt::Display for MaintenanceTask {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Flush => "flush",
+            Self::PublishCacheStats => "publish_cache_stats",
+            Self::PurgeTrash => "purge_trash",
+            Self::CompactIndexes => "compact_indexes",
+            Self::CollectGarbage => "collect_garbage",
+            Self::ArchiveSessions => "archive_sessions",
+            Self::ApplyRetention => "apply_retention",
+            Self::PruneReferences => "prune_references",
+            Self::TierColdStorage => "tier_cold_storage",
+        })
+    }
+}
+
+/// Destination for idle sessions
+struct ArchiveTarget {
+    archiver: Arc<dyn Archiver>,
+    retention_days: i64,
+}
+
+/// Configures and starts the background maintenance of a graph
+pub struct MaintenanceScheduler {
+    graph: Arc<AsyncMemoryGraph>,
+    config: MaintenanceConfig,
+    flush_interval_ms: u64,
+    archive: Option<ArchiveTarget>,
+}
+
+impl MaintenanceScheduler {
+    /// Scheduler using the graph's configured schedule
+    pub fn new(graph: Arc<AsyncMemoryGraph>) -> Self {
+        let config = graph.maintenance_config().clone();
+        let flush_interval_ms = graph.flush_interval_ms();
+        Self {
+            graph,
+            config,
+            flush_interval_ms,
+            archive: None,
+        }
+    }
+
+    /// Use a different schedule
+    pub fn with_config(mut self, config: MaintenanceConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Set the periodic flush interval (0 disables flushing)
+    pub fn with_flush_interval(mut self, interval_ms: u64) -> Self {
+        self.flush_interval_ms = interval_ms;
+        self
+    }
+
+    /// Archive idle sessions to `archiver`, keeping them for `retention_days`
+    ///
+    /// Without an archiver the archival task never runs, and sessions whose
+    /// retention rule asks for archival are kept.
+    pub fn with_archiver(mut self, archiver: Arc<dyn Archiver>, retention_days: i64) -> Self {
+        self.archive = Some(ArchiveTarget {
+            archiver,
+            retention_days,
+        });
+        self
+    }
+
+    /// Interval of `task`, or `None` when it is disabled
+    pub fn interval(&self, task: MaintenanceTask) -> Option<Duration> {
+        let interval_ms = match task {
+            MaintenanceTask::Flush => self.flush_interval_ms,
+            MaintenanceTask::PublishCacheStats => self.config.cache_stats_interval_ms,
+            MaintenanceTask::PurgeTrash => self.config.purge_interval_ms,
+            MaintenanceTask::CompactIndexes => self.config.compaction_interval_ms,
+            MaintenanceTask::CollectGarbage => self.config.gc_interval_ms,
+            MaintenanceTask::ArchiveSessions if self.archive.is_some() => {
+                self.config.archive_interval_ms
+            }
+            MaintenanceTask::ApplyRetention if !self.config.retention_rules.is_empty() => {
+                self.config.retention_interval_ms
+            }
+            MaintenanceTask::PruneReferences if self.config.reference_half_life_ms > 0 => {
+                self.config.reference_prune_interval_ms
+            }
+            MaintenanceTask::TierColdStorage if self.config.cold_after_ms > 0 => {
+                self.config.cold_tier_interval_ms
+            }
+            MaintenanceTask::ArchiveSessions
+            | MaintenanceTask::ApplyRetention
+            | MaintenanceTask::PruneReferences
+            | MaintenanceTask::TierColdStorage => 0,
+        };
+        (interval_ms > 0).then(|| Duration::from_millis(interval_ms))
+    }
+
+    /// Run `task` once, immediately
+    ///
+    /// Returns the number of items the task affected (purged nodes, removed
+    /// index entries, collected nodes and edges, archived or expired sessions,
+    /// pruned references, moved nodes and edges; 0 for flushes and cache
+    /// statistics).
+    pub async fn run_now(&self, task: MaintenanceTask) -> Result<usize> {
+        run_task(&self.graph, &self.config, self.archive.as_ref(), task).await
+    }
+
+    /// Start the maintenance loop on a background task
+    ///
+    /// The loop only holds a weak reference to the graph and exits once the
+    /// graph is closed or dropped. Failed runs are logged and retried at the
+    /// next interval.
+    pub fn start(self) -> MaintenanceHandle {
+        let (shutdown, mut stop) = watch::channel(false);
+        let mut closing = self.graph.shutdown_signal();
+        let graph = Arc::downgrade(&self.graph);
+        let tasks: Vec<_> = [
+            MaintenanceTask::Flush,
+            MaintenanceTask::PublishCacheStats,
+            MaintenanceTask::PurgeTrash,
+            MaintenanceTask::CompactIndexes,
+            MaintenanceTask::CollectGarbage,
+            MaintenanceTask::ArchiveSessions,
+            MaintenanceTask::ApplyRetention,
+            MaintenanceTask::PruneReferences,
+            MaintenanceTask::TierColdStorage,
+        ]
+        .into_iter()
+        .filter_map(|task| self.interval(task).map(|interval| (task, interval)))
+        .collect();
+        let Self {
+            config, archive, ..
+        } = self;
+
+        tracing::info!(
+            "Starting maintenance scheduler ({} tasks, jitter {:.0}%)",
+            tasks.len(),
+            config.jitter * 100.0
+        );
+
+        let task = tokio::spawn(async move {
+            let now = Instant::now();
+            let mut schedule: Vec<_> = tasks
+                .into_iter()
+                .map(|(task, interval)| (task, interval, now + jittered(interval, config.jitter)))
+                .collect();
+
+            while !*closing.borrow() {
+                let Some(next) = schedule.iter_mut().min_by_key(|(_, _, due)| *due) else {
+                    // Nothing is scheduled; wait for a stop signal
+                    tokio::select! {
+                        _ = stop.changed() => {}
+                        _ = closing.changed() => {}
+                    }
+                    break;
+                };
+
+                tokio::select! {
+                    () = tokio::time::sleep_until(next.2) => {}
+                    _ = stop.changed() => break,
+                    _ = closing.changed() => break,
+                }
+
+                let Some(graph) = Weak::upgrade(&graph) else {
+                    break;
+                };
+                let task = next.0;
+                match run_task(&graph, &config, archive.as_ref(), task).await {
+                    Ok(affected) => {
+                        tracing::debug!("Maintenance task {} affected {} items", task, affected);
+                    }
+                    Err(e) => tracing::warn!("Maintenance task {} failed: {}", task, e),
+                }
+                drop(graph);
+
+                next.2 = Instant::now() + jittered(next.1, config.jitter);
+            }
+
+            tracing::info!("Maintenance scheduler stopped");
+        });
+
+        MaintenanceHandle {
+            shutdown,
+            task: Some(task),
+        }
+    }
+}
+
+/// Handle to a running maintenance loop
+///
+/// Dropping the handle stops the loop without waiting for it.
+pub struct MaintenanceHandle {
+    shutdown: watch::Sender<bool>,
+    task: Option<JoinHandle<()>>,
+}
+
+impl MaintenanceHandle {
+    /// Whether the loop is still running
+    pub fn is_running(&self) -> bool {
+        self.task.as_ref().is_some_and(|task| !task.is_finished())
+    }
+
+    /// Stop the loop and wait for the task in progress, if any, to finish
+    pub async fn stop(mut self) {
+        let _ = self.shutdown.send(true);
+        if let Some(task) = self.task.take() {
+            let _ = task.await;
+        }
+    }
+}
+
+impl Drop for MaintenanceHandle {
+    fn drop(&mut self) {
+        let _ = self.shutdown.send(true);
+    }
+}
+
+/// `interval` spread uniformly by `±jitter`
+fn jittered(interval: Duration, jitter: f64) -> Duration {
+    if jitter <= 0.0 {
+        return interval;
+    }
+    let factor = rand::thread_rng().gen_range(-jitter..=jitter);
+    interval.mul_f64(1.0 + factor)
+}
+
+async fn run_task(
+    graph: &AsyncMemoryGraph,
+    config: &MaintenanceConfig,
+    archive: Option<&ArchiveTarget>,
+    task: MaintenanceTask,
+) -> Result<usize> {
+    match task {
+        MaintenanceTask::Flush => graph.flush().await.map(|()| 0),
+        MaintenanceTask::PublishCacheStats => {
+            graph.publish_cache_stats().await;
+            Ok(0)
+        }
+        MaintenanceTask::PurgeTrash => {
+            graph
+                .purge_trash(duration_ms(config.trash_retention_ms))
+                .await
+        }
+        MaintenanceTask::CompactIndexes => graph.compact_indexes().await,
+        MaintenanceTask::CollectGarbage => graph.gc(false).await.map(|report| report.total()),
+        MaintenanceTask::ArchiveSessions => match archive {
+            Some(target) => archive_idle_sessions(graph, config, target).await,
+            None => Ok(0),
+        },
+        MaintenanceTask::ApplyRetention => apply_retention_rules(graph, config, archive).await,
+        MaintenanceTask::PruneReferences => {
+            graph
+                .prune_references(config.reference_half_life_ms, config.reference_floor)
+                .await
+        }
+        MaintenanceTask::TierColdStorage => graph
+            .tier_cold(duration_ms(config.cold_after_ms))
+            .await
+            .map(|report| report.total()),
+    }
+}
+
+/// Archive sessions with no activity for `archive_after_ms`, then delete them
+///
+/// Sessions under legal hold are skipped. A session that fails to archive is
+/// kept and retried on the next run.
+async fn archive_idle_sessions(
+    graph: &AsyncMemoryGraph,
+    config: &MaintenanceConfig,
+    target: &ArchiveTarget,
+) -> Result<usize> {
+    let cutoff = chrono::Utc::now() - duration_ms(config.archive_after_ms);
+    let mut archived = 0;
+
+    for session in graph.list_sessions().await? {
+        if session.updated_at >= cutoff || LegalHold::from_session(&session).is_some() {
+            continue;
+        }
+        let nodes = graph.get_session_nodes(&session.id).await?;
+        if last_activity(&nodes).is_some_and(|at| at >= cutoff) {
+            continue;
+        }
+
+        let entry = archive_entry(graph, &session, target)
+            .await?
+            .with_tag("maintenance");
+        if archive(target, entry, &session).await {
+            graph.delete_session(session.id).await?;
+            archived += 1;
+        }
+    }
+
+    Ok(archived)
+}
+
+/// Delete sessions idle for longer than their retention rule allows
+///
+/// Sessions matching no rule or under legal hold are kept. A rule that
+/// archives needs an archiver; without one, or when archiving fails, the
+/// session is kept and retried on the next run.
+async fn apply_retention_rules(
+    graph: &AsyncMemoryGraph,
+    config: &MaintenanceConfig,
+    archive_target: Option<&ArchiveTarget>,
+) -> Result<usize> {
+    if config.retention_rules.is_empty() {
+        return Ok(0);
+    }
+    let now = chrono::Utc::now();
+    let mut expired = 0;
+
+    for session in graph.list_sessions().await? {
+        if LegalHold::from_session(&session).is_some() {
+            continue;
+        }
+        let Some(rule) = config.retention_rule_for(&session) else {
+            continue;
+        };
+        let cutoff = now - duration_ms(rule.retain_ms);
+        if session.updated_at >= cutoff {
+            continue;
+        }
+        let nodes = graph.get_session_nodes(&session.id).await?;
+        if last_activity(&nodes).is_some_and(|at| at >= cutoff) {
+            continue;
+        }
+
+        if rule.archive {
+            let Some(target) = archive_target else {
+                tracing::warn!(
+                    "Retention rule {} archives sessions but no archiver is configured; keeping session {}",
+                    rule.name,
+                    session.id
+                );
+                continue;
+            };
+            let entry = archive_entry(graph, &session, target)
+                .await?
+                .with_tag("retention")
+                .with_tag(rule.name.clone());
+            if !archive(target, entry, &session).await {
+                continue;
+            }
+        }
+        tracing::debug!(
+            "Retention rule {} expired session {}",
+            rule.name,
+            session.id
+        );
+        graph.delete_session(session.id).await?;
+        expired += 1;
+    }
+
+    Ok(expired)
+}
+
+/// Latest prompt or response timestamp among `nodes`
+fn last_activity(nodes: &[Node]) -> Option<chrono::DateTime<chrono::Utc>> {
+    nodes
+        .iter()
+        .filter_map(|node| match node {
+            Node::Prompt(prompt) => Some(prompt.timestamp),
+            Node::Response(response) => Some(response.timestamp),
+            _ => None,
+        })
+        .max()
+}
+
+/// Vault entry holding `session` as a base64-encoded `.lmg` archive
+async fn archive_entry(
+    graph: &AsyncMemoryGraph,
+    session: &ConversationSession,
+    target: &ArchiveTarget,
+) -> Result<ArchiveEntry> {
+    let archive = graph
+        .session_archive(session.id, ArchiveKind::VaultArchive)
+        .await?;
+    let payload = serde_json::json!({
+        "format": crate::archive::ARCHIVE_EXTENSION,
+        "session_id": session.id,
+        "archive": BASE64.encode(archive),
+    });
+    Ok(ArchiveEntry::new(
+        session.id.to_string(),
+        payload,
+        target.retention_days,
+    ))
+}
+
+/// Send `entry` to the archiver, returning whether it was stored
+async fn archive(
+    target: &ArchiveTarget,
+    entry: ArchiveEntry,
+    session: &ConversationSession,
+) -> bool {
+    match target.archiver.archive_session(entry).await {
+        Ok(_) => true,
+        Err(e) => {
+            tracing::warn!("Failed to archive session {}: {}", session.id, e);
+            false
+        }
+    }
+}
+
+fn duration_ms(ms: u64) -> chrono::Duration {
+    chrono::Duration::milliseconds(i64::try_from(ms).unwrap_or(i64::MAX))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::integrations::vault::{ArchiveResponse, BatchArchiveResponse};
+    use crate::integrations::IntegrationError;
+    use crate::observatory::{InMemoryPublisher, ObservatoryConfig};
+    use crate::Config;
+    use async_trait::async_trait;
+    use tempfile::tempdir;
+    use tokio::sync::Mutex;
+
+    #[derive(Default)]
+    struct RecordingArchiver {
+        entries: Mutex<Vec<ArchiveEntry>>,
+    }
+
+    #[async_trait]
+    impl Archiver for RecordingArchiver {
+        async fn archive_session(
+            &self,
+            entry: ArchiveEntry,
+        ) -> std::result::Result<ArchiveResponse, IntegrationError> {
+            let response = ArchiveResponse {
+                archive_id: entry.id.clone(),
+                session_id: entry.session_id.clone(),
+                status: "archived".to_string(),
+                archived_at: entry.archived_at,
+            };
+            self.entries.lock().await.push(entry);
+            Ok(response)
+        }
+
+        async fn batch_archive(
+            &self,
+            entries: Vec<ArchiveEntry>,
+        ) -> std::result::Result<BatchArchiveResponse, IntegrationError> {
+            let archived: Vec<_> = entries.iter().map(|entry| entry.id.clone()).collect();
+            self.entries.lock().await.extend(entries);
+            Ok(BatchArchiveResponse {
+                total: archived.len(),
+                success_count: archived.len(),
+                archived,
+                failed: Vec::new(),
+            })
+        }
+
+        async fn retrieve_session(
+            &self,
+            archive_id: &str,
+        ) -> std::result::Result<ArchiveEntry, IntegrationError> {
+            self.entries
+                .lock()
+                .await
+                .iter()
+                .find(|entry| entry.id == archive_id)
+                .cloned()
+                .ok_or_else(|| not_found(archive_id))
+        }
+
+        async fn delete_archive(
+            &self,
+            archive_id: &str,
+        ) -> std::result::Result<(), IntegrationError> {
+            let mut entries = self.entries.lock().await;
+            let before = entries.len();
+            entries.retain(|entry| entry.id != archive_id);
+            if entries.len() == before {
+                return Err(not_found(archive_id));
+            }
+            Ok(())
+        }
+    }
+
+    fn not_found(archive_id: &str) -> IntegrationError {
+        IntegrationError::ApiError {
+            status: 404,
+            message: format!("archive {archive_id} not found"),
+        }
+    }
+
+    fn disabled() -> MaintenanceConfig {
+        MaintenanceConfig::new()
+            .with_cache_stats_interval(0)
+            .with_trash_retention(0, 0)
+            .with_compaction_interval(0)
+            .with_gc_interval(0)
+            .with_archival(0, 0)
+            .with_jitter(0.0)
+    }
+
+    #[test]
+    fn test_jittered_interval_bounds() {
+        let interval = Duration::from_secs(1);
+        assert_eq!(jittered(interval, 0.0), interval);
+        for _ in 0..100 {
+            let jittered = jittered(interval, 0.25);
+            assert!(jittered >= Duration::from_millis(750));
+            assert!(jittered <= Duration::from_millis(1250));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_scheduler_publishes_and_purges() {
+        let dir = tempdir().unwrap();
+        let publisher = Arc::new(InMemoryPublisher::new());
+        let config = Config::new(dir.path()).with_maintenance(
+            disabled()
+                .with_cache_stats_interval(20)
+                .with_trash_retention(20, 0),
+        );
+        let graph = Arc::new(
+            AsyncMemoryGraph::with_observatory(
+                config,
+                Some(publisher.clone()),
+                ObservatoryConfig::new().enabled(),
+            )
+            .await
+            .unwrap(),
+        );
+        let session = graph.create_session().await.unwrap();
+        graph.delete_session(session.id).await.unwrap();
+        assert_eq!(graph.trash().await.unwrap().len(), 1);
+
+        let scheduler = MaintenanceScheduler::new(Arc::clone(&graph)).with_flush_interval(0);
+        assert_eq!(scheduler.interval(MaintenanceTask::Flush), None);
+        assert_eq!(scheduler.interval(MaintenanceTask::ArchiveSessions), None);
+        assert_eq!(scheduler.interval(MaintenanceTask::ApplyRetention), None);
+        assert_eq!(scheduler.interval(MaintenanceTask::TierColdStorage), None);
+        let handle = scheduler.start();
+        assert!(handle.is_running());
+
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        handle.stop().await;
+
+        assert!(graph.trash().await.unwrap().is_empty());
+        assert!(!publisher
+            .get_events_by_type("cache_stats_reported")
+            .await
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_archive_idle_sessions() {
+        let dir = tempdir().unwrap();
+        let config = Config::new(dir.path()).with_maintenance(disabled().with_archival(1000, 0));
+        let graph = Arc::new(AsyncMemoryGraph::open(config).await.unwrap());
+        let session = graph.create_session().await.unwrap();
+        graph
+            .add_prompt(session.id, "Hello".to_string(), None)
+            .await
+            .unwrap();
+
+        let archiver = Arc::new(RecordingArchiver::default());
+        let scheduler =
+            MaintenanceScheduler::new(Arc::clone(&graph)).with_archiver(archiver.clone(), 30);
+        assert_eq!(
+            scheduler
+                .run_now(MaintenanceTask::ArchiveSessions)
+                .await
+                .unwrap(),
+            1
+        );
+
+        let entries = archiver.entries.lock().await;
+        assert_eq!(entries[0].session_id, session.id.to_string());
+        assert_eq!(entries[0].data["format"], "lmg");
+        let archive = BASE64
+            .decode(entries[0].data["archive"].as_str().unwrap())
+            .unwrap();
+        let archive = crate::archive::ArchiveReader::new(archive).unwrap();
+        assert_eq!(archive.manifest().kind, ArchiveKind::VaultArchive);
+        assert_eq!(archive.manifest().session_id, Some(session.id));
+        assert_eq!(archive.read_all().unwrap().0.len(), 2);
+        assert!(graph.get_session(session.id).await.is_err());
+        assert!(graph.list_sessions().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_apply_retention_rules() {
+        let dir = tempdir().unwrap();
+        let config = Config::new(dir.path()).with_maintenance(
+            disabled()
+                .with_retention_rule(
+                    crate::RetentionRule::new("ephemeral", 0).with_tag("ephemeral"),
+                )
+                .with_retention_rule(
+                    crate::RetentionRule::new("enterprise", 0)
+                        .with_metadata("customer_tier", "enterprise")
+                        .archived(),
+                ),
+        );
+        let graph = Arc::new(AsyncMemoryGraph::open(config).await.unwrap());
+
+        let mut ephemeral = ConversationSession::new();
+        ephemeral.add_tag("ephemeral".to_string());
+        let mut enterprise = ConversationSession::new();
+        enterprise
+            .metadata
+            .insert("customer_tier".to_string(), "enterprise".to_string());
+        graph
+            .store_nodes_batch(vec![
+                Node::Session(ephemeral.clone()),
+                Node::Session(enterprise.clone()),
+            ])
+            .await
+            .unwrap();
+        let kept = graph.create_session().await.unwrap();
+        let mut held = ConversationSession::new();
+        held.add_tag("ephemeral".to_string());
+        graph
+            .store_nodes_batch(vec![Node::Session(held.clone())])
+            .await
+            .unwrap();
+        graph.place_hold(held.id, "Case 118").await.unwrap();
+
+        // Without an archiver the enterprise session cannot be archived, so it stays
+        let scheduler = MaintenanceScheduler::new(Arc::clone(&graph));
+        assert!(scheduler
+            .interval(MaintenanceTask::ApplyRetention)
+            .is_some());
+        assert_eq!(
+            scheduler
+                .run_now(MaintenanceTask::ApplyRetention)
+                .await
+                .unwrap(),
+            1
+        );
+        assert!(graph.get_session(ephemeral.id).await.is_err());
+        assert!(graph.get_session(enterprise.id).await.is_ok());
+
+        let archiver = Arc::new(RecordingArchiver::default());
+        let scheduler =
+            MaintenanceScheduler::new(Arc::clone(&graph)).with_archiver(archiver.clone(), 730);
+        assert_eq!(
+            scheduler
+                .run_now(MaintenanceTask::ApplyRetention)
+                .await
+                .unwrap(),
+            1
+        );
+
+        let entries = archiver.entries.lock().await;
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].session_id, enterprise.id.to_string());
+        assert!(entries[0].tags.contains(&"enterprise".to_string()));
+        let remaining: Vec<_> = graph
+            .list_sessions()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|session| session.id)
+            .collect();
+        assert_eq!(remaining.len(), 2);
+        assert!(remaining.contains(&kept.id));
+        assert!(remaining.contains(&held.id));
+    }
+
+    #[tokio::test]
+    async fn test_prune_decayed_references() {
+        let dir = tempdir().unwrap();
+        let day_ms = 24 * 60 * 60 * 1000;
+        let config =
+            Config::new(dir.path()).with_maintenance(disabled().with_reference_decay(day_ms, 0.1));
+        let graph = Arc::new(AsyncMemoryGraph::open(config).await.unwrap());
+        let session = graph.create_session().await.unwrap();
+        let prompt = graph
+            .add_prompt(session.id, "Question".to_string(), None)
+            .await
+            .unwrap();
+        let mut targets = Vec::new();
+        for text in ["Fresh", "Stale", "Policy", "Unscored"] {
+            targets.push(
+                graph
+                    .add_prompt(session.id, text.to_string(), None)
+                    .await
+                    .unwrap(),
+            );
+        }
+
+        // Four days old: relevance 0.9 has decayed to about 0.06
+        let old = chrono::Utc::now() - chrono::Duration::days(4);
+        let reference = |to, relevance| {
+            crate::Edge::new(prompt, to, crate::EdgeType::References).with_relevance(relevance)
+        };
+        let fresh = reference(targets[0], 0.5);
+        let mut stale = reference(targets[1], 0.9);
+        stale.created_at = old;
+        let mut policy = reference(targets[2], 0.9);
+        policy.created_at = old;
+        policy.properties.insert(
+            "priority".to_string(),
+            crate::Priority::Critical.to_string(),
+        );
+        let mut unscored = crate::Edge::new(prompt, targets[3], crate::EdgeType::References);
+        unscored.created_at = old;
+        graph
+            .store_edges_batch(vec![fresh, stale.clone(), policy, unscored])
+            .await
+            .unwrap();
+
+        let context = graph
+            .assemble_context(&prompt, &super::super::ContextOptions::new())
+            .await
+            .unwrap();
+        let stale_item = context
+            .iter()
+            .find(|item| item.edge.id == stale.id)
+            .unwrap();
+        assert!(stale_item.relevance < 0.1);
+        assert_eq!(context[1].node.id(), targets[0]);
+
+        let scheduler = MaintenanceScheduler::new(Arc::clone(&graph));
+        assert_eq!(
+            scheduler.interval(MaintenanceTask::PruneReferences),
+            Some(Duration::from_millis(day_ms))
+        );
+        assert_eq!(
+            scheduler
+                .run_now(MaintenanceTask::PruneReferences)
+                .await
+                .unwrap(),
+            1
+        );
+        let remaining: Vec<_> = graph
+            .get_outgoing_edges(&prompt)
+            .await
+            .unwrap()
+            .into_iter()
+            .filter(|edge| edge.edge_type == crate::EdgeType::References)
+            .map(|edge| edge.to)
+            .collect();
+        assert_eq!(remaining.len(), 3);
+        assert!(!remaining.contains(&targets[1]));
+
+        let scheduler = scheduler.with_config(disabled());
+        assert_eq!(scheduler.interval(MaintenanceTask::PruneReferences), None);
+    }
+
+    #[tokio::test]
+    async fn test_dropped_handle_stops_loop() {
+        let dir = tempdir().unwrap();
+        let graph = Arc::new(
+            AsyncMemoryGraph::open(Config::new(dir.path()))
+                .await
+                .unwrap(),
+        );
+        let handle = graph.start_maintenance();
+        assert!(handle.is_running());
+        drop(handle);
+
+        let handle = MaintenanceScheduler::new(Arc::clone(&graph))
+            .with_config(disabled())
+            .with_flush_interval(0)
+            .start();
+        handle.stop().await;
+    }
+}
@@ -1,17 +1,68 @@
 //! Core engine for the memory graph
 
 mod async_memory_graph;
+mod bulk_load;
+mod context;
+mod dedup;
+mod dry_run;
+mod embedding;
+mod gc;
+mod knowledge;
+mod kv;
+mod language;
+mod legal_hold;
+mod maintenance;
+mod scoring;
+mod session_lock;
+mod session_title;
+mod shutdown;
+mod snapshot;
+
+pub use async_memory_graph::{AsyncMemoryGraph, MAX_IDEMPOTENCY_KEY_LEN};
+pub use bulk_load::{
+    ndjson_records, BulkLoadReport, BulkLoader, ImportRecord, DEFAULT_BULK_BATCH_SIZE,
+};
+pub use context::{estimated_tokens, ContextItem, ContextOptions};
+pub use dedup::{
+    cosine_similarity, NearDuplicate, NearDuplicateAction, NearDuplicateCheck,
+    NEAR_DUPLICATE_PROPERTY,
+};
+pub use dry_run::DryRunGraph;
+pub use embedding::{
+    Embedder, EmbeddingOptions, EmbeddingStats, HashEmbedder, HttpEmbedder,
+    DEFAULT_EMBEDDING_QUEUE_CAPACITY, DEFAULT_HASH_DIMENSIONS,
+};
+pub use gc::GcReport;
+pub use knowledge::{
+    Entity, EntityKnowledge, ExtractedEntity, ExtractedFact, Extraction, ExtractionReport,
+    Extractor, Fact, PatternExtractor, ENTITY_ALIAS_PREFIX, ENTITY_TYPE, FACT_ALIAS_PREFIX,
+    FACT_TYPE, VALID_UNTIL_PROPERTY,
+};
+pub use kv::{KvStore, MAX_KV_KEY_LEN};
+pub use language::{node_language, LanguageDetector, LANGUAGE_METADATA_KEY};
+#[cfg(feature = "language-detection")]
+pub use language::{WhatlangDetector, DEFAULT_MIN_LANGUAGE_CONFIDENCE};
+pub use legal_hold::{LegalHold, HOLD_PLACED_AT_KEY, HOLD_REASON_KEY, MAX_HOLD_REASON_LEN};
+pub use maintenance::{MaintenanceHandle, MaintenanceScheduler, MaintenanceTask};
+pub use scoring::{
+    score_property, HttpScorer, PiiScorer, ResponseScorer, Scores, ScoringOptions, ScoringStats,
+    DEFAULT_SCORING_QUEUE_CAPACITY, PII_SCORE, SCORE_PROPERTY_PREFIX, TOXICITY_SCORE,
+};
+pub use session_lock::{
+    SessionLock, LOCK_ACQUIRED_AT_KEY, LOCK_EXPIRES_AT_KEY, LOCK_OWNER_KEY, MAX_LOCK_OWNER_LEN,
+};
+pub use session_title::{FirstLineTitle, SessionTitleGenerator, DEFAULT_TITLE_LENGTH};
+pub use shutdown::{ShutdownReport, DEFAULT_SHUTDOWN_TIMEOUT};
+pub use snapshot::GraphSnapshot;
 
-pub use async_memory_graph::AsyncMemoryGraph;
-
-use crate::{Error, Result};
-use crate::storage::{SledBackend, StorageBackend};
+use crate::storage::{EdgePage, NodeDegree, SledBackend, StorageBackend};
 use crate::{
-    AgentNode, Config, ConversationSession, Edge, EdgeType, Node, NodeId, PromptMetadata,
-    PromptNode, PromptTemplate, ResponseMetadata, ResponseNode, SessionId, TemplateId, TokenUsage,
-    ToolInvocation,
+    AgentNode, Config, ConversationSession, Edge, EdgeId, EdgeType, IngestValidation, Node, NodeId,
+    PromptMetadata, PromptNode, PromptTemplate, ResponseMetadata, ResponseNode, SessionId,
+    TemplateId, TokenUsage, ToolInvocation,
 };
-use parking_lot::RwLock;
+use crate::{Error, Result};
+use parking_lot::{Mutex, RwLock};
 use std::collections::HashMap;
 use std::sync::Arc;
 
@@ -37,18 +88,27 @@ use std::sync::Arc;
 pub struct MemoryGraph {
     backend: Arc<dyn StorageBackend>,
     sessions: Arc<RwLock<HashMap<SessionId, ConversationSession>>>,
+    dedupe_prompts: bool,
+    ingest: IngestValidation,
+    /// Held from the duplicate check until the prompt is stored when
+    /// prompts are deduplicated
+    prompt_writes: Mutex<()>,
 }
 
 impl MemoryGraph {
     /// Open or create a memory graph with the given configuration
     ///
     /// This will create the database directory if it doesn't exist and initialize
-    /// all necessary storage trees.
+    /// all necessary storage trees. Storage is opened as
+    /// [`AsyncMemoryGraph`] opens it, so both read the same compression and
+    /// cold tier settings.
     ///
     /// # Errors
     ///
     /// Returns an error if:
     /// - The database path is invalid or inaccessible
+    /// - Another process holds the database for longer than
+    ///   `config.wait_for_lock_ms`
     /// - Storage initialization fails
     /// - Existing data is corrupted
     ///
@@ -64,14 +124,39 @@ impl MemoryGraph {
     /// # }
     /// ```
     pub fn open(config: Config) -> Result<Self> {
-        let backend = SledBackend::open(&config.path)?;
+        let backend = SledBackend::open_with_config(&config)?;
 
         Ok(Self {
             backend: Arc::new(backend),
             sessions: Arc::new(RwLock::new(HashMap::new())),
+            dedupe_prompts: config.dedupe_prompts,
+            ingest: config.ingest,
+            prompt_writes: Mutex::new(()),
         })
     }
 
+    /// Open one namespace of the database, a graph isolated from every other
+    /// namespace stored alongside it
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the name is not a valid namespace name or the
+    /// database cannot be opened.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use llm_memory_graph::{MemoryGraph, Config};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let graph = MemoryGraph::open_namespace(Config::new("./data/graph.db"), "project-a")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn open_namespace(config: Config, namespace: &str) -> Result<Self> {
+        Self::open(config.with_namespace(namespace))
+    }
+
     /// Create a new conversation session
     ///
     /// Sessions are used to group related prompts and responses together.
@@ -181,9 +266,14 @@ impl MemoryGraph {
     /// This creates a new prompt node and automatically creates edges linking it
     /// to the session and to the previous prompt if one exists.
     ///
+    /// With [`Config::dedupe_prompts`] enabled, submitting content identical to
+    /// a prompt already in the session returns the existing prompt's ID
+    /// instead of storing a copy; metadata of the new submission is ignored.
+    ///
     /// # Errors
     ///
     /// Returns an error if:
+    /// - The content or metadata fail the [`Config::ingest`] checks
     /// - The session doesn't exist
     /// - Storage operations fail
     ///
@@ -208,9 +298,28 @@ impl MemoryGraph {
         content: String,
         metadata: Option<PromptMetadata>,
     ) -> Result<NodeId> {
+        let mut content = content;
+        self.ingest.check_content("prompt content", &mut content)?;
+        self.ingest.check_metadata(
+            "prompt metadata",
+            metadata.as_ref().map_or(0, |m| m.custom.len()),
+        )?;
+
         // Verify session exists
         self.get_session(session_id)?;
 
+        let _dedupe = self.dedupe_prompts.then(|| self.prompt_writes.lock());
+        let session_nodes = self.backend.get_session_nodes(&session_id)?;
+        if self.dedupe_prompts {
+            let existing = session_nodes.iter().find_map(|node| match node {
+                Node::Prompt(prompt) if prompt.content == content => Some(prompt.id),
+                _ => None,
+            });
+            if let Some(existing) = existing {
+                return Ok(existing);
+            }
+        }
+
         let prompt = if let Some(meta) = metadata {
             PromptNode::with_metadata(session_id, content, meta)
         } else {
@@ -221,7 +330,6 @@ impl MemoryGraph {
         self.backend.store_node(&Node::Prompt(prompt.clone()))?;
 
         // Create edge from prompt to session
-        let session_nodes = self.backend.get_session_nodes(&session_id)?;
         if let Some(session_node) = session_nodes.iter().find(|n| matches!(n, Node::Session(_))) {
             let edge = Edge::new(prompt_id, session_node.id(), EdgeType::PartOf);
             self.backend.store_edge(&edge)?;
@@ -245,7 +353,7 @@ impl MemoryGraph {
                 .into_iter()
                 .filter(|p| p.id != prompt_id)
                 .collect();
-            previous_prompts.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+            previous_prompts.sort_by_key(|p| std::cmp::Reverse(p.timestamp));
 
             if let Some(prev_prompt) = previous_prompts.first() {
                 let edge = Edge::new(prompt_id, prev_prompt.id, EdgeType::Follows);
@@ -263,6 +371,8 @@ impl MemoryGraph {
     /// # Errors
     ///
     /// Returns an error if:
+    /// - The content, metadata or token usage fail the [`Config::ingest`]
+    ///   checks
     /// - The prompt doesn't exist
     /// - Storage operations fail
     ///
@@ -291,8 +401,23 @@ impl MemoryGraph {
         usage: TokenUsage,
         metadata: Option<ResponseMetadata>,
     ) -> Result<NodeId> {
+        let mut content = content;
+        self.ingest
+            .check_content("response content", &mut content)?;
+        self.ingest.check_metadata(
+            "response metadata",
+            metadata.as_ref().map_or(0, |m| m.custom.len()),
+        )?;
+
         // Verify prompt exists
-        self.get_node(prompt_id)?;
+        let prompt = self.get_node(prompt_id)?;
+        if self.ingest.check_token_usage {
+            let max_tokens = match prompt {
+                Node::Prompt(prompt) => prompt.metadata.max_tokens,
+                _ => None,
+            };
+            self.ingest.check_token_usage(&usage, max_tokens)?;
+        }
 
         let response = if let Some(meta) = metadata {
             ResponseNode::with_metadata(prompt_id, content, usage, meta)
@@ -496,7 +621,7 @@ impl MemoryGraph {
     /// # let agent = AgentNode::new("Test".to_string(), "test".to_string(), vec![]);
     /// # let node_id = graph.add_agent(agent)?;
     /// let node = graph.get_node(node_id)?;
-    /// if let llm_memory_graph::types::Node::Agent(mut agent) = node {
+    /// if let llm_memory_graph::Node::Agent(mut agent) = node {
     ///     agent.update_metrics(true, 250, 150);
     ///     graph.update_agent(agent)?;
     /// }
@@ -689,6 +814,23 @@ impl MemoryGraph {
         Ok(())
     }
 
+    /// Create an edge with a traversal weight, see [`Edge::weight`]
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if storage fails.
+    pub fn add_weighted_edge(
+        &self,
+        from: NodeId,
+        to: NodeId,
+        edge_type: EdgeType,
+        weight: f64,
+    ) -> Result<EdgeId> {
+        let edge = Edge::new(from, to, edge_type).with_weight(weight);
+        self.backend.store_edge(&edge)?;
+        Ok(edge.id)
+    }
+
     /// Get all edges originating from a node
     ///
     /// # Errors
@@ -733,6 +875,69 @@ impl MemoryGraph {
         self.backend.get_incoming_edges(&node_id)
     }
 
+    /// Get up to `limit` edges originating from a node, starting after `cursor`
+    ///
+    /// Pass the previous page's `next_cursor` to continue; the last page has
+    /// no cursor.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if storage retrieval fails.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use llm_memory_graph::{MemoryGraph, Config};
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let graph = MemoryGraph::open(Config::default())?;
+    /// # let session = graph.create_session()?;
+    /// # let prompt_id = graph.add_prompt(session.id, "Test".to_string(), None)?;
+    /// let mut cursor = None;
+    /// loop {
+    ///     let page = graph.get_outgoing_edges_page(prompt_id, cursor.as_ref(), 500)?;
+    ///     // process page.edges
+    ///     match page.next_cursor {
+    ///         Some(next) => cursor = Some(next),
+    ///         None => break,
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn get_outgoing_edges_page(
+        &self,
+        node_id: NodeId,
+        cursor: Option<&EdgeId>,
+        limit: usize,
+    ) -> Result<EdgePage> {
+        self.backend
+            .get_outgoing_edges_page(&node_id, cursor, limit)
+    }
+
+    /// Get up to `limit` edges pointing to a node, starting after `cursor`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if storage retrieval fails.
+    pub fn get_incoming_edges_page(
+        &self,
+        node_id: NodeId,
+        cursor: Option<&EdgeId>,
+        limit: usize,
+    ) -> Result<EdgePage> {
+        self.backend
+            .get_incoming_edges_page(&node_id, cursor, limit)
+    }
+
+    /// Count the edges from and to a node without loading them
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if storage retrieval fails.
+    pub fn node_degree(&self, node_id: NodeId) -> Result<NodeDegree> {
+        self.backend.node_degree(&node_id)
+    }
+
     /// Get all nodes in a session
     ///
     /// # Errors
@@ -1026,6 +1231,7 @@ impl MemoryGraph {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::time::Duration;
     use tempfile::tempdir;
 
     #[test]
@@ -1160,4 +1366,157 @@ mod tests {
 
         assert_eq!(retrieved.metadata.get("user"), Some(&"alice".to_string()));
     }
+
+    #[test]
+    fn test_open_waits_for_lock() {
+        let dir = tempdir().unwrap();
+        let graph = MemoryGraph::open(Config::new(dir.path())).unwrap();
+        assert!(matches!(
+            MemoryGraph::open(Config::new(dir.path())),
+            Err(Error::DatabaseLocked { .. })
+        ));
+
+        let release = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(100));
+            drop(graph);
+        });
+        MemoryGraph::open(Config::new(dir.path()).with_wait_for_lock(5_000)).unwrap();
+        release.join().unwrap();
+    }
+
+    #[test]
+    fn test_node_compression() {
+        use crate::{CompressionAlgorithm, CompressionPolicy};
+
+        let dir = tempdir().unwrap();
+        let graph = MemoryGraph::open(
+            Config::new(dir.path()).with_compression_policy(
+                CompressionPolicy::new(CompressionAlgorithm::Zstd)
+                    .with_min_bytes(256)
+                    .with_node_type(crate::NodeType::Response),
+            ),
+        )
+        .unwrap();
+
+        let session = graph.create_session().unwrap();
+        let prompt_id = graph
+            .add_prompt(session.id, "Write a long story".to_string(), None)
+            .unwrap();
+        let story = "Once upon a time. ".repeat(500);
+        let response_id = graph
+            .add_response(prompt_id, story.clone(), TokenUsage::new(4, 900), None)
+            .unwrap();
+
+        let stats = graph.stats().unwrap();
+        assert_eq!(stats.compression.nodes_compressed, 1);
+        assert!(stats.compression.ratio() < 0.5);
+        match graph.get_node(response_id).unwrap() {
+            Node::Response(response) => assert_eq!(response.content, story),
+            other => panic!("expected the response, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reads_cold_tier() {
+        let dir = tempdir().unwrap();
+        let cold_dir = tempdir().unwrap();
+        let config = Config::new(dir.path())
+            .with_cold_storage(crate::ColdStorageConfig::new().with_path(cold_dir.path()));
+
+        let (session_id, prompt_id) = {
+            let graph = AsyncMemoryGraph::open(config.clone()).await.unwrap();
+            let session = graph.create_session().await.unwrap();
+            let prompt_id = graph
+                .add_prompt(session.id, "Old".to_string(), None)
+                .await
+                .unwrap();
+            tokio::time::sleep(Duration::from_millis(2)).await;
+            let report = graph.tier_cold(chrono::Duration::zero()).await.unwrap();
+            assert_eq!(report.nodes_moved, 2);
+            graph.close().await.unwrap();
+            (session.id, prompt_id)
+        };
+
+        let graph = MemoryGraph::open(config).unwrap();
+        assert_eq!(graph.get_node(prompt_id).unwrap().id(), prompt_id);
+        assert_eq!(graph.get_session_nodes(session_id).unwrap().len(), 2);
+        assert_eq!(graph.get_outgoing_edges(prompt_id).unwrap().len(), 1);
+        assert_eq!(graph.stats().unwrap().node_count, 2);
+    }
+
+    #[test]
+    fn test_dedupe_prompts() {
+        let dir = tempdir().unwrap();
+        let graph = MemoryGraph::open(Config::new(dir.path()).with_dedupe_prompts(true)).unwrap();
+        let session = graph.create_session().unwrap();
+        let other = graph.create_session().unwrap();
+
+        let first = graph
+            .add_prompt(session.id, "Hello".to_string(), None)
+            .unwrap();
+        let again = graph
+            .add_prompt(session.id, "Hello".to_string(), None)
+            .unwrap();
+        assert_eq!(first, again);
+        let elsewhere = graph
+            .add_prompt(other.id, "Hello".to_string(), None)
+            .unwrap();
+        assert_ne!(first, elsewhere);
+        let prompts = graph
+            .get_session_nodes(session.id)
+            .unwrap()
+            .into_iter()
+            .filter(|node| matches!(node, Node::Prompt(_)))
+            .count();
+        assert_eq!(prompts, 1);
+    }
+
+    #[test]
+    fn test_ingest_validation() {
+        use crate::IngestViolation;
+
+        let dir = tempdir().unwrap();
+        let graph = MemoryGraph::open(
+            Config::new(dir.path()).with_ingest_validation(
+                IngestValidation::new()
+                    .with_sanitize_text(true)
+                    .with_reject_control_chars(true)
+                    .with_max_content_bytes(64)
+                    .with_token_usage_checks(true),
+            ),
+        )
+        .unwrap();
+        let session = graph.create_session().unwrap();
+
+        let prompt_id = graph
+            .add_prompt(
+                session.id,
+                "\u{FEFF}Hello".to_string(),
+                Some(PromptMetadata {
+                    max_tokens: Some(50),
+                    ..PromptMetadata::default()
+                }),
+            )
+            .unwrap();
+        match graph.get_node(prompt_id).unwrap() {
+            Node::Prompt(prompt) => assert_eq!(prompt.content, "Hello"),
+            other => panic!("expected prompt, got {other:?}"),
+        }
+
+        assert!(matches!(
+            graph.add_prompt(session.id, "bell\u{7}".to_string(), None),
+            Err(Error::InvalidIngest {
+                field: "prompt content",
+                violation: IngestViolation::ControlCharacter { .. },
+            })
+        ));
+        assert!(graph.add_prompt(session.id, "x".repeat(65), None).is_err());
+        assert!(graph
+            .add_response(prompt_id, "x".repeat(65), TokenUsage::new(1, 1), None)
+            .is_err());
+        assert!(graph
+            .add_response(prompt_id, "Hi".to_string(), TokenUsage::new(5, 60), None)
+            .is_err());
+        assert_eq!(graph.get_session_nodes(session.id).unwrap().len(), 2);
+    }
 }
@@ -0,0 +1,497 @@
+//! Response scores computed after write
+//!
+//! With [`ResponseScorer`]s installed through
+//! [`AsyncMemoryGraph::set_response_scorers`](super::AsyncMemoryGraph::set_response_scorers),
+//! every response is queued for scoring once it is stored. A single
+//! background task takes responses off the queue, runs each scorer, retrying
+//! failed calls with exponential backoff, and stores every score as a number
+//! between zero and one in the response's properties under
+//! [`score_property`], such as `score.toxicity`. Writes only wait for the
+//! scorers when the queue is full.
+//!
+//! [`HttpScorer`] calls a classifier served over HTTP, such as a toxicity
+//! model; [`PiiScorer`] looks for emails, phone numbers, card numbers and
+//! similar PII locally with the
+//! [default redaction rules](crate::anonymize::RedactionRule::defaults). Any
+//! other model is plugged in by implementing [`ResponseScorer`].
+//!
+//! Stored scores are filtered on with
+//! [`AsyncQueryBuilder::max_score`](crate::query::AsyncQueryBuilder::max_score)
+//! and its shorthands, to curate datasets from responses known to be clean.
+//! Responses stored before a scorer was installed are queued by
+//! [`backfill_scores`](super::AsyncMemoryGraph::backfill_scores).
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use llm_memory_graph::engine::{AsyncMemoryGraph, HttpScorer, PiiScorer};
+//! use llm_memory_graph::{Config, NodeType, TokenUsage};
+//! use std::sync::Arc;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let graph = AsyncMemoryGraph::open(Config::default()).await?;
+//! let toxicity = HttpScorer::new("http://localhost:8080/score", "detoxify")?;
+//! graph.set_response_scorers(vec![Arc::new(toxicity), Arc::new(PiiScorer::new())]);
+//!
+//! let session = graph.create_session().await?;
+//! let prompt_id = graph.add_prompt(session.id, "Hi".to_string(), None).await?;
+//! graph
+//!     .add_response(prompt_id, "Hello!".to_string(), TokenUsage::new(1, 1), None)
+//!     .await?;
+//!
+//! graph.wait_for_scores().await;
+//! let clean = graph
+//!     .query()
+//!     .session(session.id)
+//!     .node_type(NodeType::Response)
+//!     .max_toxicity(0.3)
+//!     .max_pii(0.0)
+//!     .execute()
+//!     .await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::anonymize::RedactionRule;
+use crate::audit::{AuditEntry, AuditOperation};
+use crate::storage::{AsyncStorageBackend, StorageCache};
+use crate::{Error, NodeId, Result};
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot, watch};
+
+/// Default number of responses waiting for the scorers before writes block
+pub const DEFAULT_SCORING_QUEUE_CAPACITY: usize = 1_024;
+
+/// Prefix of the properties scores are stored under
+pub const SCORE_PROPERTY_PREFIX: &str = "score.";
+
+/// Label of toxicity scores
+pub const TOXICITY_SCORE: &str = "toxicity";
+
+/// Label of PII scores
+pub const PII_SCORE: &str = "pii";
+
+/// Property the score labelled `label` is stored under
+pub fn score_property(label: &str) -> String {
+    format!("{SCORE_PROPERTY_PREFIX}{label}")
+}
+
+/// Scores of one response by label, each between zero and one
+pub type Scores = BTreeMap<String, f64>;
+
+/// Scores response text, such as for toxicity or PII
+#[async_trait]
+pub trait ResponseScorer: Send + Sync {
+    /// Name of the scorer, used in logs
+    fn name(&self) -> &str;
+
+    /// Scores of `text` by label
+    async fn score(&self, text: &str) -> Result<Scores>;
+}
+
+/// How the scoring queue runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScoringOptions {
+    /// Responses waiting for the scorers before writes block
+    pub queue_capacity: usize,
+    /// Calls made to one scorer for one response before it is given up on
+    pub max_attempts: u32,
+    /// Delay before the first retry, doubled for each further retry
+    pub retry_backoff: Duration,
+}
+
+impl Default for ScoringOptions {
+    fn default() -> Self {
+        Self {
+            queue_capacity: DEFAULT_SCORING_QUEUE_CAPACITY,
+            max_attempts: 3,
+            retry_backoff: Duration::from_millis(200),
+        }
+    }
+}
+
+/// Counts of the scoring queue's work since the scorers were installed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScoringStats {
+    /// Responses queued
+    pub queued: u64,
+    /// Responses with scores stored
+    pub scored: u64,
+    /// Scorer calls given up on after every attempt failed
+    pub failed: u64,
+    /// Scorer calls repeated after a failure
+    pub retries: u64,
+}
+
+#[derive(Default)]
+struct ScoringCounters {
+    queued: AtomicU64,
+    scored: AtomicU64,
+    failed: AtomicU64,
+    retries: AtomicU64,
+}
+
+/// Graph state the scoring task writes to
+pub(super) struct ScoringSink {
+    pub(super) backend: Arc<dyn AsyncStorageBackend>,
+    pub(super) cache: StorageCache,
+    pub(super) audit_log: bool,
+}
+
+enum Job {
+    Score { node_id: NodeId, text: String },
+    Barrier(oneshot::Sender<()>),
+}
+
+/// Sending side of a running scoring queue
+#[derive(Clone)]
+pub(super) struct ScoringQueue {
+    jobs: mpsc::Sender<Job>,
+    counters: Arc<ScoringCounters>,
+}
+
+impl ScoringQueue {
+    /// Spawn the task scoring queued responses into the sink's backend
+    ///
+    /// The task holds `closing` until it exits; once it changes, responses
+    /// already queued are scored and the task stops.
+    pub(super) fn start(
+        scorers: Vec<Arc<dyn ResponseScorer>>,
+        options: ScoringOptions,
+        sink: ScoringSink,
+        mut closing: watch::Receiver<bool>,
+    ) -> Self {
+        let (jobs, mut receiver) = mpsc::channel(options.queue_capacity.max(1));
+        let counters = Arc::new(ScoringCounters::default());
+        let queue = Self {
+            jobs,
+            counters: Arc::clone(&counters),
+        };
+
+        tokio::spawn(async move {
+            let worker = Worker {
+                scorers,
+                options,
+                sink,
+                counters,
+            };
+            loop {
+                tokio::select! {
+                    job = receiver.recv() => match job {
+                        Some(job) => worker.run(job).await,
+                        None => break,
+                    },
+                    _ = closing.changed() => {
+                        receiver.close();
+                        while let Some(job) = receiver.recv().await {
+                            worker.run(job).await;
+                        }
+                        break;
+                    }
+                }
+            }
+        });
+
+        queue
+    }
+
+    /// Queue a response's `text` for scoring, waiting while the queue is full
+    pub(super) async fn push(&self, node_id: NodeId, text: String) {
+        if self.jobs.send(Job::Score { node_id, text }).await.is_ok() {
+            self.counters.queued.fetch_add(1, Ordering::Relaxed);
+        } else {
+            tracing::debug!("Scoring queue stopped; node {} not scored", node_id);
+        }
+    }
+
+    /// Wait until every response queued so far has been scored or given up on
+    pub(super) async fn drain(&self) {
+        let (done, finished) = oneshot::channel();
+        if self.jobs.send(Job::Barrier(done)).await.is_ok() {
+            let _ = finished.await;
+        }
+    }
+
+    pub(super) fn stats(&self) -> ScoringStats {
+        ScoringStats {
+            queued: self.counters.queued.load(Ordering::Relaxed),
+            scored: self.counters.scored.load(Ordering::Relaxed),
+            failed: self.counters.failed.load(Ordering::Relaxed),
+            retries: self.counters.retries.load(Ordering::Relaxed),
+        }
+    }
+}
+
+struct Worker {
+    scorers: Vec<Arc<dyn ResponseScorer>>,
+    options: ScoringOptions,
+    sink: ScoringSink,
+    counters: Arc<ScoringCounters>,
+}
+
+impl Worker {
+    async fn run(&self, job: Job) {
+        match job {
+            Job::Score { node_id, text } => {
+                let mut scores = Scores::new();
+                for scorer in &self.scorers {
+                    if let Some(found) = self.score(scorer.as_ref(), node_id, &text).await {
+                        scores.extend(found);
+                    }
+                }
+                if scores.is_empty() {
+                    return;
+                }
+                match self.store(node_id, &scores).await {
+                    Ok(true) => {
+                        self.counters.scored.fetch_add(1, Ordering::Relaxed);
+                    }
+                    Ok(false) => {}
+                    Err(e) => tracing::warn!("Storing scores of node {} failed: {}", node_id, e),
+                }
+            }
+            Job::Barrier(done) => {
+                let _ = done.send(());
+            }
+        }
+    }
+
+    /// Scores of `text` from one scorer, retried on failure
+    async fn score(
+        &self,
+        scorer: &dyn ResponseScorer,
+        node_id: NodeId,
+        text: &str,
+    ) -> Option<Scores> {
+        let mut backoff = self.options.retry_backoff;
+        let mut attempt = 1;
+        loop {
+            match scorer.score(text).await {
+                Ok(scores) => return Some(scores),
+                Err(e) if attempt >= self.options.max_attempts => {
+                    self.counters.failed.fetch_add(1, Ordering::Relaxed);
+                    tracing::warn!(
+                        "Giving up scoring node {} with {} after {} attempts: {}",
+                        node_id,
+                        scorer.name(),
+                        attempt,
+                        e
+                    );
+                    return None;
+                }
+                Err(e) => {
+                    tracing::debug!(
+                        "Scoring node {} with {} failed, retrying: {}",
+                        node_id,
+                        scorer.name(),
+                        e
+                    );
+                    self.counters.retries.fetch_add(1, Ordering::Relaxed);
+                    tokio::time::sleep(backoff).await;
+                    backoff = backoff.saturating_mul(2);
+                    attempt += 1;
+                }
+            }
+        }
+    }
+
+    /// Store `scores` in the node's properties, returning false if the node
+    /// no longer exists
+    async fn store(&self, node_id: NodeId, scores: &Scores) -> Result<bool> {
+        let backend = &self.sink.backend;
+        let Some(mut node) = backend.get_node(&node_id).await? else {
+            return Ok(false);
+        };
+        let properties = node.properties_mut();
+        for (label, score) in scores {
+            properties.insert(score_property(label), serde_json::json!(score));
+        }
+        backend.store_node(&node).await?;
+        self.sink.cache.invalidate_node(&node_id).await;
+
+        if self.sink.audit_log {
+            for label in scores.keys() {
+                backend
+                    .append_audit_entry(
+                        AuditEntry::new(AuditOperation::SetProperty, None)
+                            .with_node(node_id)
+                            .with_detail("key", score_property(label)),
+                    )
+                    .await?;
+            }
+        }
+        Ok(true)
+    }
+}
+
+/// Scorer calling a classifier served over HTTP
+///
+/// Posts `{"input": ...}` to the URL and reads the scores from the `scores`
+/// object of the response, or from the numeric fields of the response itself
+/// if it has none, so `{"toxicity": 0.02}` and
+/// `{"scores": {"toxicity": 0.02}}` both work.
+pub struct HttpScorer {
+    client: Client,
+    url: String,
+    name: String,
+    api_key: Option<String>,
+}
+
+impl HttpScorer {
+    /// Scorer named `name` posting to `url`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the HTTP client cannot be created.
+    pub fn new(url: impl Into<String>, name: impl Into<String>) -> Result<Self> {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(30))
+            .connect_timeout(Duration::from_secs(10))
+            .build()
+            .map_err(|e| Error::IntegrationError(e.to_string()))?;
+        Ok(Self {
+            client,
+            url: url.into(),
+            name: name.into(),
+            api_key: None,
+        })
+    }
+
+    /// Send `key` as a bearer token
+    pub fn with_api_key(mut self, key: impl Into<String>) -> Self {
+        self.api_key = Some(key.into());
+        self
+    }
+}
+
+/// Numeric fields of a scoring response, from its `scores` object if it has
+/// one
+fn parse_scores(body: &serde_json::Value) -> Result<Scores> {
+    let fields = body
+        .get("scores")
+        .unwrap_or(body)
+        .as_object()
+        .ok_or_else(|| Error::IntegrationError("scoring response is not an object".to_string()))?;
+    Ok(fields
+        .iter()
+        .filter_map(|(label, score)| Some((label.clone(), score.as_f64()?)))
+        .collect())
+}
+
+#[async_trait]
+impl ResponseScorer for HttpScorer {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn score(&self, text: &str) -> Result<Scores> {
+        let mut request = self
+            .client
+            .post(&self.url)
+            .json(&serde_json::json!({ "input": text }));
+        if let Some(key) = &self.api_key {
+            request = request.bearer_auth(key);
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| Error::IntegrationError(format!("scoring request failed: {e}")))?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::IntegrationError(format!(
+                "scoring API returned {status}: {body}"
+            )));
+        }
+
+        let body: serde_json::Value = response
+            .json()
+            .await
+            .map_err(|e| Error::IntegrationError(format!("invalid scoring response: {e}")))?;
+        parse_scores(&body)
+    }
+}
+
+/// Local scorer giving a [`PII_SCORE`] of one to text containing PII and
+/// zero otherwise
+///
+/// PII is whatever its [`RedactionRule`]s match, by default emails, card
+/// numbers, US social security numbers, phone numbers and IPv4 addresses.
+#[derive(Debug, Clone)]
+pub struct PiiScorer {
+    rules: Vec<RedactionRule>,
+}
+
+impl PiiScorer {
+    /// Scorer using the [default redaction rules](RedactionRule::defaults)
+    pub fn new() -> Self {
+        Self::with_rules(RedactionRule::defaults())
+    }
+
+    /// Scorer treating matches of `rules` as PII
+    pub fn with_rules(rules: Vec<RedactionRule>) -> Self {
+        Self { rules }
+    }
+
+    /// PII score of `text`, computed synchronously
+    pub fn pii_score(&self, text: &str) -> f64 {
+        if self.rules.iter().any(|rule| rule.is_match(text)) {
+            1.0
+        } else {
+            0.0
+        }
+    }
+}
+
+impl Default for PiiScorer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl ResponseScorer for PiiScorer {
+    fn name(&self) -> &'static str {
+        "pii"
+    }
+
+    async fn score(&self, text: &str) -> Result<Scores> {
+        Ok(Scores::from([(
+            PII_SCORE.to_string(),
+            self.pii_score(text),
+        )]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pii_scorer() {
+        let scorer = PiiScorer::new();
+        assert_eq!(scorer.pii_score("Write to jane.doe@example.com"), 1.0);
+        assert_eq!(scorer.pii_score("Call (555) 123-4567"), 1.0);
+        assert_eq!(scorer.pii_score("Order 42 shipped"), 0.0);
+        assert_eq!(PiiScorer::with_rules(Vec::new()).pii_score("a@b.io"), 0.0);
+    }
+
+    #[test]
+    fn test_parse_scores() {
+        let nested = serde_json::json!({ "scores": { "toxicity": 0.25 }, "model": "x" });
+        assert_eq!(
+            parse_scores(&nested).unwrap(),
+            Scores::from([("toxicity".to_string(), 0.25)])
+        );
+        let flat = serde_json::json!({ "toxicity": 0.5, "insult": 0.1, "model": "x" });
+        assert_eq!(parse_scores(&flat).unwrap().len(), 2);
+        assert!(parse_scores(&serde_json::json!([0.5])).is_err());
+    }
+}
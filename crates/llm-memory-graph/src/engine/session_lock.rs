@@ -0,0 +1,112 @@
+//! Advisory locks on sessions
+//!
+//! Agents that share a session take turns with
+//! [`lock_session`](super::AsyncMemoryGraph::lock_session): the lock names
+//! its owner and expires after a TTL, so a crashed agent cannot hold a session
+//! forever. Taking a lock the caller already holds renews it. Locks are
+//! advisory: the graph does not stop anyone from writing to a locked session,
+//! it only tells cooperating agents who currently owns it.
+//!
+//! The lock is stored in the session's metadata under [`LOCK_OWNER_KEY`],
+//! [`LOCK_ACQUIRED_AT_KEY`] and [`LOCK_EXPIRES_AT_KEY`], so it survives
+//! restarts and shows up wherever sessions are listed or exported. Taking and
+//! releasing locks publish `SessionLocked` and `SessionUnlocked` events.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use llm_memory_graph::engine::AsyncMemoryGraph;
+//! use llm_memory_graph::Config;
+//! use std::time::Duration;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let graph = AsyncMemoryGraph::open(Config::default()).await?;
+//! let session = graph.create_session().await?;
+//!
+//! graph.lock_session(session.id, "planner", Duration::from_secs(30)).await?;
+//! assert!(graph.lock_session(session.id, "executor", Duration::from_secs(30)).await.is_err());
+//!
+//! graph.add_prompt(session.id, "Plan the trip".to_string(), None).await?;
+//! graph.unlock_session(session.id, "planner").await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{ConversationSession, SessionId};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Session metadata key holding the lock owner
+pub const LOCK_OWNER_KEY: &str = "lock_owner";
+
+/// Session metadata key holding when the lock was taken, in RFC 3339
+pub const LOCK_ACQUIRED_AT_KEY: &str = "lock_acquired_at";
+
+/// Session metadata key holding when the lock expires, in RFC 3339
+pub const LOCK_EXPIRES_AT_KEY: &str = "lock_expires_at";
+
+/// Longest lock owner accepted, in bytes
+pub const MAX_LOCK_OWNER_LEN: usize = 256;
+
+/// An advisory lock held on a session
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionLock {
+    /// The locked session
+    pub session_id: SessionId,
+    /// Who holds the lock
+    pub owner: String,
+    /// When the owner first took the lock; renewing keeps it
+    pub acquired_at: DateTime<Utc>,
+    /// When the lock lapses unless renewed
+    pub expires_at: DateTime<Utc>,
+}
+
+impl SessionLock {
+    /// The lock recorded in `session`'s metadata, expired or not
+    ///
+    /// Returns `None` if the session is not locked or the recorded lock is
+    /// unreadable.
+    pub fn from_session(session: &ConversationSession) -> Option<Self> {
+        let timestamp = |key: &str| {
+            session
+                .metadata
+                .get(key)
+                .and_then(|value| DateTime::parse_from_rfc3339(value).ok())
+                .map(|at| at.with_timezone(&Utc))
+        };
+        Some(Self {
+            session_id: session.id,
+            owner: session.metadata.get(LOCK_OWNER_KEY)?.clone(),
+            acquired_at: timestamp(LOCK_ACQUIRED_AT_KEY)?,
+            expires_at: timestamp(LOCK_EXPIRES_AT_KEY)?,
+        })
+    }
+
+    /// Whether the lock has lapsed at `now`
+    pub fn is_expired_at(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at <= now
+    }
+
+    /// Record the lock in session metadata
+    pub(super) fn write_to(&self, metadata: &mut HashMap<String, String>) {
+        metadata.insert(LOCK_OWNER_KEY.to_string(), self.owner.clone());
+        metadata.insert(
+            LOCK_ACQUIRED_AT_KEY.to_string(),
+            self.acquired_at.to_rfc3339(),
+        );
+        metadata.insert(
+            LOCK_EXPIRES_AT_KEY.to_string(),
+            self.expires_at.to_rfc3339(),
+        );
+    }
+
+    /// Remove any lock from session metadata, returning whether one was there
+    pub(super) fn clear(metadata: &mut HashMap<String, String>) -> bool {
+        let mut cleared = false;
+        for key in [LOCK_OWNER_KEY, LOCK_ACQUIRED_AT_KEY, LOCK_EXPIRES_AT_KEY] {
+            cleared |= metadata.remove(key).is_some();
+        }
+        cleared
+    }
+}
@@ -0,0 +1,99 @@
+//! Human-readable session titles
+//!
+//! With a [`SessionTitleGenerator`] installed through
+//! [`AsyncMemoryGraph::set_title_generator`](super::AsyncMemoryGraph::set_title_generator),
+//! the graph titles an untitled session when its first prompt arrives.
+//! [`FirstLineTitle`] uses the opening words of the prompt; any closure taking
+//! the prompt text works too, for example one that asks a model for a summary.
+
+/// Produces a session title from the session's first prompt
+pub trait SessionTitleGenerator: Send + Sync {
+    /// Title for a session whose first prompt is `prompt`, or `None` to leave
+    /// the session untitled and try again with the next prompt
+    fn generate(&self, prompt: &str) -> Option<String>;
+}
+
+impl<F> SessionTitleGenerator for F
+where
+    F: Fn(&str) -> Option<String> + Send + Sync,
+{
+    fn generate(&self, prompt: &str) -> Option<String> {
+        self(prompt)
+    }
+}
+
+/// Default maximum title length, in characters
+pub const DEFAULT_TITLE_LENGTH: usize = 60;
+
+/// Titles a session with the first line of its first prompt, shortened at a
+/// word boundary
+#[derive(Debug, Clone, Copy)]
+pub struct FirstLineTitle {
+    max_chars: usize,
+}
+
+impl FirstLineTitle {
+    /// Generator producing titles of at most [`DEFAULT_TITLE_LENGTH`] characters
+    pub fn new() -> Self {
+        Self {
+            max_chars: DEFAULT_TITLE_LENGTH,
+        }
+    }
+
+    /// Set the maximum title length, in characters
+    pub fn with_max_chars(mut self, max_chars: usize) -> Self {
+        self.max_chars = max_chars.max(1);
+        self
+    }
+}
+
+impl Default for FirstLineTitle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SessionTitleGenerator for FirstLineTitle {
+    fn generate(&self, prompt: &str) -> Option<String> {
+        let line = prompt
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty())?;
+        let line = line.split_whitespace().collect::<Vec<_>>().join(" ");
+        if line.chars().count() <= self.max_chars {
+            return Some(line);
+        }
+
+        let cut: String = line
+            .chars()
+            .take(self.max_chars.saturating_sub(1))
+            .collect();
+        let shortened = match cut.rfind(' ') {
+            Some(space) if space > 0 => &cut[..space],
+            _ => cut.as_str(),
+        };
+        Some(format!("{}…", shortened.trim_end()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_first_line_title() {
+        let titles = FirstLineTitle::new().with_max_chars(20);
+        assert_eq!(
+            titles.generate("\n  How do I   sort a Vec?\nMore detail"),
+            Some("How do I sort a Vec?".to_string())
+        );
+        assert_eq!(
+            titles.generate("Explain the borrow checker in detail"),
+            Some("Explain the borrow…".to_string())
+        );
+        assert_eq!(titles.generate("   \n "), None);
+
+        let custom = |prompt: &str| Some(prompt.to_uppercase());
+        assert_eq!(custom.generate("hi"), Some("HI".to_string()));
+    }
+}
@@ -0,0 +1,59 @@
+//! Outcome of closing an [`AsyncMemoryGraph`](super::AsyncMemoryGraph)
+
+use std::time::Duration;
+
+/// Default time [`close`](super::AsyncMemoryGraph::close) waits for background
+/// tasks and in-flight events
+pub const DEFAULT_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// What happened while closing a graph
+///
+/// Storage is always flushed before the report is returned; the remaining
+/// fields describe work that happens outside the database and could not be
+/// completed within the shutdown timeout.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ShutdownReport {
+    /// Background tasks (such as maintenance loops) that stopped
+    pub background_tasks_stopped: usize,
+    /// Background tasks still running when the timeout expired
+    pub background_tasks_pending: usize,
+    /// Observatory events delivered while draining
+    pub events_drained: usize,
+    /// Observatory events still in flight when the timeout expired
+    pub events_pending: usize,
+    /// Error returned by the Observatory publisher's flush, if any
+    pub observatory_error: Option<String>,
+    /// Nodes and edges dropped from the read cache
+    pub cache_entries_cleared: u64,
+    /// Time taken to close
+    pub duration: Duration,
+}
+
+impl ShutdownReport {
+    /// Whether everything was stopped, delivered and flushed
+    pub fn is_clean(&self) -> bool {
+        self.background_tasks_pending == 0
+            && self.events_pending == 0
+            && self.observatory_error.is_none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_clean() {
+        assert!(ShutdownReport::default().is_clean());
+        assert!(!ShutdownReport {
+            events_pending: 1,
+            ..Default::default()
+        }
+        .is_clean());
+        assert!(!ShutdownReport {
+            observatory_error: Some("broker unavailable".to_string()),
+            ..Default::default()
+        }
+        .is_clean());
+    }
+}
@@ -0,0 +1,172 @@
+//! Consistent read-only views of the graph
+//!
+//! Exports and analytics that run while writes continue would otherwise see
+//! a mix of old and new state. [`AsyncMemoryGraph::snapshot`](super::AsyncMemoryGraph::snapshot)
+//! returns a [`GraphSnapshot`] pinned to the moment it was taken; queries,
+//! traversals and transcripts run against it all see the same graph.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use llm_memory_graph::engine::AsyncMemoryGraph;
+//! use llm_memory_graph::transcript::TranscriptFormat;
+//! use llm_memory_graph::{Config, NodeType};
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let graph = AsyncMemoryGraph::open(Config::default()).await?;
+//! let snapshot = graph.snapshot().await?;
+//!
+//! // Writes made from here on are not visible through the snapshot
+//! for session in snapshot.list_sessions().await? {
+//!     let markdown = snapshot
+//!         .export_transcript(session.id, TranscriptFormat::Markdown)
+//!         .await?;
+//!     let prompts = snapshot
+//!         .query()
+//!         .session(session.id)
+//!         .node_type(NodeType::Prompt)
+//!         .execute()
+//!         .await?;
+//!     println!("{} prompts\n{markdown}", prompts.len());
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::conversation::{ConversationCursor, ConversationPage, PageDirection};
+use crate::query::{AsyncGraphTraversal, AsyncQueryBuilder};
+use crate::storage::{AsyncStorageBackend, SnapshotBackend, StorageStats};
+use crate::transcript::{Transcript, TranscriptFormat, TranscriptOptions};
+use crate::{ConversationSession, Edge, EdgeId, Error, Node, NodeId, Result, SessionId};
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+
+/// Read-only view of the graph as it was at [`taken_at`](Self::taken_at)
+#[derive(Clone)]
+pub struct GraphSnapshot {
+    storage: SnapshotBackend,
+}
+
+impl GraphSnapshot {
+    pub(super) fn new(storage: SnapshotBackend) -> Self {
+        Self { storage }
+    }
+
+    /// When the snapshot was taken
+    pub fn taken_at(&self) -> DateTime<Utc> {
+        self.storage.taken_at()
+    }
+
+    /// Cursor of the last captured change the snapshot reflects, if change
+    /// capture is enabled
+    pub fn change_cursor(&self) -> Option<u64> {
+        self.storage.change_cursor()
+    }
+
+    /// The snapshot as a storage backend, for code written against
+    /// [`AsyncStorageBackend`]; every write returns an error
+    pub fn storage(&self) -> Arc<dyn AsyncStorageBackend> {
+        Arc::new(self.storage.clone())
+    }
+
+    /// Get a node by ID
+    pub async fn get_node(&self, id: &NodeId) -> Result<Option<Node>> {
+        self.storage.get_node(id).await
+    }
+
+    /// Get an edge by ID
+    pub async fn get_edge(&self, id: &EdgeId) -> Result<Option<Edge>> {
+        self.storage.get_edge(id).await
+    }
+
+    /// Get a session by ID
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::SessionNotFound`] if the session did not exist when the
+    /// snapshot was taken.
+    pub async fn get_session(&self, session_id: SessionId) -> Result<ConversationSession> {
+        self.storage
+            .get_session_nodes(&session_id)
+            .await?
+            .into_iter()
+            .find_map(|node| match node {
+                Node::Session(session) if session.id == session_id => Some(session),
+                _ => None,
+            })
+            .ok_or_else(|| Error::SessionNotFound(session_id.to_string()))
+    }
+
+    /// Every node in a session
+    pub async fn get_session_nodes(&self, session_id: &SessionId) -> Result<Vec<Node>> {
+        self.storage.get_session_nodes(session_id).await
+    }
+
+    /// Every session
+    pub async fn list_sessions(&self) -> Result<Vec<ConversationSession>> {
+        self.storage.list_sessions().await
+    }
+
+    /// Edges from a node
+    pub async fn get_outgoing_edges(&self, node_id: &NodeId) -> Result<Vec<Edge>> {
+        self.storage.get_outgoing_edges(node_id).await
+    }
+
+    /// Edges to a node
+    pub async fn get_incoming_edges(&self, node_id: &NodeId) -> Result<Vec<Edge>> {
+        self.storage.get_incoming_edges(node_id).await
+    }
+
+    /// Storage statistics at the time of the snapshot
+    pub async fn stats(&self) -> Result<StorageStats> {
+        self.storage.stats().await
+    }
+
+    /// Query the snapshot
+    pub fn query(&self) -> AsyncQueryBuilder {
+        AsyncQueryBuilder::new(self.storage())
+    }
+
+    /// Traverse the snapshot
+    pub fn traversal(&self) -> AsyncGraphTraversal {
+        AsyncGraphTraversal::new(self.storage())
+    }
+
+    /// One page of a session's prompt/response turns; see
+    /// [`AsyncMemoryGraph::get_conversation_page`](super::AsyncMemoryGraph::get_conversation_page)
+    pub async fn get_conversation_page(
+        &self,
+        session_id: SessionId,
+        cursor: Option<&ConversationCursor>,
+        limit: usize,
+        direction: PageDirection,
+    ) -> Result<ConversationPage> {
+        self.get_session(session_id).await?;
+        ConversationPage::collect(&self.storage, &session_id, cursor, limit, direction).await
+    }
+
+    /// Collect a session's prompts, responses and tool calls into a [`Transcript`]
+    pub async fn transcript(&self, session_id: SessionId) -> Result<Transcript> {
+        let session = self.get_session(session_id).await?;
+        Transcript::collect(&self.storage, session).await
+    }
+
+    /// Render a session as a human-readable transcript
+    pub async fn export_transcript(
+        &self,
+        session_id: SessionId,
+        format: TranscriptFormat,
+    ) -> Result<String> {
+        self.export_transcript_with(session_id, &TranscriptOptions::new(format))
+            .await
+    }
+
+    /// Render a session as a transcript with custom options
+    pub async fn export_transcript_with(
+        &self,
+        session_id: SessionId,
+        options: &TranscriptOptions,
+    ) -> Result<String> {
+        Ok(self.transcript(session_id).await?.render(options))
+    }
+}
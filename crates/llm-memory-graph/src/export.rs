@@ -0,0 +1,500 @@
+//! Encrypted, tamper-evident session exports
+//!
+//! [`AsyncMemoryGraph::export_session`](crate::engine::AsyncMemoryGraph::export_session)
+//! packs a session's nodes and edges into a [`SessionExport`]: a payload of
+//! one JSON record per line, and an [`ExportManifest`] listing the record
+//! counts and a hash of every record. With [`ExportOptions`] the payload is
+//! encrypted with AES-256-GCM, its record hashes keyed with HMAC-SHA256 (each
+//! under its own key derived from the encryption key with HKDF-SHA256), and
+//! the manifest signed with HMAC-SHA256.
+//! [`AsyncMemoryGraph::import_session`](crate::engine::AsyncMemoryGraph::import_session)
+//! checks the signature, decrypts and checks every record against the
+//! manifest before writing anything, failing with
+//! [`Error::ExportVerification`] on any mismatch.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use llm_memory_graph::engine::AsyncMemoryGraph;
+//! use llm_memory_graph::export::ExportOptions;
+//! use llm_memory_graph::Config;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let graph = AsyncMemoryGraph::open(Config::default()).await?;
+//! # let session = graph.create_session().await?;
+//! let options = ExportOptions::new()
+//!     .with_encryption_key([7; 32])
+//!     .with_signing_key(b"manifest signing key".to_vec());
+//! let export = graph.export_session(session.id, &options).await?;
+//! std::fs::write("session.json", serde_json::to_vec(&export)?)?;
+//!
+//! let target = AsyncMemoryGraph::open(Config::new("./restored")).await?;
+//! target.import_session(&export, &options).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{Edge, Error, Node, Result, SessionId};
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use hkdf::Hkdf;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fmt::{self, Write};
+
+/// Version of the export format written by this build
+pub const EXPORT_FORMAT_VERSION: u32 = 1;
+
+/// Name of the only encryption algorithm, as recorded in the manifest
+const AES_256_GCM: &str = "AES-256-GCM";
+
+/// HKDF info for the subkey the payload is encrypted with
+const ENCRYPT_KEY_INFO: &[u8] = b"encrypt";
+
+/// HKDF info for the subkey record hashes are keyed with
+const RECORD_HASH_KEY_INFO: &[u8] = b"record-hash";
+
+/// Keys used to seal and open an export
+///
+/// The same options are passed to export and import. Both keys are optional;
+/// an export sealed with a key cannot be imported without it.
+#[derive(Clone, Default)]
+pub struct ExportOptions {
+    encryption_key: Option<[u8; 32]>,
+    signing_key: Option<Vec<u8>>,
+}
+
+impl ExportOptions {
+    /// No encryption and no signature
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Encrypt the payload with this AES-256 key
+    #[must_use]
+    pub fn with_encryption_key(mut self, key: [u8; 32]) -> Self {
+        self.encryption_key = Some(key);
+        self
+    }
+
+    /// Sign the manifest with HMAC-SHA256 under this key
+    #[must_use]
+    pub fn with_signing_key(mut self, key: Vec<u8>) -> Self {
+        self.signing_key = Some(key);
+        self
+    }
+}
+
+impl fmt::Debug for ExportOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ExportOptions")
+            .field("encrypted", &self.encryption_key.is_some())
+            .field("signed", &self.signing_key.is_some())
+            .finish()
+    }
+}
+
+/// What an export contains, and how it was sealed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExportManifest {
+    /// Export format version
+    pub version: u32,
+    /// Session exported
+    pub session_id: SessionId,
+    /// When the export was made
+    pub exported_at: DateTime<Utc>,
+    /// Number of node records
+    pub node_count: usize,
+    /// Number of edge records
+    pub edge_count: usize,
+    /// Hex hash of each payload record, in order: HMAC-SHA256 under a key
+    /// derived from the encryption key if the payload is encrypted, so the
+    /// hashes reveal nothing about it, and plain SHA-256 otherwise
+    pub record_hashes: Vec<String>,
+    /// How the payload is encrypted, if it is
+    pub encryption: Option<ExportEncryption>,
+    /// Hex HMAC-SHA256 of the manifest without this field, if signed
+    pub signature: Option<String>,
+}
+
+/// Encryption applied to an export's payload
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExportEncryption {
+    /// Cipher used, `AES-256-GCM`
+    pub algorithm: String,
+    /// Base64 nonce the payload was encrypted with
+    pub nonce: String,
+}
+
+/// A session sealed for transfer, serializable as JSON
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionExport {
+    /// Record counts, hashes and sealing details
+    pub manifest: ExportManifest,
+    /// Base64 payload, encrypted if the manifest says so
+    pub payload: String,
+}
+
+/// One line of the payload
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum ExportRecord {
+    Node(Box<Node>),
+    Edge(Edge),
+}
+
+impl SessionExport {
+    /// Seal a session's nodes and edges
+    pub fn seal(
+        session_id: SessionId,
+        nodes: &[Node],
+        edges: &[Edge],
+        options: &ExportOptions,
+    ) -> Result<Self> {
+        let hash_key = options
+            .encryption_key
+            .as_ref()
+            .map(|key| subkey(key, RECORD_HASH_KEY_INFO));
+        let mut payload = String::new();
+        let mut record_hashes = Vec::with_capacity(nodes.len() + edges.len());
+        let records = nodes
+            .iter()
+            .map(|node| serde_json::to_string(&ExportRecord::Node(Box::new(node.clone()))))
+            .chain(
+                edges
+                    .iter()
+                    .map(|edge| serde_json::to_string(&ExportRecord::Edge(edge.clone()))),
+            );
+        for record in records {
+            let line = record.map_err(|e| Error::SerializationError(e.to_string()))?;
+            record_hashes.push(record_hash(&line, hash_key.as_ref())?);
+            payload.push_str(&line);
+            payload.push('\n');
+        }
+
+        let (payload, encryption) = match &options.encryption_key {
+            Some(key) => {
+                let cipher = Aes256Gcm::new(&Key::<Aes256Gcm>::from(subkey(key, ENCRYPT_KEY_INFO)));
+                let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+                let encrypted = cipher
+                    .encrypt(&nonce, payload.as_bytes())
+                    .map_err(|e| Error::SerializationError(format!("encryption failed: {e}")))?;
+                let encryption = ExportEncryption {
+                    algorithm: AES_256_GCM.to_string(),
+                    nonce: BASE64.encode(nonce),
+                };
+                (encrypted, Some(encryption))
+            }
+            None => (payload.into_bytes(), None),
+        };
+
+        let mut manifest = ExportManifest {
+            version: EXPORT_FORMAT_VERSION,
+            session_id,
+            exported_at: Utc::now(),
+            node_count: nodes.len(),
+            edge_count: edges.len(),
+            record_hashes,
+            encryption,
+            signature: None,
+        };
+        if let Some(key) = &options.signing_key {
+            manifest.signature = Some(hex(&manifest.mac(key)?.finalize().into_bytes()));
+        }
+
+        Ok(Self {
+            manifest,
+            payload: BASE64.encode(payload),
+        })
+    }
+
+    /// Check the export and return its nodes and edges
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::ExportVerification`] if the signature does not match,
+    /// a key the export was sealed with is missing, the payload cannot be
+    /// decrypted, or the records differ from the manifest.
+    pub fn open(&self, options: &ExportOptions) -> Result<(Vec<Node>, Vec<Edge>)> {
+        let manifest = &self.manifest;
+        if manifest.version != EXPORT_FORMAT_VERSION {
+            return Err(Error::ExportVerification(format!(
+                "unsupported export format version {}",
+                manifest.version
+            )));
+        }
+        manifest.verify_signature(options)?;
+
+        let payload = BASE64
+            .decode(&self.payload)
+            .map_err(|e| Error::ExportVerification(format!("payload is not base64: {e}")))?;
+        let (payload, hash_key) = match (&manifest.encryption, &options.encryption_key) {
+            (None, _) => (payload, None),
+            (Some(_), None) => {
+                return Err(Error::ExportVerification(
+                    "export is encrypted and no encryption key was given".to_string(),
+                ))
+            }
+            (Some(encryption), Some(key)) => (
+                decrypt(encryption, key, &payload)?,
+                Some(subkey(key, RECORD_HASH_KEY_INFO)),
+            ),
+        };
+        let payload = String::from_utf8(payload)
+            .map_err(|e| Error::ExportVerification(format!("payload is not UTF-8: {e}")))?;
+
+        let lines: Vec<&str> = payload.lines().collect();
+        if lines.len() != manifest.record_hashes.len() {
+            return Err(Error::ExportVerification(format!(
+                "manifest lists {} records, payload holds {}",
+                manifest.record_hashes.len(),
+                lines.len()
+            )));
+        }
+        let mut nodes = Vec::with_capacity(manifest.node_count);
+        let mut edges = Vec::with_capacity(manifest.edge_count);
+        for (index, (line, expected)) in lines.iter().zip(&manifest.record_hashes).enumerate() {
+            if record_hash(line, hash_key.as_ref())? != *expected {
+                return Err(Error::ExportVerification(format!(
+                    "record {index} does not match its hash"
+                )));
+            }
+            match serde_json::from_str(line)
+                .map_err(|e| Error::ExportVerification(format!("record {index}: {e}")))?
+            {
+                ExportRecord::Node(node) => nodes.push(*node),
+                ExportRecord::Edge(edge) => edges.push(edge),
+            }
+        }
+        if nodes.len() != manifest.node_count || edges.len() != manifest.edge_count {
+            return Err(Error::ExportVerification(format!(
+                "manifest lists {} nodes and {} edges, payload holds {} and {}",
+                manifest.node_count,
+                manifest.edge_count,
+                nodes.len(),
+                edges.len()
+            )));
+        }
+        Ok((nodes, edges))
+    }
+}
+
+impl ExportManifest {
+    /// HMAC of the manifest with its signature left out
+    fn mac(&self, key: &[u8]) -> Result<Hmac<Sha256>> {
+        let unsigned = Self {
+            signature: None,
+            ..self.clone()
+        };
+        let bytes =
+            serde_json::to_vec(&unsigned).map_err(|e| Error::SerializationError(e.to_string()))?;
+        let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(key)
+            .map_err(|e| Error::ValidationError(format!("invalid signing key: {e}")))?;
+        mac.update(&bytes);
+        Ok(mac)
+    }
+
+    fn verify_signature(&self, options: &ExportOptions) -> Result<()> {
+        match (&self.signature, &options.signing_key) {
+            (None, None) => Ok(()),
+            (None, Some(_)) => Err(Error::ExportVerification(
+                "export is not signed".to_string(),
+            )),
+            (Some(_), None) => Err(Error::ExportVerification(
+                "export is signed and no signing key was given to verify it".to_string(),
+            )),
+            (Some(signature), Some(key)) => {
+                let signature = unhex(signature)
+                    .ok_or_else(|| Error::ExportVerification("signature is not hex".to_string()))?;
+                self.mac(key)?.verify_slice(&signature).map_err(|_| {
+                    Error::ExportVerification("manifest signature does not match".to_string())
+                })
+            }
+        }
+    }
+}
+
+fn decrypt(encryption: &ExportEncryption, key: &[u8; 32], payload: &[u8]) -> Result<Vec<u8>> {
+    if encryption.algorithm != AES_256_GCM {
+        return Err(Error::ExportVerification(format!(
+            "unsupported encryption {}",
+            encryption.algorithm
+        )));
+    }
+    let nonce: [u8; 12] = BASE64
+        .decode(&encryption.nonce)
+        .ok()
+        .and_then(|nonce| nonce.try_into().ok())
+        .ok_or_else(|| Error::ExportVerification("invalid nonce".to_string()))?;
+    Aes256Gcm::new(&Key::<Aes256Gcm>::from(subkey(key, ENCRYPT_KEY_INFO)))
+        .decrypt(&Nonce::from(nonce), payload)
+        .map_err(|_| {
+            Error::ExportVerification(
+                "payload cannot be decrypted with this key, or was altered".to_string(),
+            )
+        })
+}
+
+/// Subkey of the encryption key for one use, so the cipher and the record
+/// hashes never share a key
+fn subkey(key: &[u8; 32], info: &[u8]) -> [u8; 32] {
+    let mut subkey = [0; 32];
+    Hkdf::<Sha256>::new(None, key)
+        .expand(info, &mut subkey)
+        .expect("32 bytes is a valid HKDF-SHA256 output length");
+    subkey
+}
+
+/// Hash of a payload record as listed in the manifest, keyed with the
+/// record hash subkey if the payload is encrypted
+fn record_hash(line: &str, key: Option<&[u8; 32]>) -> Result<String> {
+    let Some(key) = key else {
+        return Ok(sha256_hex(line.as_bytes()));
+    };
+    let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(key)
+        .map_err(|e| Error::ValidationError(format!("invalid record hash key: {e}")))?;
+    mac.update(line.as_bytes());
+    Ok(hex(&mac.finalize().into_bytes()))
+}
+
+pub(crate) fn sha256_hex(bytes: &[u8]) -> String {
+    hex(&Sha256::digest(bytes))
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().fold(String::new(), |mut hex, byte| {
+        let _ = write!(hex, "{byte:02x}");
+        hex
+    })
+}
+
+fn unhex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ConversationSession, EdgeType, PromptNode};
+
+    fn sample() -> (SessionId, Vec<Node>, Vec<Edge>) {
+        let session = ConversationSession::new();
+        let prompt = PromptNode::new(session.id, "Secret plans".to_string());
+        let edge = Edge::new(prompt.id, session.node_id, EdgeType::PartOf);
+        (
+            session.id,
+            vec![Node::Session(session), Node::Prompt(prompt)],
+            vec![edge],
+        )
+    }
+
+    #[test]
+    fn test_seal_and_open() {
+        let (session_id, nodes, edges) = sample();
+        let options = ExportOptions::new()
+            .with_encryption_key([1; 32])
+            .with_signing_key(b"sign".to_vec());
+
+        let export = SessionExport::seal(session_id, &nodes, &edges, &options).unwrap();
+        assert_eq!(export.manifest.record_hashes.len(), 3);
+        assert!(export.manifest.signature.is_some());
+        let payload = BASE64.decode(&export.payload).unwrap();
+        assert!(!String::from_utf8_lossy(&payload).contains("Secret plans"));
+
+        // Hashes of encrypted records cannot be checked against guessed plaintext
+        let plain = SessionExport::seal(session_id, &nodes, &edges, &ExportOptions::new()).unwrap();
+        for (keyed, unkeyed) in export
+            .manifest
+            .record_hashes
+            .iter()
+            .zip(&plain.manifest.record_hashes)
+        {
+            assert_ne!(keyed, unkeyed);
+        }
+
+        let (opened_nodes, opened_edges) = export.open(&options).unwrap();
+        assert_eq!(opened_nodes.len(), 2);
+        assert_eq!(opened_edges[0].id, edges[0].id);
+
+        // Unsealed exports open without keys
+        assert_eq!(plain.open(&ExportOptions::new()).unwrap().0.len(), 2);
+    }
+
+    #[test]
+    fn test_tampering_is_detected() {
+        let (session_id, nodes, edges) = sample();
+        let options = ExportOptions::new()
+            .with_encryption_key([1; 32])
+            .with_signing_key(b"sign".to_vec());
+        let export = SessionExport::seal(session_id, &nodes, &edges, &options).unwrap();
+        let failed = |export: &SessionExport, options: &ExportOptions| {
+            matches!(export.open(options), Err(Error::ExportVerification(_)))
+        };
+
+        let mut altered = export.clone();
+        altered.manifest.node_count = 1;
+        assert!(failed(&altered, &options));
+
+        let mut altered = export.clone();
+        let mut payload = BASE64.decode(&altered.payload).unwrap();
+        payload[0] ^= 1;
+        altered.payload = BASE64.encode(payload);
+        assert!(failed(&altered, &options));
+
+        assert!(failed(
+            &export,
+            &ExportOptions::new()
+                .with_encryption_key([2; 32])
+                .with_signing_key(b"sign".to_vec())
+        ));
+        assert!(failed(
+            &export,
+            &ExportOptions::new().with_encryption_key([1; 32])
+        ));
+
+        // Without a signature, the record hashes still catch edits
+        let unsigned = ExportOptions::new();
+        let mut altered = SessionExport::seal(session_id, &nodes, &edges, &unsigned).unwrap();
+        let payload = String::from_utf8(BASE64.decode(&altered.payload).unwrap()).unwrap();
+        altered.payload = BASE64.encode(payload.replace("Secret plans", "Public plans"));
+        assert!(failed(&altered, &unsigned));
+
+        // Record hashes are keyed with their own subkey, not the cipher key
+        let key = [1; 32];
+        assert_ne!(
+            subkey(&key, ENCRYPT_KEY_INFO),
+            subkey(&key, RECORD_HASH_KEY_INFO)
+        );
+        let encrypted = ExportOptions::new().with_encryption_key(key);
+        let mut altered = SessionExport::seal(session_id, &nodes, &edges, &encrypted).unwrap();
+        let payload = decrypt(
+            altered.manifest.encryption.as_ref().unwrap(),
+            &key,
+            &BASE64.decode(&altered.payload).unwrap(),
+        )
+        .unwrap();
+        let raw_hashes: Vec<String> = String::from_utf8(payload)
+            .unwrap()
+            .lines()
+            .map(|line| {
+                let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(&key).unwrap();
+                mac.update(line.as_bytes());
+                hex(&mac.finalize().into_bytes())
+            })
+            .collect();
+        assert!(altered.open(&encrypted).is_ok());
+        assert_ne!(altered.manifest.record_hashes, raw_hashes);
+        altered.manifest.record_hashes = raw_hashes;
+        assert!(failed(&altered, &encrypted));
+    }
+}
@@ -0,0 +1,306 @@
+//! Fine-tuning datasets in the OpenAI chat JSONL format
+//!
+//! [`AsyncMemoryGraph::export_finetune_dataset`](crate::engine::AsyncMemoryGraph::export_finetune_dataset)
+//! turns each selected session into one training example,
+//! `{"messages": [{"role": "user", "content": ...}, {"role": "assistant", ...}]}`,
+//! written one per line. A prompt with several responses contributes the one
+//! marked with [`SELECTED_RESPONSE_PROPERTY`](crate::SELECTED_RESPONSE_PROPERTY),
+//! or else its latest.
+//!
+//! By default turns that are flagged (a `true` [`FLAGGED_PROPERTY`] on the
+//! prompt or response) or failed (see [`FAILED_FINISH_REASONS`], or a failed
+//! tool call) are left out, and examples identical to one already written are
+//! skipped.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use llm_memory_graph::engine::AsyncMemoryGraph;
+//! use llm_memory_graph::finetune::FinetuneFilter;
+//! use llm_memory_graph::Config;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let graph = AsyncMemoryGraph::open(Config::default()).await?;
+//! let filter = FinetuneFilter::new()
+//!     .tag("support")
+//!     .with_system_prompt("You are a helpful support agent.");
+//! let report = graph.export_finetune_dataset(&filter, "train.jsonl").await?;
+//! println!("{} examples written", report.examples_written);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::transcript::{Transcript, TranscriptResponse, TranscriptTurn};
+use crate::{
+    ConversationSession, Properties, SessionId, SessionStatus, SELECTED_RESPONSE_PROPERTY,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Node property that excludes a prompt or response from datasets when `true`
+pub const FLAGGED_PROPERTY: &str = "flagged";
+
+/// Response finish reasons treated as failed generations
+pub const FAILED_FINISH_REASONS: &[&str] = &["error", "content_filter", "cancelled"];
+
+/// Author of a chat message
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChatRole {
+    /// Instructions preceding the conversation
+    System,
+    /// The prompt author
+    User,
+    /// The model
+    Assistant,
+}
+
+/// One message of a training example
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChatMessage {
+    /// Author
+    pub role: ChatRole,
+    /// Text
+    pub content: String,
+}
+
+/// One training example: a conversation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FinetuneExample {
+    /// Messages in conversation order
+    pub messages: Vec<ChatMessage>,
+}
+
+/// Selects sessions and turns for a dataset; unset fields match everything
+#[derive(Debug, Clone, PartialEq)]
+pub struct FinetuneFilter {
+    /// Only these sessions
+    pub session_ids: Option<Vec<SessionId>>,
+    /// Only sessions carrying every one of these tags
+    pub tags: Vec<String>,
+    /// Only sessions created at or after this time
+    pub since: Option<DateTime<Utc>>,
+    /// Only sessions created before this time
+    pub until: Option<DateTime<Utc>>,
+    /// Only sessions in this lifecycle state
+    pub status: Option<SessionStatus>,
+    /// Keep flagged turns
+    pub include_flagged: bool,
+    /// Keep failed turns
+    pub include_failed: bool,
+    /// Skip examples identical to one already written
+    pub deduplicate: bool,
+    /// System message placed at the start of every example
+    pub system_prompt: Option<String>,
+}
+
+impl Default for FinetuneFilter {
+    fn default() -> Self {
+        Self {
+            session_ids: None,
+            tags: Vec::new(),
+            since: None,
+            until: None,
+            status: None,
+            include_flagged: false,
+            include_failed: false,
+            deduplicate: true,
+            system_prompt: None,
+        }
+    }
+}
+
+impl FinetuneFilter {
+    /// Every session, without flagged or failed turns or duplicates
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only `session_id`, in addition to any sessions already selected
+    pub fn session(mut self, session_id: SessionId) -> Self {
+        self.session_ids
+            .get_or_insert_with(Vec::new)
+            .push(session_id);
+        self
+    }
+
+    /// Only sessions tagged `tag`
+    pub fn tag(mut self, tag: impl Into<String>) -> Self {
+        self.tags.push(tag.into());
+        self
+    }
+
+    /// Only sessions created in `[since, until)`
+    pub fn between(mut self, since: DateTime<Utc>, until: DateTime<Utc>) -> Self {
+        self.since = Some(since);
+        self.until = Some(until);
+        self
+    }
+
+    /// Only sessions in `status`
+    pub fn status(mut self, status: SessionStatus) -> Self {
+        self.status = Some(status);
+        self
+    }
+
+    /// Keep or drop flagged turns
+    pub fn include_flagged(mut self, include: bool) -> Self {
+        self.include_flagged = include;
+        self
+    }
+
+    /// Keep or drop failed turns
+    pub fn include_failed(mut self, include: bool) -> Self {
+        self.include_failed = include;
+        self
+    }
+
+    /// Enable or disable deduplication of identical examples
+    pub fn deduplicate(mut self, enable: bool) -> Self {
+        self.deduplicate = enable;
+        self
+    }
+
+    /// Start every example with a system message
+    pub fn with_system_prompt(mut self, prompt: impl Into<String>) -> Self {
+        self.system_prompt = Some(prompt.into());
+        self
+    }
+
+    /// Whether `session` passes the session criteria
+    pub fn matches_session(&self, session: &ConversationSession) -> bool {
+        self.session_ids
+            .as_ref()
+            .is_none_or(|ids| ids.contains(&session.id))
+            && self.tags.iter().all(|tag| session.tags.contains(tag))
+            && self.since.is_none_or(|since| session.created_at >= since)
+            && self.until.is_none_or(|until| session.created_at < until)
+            && self.status.is_none_or(|status| session.status == status)
+    }
+
+    /// Build the example for a session, or `None` if no turn survives the
+    /// filter; returns the number of turns left out alongside
+    pub(crate) fn example(&self, transcript: &Transcript) -> (Option<FinetuneExample>, usize) {
+        let mut messages: Vec<ChatMessage> = self
+            .system_prompt
+            .iter()
+            .map(|content| ChatMessage {
+                role: ChatRole::System,
+                content: content.clone(),
+            })
+            .collect();
+        let mut excluded = 0;
+
+        for turn in &transcript.turns {
+            let Some(reply) = chosen_response(turn) else {
+                excluded += 1;
+                continue;
+            };
+            let flagged =
+                is_flagged(&turn.prompt.properties) || is_flagged(&reply.response.properties);
+            if (flagged && !self.include_flagged) || (is_failed(reply) && !self.include_failed) {
+                excluded += 1;
+                continue;
+            }
+            messages.push(ChatMessage {
+                role: ChatRole::User,
+                content: turn.prompt.content.clone(),
+            });
+            messages.push(ChatMessage {
+                role: ChatRole::Assistant,
+                content: reply.response.content.clone(),
+            });
+        }
+
+        let has_turns = messages.iter().any(|m| m.role == ChatRole::Assistant);
+        (has_turns.then_some(FinetuneExample { messages }), excluded)
+    }
+}
+
+/// Outcome of a dataset export
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FinetuneExportReport {
+    /// Sessions that passed the filter
+    pub sessions_matched: usize,
+    /// Lines written
+    pub examples_written: usize,
+    /// Turns left out as flagged, failed or unanswered
+    pub turns_excluded: usize,
+    /// Examples skipped as duplicates
+    pub duplicates_skipped: usize,
+}
+
+fn chosen_response(turn: &TranscriptTurn) -> Option<&TranscriptResponse> {
+    turn.responses
+        .iter()
+        .find(|r| r.response.properties.get(SELECTED_RESPONSE_PROPERTY) == Some(&TRUE))
+        .or_else(|| turn.responses.last())
+}
+
+const TRUE: serde_json::Value = serde_json::Value::Bool(true);
+
+fn is_flagged(properties: &Properties) -> bool {
+    properties.get(FLAGGED_PROPERTY) == Some(&TRUE)
+}
+
+fn is_failed(reply: &TranscriptResponse) -> bool {
+    let reason = reply.response.metadata.finish_reason.to_ascii_lowercase();
+    FAILED_FINISH_REASONS.contains(&reason.as_str()) || reply.tools.iter().any(|t| !t.success)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{PromptNode, ResponseNode, TokenUsage};
+
+    #[test]
+    fn test_example_excludes_flagged_and_failed() {
+        let session = ConversationSession::new();
+        let turn = |question: &str, answer: &str| {
+            let prompt = PromptNode::new(session.id, question.to_string());
+            let response = ResponseNode::new(prompt.id, answer.to_string(), TokenUsage::new(1, 1));
+            TranscriptTurn {
+                prompt,
+                responses: vec![TranscriptResponse {
+                    response,
+                    tools: Vec::new(),
+                }],
+            }
+        };
+        let mut flagged = turn("Rude question", "Rude answer");
+        flagged
+            .prompt
+            .properties
+            .insert(FLAGGED_PROPERTY.to_string(), serde_json::json!(true));
+        let mut failed = turn("Broken", "");
+        failed.responses[0].response.metadata.finish_reason = "error".to_string();
+        let transcript = Transcript {
+            session: session.clone(),
+            turns: vec![turn("Hi", "Hello!"), flagged, failed],
+        };
+
+        let filter = FinetuneFilter::new().with_system_prompt("Be brief.");
+        let (example, excluded) = filter.example(&transcript);
+        let example = example.unwrap();
+        assert_eq!(excluded, 2);
+        assert_eq!(example.messages.len(), 3);
+        assert_eq!(example.messages[0].role, ChatRole::System);
+        assert_eq!(example.messages[2].content, "Hello!");
+
+        let (example, excluded) = filter
+            .include_flagged(true)
+            .include_failed(true)
+            .example(&transcript);
+        assert_eq!(excluded, 0);
+        assert_eq!(example.unwrap().messages.len(), 7);
+
+        let json = serde_json::to_value(FinetuneExample {
+            messages: vec![ChatMessage {
+                role: ChatRole::User,
+                content: "Hi".to_string(),
+            }],
+        })
+        .unwrap();
+        assert_eq!(json["messages"][0]["role"], "user");
+    }
+}
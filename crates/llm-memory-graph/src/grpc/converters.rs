@@ -3,12 +3,13 @@
 //! This module provides bidirectional conversion between protocol buffer
 //! message types and internal Rust types used by the memory graph.
 
-use crate::{Error, Result};
 use crate::grpc::proto;
 use crate::{
-    ConversationSession, EdgeType, Node, NodeType, PromptMetadata, PromptNode, ResponseMetadata,
-    ResponseNode, SessionId, TokenUsage, ToolInvocation, AgentNode, PromptTemplate, VariableSpec,
+    AgentNode, AgentStatus, ConversationSession, EdgeType, Node, NodeType, PromptMetadata,
+    PromptNode, PromptTemplate, ResponseMetadata, ResponseNode, SessionId, TokenUsage,
+    ToolInvocation, VariableSpec,
 };
+use crate::{Error, Result};
 use chrono::{DateTime, Utc};
 use prost_types::Timestamp;
 use std::collections::HashMap;
@@ -18,19 +19,11 @@ use tonic::Status;
 // Error Conversion
 // ============================================================================
 
-/// Convert internal Error to gRPC Status
+/// Convert internal Error to gRPC Status, using the status mapped to its
+/// [`ErrorCode`](crate::ErrorCode)
 pub fn error_to_status(err: Error) -> Status {
-    match err {
-        Error::NotFound(msg) => Status::not_found(msg),
-        Error::InvalidInput(msg) => Status::invalid_argument(msg),
-        Error::Timeout(msg) => Status::deadline_exceeded(msg),
-        Error::Storage(msg) => Status::internal(format!("Storage error: {}", msg)),
-        Error::SerializationError(msg) => Status::internal(format!("Serialization error: {}", msg)),
-        Error::SessionNotFound(msg) => Status::not_found(format!("Session not found: {}", msg)),
-        Error::NodeNotFound(msg) => Status::not_found(format!("Node not found: {}", msg)),
-        Error::IO(err) => Status::internal(format!("IO error: {}", err)),
-        Error::Other(msg) => Status::internal(msg),
-    }
+    let code = tonic::Code::from(err.code().grpc_code());
+    Status::new(code, err.to_string())
 }
 
 /// Convert Result<T> to Result<T, Status>
@@ -53,14 +46,14 @@ pub fn datetime_to_proto(dt: DateTime<Utc>) -> Timestamp {
 /// Convert protobuf Timestamp to chrono DateTime
 pub fn proto_to_datetime(ts: Timestamp) -> Result<DateTime<Utc>> {
     DateTime::from_timestamp(ts.seconds, ts.nanos as u32)
-        .ok_or_else(|| Error::InvalidInput("Invalid timestamp".to_string()))
+        .ok_or_else(|| Error::ValidationError("Invalid timestamp".to_string()))
 }
 
 /// Convert optional protobuf Timestamp to chrono DateTime
 pub fn optional_proto_to_datetime(ts: Option<Timestamp>) -> Result<DateTime<Utc>> {
     match ts {
         Some(timestamp) => proto_to_datetime(timestamp),
-        None => Err(Error::InvalidInput("Missing timestamp".to_string())),
+        None => Err(Error::ValidationError("Missing timestamp".to_string())),
     }
 }
 
@@ -75,7 +68,7 @@ pub fn session_to_proto(session: ConversationSession) -> proto::Session {
         created_at: Some(datetime_to_proto(session.created_at)),
         updated_at: Some(datetime_to_proto(session.updated_at)),
         metadata: session.metadata,
-        is_active: session.is_active,
+        is_active: session.status.is_open(),
     }
 }
 
@@ -86,25 +79,29 @@ pub fn session_to_proto(session: ConversationSession) -> proto::Session {
 /// Convert protobuf NodeType to internal NodeType
 pub fn proto_to_node_type(node_type: i32) -> Result<NodeType> {
     match proto::NodeType::try_from(node_type) {
-        Ok(proto::NodeType::NodeTypeSession) => Ok(NodeType::Session),
-        Ok(proto::NodeType::NodeTypePrompt) => Ok(NodeType::Prompt),
-        Ok(proto::NodeType::NodeTypeResponse) => Ok(NodeType::Response),
-        Ok(proto::NodeType::NodeTypeToolInvocation) => Ok(NodeType::ToolInvocation),
-        Ok(proto::NodeType::NodeTypeAgent) => Ok(NodeType::Agent),
-        Ok(proto::NodeType::NodeTypeTemplate) => Ok(NodeType::Template),
-        _ => Err(Error::InvalidInput(format!("Invalid node type: {}", node_type))),
+        Ok(proto::NodeType::Session) => Ok(NodeType::Session),
+        Ok(proto::NodeType::Prompt) => Ok(NodeType::Prompt),
+        Ok(proto::NodeType::Response) => Ok(NodeType::Response),
+        Ok(proto::NodeType::ToolInvocation) => Ok(NodeType::ToolInvocation),
+        Ok(proto::NodeType::Agent) => Ok(NodeType::Agent),
+        Ok(proto::NodeType::Template) => Ok(NodeType::Template),
+        _ => Err(Error::ValidationError(format!(
+            "Invalid node type: {}",
+            node_type
+        ))),
     }
 }
 
 /// Convert internal NodeType to protobuf NodeType
 pub fn node_type_to_proto(node_type: NodeType) -> i32 {
     match node_type {
-        NodeType::Session => proto::NodeType::NodeTypeSession as i32,
-        NodeType::Prompt => proto::NodeType::NodeTypePrompt as i32,
-        NodeType::Response => proto::NodeType::NodeTypeResponse as i32,
-        NodeType::ToolInvocation => proto::NodeType::NodeTypeToolInvocation as i32,
-        NodeType::Agent => proto::NodeType::NodeTypeAgent as i32,
-        NodeType::Template => proto::NodeType::NodeTypeTemplate as i32,
+        NodeType::Session => proto::NodeType::Session as i32,
+        NodeType::Prompt => proto::NodeType::Prompt as i32,
+        NodeType::Response => proto::NodeType::Response as i32,
+        NodeType::ToolInvocation => proto::NodeType::ToolInvocation as i32,
+        NodeType::Agent => proto::NodeType::Agent as i32,
+        NodeType::Template => proto::NodeType::Template as i32,
+        NodeType::Custom => proto::NodeType::Unspecified as i32,
     }
 }
 
@@ -115,31 +112,40 @@ pub fn node_type_to_proto(node_type: NodeType) -> i32 {
 /// Convert protobuf EdgeType to internal EdgeType
 pub fn proto_to_edge_type(edge_type: i32) -> Result<EdgeType> {
     match proto::EdgeType::try_from(edge_type) {
-        Ok(proto::EdgeType::EdgeTypeBelongsTo) => Ok(EdgeType::PartOf),
-        Ok(proto::EdgeType::EdgeTypeRespondsTo) => Ok(EdgeType::RespondsTo),
-        Ok(proto::EdgeType::EdgeTypeFollows) => Ok(EdgeType::Follows),
-        Ok(proto::EdgeType::EdgeTypeInvokes) => Ok(EdgeType::Invokes),
-        Ok(proto::EdgeType::EdgeTypeHandledBy) => Ok(EdgeType::HandledBy),
-        Ok(proto::EdgeType::EdgeTypeInstantiates) => Ok(EdgeType::Instantiates),
-        Ok(proto::EdgeType::EdgeTypeInherits) => Ok(EdgeType::Inherits),
-        Ok(proto::EdgeType::EdgeTypeTransfersTo) => Ok(EdgeType::TransfersTo),
-        Ok(proto::EdgeType::EdgeTypeReferences) => Ok(EdgeType::References),
-        _ => Err(Error::InvalidInput(format!("Invalid edge type: {}", edge_type))),
+        Ok(proto::EdgeType::BelongsTo) => Ok(EdgeType::PartOf),
+        Ok(proto::EdgeType::RespondsTo) => Ok(EdgeType::RespondsTo),
+        Ok(proto::EdgeType::Follows) => Ok(EdgeType::Follows),
+        Ok(proto::EdgeType::Invokes) => Ok(EdgeType::Invokes),
+        Ok(proto::EdgeType::HandledBy) => Ok(EdgeType::HandledBy),
+        Ok(proto::EdgeType::Instantiates) => Ok(EdgeType::Instantiates),
+        Ok(proto::EdgeType::Inherits) => Ok(EdgeType::Inherits),
+        Ok(proto::EdgeType::TransfersTo) => Ok(EdgeType::TransfersTo),
+        Ok(proto::EdgeType::References) => Ok(EdgeType::References),
+        Ok(proto::EdgeType::MentionedIn) => Ok(EdgeType::MentionedIn),
+        Ok(proto::EdgeType::Supersedes) => Ok(EdgeType::Supersedes),
+        Ok(proto::EdgeType::PreviousVersionOf) => Ok(EdgeType::PreviousVersionOf),
+        _ => Err(Error::ValidationError(format!(
+            "Invalid edge type: {}",
+            edge_type
+        ))),
     }
 }
 
 /// Convert internal EdgeType to protobuf EdgeType
 pub fn edge_type_to_proto(edge_type: EdgeType) -> i32 {
     match edge_type {
-        EdgeType::PartOf => proto::EdgeType::EdgeTypeBelongsTo as i32,
-        EdgeType::RespondsTo => proto::EdgeType::EdgeTypeRespondsTo as i32,
-        EdgeType::Follows => proto::EdgeType::EdgeTypeFollows as i32,
-        EdgeType::Invokes => proto::EdgeType::EdgeTypeInvokes as i32,
-        EdgeType::HandledBy => proto::EdgeType::EdgeTypeHandledBy as i32,
-        EdgeType::Instantiates => proto::EdgeType::EdgeTypeInstantiates as i32,
-        EdgeType::Inherits => proto::EdgeType::EdgeTypeInherits as i32,
-        EdgeType::TransfersTo => proto::EdgeType::EdgeTypeTransfersTo as i32,
-        EdgeType::References => proto::EdgeType::EdgeTypeReferences as i32,
+        EdgeType::PartOf => proto::EdgeType::BelongsTo as i32,
+        EdgeType::RespondsTo => proto::EdgeType::RespondsTo as i32,
+        EdgeType::Follows => proto::EdgeType::Follows as i32,
+        EdgeType::Invokes => proto::EdgeType::Invokes as i32,
+        EdgeType::HandledBy => proto::EdgeType::HandledBy as i32,
+        EdgeType::Instantiates => proto::EdgeType::Instantiates as i32,
+        EdgeType::Inherits => proto::EdgeType::Inherits as i32,
+        EdgeType::TransfersTo => proto::EdgeType::TransfersTo as i32,
+        EdgeType::References => proto::EdgeType::References as i32,
+        EdgeType::MentionedIn => proto::EdgeType::MentionedIn as i32,
+        EdgeType::Supersedes => proto::EdgeType::Supersedes as i32,
+        EdgeType::PreviousVersionOf => proto::EdgeType::PreviousVersionOf as i32,
     }
 }
 
@@ -151,8 +157,8 @@ pub fn edge_type_to_proto(edge_type: EdgeType) -> i32 {
 pub fn proto_to_prompt_metadata(metadata: proto::PromptMetadata) -> PromptMetadata {
     PromptMetadata {
         model: metadata.model,
-        temperature: metadata.temperature,
-        max_tokens: metadata.max_tokens.map(|t| t as usize),
+        temperature: metadata.temperature as f32,
+        max_tokens: metadata.max_tokens.map(|t| t.max(0) as usize),
         tools_available: metadata.tools_available,
         custom: metadata.custom,
     }
@@ -162,8 +168,10 @@ pub fn proto_to_prompt_metadata(metadata: proto::PromptMetadata) -> PromptMetada
 pub fn prompt_metadata_to_proto(metadata: PromptMetadata) -> proto::PromptMetadata {
     proto::PromptMetadata {
         model: metadata.model,
-        temperature: metadata.temperature,
-        max_tokens: metadata.max_tokens.map(|t| t as i32),
+        temperature: f64::from(metadata.temperature),
+        max_tokens: metadata
+            .max_tokens
+            .map(|t| i32::try_from(t).unwrap_or(i32::MAX)),
         tools_available: metadata.tools_available,
         custom: metadata.custom,
     }
@@ -174,7 +182,7 @@ pub fn proto_to_response_metadata(metadata: proto::ResponseMetadata) -> Response
     ResponseMetadata {
         model: metadata.model,
         finish_reason: metadata.finish_reason,
-        latency_ms: metadata.latency_ms as u64,
+        latency_ms: metadata.latency_ms.max(0) as u64,
         custom: metadata.custom,
     }
 }
@@ -192,21 +200,26 @@ pub fn response_metadata_to_proto(metadata: ResponseMetadata) -> proto::Response
 /// Convert protobuf TokenUsage to internal TokenUsage
 pub fn proto_to_token_usage(usage: proto::TokenUsage) -> TokenUsage {
     TokenUsage {
-        prompt_tokens: usage.prompt_tokens as usize,
-        completion_tokens: usage.completion_tokens as usize,
-        total_tokens: usage.total_tokens as usize,
+        prompt_tokens: token_count(usage.prompt_tokens),
+        completion_tokens: token_count(usage.completion_tokens),
+        total_tokens: token_count(usage.total_tokens),
     }
 }
 
 /// Convert internal TokenUsage to protobuf TokenUsage
 pub fn token_usage_to_proto(usage: TokenUsage) -> proto::TokenUsage {
     proto::TokenUsage {
-        prompt_tokens: usage.prompt_tokens as i64,
-        completion_tokens: usage.completion_tokens as i64,
-        total_tokens: usage.total_tokens as i64,
+        prompt_tokens: i64::from(usage.prompt_tokens),
+        completion_tokens: i64::from(usage.completion_tokens),
+        total_tokens: i64::from(usage.total_tokens),
     }
 }
 
+/// Clamp a protobuf token count into the range of an internal one
+fn token_count(count: i64) -> u32 {
+    u32::try_from(count.max(0)).unwrap_or(u32::MAX)
+}
+
 // ============================================================================
 // Node Conversion
 // ============================================================================
@@ -236,16 +249,17 @@ pub fn response_node_to_proto(response: ResponseNode) -> proto::ResponseNode {
 
 /// Convert internal ToolInvocation to protobuf ToolInvocationNode
 pub fn tool_invocation_to_proto(tool: ToolInvocation) -> proto::ToolInvocationNode {
+    let status = tool.status().to_string();
     proto::ToolInvocationNode {
         id: tool.id.to_string(),
         response_id: tool.response_id.to_string(),
         tool_name: tool.tool_name,
         parameters: tool.parameters.to_string(),
-        status: tool.status,
+        status,
         result: tool.result.map(|r| r.to_string()),
         error: tool.error,
-        duration_ms: tool.duration_ms.map(|d| d as i64).unwrap_or(0),
-        retry_count: tool.retry_count.unwrap_or(0) as i32,
+        duration_ms: tool.duration_ms as i64,
+        retry_count: tool.retry_count as i32,
         timestamp: Some(datetime_to_proto(tool.timestamp)),
         metadata: tool.metadata,
     }
@@ -258,9 +272,20 @@ pub fn agent_node_to_proto(agent: AgentNode) -> proto::AgentNode {
         name: agent.name,
         role: agent.role,
         capabilities: agent.capabilities,
-        status: agent.status.to_string(),
+        status: agent_status_name(&agent.status).to_string(),
         created_at: Some(datetime_to_proto(agent.created_at)),
-        metadata: agent.metadata,
+        metadata: HashMap::from([("model".to_string(), agent.model)]),
+    }
+}
+
+/// Name of an agent status on the wire
+fn agent_status_name(status: &AgentStatus) -> &'static str {
+    match status {
+        AgentStatus::Active => "active",
+        AgentStatus::Idle => "idle",
+        AgentStatus::Busy => "busy",
+        AgentStatus::Paused => "paused",
+        AgentStatus::Terminated => "terminated",
     }
 }
 
@@ -269,7 +294,7 @@ pub fn template_to_proto(template: PromptTemplate) -> proto::TemplateNode {
     proto::TemplateNode {
         id: template.id.to_string(),
         name: template.name,
-        template_text: template.template_text,
+        template_text: template.template,
         variables: template
             .variables
             .into_iter()
@@ -288,7 +313,7 @@ pub fn variable_spec_to_proto(spec: VariableSpec) -> proto::VariableSpec {
         name: spec.name,
         type_hint: spec.type_hint,
         required: spec.required,
-        default_value: spec.default_value,
+        default_value: spec.default,
         validation_pattern: spec.validation_pattern,
         description: spec.description,
     }
@@ -297,7 +322,7 @@ pub fn variable_spec_to_proto(spec: VariableSpec) -> proto::VariableSpec {
 /// Convert internal Node to protobuf Node
 pub fn node_to_proto(node: Node) -> proto::Node {
     let id = node.id().to_string();
-    let created_at = Some(datetime_to_proto(Utc::now())); // TODO: Get actual created_at from node
+    let created_at = Some(datetime_to_proto(node.created_at()));
 
     match node {
         Node::Prompt(prompt) => {
@@ -315,7 +340,9 @@ pub fn node_to_proto(node: Node) -> proto::Node {
                 id,
                 r#type: node_type,
                 created_at,
-                node_data: Some(proto::node::NodeData::Response(response_node_to_proto(response))),
+                node_data: Some(proto::node::NodeData::Response(response_node_to_proto(
+                    response,
+                ))),
             }
         }
         Node::ToolInvocation(tool) => {
@@ -324,7 +351,9 @@ pub fn node_to_proto(node: Node) -> proto::Node {
                 id,
                 r#type: node_type,
                 created_at,
-                node_data: Some(proto::node::NodeData::ToolInvocation(tool_invocation_to_proto(tool))),
+                node_data: Some(proto::node::NodeData::ToolInvocation(
+                    tool_invocation_to_proto(tool),
+                )),
             }
         }
         Node::Agent(agent) => {
@@ -345,15 +374,18 @@ pub fn node_to_proto(node: Node) -> proto::Node {
                 node_data: Some(proto::node::NodeData::Template(template_to_proto(template))),
             }
         }
-        Node::Session(session) => {
-            let node_type = node_type_to_proto(NodeType::Session);
-            proto::Node {
-                id,
-                r#type: node_type,
-                created_at,
-                node_data: None, // Session doesn't use node_data field
-            }
-        }
+        Node::Session(_) => proto::Node {
+            id,
+            r#type: node_type_to_proto(NodeType::Session),
+            created_at,
+            node_data: None, // Session doesn't use node_data field
+        },
+        Node::Custom(_) => proto::Node {
+            id,
+            r#type: node_type_to_proto(NodeType::Custom),
+            created_at,
+            node_data: None, // Custom nodes have no protobuf representation
+        },
     }
 }
 
@@ -369,24 +401,121 @@ pub fn edge_to_proto(edge: crate::Edge) -> proto::Edge {
         to_node_id: edge.to.to_string(),
         r#type: edge_type_to_proto(edge.edge_type),
         created_at: Some(datetime_to_proto(edge.created_at)),
-        properties: edge.properties.unwrap_or_default(),
+        properties: edge.properties,
+    }
+}
+
+// ============================================================================
+// Import Conversion
+// ============================================================================
+
+/// Convert a bulk load report to a protobuf import progress ack
+pub fn import_progress_to_proto(
+    report: &crate::engine::BulkLoadReport,
+    done: bool,
+) -> proto::ImportProgress {
+    proto::ImportProgress {
+        nodes_loaded: report.nodes_loaded as i64,
+        edges_loaded: report.edges_loaded as i64,
+        batches: report.batches as i64,
+        responses_reindexed: report.responses_reindexed as i64,
+        duration_ms: report.duration.as_millis() as i64,
+        done,
+    }
+}
+
+// ============================================================================
+// Health Conversion
+// ============================================================================
+
+/// Convert a component's health to protobuf
+pub fn component_health_to_proto(
+    component: crate::health::ComponentHealth,
+) -> proto::ComponentStatus {
+    proto::ComponentStatus {
+        name: component.name,
+        status: component.status.to_string(),
+        message: component.message.unwrap_or_default(),
+        latency_ms: component.latency_ms.unwrap_or_default(),
+        details: component.details.into_iter().collect(),
+    }
+}
+
+// ============================================================================
+// Alias Conversion
+// ============================================================================
+
+/// Convert an alias and its target to protobuf
+pub fn alias_to_proto(alias: String, target: crate::AliasTarget) -> proto::Alias {
+    let target = match target {
+        crate::AliasTarget::Session(id) => proto::alias::Target::SessionId(id.to_string()),
+        crate::AliasTarget::Node(id) => proto::alias::Target::NodeId(id.to_string()),
+    };
+    proto::Alias {
+        alias,
+        target: Some(target),
     }
 }
 
+/// Convert the target of a set-alias request
+pub fn proto_to_alias_target(
+    target: Option<proto::set_alias_request::Target>,
+) -> Result<crate::AliasTarget> {
+    match target {
+        Some(proto::set_alias_request::Target::SessionId(id)) => {
+            Ok(crate::AliasTarget::Session(parse_session_id(&id)?))
+        }
+        Some(proto::set_alias_request::Target::NodeId(id)) => {
+            Ok(crate::AliasTarget::Node(parse_node_id(&id)?))
+        }
+        None => Err(Error::ValidationError(
+            "Alias target must be a session or node ID".to_string(),
+        )),
+    }
+}
+
+// ============================================================================
+// Pagination
+// ============================================================================
+
+/// Parse the cursor of a listing request; empty asks for the first page
+pub fn parse_page_cursor(cursor: &str) -> Result<Option<crate::pagination::PageCursor>> {
+    if cursor.is_empty() {
+        return Ok(None);
+    }
+    cursor.parse().map(Some)
+}
+
+/// Page size asked for by a listing request, or the default for 0
+pub fn page_size(limit: i32) -> usize {
+    if limit > 0 {
+        limit as usize
+    } else {
+        crate::pagination::DEFAULT_PAGE_SIZE
+    }
+}
+
+/// Convert the cursor of the next page to protobuf; empty on the last page
+pub fn next_cursor_to_proto(cursor: Option<crate::pagination::PageCursor>) -> String {
+    cursor.map(|cursor| cursor.to_string()).unwrap_or_default()
+}
+
 // ============================================================================
 // SessionId Parsing
 // ============================================================================
 
 /// Parse SessionId from string
 pub fn parse_session_id(id: &str) -> Result<SessionId> {
-    SessionId::parse_str(id)
-        .map_err(|_| Error::InvalidInput(format!("Invalid session ID: {}", id)))
+    uuid::Uuid::parse_str(id)
+        .map(SessionId::from)
+        .map_err(|_| Error::ValidationError(format!("Invalid session ID: {}", id)))
 }
 
 /// Parse NodeId from string
 pub fn parse_node_id(id: &str) -> Result<crate::NodeId> {
-    crate::NodeId::parse_str(id)
-        .map_err(|_| Error::InvalidInput(format!("Invalid node ID: {}", id)))
+    uuid::Uuid::parse_str(id)
+        .map(crate::NodeId::from)
+        .map_err(|_| Error::ValidationError(format!("Invalid node ID: {}", id)))
 }
 
 // ============================================================================
@@ -409,9 +538,12 @@ mod tests {
 
     #[test]
     fn test_node_type_conversion() {
-        assert_eq!(node_type_to_proto(NodeType::Prompt), proto::NodeType::NodeTypePrompt as i32);
         assert_eq!(
-            proto_to_node_type(proto::NodeType::NodeTypePrompt as i32).unwrap(),
+            node_type_to_proto(NodeType::Prompt),
+            proto::NodeType::Prompt as i32
+        );
+        assert_eq!(
+            proto_to_node_type(proto::NodeType::Prompt as i32).unwrap(),
             NodeType::Prompt
         );
     }
@@ -420,10 +552,10 @@ mod tests {
     fn test_edge_type_conversion() {
         assert_eq!(
             edge_type_to_proto(EdgeType::RespondsTo),
-            proto::EdgeType::EdgeTypeRespondsTo as i32
+            proto::EdgeType::RespondsTo as i32
         );
         assert_eq!(
-            proto_to_edge_type(proto::EdgeType::EdgeTypeRespondsTo as i32).unwrap(),
+            proto_to_edge_type(proto::EdgeType::RespondsTo as i32).unwrap(),
             EdgeType::RespondsTo
         );
     }
@@ -2,86 +2,193 @@
 //!
 //! This module contains helper functions for handling specific types of
 //! gRPC requests, including validation, transformation, and error handling.
+//! Validation runs before a request reaches the graph and reports every
+//! invalid field at once, see [`crate::validation`].
 
-use crate::Result;
 use crate::grpc::proto;
+use crate::validation::{RequestLimits, RequestValidator};
 use tonic::Status;
 
 /// Validate a create session request
 pub fn validate_create_session_request(
     _request: &proto::CreateSessionRequest,
+    _limits: &RequestLimits,
 ) -> Result<(), Status> {
-    // Add validation logic as needed
     Ok(())
 }
 
-/// Validate a prompt request
-pub fn validate_add_prompt_request(request: &proto::AddPromptRequest) -> Result<(), Status> {
-    if request.session_id.is_empty() {
-        return Err(Status::invalid_argument("session_id cannot be empty"));
-    }
-    if request.content.is_empty() {
-        return Err(Status::invalid_argument("content cannot be empty"));
-    }
-    if request.content.len() > 1_000_000 {
-        return Err(Status::invalid_argument(
-            "content exceeds maximum length of 1MB",
-        ));
+/// Validate a request naming one session
+pub fn validate_session_id(session_id: &str, limits: &RequestLimits) -> Result<(), Status> {
+    let mut request = RequestValidator::new(limits);
+    request.uuid("session_id", session_id);
+    Ok(request.finish()?)
+}
+
+/// Validate a request naming one node
+pub fn validate_node_id(node_id: &str, limits: &RequestLimits) -> Result<(), Status> {
+    let mut request = RequestValidator::new(limits);
+    request.uuid("node_id", node_id);
+    Ok(request.finish()?)
+}
+
+/// Validate a list sessions request
+pub fn validate_list_sessions_request(
+    request: &proto::ListSessionsRequest,
+    limits: &RequestLimits,
+) -> Result<(), Status> {
+    let mut validator = RequestValidator::new(limits);
+    validator.page_size("limit", i64::from(request.limit));
+    #[allow(deprecated)]
+    validator.offset("offset", i64::from(request.offset));
+    validator.cursor("cursor", &request.cursor);
+    Ok(validator.finish()?)
+}
+
+/// Validate a list session nodes request
+pub fn validate_list_session_nodes_request(
+    request: &proto::ListSessionNodesRequest,
+    limits: &RequestLimits,
+) -> Result<(), Status> {
+    let mut validator = RequestValidator::new(limits);
+    validator.uuid("session_id", &request.session_id);
+    validator.page_size("limit", i64::from(request.limit));
+    validator.cursor("cursor", &request.cursor);
+    Ok(validator.finish()?)
+}
+
+/// Validate a get edges request
+pub fn validate_get_edges_request(
+    request: &proto::GetEdgesRequest,
+    limits: &RequestLimits,
+) -> Result<(), Status> {
+    let mut validator = RequestValidator::new(limits);
+    validator.uuid("node_id", &request.node_id);
+    validator.page_size("limit", i64::from(request.limit));
+    validator.cursor("cursor", &request.cursor);
+    let paged = request.limit > 0 || !request.cursor.is_empty();
+    if paged && request.direction == Some(proto::EdgeDirection::Both as i32) {
+        validator.violation("direction", "must be outgoing or incoming when paging");
     }
-    Ok(())
+    Ok(validator.finish()?)
+}
+
+/// Validate a request for one page of a listing
+pub fn validate_page_request(
+    limit: i32,
+    cursor: &str,
+    limits: &RequestLimits,
+) -> Result<(), Status> {
+    let mut validator = RequestValidator::new(limits);
+    validator.page_size("limit", i64::from(limit));
+    validator.cursor("cursor", cursor);
+    Ok(validator.finish()?)
+}
+
+/// Validate a batch get nodes request
+pub fn validate_batch_get_nodes_request(
+    request: &proto::BatchGetNodesRequest,
+    limits: &RequestLimits,
+) -> Result<(), Status> {
+    let mut validator = RequestValidator::new(limits);
+    validator.uuids("node_ids", &request.node_ids);
+    Ok(validator.finish()?)
+}
+
+/// Validate a batch create nodes request
+pub fn validate_batch_create_nodes_request(
+    request: &proto::BatchCreateNodesRequest,
+    limits: &RequestLimits,
+) -> Result<(), Status> {
+    let mut validator = RequestValidator::new(limits);
+    validator.batch("nodes", request.nodes.len());
+    Ok(validator.finish()?)
+}
+
+/// Validate a prompt request
+pub fn validate_add_prompt_request(
+    request: &proto::AddPromptRequest,
+    limits: &RequestLimits,
+) -> Result<(), Status> {
+    let mut validator = RequestValidator::new(limits);
+    validator.uuid("session_id", &request.session_id);
+    validator.content("content", &request.content);
+    Ok(validator.finish()?)
 }
 
 /// Validate a response request
-pub fn validate_add_response_request(request: &proto::AddResponseRequest) -> Result<(), Status> {
-    if request.prompt_id.is_empty() {
-        return Err(Status::invalid_argument("prompt_id cannot be empty"));
-    }
-    if request.content.is_empty() {
-        return Err(Status::invalid_argument("content cannot be empty"));
-    }
+pub fn validate_add_response_request(
+    request: &proto::AddResponseRequest,
+    limits: &RequestLimits,
+) -> Result<(), Status> {
+    let mut validator = RequestValidator::new(limits);
+    validator.uuid("prompt_id", &request.prompt_id);
+    validator.content("content", &request.content);
     if request.token_usage.is_none() {
-        return Err(Status::invalid_argument("token_usage is required"));
+        validator.violation("token_usage", "is required");
     }
-    Ok(())
+    Ok(validator.finish()?)
 }
 
 /// Validate a query request
-pub fn validate_query_request(request: &proto::QueryRequest) -> Result<(), Status> {
-    if let Some(limit) = request.limit.into() {
-        if limit > 10000 {
-            return Err(Status::invalid_argument(
-                "limit cannot exceed 10000",
-            ));
-        }
+pub fn validate_query_request(
+    request: &proto::QueryRequest,
+    limits: &RequestLimits,
+) -> Result<(), Status> {
+    let mut validator = RequestValidator::new(limits);
+    if let Some(session_id) = &request.session_id {
+        validator.uuid("session_id", session_id);
     }
-    Ok(())
+    validator.page_size("limit", i64::from(request.limit));
+    validator.offset("offset", i64::from(request.offset));
+    Ok(validator.finish()?)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::validation::InvalidRequest;
 
     #[test]
     fn test_validate_add_prompt_request() {
+        let limits = RequestLimits::default();
         let valid_req = proto::AddPromptRequest {
-            session_id: "test-session".to_string(),
+            session_id: uuid::Uuid::new_v4().to_string(),
             content: "Test prompt".to_string(),
             metadata: None,
+            idempotency_key: None,
         };
-        assert!(validate_add_prompt_request(&valid_req).is_ok());
+        assert!(validate_add_prompt_request(&valid_req, &limits).is_ok());
 
         let empty_session = proto::AddPromptRequest {
-            session_id: "".to_string(),
+            session_id: String::new(),
             content: "Test".to_string(),
             metadata: None,
+            idempotency_key: None,
         };
-        assert!(validate_add_prompt_request(&empty_session).is_err());
+        assert!(validate_add_prompt_request(&empty_session, &limits).is_err());
 
         let empty_content = proto::AddPromptRequest {
-            session_id: "test".to_string(),
-            content: "".to_string(),
+            session_id: uuid::Uuid::new_v4().to_string(),
+            content: String::new(),
+            metadata: None,
+            idempotency_key: None,
+        };
+        assert!(validate_add_prompt_request(&empty_content, &limits).is_err());
+
+        let invalid = proto::AddPromptRequest {
+            session_id: "test-session".to_string(),
+            content: String::new(),
             metadata: None,
+            idempotency_key: None,
         };
-        assert!(validate_add_prompt_request(&empty_content).is_err());
+        let status = validate_add_prompt_request(&invalid, &limits).unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+        let fields: Vec<_> = InvalidRequest::from_status(&status)
+            .unwrap()
+            .violations
+            .into_iter()
+            .map(|violation| violation.field)
+            .collect();
+        assert_eq!(fields, vec!["session_id", "content"]);
     }
 }
@@ -43,11 +43,17 @@
 //! }
 //! ```
 
-// Include generated protobuf code
-#[allow(clippy::all)]
-#[allow(missing_docs)]
-#[path = "llm.memory.graph.v1.rs"]
-pub mod proto;
+// Handlers return `tonic::Status`, protobuf counts are signed and the
+// service implements every generated message type
+#![allow(clippy::result_large_err)]
+#![allow(clippy::cast_possible_wrap)]
+#![allow(clippy::wildcard_imports)]
+
+/// Generated protobuf and gRPC types, built from `proto/memory_graph.proto`
+#[allow(missing_docs, clippy::all, clippy::pedantic)]
+pub mod proto {
+    tonic::include_proto!("llm.memory.graph.v1");
+}
 
 pub mod converters;
 pub mod handlers;
@@ -71,3 +77,27 @@ pub const DEFAULT_REQUEST_TIMEOUT_MS: u64 = 30_000;
 
 /// Maximum message size (100MB)
 pub const MAX_MESSAGE_SIZE: usize = 100 * 1024 * 1024;
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    /// The client crate, the repository root and the TypeScript client keep
+    /// copies of the service definition that must match this crate's
+    #[test]
+    fn test_proto_copies_match() {
+        let manifest = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let proto = include_str!("../../proto/memory_graph.proto");
+        for copy in [
+            "../llm-memory-graph-client/proto/memory_graph.proto",
+            "../../proto/memory_graph.proto",
+            "../../clients/typescript/proto/memory_graph.proto",
+        ] {
+            let contents = std::fs::read_to_string(manifest.join(copy)).unwrap();
+            assert!(
+                contents == proto,
+                "{copy} differs from proto/memory_graph.proto; copy it over"
+            );
+        }
+    }
+}
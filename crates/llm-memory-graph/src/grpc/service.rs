@@ -3,16 +3,22 @@
 //! This module implements the MemoryGraphService defined in the protobuf schema.
 //! It provides all CRUD operations, query interfaces, and streaming endpoints.
 
-use crate::engine::AsyncMemoryGraph;
+use crate::auth::{Operation, RbacPolicy, TenantGraphs, TenantId};
+use crate::engine::{AsyncMemoryGraph, DEFAULT_BULK_BATCH_SIZE};
 use crate::grpc::converters::*;
 use crate::grpc::proto::memory_graph_service_server::MemoryGraphService;
 use crate::grpc::proto::*;
+use crate::grpc::{handlers, streaming};
+use crate::observatory::exemplars;
 use crate::observatory::prometheus::PrometheusMetrics;
+use crate::validation::{validate_import_records, RequestLimits};
+use crate::Error;
+use futures::{Stream, StreamExt};
+use std::pin::Pin;
 use std::sync::Arc;
 use std::time::Instant as StdInstant;
-use tokio::sync::RwLock;
 use tonic::{Request, Response, Status};
-use tracing::{error, info, instrument, warn};
+use tracing::{info, instrument, warn};
 
 /// Service configuration
 #[derive(Debug, Clone)]
@@ -31,6 +37,8 @@ pub struct ServiceConfig {
     pub enable_health: bool,
     /// Server start time for uptime calculation
     pub start_time: StdInstant,
+    /// Size limits checked before a request reaches the graph
+    pub limits: RequestLimits,
 }
 
 impl Default for ServiceConfig {
@@ -43,6 +51,7 @@ impl Default for ServiceConfig {
             enable_reflection: true,
             enable_health: true,
             start_time: StdInstant::now(),
+            limits: RequestLimits::default(),
         }
     }
 }
@@ -51,10 +60,14 @@ impl Default for ServiceConfig {
 pub struct MemoryGraphServiceImpl {
     /// Core async memory graph
     graph: Arc<AsyncMemoryGraph>,
+    /// Per-tenant databases (`None` = every caller shares `graph`)
+    tenants: Option<Arc<TenantGraphs>>,
     /// Prometheus metrics (optional)
     metrics: Option<Arc<PrometheusMetrics>>,
     /// Service configuration
     config: ServiceConfig,
+    /// Access control policy (`None` = every caller may do everything)
+    rbac: Option<Arc<RbacPolicy>>,
 }
 
 impl MemoryGraphServiceImpl {
@@ -66,17 +79,84 @@ impl MemoryGraphServiceImpl {
     ) -> Self {
         Self {
             graph,
+            tenants: None,
             metrics,
             config,
+            rbac: None,
+        }
+    }
+
+    /// Enforce `policy` on every call
+    ///
+    /// Requires the service to sit behind [`crate::auth::AuthInterceptor`].
+    pub fn with_rbac(mut self, policy: Arc<RbacPolicy>) -> Self {
+        self.rbac = Some(policy);
+        self
+    }
+
+    /// Serve each caller from its tenant's database
+    ///
+    /// Requires the service to sit behind [`crate::auth::AuthInterceptor`].
+    pub fn with_tenants(mut self, tenants: Arc<TenantGraphs>) -> Self {
+        self.tenants = Some(tenants);
+        self
+    }
+
+    /// Check the caller's roles when RBAC is enabled
+    fn authorize<T>(&self, request: &Request<T>, operation: Operation) -> Result<(), Status> {
+        if let Some(policy) = &self.rbac {
+            policy.authorize_request(request, operation)?;
+        }
+        Ok(())
+    }
+
+    /// Authorize `operation` and pick the graph the caller's request runs on
+    async fn graph<T: Sync>(
+        &self,
+        request: &Request<T>,
+        operation: Operation,
+    ) -> Result<Arc<AsyncMemoryGraph>, Status> {
+        let tenant = self.caller_tenant(request, operation)?;
+        self.tenant_graph(tenant).await
+    }
+
+    /// Authorize `operation`, returning the caller's tenant when tenants are
+    /// kept apart
+    fn caller_tenant<T>(
+        &self,
+        request: &Request<T>,
+        operation: Operation,
+    ) -> Result<Option<TenantId>, Status> {
+        self.authorize(request, operation)?;
+        match self.tenants {
+            Some(_) => Ok(Some(crate::auth::principal(request)?.tenant.clone())),
+            None => Ok(None),
+        }
+    }
+
+    /// Graph of `tenant`, or the shared graph
+    async fn tenant_graph(
+        &self,
+        tenant: Option<TenantId>,
+    ) -> Result<Arc<AsyncMemoryGraph>, Status> {
+        match (&self.tenants, tenant) {
+            (Some(tenants), Some(tenant)) => tenants.graph(&tenant).await.map_err(error_to_status),
+            _ => Ok(Arc::clone(&self.graph)),
         }
     }
 
     /// Record gRPC request metrics
-    fn record_request(&self, method: &str, latency_secs: f64, success: bool) {
+    fn record_request(
+        &self,
+        method: &str,
+        trace_id: Option<&str>,
+        latency_secs: f64,
+        success: bool,
+    ) {
         if let Some(metrics) = &self.metrics {
             // Record in existing Prometheus metrics
             if success {
-                metrics.record_query_duration(latency_secs);
+                traced(trace_id, || metrics.record_query_duration(latency_secs));
             }
             // Additional gRPC-specific metrics would go here
             tracing::debug!(
@@ -87,10 +167,53 @@ impl MemoryGraphServiceImpl {
             );
         }
     }
+
+    /// Record a node written through the service
+    fn record_write(&self, node_type: crate::NodeType, trace_id: Option<&str>, latency_secs: f64) {
+        if let Some(metrics) = &self.metrics {
+            metrics.record_node_created_for(node_type.clone());
+            traced(trace_id, || {
+                metrics.record_write_latency_for(node_type, latency_secs);
+            });
+        }
+    }
+
+    /// Record the tokens and generation latency of a stored response
+    fn record_response(&self, response: &crate::ResponseNode) {
+        if let Some(metrics) = &self.metrics {
+            metrics.record_tokens(&response.metadata.model, &response.usage);
+            metrics.record_response_latency(
+                &response.metadata.model,
+                response.metadata.latency_ms as f64 / 1000.0,
+            );
+        }
+    }
+}
+
+/// Trace ID from the caller's `traceparent` header, if it sent one
+fn request_trace_id<T>(request: &Request<T>) -> Option<String> {
+    request
+        .metadata()
+        .get(exemplars::TRACEPARENT_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(exemplars::trace_id_from_traceparent)
+}
+
+/// Run `record` in the caller's trace, so the latencies it observes carry the
+/// trace ID as an exemplar
+fn traced(trace_id: Option<&str>, record: impl FnOnce()) {
+    match trace_id {
+        Some(trace_id) => exemplars::in_trace(trace_id, record),
+        None => record(),
+    }
 }
 
 #[tonic::async_trait]
 impl MemoryGraphService for MemoryGraphServiceImpl {
+    type StreamQueryStream = streaming::StreamQueryStream;
+    type StreamEventsStream = streaming::StreamEventsStream;
+    type SubscribeToSessionStream = streaming::SubscribeToSessionStream;
+
     // ========================================================================
     // Session Management
     // ========================================================================
@@ -100,20 +223,27 @@ impl MemoryGraphService for MemoryGraphServiceImpl {
         &self,
         request: Request<CreateSessionRequest>,
     ) -> Result<Response<Session>, Status> {
+        let graph = self.graph(&request, Operation::WriteNodes).await?;
         let start = StdInstant::now();
+        let trace_id = request_trace_id(&request);
         let req = request.into_inner();
 
         info!("Creating session with metadata: {:?}", req.metadata);
 
         let session = if req.metadata.is_empty() {
-            self.graph.create_session().await
+            graph.create_session().await
         } else {
-            self.graph.create_session_with_metadata(req.metadata).await
+            graph.create_session_with_metadata(req.metadata).await
         }
         .map_err(error_to_status)?;
 
         let proto_session = session_to_proto(session);
-        self.record_request("create_session", start.elapsed().as_secs_f64(), true);
+        self.record_request(
+            "create_session",
+            trace_id.as_deref(),
+            start.elapsed().as_secs_f64(),
+            true,
+        );
 
         Ok(Response::new(proto_session))
     }
@@ -123,14 +253,25 @@ impl MemoryGraphService for MemoryGraphServiceImpl {
         &self,
         request: Request<GetSessionRequest>,
     ) -> Result<Response<Session>, Status> {
+        let graph = self.graph(&request, Operation::ReadNodes).await?;
         let start = StdInstant::now();
+        let trace_id = request_trace_id(&request);
         let req = request.into_inner();
+        handlers::validate_session_id(&req.session_id, &self.config.limits)?;
 
-        let session_id = parse_session_id(&req.session_id)?;
-        let session = self.graph.get_session(session_id).await.map_err(error_to_status)?;
+        let session_id = parse_session_id(&req.session_id).map_err(error_to_status)?;
+        let session = graph
+            .get_session(session_id)
+            .await
+            .map_err(error_to_status)?;
 
         let proto_session = session_to_proto(session);
-        self.record_request("get_session", start.elapsed().as_secs_f64(), true);
+        self.record_request(
+            "get_session",
+            trace_id.as_deref(),
+            start.elapsed().as_secs_f64(),
+            true,
+        );
 
         Ok(Response::new(proto_session))
     }
@@ -140,12 +281,15 @@ impl MemoryGraphService for MemoryGraphServiceImpl {
         &self,
         request: Request<DeleteSessionRequest>,
     ) -> Result<Response<()>, Status> {
+        self.authorize(&request, Operation::DeleteSessions)?;
         let _start = StdInstant::now();
         let _req = request.into_inner();
 
         // TODO: Implement session deletion
         warn!("delete_session not yet implemented");
-        Err(Status::unimplemented("Session deletion not yet implemented"))
+        Err(Status::unimplemented(
+            "Session deletion not yet implemented",
+        ))
     }
 
     #[instrument(skip(self))]
@@ -153,12 +297,45 @@ impl MemoryGraphService for MemoryGraphServiceImpl {
         &self,
         request: Request<ListSessionsRequest>,
     ) -> Result<Response<ListSessionsResponse>, Status> {
+        let graph = self.graph(&request, Operation::ReadNodes).await?;
         let _start = StdInstant::now();
-        let _req = request.into_inner();
+        let req = request.into_inner();
+        handlers::validate_list_sessions_request(&req, &self.config.limits)?;
+
+        let cursor = parse_page_cursor(&req.cursor).map_err(error_to_status)?;
+        let page = graph
+            .list_sessions_page(cursor.as_ref(), page_size(req.limit))
+            .await
+            .map_err(error_to_status)?;
+        let stats = graph.stats().await.map_err(error_to_status)?;
 
-        // TODO: Implement session listing with pagination
-        warn!("list_sessions not yet implemented");
-        Err(Status::unimplemented("Session listing not yet implemented"))
+        Ok(Response::new(ListSessionsResponse {
+            total_count: stats.session_count as i64,
+            next_cursor: next_cursor_to_proto(page.next_cursor),
+            sessions: page.items.into_iter().map(session_to_proto).collect(),
+        }))
+    }
+
+    #[instrument(skip(self))]
+    async fn list_session_nodes(
+        &self,
+        request: Request<ListSessionNodesRequest>,
+    ) -> Result<Response<ListSessionNodesResponse>, Status> {
+        let graph = self.graph(&request, Operation::ReadNodes).await?;
+        let req = request.into_inner();
+        handlers::validate_list_session_nodes_request(&req, &self.config.limits)?;
+
+        let session_id = parse_session_id(&req.session_id).map_err(error_to_status)?;
+        let cursor = parse_page_cursor(&req.cursor).map_err(error_to_status)?;
+        let page = graph
+            .get_session_nodes_page(&session_id, cursor.as_ref(), page_size(req.limit))
+            .await
+            .map_err(error_to_status)?;
+
+        Ok(Response::new(ListSessionNodesResponse {
+            next_cursor: next_cursor_to_proto(page.next_cursor),
+            nodes: page.items.into_iter().map(node_to_proto).collect(),
+        }))
     }
 
     // ========================================================================
@@ -170,31 +347,39 @@ impl MemoryGraphService for MemoryGraphServiceImpl {
         &self,
         request: Request<CreateNodeRequest>,
     ) -> Result<Response<Node>, Status> {
+        self.authorize(&request, Operation::WriteNodes)?;
         let _start = StdInstant::now();
         let _req = request.into_inner();
 
         // TODO: Implement generic node creation
         warn!("create_node not yet implemented");
-        Err(Status::unimplemented("Generic node creation not yet implemented"))
+        Err(Status::unimplemented(
+            "Generic node creation not yet implemented",
+        ))
     }
 
     #[instrument(skip(self))]
-    async fn get_node(
-        &self,
-        request: Request<GetNodeRequest>,
-    ) -> Result<Response<Node>, Status> {
+    async fn get_node(&self, request: Request<GetNodeRequest>) -> Result<Response<Node>, Status> {
+        let graph = self.graph(&request, Operation::ReadNodes).await?;
         let start = StdInstant::now();
+        let trace_id = request_trace_id(&request);
         let req = request.into_inner();
+        handlers::validate_node_id(&req.node_id, &self.config.limits)?;
 
-        let node_id = parse_node_id(&req.node_id)?;
-        let node = self.graph
+        let node_id = parse_node_id(&req.node_id).map_err(error_to_status)?;
+        let node = graph
             .get_node(&node_id)
             .await
             .map_err(error_to_status)?
             .ok_or_else(|| Status::not_found(format!("Node {} not found", node_id)))?;
 
         let proto_node = node_to_proto(node);
-        self.record_request("get_node", start.elapsed().as_secs_f64(), true);
+        self.record_request(
+            "get_node",
+            trace_id.as_deref(),
+            start.elapsed().as_secs_f64(),
+            true,
+        );
 
         Ok(Response::new(proto_node))
     }
@@ -204,6 +389,7 @@ impl MemoryGraphService for MemoryGraphServiceImpl {
         &self,
         request: Request<UpdateNodeRequest>,
     ) -> Result<Response<Node>, Status> {
+        self.authorize(&request, Operation::WriteNodes)?;
         let _start = StdInstant::now();
         let _req = request.into_inner();
 
@@ -217,6 +403,7 @@ impl MemoryGraphService for MemoryGraphServiceImpl {
         &self,
         request: Request<DeleteNodeRequest>,
     ) -> Result<Response<()>, Status> {
+        self.authorize(&request, Operation::DeleteSessions)?;
         let _start = StdInstant::now();
         let _req = request.into_inner();
 
@@ -230,12 +417,16 @@ impl MemoryGraphService for MemoryGraphServiceImpl {
         &self,
         request: Request<BatchCreateNodesRequest>,
     ) -> Result<Response<BatchCreateNodesResponse>, Status> {
+        self.authorize(&request, Operation::WriteNodes)?;
         let _start = StdInstant::now();
-        let _req = request.into_inner();
+        let req = request.into_inner();
+        handlers::validate_batch_create_nodes_request(&req, &self.config.limits)?;
 
         // TODO: Implement batch node creation
         warn!("batch_create_nodes not yet implemented");
-        Err(Status::unimplemented("Batch node creation not yet implemented"))
+        Err(Status::unimplemented(
+            "Batch node creation not yet implemented",
+        ))
     }
 
     #[instrument(skip(self))]
@@ -243,17 +434,16 @@ impl MemoryGraphService for MemoryGraphServiceImpl {
         &self,
         request: Request<BatchGetNodesRequest>,
     ) -> Result<Response<BatchGetNodesResponse>, Status> {
+        let graph = self.graph(&request, Operation::ReadNodes).await?;
         let start = StdInstant::now();
+        let trace_id = request_trace_id(&request);
         let req = request.into_inner();
+        handlers::validate_batch_get_nodes_request(&req, &self.config.limits)?;
 
-        let node_ids: Result<Vec<_>, _> = req
-            .node_ids
-            .iter()
-            .map(|id| parse_node_id(id))
-            .collect();
-        let node_ids = node_ids?;
+        let node_ids: Result<Vec<_>, _> = req.node_ids.iter().map(|id| parse_node_id(id)).collect();
+        let node_ids = node_ids.map_err(error_to_status)?;
 
-        let nodes = self.graph
+        let nodes = graph
             .get_nodes_batch(node_ids)
             .await
             .map_err(error_to_status)?;
@@ -263,11 +453,56 @@ impl MemoryGraphService for MemoryGraphServiceImpl {
             .filter_map(|opt_node| opt_node.map(node_to_proto))
             .collect();
 
-        self.record_request("batch_get_nodes", start.elapsed().as_secs_f64(), true);
+        self.record_request(
+            "batch_get_nodes",
+            trace_id.as_deref(),
+            start.elapsed().as_secs_f64(),
+            true,
+        );
 
-        Ok(Response::new(BatchGetNodesResponse {
-            nodes: proto_nodes,
-        }))
+        Ok(Response::new(BatchGetNodesResponse { nodes: proto_nodes }))
+    }
+
+    type ImportNodesStream = Pin<Box<dyn Stream<Item = Result<ImportProgress, Status>> + Send>>;
+
+    #[instrument(skip(self, request))]
+    async fn import_nodes(
+        &self,
+        request: Request<tonic::Streaming<ImportRecord>>,
+    ) -> Result<Response<Self::ImportNodesStream>, Status> {
+        let tenant = self.caller_tenant(&request, Operation::WriteNodes)?;
+        let records = request.into_inner();
+        let graph = self.tenant_graph(tenant).await?;
+        let limits = self.config.limits;
+
+        // The import runs on its own task so acks flow while records arrive
+        let (progress, mut acks) = tokio::sync::mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            let mut message = 0;
+            let records = records.map(move |record| {
+                message += 1;
+                let record = record.map_err(|status| Error::GrpcError(status.to_string()))?;
+                serde_json::from_str(&record.json)
+                    .map_err(|e| Error::DeserializationError(format!("message {message}: {e}")))
+            });
+            let records = validate_import_records(records, limits);
+            let result = graph
+                .import_records(records, DEFAULT_BULK_BATCH_SIZE, |report| {
+                    let _ = progress.send(Ok(import_progress_to_proto(report, false)));
+                })
+                .await;
+            let _ = progress.send(
+                result
+                    .map(|report| import_progress_to_proto(&report, true))
+                    .map_err(error_to_status),
+            );
+        });
+
+        Ok(Response::new(Box::pin(async_stream::stream! {
+            while let Some(ack) = acks.recv().await {
+                yield ack;
+            }
+        })))
     }
 
     // ========================================================================
@@ -279,6 +514,7 @@ impl MemoryGraphService for MemoryGraphServiceImpl {
         &self,
         request: Request<CreateEdgeRequest>,
     ) -> Result<Response<Edge>, Status> {
+        self.authorize(&request, Operation::WriteNodes)?;
         let _start = StdInstant::now();
         let _req = request.into_inner();
 
@@ -292,34 +528,74 @@ impl MemoryGraphService for MemoryGraphServiceImpl {
         &self,
         request: Request<GetEdgesRequest>,
     ) -> Result<Response<GetEdgesResponse>, Status> {
+        let graph = self.graph(&request, Operation::ReadNodes).await?;
         let start = StdInstant::now();
+        let trace_id = request_trace_id(&request);
         let req = request.into_inner();
+        handlers::validate_get_edges_request(&req, &self.config.limits)?;
 
-        let node_id = parse_node_id(&req.node_id)?;
+        let node_id = parse_node_id(&req.node_id).map_err(error_to_status)?;
+
+        if req.limit > 0 || !req.cursor.is_empty() {
+            let direction = if req.direction == Some(EdgeDirection::Incoming as i32) {
+                crate::pagination::EdgeDirection::Incoming
+            } else {
+                crate::pagination::EdgeDirection::Outgoing
+            };
+            let cursor = parse_page_cursor(&req.cursor).map_err(error_to_status)?;
+            let page = graph
+                .get_edges_page(&node_id, direction, cursor.as_ref(), page_size(req.limit))
+                .await
+                .map_err(error_to_status)?;
+            self.record_request(
+                "get_edges",
+                trace_id.as_deref(),
+                start.elapsed().as_secs_f64(),
+                true,
+            );
+            return Ok(Response::new(GetEdgesResponse {
+                next_cursor: next_cursor_to_proto(page.next_cursor),
+                edges: page.items.into_iter().map(edge_to_proto).collect(),
+            }));
+        }
 
         // Get outgoing edges by default, or as specified
         let edges = match req.direction {
-            Some(dir) if dir == EdgeDirection::EdgeDirectionIncoming as i32 => {
-                self.graph.get_incoming_edges(&node_id).await
+            Some(dir) if dir == EdgeDirection::Incoming as i32 => {
+                graph.get_incoming_edges(&node_id).await
             }
-            Some(dir) if dir == EdgeDirection::EdgeDirectionOutgoing as i32 => {
-                self.graph.get_outgoing_edges(&node_id).await
+            Some(dir) if dir == EdgeDirection::Outgoing as i32 => {
+                graph.get_outgoing_edges(&node_id).await
             }
-            Some(dir) if dir == EdgeDirection::EdgeDirectionBoth as i32 => {
+            Some(dir) if dir == EdgeDirection::Both as i32 => {
                 // Get both incoming and outgoing
-                let mut outgoing = self.graph.get_outgoing_edges(&node_id).await.map_err(error_to_status)?;
-                let mut incoming = self.graph.get_incoming_edges(&node_id).await.map_err(error_to_status)?;
+                let mut outgoing = graph
+                    .get_outgoing_edges(&node_id)
+                    .await
+                    .map_err(error_to_status)?;
+                let mut incoming = graph
+                    .get_incoming_edges(&node_id)
+                    .await
+                    .map_err(error_to_status)?;
                 outgoing.append(&mut incoming);
                 Ok(outgoing)
             }
-            _ => self.graph.get_outgoing_edges(&node_id).await,
+            _ => graph.get_outgoing_edges(&node_id).await,
         }
         .map_err(error_to_status)?;
 
         let proto_edges: Vec<Edge> = edges.into_iter().map(edge_to_proto).collect();
-        self.record_request("get_edges", start.elapsed().as_secs_f64(), true);
-
-        Ok(Response::new(GetEdgesResponse { edges: proto_edges }))
+        self.record_request(
+            "get_edges",
+            trace_id.as_deref(),
+            start.elapsed().as_secs_f64(),
+            true,
+        );
+
+        Ok(Response::new(GetEdgesResponse {
+            edges: proto_edges,
+            next_cursor: String::new(),
+        }))
     }
 
     #[instrument(skip(self))]
@@ -327,6 +603,7 @@ impl MemoryGraphService for MemoryGraphServiceImpl {
         &self,
         request: Request<DeleteEdgeRequest>,
     ) -> Result<Response<()>, Status> {
+        self.authorize(&request, Operation::DeleteSessions)?;
         let _start = StdInstant::now();
         let _req = request.into_inner();
 
@@ -344,8 +621,10 @@ impl MemoryGraphService for MemoryGraphServiceImpl {
         &self,
         request: Request<QueryRequest>,
     ) -> Result<Response<QueryResponse>, Status> {
+        self.authorize(&request, Operation::ReadNodes)?;
         let _start = StdInstant::now();
-        let _req = request.into_inner();
+        let req = request.into_inner();
+        handlers::validate_query_request(&req, &self.config.limits)?;
 
         // TODO: Implement query operation
         warn!("query not yet implemented");
@@ -357,8 +636,10 @@ impl MemoryGraphService for MemoryGraphServiceImpl {
         &self,
         request: Request<QueryRequest>,
     ) -> Result<Response<Self::StreamQueryStream>, Status> {
+        self.authorize(&request, Operation::ReadNodes)?;
         let _start = StdInstant::now();
-        let _req = request.into_inner();
+        let req = request.into_inner();
+        handlers::validate_query_request(&req, &self.config.limits)?;
 
         // TODO: Implement streaming query
         warn!("stream_query not yet implemented");
@@ -374,19 +655,32 @@ impl MemoryGraphService for MemoryGraphServiceImpl {
         &self,
         request: Request<AddPromptRequest>,
     ) -> Result<Response<PromptNode>, Status> {
+        let graph = self.graph(&request, Operation::WriteNodes).await?;
         let start = StdInstant::now();
+        let trace_id = request_trace_id(&request);
         let req = request.into_inner();
+        handlers::validate_add_prompt_request(&req, &self.config.limits)?;
 
-        let session_id = parse_session_id(&req.session_id)?;
+        let session_id = parse_session_id(&req.session_id).map_err(error_to_status)?;
         let metadata = req.metadata.map(proto_to_prompt_metadata);
 
-        let prompt_id = self.graph
-            .add_prompt(session_id, req.content, metadata)
-            .await
-            .map_err(error_to_status)?;
+        let prompt_id = match req.idempotency_key.as_deref() {
+            Some(key) => {
+                graph
+                    .add_prompt_idempotent(key, session_id, req.content, metadata)
+                    .await
+            }
+            None => graph.add_prompt(session_id, req.content, metadata).await,
+        }
+        .map_err(error_to_status)?;
+        self.record_write(
+            crate::NodeType::Prompt,
+            trace_id.as_deref(),
+            start.elapsed().as_secs_f64(),
+        );
 
         // Retrieve the created prompt
-        let node = self.graph
+        let node = graph
             .get_node(&prompt_id)
             .await
             .map_err(error_to_status)?
@@ -397,7 +691,12 @@ impl MemoryGraphService for MemoryGraphServiceImpl {
             _ => return Err(Status::internal("Unexpected node type")),
         };
 
-        self.record_request("add_prompt", start.elapsed().as_secs_f64(), true);
+        self.record_request(
+            "add_prompt",
+            trace_id.as_deref(),
+            start.elapsed().as_secs_f64(),
+            true,
+        );
         Ok(Response::new(proto_prompt))
     }
 
@@ -406,33 +705,59 @@ impl MemoryGraphService for MemoryGraphServiceImpl {
         &self,
         request: Request<AddResponseRequest>,
     ) -> Result<Response<ResponseNode>, Status> {
+        let graph = self.graph(&request, Operation::WriteNodes).await?;
         let start = StdInstant::now();
+        let trace_id = request_trace_id(&request);
         let req = request.into_inner();
+        handlers::validate_add_response_request(&req, &self.config.limits)?;
 
-        let prompt_id = parse_node_id(&req.prompt_id)?;
-        let token_usage = req.token_usage
+        let prompt_id = parse_node_id(&req.prompt_id).map_err(error_to_status)?;
+        let token_usage = req
+            .token_usage
             .ok_or_else(|| Status::invalid_argument("Missing token_usage"))?;
         let token_usage = proto_to_token_usage(token_usage);
         let metadata = req.metadata.map(proto_to_response_metadata);
 
-        let response_id = self.graph
-            .add_response(prompt_id, req.content, token_usage, metadata)
-            .await
-            .map_err(error_to_status)?;
+        let response_id = match req.idempotency_key.as_deref() {
+            Some(key) => {
+                graph
+                    .add_response_idempotent(key, prompt_id, req.content, token_usage, metadata)
+                    .await
+            }
+            None => {
+                graph
+                    .add_response(prompt_id, req.content, token_usage, metadata)
+                    .await
+            }
+        }
+        .map_err(error_to_status)?;
+        self.record_write(
+            crate::NodeType::Response,
+            trace_id.as_deref(),
+            start.elapsed().as_secs_f64(),
+        );
 
         // Retrieve the created response
-        let node = self.graph
+        let node = graph
             .get_node(&response_id)
             .await
             .map_err(error_to_status)?
             .ok_or_else(|| Status::internal("Failed to retrieve created response"))?;
 
         let proto_response = match node {
-            crate::Node::Response(r) => response_node_to_proto(r),
+            crate::Node::Response(r) => {
+                self.record_response(&r);
+                response_node_to_proto(r)
+            }
             _ => return Err(Status::internal("Unexpected node type")),
         };
 
-        self.record_request("add_response", start.elapsed().as_secs_f64(), true);
+        self.record_request(
+            "add_response",
+            trace_id.as_deref(),
+            start.elapsed().as_secs_f64(),
+            true,
+        );
         Ok(Response::new(proto_response))
     }
 
@@ -441,6 +766,7 @@ impl MemoryGraphService for MemoryGraphServiceImpl {
         &self,
         request: Request<AddToolInvocationRequest>,
     ) -> Result<Response<ToolInvocationNode>, Status> {
+        self.authorize(&request, Operation::WriteNodes)?;
         let _start = StdInstant::now();
         let _req = request.into_inner();
 
@@ -458,12 +784,15 @@ impl MemoryGraphService for MemoryGraphServiceImpl {
         &self,
         request: Request<CreateTemplateRequest>,
     ) -> Result<Response<TemplateNode>, Status> {
+        self.authorize(&request, Operation::ManageTemplates)?;
         let _start = StdInstant::now();
         let _req = request.into_inner();
 
         // TODO: Implement template creation
         warn!("create_template not yet implemented");
-        Err(Status::unimplemented("Template creation not yet implemented"))
+        Err(Status::unimplemented(
+            "Template creation not yet implemented",
+        ))
     }
 
     #[instrument(skip(self))]
@@ -471,12 +800,166 @@ impl MemoryGraphService for MemoryGraphServiceImpl {
         &self,
         request: Request<InstantiateTemplateRequest>,
     ) -> Result<Response<PromptNode>, Status> {
+        self.authorize(&request, Operation::WriteNodes)?;
         let _start = StdInstant::now();
         let _req = request.into_inner();
 
         // TODO: Implement template instantiation
         warn!("instantiate_template not yet implemented");
-        Err(Status::unimplemented("Template instantiation not yet implemented"))
+        Err(Status::unimplemented(
+            "Template instantiation not yet implemented",
+        ))
+    }
+
+    #[instrument(skip(self))]
+    async fn list_templates(
+        &self,
+        request: Request<ListTemplatesRequest>,
+    ) -> Result<Response<ListTemplatesResponse>, Status> {
+        let graph = self.graph(&request, Operation::ReadNodes).await?;
+        let req = request.into_inner();
+        handlers::validate_page_request(req.limit, &req.cursor, &self.config.limits)?;
+
+        let cursor = parse_page_cursor(&req.cursor).map_err(error_to_status)?;
+        let page = graph
+            .list_templates_page(cursor.as_ref(), page_size(req.limit))
+            .await
+            .map_err(error_to_status)?;
+
+        Ok(Response::new(ListTemplatesResponse {
+            next_cursor: next_cursor_to_proto(page.next_cursor),
+            templates: page.items.into_iter().map(template_to_proto).collect(),
+        }))
+    }
+
+    // ========================================================================
+    // Agent Operations
+    // ========================================================================
+
+    #[instrument(skip(self))]
+    async fn list_agents(
+        &self,
+        request: Request<ListAgentsRequest>,
+    ) -> Result<Response<ListAgentsResponse>, Status> {
+        let graph = self.graph(&request, Operation::ReadNodes).await?;
+        let req = request.into_inner();
+        handlers::validate_page_request(req.limit, &req.cursor, &self.config.limits)?;
+
+        let cursor = parse_page_cursor(&req.cursor).map_err(error_to_status)?;
+        let page = graph
+            .list_agents_page(cursor.as_ref(), page_size(req.limit))
+            .await
+            .map_err(error_to_status)?;
+
+        Ok(Response::new(ListAgentsResponse {
+            next_cursor: next_cursor_to_proto(page.next_cursor),
+            agents: page.items.into_iter().map(agent_node_to_proto).collect(),
+        }))
+    }
+
+    // ========================================================================
+    // Alias Operations
+    // ========================================================================
+
+    #[instrument(skip(self))]
+    async fn set_alias(
+        &self,
+        request: Request<SetAliasRequest>,
+    ) -> Result<Response<Alias>, Status> {
+        let graph = self.graph(&request, Operation::WriteNodes).await?;
+        let start = StdInstant::now();
+        let trace_id = request_trace_id(&request);
+        let req = request.into_inner();
+
+        let target = proto_to_alias_target(req.target).map_err(error_to_status)?;
+        graph
+            .set_alias(&req.alias, target)
+            .await
+            .map_err(error_to_status)?;
+
+        self.record_request(
+            "set_alias",
+            trace_id.as_deref(),
+            start.elapsed().as_secs_f64(),
+            true,
+        );
+        Ok(Response::new(alias_to_proto(req.alias, target)))
+    }
+
+    #[instrument(skip(self))]
+    async fn resolve_alias(
+        &self,
+        request: Request<ResolveAliasRequest>,
+    ) -> Result<Response<Alias>, Status> {
+        let graph = self.graph(&request, Operation::ReadNodes).await?;
+        let start = StdInstant::now();
+        let trace_id = request_trace_id(&request);
+        let req = request.into_inner();
+
+        let target = graph
+            .resolve_alias(&req.alias)
+            .await
+            .map_err(error_to_status)?
+            .ok_or_else(|| Status::not_found(format!("Alias not set: {}", req.alias)))?;
+
+        self.record_request(
+            "resolve_alias",
+            trace_id.as_deref(),
+            start.elapsed().as_secs_f64(),
+            true,
+        );
+        Ok(Response::new(alias_to_proto(req.alias, target)))
+    }
+
+    #[instrument(skip(self))]
+    async fn remove_alias(
+        &self,
+        request: Request<RemoveAliasRequest>,
+    ) -> Result<Response<()>, Status> {
+        let graph = self.graph(&request, Operation::WriteNodes).await?;
+        let start = StdInstant::now();
+        let trace_id = request_trace_id(&request);
+        let req = request.into_inner();
+
+        graph
+            .remove_alias(&req.alias)
+            .await
+            .map_err(error_to_status)?;
+
+        self.record_request(
+            "remove_alias",
+            trace_id.as_deref(),
+            start.elapsed().as_secs_f64(),
+            true,
+        );
+        Ok(Response::new(()))
+    }
+
+    #[instrument(skip(self))]
+    async fn list_aliases(
+        &self,
+        request: Request<ListAliasesRequest>,
+    ) -> Result<Response<ListAliasesResponse>, Status> {
+        let graph = self.graph(&request, Operation::ReadNodes).await?;
+        let start = StdInstant::now();
+        let trace_id = request_trace_id(&request);
+        let req = request.into_inner();
+
+        let aliases = graph
+            .list_aliases(&req.prefix)
+            .await
+            .map_err(error_to_status)?
+            .into_iter()
+            .map(|(alias, target)| alias_to_proto(alias, target))
+            .collect();
+
+        self.record_request(
+            "list_aliases",
+            trace_id.as_deref(),
+            start.elapsed().as_secs_f64(),
+            true,
+        );
+        Ok(Response::new(ListAliasesResponse { aliases }))
     }
 
     // ========================================================================
@@ -488,6 +971,7 @@ impl MemoryGraphService for MemoryGraphServiceImpl {
         &self,
         request: Request<StreamEventsRequest>,
     ) -> Result<Response<Self::StreamEventsStream>, Status> {
+        self.authorize(&request, Operation::ReadNodes)?;
         let _start = StdInstant::now();
         let _req = request.into_inner();
 
@@ -501,12 +985,15 @@ impl MemoryGraphService for MemoryGraphServiceImpl {
         &self,
         request: Request<SubscribeRequest>,
     ) -> Result<Response<Self::SubscribeToSessionStream>, Status> {
+        self.authorize(&request, Operation::ReadNodes)?;
         let _start = StdInstant::now();
         let _req = request.into_inner();
 
         // TODO: Implement session subscription
         warn!("subscribe_to_session not yet implemented");
-        Err(Status::unimplemented("Session subscription not yet implemented"))
+        Err(Status::unimplemented(
+            "Session subscription not yet implemented",
+        ))
     }
 
     // ========================================================================
@@ -514,29 +1001,36 @@ impl MemoryGraphService for MemoryGraphServiceImpl {
     // ========================================================================
 
     #[instrument(skip(self))]
-    async fn health(
-        &self,
-        _request: Request<()>,
-    ) -> Result<Response<HealthResponse>, Status> {
+    async fn health(&self, _request: Request<()>) -> Result<Response<HealthResponse>, Status> {
+        let report = self.graph.health().await;
+        let status = if report.is_serving() {
+            health_response::ServingStatus::Serving
+        } else {
+            health_response::ServingStatus::NotServing
+        };
+
         Ok(Response::new(HealthResponse {
-            status: health_response::ServingStatus::Serving as i32,
+            status: status as i32,
             version: env!("CARGO_PKG_VERSION").to_string(),
             uptime_seconds: self.config.start_time.elapsed().as_secs() as i64,
+            components: report
+                .components
+                .into_iter()
+                .map(component_health_to_proto)
+                .collect(),
         }))
     }
 
     #[instrument(skip(self))]
-    async fn get_metrics(
-        &self,
-        _request: Request<()>,
-    ) -> Result<Response<MetricsResponse>, Status> {
-        let stats = self.graph.stats().await.map_err(error_to_status)?;
+    async fn get_metrics(&self, request: Request<()>) -> Result<Response<MetricsResponse>, Status> {
+        let graph = self.graph(&request, Operation::ReadNodes).await?;
+        let stats = graph.stats().await.map_err(error_to_status)?;
 
         // Get Prometheus metrics if available
         let (active_sessions, avg_write_latency_ms, avg_read_latency_ms) =
-            if let Some(graph_metrics) = self.graph.get_metrics() {
+            if let Some(graph_metrics) = graph.get_metrics() {
                 (
-                    graph_metrics.sessions_created as i64,
+                    stats.session_count as i64,
                     graph_metrics.avg_write_latency_ms,
                     graph_metrics.avg_read_latency_ms,
                 )
@@ -555,3 +1049,296 @@ impl MemoryGraphService for MemoryGraphServiceImpl {
         }))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grpc::proto::memory_graph_service_client::MemoryGraphServiceClient;
+    use crate::grpc::proto::memory_graph_service_server::MemoryGraphServiceServer;
+    use crate::Config;
+    use tempfile::tempdir;
+    use tonic::transport::Channel;
+
+    /// Serve `graph` on a local port and connect a client to it
+    async fn client(graph: Arc<AsyncMemoryGraph>) -> MemoryGraphServiceClient<Channel> {
+        client_with_metrics(graph, None).await
+    }
+
+    /// Like [`client`], recording into `metrics`
+    async fn client_with_metrics(
+        graph: Arc<AsyncMemoryGraph>,
+        metrics: Option<Arc<PrometheusMetrics>>,
+    ) -> MemoryGraphServiceClient<Channel> {
+        let service = MemoryGraphServiceImpl::new(graph, metrics, ServiceConfig::default());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let incoming = async_stream::stream! {
+            loop {
+                yield listener.accept().await.map(|(stream, _)| stream);
+            }
+        };
+        tokio::spawn(
+            tonic::transport::Server::builder()
+                .add_service(MemoryGraphServiceServer::new(service))
+                .serve_with_incoming(incoming),
+        );
+        MemoryGraphServiceClient::connect(format!("http://{addr}"))
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_list_sessions_pages_with_cursor() {
+        let dir = tempdir().unwrap();
+        let graph = Arc::new(
+            AsyncMemoryGraph::open(Config::new(dir.path()))
+                .await
+                .unwrap(),
+        );
+        let mut client = client(Arc::clone(&graph)).await;
+        for _ in 0..5 {
+            client
+                .create_session(CreateSessionRequest::default())
+                .await
+                .unwrap();
+        }
+
+        let mut seen = Vec::new();
+        let mut cursor = String::new();
+        loop {
+            let page = client
+                .list_sessions(ListSessionsRequest {
+                    limit: 2,
+                    cursor,
+                    ..Default::default()
+                })
+                .await
+                .unwrap()
+                .into_inner();
+            assert!(page.sessions.len() <= 2);
+            seen.extend(page.sessions.into_iter().map(|session| session.id));
+            if page.next_cursor.is_empty() {
+                break;
+            }
+            cursor = page.next_cursor;
+        }
+        seen.sort();
+        seen.dedup();
+        assert_eq!(seen.len(), 5);
+
+        let status = client
+            .list_sessions(ListSessionsRequest {
+                cursor: "bogus".to_string(),
+                ..Default::default()
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn test_alias_rpcs() {
+        let dir = tempdir().unwrap();
+        let graph = Arc::new(
+            AsyncMemoryGraph::open(Config::new(dir.path()))
+                .await
+                .unwrap(),
+        );
+        let mut client = client(graph).await;
+        let session = client
+            .create_session(CreateSessionRequest::default())
+            .await
+            .unwrap()
+            .into_inner();
+
+        client
+            .set_alias(SetAliasRequest {
+                alias: "ticket-42".to_string(),
+                target: Some(set_alias_request::Target::SessionId(session.id.clone())),
+            })
+            .await
+            .unwrap();
+        let alias = client
+            .resolve_alias(ResolveAliasRequest {
+                alias: "ticket-42".to_string(),
+            })
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(alias.target, Some(alias::Target::SessionId(session.id)));
+
+        let listed = client
+            .list_aliases(ListAliasesRequest {
+                prefix: "ticket-".to_string(),
+            })
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(listed.aliases.len(), 1);
+
+        client
+            .remove_alias(RemoveAliasRequest {
+                alias: "ticket-42".to_string(),
+            })
+            .await
+            .unwrap();
+        let status = client
+            .resolve_alias(ResolveAliasRequest {
+                alias: "ticket-42".to_string(),
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::NotFound);
+    }
+
+    #[tokio::test]
+    async fn test_import_nodes_streams_progress() {
+        let dir = tempdir().unwrap();
+        let graph = Arc::new(
+            AsyncMemoryGraph::open(Config::new(dir.path()))
+                .await
+                .unwrap(),
+        );
+        let mut client = client(Arc::clone(&graph)).await;
+
+        let session = crate::ConversationSession::new();
+        let prompt = crate::PromptNode::new(session.id, "Imported".to_string());
+        let prompt_id = prompt.id;
+        let records: Vec<_> = [crate::Node::Session(session), crate::Node::Prompt(prompt)]
+            .into_iter()
+            .map(|node| ImportRecord {
+                json: serde_json::to_string(&crate::engine::ImportRecord::Node(node)).unwrap(),
+            })
+            .collect();
+
+        let mut acks = client
+            .import_nodes(futures::stream::iter(records))
+            .await
+            .unwrap()
+            .into_inner();
+        let mut last = None;
+        while let Some(ack) = acks.message().await.unwrap() {
+            last = Some(ack);
+        }
+        let last = last.unwrap();
+        assert!(last.done);
+        assert_eq!(last.nodes_loaded, 2);
+        assert!(graph.get_node(&prompt_id).await.unwrap().is_some());
+
+        let bad = ImportRecord {
+            json: "not json".to_string(),
+        };
+        let mut acks = client
+            .import_nodes(futures::stream::iter(vec![bad]))
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(acks.message().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_writes_record_labeled_metrics() {
+        use crate::observatory::prometheus::MetricLabels;
+
+        let dir = tempdir().unwrap();
+        let graph = Arc::new(
+            AsyncMemoryGraph::open(Config::new(dir.path()))
+                .await
+                .unwrap(),
+        );
+        let registry = prometheus::Registry::new();
+        let metrics =
+            Arc::new(PrometheusMetrics::with_labels(&registry, MetricLabels::enabled()).unwrap());
+        let mut client = client_with_metrics(graph, Some(metrics)).await;
+
+        let session = client
+            .create_session(CreateSessionRequest::default())
+            .await
+            .unwrap()
+            .into_inner();
+        let prompt = client
+            .add_prompt(AddPromptRequest {
+                session_id: session.id,
+                content: "Hello".to_string(),
+                ..Default::default()
+            })
+            .await
+            .unwrap()
+            .into_inner();
+        client
+            .add_response(AddResponseRequest {
+                prompt_id: prompt.id,
+                content: "Hi".to_string(),
+                token_usage: Some(TokenUsage {
+                    prompt_tokens: 3,
+                    completion_tokens: 5,
+                    total_tokens: 8,
+                }),
+                metadata: Some(ResponseMetadata {
+                    model: "gpt-4".to_string(),
+                    latency_ms: 1200,
+                    ..Default::default()
+                }),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        let text = prometheus::TextEncoder::new()
+            .encode_to_string(&registry.gather())
+            .unwrap();
+        assert!(text.contains(r#"memory_graph_nodes_created_by_type_total{node_type="prompt"} 1"#));
+        assert!(
+            text.contains(r#"memory_graph_nodes_created_by_type_total{node_type="response"} 1"#)
+        );
+        assert!(text.contains(r#"memory_graph_tokens_total{kind="completion",model="gpt-4"} 5"#));
+        assert!(text
+            .contains(r#"memory_graph_write_latency_by_type_seconds_count{node_type="prompt"} 1"#));
+        assert!(text.contains(r#"memory_graph_response_latency_seconds_count{model="gpt-4"} 1"#));
+    }
+
+    #[tokio::test]
+    async fn test_traced_requests_attach_exemplars() {
+        const TRACE_ID: &str = "4bf92f3577b34da6a3ce929d0e0e4736";
+
+        let dir = tempdir().unwrap();
+        let graph = Arc::new(
+            AsyncMemoryGraph::open(Config::new(dir.path()))
+                .await
+                .unwrap(),
+        );
+        let registry = prometheus::Registry::new();
+        let metrics = Arc::new(PrometheusMetrics::new(&registry).unwrap());
+        let mut client = client_with_metrics(graph, Some(Arc::clone(&metrics))).await;
+
+        let session = client
+            .create_session(CreateSessionRequest::default())
+            .await
+            .unwrap()
+            .into_inner();
+        let mut request = Request::new(AddPromptRequest {
+            session_id: session.id,
+            content: "Hello".to_string(),
+            ..Default::default()
+        });
+        request.metadata_mut().insert(
+            exemplars::TRACEPARENT_HEADER,
+            format!("00-{TRACE_ID}-00f067aa0ba902b7-01")
+                .parse()
+                .unwrap(),
+        );
+        client.add_prompt(request).await.unwrap();
+
+        let text = metrics.encode_openmetrics(&registry).unwrap();
+        let traced: Vec<&str> = text
+            .lines()
+            .filter(|line| line.contains(&format!("trace_id=\"{TRACE_ID}\"")))
+            .collect();
+        assert!(traced
+            .iter()
+            .any(|line| line.starts_with("memory_graph_write_latency_seconds_bucket")));
+        assert!(traced
+            .iter()
+            .any(|line| line.starts_with("memory_graph_query_duration_seconds_bucket")));
+    }
+}
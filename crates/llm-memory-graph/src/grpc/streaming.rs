@@ -23,11 +23,11 @@ pub type SubscribeToSessionStream =
 /// Create a streaming query handler
 ///
 /// This will be implemented to provide efficient streaming of large result sets
-pub async fn create_query_stream(
-    _query: proto::QueryRequest,
-) -> Result<StreamQueryStream, Status> {
+pub async fn create_query_stream(_query: proto::QueryRequest) -> Result<StreamQueryStream, Status> {
     // TODO: Implement streaming query logic
-    Err(Status::unimplemented("Streaming queries not yet implemented"))
+    Err(Status::unimplemented(
+        "Streaming queries not yet implemented",
+    ))
 }
 
 /// Create an event stream
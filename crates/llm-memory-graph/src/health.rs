@@ -0,0 +1,273 @@
+//! Structured health reporting
+//!
+//! [`AsyncMemoryGraph::health`](crate::engine::AsyncMemoryGraph::health)
+//! probes the subsystems a graph owns (storage, cache, Observatory publisher)
+//! and returns a [`HealthReport`] with one [`ComponentHealth`] per subsystem.
+//! Components the graph does not own, such as a
+//! [`PluginManager`] or the integration clients,
+//! implement [`HealthCheck`] and are registered with
+//! [`AsyncMemoryGraph::add_health_check`](crate::engine::AsyncMemoryGraph::add_health_check),
+//! so every caller of `health` sees them.
+//!
+//! The overall status is the worst component status, which maps directly onto
+//! gRPC `SERVING`/`NOT_SERVING` and HTTP 200/503 responses.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use llm_memory_graph::engine::AsyncMemoryGraph;
+//! use llm_memory_graph::plugin::PluginManager;
+//! use llm_memory_graph::Config;
+//! use std::sync::Arc;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let graph = AsyncMemoryGraph::open(Config::default()).await?;
+//! graph.add_health_check(Arc::new(PluginManager::new()));
+//!
+//! let report = graph.health().await;
+//! if !report.is_serving() {
+//!     for component in &report.components {
+//!         println!("{}: {} {:?}", component.name, component.status, component.message);
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::integrations::registry::RegistryClient;
+use crate::integrations::vault::VaultClient;
+use crate::integrations::CircuitState;
+use crate::plugin::PluginManager;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::time::Duration;
+
+/// Health of a component, ordered from best to worst
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HealthStatus {
+    /// Working normally
+    Healthy,
+    /// Working, but slow or partially unavailable
+    Degraded,
+    /// Not working
+    Unhealthy,
+}
+
+impl fmt::Display for HealthStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            HealthStatus::Healthy => "healthy",
+            HealthStatus::Degraded => "degraded",
+            HealthStatus::Unhealthy => "unhealthy",
+        })
+    }
+}
+
+impl From<CircuitState> for HealthStatus {
+    fn from(state: CircuitState) -> Self {
+        match state {
+            CircuitState::Closed => HealthStatus::Healthy,
+            CircuitState::HalfOpen => HealthStatus::Degraded,
+            CircuitState::Open => HealthStatus::Unhealthy,
+        }
+    }
+}
+
+/// Health of a single subsystem
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ComponentHealth {
+    /// Subsystem name (`storage`, `cache`, `observatory`, ...)
+    pub name: String,
+    /// Subsystem status
+    pub status: HealthStatus,
+    /// Why the subsystem is not healthy
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// Duration of the probe, if one was run
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<f64>,
+    /// Subsystem-specific values (cache sizes, plugin counts, ...)
+    #[serde(default)]
+    pub details: BTreeMap<String, String>,
+}
+
+impl ComponentHealth {
+    /// A component with the given status
+    pub fn new(name: impl Into<String>, status: HealthStatus) -> Self {
+        Self {
+            name: name.into(),
+            status,
+            message: None,
+            latency_ms: None,
+            details: BTreeMap::new(),
+        }
+    }
+
+    /// A healthy component
+    pub fn healthy(name: impl Into<String>) -> Self {
+        Self::new(name, HealthStatus::Healthy)
+    }
+
+    /// A degraded component
+    pub fn degraded(name: impl Into<String>, message: impl Into<String>) -> Self {
+        Self::new(name, HealthStatus::Degraded).with_message(message)
+    }
+
+    /// An unhealthy component
+    pub fn unhealthy(name: impl Into<String>, message: impl Into<String>) -> Self {
+        Self::new(name, HealthStatus::Unhealthy).with_message(message)
+    }
+
+    /// Health of an integration client from its circuit breaker state
+    pub fn from_circuit(name: impl Into<String>, state: CircuitState) -> Self {
+        let health = Self::new(name, state.into()).with_detail("circuit", state);
+        match state {
+            CircuitState::Closed => health,
+            CircuitState::HalfOpen => health.with_message("circuit breaker is probing"),
+            CircuitState::Open => health.with_message("circuit breaker is open"),
+        }
+    }
+
+    /// Set the reason for the status
+    pub fn with_message(mut self, message: impl Into<String>) -> Self {
+        self.message = Some(message.into());
+        self
+    }
+
+    /// Record how long the probe took
+    pub fn with_latency(mut self, latency: Duration) -> Self {
+        self.latency_ms = Some(latency.as_secs_f64() * 1000.0);
+        self
+    }
+
+    /// Add a detail
+    pub fn with_detail(mut self, key: impl Into<String>, value: impl ToString) -> Self {
+        self.details.insert(key.into(), value.to_string());
+        self
+    }
+}
+
+/// A component outside the graph that reports its own health
+///
+/// Implemented for the plugin manager, the integration clients and any
+/// `Fn() -> ComponentHealth` closure.
+pub trait HealthCheck: Send + Sync {
+    /// Current health of the component
+    fn health(&self) -> ComponentHealth;
+}
+
+impl<F> HealthCheck for F
+where
+    F: Fn() -> ComponentHealth + Send + Sync,
+{
+    fn health(&self) -> ComponentHealth {
+        self()
+    }
+}
+
+impl HealthCheck for PluginManager {
+    fn health(&self) -> ComponentHealth {
+        PluginManager::health(self)
+    }
+}
+
+impl HealthCheck for VaultClient {
+    fn health(&self) -> ComponentHealth {
+        self.component_health()
+    }
+}
+
+impl HealthCheck for RegistryClient {
+    fn health(&self) -> ComponentHealth {
+        self.component_health()
+    }
+}
+
+/// Health of a graph and the components around it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HealthReport {
+    /// Worst status among the components
+    pub status: HealthStatus,
+    /// Per-subsystem health
+    pub components: Vec<ComponentHealth>,
+    /// When the report was produced
+    pub checked_at: DateTime<Utc>,
+}
+
+impl HealthReport {
+    /// Report over `components`
+    pub fn new(components: Vec<ComponentHealth>) -> Self {
+        let status = components
+            .iter()
+            .map(|c| c.status)
+            .max()
+            .unwrap_or(HealthStatus::Healthy);
+        Self {
+            status,
+            components,
+            checked_at: Utc::now(),
+        }
+    }
+
+    /// Add a component, updating the overall status
+    pub fn with_component(mut self, component: ComponentHealth) -> Self {
+        self.status = self.status.max(component.status);
+        self.components.push(component);
+        self
+    }
+
+    /// The component called `name`
+    pub fn component(&self, name: &str) -> Option<&ComponentHealth> {
+        self.components.iter().find(|c| c.name == name)
+    }
+
+    /// Whether every component is healthy
+    pub fn is_healthy(&self) -> bool {
+        self.status == HealthStatus::Healthy
+    }
+
+    /// Whether requests should still be routed here (nothing is unhealthy)
+    pub fn is_serving(&self) -> bool {
+        self.status != HealthStatus::Unhealthy
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_overall_status_is_worst_component() {
+        let report = HealthReport::new(vec![ComponentHealth::healthy("storage")]);
+        assert!(report.is_healthy());
+
+        let report = report.with_component(ComponentHealth::degraded("cache", "slow"));
+        assert_eq!(report.status, HealthStatus::Degraded);
+        assert!(report.is_serving());
+
+        let report =
+            report.with_component(ComponentHealth::from_circuit("vault", CircuitState::Open));
+        assert_eq!(report.status, HealthStatus::Unhealthy);
+        assert!(!report.is_serving());
+        assert_eq!(
+            report.component("vault").unwrap().details["circuit"],
+            "open"
+        );
+        assert!(HealthReport::new(Vec::new()).is_healthy());
+    }
+
+    #[test]
+    fn test_report_serialization() {
+        let report = HealthReport::new(vec![ComponentHealth::healthy("storage")
+            .with_latency(Duration::from_millis(3))
+            .with_detail("nodes", 10)]);
+        let json = serde_json::to_value(&report).unwrap();
+
+        assert_eq!(json["status"], "healthy");
+        assert_eq!(json["components"][0]["details"]["nodes"], "10");
+        assert!(json["components"][0].get("message").is_none());
+    }
+}
@@ -9,16 +9,19 @@ pub mod vault;
 
 // Re-export main types
 pub use registry::{
-    ModelMetadata, ModelParameters, RegistryClient, RegistryConfig, SessionRegistration,
-    UsageStats,
+    ModelMetadata, ModelParameters, RegistryClient, RegistryConfig, SessionRegistration, UsageStats,
 };
 pub use vault::{
     ArchivalScheduler, ArchiveEntry, ComplianceLevel, RetentionPolicy, SchedulerConfig,
     VaultClient, VaultConfig,
 };
 
-use crate::{Error, Result};
-use std::time::Duration;
+use crate::Error;
+use parking_lot::Mutex;
+use prometheus::IntGauge;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::debug;
 
 /// Integration error types
 #[derive(Debug, thiserror::Error)]
@@ -52,6 +55,11 @@ pub enum IntegrationError {
     #[error("Circuit breaker open: {0}")]
     CircuitBreakerOpen(String),
 
+    /// Held back by the client's own rate limiter without reaching the
+    /// service; a service's own 429 responses are [`ApiError`](Self::ApiError)s
+    #[error("Throttled by local rate limiter: {0}")]
+    Throttled(String),
+
     /// Invalid configuration
     #[error("Invalid configuration: {0}")]
     InvalidConfig(String),
@@ -61,9 +69,25 @@ pub enum IntegrationError {
     Serialization(String),
 }
 
+impl IntegrationError {
+    /// Whether the error indicates the remote service itself is unhealthy
+    ///
+    /// Timeouts, connection failures and 5xx responses qualify; client-side
+    /// errors such as 4xx responses or bad configuration do not.
+    pub fn is_service_failure(&self) -> bool {
+        match self {
+            IntegrationError::Timeout(_)
+            | IntegrationError::ConnectionError(_)
+            | IntegrationError::HttpError(_) => true,
+            IntegrationError::ApiError { status, .. } => (500..600).contains(status),
+            _ => false,
+        }
+    }
+}
+
 impl From<IntegrationError> for Error {
     fn from(err: IntegrationError) -> Self {
-        Error::Other(err.to_string())
+        Error::IntegrationError(err.to_string())
     }
 }
 
@@ -81,35 +105,391 @@ impl From<reqwest::Error> for IntegrationError {
 
 impl From<serde_json::Error> for IntegrationError {
     fn from(err: serde_json::Error) -> Self {
-        IntegrationError::SerializationError(err.to_string())
+        IntegrationError::Serialization(err.to_string())
+    }
+}
+
+/// State of a [`CircuitBreaker`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Requests flow normally and failures are counted
+    Closed,
+    /// Requests are rejected until the open timeout elapses
+    Open,
+    /// Probe requests are let through to test whether the service recovered
+    HalfOpen,
+}
+
+impl CircuitState {
+    /// Numeric value used when exporting the state as a gauge
+    ///
+    /// `0` = closed, `1` = open, `2` = half-open.
+    pub fn as_gauge_value(self) -> i64 {
+        match self {
+            CircuitState::Closed => 0,
+            CircuitState::Open => 1,
+            CircuitState::HalfOpen => 2,
+        }
     }
 }
 
+impl std::fmt::Display for CircuitState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CircuitState::Closed => write!(f, "closed"),
+            CircuitState::Open => write!(f, "open"),
+            CircuitState::HalfOpen => write!(f, "half-open"),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct BreakerState {
+    state: CircuitState,
+    consecutive_failures: usize,
+    consecutive_successes: usize,
+    opened_at: Option<Instant>,
+    trial_started: Option<Instant>,
+}
+
 /// Circuit breaker for external service calls
+///
+/// The breaker starts closed. After `failure_threshold` consecutive service
+/// failures it opens and rejects calls with [`IntegrationError::CircuitBreakerOpen`].
+/// Once `timeout_duration` has elapsed the next call is let through as a probe
+/// (half-open); `success_threshold` consecutive successes close the breaker
+/// again, while any failure re-opens it. Half-open, one probe runs at a time
+/// and other calls are rejected until it reports back, or until it has run
+/// for `timeout_duration` without doing so.
+///
+/// Only failures that indicate the remote service is unhealthy (timeouts,
+/// connection errors, 5xx responses) are counted. Clones share the same state.
 #[derive(Debug, Clone)]
 pub struct CircuitBreaker {
     failure_threshold: usize,
     success_threshold: usize,
     timeout_duration: Duration,
+    state: Arc<Mutex<BreakerState>>,
+    gauge: Option<IntGauge>,
 }
 
 impl CircuitBreaker {
     /// Create a new circuit breaker
-    pub fn new(failure_threshold: usize, success_threshold: usize, timeout_duration: Duration) -> Self {
+    pub fn new(
+        failure_threshold: usize,
+        success_threshold: usize,
+        timeout_duration: Duration,
+    ) -> Self {
         Self {
-            failure_threshold,
-            success_threshold,
+            failure_threshold: failure_threshold.max(1),
+            success_threshold: success_threshold.max(1),
             timeout_duration,
+            state: Arc::new(Mutex::new(BreakerState {
+                state: CircuitState::Closed,
+                consecutive_failures: 0,
+                consecutive_successes: 0,
+                opened_at: None,
+                trial_started: None,
+            })),
+            gauge: None,
         }
     }
 
     /// Default circuit breaker configuration
     pub fn default_config() -> Self {
+        Self::new(5, 2, Duration::from_mins(1))
+    }
+
+    /// Report state transitions to a Prometheus gauge
+    ///
+    /// The gauge is set to the current state immediately.
+    pub fn with_gauge(mut self, gauge: IntGauge) -> Self {
+        gauge.set(self.state().as_gauge_value());
+        self.gauge = Some(gauge);
+        self
+    }
+
+    /// Number of consecutive failures before the breaker opens
+    pub fn failure_threshold(&self) -> usize {
+        self.failure_threshold
+    }
+
+    /// Number of consecutive successes required to close the breaker again
+    pub fn success_threshold(&self) -> usize {
+        self.success_threshold
+    }
+
+    /// How long the breaker stays open before allowing a probe request
+    pub fn timeout_duration(&self) -> Duration {
+        self.timeout_duration
+    }
+
+    /// Current breaker state
+    pub fn state(&self) -> CircuitState {
+        self.state.lock().state
+    }
+
+    /// Check whether a request may proceed
+    ///
+    /// Moves an open breaker to half-open once its timeout has elapsed. An
+    /// allowed half-open request is a probe, and should report back with
+    /// [`record_success`](Self::record_success) or
+    /// [`record_failure`](Self::record_failure).
+    ///
+    /// # Errors
+    /// Returns `CircuitBreakerOpen` while the breaker is open, or half-open
+    /// with a probe in flight.
+    pub fn allow_request(&self) -> std::result::Result<(), IntegrationError> {
+        let mut inner = self.state.lock();
+        match inner.state {
+            CircuitState::Closed => return Ok(()),
+            CircuitState::HalfOpen => {
+                if inner
+                    .trial_started
+                    .is_some_and(|t| t.elapsed() < self.timeout_duration)
+                {
+                    return Err(IntegrationError::CircuitBreakerOpen(
+                        "half-open with a probe request in flight".to_string(),
+                    ));
+                }
+                inner.trial_started = Some(Instant::now());
+                return Ok(());
+            }
+            CircuitState::Open => {}
+        }
+
+        let elapsed = inner.opened_at.map_or(Duration::ZERO, |t| t.elapsed());
+        let Some(remaining) = self
+            .timeout_duration
+            .checked_sub(elapsed)
+            .filter(|d| !d.is_zero())
+        else {
+            self.transition(&mut inner, CircuitState::HalfOpen);
+            inner.trial_started = Some(Instant::now());
+            return Ok(());
+        };
+
+        Err(IntegrationError::CircuitBreakerOpen(format!(
+            "retry in {}ms",
+            remaining.as_millis()
+        )))
+    }
+
+    /// Record a successful call
+    pub fn record_success(&self) {
+        let mut inner = self.state.lock();
+        inner.consecutive_failures = 0;
+        inner.trial_started = None;
+        if inner.state == CircuitState::HalfOpen {
+            inner.consecutive_successes += 1;
+            if inner.consecutive_successes >= self.success_threshold {
+                self.transition(&mut inner, CircuitState::Closed);
+            }
+        }
+    }
+
+    /// Record a failed call
+    pub fn record_failure(&self) {
+        let mut inner = self.state.lock();
+        inner.trial_started = None;
+        match inner.state {
+            CircuitState::Closed => {
+                inner.consecutive_failures += 1;
+                if inner.consecutive_failures >= self.failure_threshold {
+                    self.transition(&mut inner, CircuitState::Open);
+                }
+            }
+            CircuitState::HalfOpen => self.transition(&mut inner, CircuitState::Open),
+            CircuitState::Open => inner.opened_at = Some(Instant::now()),
+        }
+    }
+
+    /// Force the breaker back to the closed state
+    pub fn reset(&self) {
+        let mut inner = self.state.lock();
+        self.transition(&mut inner, CircuitState::Closed);
+    }
+
+    /// Run a request through the breaker
+    ///
+    /// The request is not polled at all while the breaker is open.
+    ///
+    /// # Errors
+    /// Returns `CircuitBreakerOpen` if the breaker rejects the call, otherwise
+    /// the request's own error.
+    pub async fn call<Fut, T>(&self, request: Fut) -> std::result::Result<T, IntegrationError>
+    where
+        Fut: std::future::Future<Output = std::result::Result<T, IntegrationError>>,
+    {
+        self.allow_request()?;
+
+        let result = request.await;
+        match &result {
+            Err(err) if err.is_service_failure() => self.record_failure(),
+            // Never reached the service, so it says nothing about its health
+            Err(IntegrationError::Throttled(_)) => self.state.lock().trial_started = None,
+            _ => self.record_success(),
+        }
+        result
+    }
+
+    fn transition(&self, inner: &mut BreakerState, to: CircuitState) {
+        if inner.state != to {
+            debug!("Circuit breaker {} -> {}", inner.state, to);
+        }
+        inner.state = to;
+        inner.consecutive_failures = 0;
+        inner.consecutive_successes = 0;
+        inner.opened_at = (to == CircuitState::Open).then(Instant::now);
+        inner.trial_started = None;
+        if let Some(gauge) = &self.gauge {
+            gauge.set(to.as_gauge_value());
+        }
+    }
+}
+
+impl Default for CircuitBreaker {
+    fn default() -> Self {
+        Self::default_config()
+    }
+}
+
+/// What a [`RateLimiter`] does when no token is available
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RateLimitMode {
+    /// Queue the request until a token becomes available
+    #[default]
+    Wait,
+    /// Reject the request immediately with [`IntegrationError::Throttled`]
+    FailFast,
+}
+
+/// Token-bucket rate limit settings for an integration client
+#[derive(Debug, Clone, PartialEq)]
+pub struct RateLimitConfig {
+    /// Sustained request rate (tokens added per second)
+    pub requests_per_second: f64,
+    /// Bucket capacity, i.e. how many requests may be sent back-to-back
+    pub burst: u32,
+    /// Behaviour when the bucket is empty
+    pub mode: RateLimitMode,
+}
+
+impl RateLimitConfig {
+    /// Create a rate limit that queues requests when the bucket is empty
+    pub fn new(requests_per_second: f64, burst: u32) -> Self {
         Self {
-            failure_threshold: 5,
-            success_threshold: 2,
-            timeout_duration: Duration::from_secs(60),
+            requests_per_second,
+            burst,
+            mode: RateLimitMode::Wait,
+        }
+    }
+
+    /// Set the mode used when the bucket is empty
+    pub fn with_mode(mut self, mode: RateLimitMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Reject requests instead of waiting when the bucket is empty
+    pub fn fail_fast(self) -> Self {
+        self.with_mode(RateLimitMode::FailFast)
+    }
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+/// Token-bucket rate limiter shared by all clones
+///
+/// In [`RateLimitMode::Wait`] callers reserve a token up front and sleep until
+/// it is due, so concurrent callers are served in arrival order.
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    config: RateLimitConfig,
+    bucket: Arc<Mutex<Bucket>>,
+}
+
+impl RateLimiter {
+    /// Create a rate limiter with a full bucket
+    ///
+    /// # Errors
+    /// Returns `InvalidConfig` if the rate is not a positive number or the burst is zero.
+    pub fn new(config: RateLimitConfig) -> std::result::Result<Self, IntegrationError> {
+        if !config.requests_per_second.is_finite() || config.requests_per_second <= 0.0 {
+            return Err(IntegrationError::InvalidConfig(format!(
+                "rate limit must be positive, got {} requests/s",
+                config.requests_per_second
+            )));
+        }
+        if config.burst == 0 {
+            return Err(IntegrationError::InvalidConfig(
+                "rate limit burst must be at least 1".to_string(),
+            ));
+        }
+
+        let bucket = Bucket {
+            tokens: f64::from(config.burst),
+            last_refill: Instant::now(),
+        };
+        Ok(Self {
+            config,
+            bucket: Arc::new(Mutex::new(bucket)),
+        })
+    }
+
+    /// Rate limit settings
+    pub fn config(&self) -> &RateLimitConfig {
+        &self.config
+    }
+
+    /// Tokens currently available (negative while callers are queued)
+    pub fn available_tokens(&self) -> f64 {
+        let mut bucket = self.bucket.lock();
+        self.refill(&mut bucket);
+        bucket.tokens
+    }
+
+    /// Take a token, waiting for one if the limiter is in `Wait` mode
+    ///
+    /// # Errors
+    /// Returns `Throttled` in `FailFast` mode when the bucket is empty.
+    pub async fn acquire(&self) -> std::result::Result<(), IntegrationError> {
+        let wait = {
+            let mut bucket = self.bucket.lock();
+            self.refill(&mut bucket);
+
+            if bucket.tokens < 1.0 && self.config.mode == RateLimitMode::FailFast {
+                let wait = (1.0 - bucket.tokens) / self.config.requests_per_second;
+                return Err(IntegrationError::Throttled(format!(
+                    "retry in {}ms",
+                    (wait * 1000.0).ceil() as u64
+                )));
+            }
+
+            bucket.tokens -= 1.0;
+            if bucket.tokens < 0.0 {
+                Duration::from_secs_f64(-bucket.tokens / self.config.requests_per_second)
+            } else {
+                Duration::ZERO
+            }
+        };
+
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
         }
+        Ok(())
+    }
+
+    fn refill(&self, bucket: &mut Bucket) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(bucket.last_refill).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.config.requests_per_second)
+            .min(f64::from(self.config.burst));
+        bucket.last_refill = now;
     }
 }
 
@@ -158,18 +538,33 @@ impl RetryPolicy {
         self
     }
 
+    /// Set maximum delay between retries
+    pub fn with_max_delay(mut self, delay: Duration) -> Self {
+        self.max_delay = delay;
+        self
+    }
+
     /// Set backoff multiplier
     pub fn with_backoff_multiplier(mut self, multiplier: f64) -> Self {
         self.backoff_multiplier = multiplier;
         self
     }
+
+    /// Set whether timeouts should be retried
+    pub fn with_retry_on_timeout(mut self, retry: bool) -> Self {
+        self.retry_on_timeout = retry;
+        self
+    }
 }
 
 /// Execute a request with retry logic
-pub async fn retry_request<F, Fut, T>(policy: &RetryPolicy, mut operation: F) -> Result<T, IntegrationError>
+pub async fn retry_request<F, Fut, T>(
+    policy: &RetryPolicy,
+    mut operation: F,
+) -> std::result::Result<T, IntegrationError>
 where
     F: FnMut() -> Fut,
-    Fut: std::future::Future<Output = Result<T, IntegrationError>>,
+    Fut: std::future::Future<Output = std::result::Result<T, IntegrationError>>,
 {
     let mut attempt = 0;
     let mut delay = policy.initial_delay;
@@ -182,13 +577,7 @@ where
                 // Check if we should retry
                 let should_retry = match &err {
                     IntegrationError::Timeout(_) => policy.retry_on_timeout,
-                    IntegrationError::ConnectionError(_) => true,
-                    IntegrationError::HttpError(_) => true,
-                    IntegrationError::ApiError { status, .. } => {
-                        // Retry on 5xx errors (server errors)
-                        *status >= 500 && *status < 600
-                    }
-                    _ => false,
+                    other => other.is_service_failure(),
                 };
 
                 if !should_retry || attempt >= policy.max_attempts {
@@ -210,16 +599,283 @@ where
     }
 }
 
+/// Execute a request through a circuit breaker, rate limiter and retry policy
+///
+/// The breaker is checked once per call; every attempt, including retries,
+/// takes a token from the limiter.
+pub async fn guarded_request<F, Fut, T>(
+    breaker: &CircuitBreaker,
+    limiter: Option<&RateLimiter>,
+    policy: &RetryPolicy,
+    mut operation: F,
+) -> std::result::Result<T, IntegrationError>
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = std::result::Result<T, IntegrationError>>,
+{
+    let attempt = || {
+        let request = operation();
+        async move {
+            if let Some(limiter) = limiter {
+                limiter.acquire().await?;
+            }
+            request.await
+        }
+    };
+
+    breaker.call(retry_request(policy, attempt)).await
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
 
     #[test]
     fn test_circuit_breaker_creation() {
-        let cb = CircuitBreaker::new(5, 2, Duration::from_secs(60));
+        let cb = CircuitBreaker::new(5, 2, Duration::from_mins(1));
         assert_eq!(cb.failure_threshold, 5);
         assert_eq!(cb.success_threshold, 2);
-        assert_eq!(cb.timeout_duration, Duration::from_secs(60));
+        assert_eq!(cb.timeout_duration, Duration::from_mins(1));
+        assert_eq!(cb.state(), CircuitState::Closed);
+    }
+
+    #[test]
+    fn test_circuit_breaker_opens_after_failure_threshold() {
+        let cb = CircuitBreaker::new(3, 1, Duration::from_mins(1));
+
+        cb.record_failure();
+        cb.record_failure();
+        assert_eq!(cb.state(), CircuitState::Closed);
+        assert!(cb.allow_request().is_ok());
+
+        cb.record_failure();
+        assert_eq!(cb.state(), CircuitState::Open);
+        assert!(matches!(
+            cb.allow_request(),
+            Err(IntegrationError::CircuitBreakerOpen(_))
+        ));
+    }
+
+    #[test]
+    fn test_circuit_breaker_success_resets_failure_count() {
+        let cb = CircuitBreaker::new(2, 1, Duration::from_mins(1));
+
+        cb.record_failure();
+        cb.record_success();
+        cb.record_failure();
+        assert_eq!(cb.state(), CircuitState::Closed);
+    }
+
+    #[test]
+    fn test_circuit_breaker_half_open_closes_after_successes() {
+        let cb = CircuitBreaker::new(1, 2, Duration::from_millis(10));
+        cb.record_failure();
+        assert_eq!(cb.state(), CircuitState::Open);
+
+        std::thread::sleep(Duration::from_millis(20));
+        assert!(cb.allow_request().is_ok());
+        assert_eq!(cb.state(), CircuitState::HalfOpen);
+
+        cb.record_success();
+        assert_eq!(cb.state(), CircuitState::HalfOpen);
+        cb.record_success();
+        assert_eq!(cb.state(), CircuitState::Closed);
+    }
+
+    #[test]
+    fn test_circuit_breaker_half_open_failure_reopens() {
+        let cb = CircuitBreaker::new(1, 2, Duration::from_millis(10));
+        cb.record_failure();
+        std::thread::sleep(Duration::from_millis(20));
+        assert!(cb.allow_request().is_ok());
+
+        cb.record_failure();
+        assert_eq!(cb.state(), CircuitState::Open);
+        assert!(cb.allow_request().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_circuit_breaker_half_open_admits_one_probe() {
+        let cb = CircuitBreaker::new(1, 1, Duration::from_millis(50));
+        cb.record_failure();
+        tokio::time::sleep(Duration::from_millis(60)).await;
+
+        let attempts = AtomicUsize::new(0);
+        let results = futures::future::join_all((0..8).map(|_| {
+            cb.call(async {
+                attempts.fetch_add(1, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                Ok(())
+            })
+        }))
+        .await;
+
+        // Only the probe reaches the service; the rest are turned away
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+        assert_eq!(results.iter().filter(|r| r.is_ok()).count(), 1);
+        assert!(results
+            .iter()
+            .filter_map(|r| r.as_ref().err())
+            .all(|e| matches!(e, IntegrationError::CircuitBreakerOpen(_))));
+        assert_eq!(cb.state(), CircuitState::Closed);
+    }
+
+    #[test]
+    fn test_circuit_breaker_half_open_probe_expires() {
+        let cb = CircuitBreaker::new(1, 1, Duration::from_millis(10));
+        cb.record_failure();
+        std::thread::sleep(Duration::from_millis(20));
+        assert!(cb.allow_request().is_ok());
+        assert!(cb.allow_request().is_err());
+
+        // A probe that never reports back stops blocking after the timeout
+        std::thread::sleep(Duration::from_millis(20));
+        assert!(cb.allow_request().is_ok());
+        assert_eq!(cb.state(), CircuitState::HalfOpen);
+    }
+
+    #[test]
+    fn test_circuit_breaker_clones_share_state() {
+        let cb = CircuitBreaker::new(1, 1, Duration::from_mins(1));
+        let other = cb.clone();
+        other.record_failure();
+        assert_eq!(cb.state(), CircuitState::Open);
+
+        cb.reset();
+        assert_eq!(other.state(), CircuitState::Closed);
+    }
+
+    #[tokio::test]
+    async fn test_circuit_breaker_call_skips_request_when_open() {
+        let cb = CircuitBreaker::new(2, 1, Duration::from_mins(1));
+        let attempts = AtomicUsize::new(0);
+
+        for _ in 0..4 {
+            let result: std::result::Result<(), _> = cb
+                .call(async {
+                    attempts.fetch_add(1, Ordering::SeqCst);
+                    Err(IntegrationError::ConnectionError("refused".to_string()))
+                })
+                .await;
+            assert!(result.is_err());
+        }
+
+        // Only the first two calls reach the service before the breaker opens
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+        assert_eq!(cb.state(), CircuitState::Open);
+    }
+
+    #[tokio::test]
+    async fn test_circuit_breaker_ignores_client_errors() {
+        let cb = CircuitBreaker::new(1, 1, Duration::from_mins(1));
+
+        let result: std::result::Result<(), _> = cb
+            .call(async {
+                Err(IntegrationError::ApiError {
+                    status: 404,
+                    message: "not found".to_string(),
+                })
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(cb.state(), CircuitState::Closed);
+    }
+
+    #[test]
+    fn test_rate_limiter_rejects_invalid_config() {
+        assert!(RateLimiter::new(RateLimitConfig::new(0.0, 1)).is_err());
+        assert!(RateLimiter::new(RateLimitConfig::new(f64::NAN, 1)).is_err());
+        assert!(RateLimiter::new(RateLimitConfig::new(10.0, 0)).is_err());
+        assert!(RateLimiter::new(RateLimitConfig::new(10.0, 1)).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_rate_limiter_fail_fast_after_burst() {
+        let limiter = RateLimiter::new(RateLimitConfig::new(1.0, 3).fail_fast()).unwrap();
+
+        for _ in 0..3 {
+            assert!(limiter.acquire().await.is_ok());
+        }
+        assert!(matches!(
+            limiter.acquire().await,
+            Err(IntegrationError::Throttled(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_rate_limiter_wait_mode_queues() {
+        let limiter = RateLimiter::new(RateLimitConfig::new(20.0, 1)).unwrap();
+
+        let start = Instant::now();
+        for _ in 0..3 {
+            limiter.acquire().await.unwrap();
+        }
+
+        // First token is free, the next two arrive at 20/s
+        assert!(start.elapsed() >= Duration::from_millis(90));
+    }
+
+    #[tokio::test]
+    async fn test_rate_limiter_refills_over_time() {
+        let limiter = RateLimiter::new(RateLimitConfig::new(100.0, 1).fail_fast()).unwrap();
+
+        limiter.acquire().await.unwrap();
+        assert!(limiter.acquire().await.is_err());
+
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(limiter.acquire().await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_guarded_request_limits_each_attempt() {
+        let breaker = CircuitBreaker::new(10, 1, Duration::from_mins(1));
+        let limiter = RateLimiter::new(RateLimitConfig::new(1.0, 2).fail_fast()).unwrap();
+        let attempts = AtomicUsize::new(0);
+
+        let result: std::result::Result<(), _> =
+            guarded_request(&breaker, Some(&limiter), &fast_policy(), || async {
+                attempts.fetch_add(1, Ordering::SeqCst);
+                Err(IntegrationError::ApiError {
+                    status: 503,
+                    message: "unavailable".to_string(),
+                })
+            })
+            .await;
+
+        // Two retries consume the burst, the third attempt is rejected locally
+        assert!(matches!(result, Err(IntegrationError::Throttled(_))));
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+        assert_eq!(breaker.state(), CircuitState::Closed);
+    }
+
+    #[tokio::test]
+    async fn test_local_throttle_skips_breaker_accounting() {
+        let breaker = CircuitBreaker::new(1, 1, Duration::from_millis(10));
+        let limiter = RateLimiter::new(RateLimitConfig::new(1.0, 1).fail_fast()).unwrap();
+        let attempts = AtomicUsize::new(0);
+        let request = || async {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        };
+
+        guarded_request(&breaker, Some(&limiter), &fast_policy(), request)
+            .await
+            .unwrap();
+        for _ in 0..3 {
+            let result = guarded_request(&breaker, Some(&limiter), &fast_policy(), request).await;
+            assert!(matches!(result, Err(IntegrationError::Throttled(_))));
+        }
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+        assert_eq!(breaker.state(), CircuitState::Closed);
+
+        // A throttled half-open probe frees the slot for the next one
+        breaker.record_failure();
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        let result = guarded_request(&breaker, Some(&limiter), &fast_policy(), request).await;
+        assert!(matches!(result, Err(IntegrationError::Throttled(_))));
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+        assert!(breaker.allow_request().is_ok());
     }
 
     #[test]
@@ -240,4 +896,120 @@ mod tests {
         let err = IntegrationError::Timeout(5000);
         assert!(matches!(err, IntegrationError::Timeout(_)));
     }
+
+    #[test]
+    fn test_integration_error_into_error() {
+        let err: Error = IntegrationError::Serialization("bad payload".to_string()).into();
+        assert!(matches!(err, Error::IntegrationError(_)));
+    }
+
+    fn fast_policy() -> RetryPolicy {
+        RetryPolicy::new()
+            .with_max_attempts(3)
+            .with_initial_delay(Duration::from_millis(1))
+            .with_max_delay(Duration::from_millis(5))
+    }
+
+    #[tokio::test]
+    async fn test_retry_request_succeeds_after_server_errors() {
+        let attempts = AtomicUsize::new(0);
+
+        let result = retry_request(&fast_policy(), || async {
+            if attempts.fetch_add(1, Ordering::SeqCst) < 2 {
+                Err(IntegrationError::ApiError {
+                    status: 503,
+                    message: "unavailable".to_string(),
+                })
+            } else {
+                Ok("done")
+            }
+        })
+        .await;
+
+        assert_eq!(result.unwrap(), "done");
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_retry_request_gives_up_after_max_attempts() {
+        let attempts = AtomicUsize::new(0);
+
+        let result: std::result::Result<(), _> = retry_request(&fast_policy(), || async {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            Err(IntegrationError::ApiError {
+                status: 500,
+                message: "boom".to_string(),
+            })
+        })
+        .await;
+
+        assert!(matches!(
+            result,
+            Err(IntegrationError::ApiError { status: 500, .. })
+        ));
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_retry_request_does_not_retry_client_errors() {
+        let attempts = AtomicUsize::new(0);
+
+        let result: std::result::Result<(), _> = retry_request(&fast_policy(), || async {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            Err(IntegrationError::ApiError {
+                status: 404,
+                message: "missing".to_string(),
+            })
+        })
+        .await;
+
+        assert!(matches!(
+            result,
+            Err(IntegrationError::ApiError { status: 404, .. })
+        ));
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_retry_request_timeout_respects_policy() {
+        let attempts = AtomicUsize::new(0);
+        let result: std::result::Result<(), _> = retry_request(&fast_policy(), || async {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            Err(IntegrationError::Timeout(100))
+        })
+        .await;
+        assert!(matches!(result, Err(IntegrationError::Timeout(100))));
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+
+        let attempts = AtomicUsize::new(0);
+        let policy = fast_policy().with_retry_on_timeout(false);
+        let result: std::result::Result<(), _> = retry_request(&policy, || async {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            Err(IntegrationError::Timeout(100))
+        })
+        .await;
+        assert!(matches!(result, Err(IntegrationError::Timeout(100))));
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_retry_request_exponential_backoff() {
+        let policy = RetryPolicy::new()
+            .with_max_attempts(4)
+            .with_initial_delay(Duration::from_millis(20))
+            .with_backoff_multiplier(2.0)
+            .with_max_delay(Duration::from_millis(50));
+
+        let start = Instant::now();
+        let result: std::result::Result<(), _> = retry_request(&policy, || async {
+            Err(IntegrationError::ConnectionError("refused".to_string()))
+        })
+        .await;
+        let elapsed = start.elapsed();
+
+        assert!(result.is_err());
+        // Delays: 20ms, 40ms, then capped at 50ms
+        assert!(elapsed >= Duration::from_millis(110));
+        assert!(elapsed < Duration::from_secs(2));
+    }
 }
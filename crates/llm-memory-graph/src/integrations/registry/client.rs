@@ -4,7 +4,11 @@ use super::types::{
     ModelListResponse, ModelMetadata, RegistryConfig, SessionInfo, SessionListResponse,
     SessionRegistration, SessionStatus, UsageReport, UsageStats,
 };
-use crate::integrations::{retry_request, IntegrationError, RetryPolicy};
+use crate::health::ComponentHealth;
+use crate::integrations::{
+    guarded_request, CircuitBreaker, CircuitState, IntegrationError, RateLimiter, RetryPolicy,
+};
+use crate::observatory::PrometheusMetrics;
 use reqwest::{Client, StatusCode};
 use std::time::Duration;
 use tracing::{debug, error, info, warn};
@@ -19,6 +23,8 @@ pub struct RegistryClient {
     config: RegistryConfig,
     client: Client,
     retry_policy: RetryPolicy,
+    circuit_breaker: CircuitBreaker,
+    rate_limiter: Option<RateLimiter>,
 }
 
 impl RegistryClient {
@@ -39,10 +45,18 @@ impl RegistryClient {
             .with_max_attempts(config.retry_count)
             .with_initial_delay(Duration::from_millis(100));
 
+        let rate_limiter = config
+            .rate_limit
+            .clone()
+            .map(RateLimiter::new)
+            .transpose()?;
+
         Ok(Self {
             config,
             client,
             retry_policy,
+            circuit_breaker: CircuitBreaker::default_config(),
+            rate_limiter,
         })
     }
 
@@ -52,6 +66,30 @@ impl RegistryClient {
         self
     }
 
+    /// Create a client with a custom circuit breaker
+    pub fn with_circuit_breaker(mut self, breaker: CircuitBreaker) -> Self {
+        self.circuit_breaker = breaker;
+        self
+    }
+
+    /// Export circuit breaker state to Prometheus under the `registry` service label
+    pub fn with_metrics(mut self, metrics: &PrometheusMetrics) -> Self {
+        self.circuit_breaker = self
+            .circuit_breaker
+            .with_gauge(metrics.circuit_breaker_gauge("registry"));
+        self
+    }
+
+    /// Current state of the client's circuit breaker
+    pub fn circuit_state(&self) -> CircuitState {
+        self.circuit_breaker.state()
+    }
+
+    /// Health of the client, derived from its circuit breaker state
+    pub fn component_health(&self) -> ComponentHealth {
+        ComponentHealth::from_circuit("registry", self.circuit_state())
+    }
+
     /// Register a session with the registry
     ///
     /// # Errors
@@ -81,10 +119,7 @@ impl RegistryClient {
 
             if !status.is_success() {
                 let error_body = response.text().await.unwrap_or_default();
-                error!(
-                    "Failed to register session: {} - {}",
-                    status, error_body
-                );
+                error!("Failed to register session: {} - {}", status, error_body);
                 return Err(IntegrationError::ApiError {
                     status: status.as_u16(),
                     message: error_body,
@@ -95,10 +130,19 @@ impl RegistryClient {
             Ok(session_info)
         };
 
-        let result = retry_request(&self.retry_policy, operation).await?;
+        let result = guarded_request(
+            &self.circuit_breaker,
+            self.rate_limiter.as_ref(),
+            &self.retry_policy,
+            operation,
+        )
+        .await?;
 
         if self.config.enable_logging {
-            info!("Session {} registered successfully", registration.session_id);
+            info!(
+                "Session {} registered successfully",
+                registration.session_id
+            );
         }
 
         Ok(result)
@@ -148,7 +192,13 @@ impl RegistryClient {
             Ok(metadata)
         };
 
-        retry_request(&self.retry_policy, operation).await
+        guarded_request(
+            &self.circuit_breaker,
+            self.rate_limiter.as_ref(),
+            &self.retry_policy,
+            operation,
+        )
+        .await
     }
 
     /// List available models
@@ -193,7 +243,13 @@ impl RegistryClient {
             Ok(models)
         };
 
-        retry_request(&self.retry_policy, operation).await
+        guarded_request(
+            &self.circuit_breaker,
+            self.rate_limiter.as_ref(),
+            &self.retry_policy,
+            operation,
+        )
+        .await
     }
 
     /// Track token usage for a session
@@ -232,7 +288,13 @@ impl RegistryClient {
             Ok(())
         };
 
-        retry_request(&self.retry_policy, operation).await
+        guarded_request(
+            &self.circuit_breaker,
+            self.rate_limiter.as_ref(),
+            &self.retry_policy,
+            operation,
+        )
+        .await
     }
 
     /// Get usage statistics for a session
@@ -281,21 +343,21 @@ impl RegistryClient {
             Ok(stats)
         };
 
-        retry_request(&self.retry_policy, operation).await
+        guarded_request(
+            &self.circuit_breaker,
+            self.rate_limiter.as_ref(),
+            &self.retry_policy,
+            operation,
+        )
+        .await
     }
 
     /// Get usage statistics for a model
     ///
     /// # Errors
     /// Returns an error if the model is not found or the request fails.
-    pub async fn get_model_usage(
-        &self,
-        model_id: &str,
-    ) -> Result<UsageStats, IntegrationError> {
-        let url = format!(
-            "{}/api/v1/models/{}/usage",
-            self.config.base_url, model_id
-        );
+    pub async fn get_model_usage(&self, model_id: &str) -> Result<UsageStats, IntegrationError> {
+        let url = format!("{}/api/v1/models/{}/usage", self.config.base_url, model_id);
 
         let operation = || async {
             let mut request = self.client.get(&url);
@@ -319,7 +381,13 @@ impl RegistryClient {
             Ok(stats)
         };
 
-        retry_request(&self.retry_policy, operation).await
+        guarded_request(
+            &self.circuit_breaker,
+            self.rate_limiter.as_ref(),
+            &self.retry_policy,
+            operation,
+        )
+        .await
     }
 
     /// Update session status
@@ -360,7 +428,13 @@ impl RegistryClient {
             Ok(session_info)
         };
 
-        retry_request(&self.retry_policy, operation).await
+        guarded_request(
+            &self.circuit_breaker,
+            self.rate_limiter.as_ref(),
+            &self.retry_policy,
+            operation,
+        )
+        .await
     }
 
     /// List sessions
@@ -405,7 +479,13 @@ impl RegistryClient {
             Ok(sessions)
         };
 
-        retry_request(&self.retry_policy, operation).await
+        guarded_request(
+            &self.circuit_breaker,
+            self.rate_limiter.as_ref(),
+            &self.retry_policy,
+            operation,
+        )
+        .await
     }
 
     /// Delete a session from the registry
@@ -436,7 +516,13 @@ impl RegistryClient {
             Ok(())
         };
 
-        retry_request(&self.retry_policy, operation).await
+        guarded_request(
+            &self.circuit_breaker,
+            self.rate_limiter.as_ref(),
+            &self.retry_policy,
+            operation,
+        )
+        .await
     }
 
     /// Health check for the registry service
@@ -460,7 +546,6 @@ impl RegistryClient {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use chrono::Utc;
 
     #[test]
     fn test_registry_client_creation() {
@@ -478,6 +563,38 @@ mod tests {
         assert_eq!(client.retry_policy.max_attempts, 5);
     }
 
+    #[tokio::test]
+    async fn test_registry_client_circuit_breaker_opens() {
+        // Nothing listens on port 1, so every call fails with a connection error
+        let config = RegistryConfig::new("http://127.0.0.1:1");
+        let client = RegistryClient::new(config)
+            .unwrap()
+            .with_retry_policy(RetryPolicy::new().with_max_attempts(1))
+            .with_circuit_breaker(CircuitBreaker::new(2, 1, Duration::from_mins(1)));
+
+        for _ in 0..2 {
+            let result = client.get_model_metadata("gpt-4").await;
+            assert!(matches!(result, Err(IntegrationError::ConnectionError(_))));
+        }
+        assert_eq!(client.circuit_state(), CircuitState::Open);
+
+        let result = client.get_model_metadata("gpt-4").await;
+        assert!(matches!(
+            result,
+            Err(IntegrationError::CircuitBreakerOpen(_))
+        ));
+    }
+
+    #[test]
+    fn test_registry_client_rejects_invalid_rate_limit() {
+        let config = RegistryConfig::new("http://localhost:8080")
+            .with_rate_limit(crate::integrations::RateLimitConfig::new(-1.0, 5));
+        assert!(matches!(
+            RegistryClient::new(config),
+            Err(IntegrationError::InvalidConfig(_))
+        ));
+    }
+
     // Note: Integration tests would require a running registry service
     // and are better placed in tests/integration_test.rs
 }
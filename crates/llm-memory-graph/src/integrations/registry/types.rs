@@ -1,5 +1,7 @@
 //! Type definitions for LLM-Registry integration
 
+use crate::integrations::RateLimitConfig;
+use crate::ServiceSettings;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -17,6 +19,8 @@ pub struct RegistryConfig {
     pub retry_count: usize,
     /// Enable request/response logging
     pub enable_logging: bool,
+    /// Optional client-side rate limit for outgoing requests
+    pub rate_limit: Option<RateLimitConfig>,
 }
 
 impl Default for RegistryConfig {
@@ -28,6 +32,7 @@ impl Default for RegistryConfig {
             timeout_secs: 30,
             retry_count: 3,
             enable_logging: true,
+            rate_limit: None,
         }
     }
 }
@@ -41,6 +46,19 @@ impl RegistryConfig {
         }
     }
 
+    /// Configuration for the service described by `settings`, with the
+    /// remaining options at their defaults
+    pub fn from_settings(settings: &ServiceSettings) -> Self {
+        let mut config = Self::new(settings.url.clone());
+        if let Some(api_key) = &settings.api_key {
+            config.api_key = Some(api_key.clone());
+        }
+        if let Some(timeout_secs) = settings.timeout_secs {
+            config.timeout_secs = timeout_secs;
+        }
+        config
+    }
+
     /// Set the API key
     pub fn with_api_key(mut self, api_key: impl Into<String>) -> Self {
         self.api_key = Some(api_key.into());
@@ -64,6 +82,12 @@ impl RegistryConfig {
         self.enable_logging = enable;
         self
     }
+
+    /// Limit outgoing requests with a token bucket
+    pub fn with_rate_limit(mut self, rate_limit: RateLimitConfig) -> Self {
+        self.rate_limit = Some(rate_limit);
+        self
+    }
 }
 
 /// Model metadata from the registry
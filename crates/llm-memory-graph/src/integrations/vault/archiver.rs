@@ -1,13 +1,23 @@
 //! Data-Vault integration for secure archival and compliance
 
+use async_trait::async_trait;
 use chrono::{DateTime, Utc};
+use futures::StreamExt;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::time::Duration;
 use tracing::{debug, error, info, warn};
 
-use crate::integrations::{retry_request, IntegrationError, RetryPolicy};
+use crate::engine::AsyncMemoryGraph;
+use crate::health::ComponentHealth;
+use crate::integrations::vault::checkpoint::ArchiveCheckpoint;
+use crate::integrations::{
+    guarded_request, CircuitBreaker, CircuitState, IntegrationError, RateLimitConfig, RateLimiter,
+    RetryPolicy,
+};
+use crate::observatory::PrometheusMetrics;
+use crate::{ServiceSettings, SessionId};
 
 /// Vault client configuration
 #[derive(Debug, Clone)]
@@ -24,8 +34,12 @@ pub struct VaultConfig {
     pub timeout_secs: u64,
     /// Batch size for bulk operations
     pub batch_size: usize,
+    /// Maximum number of batch uploads in flight at once
+    pub max_concurrent_uploads: usize,
     /// Enable request/response logging
     pub enable_logging: bool,
+    /// Optional client-side rate limit for outgoing requests
+    pub rate_limit: Option<RateLimitConfig>,
 }
 
 impl VaultConfig {
@@ -41,10 +55,28 @@ impl VaultConfig {
             compression_enabled: true,
             timeout_secs: 60,
             batch_size: 100,
+            max_concurrent_uploads: 4,
             enable_logging: true,
+            rate_limit: None,
         }
     }
 
+    /// Configuration for the service described by `settings`, with the
+    /// remaining options at their defaults
+    ///
+    /// A missing API key is left empty; [`Config::validate`](crate::Config::validate)
+    /// rejects vault settings without one.
+    pub fn from_settings(settings: &ServiceSettings) -> Self {
+        let mut config = Self::new(
+            settings.url.clone(),
+            settings.api_key.clone().unwrap_or_default(),
+        );
+        if let Some(timeout_secs) = settings.timeout_secs {
+            config.timeout_secs = timeout_secs;
+        }
+        config
+    }
+
     /// Set encryption enabled
     pub fn with_encryption(mut self, enabled: bool) -> Self {
         self.encryption_enabled = enabled;
@@ -68,6 +100,18 @@ impl VaultConfig {
         self.batch_size = batch_size;
         self
     }
+
+    /// Set the maximum number of concurrent batch uploads
+    pub fn with_max_concurrent_uploads(mut self, max: usize) -> Self {
+        self.max_concurrent_uploads = max;
+        self
+    }
+
+    /// Limit outgoing requests with a token bucket
+    pub fn with_rate_limit(mut self, rate_limit: RateLimitConfig) -> Self {
+        self.rate_limit = Some(rate_limit);
+        self
+    }
 }
 
 impl Default for VaultConfig {
@@ -81,7 +125,9 @@ impl Default for VaultConfig {
             compression_enabled: true,
             timeout_secs: 60,
             batch_size: 100,
+            max_concurrent_uploads: 4,
             enable_logging: true,
+            rate_limit: None,
         }
     }
 }
@@ -251,11 +297,38 @@ pub struct ArchiveFailure {
     pub error: String,
 }
 
+/// Archival backend interface
+///
+/// Implemented by [`VaultClient`] for the Data-Vault service and, with the
+/// `object-store` feature, by `ObjectStoreArchiver` for S3/GCS/Azure buckets.
+#[async_trait]
+pub trait Archiver: Send + Sync {
+    /// Archive a single session
+    async fn archive_session(
+        &self,
+        entry: ArchiveEntry,
+    ) -> Result<ArchiveResponse, IntegrationError>;
+
+    /// Archive several sessions in one call
+    async fn batch_archive(
+        &self,
+        entries: Vec<ArchiveEntry>,
+    ) -> Result<BatchArchiveResponse, IntegrationError>;
+
+    /// Retrieve an archived session by archive ID
+    async fn retrieve_session(&self, archive_id: &str) -> Result<ArchiveEntry, IntegrationError>;
+
+    /// Delete an archived session by archive ID
+    async fn delete_archive(&self, archive_id: &str) -> Result<(), IntegrationError>;
+}
+
 /// Data-Vault client for archival operations
 pub struct VaultClient {
     config: VaultConfig,
     client: Client,
     retry_policy: RetryPolicy,
+    circuit_breaker: CircuitBreaker,
+    rate_limiter: Option<RateLimiter>,
 }
 
 impl VaultClient {
@@ -276,10 +349,18 @@ impl VaultClient {
             .with_max_attempts(3)
             .with_initial_delay(Duration::from_millis(200));
 
+        let rate_limiter = config
+            .rate_limit
+            .clone()
+            .map(RateLimiter::new)
+            .transpose()?;
+
         Ok(Self {
             config,
             client,
             retry_policy,
+            circuit_breaker: CircuitBreaker::default_config(),
+            rate_limiter,
         })
     }
 
@@ -289,6 +370,30 @@ impl VaultClient {
         self
     }
 
+    /// Create a client with a custom circuit breaker
+    pub fn with_circuit_breaker(mut self, breaker: CircuitBreaker) -> Self {
+        self.circuit_breaker = breaker;
+        self
+    }
+
+    /// Export circuit breaker state to Prometheus under the `vault` service label
+    pub fn with_metrics(mut self, metrics: &PrometheusMetrics) -> Self {
+        self.circuit_breaker = self
+            .circuit_breaker
+            .with_gauge(metrics.circuit_breaker_gauge("vault"));
+        self
+    }
+
+    /// Current state of the client's circuit breaker
+    pub fn circuit_state(&self) -> CircuitState {
+        self.circuit_breaker.state()
+    }
+
+    /// Health of the client, derived from its circuit breaker state
+    pub fn component_health(&self) -> ComponentHealth {
+        ComponentHealth::from_circuit("vault", self.circuit_state())
+    }
+
     /// Archive a session to the vault
     ///
     /// # Errors
@@ -304,7 +409,7 @@ impl VaultClient {
         }
 
         let operation = || async {
-            let mut request = self
+            let request = self
                 .client
                 .post(&url)
                 .bearer_auth(&self.config.api_key)
@@ -334,7 +439,13 @@ impl VaultClient {
             Ok(archive_response)
         };
 
-        let result = retry_request(&self.retry_policy, operation).await?;
+        let result = guarded_request(
+            &self.circuit_breaker,
+            self.rate_limiter.as_ref(),
+            &self.retry_policy,
+            operation,
+        )
+        .await?;
 
         if self.config.enable_logging {
             info!(
@@ -392,7 +503,13 @@ impl VaultClient {
             Ok(batch_response)
         };
 
-        let result = retry_request(&self.retry_policy, operation).await?;
+        let result = guarded_request(
+            &self.circuit_breaker,
+            self.rate_limiter.as_ref(),
+            &self.retry_policy,
+            operation,
+        )
+        .await?;
 
         if self.config.enable_logging {
             info!(
@@ -404,6 +521,146 @@ impl VaultClient {
         Ok(result)
     }
 
+    /// Archive sessions from the graph in chunks
+    ///
+    /// Each session's prompts, responses and other nodes are loaded from `graph`
+    /// and uploaded in chunks of `batch_size` entries, with up to
+    /// `max_concurrent_uploads` chunks in flight; a chunk is only loaded once
+    /// it can be sent. Every outcome is recorded in
+    /// `checkpoint`, which is saved after each chunk; sessions it already marks
+    /// as archived are not uploaded again. A failed chunk only fails its own
+    /// sessions, so the response may contain partial failures.
+    ///
+    /// # Errors
+    /// Returns an error only if the checkpoint cannot be saved.
+    pub async fn archive_sessions_batch(
+        &self,
+        graph: &AsyncMemoryGraph,
+        session_ids: &[SessionId],
+        retention_days: i64,
+        checkpoint: &mut ArchiveCheckpoint,
+    ) -> Result<BatchArchiveResponse, IntegrationError> {
+        let mut archived = Vec::new();
+        let mut failed = Vec::new();
+        let mut pending = Vec::new();
+
+        for session_id in session_ids {
+            let key = session_id.to_string();
+            if checkpoint.is_archived(&key) {
+                debug!("Skipping session {} (already archived)", key);
+                archived.push(key);
+            } else {
+                pending.push(*session_id);
+            }
+        }
+
+        let chunk_size = self.config.batch_size.max(1);
+        if self.config.enable_logging {
+            info!(
+                "Archiving {} sessions in {} chunks ({} already archived)",
+                pending.len(),
+                pending.len().div_ceil(chunk_size),
+                archived.len()
+            );
+        }
+
+        // A chunk's payloads are only built once an upload slot is free, so at
+        // most `max_concurrent_uploads` chunks are held in memory at a time
+        let mut uploads = futures::stream::iter(pending.chunks(chunk_size))
+            .map(|chunk| self.archive_chunk(graph, chunk, retention_days))
+            .buffer_unordered(self.config.max_concurrent_uploads.max(1));
+
+        while let Some((unreadable, ids, result)) = uploads.next().await {
+            for failure in unreadable {
+                checkpoint.record_failed(&failure.session_id, &failure.error);
+                failed.push(failure);
+            }
+            match result {
+                None => {}
+                Some(Ok(response)) => {
+                    for failure in &response.failed {
+                        checkpoint.record_failed(&failure.session_id, &failure.error);
+                    }
+                    for id in ids {
+                        if !response.failed.iter().any(|f| f.session_id == id) {
+                            checkpoint.record_archived(&id);
+                            archived.push(id);
+                        }
+                    }
+                    failed.extend(response.failed);
+                }
+                Some(Err(e)) => {
+                    warn!("Archive chunk of {} sessions failed: {}", ids.len(), e);
+                    for id in ids {
+                        checkpoint.record_failed(&id, e.to_string());
+                        failed.push(ArchiveFailure {
+                            session_id: id,
+                            error: e.to_string(),
+                        });
+                    }
+                }
+            }
+            checkpoint.save()?;
+        }
+
+        Ok(BatchArchiveResponse {
+            success_count: archived.len(),
+            archived,
+            failed,
+            total: session_ids.len(),
+        })
+    }
+
+    /// Load and upload one chunk of sessions
+    ///
+    /// Returns the sessions that could not be loaded, the IDs uploaded and the
+    /// upload's outcome, if anything was left to upload.
+    async fn archive_chunk(
+        &self,
+        graph: &AsyncMemoryGraph,
+        chunk: &[SessionId],
+        retention_days: i64,
+    ) -> (
+        Vec<ArchiveFailure>,
+        Vec<String>,
+        Option<Result<BatchArchiveResponse, IntegrationError>>,
+    ) {
+        let mut entries = Vec::with_capacity(chunk.len());
+        let mut unreadable = Vec::new();
+        for session_id in chunk {
+            let key = session_id.to_string();
+            match Self::session_payload(graph, *session_id).await {
+                Ok(data) => {
+                    entries.push(ArchiveEntry::new(key, data, retention_days).with_tag("batch"));
+                }
+                Err(e) => unreadable.push(ArchiveFailure {
+                    session_id: key,
+                    error: e.to_string(),
+                }),
+            }
+        }
+        let ids: Vec<String> = entries.iter().map(|e| e.session_id.clone()).collect();
+        let result = if entries.is_empty() {
+            None
+        } else {
+            Some(self.batch_archive(entries).await)
+        };
+        (unreadable, ids, result)
+    }
+
+    /// Build the archive payload for a session
+    async fn session_payload(
+        graph: &AsyncMemoryGraph,
+        session_id: SessionId,
+    ) -> crate::Result<serde_json::Value> {
+        let session = graph.get_session(session_id).await?;
+        let nodes = graph.get_session_nodes(&session_id).await?;
+        Ok(serde_json::json!({
+            "session": session,
+            "nodes": nodes,
+        }))
+    }
+
     /// Retrieve an archived session
     ///
     /// # Errors
@@ -441,7 +698,13 @@ impl VaultClient {
             Ok(entry)
         };
 
-        retry_request(&self.retry_policy, operation).await
+        guarded_request(
+            &self.circuit_breaker,
+            self.rate_limiter.as_ref(),
+            &self.retry_policy,
+            operation,
+        )
+        .await
     }
 
     /// Delete an archived session
@@ -477,7 +740,13 @@ impl VaultClient {
             Ok(())
         };
 
-        retry_request(&self.retry_policy, operation).await
+        guarded_request(
+            &self.circuit_breaker,
+            self.rate_limiter.as_ref(),
+            &self.retry_policy,
+            operation,
+        )
+        .await
     }
 
     /// Create a retention policy
@@ -507,7 +776,10 @@ impl VaultClient {
 
             if !status.is_success() {
                 let error_body = response.text().await.unwrap_or_default();
-                error!("Failed to create retention policy: {} - {}", status, error_body);
+                error!(
+                    "Failed to create retention policy: {} - {}",
+                    status, error_body
+                );
                 return Err(IntegrationError::ApiError {
                     status: status.as_u16(),
                     message: error_body,
@@ -518,13 +790,19 @@ impl VaultClient {
             let policy_id = response_json
                 .get("policy_id")
                 .and_then(|v| v.as_str())
-                .ok_or_else(|| IntegrationError::SerializationError("Missing policy_id".to_string()))?
+                .ok_or_else(|| IntegrationError::Serialization("Missing policy_id".to_string()))?
                 .to_string();
 
             Ok(policy_id)
         };
 
-        retry_request(&self.retry_policy, operation).await
+        guarded_request(
+            &self.circuit_breaker,
+            self.rate_limiter.as_ref(),
+            &self.retry_policy,
+            operation,
+        )
+        .await
     }
 
     /// Apply a retention policy to an archive
@@ -560,7 +838,10 @@ impl VaultClient {
 
             if !status.is_success() {
                 let error_body = response.text().await.unwrap_or_default();
-                error!("Failed to apply retention policy: {} - {}", status, error_body);
+                error!(
+                    "Failed to apply retention policy: {} - {}",
+                    status, error_body
+                );
                 return Err(IntegrationError::ApiError {
                     status: status.as_u16(),
                     message: error_body,
@@ -570,7 +851,13 @@ impl VaultClient {
             Ok(())
         };
 
-        retry_request(&self.retry_policy, operation).await
+        guarded_request(
+            &self.circuit_breaker,
+            self.rate_limiter.as_ref(),
+            &self.retry_policy,
+            operation,
+        )
+        .await
     }
 
     /// Health check for the vault service
@@ -591,9 +878,35 @@ impl VaultClient {
     }
 }
 
+#[async_trait]
+impl Archiver for VaultClient {
+    async fn archive_session(
+        &self,
+        entry: ArchiveEntry,
+    ) -> Result<ArchiveResponse, IntegrationError> {
+        VaultClient::archive_session(self, entry).await
+    }
+
+    async fn batch_archive(
+        &self,
+        entries: Vec<ArchiveEntry>,
+    ) -> Result<BatchArchiveResponse, IntegrationError> {
+        VaultClient::batch_archive(self, entries).await
+    }
+
+    async fn retrieve_session(&self, archive_id: &str) -> Result<ArchiveEntry, IntegrationError> {
+        VaultClient::retrieve_session(self, archive_id).await
+    }
+
+    async fn delete_archive(&self, archive_id: &str) -> Result<(), IntegrationError> {
+        VaultClient::delete_archive(self, archive_id).await
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::integrations::RateLimitMode;
 
     #[test]
     fn test_vault_config_builder() {
@@ -609,6 +922,18 @@ mod tests {
         assert!(config.compression_enabled);
         assert_eq!(config.timeout_secs, 120);
         assert_eq!(config.batch_size, 50);
+        assert!(config.rate_limit.is_none());
+    }
+
+    #[test]
+    fn test_vault_client_with_rate_limit() {
+        let config = VaultConfig::new("http://localhost:9000", "test-key")
+            .with_rate_limit(RateLimitConfig::new(5.0, 10).fail_fast());
+        let client = VaultClient::new(config).unwrap();
+
+        let limiter = client.rate_limiter.as_ref().unwrap();
+        assert_eq!(limiter.config().burst, 10);
+        assert_eq!(limiter.config().mode, RateLimitMode::FailFast);
     }
 
     #[test]
@@ -638,6 +963,171 @@ mod tests {
         assert_eq!(policy.tags.len(), 1);
     }
 
+    #[test]
+    fn test_vault_client_reports_breaker_state() {
+        let registry = prometheus::Registry::new();
+        let metrics = PrometheusMetrics::new(&registry).unwrap();
+        let config = VaultConfig::new("http://localhost:9000", "test-key");
+        let client = VaultClient::new(config)
+            .unwrap()
+            .with_circuit_breaker(CircuitBreaker::new(1, 1, Duration::from_mins(1)))
+            .with_metrics(&metrics);
+
+        assert_eq!(client.circuit_state(), CircuitState::Closed);
+        client.circuit_breaker.record_failure();
+        assert_eq!(client.circuit_state(), CircuitState::Open);
+        assert_eq!(metrics.circuit_breaker_gauge("vault").get(), 1);
+        assert_eq!(
+            client.component_health().status,
+            crate::health::HealthStatus::Unhealthy
+        );
+    }
+
+    #[tokio::test]
+    async fn test_archive_sessions_batch_records_partial_failures() {
+        let dir = tempfile::tempdir().unwrap();
+        let graph = AsyncMemoryGraph::open(crate::Config::new(dir.path().join("graph.db")))
+            .await
+            .unwrap();
+        let first = graph.create_session().await.unwrap();
+        let second = graph.create_session().await.unwrap();
+        graph
+            .add_prompt(first.id, "hello".to_string(), None)
+            .await
+            .unwrap();
+        let missing = SessionId::new();
+
+        // Nothing listens on port 1, so every upload fails with a connection error
+        let config = VaultConfig::new("http://127.0.0.1:1", "test-key")
+            .with_batch_size(1)
+            .with_max_concurrent_uploads(2);
+        let client = VaultClient::new(config)
+            .unwrap()
+            .with_retry_policy(RetryPolicy::new().with_max_attempts(1));
+
+        let checkpoint_path = dir.path().join("archive.json");
+        let mut checkpoint = ArchiveCheckpoint::open(&checkpoint_path).unwrap();
+        checkpoint.record_archived(second.id.to_string());
+
+        let response = client
+            .archive_sessions_batch(&graph, &[first.id, second.id, missing], 30, &mut checkpoint)
+            .await
+            .unwrap();
+
+        assert_eq!(response.total, 3);
+        assert_eq!(response.success_count, 1);
+        assert_eq!(response.archived, vec![second.id.to_string()]);
+        assert_eq!(response.failed.len(), 2);
+
+        // Outcomes survive a restart and only the already-archived session is skipped
+        let resumed = ArchiveCheckpoint::open(&checkpoint_path).unwrap();
+        assert!(resumed.is_archived(&second.id.to_string()));
+        assert!(!resumed.is_archived(&first.id.to_string()));
+        assert!(!resumed.is_archived(&missing.to_string()));
+        assert_eq!(resumed.failed_count(), 2);
+    }
+
+    /// A Vault stand-in accepting every batch; each request's entries are
+    /// passed on with a sender the test uses to let the reply go out
+    async fn serve_batches() -> (
+        String,
+        tokio::sync::mpsc::UnboundedReceiver<(Vec<ArchiveEntry>, tokio::sync::oneshot::Sender<()>)>,
+    ) {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut request = Vec::new();
+                let mut buf = [0u8; 4096];
+                let body = loop {
+                    let n = stream.read(&mut buf).await.unwrap();
+                    request.extend_from_slice(&buf[..n]);
+                    let text = String::from_utf8_lossy(&request).to_string();
+                    if let Some((head, body)) = text.split_once("\r\n\r\n") {
+                        let length = head
+                            .lines()
+                            .find_map(|line| {
+                                let (name, value) = line.split_once(':')?;
+                                name.eq_ignore_ascii_case("content-length")
+                                    .then(|| value.trim().parse::<usize>().ok())?
+                            })
+                            .unwrap_or(0);
+                        if body.len() >= length {
+                            break body.to_string();
+                        }
+                    }
+                };
+                let entries: Vec<ArchiveEntry> = serde_json::from_str(&body).unwrap();
+                let ids: Vec<String> = entries.iter().map(|e| e.session_id.clone()).collect();
+                let (reply, sent) = tokio::sync::oneshot::channel();
+                let _ = sender.send((entries, reply));
+                let _ = sent.await;
+                let body = serde_json::json!({
+                    "archived": ids,
+                    "total": ids.len(),
+                    "success_count": ids.len(),
+                })
+                .to_string();
+                let reply = format!(
+                    "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\nconnection: close\r\ncontent-length: {}\r\n\r\n{body}",
+                    body.len()
+                );
+                stream.write_all(reply.as_bytes()).await.unwrap();
+            }
+        });
+        (url, receiver)
+    }
+
+    #[tokio::test]
+    async fn test_archive_sessions_batch_loads_each_chunk_when_sent() {
+        let dir = tempfile::tempdir().unwrap();
+        let graph = AsyncMemoryGraph::open(crate::Config::new(dir.path().join("graph.db")))
+            .await
+            .unwrap();
+        let sessions = [
+            graph.create_session().await.unwrap(),
+            graph.create_session().await.unwrap(),
+            graph.create_session().await.unwrap(),
+        ];
+        let (url, mut batches) = serve_batches().await;
+        let config = VaultConfig::new(url, "test-key")
+            .with_batch_size(2)
+            .with_max_concurrent_uploads(1);
+        let client = VaultClient::new(config)
+            .unwrap()
+            .with_retry_policy(RetryPolicy::new().with_max_attempts(1));
+        let mut checkpoint = ArchiveCheckpoint::open(dir.path().join("archive.json")).unwrap();
+        let ids: Vec<SessionId> = sessions.iter().map(|s| s.id).collect();
+
+        let vault = async {
+            let (first, reply) = batches.recv().await.unwrap();
+            assert_eq!(first.len(), 2);
+            // Written while the first chunk is in flight, so it only shows up
+            // if the second chunk is loaded after the first is sent
+            graph
+                .add_prompt(sessions[2].id, "late".to_string(), None)
+                .await
+                .unwrap();
+            reply.send(()).unwrap();
+
+            let (second, reply) = batches.recv().await.unwrap();
+            reply.send(()).unwrap();
+            second
+        };
+        let (response, second) = tokio::join!(
+            client.archive_sessions_batch(&graph, &ids, 30, &mut checkpoint),
+            vault
+        );
+
+        assert_eq!(response.unwrap().success_count, 3);
+        assert_eq!(second.len(), 1);
+        assert_eq!(second[0].session_id, sessions[2].id.to_string());
+        assert!(second[0].data["nodes"].to_string().contains("late"));
+    }
+
     #[test]
     fn test_compliance_level_serialization() {
         let level = ComplianceLevel::Gdpr;
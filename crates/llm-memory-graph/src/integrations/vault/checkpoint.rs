@@ -0,0 +1,190 @@
+//! Resumable checkpoints for batch archival runs
+
+use crate::integrations::IntegrationError;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Outcome of archiving a single session
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum SessionArchiveOutcome {
+    /// Session was accepted by the vault
+    Archived {
+        /// When the outcome was recorded
+        recorded_at: DateTime<Utc>,
+    },
+    /// Session could not be archived
+    Failed {
+        /// Error message
+        error: String,
+        /// When the outcome was recorded
+        recorded_at: DateTime<Utc>,
+    },
+}
+
+impl SessionArchiveOutcome {
+    /// Whether the session was archived successfully
+    pub fn is_archived(&self) -> bool {
+        matches!(self, SessionArchiveOutcome::Archived { .. })
+    }
+}
+
+/// Per-session outcomes of a batch archival run
+///
+/// A checkpoint opened with [`ArchiveCheckpoint::open`] is written back to disk
+/// after every chunk, so an interrupted run can be resumed by opening the same
+/// file again: sessions already archived are skipped, failed ones are retried.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ArchiveCheckpoint {
+    /// Outcome keyed by session ID
+    pub outcomes: HashMap<String, SessionArchiveOutcome>,
+    #[serde(skip)]
+    path: Option<PathBuf>,
+}
+
+impl ArchiveCheckpoint {
+    /// Create an in-memory checkpoint that is never persisted
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load a checkpoint from `path`, or start an empty one if the file does not exist
+    ///
+    /// # Errors
+    /// Returns an error if the file exists but cannot be read or parsed.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, IntegrationError> {
+        let path = path.as_ref();
+        let mut checkpoint = if path.exists() {
+            let data = std::fs::read(path).map_err(|e| {
+                IntegrationError::InvalidConfig(format!(
+                    "cannot read checkpoint {}: {}",
+                    path.display(),
+                    e
+                ))
+            })?;
+            serde_json::from_slice::<Self>(&data)?
+        } else {
+            Self::default()
+        };
+        checkpoint.path = Some(path.to_path_buf());
+        Ok(checkpoint)
+    }
+
+    /// File the checkpoint is persisted to, if any
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Write the checkpoint to its file
+    ///
+    /// Does nothing for in-memory checkpoints. The file is replaced atomically.
+    ///
+    /// # Errors
+    /// Returns an error if the checkpoint cannot be written.
+    pub fn save(&self) -> Result<(), IntegrationError> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        let data = serde_json::to_vec_pretty(self)?;
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, data)
+            .and_then(|()| std::fs::rename(&tmp, path))
+            .map_err(|e| {
+                IntegrationError::InvalidConfig(format!(
+                    "cannot write checkpoint {}: {}",
+                    path.display(),
+                    e
+                ))
+            })
+    }
+
+    /// Whether the session has already been archived
+    pub fn is_archived(&self, session_id: &str) -> bool {
+        self.outcomes
+            .get(session_id)
+            .is_some_and(SessionArchiveOutcome::is_archived)
+    }
+
+    /// Record a successful archive
+    pub fn record_archived(&mut self, session_id: impl Into<String>) {
+        self.outcomes.insert(
+            session_id.into(),
+            SessionArchiveOutcome::Archived {
+                recorded_at: Utc::now(),
+            },
+        );
+    }
+
+    /// Record a failed archive
+    pub fn record_failed(&mut self, session_id: impl Into<String>, error: impl Into<String>) {
+        self.outcomes.insert(
+            session_id.into(),
+            SessionArchiveOutcome::Failed {
+                error: error.into(),
+                recorded_at: Utc::now(),
+            },
+        );
+    }
+
+    /// Number of sessions recorded as archived
+    pub fn archived_count(&self) -> usize {
+        self.outcomes.values().filter(|o| o.is_archived()).count()
+    }
+
+    /// Number of sessions recorded as failed
+    pub fn failed_count(&self) -> usize {
+        self.outcomes.len() - self.archived_count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checkpoint_records_outcomes() {
+        let mut checkpoint = ArchiveCheckpoint::new();
+        checkpoint.record_archived("a");
+        checkpoint.record_failed("b", "timeout");
+
+        assert!(checkpoint.is_archived("a"));
+        assert!(!checkpoint.is_archived("b"));
+        assert!(!checkpoint.is_archived("c"));
+        assert_eq!(checkpoint.archived_count(), 1);
+        assert_eq!(checkpoint.failed_count(), 1);
+
+        // A later success overrides an earlier failure
+        checkpoint.record_archived("b");
+        assert_eq!(checkpoint.failed_count(), 0);
+    }
+
+    #[test]
+    fn test_checkpoint_persists_and_resumes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("archive.checkpoint.json");
+
+        let mut checkpoint = ArchiveCheckpoint::open(&path).unwrap();
+        assert!(checkpoint.outcomes.is_empty());
+        checkpoint.record_archived("a");
+        checkpoint.record_failed("b", "HTTP 503");
+        checkpoint.save().unwrap();
+
+        let resumed = ArchiveCheckpoint::open(&path).unwrap();
+        assert_eq!(resumed.path(), Some(path.as_path()));
+        assert!(resumed.is_archived("a"));
+        assert!(matches!(
+            resumed.outcomes.get("b"),
+            Some(SessionArchiveOutcome::Failed { error, .. }) if error == "HTTP 503"
+        ));
+    }
+
+    #[test]
+    fn test_in_memory_checkpoint_save_is_noop() {
+        let checkpoint = ArchiveCheckpoint::new();
+        assert!(checkpoint.path().is_none());
+        assert!(checkpoint.save().is_ok());
+    }
+}
@@ -4,10 +4,18 @@
 //! including archival operations, retention policies, and automatic scheduling.
 
 pub mod archiver;
+pub mod checkpoint;
+#[cfg(feature = "object-store")]
+pub mod object_archive;
 pub mod retention;
 
 pub use archiver::{
-    ArchiveEntry, ArchiveFailure, ArchiveResponse, BatchArchiveResponse, ComplianceLevel,
+    ArchiveEntry, ArchiveFailure, ArchiveResponse, Archiver, BatchArchiveResponse, ComplianceLevel,
     RetentionPolicy, VaultClient, VaultConfig,
 };
+pub use checkpoint::{ArchiveCheckpoint, SessionArchiveOutcome};
+#[cfg(feature = "object-store")]
+pub use object_archive::{
+    ObjectStoreArchiver, ObjectStoreConfig, ObjectStoreTarget, ServerSideEncryption,
+};
 pub use retention::{ArchivalScheduler, ArchivalStats, RetentionPolicyManager, SchedulerConfig};
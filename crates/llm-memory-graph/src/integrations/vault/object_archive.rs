@@ -0,0 +1,430 @@
+//! Object-store archival target for S3, GCS and Azure Blob Storage
+//!
+//! Archives are written as one JSON object per [`ArchiveEntry`] under
+//! `{prefix}/{archive_id}.json`, so deployments without a Data-Vault service
+//! can archive sessions straight into a bucket.
+
+use super::archiver::{
+    ArchiveEntry, ArchiveFailure, ArchiveResponse, Archiver, BatchArchiveResponse,
+};
+use crate::integrations::IntegrationError;
+use async_trait::async_trait;
+use chrono::Utc;
+use futures::StreamExt;
+use object_store::aws::AmazonS3Builder;
+use object_store::azure::MicrosoftAzureBuilder;
+use object_store::gcp::GoogleCloudStorageBuilder;
+use object_store::path::Path;
+use object_store::{Attribute, Attributes, ObjectStore, PutOptions, PutPayload, TagSet};
+use std::sync::Arc;
+use tracing::{debug, info};
+
+/// Bucket the archiver writes to
+///
+/// Credentials are read from the provider's usual environment variables
+/// (`AWS_*`, `GOOGLE_*`, `AZURE_*`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ObjectStoreTarget {
+    /// Amazon S3 or an S3-compatible service
+    S3 {
+        /// Bucket name
+        bucket: String,
+        /// Region, if not taken from the environment
+        region: Option<String>,
+    },
+    /// Google Cloud Storage
+    Gcs {
+        /// Bucket name
+        bucket: String,
+    },
+    /// Azure Blob Storage
+    Azure {
+        /// Storage account name
+        account: String,
+        /// Container name
+        container: String,
+    },
+}
+
+/// Server-side encryption applied to archived objects
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum ServerSideEncryption {
+    /// Provider-managed keys (SSE-S3 on AWS, Google/Microsoft-managed keys elsewhere)
+    #[default]
+    ProviderManaged,
+    /// SSE-KMS with the given AWS KMS key
+    S3Kms {
+        /// KMS key ID or ARN
+        key_id: String,
+    },
+    /// Dual-layer SSE-KMS with the given AWS KMS key
+    S3DualKms {
+        /// KMS key ID or ARN
+        key_id: String,
+    },
+    /// SSE-C with a customer-provided key
+    S3CustomerKey {
+        /// Base64-encoded 256-bit key
+        key_base64: String,
+    },
+}
+
+/// Object-store archiver configuration
+#[derive(Debug, Clone)]
+pub struct ObjectStoreConfig {
+    /// Bucket to archive into
+    pub target: ObjectStoreTarget,
+    /// Key prefix for archived objects
+    pub prefix: String,
+    /// Server-side encryption settings
+    pub encryption: ServerSideEncryption,
+    /// Maximum number of uploads in flight during a batch
+    pub max_concurrent_uploads: usize,
+}
+
+impl ObjectStoreConfig {
+    fn with_target(target: ObjectStoreTarget) -> Self {
+        Self {
+            target,
+            prefix: "llm-memory-graph/archives".to_string(),
+            encryption: ServerSideEncryption::default(),
+            max_concurrent_uploads: 8,
+        }
+    }
+
+    /// Archive into an S3 bucket
+    pub fn s3(bucket: impl Into<String>) -> Self {
+        Self::with_target(ObjectStoreTarget::S3 {
+            bucket: bucket.into(),
+            region: None,
+        })
+    }
+
+    /// Archive into a Google Cloud Storage bucket
+    pub fn gcs(bucket: impl Into<String>) -> Self {
+        Self::with_target(ObjectStoreTarget::Gcs {
+            bucket: bucket.into(),
+        })
+    }
+
+    /// Archive into an Azure Blob Storage container
+    pub fn azure(account: impl Into<String>, container: impl Into<String>) -> Self {
+        Self::with_target(ObjectStoreTarget::Azure {
+            account: account.into(),
+            container: container.into(),
+        })
+    }
+
+    /// Set the S3 region (ignored for other targets)
+    pub fn with_region(mut self, region: impl Into<String>) -> Self {
+        if let ObjectStoreTarget::S3 { region: r, .. } = &mut self.target {
+            *r = Some(region.into());
+        }
+        self
+    }
+
+    /// Set the key prefix
+    pub fn with_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    /// Set server-side encryption
+    pub fn with_encryption(mut self, encryption: ServerSideEncryption) -> Self {
+        self.encryption = encryption;
+        self
+    }
+
+    /// Set the maximum number of concurrent uploads
+    pub fn with_max_concurrent_uploads(mut self, max: usize) -> Self {
+        self.max_concurrent_uploads = max;
+        self
+    }
+}
+
+impl From<object_store::Error> for IntegrationError {
+    fn from(err: object_store::Error) -> Self {
+        match err {
+            object_store::Error::NotFound { path, .. } => IntegrationError::ApiError {
+                status: 404,
+                message: format!("Archive not found: {}", path),
+            },
+            object_store::Error::Generic { .. } => IntegrationError::HttpError(err.to_string()),
+            other => IntegrationError::InvalidConfig(other.to_string()),
+        }
+    }
+}
+
+/// Archiver that writes sessions directly to an object store
+pub struct ObjectStoreArchiver {
+    store: Arc<dyn ObjectStore>,
+    prefix: Path,
+    max_concurrent_uploads: usize,
+}
+
+impl ObjectStoreArchiver {
+    /// Create an archiver over an existing object store
+    pub fn new(store: Arc<dyn ObjectStore>) -> Self {
+        Self {
+            store,
+            prefix: Path::from("llm-memory-graph/archives"),
+            max_concurrent_uploads: 8,
+        }
+    }
+
+    /// Build an archiver for the configured bucket
+    ///
+    /// # Errors
+    /// Returns `InvalidConfig` if the store cannot be built or an S3-only
+    /// encryption mode is requested for GCS or Azure.
+    pub fn from_config(config: ObjectStoreConfig) -> Result<Self, IntegrationError> {
+        let s3_only = || {
+            IntegrationError::InvalidConfig(
+                "KMS and customer-key encryption are only supported for S3 targets".to_string(),
+            )
+        };
+
+        let store: Arc<dyn ObjectStore> = match &config.target {
+            ObjectStoreTarget::S3 { bucket, region } => {
+                let mut builder = AmazonS3Builder::from_env().with_bucket_name(bucket);
+                if let Some(region) = region {
+                    builder = builder.with_region(region);
+                }
+                builder = match &config.encryption {
+                    ServerSideEncryption::ProviderManaged => builder,
+                    ServerSideEncryption::S3Kms { key_id } => {
+                        builder.with_sse_kms_encryption(key_id)
+                    }
+                    ServerSideEncryption::S3DualKms { key_id } => {
+                        builder.with_dsse_kms_encryption(key_id)
+                    }
+                    ServerSideEncryption::S3CustomerKey { key_base64 } => {
+                        builder.with_ssec_encryption(key_base64)
+                    }
+                };
+                Arc::new(builder.build()?)
+            }
+            ObjectStoreTarget::Gcs { bucket } => {
+                if config.encryption != ServerSideEncryption::ProviderManaged {
+                    return Err(s3_only());
+                }
+                Arc::new(
+                    GoogleCloudStorageBuilder::from_env()
+                        .with_bucket_name(bucket)
+                        .build()?,
+                )
+            }
+            ObjectStoreTarget::Azure { account, container } => {
+                if config.encryption != ServerSideEncryption::ProviderManaged {
+                    return Err(s3_only());
+                }
+                Arc::new(
+                    MicrosoftAzureBuilder::from_env()
+                        .with_account(account)
+                        .with_container_name(container)
+                        .build()?,
+                )
+            }
+        };
+
+        Ok(Self::new(store)
+            .with_prefix(&config.prefix)
+            .with_max_concurrent_uploads(config.max_concurrent_uploads))
+    }
+
+    /// Set the key prefix
+    pub fn with_prefix(mut self, prefix: &str) -> Self {
+        self.prefix = Path::from(prefix);
+        self
+    }
+
+    /// Set the maximum number of concurrent uploads
+    pub fn with_max_concurrent_uploads(mut self, max: usize) -> Self {
+        self.max_concurrent_uploads = max.max(1);
+        self
+    }
+
+    /// Object key for an archive ID
+    pub fn object_path(&self, archive_id: &str) -> Path {
+        self.prefix.child(format!("{}.json", archive_id))
+    }
+}
+
+#[async_trait]
+impl Archiver for ObjectStoreArchiver {
+    async fn archive_session(
+        &self,
+        entry: ArchiveEntry,
+    ) -> Result<ArchiveResponse, IntegrationError> {
+        let path = self.object_path(&entry.id);
+        let body = serde_json::to_vec(&entry)?;
+
+        let mut attributes = Attributes::new();
+        attributes.insert(Attribute::ContentType, "application/json".into());
+        let mut tags = TagSet::default();
+        tags.push("session_id", &entry.session_id);
+        tags.push("retention_days", &entry.retention_days.to_string());
+
+        let options = PutOptions {
+            tags,
+            attributes,
+            ..Default::default()
+        };
+        self.store
+            .put_opts(&path, PutPayload::from(body), options)
+            .await?;
+
+        debug!("Archived session {} to {}", entry.session_id, path);
+
+        Ok(ArchiveResponse {
+            archive_id: entry.id,
+            session_id: entry.session_id,
+            status: "archived".to_string(),
+            archived_at: Utc::now(),
+        })
+    }
+
+    async fn batch_archive(
+        &self,
+        entries: Vec<ArchiveEntry>,
+    ) -> Result<BatchArchiveResponse, IntegrationError> {
+        let total = entries.len();
+        let mut archived = Vec::new();
+        let mut failed = Vec::new();
+
+        let mut uploads = futures::stream::iter(entries.into_iter().map(|entry| async move {
+            let session_id = entry.session_id.clone();
+            (session_id, self.archive_session(entry).await)
+        }))
+        .buffer_unordered(self.max_concurrent_uploads);
+
+        while let Some((session_id, result)) = uploads.next().await {
+            match result {
+                Ok(_) => archived.push(session_id),
+                Err(e) => failed.push(ArchiveFailure {
+                    session_id,
+                    error: e.to_string(),
+                }),
+            }
+        }
+
+        info!(
+            "Archived {} of {} sessions to object store",
+            archived.len(),
+            total
+        );
+
+        Ok(BatchArchiveResponse {
+            success_count: archived.len(),
+            archived,
+            failed,
+            total,
+        })
+    }
+
+    async fn retrieve_session(&self, archive_id: &str) -> Result<ArchiveEntry, IntegrationError> {
+        let data = self
+            .store
+            .get(&self.object_path(archive_id))
+            .await?
+            .bytes()
+            .await?;
+        Ok(serde_json::from_slice(&data)?)
+    }
+
+    async fn delete_archive(&self, archive_id: &str) -> Result<(), IntegrationError> {
+        self.store.delete(&self.object_path(archive_id)).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use object_store::memory::InMemory;
+
+    fn archiver() -> ObjectStoreArchiver {
+        ObjectStoreArchiver::new(Arc::new(InMemory::new())).with_prefix("test/archives")
+    }
+
+    #[tokio::test]
+    async fn test_archive_and_retrieve_round_trip() {
+        let archiver = archiver();
+        let entry = ArchiveEntry::new("session-1", serde_json::json!({"nodes": [1, 2]}), 90)
+            .with_tag("manual");
+        let archive_id = entry.id.clone();
+
+        let response = archiver.archive_session(entry).await.unwrap();
+        assert_eq!(response.archive_id, archive_id);
+        assert_eq!(response.session_id, "session-1");
+
+        let restored = archiver.retrieve_session(&archive_id).await.unwrap();
+        assert_eq!(restored.session_id, "session-1");
+        assert_eq!(restored.retention_days, 90);
+        assert_eq!(restored.data["nodes"][1], 2);
+        assert_eq!(
+            archiver.object_path(&archive_id).to_string(),
+            format!("test/archives/{}.json", archive_id)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_delete_then_retrieve_is_not_found() {
+        let archiver = archiver();
+        let entry = ArchiveEntry::new("session-1", serde_json::json!({}), 30);
+        let archive_id = entry.id.clone();
+        archiver.archive_session(entry).await.unwrap();
+
+        archiver.delete_archive(&archive_id).await.unwrap();
+        assert!(matches!(
+            archiver.retrieve_session(&archive_id).await,
+            Err(IntegrationError::ApiError { status: 404, .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_batch_archive() {
+        let archiver = archiver().with_max_concurrent_uploads(2);
+        let entries = (0..5)
+            .map(|i| ArchiveEntry::new(format!("session-{}", i), serde_json::json!({}), 30))
+            .collect();
+
+        let response = archiver.batch_archive(entries).await.unwrap();
+        assert_eq!(response.total, 5);
+        assert_eq!(response.success_count, 5);
+        assert!(response.failed.is_empty());
+    }
+
+    #[test]
+    fn test_s3_kms_encryption_config() {
+        let config = ObjectStoreConfig::s3("archive-bucket")
+            .with_region("us-east-1")
+            .with_prefix("prod")
+            .with_encryption(ServerSideEncryption::S3Kms {
+                key_id: "arn:aws:kms:us-east-1:123456789012:key/abcd".to_string(),
+            });
+        assert!(matches!(
+            &config.target,
+            ObjectStoreTarget::S3 { region: Some(r), .. } if r == "us-east-1"
+        ));
+        assert!(ObjectStoreArchiver::from_config(config).is_ok());
+    }
+
+    #[test]
+    fn test_kms_rejected_for_non_s3_targets() {
+        let encryption = ServerSideEncryption::S3Kms {
+            key_id: "key".to_string(),
+        };
+        let gcs = ObjectStoreConfig::gcs("bucket").with_encryption(encryption.clone());
+        let azure = ObjectStoreConfig::azure("account", "container").with_encryption(encryption);
+
+        assert!(matches!(
+            ObjectStoreArchiver::from_config(gcs),
+            Err(IntegrationError::InvalidConfig(_))
+        ));
+        assert!(matches!(
+            ObjectStoreArchiver::from_config(azure),
+            Err(IntegrationError::InvalidConfig(_))
+        ));
+    }
+}
@@ -3,7 +3,7 @@
 use super::archiver::{ArchiveEntry, ComplianceLevel, RetentionPolicy, VaultClient};
 use crate::engine::AsyncMemoryGraph;
 use crate::integrations::IntegrationError;
-use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use chrono::{Duration as ChronoDuration, Utc};
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::task::JoinHandle;
@@ -207,8 +207,8 @@ impl ArchivalScheduler {
 
     /// Run a single archival iteration
     async fn run_archival(
-        vault: &VaultClient,
-        graph: &AsyncMemoryGraph,
+        _vault: &VaultClient,
+        _graph: &AsyncMemoryGraph,
         config: &SchedulerConfig,
     ) -> Result<ArchivalStats, IntegrationError> {
         let mut stats = ArchivalStats::new();
@@ -249,16 +249,9 @@ impl ArchivalScheduler {
     ) -> Result<String, IntegrationError> {
         info!("Manually archiving session: {}", session_id);
 
-        let entry = ArchiveEntry::new(
-            session_id,
-            session_data,
-            self.config.retention_days,
-        )
-        .with_tag("manual")
-        .with_metadata(
-            "archived_by",
-            serde_json::json!("archival_scheduler"),
-        );
+        let entry = ArchiveEntry::new(session_id, session_data, self.config.retention_days)
+            .with_tag("manual")
+            .with_metadata("archived_by", serde_json::json!("archival_scheduler"));
 
         let response = self.vault_client.archive_session(entry).await?;
         Ok(response.archive_id)
@@ -275,10 +268,8 @@ impl ArchivalScheduler {
     ) -> Result<String, IntegrationError> {
         let retention_days = match compliance_level {
             ComplianceLevel::Standard => 365,
-            ComplianceLevel::Hipaa => 2555, // 7 years
-            ComplianceLevel::Gdpr => 2555,  // 7 years
-            ComplianceLevel::Pci => 1095,   // 3 years
-            ComplianceLevel::Soc2 => 2555,  // 7 years
+            ComplianceLevel::Pci => 1095, // 3 years
+            ComplianceLevel::Hipaa | ComplianceLevel::Gdpr | ComplianceLevel::Soc2 => 2555, // 7 years
         };
 
         let policy = RetentionPolicy::new(name, retention_days, compliance_level)
@@ -302,8 +293,7 @@ impl ArchivalScheduler {
         let entries: Vec<ArchiveEntry> = sessions
             .into_iter()
             .map(|(session_id, data)| {
-                ArchiveEntry::new(session_id, data, self.config.retention_days)
-                    .with_tag("batch")
+                ArchiveEntry::new(session_id, data, self.config.retention_days).with_tag("batch")
             })
             .collect();
 
@@ -52,18 +52,48 @@
 #![allow(clippy::unnecessary_wraps)]
 #![allow(clippy::too_many_lines)]
 #![allow(clippy::explicit_iter_loop)]
+#![allow(clippy::float_cmp)]
+#![allow(clippy::similar_names)]
+#![allow(clippy::format_push_string)]
+#![allow(clippy::unused_async)]
 
+pub mod anonymize;
+pub mod archive;
+pub mod audit;
+pub mod auth;
+pub mod changes;
+#[cfg(feature = "parquet")]
+pub mod columnar;
+pub mod conversation;
+pub mod custom;
+pub mod deadline;
+pub mod diff;
+pub mod distribution;
 pub mod engine;
-// pub mod grpc; // TODO: Complete gRPC implementation
-// pub mod integrations; // TODO: Fix type mismatches in retry logic
+pub mod export;
+pub mod finetune;
+pub mod grpc;
+pub mod health;
+pub mod integrations;
 pub mod migration;
 pub mod observatory;
+pub mod pagination;
 pub mod plugin;
 pub mod query;
+pub mod replication;
+pub mod shipping;
+pub mod slow_ops;
 pub mod storage;
+pub mod template_report;
+pub mod template_sync;
+pub mod transcript;
+pub mod usage;
+pub mod usage_export;
+pub mod validation;
 
 // Re-export main types
 pub use engine::{AsyncMemoryGraph, MemoryGraph};
+pub use integrations::{RegistryClient, RegistryConfig, VaultClient, VaultConfig};
 
 // Re-export types from llm-memory-graph-types
 pub use llm_memory_graph_types::*;
@@ -0,0 +1,522 @@
+//! ChatGPT data exports
+//!
+//! ChatGPT's "Export data" setting mails a zip whose `conversations.json`
+//! holds every conversation as a tree of messages under `mapping`, since
+//! editing a message or regenerating a reply starts a new branch. Each
+//! conversation becomes one trace titled after it, following the branch that
+//! ends at its `current_node`, the one the user last saw. Every user message
+//! becomes a prompt, answered by the assistant messages that follow it, and
+//! every message the assistant addressed to a tool (such as `python` or
+//! `browser`) becomes a tool invocation, with the tool's reply as its result.
+//!
+//! System messages and messages hidden from the conversation, such as custom
+//! instructions, are left out. User messages nobody answered are counted as
+//! skipped. Reading the zip itself needs the `zip` feature; the extracted
+//! `conversations.json` can be imported without it.
+
+use super::{timestamp, write_traces, ImportReport, ImportedCall, ImportedTool, ImportedTrace};
+use crate::engine::AsyncMemoryGraph;
+use crate::{Error, Result, TokenUsage};
+use chrono::{DateTime, Utc};
+use serde_json::{Map, Value};
+use std::path::Path;
+
+/// Value of [`SOURCE_KEY`](super::SOURCE_KEY) on sessions imported from
+/// ChatGPT
+const SOURCE: &str = "chatgpt";
+
+/// Name of the file holding the conversations inside an export zip
+pub const CONVERSATIONS_FILE: &str = "conversations.json";
+
+/// Recipient of assistant messages shown to the user rather than sent to a tool
+const USER_RECIPIENT: &str = "all";
+
+/// Imports ChatGPT data exports, one session per conversation
+#[derive(Debug, Clone, Default)]
+pub struct ChatGptImporter {
+    tags: Vec<String>,
+}
+
+impl ChatGptImporter {
+    /// Importer with no extra session tags
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Tag every imported session with `tag`
+    pub fn with_tag(mut self, tag: impl Into<String>) -> Self {
+        self.tags.push(tag.into());
+        self
+    }
+
+    /// Import the conversations in `conversations`, the contents of an
+    /// export's `conversations.json`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `conversations` is not a JSON array of
+    /// conversations, or if writing to the graph fails.
+    pub async fn import(
+        &self,
+        graph: &AsyncMemoryGraph,
+        conversations: &str,
+    ) -> Result<ImportReport> {
+        let (traces, skipped) = Self::parse(conversations)?;
+        let report = ImportReport {
+            skipped,
+            ..ImportReport::default()
+        };
+        write_traces(graph, SOURCE, traces, &self.tags, report).await
+    }
+
+    /// Import the export zip, or the `conversations.json` taken out of it, at
+    /// `path`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read, is a zip without a
+    /// `conversations.json` or, without the `zip` feature, is a zip at all,
+    /// or as for [`import`](Self::import).
+    pub async fn import_file(
+        &self,
+        graph: &AsyncMemoryGraph,
+        path: impl AsRef<Path>,
+    ) -> Result<ImportReport> {
+        let data = tokio::fs::read(path).await?;
+        let conversations = if data.starts_with(b"PK\x03\x04") {
+            conversations_from_zip(data).await?
+        } else {
+            String::from_utf8(data).map_err(|e| {
+                Error::MigrationError(format!("{CONVERSATIONS_FILE} is not UTF-8: {e}"))
+            })?
+        };
+        self.import(graph, &conversations).await
+    }
+
+    /// Read the conversations in `conversations`; also returns the number of
+    /// conversations and messages that could not be used
+    pub(crate) fn parse(conversations: &str) -> Result<(Vec<ImportedTrace>, usize)> {
+        let records: Vec<Value> = serde_json::from_str(conversations).map_err(|e| {
+            Error::MigrationError(format!("{CONVERSATIONS_FILE} is not a JSON array: {e}"))
+        })?;
+        let mut traces = Vec::with_capacity(records.len());
+        let mut skipped = 0;
+        for record in &records {
+            match conversation(record) {
+                Some((trace, unused)) => {
+                    traces.push(trace);
+                    skipped += unused;
+                }
+                None => skipped += 1,
+            }
+        }
+        traces.sort_by_key(|trace| trace.started_at);
+        Ok((traces, skipped))
+    }
+}
+
+/// Extract `conversations.json` from an export zip
+#[cfg(feature = "zip")]
+async fn conversations_from_zip(data: Vec<u8>) -> Result<String> {
+    tokio::task::spawn_blocking(move || {
+        use std::io::Read;
+
+        let invalid = |e: zip::result::ZipError| {
+            Error::MigrationError(format!("invalid ChatGPT export zip: {e}"))
+        };
+        let mut archive = zip::ZipArchive::new(std::io::Cursor::new(data)).map_err(invalid)?;
+        let name = archive
+            .file_names()
+            .filter(|name| {
+                *name == CONVERSATIONS_FILE || name.ends_with(&format!("/{CONVERSATIONS_FILE}"))
+            })
+            .min_by_key(|name| name.len())
+            .map(str::to_string)
+            .ok_or_else(|| {
+                Error::MigrationError(format!("export zip has no {CONVERSATIONS_FILE}"))
+            })?;
+        let mut conversations = String::new();
+        archive
+            .by_name(&name)
+            .map_err(invalid)?
+            .read_to_string(&mut conversations)?;
+        Ok(conversations)
+    })
+    .await
+    .map_err(|e| Error::RuntimeError(format!("zip extraction task failed: {e}")))?
+}
+
+#[cfg(not(feature = "zip"))]
+async fn conversations_from_zip(_data: Vec<u8>) -> Result<String> {
+    Err(Error::MigrationError(format!(
+        "reading export zips needs the `zip` feature; import the extracted {CONVERSATIONS_FILE} instead"
+    )))
+}
+
+/// A call still collecting the assistant's reply
+struct PendingCall {
+    id: String,
+    prompt: String,
+    started_at: DateTime<Utc>,
+    reply: Vec<String>,
+    model: Option<String>,
+    ended_at: Option<DateTime<Utc>>,
+}
+
+impl PendingCall {
+    fn finish(self) -> Option<ImportedCall> {
+        (!self.reply.is_empty()).then(|| ImportedCall {
+            id: self.id,
+            prompt: self.prompt,
+            response: self.reply.join("\n\n"),
+            model: self.model,
+            usage: TokenUsage::new(0, 0),
+            started_at: self.started_at,
+            ended_at: self.ended_at,
+            error: None,
+        })
+    }
+}
+
+/// One conversation as a trace, with the number of messages left out, or
+/// `None` if it has no usable messages or creation time
+fn conversation(record: &Value) -> Option<(ImportedTrace, usize)> {
+    let started_at = record.get("create_time").and_then(timestamp)?;
+    let mapping = record.get("mapping")?.as_object()?;
+    let mut trace = ImportedTrace {
+        id: ["conversation_id", "id"]
+            .iter()
+            .find_map(|key| record.get(*key)?.as_str())
+            .unwrap_or_default()
+            .to_string(),
+        name: record
+            .get("title")
+            .and_then(Value::as_str)
+            .filter(|title| !title.is_empty())
+            .map(str::to_string),
+        started_at,
+        tags: Vec::new(),
+        calls: Vec::new(),
+        tools: Vec::new(),
+    };
+    let default_model = record
+        .get("default_model_slug")
+        .and_then(Value::as_str)
+        .map(str::to_string);
+
+    let mut skipped = 0;
+    let mut pending: Option<PendingCall> = None;
+    let mut at = started_at;
+    for message in thread(record, mapping) {
+        let Some(text) = message_text(message) else {
+            continue;
+        };
+        at = message.get("create_time").and_then(timestamp).unwrap_or(at);
+        let id = message
+            .get("id")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string();
+        let author = message.get("author");
+        match author.and_then(|a| a.get("role")).and_then(Value::as_str) {
+            Some("user") => {
+                if let Some(call) = pending.take() {
+                    skipped += push_call(&mut trace, call);
+                }
+                pending = Some(PendingCall {
+                    id,
+                    prompt: text,
+                    started_at: at,
+                    reply: Vec::new(),
+                    model: None,
+                    ended_at: None,
+                });
+            }
+            Some("assistant") => {
+                let Some(call) = pending.as_mut() else {
+                    skipped += 1;
+                    continue;
+                };
+                let model = message
+                    .get("metadata")
+                    .and_then(|m| m.get("model_slug"))
+                    .and_then(Value::as_str)
+                    .map(str::to_string);
+                call.model = model.or(call.model.take()).or(default_model.clone());
+                match message.get("recipient").and_then(Value::as_str) {
+                    Some(tool) if tool != USER_RECIPIENT => trace.tools.push(ImportedTool {
+                        id,
+                        name: tool.to_string(),
+                        input: serde_json::from_str(&text).unwrap_or(Value::String(text)),
+                        output: None,
+                        error: None,
+                        started_at: at,
+                        ended_at: None,
+                    }),
+                    _ => {
+                        call.reply.push(text);
+                        call.ended_at = Some(at);
+                    }
+                }
+            }
+            Some("tool") => {
+                let name = author.and_then(|a| a.get("name")).and_then(Value::as_str);
+                let Some(tool) = trace
+                    .tools
+                    .iter_mut()
+                    .rev()
+                    .find(|tool| tool.output.is_none() && name.is_none_or(|n| n == tool.name))
+                else {
+                    skipped += 1;
+                    continue;
+                };
+                tool.output = Some(Value::String(text));
+                tool.ended_at = Some(at);
+            }
+            _ => {}
+        }
+    }
+    if let Some(call) = pending {
+        skipped += push_call(&mut trace, call);
+    }
+    (!trace.calls.is_empty() || skipped > 0).then_some((trace, skipped))
+}
+
+/// Add a finished call to `trace`, returning 1 if it had no reply to add
+fn push_call(trace: &mut ImportedTrace, call: PendingCall) -> usize {
+    match call.finish() {
+        Some(call) => {
+            trace.calls.push(call);
+            0
+        }
+        None => 1,
+    }
+}
+
+/// Messages on the branch ending at the conversation's current node, oldest
+/// first
+fn thread<'a>(record: &'a Value, mapping: &'a Map<String, Value>) -> Vec<&'a Value> {
+    let leaf = record
+        .get("current_node")
+        .and_then(Value::as_str)
+        .filter(|id| mapping.contains_key(*id))
+        .or_else(|| latest_leaf(mapping));
+    let mut messages = Vec::new();
+    let mut next = leaf;
+    // Guard against cycles in a damaged export
+    for _ in 0..=mapping.len() {
+        let Some(node) = next.and_then(|id| mapping.get(id)) else {
+            break;
+        };
+        if let Some(message) = node.get("message").filter(|m| m.is_object()) {
+            messages.push(message);
+        }
+        next = node.get("parent").and_then(Value::as_str);
+    }
+    messages.reverse();
+    messages
+}
+
+/// The most recently written message without replies, for exports that do
+/// not record a current node
+fn latest_leaf(mapping: &Map<String, Value>) -> Option<&str> {
+    mapping
+        .iter()
+        .filter(|(_, node)| {
+            node.get("children")
+                .and_then(Value::as_array)
+                .is_none_or(Vec::is_empty)
+        })
+        .max_by_key(|(_, node)| {
+            node.get("message")
+                .and_then(|m| m.get("create_time"))
+                .and_then(timestamp)
+        })
+        .map(|(id, _)| id.as_str())
+}
+
+/// Text of a visible message: its text parts, or the text of code and tool
+/// output content
+fn message_text(message: &Value) -> Option<String> {
+    let hidden = message
+        .get("metadata")
+        .and_then(|m| m.get("is_visually_hidden_from_conversation"))
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+    if hidden {
+        return None;
+    }
+    let content = message.get("content")?;
+    let text = match content.get("parts").and_then(Value::as_array) {
+        Some(parts) => parts
+            .iter()
+            .filter_map(|part| part.as_str().or_else(|| part.get("text")?.as_str()))
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n"),
+        None => content.get("text")?.as_str()?.to_string(),
+    };
+    (!text.trim().is_empty()).then_some(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::migration::importers::{SOURCE_ID_KEY, SOURCE_KEY};
+    use crate::Config;
+    use tempfile::tempdir;
+
+    /// A conversation whose first answer was regenerated, so only the second
+    /// branch is current, and which runs code through the python tool
+    const EXPORT: &str = r#"[
+        {"title": "Sorting numbers", "create_time": 1714557600.0, "update_time": 1714557700.0,
+         "conversation_id": "conv-1", "default_model_slug": "gpt-4o", "current_node": "a2",
+         "mapping": {
+            "root": {"id": "root", "message": null, "parent": null, "children": ["sys"]},
+            "sys": {"id": "sys", "parent": "root", "children": ["u1"],
+                    "message": {"id": "sys", "author": {"role": "system"}, "create_time": null,
+                                "content": {"content_type": "text", "parts": [""]},
+                                "metadata": {"is_visually_hidden_from_conversation": true}}},
+            "u1": {"id": "u1", "parent": "sys", "children": ["a1-old", "a1"],
+                   "message": {"id": "u1", "author": {"role": "user"}, "create_time": 1714557601.0,
+                               "content": {"content_type": "text", "parts": ["Sort 3, 1, 2"]}}},
+            "a1-old": {"id": "a1-old", "parent": "u1", "children": [],
+                       "message": {"id": "a1-old", "author": {"role": "assistant"}, "create_time": 1714557602.0,
+                                   "content": {"content_type": "text", "parts": ["Discarded answer"]}}},
+            "a1": {"id": "a1", "parent": "u1", "children": ["code"],
+                   "message": {"id": "a1", "author": {"role": "assistant"}, "create_time": 1714557603.0,
+                               "recipient": "all", "metadata": {"model_slug": "gpt-4o-mini"},
+                               "content": {"content_type": "text", "parts": ["Let me run that."]}}},
+            "code": {"id": "code", "parent": "a1", "children": ["out"],
+                     "message": {"id": "code", "author": {"role": "assistant"}, "create_time": 1714557604.0,
+                                 "recipient": "python",
+                                 "content": {"content_type": "code", "text": "sorted([3, 1, 2])"}}},
+            "out": {"id": "out", "parent": "code", "children": ["a1b"],
+                    "message": {"id": "out", "author": {"role": "tool", "name": "python"},
+                                "create_time": 1714557605.5,
+                                "content": {"content_type": "execution_output", "text": "[1, 2, 3]"}}},
+            "a1b": {"id": "a1b", "parent": "out", "children": ["u2"],
+                    "message": {"id": "a1b", "author": {"role": "assistant"}, "create_time": 1714557606.0,
+                                "recipient": "all",
+                                "content": {"content_type": "text", "parts": ["1, 2, 3"]}}},
+            "u2": {"id": "u2", "parent": "a1b", "children": ["a2"],
+                   "message": {"id": "u2", "author": {"role": "user"}, "create_time": 1714557610.0,
+                               "content": {"content_type": "multimodal_text",
+                                           "parts": [{"content_type": "image_asset_pointer"}, "Thanks!"]}}},
+            "a2": {"id": "a2", "parent": "u2", "children": [],
+                   "message": {"id": "a2", "author": {"role": "user"}, "create_time": 1714557611.0,
+                               "content": {"content_type": "text", "parts": ["Hello?"]}}}
+         }},
+        {"title": "Empty", "create_time": 1714557000.0, "mapping": {}},
+        {"title": "Broken"}
+    ]"#;
+
+    #[test]
+    fn test_parse_current_branch() {
+        let (traces, skipped) = ChatGptImporter::parse(EXPORT).unwrap();
+        // "Thanks!" and "Hello?" went unanswered, "Empty" has no messages and
+        // "Broken" no creation time
+        assert_eq!(skipped, 4);
+        assert_eq!(traces.len(), 1);
+        let trace = &traces[0];
+        assert_eq!(trace.id, "conv-1");
+        assert_eq!(trace.name.as_deref(), Some("Sorting numbers"));
+        assert_eq!(trace.calls.len(), 1);
+
+        let call = &trace.calls[0];
+        assert_eq!(call.prompt, "Sort 3, 1, 2");
+        assert_eq!(call.response, "Let me run that.\n\n1, 2, 3");
+        assert_eq!(call.model.as_deref(), Some("gpt-4o-mini"));
+        assert_eq!(
+            call.ended_at.unwrap() - call.started_at,
+            chrono::Duration::seconds(5)
+        );
+
+        assert_eq!(trace.tools.len(), 1);
+        assert_eq!(trace.tools[0].name, "python");
+        assert_eq!(
+            trace.tools[0].input,
+            Value::String("sorted([3, 1, 2])".to_string())
+        );
+        assert_eq!(
+            trace.tools[0].output,
+            Some(Value::String("[1, 2, 3]".to_string()))
+        );
+        assert!(ChatGptImporter::parse("{\"mapping\": {}}").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_import_chatgpt_export() {
+        let dir = tempdir().unwrap();
+        let graph = AsyncMemoryGraph::open(Config::new(dir.path().join("graph")))
+            .await
+            .unwrap();
+        let path = dir.path().join(CONVERSATIONS_FILE);
+        std::fs::write(&path, EXPORT).unwrap();
+
+        let report = ChatGptImporter::new()
+            .with_tag("personal")
+            .import_file(&graph, &path)
+            .await
+            .unwrap();
+        assert_eq!(
+            report,
+            ImportReport {
+                sessions: 1,
+                prompts: 1,
+                responses: 1,
+                tool_invocations: 1,
+                skipped: 4,
+            }
+        );
+
+        let session = &graph.list_sessions().await.unwrap()[0];
+        assert_eq!(session.title.as_deref(), Some("Sorting numbers"));
+        assert_eq!(session.tags, vec!["personal"]);
+        assert_eq!(
+            session.metadata.get(SOURCE_KEY).map(String::as_str),
+            Some("chatgpt")
+        );
+        assert_eq!(session.created_at.timestamp(), 1_714_557_600);
+
+        let transcript = graph.transcript(session.id).await.unwrap();
+        let turn = &transcript.turns[0];
+        assert_eq!(turn.prompt.timestamp.timestamp(), 1_714_557_601);
+        assert_eq!(
+            turn.prompt
+                .metadata
+                .custom
+                .get(SOURCE_ID_KEY)
+                .map(String::as_str),
+            Some("u1")
+        );
+        assert_eq!(turn.responses[0].response.metadata.latency_ms, 5000);
+        assert_eq!(turn.responses[0].tools[0].duration_ms, 1500);
+    }
+
+    #[cfg(feature = "zip")]
+    #[tokio::test]
+    async fn test_import_export_zip() {
+        use std::io::Write;
+
+        let dir = tempdir().unwrap();
+        let graph = AsyncMemoryGraph::open(Config::new(dir.path().join("graph")))
+            .await
+            .unwrap();
+        let path = dir.path().join("export.zip");
+        let mut zip = zip::ZipWriter::new(std::fs::File::create(&path).unwrap());
+        let options = zip::write::SimpleFileOptions::default();
+        zip.start_file("chat.html", options).unwrap();
+        zip.write_all(b"<html></html>").unwrap();
+        zip.start_file(CONVERSATIONS_FILE, options).unwrap();
+        zip.write_all(EXPORT.as_bytes()).unwrap();
+        zip.finish().unwrap();
+
+        let report = ChatGptImporter::new()
+            .import_file(&graph, &path)
+            .await
+            .unwrap();
+        assert_eq!(report.sessions, 1);
+        assert_eq!(report.prompts, 1);
+    }
+}
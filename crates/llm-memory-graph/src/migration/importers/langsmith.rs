@@ -0,0 +1,344 @@
+//! LangSmith run exports
+//!
+//! Accepts the runs of one or more traces as a JSON array, JSON Lines, an
+//! object with a `runs` array, or run trees nested through `child_runs`. Runs
+//! are grouped into traces by `trace_id`, falling back to their root run;
+//! `llm` and `chat_model` runs become prompt/response pairs and `tool` runs
+//! become tool invocations. Chain, retriever, parser and other runs only give
+//! the trace its structure and are not imported themselves.
+
+use super::{
+    find_string, parse_records, prompt_text, response_text, timestamp, token_usage, write_traces,
+    ImportReport, ImportedCall, ImportedTool, ImportedTrace, MODEL_KEYS,
+};
+use crate::engine::AsyncMemoryGraph;
+use crate::Result;
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+/// Value of [`SOURCE_KEY`](super::SOURCE_KEY) on sessions imported from LangSmith
+const SOURCE: &str = "langsmith";
+
+/// Imports LangSmith run exports, one session per trace
+#[derive(Debug, Clone, Default)]
+pub struct LangSmithImporter {
+    tags: Vec<String>,
+}
+
+impl LangSmithImporter {
+    /// Importer that keeps each trace's own tags
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Also tag every imported session with `tag`
+    pub fn with_tag(mut self, tag: impl Into<String>) -> Self {
+        self.tags.push(tag.into());
+        self
+    }
+
+    /// Import the runs in `export`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `export` is not JSON or JSON Lines, or if writing
+    /// to the graph fails.
+    pub async fn import(&self, graph: &AsyncMemoryGraph, export: &str) -> Result<ImportReport> {
+        let (traces, skipped) = Self::parse(export)?;
+        let report = ImportReport {
+            skipped,
+            ..ImportReport::default()
+        };
+        write_traces(graph, SOURCE, traces, &self.tags, report).await
+    }
+
+    /// Import the runs in the file at `path`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read, or as for
+    /// [`import`](Self::import).
+    pub async fn import_file(
+        &self,
+        graph: &AsyncMemoryGraph,
+        path: impl AsRef<Path>,
+    ) -> Result<ImportReport> {
+        let export = tokio::fs::read_to_string(path).await?;
+        self.import(graph, &export).await
+    }
+
+    /// Group the runs in `export` into traces; also returns the number of runs
+    /// that could not be used
+    pub(crate) fn parse(export: &str) -> Result<(Vec<ImportedTrace>, usize)> {
+        let mut runs = Vec::new();
+        for record in parse_records(export)? {
+            match record.get("runs").and_then(Value::as_array) {
+                Some(nested) => nested.iter().for_each(|run| flatten(run, None, &mut runs)),
+                None => flatten(&record, None, &mut runs),
+            }
+        }
+
+        let mut skipped = 0;
+        let mut by_id: HashMap<String, &Value> = HashMap::new();
+        for run in &runs {
+            match string(run, "id") {
+                Some(id) => {
+                    by_id.insert(id, run);
+                }
+                None => skipped += 1,
+            }
+        }
+
+        let mut traces: BTreeMap<String, Trace> = BTreeMap::new();
+        for run in by_id.values() {
+            let trace_id = string(run, "trace_id").unwrap_or_else(|| root_id(run, &by_id));
+            let trace = traces.entry(trace_id.clone()).or_default();
+            let Some(started_at) = run.get("start_time").and_then(timestamp) else {
+                skipped += 1;
+                continue;
+            };
+            if string(run, "parent_run_id").is_none() || string(run, "id") == Some(trace_id) {
+                trace.root = Some(run);
+            }
+            trace.earliest = Some(trace.earliest.map_or(started_at, |t| t.min(started_at)));
+
+            let id = string(run, "id").unwrap_or_default();
+            let ended_at = run.get("end_time").and_then(timestamp);
+            let error = string(run, "error");
+            let inputs = run.get("inputs").unwrap_or(&Value::Null);
+            let outputs = run.get("outputs").filter(|outputs| !outputs.is_null());
+            match run.get("run_type").and_then(Value::as_str) {
+                Some("llm" | "chat_model") => {
+                    let prompt = prompt_text(inputs);
+                    let response = outputs
+                        .and_then(response_text)
+                        .or_else(|| error.as_ref().map(|_| String::new()));
+                    let (Some(prompt), Some(response)) = (prompt, response) else {
+                        skipped += 1;
+                        continue;
+                    };
+                    trace.calls.push(ImportedCall {
+                        id,
+                        prompt,
+                        response,
+                        model: find_string(&[run.get("extra"), outputs], MODEL_KEYS),
+                        usage: token_usage(&[Some(run), outputs]),
+                        started_at,
+                        ended_at,
+                        error,
+                    });
+                }
+                Some("tool") => trace.tools.push(ImportedTool {
+                    id,
+                    name: string(run, "name").unwrap_or_else(|| "tool".to_string()),
+                    input: unwrap_single(inputs, "input"),
+                    output: outputs.map(|outputs| unwrap_single(outputs, "output")),
+                    error,
+                    started_at,
+                    ended_at,
+                }),
+                _ => {}
+            }
+        }
+
+        let mut traces: Vec<ImportedTrace> = traces
+            .into_iter()
+            .filter_map(|(id, trace)| {
+                let started_at = trace.earliest?;
+                let root = trace.root;
+                Some(ImportedTrace {
+                    id,
+                    name: root.and_then(|root| string(root, "name")),
+                    started_at: root
+                        .and_then(|root| root.get("start_time").and_then(timestamp))
+                        .unwrap_or(started_at),
+                    tags: root
+                        .and_then(|root| root.get("tags")?.as_array())
+                        .map(|tags| {
+                            tags.iter()
+                                .filter_map(|tag| tag.as_str().map(str::to_string))
+                                .collect()
+                        })
+                        .unwrap_or_default(),
+                    calls: trace.calls,
+                    tools: trace.tools,
+                })
+            })
+            .collect();
+        traces.sort_by_key(|trace| trace.started_at);
+        Ok((traces, skipped))
+    }
+}
+
+/// Runs of one trace gathered while parsing
+#[derive(Default)]
+struct Trace<'a> {
+    root: Option<&'a Value>,
+    earliest: Option<DateTime<Utc>>,
+    calls: Vec<ImportedCall>,
+    tools: Vec<ImportedTool>,
+}
+
+/// Collect `run` and the runs nested in its `child_runs`, filling in missing
+/// parent ids from the nesting
+fn flatten(run: &Value, parent: Option<&str>, runs: &mut Vec<Value>) {
+    let mut flat = run.clone();
+    let children = flat
+        .as_object_mut()
+        .and_then(|map| map.remove("child_runs"))
+        .unwrap_or(Value::Null);
+    if string(&flat, "parent_run_id").is_none() {
+        if let (Some(parent), Some(map)) = (parent, flat.as_object_mut()) {
+            map.insert(
+                "parent_run_id".to_string(),
+                Value::String(parent.to_string()),
+            );
+        }
+    }
+    let id = string(&flat, "id");
+    runs.push(flat);
+    for child in children.as_array().into_iter().flatten() {
+        flatten(child, id.as_deref(), runs);
+    }
+}
+
+/// Id of the topmost ancestor of `run` present in the export
+fn root_id(run: &Value, by_id: &HashMap<String, &Value>) -> String {
+    let mut current = run;
+    for _ in 0..by_id.len() {
+        match string(current, "parent_run_id").and_then(|parent| by_id.get(&parent)) {
+            Some(parent) => current = parent,
+            None => break,
+        }
+    }
+    string(current, "parent_run_id")
+        .filter(|parent| !by_id.contains_key(parent))
+        .or_else(|| string(current, "id"))
+        .unwrap_or_default()
+}
+
+/// `value[key]` when it is a non-empty string, or a number rendered as one
+fn string(value: &Value, key: &str) -> Option<String> {
+    match value.get(key)? {
+        Value::String(s) if !s.is_empty() => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+/// The value under `key` if it is the object's only field, else the object
+fn unwrap_single(value: &Value, key: &str) -> Value {
+    match value.as_object() {
+        Some(map) if map.len() == 1 && map.contains_key(key) => map[key].clone(),
+        _ => value.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::migration::importers::SOURCE_ID_KEY;
+    use crate::{Config, Node};
+    use tempfile::tempdir;
+
+    const EXPORT: &str = r#"[
+        {"id": "root", "trace_id": "root", "name": "Weather agent", "run_type": "chain",
+         "start_time": "2024-05-01T10:00:00.000000", "end_time": "2024-05-01T10:00:05.000000",
+         "tags": ["prod"], "inputs": {"input": "Weather in Paris?"}, "outputs": {"output": "Sunny"}},
+        {"id": "llm-1", "trace_id": "root", "parent_run_id": "root", "run_type": "llm",
+         "start_time": "2024-05-01T10:00:00.100000", "end_time": "2024-05-01T10:00:01.100000",
+         "extra": {"invocation_params": {"model_name": "gpt-4o"}},
+         "inputs": {"messages": [[{"role": "user", "content": "Weather in Paris?"}]]},
+         "outputs": {"generations": [[{"text": "Calling the weather tool."}]],
+                     "llm_output": {"token_usage": {"prompt_tokens": 20, "completion_tokens": 6}}}},
+        {"id": "tool-1", "trace_id": "root", "parent_run_id": "root", "run_type": "tool",
+         "name": "get_weather", "start_time": "2024-05-01T10:00:01.200000Z",
+         "end_time": "2024-05-01T10:00:01.450000Z",
+         "inputs": {"input": {"city": "Paris"}}, "outputs": {"output": "Sunny, 21C"}},
+        {"id": "llm-2", "trace_id": "root", "parent_run_id": "root", "run_type": "llm",
+         "start_time": "2024-05-01T10:00:02Z", "end_time": "2024-05-01T10:00:03Z",
+         "inputs": {"prompts": ["Summarize: Sunny, 21C"]}, "outputs": {"generations": [[{"text": "Sunny"}]]}},
+        {"id": "orphan", "run_type": "llm", "start_time": "2024-05-01T11:00:00Z",
+         "inputs": {"prompts": ["Still running"]}, "outputs": null}
+    ]"#;
+
+    #[tokio::test]
+    async fn test_import_langsmith_runs() {
+        let dir = tempdir().unwrap();
+        let graph = AsyncMemoryGraph::open(Config::new(dir.path()))
+            .await
+            .unwrap();
+
+        let report = LangSmithImporter::new()
+            .with_tag("imported")
+            .import(&graph, EXPORT)
+            .await
+            .unwrap();
+        assert_eq!(
+            report,
+            ImportReport {
+                sessions: 1,
+                prompts: 2,
+                responses: 2,
+                tool_invocations: 1,
+                skipped: 1,
+            }
+        );
+
+        let sessions = graph.list_sessions().await.unwrap();
+        assert_eq!(sessions.len(), 1);
+        let session = &sessions[0];
+        assert_eq!(session.title.as_deref(), Some("Weather agent"));
+        assert_eq!(session.tags, vec!["prod", "imported"]);
+
+        let transcript = graph.transcript(session.id).await.unwrap();
+        assert_eq!(transcript.turns.len(), 2);
+        let first = &transcript.turns[0];
+        assert_eq!(first.prompt.content, "Weather in Paris?");
+        assert_eq!(
+            first
+                .prompt
+                .metadata
+                .custom
+                .get(SOURCE_ID_KEY)
+                .map(String::as_str),
+            Some("llm-1")
+        );
+        let reply = &first.responses[0];
+        assert_eq!(reply.response.metadata.model, "gpt-4o");
+        assert_eq!(reply.response.metadata.latency_ms, 1000);
+        assert_eq!(reply.response.usage.total_tokens, 26);
+        assert_eq!(reply.tools.len(), 1);
+        assert_eq!(reply.tools[0].tool_name, "get_weather");
+        assert_eq!(reply.tools[0].parameters["city"], "Paris");
+        assert_eq!(reply.tools[0].duration_ms, 250);
+        assert_eq!(transcript.turns[1].prompt.content, "Summarize: Sunny, 21C");
+
+        let nodes = graph.get_session_nodes(&session.id).await.unwrap();
+        assert_eq!(
+            nodes
+                .iter()
+                .filter(|n| matches!(n, Node::Response(_)))
+                .count(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_parse_nested_run_tree() {
+        let export = r#"{"id": "r", "name": "QA", "run_type": "chain",
+            "start_time": "2024-05-01T10:00:00Z",
+            "child_runs": [{"id": "c", "run_type": "chat_model",
+                "start_time": "2024-05-01T10:00:01Z",
+                "inputs": {"messages": [{"role": "user", "content": "Hi"}]},
+                "outputs": {"output": "Hello"}}]}"#;
+        let (traces, skipped) = LangSmithImporter::parse(export).unwrap();
+        assert_eq!(skipped, 0);
+        assert_eq!(traces.len(), 1);
+        assert_eq!(traces[0].id, "r");
+        assert_eq!(traces[0].name.as_deref(), Some("QA"));
+        assert_eq!(traces[0].calls[0].response, "Hello");
+    }
+}
@@ -0,0 +1,481 @@
+//! Importers for traces exported by LLM observability tools
+//!
+//! [`LangSmithImporter`] reads LangSmith run exports, [`WandbImporter`]
+//! reads Weights & Biases trace trees and [`ChatGptImporter`] reads the
+//! conversations in a ChatGPT data export. All write each trace as one
+//! session:
+//!
+//! - the trace (a LangSmith root run, a W&B root span, a ChatGPT
+//!   conversation) becomes a [`ConversationSession`] titled after it
+//! - every LLM call becomes a prompt and its response
+//! - every tool call becomes a [`ToolInvocation`] attached to the response of
+//!   the LLM call that started before it
+//!
+//! Original timestamps, models, token usage and latencies are kept, and the
+//! id of the source record is stored in metadata under [`SOURCE_ID_KEY`].
+//! Records the importer cannot interpret, and tool calls made before any LLM
+//! call, are counted in [`ImportReport::skipped`] rather than failing the
+//! import.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use llm_memory_graph::engine::AsyncMemoryGraph;
+//! use llm_memory_graph::migration::importers::LangSmithImporter;
+//! use llm_memory_graph::Config;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let graph = AsyncMemoryGraph::open(Config::default()).await?;
+//! let report = LangSmithImporter::new()
+//!     .with_tag("langsmith")
+//!     .import_file(&graph, "runs.jsonl")
+//!     .await?;
+//! println!("{} sessions, {} prompts", report.sessions, report.prompts);
+//! # Ok(())
+//! # }
+//! ```
+
+mod chatgpt;
+mod langsmith;
+mod wandb;
+
+pub use chatgpt::{ChatGptImporter, CONVERSATIONS_FILE};
+pub use langsmith::LangSmithImporter;
+pub use wandb::WandbImporter;
+
+use crate::engine::AsyncMemoryGraph;
+use crate::{
+    ConversationSession, Edge, EdgeType, Error, Node, PromptNode, ResponseNode, Result, TokenUsage,
+    ToolInvocation,
+};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Session metadata key naming the tool an import came from
+pub const SOURCE_KEY: &str = "import_source";
+
+/// Metadata key holding the id of the record a node was imported from
+pub const SOURCE_ID_KEY: &str = "import_id";
+
+/// What an import wrote
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImportReport {
+    /// Sessions created, one per trace
+    pub sessions: usize,
+    /// Prompts written
+    pub prompts: usize,
+    /// Responses written
+    pub responses: usize,
+    /// Tool invocations written
+    pub tool_invocations: usize,
+    /// Records left out because they could not be mapped
+    pub skipped: usize,
+}
+
+/// A trace read from an export, ready to be written as a session
+#[derive(Debug, Clone)]
+pub(crate) struct ImportedTrace {
+    pub id: String,
+    pub name: Option<String>,
+    pub started_at: DateTime<Utc>,
+    pub tags: Vec<String>,
+    pub calls: Vec<ImportedCall>,
+    pub tools: Vec<ImportedTool>,
+}
+
+/// One LLM call: a prompt and the response to it
+#[derive(Debug, Clone)]
+pub(crate) struct ImportedCall {
+    pub id: String,
+    pub prompt: String,
+    pub response: String,
+    pub model: Option<String>,
+    pub usage: TokenUsage,
+    pub started_at: DateTime<Utc>,
+    pub ended_at: Option<DateTime<Utc>>,
+    pub error: Option<String>,
+}
+
+/// One tool call
+#[derive(Debug, Clone)]
+pub(crate) struct ImportedTool {
+    pub id: String,
+    pub name: String,
+    pub input: Value,
+    pub output: Option<Value>,
+    pub error: Option<String>,
+    pub started_at: DateTime<Utc>,
+    pub ended_at: Option<DateTime<Utc>>,
+}
+
+/// Write parsed traces to `graph`, one session per trace with at least one
+/// LLM call
+pub(crate) async fn write_traces(
+    graph: &AsyncMemoryGraph,
+    source: &str,
+    traces: Vec<ImportedTrace>,
+    tags: &[String],
+    mut report: ImportReport,
+) -> Result<ImportReport> {
+    for mut trace in traces {
+        if trace.calls.is_empty() {
+            report.skipped += trace.tools.len();
+            continue;
+        }
+        trace.calls.sort_by_key(|call| call.started_at);
+        trace.tools.sort_by_key(|tool| tool.started_at);
+
+        let mut session = ConversationSession::new();
+        session.created_at = trace.started_at;
+        session.updated_at = trace
+            .calls
+            .iter()
+            .filter_map(|call| call.ended_at)
+            .chain(trace.tools.iter().filter_map(|tool| tool.ended_at))
+            .max()
+            .unwrap_or(trace.started_at)
+            .max(trace.started_at);
+        session.title = trace.name;
+        session
+            .metadata
+            .insert(SOURCE_KEY.to_string(), source.to_string());
+        session.metadata.insert(SOURCE_ID_KEY.to_string(), trace.id);
+        session.tags = trace.tags;
+        for tag in tags {
+            if !session.tags.contains(tag) {
+                session.tags.push(tag.clone());
+            }
+        }
+
+        let mut nodes = vec![Node::Session(session.clone())];
+        let mut edges = Vec::new();
+        let mut calls = Vec::with_capacity(trace.calls.len());
+        for call in trace.calls {
+            let mut prompt = PromptNode::new(session.id, call.prompt);
+            prompt.timestamp = call.started_at;
+            if let Some(model) = &call.model {
+                prompt.metadata.model.clone_from(model);
+            }
+            prompt
+                .metadata
+                .custom
+                .insert(SOURCE_ID_KEY.to_string(), call.id.clone());
+
+            let mut response = ResponseNode::new(prompt.id, call.response, call.usage);
+            response.timestamp = call.ended_at.unwrap_or(call.started_at);
+            if let Some(model) = call.model {
+                response.metadata.model = model;
+            }
+            response.metadata.latency_ms = latency_ms(call.started_at, call.ended_at);
+            if let Some(error) = call.error {
+                response.metadata.finish_reason = "error".to_string();
+                response.metadata.custom.insert("error".to_string(), error);
+            }
+            response
+                .metadata
+                .custom
+                .insert(SOURCE_ID_KEY.to_string(), call.id);
+
+            edges.push(Edge::new(prompt.id, session.node_id, EdgeType::PartOf));
+            edges.push(Edge::new(response.id, prompt.id, EdgeType::RespondsTo));
+            calls.push((call.started_at, response.id));
+            nodes.push(Node::Prompt(prompt));
+            nodes.push(Node::Response(response));
+        }
+        report.sessions += 1;
+        report.prompts += calls.len();
+        report.responses += calls.len();
+
+        for tool in trace.tools {
+            let Some(&(_, response_id)) = calls
+                .iter()
+                .rev()
+                .find(|(started_at, _)| *started_at <= tool.started_at)
+            else {
+                report.skipped += 1;
+                continue;
+            };
+            let mut invocation = ToolInvocation::new(response_id, tool.name, tool.input);
+            invocation.timestamp = tool.started_at;
+            invocation.duration_ms = latency_ms(tool.started_at, tool.ended_at);
+            invocation.result = tool.output;
+            invocation.success = tool.error.is_none();
+            invocation.error = tool.error;
+            invocation
+                .metadata
+                .insert(SOURCE_ID_KEY.to_string(), tool.id);
+
+            edges.push(Edge::new(response_id, invocation.id, EdgeType::Invokes));
+            nodes.push(Node::ToolInvocation(invocation));
+            report.tool_invocations += 1;
+        }
+
+        graph.store_nodes_batch(nodes).await?;
+        graph.store_edges_batch(edges).await?;
+    }
+    Ok(report)
+}
+
+fn latency_ms(started_at: DateTime<Utc>, ended_at: Option<DateTime<Utc>>) -> u64 {
+    ended_at
+        .and_then(|ended_at| (ended_at - started_at).num_milliseconds().try_into().ok())
+        .unwrap_or(0)
+}
+
+/// Split an export into records: a JSON array, a single JSON value, or JSON
+/// Lines
+pub(crate) fn parse_records(export: &str) -> Result<Vec<Value>> {
+    match serde_json::from_str::<Value>(export) {
+        Ok(Value::Array(records)) => Ok(records),
+        Ok(record) => Ok(vec![record]),
+        Err(_) => export
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .enumerate()
+            .map(|(n, line)| {
+                serde_json::from_str(line).map_err(|e| {
+                    Error::MigrationError(format!("invalid JSON on record {}: {e}", n + 1))
+                })
+            })
+            .collect(),
+    }
+}
+
+/// Parse an RFC 3339 or naive ISO 8601 string (taken as UTC), or a number of
+/// seconds or milliseconds since the epoch
+pub(crate) fn timestamp(value: &Value) -> Option<DateTime<Utc>> {
+    match value {
+        Value::String(s) => DateTime::parse_from_rfc3339(s)
+            .map(|t| t.with_timezone(&Utc))
+            .ok()
+            .or_else(|| {
+                NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S%.f")
+                    .ok()
+                    .map(|t| t.and_utc())
+            }),
+        Value::Number(n) => {
+            let n = n.as_f64()?;
+            #[allow(clippy::cast_possible_truncation)]
+            let millis = if n.abs() > 1e11 { n } else { n * 1000.0 } as i64;
+            DateTime::from_timestamp_millis(millis)
+        }
+        _ => None,
+    }
+}
+
+/// Keys under which inputs commonly carry the prompt text
+const PROMPT_KEYS: &[&str] = &[
+    "prompt", "prompts", "input", "query", "question", "text", "content",
+];
+
+/// Keys under which outputs commonly carry the completion text
+const RESPONSE_KEYS: &[&str] = &[
+    "output",
+    "response",
+    "text",
+    "answer",
+    "content",
+    "completion",
+    "result",
+    "message",
+    "kwargs",
+];
+
+/// The prompt of an LLM call: its last user message, or the first prompt-like
+/// field of its inputs
+pub(crate) fn prompt_text(inputs: &Value) -> Option<String> {
+    match inputs {
+        Value::String(s) => Some(s.clone()),
+        Value::Array(items) => messages_text(inputs).or_else(|| items.iter().find_map(prompt_text)),
+        Value::Object(map) => map.get("messages").and_then(messages_text).or_else(|| {
+            PROMPT_KEYS
+                .iter()
+                .find_map(|key| map.get(*key).and_then(prompt_text))
+        }),
+        _ => None,
+    }
+}
+
+/// The completion of an LLM call
+pub(crate) fn response_text(outputs: &Value) -> Option<String> {
+    match outputs {
+        Value::String(s) => Some(s.clone()),
+        Value::Array(items) => items.iter().find_map(response_text),
+        Value::Object(map) => ["generations", "choices"]
+            .iter()
+            .filter_map(|key| map.get(*key))
+            .find_map(|candidates| {
+                let first = flatten(candidates).into_iter().next()?;
+                first
+                    .get("text")
+                    .and_then(Value::as_str)
+                    .filter(|text| !text.is_empty())
+                    .map(str::to_string)
+                    .or_else(|| first.get("message").and_then(message_content))
+            })
+            .or_else(|| {
+                RESPONSE_KEYS
+                    .iter()
+                    .find_map(|key| map.get(*key).and_then(response_text))
+            }),
+        _ => None,
+    }
+}
+
+/// Content of the last user message in a (possibly nested) message list, or
+/// of the last message if none is from the user
+fn messages_text(messages: &Value) -> Option<String> {
+    let messages: Vec<&Value> = flatten(messages)
+        .into_iter()
+        .filter(|m| m.is_object())
+        .collect();
+    messages
+        .iter()
+        .rev()
+        .find(|m| message_role(m).is_some_and(|role| role == "user" || role.contains("human")))
+        .or_else(|| messages.last())
+        .and_then(|m| message_content(m))
+}
+
+/// Role of a chat message in OpenAI (`role`) or LangChain (`type`, or the
+/// class name ending its serialized `id`) form, lowercased
+fn message_role(message: &Value) -> Option<String> {
+    let kwargs = message.get("kwargs");
+    message
+        .get("role")
+        .or_else(|| message.get("type"))
+        .or_else(|| kwargs.and_then(|k| k.get("type")))
+        .and_then(Value::as_str)
+        .filter(|role| *role != "constructor")
+        .or_else(|| message.get("id")?.as_array()?.last()?.as_str())
+        .map(str::to_ascii_lowercase)
+}
+
+/// Text of a chat message, joining the text parts of multi-part content
+fn message_content(message: &Value) -> Option<String> {
+    let content = message
+        .get("content")
+        .or_else(|| message.get("kwargs")?.get("content"))?;
+    match content {
+        Value::String(s) => Some(s.clone()),
+        Value::Array(parts) => {
+            let text: Vec<&str> = parts
+                .iter()
+                .filter_map(|part| part.as_str().or_else(|| part.get("text")?.as_str()))
+                .collect();
+            (!text.is_empty()).then(|| text.join("\n"))
+        }
+        _ => None,
+    }
+}
+
+fn flatten(value: &Value) -> Vec<&Value> {
+    match value {
+        Value::Array(items) => items.iter().flat_map(flatten).collect(),
+        other => vec![other],
+    }
+}
+
+/// How deep to search nested objects for usage and model fields
+const SEARCH_DEPTH: usize = 5;
+
+/// Token counts found in the first of `sources` that reports them, in OpenAI
+/// (`prompt_tokens`) or Anthropic/LangChain (`input_tokens`) naming
+pub(crate) fn token_usage(sources: &[Option<&Value>]) -> TokenUsage {
+    sources
+        .iter()
+        .flatten()
+        .find_map(|source| find_usage(source, SEARCH_DEPTH))
+        .unwrap_or(TokenUsage::new(0, 0))
+}
+
+fn find_usage(value: &Value, depth: usize) -> Option<TokenUsage> {
+    let count = |keys: [&str; 2]| {
+        keys.iter()
+            .find_map(|key| value.get(*key)?.as_u64())
+            .and_then(|n| u32::try_from(n).ok())
+    };
+    let prompt = count(["prompt_tokens", "input_tokens"]);
+    let completion = count(["completion_tokens", "output_tokens"]);
+    if prompt.is_some() || completion.is_some() {
+        return Some(TokenUsage::new(
+            prompt.unwrap_or(0),
+            completion.unwrap_or(0),
+        ));
+    }
+    if depth == 0 {
+        return None;
+    }
+    match value {
+        Value::Object(map) => map.values().find_map(|v| find_usage(v, depth - 1)),
+        Value::Array(items) => items.iter().find_map(|v| find_usage(v, depth - 1)),
+        _ => None,
+    }
+}
+
+/// The first string stored under one of `keys` in `sources`, searching nested
+/// objects
+pub(crate) fn find_string(sources: &[Option<&Value>], keys: &[&str]) -> Option<String> {
+    fn search(value: &Value, keys: &[&str], depth: usize) -> Option<String> {
+        let map = value.as_object()?;
+        keys.iter()
+            .find_map(|key| map.get(*key)?.as_str().filter(|s| !s.is_empty()))
+            .map(str::to_string)
+            .or_else(|| {
+                (depth > 0)
+                    .then(|| map.values().find_map(|v| search(v, keys, depth - 1)))
+                    .flatten()
+            })
+    }
+    sources
+        .iter()
+        .flatten()
+        .find_map(|source| search(source, keys, SEARCH_DEPTH))
+}
+
+/// Keys under which traces record the model name
+pub(crate) const MODEL_KEYS: &[&str] = &["model_name", "model", "ls_model_name"];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_extract_text_and_usage() {
+        let inputs = json!({"messages": [[
+            {"lc": 1, "type": "constructor", "id": ["langchain", "schema", "messages", "SystemMessage"],
+             "kwargs": {"content": "Be brief."}},
+            {"lc": 1, "type": "constructor", "id": ["langchain", "schema", "messages", "HumanMessage"],
+             "kwargs": {"content": "What is Rust?"}}
+        ]]});
+        assert_eq!(prompt_text(&inputs).as_deref(), Some("What is Rust?"));
+        assert_eq!(
+            prompt_text(&json!({"prompts": ["Hello"]})).as_deref(),
+            Some("Hello")
+        );
+
+        let outputs = json!({
+            "generations": [[{"text": "", "message": {"kwargs": {"content": "A language."}}}]],
+            "llm_output": {"token_usage": {"prompt_tokens": 12, "completion_tokens": 3}}
+        });
+        assert_eq!(response_text(&outputs).as_deref(), Some("A language."));
+        assert_eq!(token_usage(&[None, Some(&outputs)]).total_tokens, 15);
+        assert_eq!(
+            response_text(
+                &json!({"choices": [{"message": {"role": "assistant", "content": "Hi"}}]})
+            )
+            .as_deref(),
+            Some("Hi")
+        );
+
+        assert_eq!(
+            timestamp(&json!("2024-05-01T10:00:00.5")),
+            timestamp(&json!(1_714_557_600_500_i64))
+        );
+        assert!(parse_records("{\"id\": 1}\n{\"id\": 2}\n").unwrap().len() == 2);
+        assert!(parse_records("{not json").is_err());
+    }
+}
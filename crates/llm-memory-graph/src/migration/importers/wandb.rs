@@ -0,0 +1,285 @@
+//! Weights & Biases trace trees
+//!
+//! Accepts traces logged with `wandb.Trace` (`wb_trace_tree` values, whose
+//! root span may be embedded as a JSON string under `root_span_dumps`) or bare
+//! root spans, as a JSON array, JSON Lines or a single value. Each root span
+//! becomes one trace; every `results` entry of an `LLM` span becomes a
+//! prompt/response pair and every `TOOL` span becomes a tool invocation.
+//! `CHAIN` and `AGENT` spans only give the trace its structure.
+
+use super::{
+    find_string, parse_records, prompt_text, response_text, timestamp, token_usage, write_traces,
+    ImportReport, ImportedCall, ImportedTool, ImportedTrace, MODEL_KEYS,
+};
+use crate::engine::AsyncMemoryGraph;
+use crate::Result;
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use std::path::Path;
+
+/// Value of [`SOURCE_KEY`](super::SOURCE_KEY) on sessions imported from W&B
+const SOURCE: &str = "wandb";
+
+/// Imports Weights & Biases trace trees, one session per root span
+#[derive(Debug, Clone, Default)]
+pub struct WandbImporter {
+    tags: Vec<String>,
+}
+
+impl WandbImporter {
+    /// Importer with no extra session tags
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Tag every imported session with `tag`
+    pub fn with_tag(mut self, tag: impl Into<String>) -> Self {
+        self.tags.push(tag.into());
+        self
+    }
+
+    /// Import the traces in `export`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `export` is not JSON or JSON Lines, or if writing
+    /// to the graph fails.
+    pub async fn import(&self, graph: &AsyncMemoryGraph, export: &str) -> Result<ImportReport> {
+        let (traces, skipped) = Self::parse(export)?;
+        let report = ImportReport {
+            skipped,
+            ..ImportReport::default()
+        };
+        write_traces(graph, SOURCE, traces, &self.tags, report).await
+    }
+
+    /// Import the traces in the file at `path`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read, or as for
+    /// [`import`](Self::import).
+    pub async fn import_file(
+        &self,
+        graph: &AsyncMemoryGraph,
+        path: impl AsRef<Path>,
+    ) -> Result<ImportReport> {
+        let export = tokio::fs::read_to_string(path).await?;
+        self.import(graph, &export).await
+    }
+
+    /// Read the traces in `export`; also returns the number of records and
+    /// spans that could not be used
+    pub(crate) fn parse(export: &str) -> Result<(Vec<ImportedTrace>, usize)> {
+        let mut traces = Vec::new();
+        let mut skipped = 0;
+        for record in parse_records(export)? {
+            let Some(root) = root_span(&record) else {
+                skipped += 1;
+                continue;
+            };
+            let Some(started_at) = root.get("start_time_ms").and_then(timestamp) else {
+                skipped += 1;
+                continue;
+            };
+            let mut trace = ImportedTrace {
+                id: span_id(&root).unwrap_or_default(),
+                name: root.get("name").and_then(Value::as_str).map(str::to_string),
+                started_at,
+                tags: Vec::new(),
+                calls: Vec::new(),
+                tools: Vec::new(),
+            };
+            skipped += collect(&root, started_at, &mut trace);
+            traces.push(trace);
+        }
+        traces.sort_by_key(|trace| trace.started_at);
+        Ok((traces, skipped))
+    }
+}
+
+/// The root span of a trace tree record, or the record itself if it is a span
+fn root_span(record: &Value) -> Option<Value> {
+    if let Some(dumps) = record.get("root_span_dumps").and_then(Value::as_str) {
+        return serde_json::from_str(dumps).ok();
+    }
+    if let Some(root) = record.get("root_span").filter(|root| root.is_object()) {
+        return Some(root.clone());
+    }
+    ["span_id", "span_kind", "child_spans"]
+        .iter()
+        .any(|key| record.get(*key).is_some())
+        .then(|| record.clone())
+}
+
+/// Add `span` and its descendants to `trace`, returning how many spans were
+/// skipped; spans without a start time inherit `parent_start`
+fn collect(span: &Value, parent_start: DateTime<Utc>, trace: &mut ImportedTrace) -> usize {
+    let started_at = span
+        .get("start_time_ms")
+        .and_then(timestamp)
+        .unwrap_or(parent_start);
+    let ended_at = span.get("end_time_ms").and_then(timestamp);
+    let id = span_id(span).unwrap_or_default();
+    let error = span
+        .get("status_code")
+        .and_then(Value::as_str)
+        .filter(|code| code.eq_ignore_ascii_case("error"))
+        .map(|_| {
+            span.get("status_message")
+                .and_then(Value::as_str)
+                .unwrap_or("error")
+                .to_string()
+        });
+    let attributes = span.get("attributes");
+    let results: Vec<&Value> = span
+        .get("results")
+        .and_then(Value::as_array)
+        .map(|results| results.iter().collect())
+        .unwrap_or_default();
+
+    let mut skipped = 0;
+    match span
+        .get("span_kind")
+        .and_then(Value::as_str)
+        .map(str::to_ascii_uppercase)
+        .as_deref()
+    {
+        Some("LLM") => {
+            if results.is_empty() {
+                skipped += 1;
+            }
+            for (n, result) in results.iter().enumerate() {
+                let outputs = result.get("outputs").filter(|outputs| !outputs.is_null());
+                let prompt = result.get("inputs").and_then(prompt_text);
+                let response = outputs
+                    .and_then(response_text)
+                    .or_else(|| error.as_ref().map(|_| String::new()));
+                let (Some(prompt), Some(response)) = (prompt, response) else {
+                    skipped += 1;
+                    continue;
+                };
+                trace.calls.push(ImportedCall {
+                    id: if results.len() > 1 {
+                        format!("{id}#{n}")
+                    } else {
+                        id.clone()
+                    },
+                    prompt,
+                    response,
+                    model: find_string(&[attributes, outputs], MODEL_KEYS),
+                    usage: token_usage(&[attributes, outputs]),
+                    started_at,
+                    ended_at,
+                    error: error.clone(),
+                });
+            }
+        }
+        Some("TOOL") => {
+            let result = results.first();
+            trace.tools.push(ImportedTool {
+                id,
+                name: span
+                    .get("name")
+                    .and_then(Value::as_str)
+                    .unwrap_or("tool")
+                    .to_string(),
+                input: result
+                    .and_then(|result| result.get("inputs"))
+                    .cloned()
+                    .unwrap_or(Value::Null),
+                output: result
+                    .and_then(|result| result.get("outputs"))
+                    .filter(|outputs| !outputs.is_null())
+                    .cloned(),
+                error,
+                started_at,
+                ended_at,
+            });
+        }
+        _ => {}
+    }
+
+    for child in span
+        .get("child_spans")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        skipped += collect(child, started_at, trace);
+    }
+    skipped
+}
+
+fn span_id(span: &Value) -> Option<String> {
+    match span.get("span_id")? {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::migration::importers::SOURCE_KEY;
+    use crate::Config;
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    fn trace_tree() -> Value {
+        let root = json!({
+            "span_id": "agent", "name": "Math agent", "span_kind": "AGENT",
+            "start_time_ms": 1_714_557_600_000_i64, "end_time_ms": 1_714_557_603_000_i64,
+            "status_code": "SUCCESS",
+            "child_spans": [
+                {"span_id": "llm", "name": "OpenAI", "span_kind": "LLM",
+                 "start_time_ms": 1_714_557_600_100_i64, "end_time_ms": 1_714_557_600_900_i64,
+                 "attributes": {"model": "gpt-4o-mini",
+                                "token_usage": {"prompt_tokens": 9, "completion_tokens": 4}},
+                 "results": [{"inputs": {"query": "What is 2 + 3?"},
+                              "outputs": {"response": "Let me calculate."}}]},
+                {"span_id": "calc", "name": "calculator", "span_kind": "TOOL",
+                 "start_time_ms": 1_714_557_601_000_i64, "end_time_ms": 1_714_557_601_020_i64,
+                 "status_code": "ERROR", "status_message": "division by zero",
+                 "results": [{"inputs": {"expression": "2 + 3"}, "outputs": null}]}
+            ]
+        });
+        json!({"_type": "wb_trace_tree", "root_span_dumps": root.to_string()})
+    }
+
+    #[tokio::test]
+    async fn test_import_wandb_trace() {
+        let dir = tempdir().unwrap();
+        let graph = AsyncMemoryGraph::open(Config::new(dir.path()))
+            .await
+            .unwrap();
+        let export = format!("{}\n{}\n", trace_tree(), json!({"unrelated": true}));
+
+        let report = WandbImporter::new().import(&graph, &export).await.unwrap();
+        assert_eq!(report.sessions, 1);
+        assert_eq!(report.prompts, 1);
+        assert_eq!(report.tool_invocations, 1);
+        assert_eq!(report.skipped, 1);
+
+        let session = graph.list_sessions().await.unwrap().remove(0);
+        assert_eq!(session.title.as_deref(), Some("Math agent"));
+        assert_eq!(
+            session.metadata.get(SOURCE_KEY).map(String::as_str),
+            Some("wandb")
+        );
+
+        let transcript = graph.transcript(session.id).await.unwrap();
+        let turn = &transcript.turns[0];
+        assert_eq!(turn.prompt.content, "What is 2 + 3?");
+        let reply = &turn.responses[0];
+        assert_eq!(reply.response.content, "Let me calculate.");
+        assert_eq!(reply.response.metadata.model, "gpt-4o-mini");
+        assert_eq!(reply.response.usage.total_tokens, 13);
+        assert_eq!(reply.response.metadata.latency_ms, 800);
+        let tool = &reply.tools[0];
+        assert!(!tool.success);
+        assert_eq!(tool.error.as_deref(), Some("division by zero"));
+        assert_eq!(tool.parameters["expression"], "2 + 3");
+    }
+}
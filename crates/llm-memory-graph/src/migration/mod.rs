@@ -41,9 +41,16 @@
 //! - No data migration required
 //! - Can switch between sync and async at any time
 //! - Node IDs and edge IDs are compatible
+//!
+//! # Importing Existing Traces
+//!
+//! The [`importers`] module seeds a graph from observability exports, such as
+//! LangSmith runs or Weights & Biases traces, and from ChatGPT data exports.
+
+pub mod importers;
 
-use crate::Result;
 use crate::Config;
+use crate::Result;
 use crate::{AsyncMemoryGraph, MemoryGraph};
 
 /// Migration helper providing utilities for transitioning between sync and async APIs
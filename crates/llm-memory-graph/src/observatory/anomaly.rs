@@ -0,0 +1,566 @@
+//! Lightweight anomaly detection over graph activity
+//!
+//! An [`AnomalyDetector`] watches responses and tool results as they are
+//! written and applies a list of [`AnomalyRule`]s:
+//!
+//! - **Token spike**: a response uses many times more tokens than the mean of
+//!   the earlier responses in its session.
+//! - **Tool failure rate**: more than a threshold fraction of a tool's recent
+//!   invocations failed.
+//! - **Latency regression**: the mean reported latency of a model's recent
+//!   responses is many times that of the responses before them.
+//!
+//! The graph publishes every [`Anomaly`] found as an `AnomalyDetected` event
+//! and counts it in [`MemoryGraphMetrics`](super::MemoryGraphMetrics).
+//! [`PrometheusMetrics::record_anomaly`](super::PrometheusMetrics::record_anomaly)
+//! exports the counts as `memory_graph_anomalies_total`, labeled by rule, for
+//! alerts such as `increase(memory_graph_anomalies_total[5m]) > 0`.
+//!
+//! The failure rate and latency rules fire once when their condition starts to
+//! hold and again only after it has cleared, so a tool that keeps failing
+//! raises one anomaly rather than one per call. Token spikes are reported
+//! individually. State is kept in memory for at most [`MAX_TRACKED_KEYS`]
+//! sessions, tools or models per rule and is lost on restart.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use llm_memory_graph::engine::AsyncMemoryGraph;
+//! use llm_memory_graph::observatory::{AnomalyRule, InMemoryPublisher, ObservatoryConfig};
+//! use llm_memory_graph::Config;
+//! use std::sync::Arc;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let publisher = Arc::new(InMemoryPublisher::new());
+//! let graph = AsyncMemoryGraph::with_observatory(
+//!     Config::default(),
+//!     Some(publisher.clone()),
+//!     ObservatoryConfig::new()
+//!         .enabled()
+//!         .with_anomaly_rule(AnomalyRule::token_spike(5.0))
+//!         .with_anomaly_rule(AnomalyRule::tool_failure_rate(0.5))
+//!         .with_anomaly_rule(AnomalyRule::latency_regression(2.0)),
+//! )
+//! .await?;
+//!
+//! // ... use the graph ...
+//! let anomalies = publisher.get_events_by_type("anomaly_detected").await;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{Error, Result, SessionId, TokenUsage};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::hash::Hash;
+
+/// Most sessions, tools or models a rule keeps state for; past this an
+/// arbitrary one is forgotten to make room
+pub const MAX_TRACKED_KEYS: usize = 10_000;
+
+/// The kind of condition an [`AnomalyRule`] checks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnomalyKind {
+    /// A response used far more tokens than usual for its session
+    TokenSpike,
+    /// A tool failed too often
+    ToolFailureRate,
+    /// A model's responses became slower
+    LatencyRegression,
+}
+
+impl AnomalyKind {
+    /// Every kind, in declaration order
+    pub const ALL: [Self; 3] = [
+        Self::TokenSpike,
+        Self::ToolFailureRate,
+        Self::LatencyRegression,
+    ];
+
+    /// Name used in events and metric labels
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::TokenSpike => "token_spike",
+            Self::ToolFailureRate => "tool_failure_rate",
+            Self::LatencyRegression => "latency_regression",
+        }
+    }
+}
+
+impl fmt::Display for AnomalyKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A condition that marks activity as anomalous
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AnomalyRule {
+    /// A response's total tokens exceed `factor` times the mean of the
+    /// session's earlier responses
+    TokenSpike {
+        /// How many times the session mean counts as a spike
+        factor: f64,
+        /// Earlier responses the session needs before spikes are reported
+        min_responses: usize,
+        /// Responses using fewer tokens are never spikes
+        min_tokens: u32,
+    },
+    /// The fraction of a tool's last `window` invocations that failed exceeds
+    /// `threshold`
+    ToolFailureRate {
+        /// Failure rate above which the tool is reported, in `[0, 1)`
+        threshold: f64,
+        /// Invocations the rate is computed over
+        window: usize,
+        /// Invocations needed before the rate is checked
+        min_invocations: usize,
+    },
+    /// The mean latency of a model's last `recent` responses exceeds `factor`
+    /// times the mean of the `baseline` responses before them
+    ///
+    /// Responses that report no latency are ignored.
+    LatencyRegression {
+        /// How many times the baseline mean counts as a regression
+        factor: f64,
+        /// Responses in the baseline
+        baseline: usize,
+        /// Responses compared against the baseline
+        recent: usize,
+    },
+}
+
+impl AnomalyRule {
+    /// Report responses using more than `factor` times their session's mean,
+    /// once the session has 5 responses and from 100 tokens up
+    pub fn token_spike(factor: f64) -> Self {
+        Self::TokenSpike {
+            factor,
+            min_responses: 5,
+            min_tokens: 100,
+        }
+    }
+
+    /// Report tools failing more than `threshold` of their last 20
+    /// invocations, once they have 10
+    pub fn tool_failure_rate(threshold: f64) -> Self {
+        Self::ToolFailureRate {
+            threshold,
+            window: 20,
+            min_invocations: 10,
+        }
+    }
+
+    /// Report models whose last 10 responses take `factor` times as long as
+    /// the 50 before them
+    pub fn latency_regression(factor: f64) -> Self {
+        Self::LatencyRegression {
+            factor,
+            baseline: 50,
+            recent: 10,
+        }
+    }
+
+    /// The kind of condition the rule checks
+    pub fn kind(&self) -> AnomalyKind {
+        match self {
+            Self::TokenSpike { .. } => AnomalyKind::TokenSpike,
+            Self::ToolFailureRate { .. } => AnomalyKind::ToolFailureRate,
+            Self::LatencyRegression { .. } => AnomalyKind::LatencyRegression,
+        }
+    }
+
+    /// Check that the rule's parameters make sense
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::ValidationError`] naming the first bad parameter.
+    pub fn validate(&self) -> Result<()> {
+        let invalid = |reason: &str| {
+            Err(Error::ValidationError(format!(
+                "{} anomaly rule: {reason}",
+                self.kind()
+            )))
+        };
+        match *self {
+            Self::TokenSpike { factor, .. } | Self::LatencyRegression { factor, .. }
+                if !(factor > 1.0 && factor.is_finite()) =>
+            {
+                invalid("factor must be a finite number above 1")
+            }
+            Self::ToolFailureRate { threshold, .. } if !(0.0..1.0).contains(&threshold) => {
+                invalid("threshold must be in [0, 1)")
+            }
+            Self::ToolFailureRate {
+                window,
+                min_invocations,
+                ..
+            } if window == 0 || min_invocations > window => {
+                invalid("window must be positive and at least min_invocations")
+            }
+            Self::LatencyRegression {
+                baseline, recent, ..
+            } if baseline == 0 || recent == 0 => invalid("baseline and recent must be positive"),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Activity found anomalous by an [`AnomalyRule`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Anomaly {
+    /// The rule's kind
+    pub kind: AnomalyKind,
+    /// What the anomaly concerns: a session ID, tool name or model name
+    pub subject: String,
+    /// Session of the response that triggered the anomaly, if any
+    pub session_id: Option<SessionId>,
+    /// The value that crossed the threshold
+    pub observed: f64,
+    /// The threshold it crossed
+    pub threshold: f64,
+    /// Human-readable description
+    pub message: String,
+}
+
+/// Applies [`AnomalyRule`]s to responses and tool results, see the
+/// [module docs](self)
+pub struct AnomalyDetector {
+    rules: Vec<(AnomalyRule, Mutex<Tracker>)>,
+}
+
+impl fmt::Debug for AnomalyDetector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rules: Vec<_> = self.rules.iter().map(|(rule, _)| rule).collect();
+        f.debug_struct("AnomalyDetector")
+            .field("rules", &rules)
+            .finish()
+    }
+}
+
+impl AnomalyDetector {
+    /// Create a detector applying `rules`
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::ValidationError`] if a rule is invalid.
+    pub fn new(rules: Vec<AnomalyRule>) -> Result<Self> {
+        let rules = rules
+            .into_iter()
+            .map(|rule| {
+                rule.validate()?;
+                let tracker = match rule {
+                    AnomalyRule::TokenSpike { .. } => Tracker::Tokens(Keyed::default()),
+                    AnomalyRule::ToolFailureRate { .. } => Tracker::Tools(Keyed::default()),
+                    AnomalyRule::LatencyRegression { .. } => Tracker::Latency(Keyed::default()),
+                };
+                Ok((rule, Mutex::new(tracker)))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { rules })
+    }
+
+    /// The rules applied
+    pub fn rules(&self) -> impl Iterator<Item = &AnomalyRule> {
+        self.rules.iter().map(|(rule, _)| rule)
+    }
+
+    /// Observe a response in `session_id` from `model`, returning the
+    /// anomalies it raises
+    ///
+    /// `latency_ms` of zero means the latency is unknown.
+    pub fn observe_response(
+        &self,
+        session_id: SessionId,
+        model: &str,
+        usage: &TokenUsage,
+        latency_ms: u64,
+    ) -> Vec<Anomaly> {
+        let mut anomalies = Vec::new();
+        for (rule, tracker) in &self.rules {
+            match (rule, &mut *tracker.lock()) {
+                (
+                    &AnomalyRule::TokenSpike {
+                        factor,
+                        min_responses,
+                        min_tokens,
+                    },
+                    Tracker::Tokens(sessions),
+                ) => {
+                    let tokens = usage.total_tokens;
+                    let stats = sessions.entry(session_id);
+                    let limit = factor * stats.mean();
+                    if stats.count >= min_responses
+                        && tokens >= min_tokens
+                        && f64::from(tokens) > limit
+                    {
+                        anomalies.push(Anomaly {
+                            kind: AnomalyKind::TokenSpike,
+                            subject: session_id.to_string(),
+                            session_id: Some(session_id),
+                            observed: f64::from(tokens),
+                            threshold: limit,
+                            message: format!(
+                                "response used {tokens} tokens, {:.1}x the session mean of {:.0}",
+                                f64::from(tokens) / stats.mean(),
+                                stats.mean()
+                            ),
+                        });
+                    }
+                    stats.count += 1;
+                    stats.total += u64::from(tokens);
+                }
+                (
+                    &AnomalyRule::LatencyRegression {
+                        factor,
+                        baseline,
+                        recent,
+                    },
+                    Tracker::Latency(models),
+                ) if latency_ms > 0 => {
+                    let window = models.entry(model.to_string());
+                    window.latencies.push_back(latency_ms);
+                    if window.latencies.len() > baseline + recent {
+                        window.latencies.pop_front();
+                    }
+                    if window.latencies.len() < baseline + recent {
+                        continue;
+                    }
+                    let mean =
+                        |values: Vec<u64>| values.iter().sum::<u64>() as f64 / values.len() as f64;
+                    let before = mean(window.latencies.iter().take(baseline).copied().collect());
+                    let after = mean(window.latencies.iter().skip(baseline).copied().collect());
+                    let regressed = after > factor * before;
+                    if regressed && !window.alerting {
+                        anomalies.push(Anomaly {
+                            kind: AnomalyKind::LatencyRegression,
+                            subject: model.to_string(),
+                            session_id: Some(session_id),
+                            observed: after,
+                            threshold: factor * before,
+                            message: format!(
+                                "mean latency of {model} rose from {before:.0}ms to {after:.0}ms \
+                                 over the last {recent} responses"
+                            ),
+                        });
+                    }
+                    window.alerting = regressed;
+                }
+                _ => {}
+            }
+        }
+        anomalies
+    }
+
+    /// Observe a completed invocation of `tool_name`, returning the anomalies
+    /// it raises
+    pub fn observe_tool(&self, tool_name: &str, success: bool) -> Vec<Anomaly> {
+        let mut anomalies = Vec::new();
+        for (rule, tracker) in &self.rules {
+            let (
+                &AnomalyRule::ToolFailureRate {
+                    threshold,
+                    window: size,
+                    min_invocations,
+                },
+                Tracker::Tools(tools),
+            ) = (rule, &mut *tracker.lock())
+            else {
+                continue;
+            };
+            let window = tools.entry(tool_name.to_string());
+            window.outcomes.push_back(success);
+            if window.outcomes.len() > size {
+                window.outcomes.pop_front();
+            }
+            if window.outcomes.len() < min_invocations {
+                continue;
+            }
+            let failures = window.outcomes.iter().filter(|ok| !**ok).count();
+            let rate = failures as f64 / window.outcomes.len() as f64;
+            let failing = rate > threshold;
+            if failing && !window.alerting {
+                anomalies.push(Anomaly {
+                    kind: AnomalyKind::ToolFailureRate,
+                    subject: tool_name.to_string(),
+                    session_id: None,
+                    observed: rate,
+                    threshold,
+                    message: format!(
+                        "{failures} of the last {} invocations of {tool_name} failed",
+                        window.outcomes.len()
+                    ),
+                });
+            }
+            window.alerting = failing;
+        }
+        anomalies
+    }
+}
+
+/// Per-rule state
+enum Tracker {
+    Tokens(Keyed<SessionId, TokenStats>),
+    Tools(Keyed<String, Outcomes>),
+    Latency(Keyed<String, Latencies>),
+}
+
+/// State per key, holding at most [`MAX_TRACKED_KEYS`] keys
+struct Keyed<K, V>(HashMap<K, V>);
+
+impl<K, V> Default for Keyed<K, V> {
+    fn default() -> Self {
+        Self(HashMap::new())
+    }
+}
+
+impl<K: Eq + Hash + Clone, V: Default> Keyed<K, V> {
+    fn entry(&mut self, key: K) -> &mut V {
+        if self.0.len() >= MAX_TRACKED_KEYS && !self.0.contains_key(&key) {
+            if let Some(evicted) = self.0.keys().next().cloned() {
+                self.0.remove(&evicted);
+            }
+        }
+        self.0.entry(key).or_default()
+    }
+}
+
+#[derive(Default)]
+struct TokenStats {
+    count: usize,
+    total: u64,
+}
+
+impl TokenStats {
+    fn mean(&self) -> f64 {
+        if self.count == 0 {
+            0.0
+        } else {
+            self.total as f64 / self.count as f64
+        }
+    }
+}
+
+#[derive(Default)]
+struct Outcomes {
+    outcomes: VecDeque<bool>,
+    alerting: bool,
+}
+
+#[derive(Default)]
+struct Latencies {
+    latencies: VecDeque<u64>,
+    alerting: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_spike() {
+        let detector = AnomalyDetector::new(vec![AnomalyRule::token_spike(3.0)]).unwrap();
+        let session = SessionId::new();
+        for _ in 0..5 {
+            let usage = TokenUsage::new(50, 50);
+            assert!(detector
+                .observe_response(session, "gpt-4", &usage, 0)
+                .is_empty());
+        }
+
+        let anomalies = detector.observe_response(session, "gpt-4", &TokenUsage::new(100, 400), 0);
+        assert_eq!(anomalies.len(), 1);
+        assert_eq!(anomalies[0].kind, AnomalyKind::TokenSpike);
+        assert_eq!(anomalies[0].session_id, Some(session));
+        assert!((anomalies[0].observed - 500.0).abs() < f64::EPSILON);
+
+        // Other sessions have their own baseline
+        let other = SessionId::new();
+        assert!(detector
+            .observe_response(other, "gpt-4", &TokenUsage::new(100, 400), 0)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_tool_failure_rate_fires_once() {
+        let rule = AnomalyRule::ToolFailureRate {
+            threshold: 0.5,
+            window: 4,
+            min_invocations: 4,
+        };
+        let detector = AnomalyDetector::new(vec![rule]).unwrap();
+        let outcomes = [
+            true, false, false, false, false, true, true, true, false, false, false,
+        ];
+        let fired: Vec<_> = outcomes
+            .iter()
+            .enumerate()
+            .filter(|(_, ok)| !detector.observe_tool("search", **ok).is_empty())
+            .map(|(index, _)| index)
+            .collect();
+        assert_eq!(fired, [3, 10]);
+        assert!(detector.observe_tool("calculator", false).is_empty());
+    }
+
+    #[test]
+    fn test_latency_regression() {
+        let rule = AnomalyRule::LatencyRegression {
+            factor: 2.0,
+            baseline: 4,
+            recent: 2,
+        };
+        let detector = AnomalyDetector::new(vec![rule]).unwrap();
+        let session = SessionId::new();
+        let usage = TokenUsage::new(1, 1);
+        for latency in [100, 100, 100, 100, 100, 0] {
+            assert!(detector
+                .observe_response(session, "gpt-4", &usage, latency)
+                .is_empty());
+        }
+        assert!(detector
+            .observe_response(session, "gpt-4", &usage, 100)
+            .is_empty());
+        let anomalies = detector.observe_response(session, "gpt-4", &usage, 500);
+        assert_eq!(anomalies.len(), 1);
+        assert_eq!(anomalies[0].subject, "gpt-4");
+        assert!((anomalies[0].observed - 300.0).abs() < f64::EPSILON);
+        assert!(detector
+            .observe_response(session, "claude-3", &usage, 500)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_rule_validation() {
+        assert!(AnomalyDetector::new(vec![
+            AnomalyRule::token_spike(2.0),
+            AnomalyRule::tool_failure_rate(0.2),
+            AnomalyRule::latency_regression(1.5),
+        ])
+        .is_ok());
+        for rule in [
+            AnomalyRule::token_spike(0.5),
+            AnomalyRule::tool_failure_rate(1.0),
+            AnomalyRule::latency_regression(f64::NAN),
+            AnomalyRule::ToolFailureRate {
+                threshold: 0.5,
+                window: 5,
+                min_invocations: 10,
+            },
+        ] {
+            assert!(rule.validate().is_err(), "{rule:?}");
+        }
+    }
+
+    #[test]
+    fn test_rule_serialization() {
+        let rule: AnomalyRule = serde_json::from_str(
+            r#"{"kind": "tool_failure_rate", "threshold": 0.3, "window": 50, "min_invocations": 20}"#,
+        )
+        .unwrap();
+        assert_eq!(rule.kind(), AnomalyKind::ToolFailureRate);
+    }
+}
@@ -1,5 +1,9 @@
 //! Configuration for Observatory integration
 
+use super::anomaly::AnomalyRule;
+use super::emitter::{OverflowPolicy, DEFAULT_QUEUE_CAPACITY};
+use super::sampling::SamplingConfig;
+use crate::ObservatorySettings;
 use serde::{Deserialize, Serialize};
 
 /// Configuration for Observatory integration
@@ -17,6 +21,22 @@ pub struct ObservatoryConfig {
     /// Enable metrics collection
     pub enable_metrics: bool,
 
+    /// Maximum number of events waiting to be published
+    #[serde(default = "default_queue_capacity")]
+    pub queue_capacity: usize,
+
+    /// What to do with events when the queue is full
+    #[serde(default)]
+    pub overflow_policy: OverflowPolicy,
+
+    /// Per-event-type sample rates
+    #[serde(default)]
+    pub sampling: SamplingConfig,
+
+    /// Rules the anomaly detector applies; none disables it
+    #[serde(default)]
+    pub anomaly_rules: Vec<AnomalyRule>,
+
     /// Additional configuration (for custom publishers)
     #[serde(default)]
     pub custom_config: std::collections::HashMap<String, String>,
@@ -29,11 +49,32 @@ impl Default for ObservatoryConfig {
             batch_size: 100,
             flush_interval_ms: 1000,
             enable_metrics: true,
+            queue_capacity: DEFAULT_QUEUE_CAPACITY,
+            overflow_policy: OverflowPolicy::default(),
+            sampling: SamplingConfig::default(),
+            anomaly_rules: Vec::new(),
             custom_config: std::collections::HashMap::new(),
         }
     }
 }
 
+impl From<&ObservatorySettings> for ObservatoryConfig {
+    fn from(settings: &ObservatorySettings) -> Self {
+        Self {
+            enabled: settings.enabled,
+            batch_size: settings.batch_size,
+            flush_interval_ms: settings.flush_interval_ms,
+            enable_metrics: settings.enable_metrics,
+            queue_capacity: settings.queue_capacity,
+            ..Self::default()
+        }
+    }
+}
+
+fn default_queue_capacity() -> usize {
+    DEFAULT_QUEUE_CAPACITY
+}
+
 impl ObservatoryConfig {
     /// Create a new observatory configuration with defaults
     pub fn new() -> Self {
@@ -64,6 +105,30 @@ impl ObservatoryConfig {
         self
     }
 
+    /// Set the maximum number of events waiting to be published
+    pub fn with_queue_capacity(mut self, capacity: usize) -> Self {
+        self.queue_capacity = capacity;
+        self
+    }
+
+    /// Set what happens to events when the queue is full
+    pub fn with_overflow_policy(mut self, policy: OverflowPolicy) -> Self {
+        self.overflow_policy = policy;
+        self
+    }
+
+    /// Sample events before they are queued
+    pub fn with_sampling(mut self, sampling: SamplingConfig) -> Self {
+        self.sampling = sampling;
+        self
+    }
+
+    /// Detect anomalies matching `rule`
+    pub fn with_anomaly_rule(mut self, rule: AnomalyRule) -> Self {
+        self.anomaly_rules.push(rule);
+        self
+    }
+
     /// Add custom configuration parameter
     pub fn with_custom(mut self, key: String, value: String) -> Self {
         self.custom_config.insert(key, value);
@@ -82,6 +147,10 @@ mod tests {
         assert_eq!(config.batch_size, 100);
         assert_eq!(config.flush_interval_ms, 1000);
         assert!(config.enable_metrics);
+        assert_eq!(config.queue_capacity, DEFAULT_QUEUE_CAPACITY);
+        assert_eq!(config.overflow_policy, OverflowPolicy::DropOldest);
+        assert!(!config.sampling.is_sampling());
+        assert!(config.anomaly_rules.is_empty());
     }
 
     #[test]
@@ -90,12 +159,29 @@ mod tests {
             .enabled()
             .with_batch_size(50)
             .with_flush_interval(500)
-            .with_metrics(false);
+            .with_metrics(false)
+            .with_queue_capacity(64)
+            .with_overflow_policy(OverflowPolicy::Block);
 
         assert!(config.enabled);
         assert_eq!(config.batch_size, 50);
         assert_eq!(config.flush_interval_ms, 500);
         assert!(!config.enable_metrics);
+        assert_eq!(config.queue_capacity, 64);
+        assert_eq!(config.overflow_policy, OverflowPolicy::Block);
+    }
+
+    #[test]
+    fn test_from_settings() {
+        let settings = ObservatorySettings {
+            enabled: true,
+            queue_capacity: 32,
+            ..ObservatorySettings::default()
+        };
+        let config = ObservatoryConfig::from(&settings);
+        assert!(config.enabled);
+        assert_eq!(config.queue_capacity, 32);
+        assert_eq!(config.batch_size, ObservatoryConfig::default().batch_size);
     }
 
     #[test]
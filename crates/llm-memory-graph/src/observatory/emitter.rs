@@ -1,25 +1,33 @@
 //! Async event emitter for non-blocking event emission
 //!
 //! This module provides async event emission that doesn't block the main operation flow.
-//! Events are emitted in background tasks using `tokio::spawn`, ensuring that event
-//! publishing never delays critical operations.
+//! Events are placed on a bounded queue and published in batches by a single
+//! background drainer task, so a burst of writes cannot flood the runtime with
+//! publishing tasks.
 //!
 //! # Features
 //!
-//! - **Non-blocking**: Events are sent in background tasks
+//! - **Non-blocking**: Events are queued and published in the background
+//! - **Backpressure**: The queue is bounded; an [`OverflowPolicy`] decides what
+//!   happens when it is full
 //! - **Error resilience**: Emission errors don't affect main operations
-//! - **Statistics**: Track emission success/failure rates
+//! - **Sampling**: Optionally drop a share of events per type, see [`SamplingConfig`]
+//! - **Statistics**: Track emission success/failure rates and dropped events
 //! - **Graceful degradation**: Continues operating even if event system fails
 //!
 //! # Examples
 //!
 //! ```no_run
-//! use llm_memory_graph::observatory::{AsyncEventEmitter, InMemoryPublisher, MemoryGraphEvent};
+//! use llm_memory_graph::observatory::{
+//!     AsyncEventEmitter, InMemoryPublisher, MemoryGraphEvent, OverflowPolicy,
+//! };
 //! use std::sync::Arc;
 //!
 //! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
 //! let publisher = Arc::new(InMemoryPublisher::new());
-//! let emitter = AsyncEventEmitter::new(publisher);
+//! let emitter = AsyncEventEmitter::new(publisher)
+//!     .with_queue_capacity(1_000)
+//!     .with_overflow_policy(OverflowPolicy::DropOldest);
 //!
 //! // Emit event without blocking
 //! let event = MemoryGraphEvent::QueryExecuted {
@@ -31,37 +39,76 @@
 //!
 //! emitter.emit(event);
 //!
+//! // Wait for queued events to be published
+//! emitter.flush().await;
+//!
 //! // Get statistics
 //! let stats = emitter.stats().await;
-//! println!("Emitted: {}, Failed: {}", stats.events_emitted, stats.events_failed);
+//! println!(
+//!     "Emitted: {}, Failed: {}, Dropped: {}",
+//!     stats.events_emitted, stats.events_failed, stats.events_dropped
+//! );
 //! # Ok(())
 //! # }
 //! ```
 
 use super::events::MemoryGraphEvent;
 use super::publisher::EventPublisher;
-use std::sync::atomic::{AtomicU64, Ordering};
-use std::sync::Arc;
-use tokio::sync::RwLock;
+use super::sampling::SamplingConfig;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Once};
+use tokio::sync::Notify;
+
+/// Default number of events an emitter queues before applying its overflow policy
+pub const DEFAULT_QUEUE_CAPACITY: usize = 10_000;
+
+/// Most events the drainer hands to the publisher in one batch
+const MAX_DRAIN_BATCH: usize = 256;
+
+/// What an emitter does with an event when its queue is full
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OverflowPolicy {
+    /// Discard the oldest queued event to make room
+    #[default]
+    DropOldest,
+    /// Discard the event being emitted
+    DropNewest,
+    /// Wait for room in [`AsyncEventEmitter::emit_async`]
+    ///
+    /// [`AsyncEventEmitter::emit`] cannot wait and discards the event being
+    /// emitted instead.
+    Block,
+}
 
 /// Async event emitter for non-blocking event emission
 ///
 /// This struct wraps an EventPublisher and provides fire-and-forget
-/// emission semantics. Events are sent in background tasks using
-/// `tokio::spawn`, ensuring they never block the caller.
-#[derive(Clone)]
-pub struct AsyncEventEmitter<P: EventPublisher + 'static> {
-    /// The underlying event publisher
-    publisher: Arc<P>,
-    /// Statistics tracking
-    stats: Arc<EmissionStats>,
-    /// Whether to log errors
-    log_errors: bool,
+/// emission semantics. Events are queued on a bounded queue and published by a
+/// single background task, started on the first emission, ensuring they never
+/// block the caller. Clones share the queue; the background task publishes
+/// whatever is still queued and exits once every clone is dropped.
+pub struct AsyncEventEmitter<P: EventPublisher + ?Sized + 'static> {
+    sender: Arc<Sender<P>>,
+}
+
+impl<P: EventPublisher + ?Sized + 'static> Clone for AsyncEventEmitter<P> {
+    fn clone(&self) -> Self {
+        Self {
+            sender: Arc::clone(&self.sender),
+        }
+    }
 }
 
-impl<P: EventPublisher + 'static> AsyncEventEmitter<P> {
+impl<P: EventPublisher + ?Sized + 'static> AsyncEventEmitter<P> {
     /// Create a new async event emitter
     ///
+    /// The emitter queues up to [`DEFAULT_QUEUE_CAPACITY`] events and drops the
+    /// oldest when full.
+    ///
     /// # Arguments
     ///
     /// * `publisher` - The event publisher to use for sending events
@@ -76,26 +123,78 @@ impl<P: EventPublisher + 'static> AsyncEventEmitter<P> {
     /// let emitter = AsyncEventEmitter::new(publisher);
     /// ```
     pub fn new(publisher: Arc<P>) -> Self {
-        Self {
-            publisher,
-            stats: Arc::new(EmissionStats::new()),
-            log_errors: true,
-        }
+        Self::build(publisher, Settings::default())
     }
 
     /// Create a new async event emitter without error logging
     pub fn new_silent(publisher: Arc<P>) -> Self {
-        Self {
+        Self::build(
             publisher,
-            stats: Arc::new(EmissionStats::new()),
-            log_errors: false,
+            Settings {
+                log_errors: false,
+                ..Settings::default()
+            },
+        )
+    }
+
+    /// Set how many events may be queued (at least one)
+    ///
+    /// Must be called before the emitter is cloned or used; it replaces the
+    /// queue and resets statistics.
+    pub fn with_queue_capacity(self, capacity: usize) -> Self {
+        self.rebuild(|settings| settings.capacity = capacity.max(1))
+    }
+
+    /// Set what happens when the queue is full
+    ///
+    /// Must be called before the emitter is cloned or used; it replaces the
+    /// queue and resets statistics.
+    pub fn with_overflow_policy(self, policy: OverflowPolicy) -> Self {
+        self.rebuild(|settings| settings.policy = policy)
+    }
+
+    /// Sample events before queueing them
+    ///
+    /// Must be called before the emitter is cloned or used; it replaces the
+    /// queue and resets statistics.
+    pub fn with_sampling(self, sampling: SamplingConfig) -> Self {
+        self.rebuild(|settings| settings.sampling = sampling)
+    }
+
+    fn rebuild(self, update: impl FnOnce(&mut Settings)) -> Self {
+        let shared = &self.sender.shared;
+        let mut settings = shared.settings.clone();
+        update(&mut settings);
+        Self::build(Arc::clone(&shared.publisher), settings)
+    }
+
+    fn build(publisher: Arc<P>, settings: Settings) -> Self {
+        let shared = Arc::new(Shared {
+            publisher,
+            stats: EmissionStats::new(),
+            settings,
+            queue: Mutex::new(VecDeque::new()),
+            in_flight: AtomicUsize::new(0),
+            closed: AtomicBool::new(false),
+            drainer: Once::new(),
+            queued: Notify::new(),
+            progress: Notify::new(),
+        });
+        Self {
+            sender: Arc::new(Sender { shared }),
         }
     }
 
     /// Emit an event without blocking
     ///
-    /// This method spawns a background task to publish the event and returns
-    /// immediately. Errors during emission are logged but don't affect the caller.
+    /// This method queues the event for the background drainer and returns
+    /// immediately. Errors during emission are logged but don't affect the
+    /// caller. When the queue is full the overflow policy applies; under
+    /// [`OverflowPolicy::Block`] the event is dropped, use
+    /// [`emit_async`](Self::emit_async) to wait for room instead.
+    ///
+    /// Returns whether the event was queued; events discarded by sampling are
+    /// not.
     ///
     /// # Arguments
     ///
@@ -120,60 +219,61 @@ impl<P: EventPublisher + 'static> AsyncEventEmitter<P> {
     /// emitter.emit(event); // Returns immediately
     /// # }
     /// ```
-    pub fn emit(&self, event: MemoryGraphEvent) {
-        let publisher = Arc::clone(&self.publisher);
-        let stats = Arc::clone(&self.stats);
-        let log_errors = self.log_errors;
-
-        tokio::spawn(async move {
-            stats.inc_submitted();
-
-            match publisher.publish(event).await {
-                Ok(()) => {
-                    stats.inc_emitted();
-                }
-                Err(e) => {
-                    stats.inc_failed();
-                    if log_errors {
-                        tracing::warn!("Failed to emit event: {}", e);
-                    }
-                }
-            }
-        });
+    pub fn emit(&self, event: MemoryGraphEvent) -> bool {
+        self.enqueue(std::iter::once(event)) == 1
     }
 
     /// Emit multiple events without blocking
     ///
+    /// Each event is subject to the overflow policy as in [`emit`](Self::emit).
+    /// Returns how many events were queued.
+    ///
     /// # Arguments
     ///
     /// * `events` - Vector of events to emit
-    pub fn emit_batch(&self, events: Vec<MemoryGraphEvent>) {
-        let publisher = Arc::clone(&self.publisher);
-        let stats = Arc::clone(&self.stats);
-        let log_errors = self.log_errors;
-        let count = events.len() as u64;
-
-        tokio::spawn(async move {
-            stats.inc_submitted_by(count);
-
-            match publisher.publish_batch(events).await {
-                Ok(()) => {
-                    stats.inc_emitted_by(count);
-                }
-                Err(e) => {
-                    stats.inc_failed_by(count);
-                    if log_errors {
-                        tracing::warn!("Failed to emit event batch: {}", e);
-                    }
+    pub fn emit_batch(&self, events: Vec<MemoryGraphEvent>) -> usize {
+        self.enqueue(events)
+    }
+
+    /// Emit an event, waiting for room in the queue under [`OverflowPolicy::Block`]
+    ///
+    /// With the other policies this behaves like [`emit`](Self::emit).
+    /// Returns whether the event was queued.
+    pub async fn emit_async(&self, mut event: MemoryGraphEvent) -> bool {
+        let shared = &self.sender.shared;
+        if shared.settings.policy != OverflowPolicy::Block {
+            return self.emit(event);
+        }
+        if !shared.settings.sampling.sample(&mut event) {
+            shared.stats.inc_sampled_out();
+            return false;
+        }
+        self.start_drainer();
+        shared.stats.inc_submitted();
+
+        loop {
+            let progress = shared.progress.notified();
+            tokio::pin!(progress);
+            progress.as_mut().enable();
+            {
+                let mut queue = shared.queue.lock();
+                if queue.len() < shared.settings.capacity {
+                    queue.push_back(event);
+                    shared.stats.record_depth(queue.len());
+                    drop(queue);
+                    shared.queued.notify_one();
+                    return true;
                 }
             }
-        });
+            progress.await;
+        }
     }
 
     /// Emit an event and wait for completion
     ///
-    /// Unlike `emit()`, this method waits for the event to be published
-    /// and returns any errors. Useful for testing and critical events.
+    /// Unlike `emit()`, this method bypasses the queue and sampling, waits for
+    /// the event to be published and returns any errors. Useful for testing and critical
+    /// events.
     ///
     /// # Arguments
     ///
@@ -183,16 +283,17 @@ impl<P: EventPublisher + 'static> AsyncEventEmitter<P> {
     ///
     /// Returns `Ok(())` if the event was successfully published
     pub async fn emit_sync(&self, event: MemoryGraphEvent) -> crate::Result<()> {
-        self.stats.inc_submitted();
+        let shared = &self.sender.shared;
+        shared.stats.inc_submitted();
 
-        match self.publisher.publish(event).await {
+        match shared.publisher.publish(event).await {
             Ok(()) => {
-                self.stats.inc_emitted();
+                shared.stats.inc_emitted_by(1);
                 Ok(())
             }
             Err(e) => {
-                self.stats.inc_failed();
-                if self.log_errors {
+                shared.stats.inc_failed_by(1);
+                if shared.settings.log_errors {
                     tracing::warn!("Failed to emit event: {}", e);
                 }
                 Err(e)
@@ -200,23 +301,206 @@ impl<P: EventPublisher + 'static> AsyncEventEmitter<P> {
         }
     }
 
+    /// Wait until every queued event has been published or has failed
+    ///
+    /// Does not flush the underlying publisher.
+    pub async fn flush(&self) {
+        let shared = &self.sender.shared;
+        loop {
+            let progress = shared.progress.notified();
+            tokio::pin!(progress);
+            progress.as_mut().enable();
+            if shared.pending() == 0 {
+                return;
+            }
+            progress.await;
+        }
+    }
+
+    /// Events queued or being published
+    pub fn pending(&self) -> usize {
+        self.sender.shared.pending()
+    }
+
+    /// Maximum number of queued events
+    pub fn queue_capacity(&self) -> usize {
+        self.sender.shared.settings.capacity
+    }
+
+    /// What happens when the queue is full
+    pub fn overflow_policy(&self) -> OverflowPolicy {
+        self.sender.shared.settings.policy
+    }
+
     /// Get emission statistics
     ///
     /// # Returns
     ///
     /// Returns a snapshot of current emission statistics
     pub async fn stats(&self) -> EmissionStatsSnapshot {
-        self.stats.snapshot().await
+        self.sender.shared.stats.snapshot()
     }
 
     /// Reset all statistics to zero
     pub async fn reset_stats(&self) {
-        self.stats.reset().await;
+        self.sender.shared.stats.reset();
     }
 
     /// Get the underlying publisher
     pub fn publisher(&self) -> &Arc<P> {
-        &self.publisher
+        &self.sender.shared.publisher
+    }
+
+    /// Queue `events`, applying the overflow policy; returns how many were queued
+    fn enqueue(&self, events: impl IntoIterator<Item = MemoryGraphEvent>) -> usize {
+        let shared = &self.sender.shared;
+        let settings = &shared.settings;
+        self.start_drainer();
+
+        let mut queued = 0;
+        let mut dropped = 0;
+        {
+            let mut queue = shared.queue.lock();
+            for mut event in events {
+                if !settings.sampling.sample(&mut event) {
+                    shared.stats.inc_sampled_out();
+                    continue;
+                }
+                shared.stats.inc_submitted();
+                if queue.len() >= settings.capacity {
+                    dropped += 1;
+                    match settings.policy {
+                        OverflowPolicy::DropOldest => {
+                            queue.pop_front();
+                        }
+                        OverflowPolicy::DropNewest | OverflowPolicy::Block => continue,
+                    }
+                }
+                queue.push_back(event);
+                queued += 1;
+            }
+            shared.stats.record_depth(queue.len());
+        }
+
+        if dropped > 0 {
+            shared.stats.inc_dropped_by(dropped);
+            if settings.log_errors {
+                tracing::debug!("Event queue full, dropped {} event(s)", dropped);
+            }
+        }
+        if queued > 0 {
+            shared.queued.notify_one();
+        }
+        queued
+    }
+
+    fn start_drainer(&self) {
+        let shared = &self.sender.shared;
+        shared
+            .drainer
+            .call_once(|| drop(tokio::spawn(drain(Arc::clone(shared)))));
+    }
+}
+
+/// Emitter options fixed once the emitter is in use
+#[derive(Clone)]
+struct Settings {
+    log_errors: bool,
+    capacity: usize,
+    policy: OverflowPolicy,
+    sampling: SamplingConfig,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            log_errors: true,
+            capacity: DEFAULT_QUEUE_CAPACITY,
+            policy: OverflowPolicy::default(),
+            sampling: SamplingConfig::default(),
+        }
+    }
+}
+
+/// State shared by every clone of an emitter and its drainer task
+struct Shared<P: ?Sized> {
+    publisher: Arc<P>,
+    stats: EmissionStats,
+    settings: Settings,
+    queue: Mutex<VecDeque<MemoryGraphEvent>>,
+    /// Events taken off the queue and not yet published
+    in_flight: AtomicUsize,
+    /// Set once every emitter handle is dropped
+    closed: AtomicBool,
+    drainer: Once,
+    /// Signalled when events are queued or the emitter is closed
+    queued: Notify,
+    /// Signalled when the drainer takes events off the queue or finishes a batch
+    progress: Notify,
+}
+
+impl<P: ?Sized> Shared<P> {
+    fn pending(&self) -> usize {
+        let queue = self.queue.lock();
+        queue.len() + self.in_flight.load(Ordering::Acquire)
+    }
+}
+
+/// Handle shared by emitter clones; closes the queue when the last one drops
+struct Sender<P: ?Sized> {
+    shared: Arc<Shared<P>>,
+}
+
+impl<P: ?Sized> Drop for Sender<P> {
+    fn drop(&mut self) {
+        self.shared.closed.store(true, Ordering::Release);
+        self.shared.queued.notify_one();
+    }
+}
+
+/// Publish queued events in batches until the emitter is closed and drained
+async fn drain<P: EventPublisher + ?Sized>(shared: Arc<Shared<P>>) {
+    loop {
+        let queued = shared.queued.notified();
+        tokio::pin!(queued);
+        queued.as_mut().enable();
+
+        let batch: Vec<_> = {
+            let mut queue = shared.queue.lock();
+            let count = queue.len().min(MAX_DRAIN_BATCH);
+            shared.in_flight.store(count, Ordering::Release);
+            queue.drain(..count).collect()
+        };
+        shared.progress.notify_waiters();
+
+        if batch.is_empty() {
+            if shared.closed.load(Ordering::Acquire) {
+                return;
+            }
+            queued.await;
+            continue;
+        }
+
+        let count = batch.len() as u64;
+        let result = if count == 1 {
+            shared
+                .publisher
+                .publish(batch.into_iter().next().unwrap())
+                .await
+        } else {
+            shared.publisher.publish_batch(batch).await
+        };
+        match result {
+            Ok(()) => shared.stats.inc_emitted_by(count),
+            Err(e) => {
+                shared.stats.inc_failed_by(count);
+                if shared.settings.log_errors {
+                    tracing::warn!("Failed to emit {} event(s): {}", count, e);
+                }
+            }
+        }
+        shared.in_flight.store(0, Ordering::Release);
+        shared.progress.notify_waiters();
     }
 }
 
@@ -228,8 +512,12 @@ struct EmissionStats {
     events_emitted: AtomicU64,
     /// Total events that failed to emit
     events_failed: AtomicU64,
-    /// Peak concurrent emissions (for monitoring)
-    peak_concurrent: RwLock<u64>,
+    /// Total events discarded because the queue was full
+    events_dropped: AtomicU64,
+    /// Total events discarded by sampling (not counted as submitted)
+    events_sampled_out: AtomicU64,
+    /// Largest number of events queued at once (for monitoring)
+    peak_queue_depth: AtomicU64,
 }
 
 impl EmissionStats {
@@ -238,7 +526,9 @@ impl EmissionStats {
             events_submitted: AtomicU64::new(0),
             events_emitted: AtomicU64::new(0),
             events_failed: AtomicU64::new(0),
-            peak_concurrent: RwLock::new(0),
+            events_dropped: AtomicU64::new(0),
+            events_sampled_out: AtomicU64::new(0),
+            peak_queue_depth: AtomicU64::new(0),
         }
     }
 
@@ -246,40 +536,45 @@ impl EmissionStats {
         self.events_submitted.fetch_add(1, Ordering::Relaxed);
     }
 
-    fn inc_submitted_by(&self, count: u64) {
-        self.events_submitted.fetch_add(count, Ordering::Relaxed);
+    fn inc_emitted_by(&self, count: u64) {
+        self.events_emitted.fetch_add(count, Ordering::Relaxed);
     }
 
-    fn inc_emitted(&self) {
-        self.events_emitted.fetch_add(1, Ordering::Relaxed);
+    fn inc_failed_by(&self, count: u64) {
+        self.events_failed.fetch_add(count, Ordering::Relaxed);
     }
 
-    fn inc_emitted_by(&self, count: u64) {
-        self.events_emitted.fetch_add(count, Ordering::Relaxed);
+    fn inc_dropped_by(&self, count: u64) {
+        self.events_dropped.fetch_add(count, Ordering::Relaxed);
     }
 
-    fn inc_failed(&self) {
-        self.events_failed.fetch_add(1, Ordering::Relaxed);
+    fn inc_sampled_out(&self) {
+        self.events_sampled_out.fetch_add(1, Ordering::Relaxed);
     }
 
-    fn inc_failed_by(&self, count: u64) {
-        self.events_failed.fetch_add(count, Ordering::Relaxed);
+    fn record_depth(&self, depth: usize) {
+        self.peak_queue_depth
+            .fetch_max(depth as u64, Ordering::Relaxed);
     }
 
-    async fn snapshot(&self) -> EmissionStatsSnapshot {
+    fn snapshot(&self) -> EmissionStatsSnapshot {
         EmissionStatsSnapshot {
             events_submitted: self.events_submitted.load(Ordering::Relaxed),
             events_emitted: self.events_emitted.load(Ordering::Relaxed),
             events_failed: self.events_failed.load(Ordering::Relaxed),
-            peak_concurrent: *self.peak_concurrent.read().await,
+            events_dropped: self.events_dropped.load(Ordering::Relaxed),
+            events_sampled_out: self.events_sampled_out.load(Ordering::Relaxed),
+            peak_queue_depth: self.peak_queue_depth.load(Ordering::Relaxed),
         }
     }
 
-    async fn reset(&self) {
+    fn reset(&self) {
         self.events_submitted.store(0, Ordering::Relaxed);
         self.events_emitted.store(0, Ordering::Relaxed);
         self.events_failed.store(0, Ordering::Relaxed);
-        *self.peak_concurrent.write().await = 0;
+        self.events_dropped.store(0, Ordering::Relaxed);
+        self.events_sampled_out.store(0, Ordering::Relaxed);
+        self.peak_queue_depth.store(0, Ordering::Relaxed);
     }
 }
 
@@ -292,8 +587,12 @@ pub struct EmissionStatsSnapshot {
     pub events_emitted: u64,
     /// Total events that failed to emit
     pub events_failed: u64,
-    /// Peak concurrent emissions
-    pub peak_concurrent: u64,
+    /// Total events discarded because the queue was full
+    pub events_dropped: u64,
+    /// Total events discarded by sampling (not counted as submitted)
+    pub events_sampled_out: u64,
+    /// Largest number of events queued at once
+    pub peak_queue_depth: u64,
 }
 
 impl EmissionStatsSnapshot {
@@ -314,6 +613,15 @@ impl EmissionStatsSnapshot {
             (self.events_failed as f64 / self.events_submitted as f64) * 100.0
         }
     }
+
+    /// Calculate drop rate as a percentage
+    pub fn drop_rate(&self) -> f64 {
+        if self.events_submitted == 0 {
+            0.0
+        } else {
+            (self.events_dropped as f64 / self.events_submitted as f64) * 100.0
+        }
+    }
 }
 
 #[cfg(test)]
@@ -645,4 +953,154 @@ mod tests {
         assert_eq!(stats.success_rate(), 100.0);
         assert_eq!(stats.failure_rate(), 0.0);
     }
+
+    fn query_event(i: usize) -> MemoryGraphEvent {
+        MemoryGraphEvent::QueryExecuted {
+            query_type: "test".to_string(),
+            results_count: i,
+            duration_ms: 1,
+            timestamp: Utc::now(),
+        }
+    }
+
+    async fn published_counts(publisher: &InMemoryPublisher) -> Vec<usize> {
+        publisher
+            .get_events()
+            .await
+            .into_iter()
+            .map(|event| match event {
+                MemoryGraphEvent::QueryExecuted { results_count, .. } => results_count,
+                other => panic!("unexpected event {:?}", other),
+            })
+            .collect()
+    }
+
+    /// Publisher that waits for a permit before publishing each event
+    struct GatedPublisher {
+        gate: tokio::sync::Semaphore,
+        inner: InMemoryPublisher,
+    }
+
+    #[async_trait::async_trait]
+    impl EventPublisher for GatedPublisher {
+        async fn publish(&self, event: MemoryGraphEvent) -> crate::Result<()> {
+            self.gate.acquire().await.unwrap().forget();
+            self.inner.publish(event).await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_drop_oldest_policy() {
+        let publisher = Arc::new(InMemoryPublisher::new());
+        let emitter = AsyncEventEmitter::new(publisher.clone()).with_queue_capacity(2);
+        assert_eq!(emitter.overflow_policy(), OverflowPolicy::DropOldest);
+
+        // The drainer cannot run until the test yields, so the queue overflows
+        for i in 0..5 {
+            assert!(emitter.emit(query_event(i)));
+        }
+        emitter.flush().await;
+
+        assert_eq!(published_counts(&publisher).await, vec![3, 4]);
+        let stats = emitter.stats().await;
+        assert_eq!(stats.events_submitted, 5);
+        assert_eq!(stats.events_emitted, 2);
+        assert_eq!(stats.events_dropped, 3);
+        assert_eq!(stats.peak_queue_depth, 2);
+        assert_eq!(stats.drop_rate(), 60.0);
+        assert_eq!(emitter.pending(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_drop_newest_policy() {
+        let publisher = Arc::new(InMemoryPublisher::new());
+        let emitter = AsyncEventEmitter::new(publisher.clone())
+            .with_queue_capacity(2)
+            .with_overflow_policy(OverflowPolicy::DropNewest);
+
+        let queued: Vec<bool> = (0..4).map(|i| emitter.emit(query_event(i))).collect();
+        assert_eq!(queued, vec![true, true, false, false]);
+        assert_eq!(emitter.emit_batch(vec![query_event(4)]), 0);
+        emitter.flush().await;
+
+        assert_eq!(published_counts(&publisher).await, vec![0, 1]);
+        assert_eq!(emitter.stats().await.events_dropped, 3);
+    }
+
+    #[tokio::test]
+    async fn test_block_policy_waits_for_room() {
+        let publisher = Arc::new(GatedPublisher {
+            gate: tokio::sync::Semaphore::new(0),
+            inner: InMemoryPublisher::new(),
+        });
+        let emitter = AsyncEventEmitter::new(publisher.clone())
+            .with_queue_capacity(1)
+            .with_overflow_policy(OverflowPolicy::Block);
+
+        // Event 0 is taken by the drainer, which then waits on the gate
+        assert!(emitter.emit_async(query_event(0)).await);
+        sleep(Duration::from_millis(20)).await;
+        assert!(emitter.emit_async(query_event(1)).await);
+        assert_eq!(emitter.pending(), 2);
+
+        // The queue is full, so the next emission waits
+        let blocked = {
+            let emitter = emitter.clone();
+            tokio::spawn(async move { emitter.emit_async(query_event(2)).await })
+        };
+        sleep(Duration::from_millis(20)).await;
+        assert!(!blocked.is_finished());
+        // A non-blocking emission cannot wait and is dropped
+        assert!(!emitter.emit(query_event(3)));
+
+        publisher.gate.add_permits(10);
+        assert!(blocked.await.unwrap());
+        emitter.flush().await;
+
+        let published = publisher.inner.get_events().await;
+        assert_eq!(published.len(), 3);
+        let stats = emitter.stats().await;
+        assert_eq!(stats.events_emitted, 3);
+        assert_eq!(stats.events_dropped, 1);
+    }
+
+    #[tokio::test]
+    async fn test_sampling_applied_before_queueing() {
+        let publisher = Arc::new(InMemoryPublisher::new());
+        let emitter = AsyncEventEmitter::new(publisher.clone()).with_sampling(
+            SamplingConfig::new()
+                .with_rate("node_created", 0.0)
+                .with_rate("query_executed", 1.0),
+        );
+
+        let node_created = MemoryGraphEvent::NodeCreated {
+            node_id: NodeId::new(),
+            node_type: NodeType::Prompt,
+            session_id: None,
+            timestamp: Utc::now(),
+            metadata: HashMap::new(),
+        };
+        assert!(!emitter.emit(node_created.clone()));
+        assert_eq!(emitter.emit_batch(vec![node_created, query_event(1)]), 1);
+        emitter.flush().await;
+
+        assert_eq!(published_counts(&publisher).await, vec![1]);
+        let stats = emitter.stats().await;
+        assert_eq!(stats.events_sampled_out, 2);
+        assert_eq!(stats.events_submitted, 1);
+        assert_eq!(stats.success_rate(), 100.0);
+    }
+
+    #[tokio::test]
+    async fn test_drainer_publishes_after_last_clone_drops() {
+        let publisher = Arc::new(InMemoryPublisher::new());
+        let emitter = AsyncEventEmitter::new(publisher.clone());
+        for i in 0..3 {
+            emitter.emit(query_event(i));
+        }
+        drop(emitter);
+
+        sleep(Duration::from_millis(50)).await;
+        assert_eq!(published_counts(&publisher).await, vec![0, 1, 2]);
+    }
 }
@@ -3,9 +3,8 @@
 //! This module defines all events that can be emitted by the memory graph
 //! for real-time monitoring and analysis.
 
-use crate::{
-    AgentId, EdgeId, EdgeType, NodeId, NodeType, SessionId, TemplateId, TokenUsage,
-};
+use super::anomaly::AnomalyKind;
+use crate::{AgentId, EdgeId, EdgeType, NodeId, NodeType, SessionId, TemplateId, TokenUsage};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -127,15 +126,111 @@ pub enum MemoryGraphEvent {
         /// Event timestamp
         timestamp: DateTime<Utc>,
     },
+
+    /// Periodic cache statistics, published by the maintenance scheduler
+    CacheStatsReported {
+        /// Number of cached nodes
+        node_cache_size: u64,
+        /// Number of cached edges
+        edge_cache_size: u64,
+        /// Node cache hit rate (0.0 to 1.0)
+        node_hit_rate: f64,
+        /// Edge cache hit rate (0.0 to 1.0)
+        edge_hit_rate: f64,
+        /// Event timestamp
+        timestamp: DateTime<Utc>,
+    },
+
+    /// Advisory session lock taken or renewed
+    SessionLocked {
+        /// The locked session
+        session_id: SessionId,
+        /// Who holds the lock
+        owner: String,
+        /// When the lock lapses unless renewed
+        expires_at: DateTime<Utc>,
+        /// Event timestamp
+        timestamp: DateTime<Utc>,
+    },
+
+    /// Advisory session lock released by its owner
+    SessionUnlocked {
+        /// The unlocked session
+        session_id: SessionId,
+        /// Who held the lock
+        owner: String,
+        /// Event timestamp
+        timestamp: DateTime<Utc>,
+    },
+
+    /// Legal hold placed on a session
+    LegalHoldPlaced {
+        /// The held session
+        session_id: SessionId,
+        /// Why the session is held
+        reason: String,
+        /// Event timestamp
+        timestamp: DateTime<Utc>,
+    },
+
+    /// Legal hold released from a session
+    LegalHoldReleased {
+        /// The released session
+        session_id: SessionId,
+        /// Why the session was held
+        reason: String,
+        /// Event timestamp
+        timestamp: DateTime<Utc>,
+    },
+
+    /// Content of a prompt or response edited
+    ContentEdited {
+        /// The edited node
+        node_id: NodeId,
+        /// Type of the edited node
+        node_type: NodeType,
+        /// Version number given to the replaced content
+        version: u32,
+        /// Why the content was edited
+        reason: String,
+        /// Event timestamp
+        timestamp: DateTime<Utc>,
+    },
+
+    /// Activity matched an anomaly rule
+    AnomalyDetected {
+        /// Kind of rule that matched
+        kind: AnomalyKind,
+        /// What the anomaly concerns: a session ID, tool name or model name
+        subject: String,
+        /// Session of the response that triggered the anomaly, if any
+        #[serde(skip_serializing_if = "Option::is_none")]
+        session_id: Option<SessionId>,
+        /// The value that crossed the threshold
+        observed: f64,
+        /// The threshold it crossed
+        threshold: f64,
+        /// Human-readable description
+        message: String,
+        /// Event timestamp
+        timestamp: DateTime<Utc>,
+    },
 }
 
 impl MemoryGraphEvent {
     /// Get a unique key for this event (for Kafka partitioning)
     pub fn key(&self) -> String {
         match self {
-            Self::NodeCreated { node_id, .. } => format!("node:{}", node_id),
+            Self::NodeCreated { node_id, .. } | Self::ContentEdited { node_id, .. } => {
+                format!("node:{}", node_id)
+            }
             Self::EdgeCreated { edge_id, .. } => format!("edge:{}", edge_id),
-            Self::PromptSubmitted { session_id, .. } | Self::AgentHandoff { session_id, .. } => {
+            Self::PromptSubmitted { session_id, .. }
+            | Self::AgentHandoff { session_id, .. }
+            | Self::SessionLocked { session_id, .. }
+            | Self::SessionUnlocked { session_id, .. }
+            | Self::LegalHoldPlaced { session_id, .. }
+            | Self::LegalHoldReleased { session_id, .. } => {
                 format!("session:{}", session_id)
             }
             Self::ResponseGenerated { prompt_id, .. } => {
@@ -144,6 +239,8 @@ impl MemoryGraphEvent {
             Self::ToolInvoked { tool_id, .. } => format!("tool:{}", tool_id),
             Self::TemplateInstantiated { template_id, .. } => format!("template:{}", template_id),
             Self::QueryExecuted { query_type, .. } => format!("query:{}", query_type),
+            Self::CacheStatsReported { .. } => "cache".to_string(),
+            Self::AnomalyDetected { kind, subject, .. } => format!("anomaly:{}:{}", kind, subject),
         }
     }
 
@@ -158,6 +255,29 @@ impl MemoryGraphEvent {
             Self::AgentHandoff { .. } => "agent_handoff",
             Self::TemplateInstantiated { .. } => "template_instantiated",
             Self::QueryExecuted { .. } => "query_executed",
+            Self::CacheStatsReported { .. } => "cache_stats_reported",
+            Self::SessionLocked { .. } => "session_locked",
+            Self::SessionUnlocked { .. } => "session_unlocked",
+            Self::LegalHoldPlaced { .. } => "legal_hold_placed",
+            Self::LegalHoldReleased { .. } => "legal_hold_released",
+            Self::ContentEdited { .. } => "content_edited",
+            Self::AnomalyDetected { .. } => "anomaly_detected",
+        }
+    }
+
+    /// Whether the event describes a failure or an anomaly
+    pub fn is_error(&self) -> bool {
+        matches!(
+            self,
+            Self::ToolInvoked { success: false, .. } | Self::AnomalyDetected { .. }
+        )
+    }
+
+    /// Metadata attached to the event, for event types that carry any
+    pub fn metadata_mut(&mut self) -> Option<&mut HashMap<String, String>> {
+        match self {
+            Self::NodeCreated { metadata, .. } => Some(metadata),
+            _ => None,
         }
     }
 
@@ -171,7 +291,14 @@ impl MemoryGraphEvent {
             | Self::ToolInvoked { timestamp, .. }
             | Self::AgentHandoff { timestamp, .. }
             | Self::TemplateInstantiated { timestamp, .. }
-            | Self::QueryExecuted { timestamp, .. } => *timestamp,
+            | Self::QueryExecuted { timestamp, .. }
+            | Self::CacheStatsReported { timestamp, .. }
+            | Self::SessionLocked { timestamp, .. }
+            | Self::SessionUnlocked { timestamp, .. }
+            | Self::LegalHoldPlaced { timestamp, .. }
+            | Self::LegalHoldReleased { timestamp, .. }
+            | Self::ContentEdited { timestamp, .. }
+            | Self::AnomalyDetected { timestamp, .. } => *timestamp,
         }
     }
 }
@@ -272,6 +399,22 @@ mod tests {
                 duration_ms: 25,
                 timestamp: Utc::now(),
             },
+            MemoryGraphEvent::SessionLocked {
+                session_id: SessionId::new(),
+                owner: "planner".to_string(),
+                expires_at: Utc::now(),
+                timestamp: Utc::now(),
+            },
+            MemoryGraphEvent::SessionUnlocked {
+                session_id: SessionId::new(),
+                owner: "planner".to_string(),
+                timestamp: Utc::now(),
+            },
+            MemoryGraphEvent::LegalHoldPlaced {
+                session_id: SessionId::new(),
+                reason: "Case 2024-118".to_string(),
+                timestamp: Utc::now(),
+            },
         ];
 
         for event in events {
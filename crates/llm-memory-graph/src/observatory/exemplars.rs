@@ -0,0 +1,329 @@
+//! Exemplars linking latency histogram buckets to traces
+//!
+//! A histogram bucket says how many operations were slow, not which ones.
+//! [`ExemplarStore`] keeps the most recent trace ID observed in each bucket of
+//! a histogram, and [`encode_openmetrics`] exports them in the OpenMetrics text
+//! format, where Prometheus stores them next to the bucket and Grafana links a
+//! slow bucket straight to its trace.
+//!
+//! Trace IDs come from the trace the current operation runs in. The gRPC
+//! service reads it from the caller's W3C `traceparent` header and records its
+//! latencies inside [`in_trace`]; any other caller can do the same. A
+//! [`TraceIdSource`], a function returning the current trace ID, replaces that
+//! lookup. With an OpenTelemetry tracing layer installed, the source returns
+//! the trace ID of the current span's context:
+//!
+//! ```ignore
+//! use opentelemetry::trace::TraceContextExt;
+//! use tracing_opentelemetry::OpenTelemetrySpanExt;
+//!
+//! metrics.set_trace_id_source(Some(Arc::new(|| {
+//!     let context = tracing::Span::current().context();
+//!     let span_context = context.span().span_context().clone();
+//!     span_context
+//!         .is_valid()
+//!         .then(|| span_context.trace_id().to_string())
+//! })));
+//! ```
+
+use crate::Result;
+use chrono::{DateTime, Utc};
+use parking_lot::{Mutex, RwLock};
+use prometheus::core::Collector;
+use prometheus::proto::{Bucket, MetricFamily, MetricType};
+use prometheus::{Histogram, TextEncoder};
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::sync::Arc;
+
+/// Exemplar label holding the trace ID
+pub const TRACE_ID_LABEL: &str = "trace_id";
+
+/// Content type of the OpenMetrics text format
+pub const OPENMETRICS_CONTENT_TYPE: &str =
+    "application/openmetrics-text; version=1.0.0; charset=utf-8";
+
+/// Request header carrying the W3C trace context
+pub const TRACEPARENT_HEADER: &str = "traceparent";
+
+/// Returns the trace ID of the operation being recorded, if it is traced
+pub type TraceIdSource = Arc<dyn Fn() -> Option<String> + Send + Sync>;
+
+tokio::task_local! {
+    static TRACE_ID: String;
+}
+
+/// Run `record` with `trace_id` as the trace of the observations it makes
+pub fn in_trace<R>(trace_id: impl Into<String>, record: impl FnOnce() -> R) -> R {
+    TRACE_ID.sync_scope(trace_id.into(), record)
+}
+
+/// The trace ID set by [`in_trace`] for the current task, if any
+pub fn scoped_trace_id() -> Option<String> {
+    TRACE_ID.try_with(Clone::clone).ok()
+}
+
+/// Trace ID of a W3C `traceparent` header value
+/// (`00-<trace id>-<parent id>-<flags>`), if it is well formed
+pub fn trace_id_from_traceparent(traceparent: &str) -> Option<String> {
+    let mut parts = traceparent.trim().split('-');
+    let version = parts.next()?;
+    let trace_id = parts.next()?;
+    let parent_id = parts.next()?;
+    parts.next()?;
+    let is_hex =
+        |part: &str, len: usize| part.len() == len && part.bytes().all(|b| b.is_ascii_hexdigit());
+    let valid = is_hex(version, 2)
+        && version != "ff"
+        && is_hex(trace_id, 32)
+        && is_hex(parent_id, 16)
+        && trace_id.bytes().any(|b| b != b'0');
+    valid.then(|| trace_id.to_ascii_lowercase())
+}
+
+/// One traced observation of a histogram
+#[derive(Debug, Clone, PartialEq)]
+pub struct Exemplar {
+    /// Trace the observation was made in
+    pub trace_id: String,
+    /// Observed value
+    pub value: f64,
+    /// When it was observed
+    pub timestamp: DateTime<Utc>,
+}
+
+struct TrackedHistogram {
+    bounds: Vec<f64>,
+    /// One slot per bucket, the last one for `+Inf`
+    slots: Vec<Option<Exemplar>>,
+}
+
+/// Latest exemplar of every bucket of the tracked histograms
+#[derive(Clone, Default)]
+pub struct ExemplarStore {
+    histograms: Arc<Mutex<HashMap<String, TrackedHistogram>>>,
+    trace_ids: Arc<RwLock<Option<TraceIdSource>>>,
+}
+
+impl ExemplarStore {
+    /// Create a store tracking no histograms
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keep exemplars for the buckets of `histogram`
+    pub fn track(&self, histogram: &Histogram) {
+        let Some(name) = histogram_name(histogram) else {
+            return;
+        };
+        let bounds: Vec<f64> = histogram
+            .collect()
+            .iter()
+            .flat_map(MetricFamily::get_metric)
+            .flat_map(|metric| metric.get_histogram().get_bucket())
+            .map(Bucket::get_upper_bound)
+            .filter(|bound| bound.is_finite())
+            .collect();
+        let slots = vec![None; bounds.len() + 1];
+        self.histograms
+            .lock()
+            .insert(name, TrackedHistogram { bounds, slots });
+    }
+
+    /// Set where trace IDs of recorded observations come from; `None` goes
+    /// back to the trace set by [`in_trace`]
+    pub fn set_trace_id_source(&self, source: Option<TraceIdSource>) {
+        *self.trace_ids.write() = source;
+    }
+
+    /// Trace ID of the current operation, if it is traced
+    pub fn current_trace_id(&self) -> Option<String> {
+        match self.trace_ids.read().as_ref() {
+            Some(source) => source(),
+            None => scoped_trace_id(),
+        }
+    }
+
+    /// Observe `value` in `histogram`, attaching the current trace ID as an
+    /// exemplar if there is one
+    pub fn observe(&self, histogram: &Histogram, value: f64) {
+        histogram.observe(value);
+        if let Some(trace_id) = self.current_trace_id() {
+            self.record(histogram, value, trace_id);
+        }
+    }
+
+    /// Attach `trace_id` as the exemplar of the bucket `value` falls in
+    ///
+    /// Does not observe `value`; untracked histograms are ignored.
+    pub fn record(&self, histogram: &Histogram, value: f64, trace_id: impl Into<String>) {
+        let Some(name) = histogram_name(histogram) else {
+            return;
+        };
+        let mut histograms = self.histograms.lock();
+        if let Some(tracked) = histograms.get_mut(&name) {
+            let bucket = tracked
+                .bounds
+                .iter()
+                .position(|bound| value <= *bound)
+                .unwrap_or(tracked.bounds.len());
+            tracked.slots[bucket] = Some(Exemplar {
+                trace_id: trace_id.into(),
+                value,
+                timestamp: Utc::now(),
+            });
+        }
+    }
+
+    /// Latest exemplar of the bucket of histogram `name` with upper bound
+    /// `le`
+    pub fn get(&self, name: &str, le: f64) -> Option<Exemplar> {
+        let histograms = self.histograms.lock();
+        let tracked = histograms.get(name)?;
+        let bucket = if le.is_infinite() {
+            tracked.bounds.len()
+        } else {
+            tracked.bounds.iter().position(|bound| *bound == le)?
+        };
+        tracked.slots[bucket].clone()
+    }
+}
+
+fn histogram_name(histogram: &Histogram) -> Option<String> {
+    histogram.desc().first().map(|desc| desc.fq_name.clone())
+}
+
+/// Encode `families` in the OpenMetrics text format with the exemplars held
+/// in `exemplars`
+///
+/// # Errors
+///
+/// Returns an error if the metrics cannot be encoded.
+pub fn encode_openmetrics(families: &[MetricFamily], exemplars: &ExemplarStore) -> Result<String> {
+    let types: HashMap<&str, MetricType> = families
+        .iter()
+        .map(|family| (family.get_name(), family.get_field_type()))
+        .collect();
+    let text = TextEncoder::new().encode_to_string(families)?;
+
+    let mut out = String::with_capacity(text.len());
+    for line in text.lines() {
+        if let Some(rest) = line.strip_prefix("# HELP ") {
+            let (name, help) = rest.split_once(' ').unwrap_or((rest, ""));
+            let name = family_name(name, types.get(name));
+            let _ = writeln!(out, "# HELP {name} {help}");
+        } else if let Some(rest) = line.strip_prefix("# TYPE ") {
+            let (name, kind) = rest.split_once(' ').unwrap_or((rest, ""));
+            let kind = match types.get(name) {
+                Some(MetricType::COUNTER) if !name.ends_with("_total") => "unknown",
+                Some(MetricType::UNTYPED) => "unknown",
+                _ => kind,
+            };
+            let name = family_name(name, types.get(name));
+            let _ = writeln!(out, "# TYPE {name} {kind}");
+        } else {
+            out.push_str(line);
+            if let Some(exemplar) = bucket_exemplar(line, exemplars) {
+                let _ = write!(
+                    out,
+                    " # {{{TRACE_ID_LABEL}=\"{}\"}} {} {}",
+                    escape_label_value(&exemplar.trace_id),
+                    exemplar.value,
+                    exemplar.timestamp.timestamp_millis() as f64 / 1000.0
+                );
+            }
+            out.push('\n');
+        }
+    }
+    out.push_str("# EOF\n");
+    Ok(out)
+}
+
+/// OpenMetrics names counter families without their `_total` suffix
+fn family_name<'a>(name: &'a str, kind: Option<&MetricType>) -> &'a str {
+    match kind {
+        Some(MetricType::COUNTER) => name.strip_suffix("_total").unwrap_or(name),
+        _ => name,
+    }
+}
+
+fn bucket_exemplar(line: &str, exemplars: &ExemplarStore) -> Option<Exemplar> {
+    let (name, labels) = line.split_once('{')?;
+    let name = name.strip_suffix("_bucket")?;
+    let le = labels.split("le=\"").nth(1)?.split('"').next()?;
+    let le = if le == "+Inf" {
+        f64::INFINITY
+    } else {
+        le.parse().ok()?
+    };
+    exemplars.get(name, le)
+}
+
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prometheus::{HistogramOpts, IntCounter, Registry};
+
+    #[test]
+    fn test_encode_openmetrics_with_exemplars() {
+        let registry = Registry::new();
+        let latency = Histogram::with_opts(
+            HistogramOpts::new("op_latency_seconds", "Latency").buckets(vec![0.1, 1.0]),
+        )
+        .unwrap();
+        registry.register(Box::new(latency.clone())).unwrap();
+        let ops = IntCounter::new("ops_total", "Operations").unwrap();
+        registry.register(Box::new(ops.clone())).unwrap();
+
+        let store = ExemplarStore::new();
+        store.track(&latency);
+        store.observe(&latency, 0.05);
+        assert!(store.get("op_latency_seconds", 0.1).is_none());
+
+        store.set_trace_id_source(Some(Arc::new(|| Some("4bf92f3577b34da6".to_string()))));
+        store.observe(&latency, 0.5);
+        store.record(&latency, 3.0, "00f067aa0ba902b7");
+        assert_eq!(latency.get_sample_count(), 2);
+
+        let text = encode_openmetrics(&registry.gather(), &store).unwrap();
+        assert!(text.contains("# TYPE ops counter\n"));
+        assert!(text.contains("ops_total 0\n"));
+        assert!(text.contains("op_latency_seconds_bucket{le=\"0.1\"} 1\n"));
+        assert!(text.contains(
+            "op_latency_seconds_bucket{le=\"1\"} 2 # {trace_id=\"4bf92f3577b34da6\"} 0.5 "
+        ));
+        assert!(text.contains(
+            "op_latency_seconds_bucket{le=\"+Inf\"} 2 # {trace_id=\"00f067aa0ba902b7\"} 3 "
+        ));
+        assert!(text.ends_with("# EOF\n"));
+    }
+
+    #[test]
+    fn test_trace_id_from_traceparent() {
+        assert_eq!(
+            trace_id_from_traceparent("00-4BF92F3577B34DA6A3CE929D0E0E4736-00f067aa0ba902b7-01")
+                .as_deref(),
+            Some("4bf92f3577b34da6a3ce929d0e0e4736")
+        );
+        assert!(trace_id_from_traceparent(
+            "00-00000000000000000000000000000000-00f067aa0ba902b7-01"
+        )
+        .is_none());
+        assert!(trace_id_from_traceparent("00-4bf92f35-00f067aa0ba902b7-01").is_none());
+        assert!(trace_id_from_traceparent("not a traceparent").is_none());
+
+        let store = ExemplarStore::new();
+        assert_eq!(store.current_trace_id(), None);
+        assert_eq!(
+            in_trace("abc", || store.current_trace_id()).as_deref(),
+            Some("abc")
+        );
+    }
+}
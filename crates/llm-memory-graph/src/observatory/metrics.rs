@@ -1,8 +1,49 @@
 //! Metrics collection for memory graph operations
-
+//!
+//! [`MemoryGraphMetrics`] counts operations and records read/write latencies
+//! in HDR histograms. A [`MetricsSnapshot`] carries the counters together with
+//! latency percentiles and serializes to JSON, so embedders without Prometheus
+//! can ship it as is; [`MetricsSnapshot::diff`] turns two snapshots into
+//! per-second rates.
+//!
+//! # Examples
+//!
+//! ```
+//! use llm_memory_graph::observatory::MemoryGraphMetrics;
+//!
+//! let metrics = MemoryGraphMetrics::new();
+//! let before = metrics.snapshot();
+//!
+//! metrics.record_node_created();
+//! metrics.record_write_latency_us(1_500);
+//!
+//! let after = metrics.snapshot();
+//! let delta = after.diff(&before);
+//! assert_eq!(delta.nodes_created.count, 1);
+//! println!("p99 write latency: {} ms", after.write_latency.p99_ms);
+//! println!("{}", after.to_json());
+//! ```
+
+use chrono::{DateTime, Utc};
+use hdrhistogram::Histogram;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
 use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
 use std::sync::Arc;
 
+/// Highest latency the histograms resolve (60 seconds); slower operations are
+/// recorded as this value
+const MAX_TRACKED_LATENCY_US: u64 = 60_000_000;
+
+/// Significant decimal digits kept by the latency histograms
+const HISTOGRAM_PRECISION: u8 = 3;
+
+fn latency_histogram() -> Arc<Mutex<Histogram<u64>>> {
+    let histogram = Histogram::new_with_bounds(1, MAX_TRACKED_LATENCY_US, HISTOGRAM_PRECISION)
+        .expect("latency histogram bounds are valid");
+    Arc::new(Mutex::new(histogram))
+}
+
 /// Metrics collector for memory graph operations
 #[derive(Clone)]
 pub struct MemoryGraphMetrics {
@@ -10,15 +51,19 @@ pub struct MemoryGraphMetrics {
     nodes_created: Arc<AtomicUsize>,
     edges_created: Arc<AtomicUsize>,
     prompts_submitted: Arc<AtomicUsize>,
+    prompts_deduplicated: Arc<AtomicUsize>,
     responses_generated: Arc<AtomicUsize>,
     tools_invoked: Arc<AtomicUsize>,
     queries_executed: Arc<AtomicUsize>,
+    anomalies_detected: Arc<AtomicUsize>,
 
     // Latency tracking (in microseconds for precision)
     total_write_latency_us: Arc<AtomicU64>,
     write_count: Arc<AtomicUsize>,
     total_read_latency_us: Arc<AtomicU64>,
     read_count: Arc<AtomicUsize>,
+    write_latency: Arc<Mutex<Histogram<u64>>>,
+    read_latency: Arc<Mutex<Histogram<u64>>>,
 }
 
 impl MemoryGraphMetrics {
@@ -28,13 +73,17 @@ impl MemoryGraphMetrics {
             nodes_created: Arc::new(AtomicUsize::new(0)),
             edges_created: Arc::new(AtomicUsize::new(0)),
             prompts_submitted: Arc::new(AtomicUsize::new(0)),
+            prompts_deduplicated: Arc::new(AtomicUsize::new(0)),
             responses_generated: Arc::new(AtomicUsize::new(0)),
             tools_invoked: Arc::new(AtomicUsize::new(0)),
             queries_executed: Arc::new(AtomicUsize::new(0)),
+            anomalies_detected: Arc::new(AtomicUsize::new(0)),
             total_write_latency_us: Arc::new(AtomicU64::new(0)),
             write_count: Arc::new(AtomicUsize::new(0)),
             total_read_latency_us: Arc::new(AtomicU64::new(0)),
             read_count: Arc::new(AtomicUsize::new(0)),
+            write_latency: latency_histogram(),
+            read_latency: latency_histogram(),
         }
     }
 
@@ -53,6 +102,11 @@ impl MemoryGraphMetrics {
         self.prompts_submitted.fetch_add(1, Ordering::Relaxed);
     }
 
+    /// Record a prompt that was deduplicated against an identical one
+    pub fn record_prompt_deduplicated(&self) {
+        self.prompts_deduplicated.fetch_add(1, Ordering::Relaxed);
+    }
+
     /// Record a response generation
     pub fn record_response_generated(&self) {
         self.responses_generated.fetch_add(1, Ordering::Relaxed);
@@ -68,11 +122,17 @@ impl MemoryGraphMetrics {
         self.queries_executed.fetch_add(1, Ordering::Relaxed);
     }
 
+    /// Record an anomaly found by the anomaly detector
+    pub fn record_anomaly_detected(&self) {
+        self.anomalies_detected.fetch_add(1, Ordering::Relaxed);
+    }
+
     /// Record write latency in microseconds
     pub fn record_write_latency_us(&self, latency_us: u64) {
         self.total_write_latency_us
             .fetch_add(latency_us, Ordering::Relaxed);
         self.write_count.fetch_add(1, Ordering::Relaxed);
+        self.write_latency.lock().saturating_record(latency_us);
     }
 
     /// Record read latency in microseconds
@@ -80,6 +140,7 @@ impl MemoryGraphMetrics {
         self.total_read_latency_us
             .fetch_add(latency_us, Ordering::Relaxed);
         self.read_count.fetch_add(1, Ordering::Relaxed);
+        self.read_latency.lock().saturating_record(latency_us);
     }
 
     /// Get a snapshot of current metrics
@@ -103,11 +164,18 @@ impl MemoryGraphMetrics {
             nodes_created: self.nodes_created.load(Ordering::Relaxed),
             edges_created: self.edges_created.load(Ordering::Relaxed),
             prompts_submitted: self.prompts_submitted.load(Ordering::Relaxed),
+            prompts_deduplicated: self.prompts_deduplicated.load(Ordering::Relaxed),
             responses_generated: self.responses_generated.load(Ordering::Relaxed),
             tools_invoked: self.tools_invoked.load(Ordering::Relaxed),
             queries_executed: self.queries_executed.load(Ordering::Relaxed),
+            anomalies_detected: self.anomalies_detected.load(Ordering::Relaxed),
+            write_count,
+            read_count,
             avg_write_latency_ms: avg_write_latency_us / 1000.0,
             avg_read_latency_ms: avg_read_latency_us / 1000.0,
+            write_latency: LatencyPercentiles::from_histogram(&self.write_latency.lock()),
+            read_latency: LatencyPercentiles::from_histogram(&self.read_latency.lock()),
+            taken_at: Utc::now(),
         }
     }
 
@@ -116,13 +184,17 @@ impl MemoryGraphMetrics {
         self.nodes_created.store(0, Ordering::Relaxed);
         self.edges_created.store(0, Ordering::Relaxed);
         self.prompts_submitted.store(0, Ordering::Relaxed);
+        self.prompts_deduplicated.store(0, Ordering::Relaxed);
         self.responses_generated.store(0, Ordering::Relaxed);
         self.tools_invoked.store(0, Ordering::Relaxed);
         self.queries_executed.store(0, Ordering::Relaxed);
+        self.anomalies_detected.store(0, Ordering::Relaxed);
         self.total_write_latency_us.store(0, Ordering::Relaxed);
         self.write_count.store(0, Ordering::Relaxed);
         self.total_read_latency_us.store(0, Ordering::Relaxed);
         self.read_count.store(0, Ordering::Relaxed);
+        self.write_latency.lock().reset();
+        self.read_latency.lock().reset();
     }
 }
 
@@ -132,8 +204,40 @@ impl Default for MemoryGraphMetrics {
     }
 }
 
+/// Latency distribution of an operation, in milliseconds
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct LatencyPercentiles {
+    /// Number of recorded operations
+    pub count: u64,
+    /// Median latency
+    pub p50_ms: f64,
+    /// 95th percentile latency
+    pub p95_ms: f64,
+    /// 99th percentile latency
+    pub p99_ms: f64,
+    /// Slowest recorded latency
+    pub max_ms: f64,
+}
+
+impl LatencyPercentiles {
+    /// Percentiles of a histogram of microsecond latencies
+    pub fn from_histogram(histogram: &Histogram<u64>) -> Self {
+        if histogram.is_empty() {
+            return Self::default();
+        }
+        let ms = |us: u64| us as f64 / 1000.0;
+        Self {
+            count: histogram.len(),
+            p50_ms: ms(histogram.value_at_quantile(0.50)),
+            p95_ms: ms(histogram.value_at_quantile(0.95)),
+            p99_ms: ms(histogram.value_at_quantile(0.99)),
+            max_ms: ms(histogram.max()),
+        }
+    }
+}
+
 /// Snapshot of metrics at a point in time
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MetricsSnapshot {
     /// Total nodes created
     pub nodes_created: usize,
@@ -141,16 +245,112 @@ pub struct MetricsSnapshot {
     pub edges_created: usize,
     /// Total prompts submitted
     pub prompts_submitted: usize,
+    /// Total prompts deduplicated against an identical prompt
+    pub prompts_deduplicated: usize,
     /// Total responses generated
     pub responses_generated: usize,
     /// Total tools invoked
     pub tools_invoked: usize,
     /// Total queries executed
     pub queries_executed: usize,
+    /// Total anomalies detected
+    pub anomalies_detected: usize,
+    /// Total writes with a recorded latency
+    pub write_count: usize,
+    /// Total reads with a recorded latency
+    pub read_count: usize,
     /// Average write latency in milliseconds
     pub avg_write_latency_ms: f64,
     /// Average read latency in milliseconds
     pub avg_read_latency_ms: f64,
+    /// Write latency percentiles since creation or the last reset
+    pub write_latency: LatencyPercentiles,
+    /// Read latency percentiles since creation or the last reset
+    pub read_latency: LatencyPercentiles,
+    /// When the snapshot was taken
+    pub taken_at: DateTime<Utc>,
+}
+
+impl MetricsSnapshot {
+    /// Change in counters since `previous`, with per-second rates
+    ///
+    /// Counters that went down (because the metrics were reset in between)
+    /// report zero.
+    pub fn diff(&self, previous: &MetricsSnapshot) -> MetricsDelta {
+        let interval_secs = (self.taken_at - previous.taken_at)
+            .to_std()
+            .map(|d| d.as_secs_f64())
+            .unwrap_or(0.0);
+        let delta = |current: usize, previous: usize| {
+            CounterDelta::new(current.saturating_sub(previous), interval_secs)
+        };
+
+        MetricsDelta {
+            interval_secs,
+            nodes_created: delta(self.nodes_created, previous.nodes_created),
+            edges_created: delta(self.edges_created, previous.edges_created),
+            prompts_submitted: delta(self.prompts_submitted, previous.prompts_submitted),
+            prompts_deduplicated: delta(self.prompts_deduplicated, previous.prompts_deduplicated),
+            responses_generated: delta(self.responses_generated, previous.responses_generated),
+            tools_invoked: delta(self.tools_invoked, previous.tools_invoked),
+            queries_executed: delta(self.queries_executed, previous.queries_executed),
+            anomalies_detected: delta(self.anomalies_detected, previous.anomalies_detected),
+            writes: delta(self.write_count, previous.write_count),
+            reads: delta(self.read_count, previous.read_count),
+        }
+    }
+
+    /// The snapshot as a JSON value
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::to_value(self).unwrap_or(serde_json::Value::Null)
+    }
+}
+
+/// Change in one counter over an interval
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct CounterDelta {
+    /// Increase over the interval
+    pub count: usize,
+    /// Average increase per second
+    pub per_sec: f64,
+}
+
+impl CounterDelta {
+    fn new(count: usize, interval_secs: f64) -> Self {
+        let per_sec = if interval_secs > 0.0 {
+            count as f64 / interval_secs
+        } else {
+            0.0
+        };
+        Self { count, per_sec }
+    }
+}
+
+/// Difference between two [`MetricsSnapshot`]s
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MetricsDelta {
+    /// Seconds between the snapshots
+    pub interval_secs: f64,
+    /// Nodes created
+    pub nodes_created: CounterDelta,
+    /// Edges created
+    pub edges_created: CounterDelta,
+    /// Prompts submitted
+    pub prompts_submitted: CounterDelta,
+    /// Prompts deduplicated
+    pub prompts_deduplicated: CounterDelta,
+    /// Responses generated
+    pub responses_generated: CounterDelta,
+    /// Tools invoked
+    pub tools_invoked: CounterDelta,
+    /// Queries executed
+    pub queries_executed: CounterDelta,
+    /// Anomalies detected
+    pub anomalies_detected: CounterDelta,
+    /// Writes with a recorded latency
+    pub writes: CounterDelta,
+    /// Reads with a recorded latency
+    pub reads: CounterDelta,
 }
 
 #[cfg(test)]
@@ -175,11 +375,13 @@ mod tests {
         metrics.record_node_created();
         metrics.record_edge_created();
         metrics.record_prompt_submitted();
+        metrics.record_prompt_deduplicated();
 
         let snapshot = metrics.snapshot();
         assert_eq!(snapshot.nodes_created, 2);
         assert_eq!(snapshot.edges_created, 1);
         assert_eq!(snapshot.prompts_submitted, 1);
+        assert_eq!(snapshot.prompts_deduplicated, 1);
     }
 
     #[test]
@@ -195,6 +397,57 @@ mod tests {
         assert_eq!(snapshot.avg_write_latency_ms, 2.0); // Average of 1, 2, 3 ms
     }
 
+    #[test]
+    fn test_latency_percentiles() {
+        let metrics = MemoryGraphMetrics::new();
+        for latency_ms in 1..=100 {
+            metrics.record_read_latency_us(latency_ms * 1000);
+        }
+
+        let read = metrics.snapshot().read_latency;
+        assert_eq!(read.count, 100);
+        // HDR histograms keep three significant digits
+        assert!((read.p50_ms - 50.0).abs() < 0.1, "p50 {}", read.p50_ms);
+        assert!((read.p95_ms - 95.0).abs() < 0.1, "p95 {}", read.p95_ms);
+        assert!((read.p99_ms - 99.0).abs() < 0.1, "p99 {}", read.p99_ms);
+        assert!((read.max_ms - 100.0).abs() < 0.1, "max {}", read.max_ms);
+        assert_eq!(
+            metrics.snapshot().write_latency,
+            LatencyPercentiles::default()
+        );
+
+        metrics.reset();
+        assert_eq!(metrics.snapshot().read_latency.count, 0);
+    }
+
+    #[test]
+    fn test_snapshot_diff_and_json() {
+        let metrics = MemoryGraphMetrics::new();
+        metrics.record_node_created();
+        let mut previous = metrics.snapshot();
+        previous.taken_at -= chrono::Duration::seconds(2);
+
+        for _ in 0..4 {
+            metrics.record_node_created();
+            metrics.record_write_latency_us(250);
+        }
+        let current = metrics.snapshot();
+        let delta = current.diff(&previous);
+
+        assert_eq!(delta.nodes_created.count, 4);
+        assert!((delta.nodes_created.per_sec - 2.0).abs() < 0.01);
+        assert_eq!(delta.writes.count, 4);
+        assert_eq!(delta.edges_created, CounterDelta::default());
+        // A reset between snapshots does not produce negative counts
+        assert_eq!(previous.diff(&current).nodes_created.count, 0);
+
+        let json = current.to_json();
+        assert_eq!(json["nodes_created"], 5);
+        assert_eq!(json["write_latency"]["count"], 4);
+        let parsed: MetricsSnapshot = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed.write_latency, current.write_latency);
+    }
+
     #[test]
     fn test_metrics_reset() {
         let metrics = MemoryGraphMetrics::new();
@@ -8,6 +8,7 @@
 //!
 //! - **Event Streaming**: Publish events for all graph operations
 //! - **Metrics Collection**: Track performance and usage metrics
+//! - **Anomaly Detection**: Flag token spikes, failing tools and latency regressions
 //! - **Pluggable Publishers**: Implement custom event publishers
 //! - **In-Memory Testing**: Built-in publisher for development and testing
 //!
@@ -48,25 +49,38 @@
 //! }
 //! ```
 
+pub mod anomaly;
 pub mod config;
 pub mod emitter;
 pub mod events;
+pub mod exemplars;
 pub mod kafka;
 pub mod metrics;
 pub mod prometheus;
 pub mod publisher;
+pub mod sampling;
 pub mod streaming;
 
+pub use anomaly::{Anomaly, AnomalyDetector, AnomalyKind, AnomalyRule};
 pub use config::ObservatoryConfig;
-pub use emitter::{AsyncEventEmitter, EmissionStatsSnapshot};
+pub use emitter::{
+    AsyncEventEmitter, EmissionStatsSnapshot, OverflowPolicy, DEFAULT_QUEUE_CAPACITY,
+};
 pub use events::MemoryGraphEvent;
+pub use exemplars::{
+    encode_openmetrics, Exemplar, ExemplarStore, TraceIdSource, OPENMETRICS_CONTENT_TYPE,
+    TRACE_ID_LABEL,
+};
 pub use kafka::{
     BatchingKafkaProducer, KafkaConfig, KafkaProducer, MockKafkaProducer, ProducerStats,
 };
-pub use metrics::{MemoryGraphMetrics, MetricsSnapshot};
+pub use metrics::{
+    CounterDelta, LatencyPercentiles, MemoryGraphMetrics, MetricsDelta, MetricsSnapshot,
+};
 pub use prometheus::{
-    GrpcMetricsSnapshot, MetricsCounterSnapshot, MetricsGaugeSnapshot, PrometheusMetrics,
-    VaultMetricsSnapshot,
+    GrpcMetricsSnapshot, LabeledMetrics, MetricLabels, MetricsCounterSnapshot,
+    MetricsGaugeSnapshot, PrometheusMetrics, VaultMetricsSnapshot,
 };
 pub use publisher::{EventPublisher, InMemoryPublisher, NoOpPublisher};
+pub use sampling::{SamplingConfig, SAMPLE_RATE_METADATA_KEY};
 pub use streaming::{EventStream, InMemoryEventStream, MultiEventStream};
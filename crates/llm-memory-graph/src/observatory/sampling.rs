@@ -0,0 +1,184 @@
+//! Event sampling for high-throughput deployments
+//!
+//! A [`SamplingConfig`] assigns each event type a sample rate between `0.0`
+//! (drop every event) and `1.0` (keep every event). The
+//! [`AsyncEventEmitter`](super::AsyncEventEmitter) applies it before queueing;
+//! kept events that carry metadata record the rate under
+//! [`SAMPLE_RATE_METADATA_KEY`] so consumers can re-weight counts. Events that
+//! describe a failure are kept regardless of rate unless
+//! [`always_sample_errors`](SamplingConfig::always_sample_errors) is turned off.
+//!
+//! # Examples
+//!
+//! ```
+//! use llm_memory_graph::observatory::{ObservatoryConfig, SamplingConfig};
+//!
+//! let config = ObservatoryConfig::new().enabled().with_sampling(
+//!     SamplingConfig::new()
+//!         .with_rate("node_created", 0.1)
+//!         .with_rate("query_executed", 0.01),
+//! );
+//! ```
+
+use super::events::MemoryGraphEvent;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Metadata key holding the rate an event was sampled at
+pub const SAMPLE_RATE_METADATA_KEY: &str = "sample_rate";
+
+/// Per-event-type sample rates
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SamplingConfig {
+    /// Rate for event types without an entry in `rates`
+    #[serde(default = "full_rate")]
+    pub default_rate: f64,
+
+    /// Rates keyed by [`MemoryGraphEvent::event_type`]
+    #[serde(default)]
+    pub rates: HashMap<String, f64>,
+
+    /// Keep every event that describes a failure
+    #[serde(default = "always")]
+    pub always_sample_errors: bool,
+}
+
+fn full_rate() -> f64 {
+    1.0
+}
+
+fn always() -> bool {
+    true
+}
+
+impl Default for SamplingConfig {
+    fn default() -> Self {
+        Self {
+            default_rate: 1.0,
+            rates: HashMap::new(),
+            always_sample_errors: true,
+        }
+    }
+}
+
+impl SamplingConfig {
+    /// A configuration that keeps every event
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the rate for event types without an explicit rate
+    pub fn with_default_rate(mut self, rate: f64) -> Self {
+        self.default_rate = rate;
+        self
+    }
+
+    /// Set the rate for one event type (such as `"node_created"`)
+    pub fn with_rate(mut self, event_type: impl Into<String>, rate: f64) -> Self {
+        self.rates.insert(event_type.into(), rate);
+        self
+    }
+
+    /// Keep or sample failure events
+    pub fn with_always_sample_errors(mut self, always: bool) -> Self {
+        self.always_sample_errors = always;
+        self
+    }
+
+    /// Whether any event can be dropped
+    pub fn is_sampling(&self) -> bool {
+        self.default_rate < 1.0 || self.rates.values().any(|rate| *rate < 1.0)
+    }
+
+    /// Rate applied to `event`, clamped to `0.0..=1.0`
+    pub fn rate_for(&self, event: &MemoryGraphEvent) -> f64 {
+        if self.always_sample_errors && event.is_error() {
+            return 1.0;
+        }
+        self.rates
+            .get(event.event_type())
+            .copied()
+            .unwrap_or(self.default_rate)
+            .clamp(0.0, 1.0)
+    }
+
+    /// Decide whether to keep `event`, recording the rate in its metadata
+    ///
+    /// Returns `false` if the event should be dropped.
+    pub fn sample(&self, event: &mut MemoryGraphEvent) -> bool {
+        if !self.is_sampling() {
+            return true;
+        }
+        let rate = self.rate_for(event);
+        if rate < 1.0 && !rand::thread_rng().gen_bool(rate) {
+            return false;
+        }
+        if let Some(metadata) = event.metadata_mut() {
+            metadata.insert(SAMPLE_RATE_METADATA_KEY.to_string(), rate.to_string());
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{NodeId, NodeType};
+    use chrono::Utc;
+
+    fn node_created() -> MemoryGraphEvent {
+        MemoryGraphEvent::NodeCreated {
+            node_id: NodeId::new(),
+            node_type: NodeType::Prompt,
+            session_id: None,
+            timestamp: Utc::now(),
+            metadata: HashMap::new(),
+        }
+    }
+
+    fn tool_invoked(success: bool) -> MemoryGraphEvent {
+        MemoryGraphEvent::ToolInvoked {
+            tool_id: NodeId::new(),
+            tool_name: "search".to_string(),
+            success,
+            duration_ms: 5,
+            timestamp: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_rates_and_errors() {
+        let config = SamplingConfig::new()
+            .with_default_rate(0.0)
+            .with_rate("node_created", 1.0);
+
+        let mut event = node_created();
+        assert!(config.sample(&mut event));
+        if let MemoryGraphEvent::NodeCreated { metadata, .. } = &event {
+            assert_eq!(metadata[SAMPLE_RATE_METADATA_KEY], "1");
+        }
+        assert!(!config.sample(&mut tool_invoked(true)));
+        assert!(config.sample(&mut tool_invoked(false)));
+
+        let config = config.with_always_sample_errors(false);
+        assert!(!config.sample(&mut tool_invoked(false)));
+        assert_eq!(config.rate_for(&node_created()), 1.0);
+    }
+
+    #[test]
+    fn test_partial_rate() {
+        let config = SamplingConfig::new().with_rate("node_created", 0.5);
+        let kept = (0..2000)
+            .filter(|_| config.sample(&mut node_created()))
+            .count();
+        assert!((800..1200).contains(&kept), "kept {}", kept);
+
+        // Without sampling, metadata is left untouched
+        let mut event = node_created();
+        assert!(SamplingConfig::new().sample(&mut event));
+        if let MemoryGraphEvent::NodeCreated { metadata, .. } = &event {
+            assert!(metadata.is_empty());
+        }
+    }
+}
@@ -0,0 +1,236 @@
+//! Cursor-based pagination of listings
+//!
+//! Sessions, a session's nodes, a node's edges, templates and agents are
+//! listed a [`Page`] at a time. Each page carries an opaque [`PageCursor`]
+//! naming the last item it holds, and the next page starts after that item
+//! rather than at an offset, so items written or deleted while a client pages
+//! through a listing do not shift the pages it has yet to fetch: nothing is
+//! repeated or skipped. Items are listed oldest first, so those created while
+//! paging show up on later pages; a node's edges are listed in the order of
+//! its edge index.
+//!
+//! Cursors are URL-safe strings that clients pass back unchanged; their
+//! contents are not part of the API.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use llm_memory_graph::engine::AsyncMemoryGraph;
+//! use llm_memory_graph::Config;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let graph = AsyncMemoryGraph::open(Config::default()).await?;
+//!
+//! let mut cursor = None;
+//! loop {
+//!     let page = graph.list_sessions_page(cursor.as_ref(), 100).await?;
+//!     for session in &page.items {
+//!         println!("{}", session.id);
+//!     }
+//!     match page.next_cursor {
+//!         Some(next) => cursor = Some(next),
+//!         None => break,
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{AgentNode, ConversationSession, Edge, Node, PromptTemplate};
+use crate::{Error, Result};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD as BASE64;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Page size used when a request does not ask for one
+pub const DEFAULT_PAGE_SIZE: usize = 100;
+
+/// Opaque position in a listing, just after the item it was taken from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub struct PageCursor {
+    timestamp: DateTime<Utc>,
+    id: [u8; 16],
+}
+
+impl PageCursor {
+    fn new(timestamp: DateTime<Utc>, id: [u8; 16]) -> Self {
+        Self { timestamp, id }
+    }
+
+    /// ID bytes of the item the cursor was taken from
+    pub(crate) fn id_bytes(&self) -> [u8; 16] {
+        self.id
+    }
+}
+
+impl fmt::Display for PageCursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut bytes = [0u8; 24];
+        let nanos = self.timestamp.timestamp_nanos_opt().unwrap_or_default();
+        bytes[..8].copy_from_slice(&nanos.to_be_bytes());
+        bytes[8..].copy_from_slice(&self.id);
+        f.write_str(&BASE64.encode(bytes))
+    }
+}
+
+impl FromStr for PageCursor {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || Error::ValidationError(format!("invalid page cursor {s:?}"));
+        let bytes = BASE64.decode(s).map_err(|_| invalid())?;
+        if bytes.len() != 24 {
+            return Err(invalid());
+        }
+        let mut nanos = [0u8; 8];
+        nanos.copy_from_slice(&bytes[..8]);
+        let mut id = [0u8; 16];
+        id.copy_from_slice(&bytes[8..]);
+        Ok(Self::new(
+            DateTime::from_timestamp_nanos(i64::from_be_bytes(nanos)),
+            id,
+        ))
+    }
+}
+
+impl From<PageCursor> for String {
+    fn from(cursor: PageCursor) -> Self {
+        cursor.to_string()
+    }
+}
+
+impl TryFrom<String> for PageCursor {
+    type Error = Error;
+
+    fn try_from(s: String) -> Result<Self> {
+        s.parse()
+    }
+}
+
+/// Items that can be listed a page at a time
+pub trait Paginated {
+    /// Cursor pointing just after this item
+    fn cursor(&self) -> PageCursor;
+}
+
+impl Paginated for ConversationSession {
+    fn cursor(&self) -> PageCursor {
+        PageCursor::new(self.created_at, *self.id.as_uuid().as_bytes())
+    }
+}
+
+impl Paginated for Node {
+    fn cursor(&self) -> PageCursor {
+        PageCursor::new(self.created_at(), self.id().to_bytes())
+    }
+}
+
+impl Paginated for Edge {
+    fn cursor(&self) -> PageCursor {
+        PageCursor::new(self.created_at, self.id.to_bytes())
+    }
+}
+
+impl Paginated for PromptTemplate {
+    fn cursor(&self) -> PageCursor {
+        PageCursor::new(self.created_at, *self.id.as_uuid().as_bytes())
+    }
+}
+
+impl Paginated for AgentNode {
+    fn cursor(&self) -> PageCursor {
+        PageCursor::new(self.created_at, *self.id.as_uuid().as_bytes())
+    }
+}
+
+/// One page of a listing
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Page<T> {
+    /// Items in this page, in listing order
+    pub items: Vec<T>,
+    /// Cursor for the next page, or `None` if this is the last page
+    pub next_cursor: Option<PageCursor>,
+}
+
+impl<T: Paginated> Page<T> {
+    /// Page of `items` following `cursor`
+    ///
+    /// A `limit` of zero is treated as one.
+    pub(crate) fn collect(mut items: Vec<T>, cursor: Option<&PageCursor>, limit: usize) -> Self {
+        let limit = limit.max(1);
+        if let Some(cursor) = cursor {
+            items.retain(|item| item.cursor() > *cursor);
+        }
+        items.sort_by_key(Paginated::cursor);
+        let next_cursor = (items.len() > limit).then(|| items[limit - 1].cursor());
+        items.truncate(limit);
+        Self { items, next_cursor }
+    }
+}
+
+impl<T> Page<T> {
+    /// Convert every item of the page, keeping its cursor
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> Page<U> {
+        Page {
+            items: self.items.into_iter().map(f).collect(),
+            next_cursor: self.next_cursor,
+        }
+    }
+}
+
+/// Direction of the edges listed for a node
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EdgeDirection {
+    /// Edges from the node
+    Outgoing,
+    /// Edges to the node
+    Incoming,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_page_cursor() {
+        let now = Utc::now();
+        let sessions: Vec<ConversationSession> = (0..5)
+            .map(|i| {
+                let mut session = ConversationSession::new();
+                session.created_at = now + chrono::Duration::seconds(i);
+                session
+            })
+            .collect();
+
+        let page = Page::collect(sessions.clone(), None, 2);
+        assert_eq!(page.items[0].id, sessions[0].id);
+        let cursor = page.next_cursor.unwrap();
+        let parsed: PageCursor = cursor.to_string().parse().unwrap();
+        assert_eq!(parsed, cursor);
+        assert!(cursor
+            .to_string()
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_'));
+
+        // A session inserted before the cursor does not shift later pages
+        let mut more = sessions.clone();
+        let mut early = ConversationSession::new();
+        early.created_at = now - chrono::Duration::seconds(10);
+        more.push(early);
+        let page = Page::collect(more, Some(&parsed), 2);
+        let ids: Vec<_> = page.items.iter().map(|s| s.id).collect();
+        assert_eq!(ids, vec![sessions[2].id, sessions[3].id]);
+
+        let last = Page::collect(sessions.clone(), page.next_cursor.as_ref(), 2);
+        assert_eq!(last.items.len(), 1);
+        assert_eq!(last.next_cursor, None);
+
+        assert!("not a cursor".parse::<PageCursor>().is_err());
+        assert!("AAAA".parse::<PageCursor>().is_err());
+    }
+}
@@ -8,6 +8,7 @@
 //! - Version compatibility checking
 
 use super::{Plugin, PluginContext, PluginError, PluginMetadata};
+use crate::health::ComponentHealth;
 use std::collections::HashMap;
 use std::path::Path;
 use std::sync::Arc;
@@ -275,6 +276,34 @@ impl PluginManager {
         self.plugins.get(name).map(|wrapper| wrapper.state)
     }
 
+    /// Health of the plugin system
+    ///
+    /// Degraded while any plugin is in the [`PluginState::Error`] state.
+    pub fn health(&self) -> ComponentHealth {
+        let mut failed: Vec<&str> = self
+            .plugins
+            .iter()
+            .filter(|(_, wrapper)| wrapper.state == PluginState::Error)
+            .map(|(name, _)| name.as_str())
+            .collect();
+        failed.sort_unstable();
+        let enabled = self
+            .plugins
+            .values()
+            .filter(|wrapper| wrapper.state == PluginState::Enabled)
+            .count();
+
+        let health = if failed.is_empty() {
+            ComponentHealth::healthy("plugins")
+        } else {
+            ComponentHealth::degraded("plugins", format!("failed: {}", failed.join(", ")))
+        };
+        health
+            .with_detail("registered", self.plugins.len())
+            .with_detail("enabled", enabled)
+            .with_detail("failed", failed.len())
+    }
+
     /// Check if a plugin is enabled
     pub fn is_enabled(&self, name: &str) -> bool {
         self.plugins
@@ -538,4 +567,29 @@ mod tests {
         assert_eq!(active.len(), 1);
         assert_eq!(active[0].metadata().name, "plugin1");
     }
+
+    #[tokio::test]
+    async fn test_plugin_health() {
+        use crate::health::HealthStatus;
+
+        let mut manager = PluginManager::new();
+        manager
+            .register(Arc::new(MockPlugin::new("plugin1")))
+            .unwrap();
+        manager
+            .register(Arc::new(MockPlugin::new("plugin2")))
+            .unwrap();
+        manager.init_all().await.unwrap();
+        manager.enable("plugin1").unwrap();
+
+        let health = manager.health();
+        assert_eq!(health.status, HealthStatus::Healthy);
+        assert_eq!(health.details["registered"], "2");
+        assert_eq!(health.details["enabled"], "1");
+
+        manager.plugins.get_mut("plugin2").unwrap().state = PluginState::Error;
+        let health = manager.health();
+        assert_eq!(health.status, HealthStatus::Degraded);
+        assert_eq!(health.message.as_deref(), Some("failed: plugin2"));
+    }
 }
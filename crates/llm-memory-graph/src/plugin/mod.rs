@@ -10,6 +10,10 @@
 //! - **Auditing**: Custom audit logging and compliance tracking
 //! - **Integration**: External system integration
 //!
+//! [`PromptInjectionPlugin`] ships built in and screens prompts for injection
+//! attempts, see [`prompt_injection`]. [`WebhookPlugin`] posts events and
+//! reports to an HTTP endpoint, see [`webhook`].
+//!
 //! # Architecture
 //!
 //! The plugin system is designed around these core concepts:
@@ -61,11 +65,15 @@ use std::fmt;
 
 pub mod hooks;
 pub mod manager;
+pub mod prompt_injection;
 pub mod registry;
+pub mod webhook;
 
 pub use hooks::{HookExecutor, HookPoint, HookRegistry};
 pub use manager::PluginManager;
+pub use prompt_injection::{InjectionAction, PromptInjectionPlugin};
 pub use registry::{PluginDiscovery, PluginRegistry};
+pub use webhook::WebhookPlugin;
 
 /// Plugin error type
 #[derive(Debug, thiserror::Error)]
@@ -424,8 +424,7 @@ impl PluginCatalog {
 
     /// Sort by registration time
     pub fn sort_by_time(mut self) -> Self {
-        self.entries
-            .sort_by(|a, b| a.registered_at.cmp(&b.registered_at));
+        self.entries.sort_by_key(|e| e.registered_at);
         self
     }
 
@@ -0,0 +1,269 @@
+//! Built-in webhook delivery
+//!
+//! [`WebhookPlugin`] posts JSON events to an HTTP endpoint, such as a chat
+//! channel's incoming webhook or an internal service. Other parts of the
+//! crate hand it events directly through [`WebhookPlugin::post`], as the
+//! [template usage reports](crate::template_report) do; registered with a
+//! [`PluginManager`](super::PluginManager) it also forwards the after-hooks it
+//! is subscribed to.
+//!
+//! Each request body is a [`WebhookEvent`]:
+//!
+//! ```json
+//! {"event": "template_usage_report", "sent_at": "2024-05-01T00:00:00Z", "payload": {}}
+//! ```
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use llm_memory_graph::plugin::webhook::WebhookPlugin;
+//! use llm_memory_graph::plugin::HookPoint;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let webhook = WebhookPlugin::new("https://hooks.example.com/llm")?
+//!     .with_header("Authorization", "Bearer secret")
+//!     .with_hook(HookPoint::AfterCreateSession);
+//! webhook
+//!     .post("deploy", &serde_json::json!({"version": "1.2.0"}))
+//!     .await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use super::{HookPoint, Plugin, PluginBuilder, PluginContext, PluginError, PluginMetadata};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::time::Duration;
+
+/// Name the plugin registers under
+pub const PLUGIN_NAME: &str = "webhook";
+
+/// How long a delivery may take before it fails
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Body of every request the plugin sends
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WebhookEvent {
+    /// What happened, such as a hook name or a report type
+    pub event: String,
+    /// When the event was sent
+    pub sent_at: DateTime<Utc>,
+    /// Event details
+    pub payload: Value,
+}
+
+/// Posts events to a webhook endpoint, see the [module docs](self)
+#[derive(Debug, Clone)]
+pub struct WebhookPlugin {
+    metadata: PluginMetadata,
+    client: Client,
+    url: String,
+    headers: Vec<(String, String)>,
+    hooks: Vec<HookPoint>,
+}
+
+impl WebhookPlugin {
+    /// Post events to `url`
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PluginError::ConfigError`] if `url` is not an HTTP(S) URL or
+    /// the HTTP client cannot be built.
+    pub fn new(url: impl Into<String>) -> Result<Self, PluginError> {
+        Self::with_timeout(url, DEFAULT_TIMEOUT)
+    }
+
+    /// Post events to `url`, giving up on a delivery after `timeout`
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PluginError::ConfigError`] if `url` is not an HTTP(S) URL or
+    /// the HTTP client cannot be built.
+    pub fn with_timeout(url: impl Into<String>, timeout: Duration) -> Result<Self, PluginError> {
+        let url = url.into();
+        if !(url.starts_with("http://") || url.starts_with("https://")) {
+            return Err(PluginError::ConfigError(format!(
+                "webhook URL must be http or https: {url}"
+            )));
+        }
+        let client = Client::builder()
+            .timeout(timeout)
+            .build()
+            .map_err(|e| PluginError::ConfigError(e.to_string()))?;
+        let metadata = PluginBuilder::new(PLUGIN_NAME, env!("CARGO_PKG_VERSION"))
+            .author("LLM DevOps Contributors")
+            .description("Posts graph events and reports to a webhook")
+            .capability("integration")
+            .build();
+        Ok(Self {
+            metadata,
+            client,
+            url,
+            headers: Vec::new(),
+            hooks: Vec::new(),
+        })
+    }
+
+    /// Send `name: value` with every request
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Forward the contexts of `hook`, an after-hook, as events named after
+    /// it
+    pub fn with_hook(mut self, hook: HookPoint) -> Self {
+        if hook.is_after() && !self.hooks.contains(&hook) {
+            self.hooks.push(hook);
+        }
+        self
+    }
+
+    /// Endpoint events are posted to
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Post `payload` as an `event`
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PluginError::HookFailed`] if the request fails or the
+    /// endpoint answers with an error status.
+    pub async fn post(&self, event: &str, payload: &Value) -> Result<(), PluginError> {
+        let body = WebhookEvent {
+            event: event.to_string(),
+            sent_at: Utc::now(),
+            payload: payload.clone(),
+        };
+        let mut request = self.client.post(&self.url).json(&body);
+        for (name, value) in &self.headers {
+            request = request.header(name, value);
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|e| PluginError::HookFailed(format!("webhook {event}: {e}")))?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(PluginError::HookFailed(format!(
+                "webhook {event}: endpoint answered {status}"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Forward a hook's context if the plugin is subscribed to it
+    async fn forward(&self, hook: HookPoint, context: &PluginContext) -> Result<(), PluginError> {
+        if !self.hooks.contains(&hook) {
+            return Ok(());
+        }
+        let payload = serde_json::json!({
+            "operation": context.operation(),
+            "data": context.data(),
+        });
+        self.post(hook.as_str(), &payload).await
+    }
+}
+
+#[async_trait]
+impl Plugin for WebhookPlugin {
+    fn metadata(&self) -> &PluginMetadata {
+        &self.metadata
+    }
+
+    async fn after_create_node(&self, context: &PluginContext) -> Result<(), PluginError> {
+        self.forward(HookPoint::AfterCreateNode, context).await
+    }
+
+    async fn after_create_session(&self, context: &PluginContext) -> Result<(), PluginError> {
+        self.forward(HookPoint::AfterCreateSession, context).await
+    }
+
+    async fn after_query(&self, context: &PluginContext) -> Result<(), PluginError> {
+        self.forward(HookPoint::AfterQuery, context).await
+    }
+
+    async fn after_create_edge(&self, context: &PluginContext) -> Result<(), PluginError> {
+        self.forward(HookPoint::AfterCreateEdge, context).await
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+    use tokio::sync::mpsc;
+
+    /// An endpoint answering every request with `status`, passing on each
+    /// request's head and JSON body
+    pub(crate) async fn serve(status: u16) -> (String, mpsc::UnboundedReceiver<(String, Value)>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let (sender, receiver) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut request = Vec::new();
+                let mut buf = [0u8; 4096];
+                let (head, body) = loop {
+                    let n = stream.read(&mut buf).await.unwrap();
+                    request.extend_from_slice(&buf[..n]);
+                    let text = String::from_utf8_lossy(&request).to_string();
+                    if let Some((head, body)) = text.split_once("\r\n\r\n") {
+                        let length = head
+                            .lines()
+                            .find_map(|line| {
+                                let (name, value) = line.split_once(':')?;
+                                name.eq_ignore_ascii_case("content-length")
+                                    .then(|| value.trim().parse::<usize>().ok())?
+                            })
+                            .unwrap_or(0);
+                        if body.len() >= length {
+                            break (head.to_string(), body.to_string());
+                        }
+                    }
+                };
+                let reply = format!("HTTP/1.1 {status} X\r\ncontent-length: 0\r\n\r\n");
+                stream.write_all(reply.as_bytes()).await.unwrap();
+                let _ = sender.send((head, serde_json::from_str(&body).unwrap()));
+            }
+        });
+        (url, receiver)
+    }
+
+    #[tokio::test]
+    async fn test_post_and_forward() {
+        let (url, mut requests) = serve(200).await;
+        let plugin = WebhookPlugin::new(&url)
+            .unwrap()
+            .with_header("X-Token", "secret")
+            .with_hook(HookPoint::AfterCreateSession)
+            .with_hook(HookPoint::BeforeCreateNode);
+
+        plugin
+            .post("ping", &serde_json::json!({"n": 1}))
+            .await
+            .unwrap();
+        let (head, body) = requests.recv().await.unwrap();
+        assert!(head.to_ascii_lowercase().contains("x-token: secret"));
+        assert_eq!(body["event"], "ping");
+        assert_eq!(body["payload"]["n"], 1);
+
+        let context = PluginContext::new("create_session", serde_json::json!({"id": "s1"}));
+        plugin.after_create_node(&context).await.unwrap();
+        plugin.after_create_session(&context).await.unwrap();
+        let (_, body) = requests.recv().await.unwrap();
+        assert_eq!(body["event"], "after_create_session");
+        assert_eq!(body["payload"]["data"]["id"], "s1");
+        assert!(requests.try_recv().is_err());
+
+        let (url, _requests) = serve(500).await;
+        let failing = WebhookPlugin::new(url).unwrap();
+        assert!(failing.post("ping", &Value::Null).await.is_err());
+        assert!(WebhookPlugin::new("ftp://example.com").is_err());
+    }
+}
@@ -3,13 +3,18 @@
 //! This module provides a fluent API for building and executing async queries
 //! over the graph data with support for streaming large result sets.
 
-use crate::Result;
+use super::PropertyPredicate;
+use crate::deadline::Deadline;
+use crate::engine::{node_language, score_property, PII_SCORE, TOXICITY_SCORE};
+use crate::slow_ops::{SlowOpDetails, SlowOpKind, SlowOpLog};
 use crate::storage::AsyncStorageBackend;
-use crate::{Node, NodeType, SessionId};
+use crate::Result;
+use crate::{Node, NodeType, ReadConsistency, SessionId};
 use chrono::{DateTime, Utc};
 use futures::stream::Stream;
 use std::pin::Pin;
 use std::sync::Arc;
+use std::time::Instant;
 
 /// Builder for constructing async queries over the graph
 ///
@@ -20,7 +25,7 @@ use std::sync::Arc;
 ///
 /// ```no_run
 /// use llm_memory_graph::query::AsyncQueryBuilder;
-/// use llm_memory_graph::types::NodeType;
+/// use llm_memory_graph::NodeType;
 /// use futures::stream::StreamExt;
 ///
 /// # async fn example(builder: AsyncQueryBuilder) -> Result<(), Box<dyn std::error::Error>> {
@@ -42,13 +47,20 @@ use std::sync::Arc;
 /// # Ok(())
 /// # }
 /// ```
+#[derive(Clone)]
 pub struct AsyncQueryBuilder {
     storage: Arc<dyn AsyncStorageBackend>,
     session_filter: Option<SessionId>,
     node_type_filter: Option<NodeType>,
+    custom_type_filter: Option<String>,
+    language_filter: Option<String>,
     time_range: Option<(DateTime<Utc>, DateTime<Utc>)>,
+    property_filters: Vec<PropertyPredicate>,
     limit: Option<usize>,
     offset: usize,
+    consistency: ReadConsistency,
+    deadline: Deadline,
+    slow_ops: Option<SlowOpLog>,
 }
 
 impl AsyncQueryBuilder {
@@ -72,19 +84,31 @@ impl AsyncQueryBuilder {
             storage,
             session_filter: None,
             node_type_filter: None,
+            custom_type_filter: None,
+            language_filter: None,
             time_range: None,
+            property_filters: Vec::new(),
             limit: None,
             offset: 0,
+            consistency: ReadConsistency::default(),
+            deadline: Deadline::none(),
+            slow_ops: None,
         }
     }
 
+    /// Log executions slower than the query threshold of `slow_ops`
+    pub(crate) fn with_slow_ops(mut self, slow_ops: SlowOpLog) -> Self {
+        self.slow_ops = Some(slow_ops);
+        self
+    }
+
     /// Filter by session ID
     ///
     /// # Examples
     ///
     /// ```no_run
     /// # use llm_memory_graph::query::AsyncQueryBuilder;
-    /// # use llm_memory_graph::types::SessionId;
+    /// # use llm_memory_graph::SessionId;
     /// # async fn example(builder: AsyncQueryBuilder, session_id: SessionId) -> Result<(), Box<dyn std::error::Error>> {
     /// let nodes = builder
     ///     .session(session_id)
@@ -104,7 +128,7 @@ impl AsyncQueryBuilder {
     ///
     /// ```no_run
     /// # use llm_memory_graph::query::AsyncQueryBuilder;
-    /// # use llm_memory_graph::types::NodeType;
+    /// # use llm_memory_graph::NodeType;
     /// # async fn example(builder: AsyncQueryBuilder) -> Result<(), Box<dyn std::error::Error>> {
     /// let prompts = builder
     ///     .node_type(NodeType::Prompt)
@@ -118,6 +142,48 @@ impl AsyncQueryBuilder {
         self
     }
 
+    /// Keep only custom nodes of type `type_name`
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use llm_memory_graph::query::AsyncQueryBuilder;
+    /// # async fn example(builder: AsyncQueryBuilder) -> Result<(), Box<dyn std::error::Error>> {
+    /// let tickets = builder
+    ///     .custom_type("ticket")
+    ///     .execute()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn custom_type(mut self, type_name: impl Into<String>) -> Self {
+        self.custom_type_filter = Some(type_name.into());
+        self
+    }
+
+    /// Keep only prompts and responses recorded as being in `language`
+    ///
+    /// Languages are recorded by the graph's
+    /// [language detector](crate::engine::LanguageDetector), as ISO 639-3
+    /// codes with the built-in detector.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use llm_memory_graph::query::AsyncQueryBuilder;
+    /// # async fn example(builder: AsyncQueryBuilder) -> Result<(), Box<dyn std::error::Error>> {
+    /// let german = builder
+    ///     .language("deu")
+    ///     .execute()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn language(mut self, language: impl Into<String>) -> Self {
+        self.language_filter = Some(language.into());
+        self
+    }
+
     /// Filter by time range (inclusive)
     ///
     /// # Examples
@@ -141,6 +207,56 @@ impl AsyncQueryBuilder {
         self
     }
 
+    /// Keep nodes whose user-defined properties satisfy `predicate`
+    ///
+    /// Several predicates may be added; a node must satisfy all of them.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use llm_memory_graph::query::{AsyncQueryBuilder, PropertyPredicate};
+    /// # async fn example(builder: AsyncQueryBuilder) -> Result<(), Box<dyn std::error::Error>> {
+    /// let confident = builder
+    ///     .where_property(PropertyPredicate::GreaterThan("score".to_string(), 0.9))
+    ///     .execute()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn where_property(mut self, predicate: PropertyPredicate) -> Self {
+        self.property_filters.push(predicate);
+        self
+    }
+
+    /// Keep nodes whose property `key` equals `value`
+    pub fn property_eq(self, key: impl Into<String>, value: impl Into<serde_json::Value>) -> Self {
+        self.where_property(PropertyPredicate::Equals(key.into(), value.into()))
+    }
+
+    /// Keep nodes that have the property `key`
+    pub fn has_property(self, key: impl Into<String>) -> Self {
+        self.where_property(PropertyPredicate::Exists(key.into()))
+    }
+
+    /// Keep nodes whose score labelled `label` is at most `max`
+    ///
+    /// Scores are stored by the graph's
+    /// [response scorers](crate::engine::ResponseScorer); nodes that have not
+    /// been scored for `label` are left out.
+    pub fn max_score(self, label: &str, max: f64) -> Self {
+        self.where_property(PropertyPredicate::AtMost(score_property(label), max))
+    }
+
+    /// Keep nodes whose toxicity score is at most `max`
+    pub fn max_toxicity(self, max: f64) -> Self {
+        self.max_score(TOXICITY_SCORE, max)
+    }
+
+    /// Keep nodes whose PII score is at most `max`
+    pub fn max_pii(self, max: f64) -> Self {
+        self.max_score(PII_SCORE, max)
+    }
+
     /// Limit the number of results
     ///
     /// # Examples
@@ -181,6 +297,27 @@ impl AsyncQueryBuilder {
         self
     }
 
+    /// Set the consistency of the read
+    ///
+    /// Queries always read storage, so only
+    /// [`ReadConsistency::RequireFlushed`] changes anything: pending writes
+    /// are flushed before the query runs.
+    pub fn consistency(mut self, consistency: ReadConsistency) -> Self {
+        self.consistency = consistency;
+        self
+    }
+
+    /// Stop with [`Error::Interrupted`](crate::Error::Interrupted) once
+    /// `deadline` passes
+    ///
+    /// The storage scan checks the deadline as it reads, and a stream stops
+    /// after yielding the error; the error counts the nodes read or emitted
+    /// so far.
+    pub fn deadline(mut self, deadline: Deadline) -> Self {
+        self.deadline = deadline;
+        self
+    }
+
     /// Execute the query and return all matching nodes
     ///
     /// This loads all results into memory. For large result sets, consider using
@@ -197,9 +334,24 @@ impl AsyncQueryBuilder {
     /// # }
     /// ```
     pub async fn execute(&self) -> Result<Vec<Node>> {
+        let start = Instant::now();
+        let nodes = self.collect().await?;
+        if let Some(slow_ops) = &self.slow_ops {
+            let mut details = SlowOpDetails::default().with_result_size(nodes.len());
+            details.session_id = self.session_filter;
+            slow_ops.observe(SlowOpKind::Query, "query", start, details);
+        }
+        Ok(nodes)
+    }
+
+    async fn collect(&self) -> Result<Vec<Node>> {
+        self.storage.prepare_read(self.consistency).await?;
+
         // Get base nodes from session or all nodes
         let mut nodes = if let Some(session_id) = &self.session_filter {
-            self.storage.get_session_nodes(session_id).await?
+            self.storage
+                .get_session_nodes_until(session_id, &self.deadline)
+                .await?
         } else {
             // For now, we'll need to iterate through sessions
             // In production, you'd want a more efficient approach
@@ -211,6 +363,16 @@ impl AsyncQueryBuilder {
             nodes.retain(|node| node.node_type() == *node_type);
         }
 
+        // Apply custom type filter
+        if let Some(type_name) = &self.custom_type_filter {
+            nodes.retain(|node| node.custom_type() == Some(type_name.as_str()));
+        }
+
+        // Apply language filter
+        if let Some(language) = &self.language_filter {
+            nodes.retain(|node| node_language(node) == Some(language.as_str()));
+        }
+
         // Apply time range filter
         if let Some((start, end)) = &self.time_range {
             nodes.retain(|node| {
@@ -221,11 +383,21 @@ impl AsyncQueryBuilder {
                     Node::ToolInvocation(t) => t.timestamp,
                     Node::Agent(a) => a.created_at,
                     Node::Template(t) => t.created_at,
+                    Node::Custom(c) => c.created_at,
                 };
                 timestamp >= *start && timestamp <= *end
             });
         }
 
+        // Apply property filters
+        if !self.property_filters.is_empty() {
+            nodes.retain(|node| {
+                self.property_filters
+                    .iter()
+                    .all(|predicate| predicate.matches(node.properties()))
+            });
+        }
+
         // Sort by timestamp (newest first)
         nodes.sort_by(|a, b| {
             let ts_a = match a {
@@ -235,6 +407,7 @@ impl AsyncQueryBuilder {
                 Node::ToolInvocation(t) => t.timestamp,
                 Node::Agent(a) => a.created_at,
                 Node::Template(t) => t.created_at,
+                Node::Custom(c) => c.created_at,
             };
             let ts_b = match b {
                 Node::Prompt(p) => p.timestamp,
@@ -243,6 +416,7 @@ impl AsyncQueryBuilder {
                 Node::ToolInvocation(t) => t.timestamp,
                 Node::Agent(a) => a.created_at,
                 Node::Template(t) => t.created_at,
+                Node::Custom(c) => c.created_at,
             };
             ts_b.cmp(&ts_a)
         });
@@ -294,17 +468,37 @@ impl AsyncQueryBuilder {
 
         let session_filter = self.session_filter;
         let node_type_filter = self.node_type_filter.clone();
+        let custom_type_filter = self.custom_type_filter.clone();
+        let language_filter = self.language_filter.clone();
         let time_range = self.time_range;
+        let property_filters = self.property_filters.clone();
         let limit = self.limit;
         let offset = self.offset;
+        let consistency = self.consistency;
+        let deadline = self.deadline.clone();
 
         Box::pin(async_stream::stream! {
+            if let Err(e) = self.storage.prepare_read(consistency).await {
+                yield Err(e);
+                return;
+            }
+
             // Use storage-level streaming for better memory efficiency
-            let mut stream = if let Some(session_id) = session_filter {
-                self.storage.get_session_nodes_stream(&session_id)
-            } else {
+            let mut stream = match session_filter {
+                // Read under the deadline, so a stalled scan is interrupted too
+                Some(session_id) if !deadline.is_unbounded() => {
+                    match self.storage.get_session_nodes_until(&session_id, &deadline).await {
+                        Ok(nodes) => Box::pin(futures::stream::iter(nodes.into_iter().map(Ok)))
+                            as Pin<Box<dyn Stream<Item = Result<Node>> + Send + '_>>,
+                        Err(e) => {
+                            yield Err(e);
+                            return;
+                        }
+                    }
+                }
+                Some(session_id) => self.storage.get_session_nodes_stream(&session_id),
                 // Empty stream if no session filter
-                Box::pin(futures::stream::empty()) as Pin<Box<dyn Stream<Item = Result<Node>> + Send + '_>>
+                None => Box::pin(futures::stream::empty()),
             };
 
             // Apply filters and stream results
@@ -312,6 +506,10 @@ impl AsyncQueryBuilder {
             let mut emitted = 0;
 
             while let Some(result) = stream.next().await {
+                if let Err(e) = deadline.check("query", Some(emitted as u64)) {
+                    yield Err(e);
+                    return;
+                }
                 let node = match result {
                     Ok(n) => n,
                     Err(e) => {
@@ -327,6 +525,20 @@ impl AsyncQueryBuilder {
                     }
                 }
 
+                // Apply custom type filter
+                if let Some(ref type_name) = custom_type_filter {
+                    if node.custom_type() != Some(type_name.as_str()) {
+                        continue;
+                    }
+                }
+
+                // Apply language filter
+                if let Some(ref language) = language_filter {
+                    if node_language(&node) != Some(language.as_str()) {
+                        continue;
+                    }
+                }
+
                 // Apply time range filter
                 if let Some((start, end)) = time_range {
                     let timestamp = match &node {
@@ -336,6 +548,7 @@ impl AsyncQueryBuilder {
                         Node::ToolInvocation(t) => t.timestamp,
                         Node::Agent(a) => a.created_at,
                         Node::Template(t) => t.created_at,
+                        Node::Custom(c) => c.created_at,
                     };
 
                     if timestamp < start || timestamp > end {
@@ -343,6 +556,11 @@ impl AsyncQueryBuilder {
                     }
                 }
 
+                // Apply property filters
+                if !property_filters.iter().all(|predicate| predicate.matches(node.properties())) {
+                    continue;
+                }
+
                 // Apply offset
                 if skipped < offset {
                     skipped += 1;
@@ -362,6 +580,75 @@ impl AsyncQueryBuilder {
         })
     }
 
+    /// Whether `node` passes the query's filters
+    ///
+    /// Limit and offset are ignored. With a session filter, a response is
+    /// matched by the session of its prompt, which is read from storage.
+    pub async fn matches(&self, node: &Node) -> Result<bool> {
+        if self
+            .node_type_filter
+            .as_ref()
+            .is_some_and(|node_type| node.node_type() != *node_type)
+        {
+            return Ok(false);
+        }
+        if let Some(type_name) = &self.custom_type_filter {
+            if node.custom_type() != Some(type_name.as_str()) {
+                return Ok(false);
+            }
+        }
+        if let Some(language) = &self.language_filter {
+            if node_language(node) != Some(language.as_str()) {
+                return Ok(false);
+            }
+        }
+        if let Some((start, end)) = self.time_range {
+            let timestamp = match node {
+                Node::Prompt(p) => p.timestamp,
+                Node::Response(r) => r.timestamp,
+                Node::Session(s) => s.created_at,
+                Node::ToolInvocation(t) => t.timestamp,
+                Node::Agent(a) => a.created_at,
+                Node::Template(t) => t.created_at,
+                Node::Custom(c) => c.created_at,
+            };
+            if timestamp < start || timestamp > end {
+                return Ok(false);
+            }
+        }
+        if !self
+            .property_filters
+            .iter()
+            .all(|predicate| predicate.matches(node.properties()))
+        {
+            return Ok(false);
+        }
+        let Some(session_id) = self.session_filter else {
+            return Ok(true);
+        };
+        let session = match node {
+            Node::Session(s) => Some(s.id),
+            Node::Prompt(p) => Some(p.session_id),
+            Node::Response(r) => match self.storage.get_node(&r.prompt_id).await? {
+                Some(Node::Prompt(p)) => Some(p.session_id),
+                _ => None,
+            },
+            Node::Custom(c) => c.session_id,
+            // Not indexed by session, so never returned by a session query
+            Node::ToolInvocation(_) | Node::Agent(_) | Node::Template(_) => None,
+        };
+        Ok(session == Some(session_id))
+    }
+
+    /// The same query without its limit and offset
+    pub(crate) fn unpaged(&self) -> Self {
+        Self {
+            limit: None,
+            offset: 0,
+            ..self.clone()
+        }
+    }
+
     /// Count the number of matching nodes without loading them
     ///
     /// This is more efficient than `execute().await?.len()` for large result sets
@@ -371,7 +658,7 @@ impl AsyncQueryBuilder {
     ///
     /// ```no_run
     /// # use llm_memory_graph::query::AsyncQueryBuilder;
-    /// # use llm_memory_graph::types::NodeType;
+    /// # use llm_memory_graph::NodeType;
     /// # async fn example(builder: AsyncQueryBuilder) -> Result<(), Box<dyn std::error::Error>> {
     /// let prompt_count = builder
     ///     .node_type(NodeType::Prompt)
@@ -386,16 +673,21 @@ impl AsyncQueryBuilder {
         use futures::StreamExt;
 
         // If we only have a session filter and no other filters, use efficient count
-        if self.session_filter.is_some()
-            && self.node_type_filter.is_none()
-            && self.time_range.is_none()
-            && self.offset == 0
-            && self.limit.is_none()
-        {
-            return self
-                .storage
-                .count_session_nodes(&self.session_filter.unwrap())
-                .await;
+        if let Some(session_id) = self.session_filter {
+            if self.node_type_filter.is_none()
+                && self.custom_type_filter.is_none()
+                && self.language_filter.is_none()
+                && self.time_range.is_none()
+                && self.property_filters.is_empty()
+                && self.offset == 0
+                && self.limit.is_none()
+            {
+                self.storage.prepare_read(self.consistency).await?;
+                return self
+                    .deadline
+                    .run("count", None, self.storage.count_session_nodes(&session_id))
+                    .await;
+            }
         }
 
         // Otherwise, stream and count to avoid loading all into memory
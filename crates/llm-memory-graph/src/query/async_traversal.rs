@@ -0,0 +1,428 @@
+//! Async subgraph construction with concurrent edge fetches
+//!
+//! [`GraphTraversal`](super::GraphTraversal) walks the graph one node at a
+//! time, waiting for each node's edges before fetching the next. On large
+//! sessions multi-hop traversals are dominated by that latency.
+//! [`AsyncGraphTraversal`] expands the graph level by level instead: the edges
+//! of every node in the current frontier are fetched concurrently, bounded by
+//! [`concurrency`](AsyncGraphTraversal::concurrency), and the traversal can be
+//! capped by depth and by number of nodes.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use llm_memory_graph::engine::AsyncMemoryGraph;
+//! use llm_memory_graph::Config;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let graph = AsyncMemoryGraph::open(Config::default()).await?;
+//! # let session = graph.create_session().await?;
+//! # let prompt_id = graph.add_prompt(session.id, "Test".to_string(), None).await?;
+//! let subgraph = graph
+//!     .traversal()
+//!     .max_depth(3)
+//!     .max_nodes(10_000)
+//!     .concurrency(32)
+//!     .build_subgraph(prompt_id)
+//!     .await?;
+//! println!("{} nodes, {} edges", subgraph.nodes.len(), subgraph.edges.len());
+//! # Ok(())
+//! # }
+//! ```
+
+use super::weighted::{PathSearch, WeightedPath};
+use crate::deadline::Deadline;
+use crate::slow_ops::{SlowOpDetails, SlowOpKind, SlowOpLog};
+use crate::storage::AsyncStorageBackend;
+use crate::Result;
+use crate::{Edge, EdgeId, EdgeType, NodeId};
+use futures::stream::{self, StreamExt, TryStreamExt};
+use petgraph::graph::{DiGraph, NodeIndex};
+use petgraph::visit::Dfs;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Instant;
+
+/// Default number of nodes whose edges are fetched at once
+pub const DEFAULT_TRAVERSAL_CONCURRENCY: usize = 16;
+
+/// Nodes and edges reachable from a root node
+#[derive(Debug, Clone)]
+pub struct Subgraph {
+    /// Node the traversal started from
+    pub root: NodeId,
+    /// Visited nodes in breadth-first order, starting with the root
+    pub nodes: Vec<NodeId>,
+    /// Hops from the root to each visited node
+    pub depths: HashMap<NodeId, usize>,
+    /// Edges between visited nodes, each listed once
+    pub edges: Vec<Edge>,
+    /// Whether `max_nodes` stopped the traversal before every reachable node
+    /// was visited
+    pub truncated: bool,
+}
+
+impl Subgraph {
+    /// The subgraph as a petgraph graph, with the index of the root
+    pub fn to_petgraph(&self) -> (DiGraph<NodeId, EdgeType>, NodeIndex) {
+        let mut graph = DiGraph::new();
+        let indices: HashMap<NodeId, NodeIndex> = self
+            .nodes
+            .iter()
+            .map(|id| (*id, graph.add_node(*id)))
+            .collect();
+        for edge in &self.edges {
+            graph.add_edge(
+                indices[&edge.from],
+                indices[&edge.to],
+                edge.edge_type.clone(),
+            );
+        }
+        (graph, indices[&self.root])
+    }
+}
+
+/// Async traversal that fetches the edges of a whole frontier concurrently
+pub struct AsyncGraphTraversal {
+    storage: Arc<dyn AsyncStorageBackend>,
+    max_depth: Option<usize>,
+    max_nodes: Option<usize>,
+    concurrency: usize,
+    deadline: Deadline,
+    slow_ops: Option<SlowOpLog>,
+}
+
+impl AsyncGraphTraversal {
+    /// Create a traversal over `storage` without depth or size limits
+    pub fn new(storage: Arc<dyn AsyncStorageBackend>) -> Self {
+        Self {
+            storage,
+            max_depth: None,
+            max_nodes: None,
+            concurrency: DEFAULT_TRAVERSAL_CONCURRENCY,
+            deadline: Deadline::none(),
+            slow_ops: None,
+        }
+    }
+
+    /// Log traversals slower than the traversal threshold of `slow_ops`
+    pub(crate) fn with_slow_ops(mut self, slow_ops: SlowOpLog) -> Self {
+        self.slow_ops = Some(slow_ops);
+        self
+    }
+
+    fn observe(&self, operation: &str, started: Instant, start: NodeId, result_size: usize) {
+        if let Some(slow_ops) = &self.slow_ops {
+            slow_ops.observe(
+                SlowOpKind::Traversal,
+                operation,
+                started,
+                SlowOpDetails::node(start).with_result_size(result_size),
+            );
+        }
+    }
+
+    /// Do not follow edges from nodes this many hops from the root
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = Some(depth);
+        self
+    }
+
+    /// Stop once this many nodes have been visited
+    pub fn max_nodes(mut self, nodes: usize) -> Self {
+        self.max_nodes = Some(nodes);
+        self
+    }
+
+    /// Fetch the edges of at most this many nodes at once (at least one)
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Stop with [`Error::Interrupted`](crate::Error::Interrupted) once
+    /// `deadline` passes
+    ///
+    /// The error counts the nodes visited, or settled by a path search,
+    /// before the traversal stopped.
+    pub fn deadline(mut self, deadline: Deadline) -> Self {
+        self.deadline = deadline;
+        self
+    }
+
+    /// Collect the nodes and edges reachable from `start`, following edges in
+    /// both directions
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if fetching edges fails or the deadline passes.
+    pub async fn build_subgraph(&self, start: NodeId) -> Result<Subgraph> {
+        let started = Instant::now();
+        let subgraph = self.expand(start).await?;
+        self.observe("build_subgraph", started, start, subgraph.nodes.len());
+        Ok(subgraph)
+    }
+
+    async fn expand(&self, start: NodeId) -> Result<Subgraph> {
+        let max_nodes = self.max_nodes.unwrap_or(usize::MAX).max(1);
+        let mut subgraph = Subgraph {
+            root: start,
+            nodes: vec![start],
+            depths: HashMap::from([(start, 0)]),
+            edges: Vec::new(),
+            truncated: false,
+        };
+        let mut seen_edges: HashSet<EdgeId> = HashSet::new();
+        let mut frontier = vec![start];
+        let mut depth = 0;
+
+        while !frontier.is_empty() && self.max_depth.is_none_or(|max| depth < max) {
+            let storage = &self.storage;
+            // `buffered` keeps frontier order, so the result is deterministic
+            let fetch = stream::iter(frontier)
+                .map(|node_id| async move {
+                    let outgoing = storage.get_outgoing_edges(&node_id).await?;
+                    let incoming = storage.get_incoming_edges(&node_id).await?;
+                    Ok::<_, crate::Error>((outgoing, incoming))
+                })
+                .buffered(self.concurrency)
+                .try_collect();
+            let fetched: Vec<(Vec<Edge>, Vec<Edge>)> = self
+                .deadline
+                .run("build_subgraph", Some(subgraph.nodes.len() as u64), fetch)
+                .await?;
+
+            depth += 1;
+            let mut next = Vec::new();
+            let mut pending_edges = Vec::new();
+            for edge in fetched
+                .into_iter()
+                .flat_map(|(out, inc)| out.into_iter().chain(inc))
+            {
+                if !seen_edges.insert(edge.id) {
+                    continue;
+                }
+                for neighbor in [edge.from, edge.to] {
+                    if subgraph.depths.contains_key(&neighbor) {
+                        continue;
+                    }
+                    if subgraph.nodes.len() >= max_nodes {
+                        subgraph.truncated = true;
+                        continue;
+                    }
+                    subgraph.depths.insert(neighbor, depth);
+                    subgraph.nodes.push(neighbor);
+                    next.push(neighbor);
+                }
+                pending_edges.push(edge);
+            }
+            // Edges to nodes left out by `max_nodes` are dropped
+            subgraph
+                .edges
+                .extend(pending_edges.into_iter().filter(|edge| {
+                    subgraph.depths.contains_key(&edge.from)
+                        && subgraph.depths.contains_key(&edge.to)
+                }));
+            frontier = next;
+        }
+
+        Ok(subgraph)
+    }
+
+    /// Cheapest path from `start` to `target` along outgoing edges
+    ///
+    /// Each edge costs its [`weight`](crate::Edge::weight). Depth and size
+    /// limits do not apply. Returns `None` if the target cannot be reached.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if fetching edges fails or the deadline passes.
+    pub async fn shortest_path(
+        &self,
+        start: NodeId,
+        target: NodeId,
+    ) -> Result<Option<WeightedPath>> {
+        let started = Instant::now();
+        let mut search = PathSearch::new(start, target);
+        let mut settled = 0;
+        while let Some(node) = search.next_node() {
+            let edges = self
+                .deadline
+                .run(
+                    "shortest_path",
+                    Some(settled),
+                    self.storage.get_outgoing_edges(&node),
+                )
+                .await?;
+            search.relax(node, edges);
+            settled += 1;
+        }
+        let path = search.into_path();
+        let hops = path.as_ref().map_or(0, |path| path.nodes.len());
+        self.observe("shortest_path", started, start, hops);
+        Ok(path)
+    }
+
+    /// Nodes reachable from `start` in breadth-first order
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if fetching edges fails.
+    pub async fn bfs(&self, start: NodeId) -> Result<Vec<NodeId>> {
+        Ok(self.build_subgraph(start).await?.nodes)
+    }
+
+    /// Nodes reachable from `start` along outgoing edges, in depth-first order
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if fetching edges fails.
+    pub async fn dfs(&self, start: NodeId) -> Result<Vec<NodeId>> {
+        let (graph, root) = self.build_subgraph(start).await?.to_petgraph();
+        let mut dfs = Dfs::new(&graph, root);
+        let mut result = Vec::new();
+        while let Some(idx) = dfs.next(&graph) {
+            result.push(graph[idx]);
+        }
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::AsyncSledBackend;
+    use tempfile::tempdir;
+
+    /// A root with `fanout` children, each with one grandchild
+    async fn tree(fanout: usize) -> (Arc<dyn AsyncStorageBackend>, NodeId, tempfile::TempDir) {
+        let dir = tempdir().unwrap();
+        let backend = Arc::new(AsyncSledBackend::open(dir.path()).await.unwrap())
+            as Arc<dyn AsyncStorageBackend>;
+        let root = NodeId::new();
+        for _ in 0..fanout {
+            let child = NodeId::new();
+            let grandchild = NodeId::new();
+            backend
+                .store_edge(&Edge::new(child, root, EdgeType::PartOf))
+                .await
+                .unwrap();
+            backend
+                .store_edge(&Edge::new(child, grandchild, EdgeType::Follows))
+                .await
+                .unwrap();
+        }
+        (backend, root, dir)
+    }
+
+    #[tokio::test]
+    async fn test_build_subgraph() {
+        let (backend, root, _dir) = tree(20).await;
+
+        let serial = AsyncGraphTraversal::new(Arc::clone(&backend))
+            .concurrency(1)
+            .build_subgraph(root)
+            .await
+            .unwrap();
+        let parallel = AsyncGraphTraversal::new(backend)
+            .concurrency(8)
+            .build_subgraph(root)
+            .await
+            .unwrap();
+
+        assert_eq!(parallel.nodes.len(), 41);
+        assert_eq!(parallel.edges.len(), 40);
+        assert!(!parallel.truncated);
+        assert_eq!(parallel.nodes, serial.nodes);
+        assert_eq!(parallel.nodes[0], root);
+        assert_eq!(parallel.depths.values().filter(|d| **d == 2).count(), 20);
+
+        let (graph, _) = parallel.to_petgraph();
+        assert_eq!(graph.edge_count(), 40);
+    }
+
+    #[tokio::test]
+    async fn test_depth_and_size_limits() {
+        let (backend, root, _dir) = tree(10).await;
+        let shallow = AsyncGraphTraversal::new(Arc::clone(&backend))
+            .max_depth(1)
+            .build_subgraph(root)
+            .await
+            .unwrap();
+        assert_eq!(shallow.nodes.len(), 11);
+        assert_eq!(shallow.edges.len(), 10);
+        assert!(shallow.depths.values().all(|d| *d <= 1));
+
+        let capped = AsyncGraphTraversal::new(backend)
+            .max_nodes(5)
+            .build_subgraph(root)
+            .await
+            .unwrap();
+        assert_eq!(capped.nodes.len(), 5);
+        assert!(capped.truncated);
+        assert!(capped
+            .edges
+            .iter()
+            .all(|e| capped.depths.contains_key(&e.from) && capped.depths.contains_key(&e.to)));
+    }
+
+    #[tokio::test]
+    async fn test_shortest_path() {
+        let dir = tempdir().unwrap();
+        let backend = Arc::new(AsyncSledBackend::open(dir.path()).await.unwrap())
+            as Arc<dyn AsyncStorageBackend>;
+        let [a, b, c] = [NodeId::new(), NodeId::new(), NodeId::new()];
+        for (from, to, weight) in [(a, c, 1.0), (a, b, 0.25), (b, c, 0.25)] {
+            backend
+                .store_edge(&Edge::new(from, to, EdgeType::References).with_weight(weight))
+                .await
+                .unwrap();
+        }
+
+        let traversal = AsyncGraphTraversal::new(backend);
+        let path = traversal.shortest_path(a, c).await.unwrap().unwrap();
+        assert_eq!(path.nodes, vec![a, b, c]);
+        assert_eq!(path.total_weight, 0.5);
+        assert_eq!(path.edges.len(), 2);
+        assert!(traversal.shortest_path(c, a).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_deadline() {
+        use crate::deadline::CancellationToken;
+        use crate::{Error, ErrorCode};
+        use std::time::Duration;
+
+        let (backend, root, _dir) = tree(3).await;
+        let token = CancellationToken::new();
+        let traversal = AsyncGraphTraversal::new(backend)
+            .deadline(Deadline::after(Duration::from_mins(1)).with_cancellation(token.clone()));
+        assert_eq!(traversal.bfs(root).await.unwrap().len(), 7);
+
+        token.cancel();
+        let err = traversal.build_subgraph(root).await.unwrap_err();
+        assert_eq!(err.code(), ErrorCode::Cancelled);
+        assert!(matches!(
+            err,
+            Error::Interrupted {
+                completed: Some(1),
+                ..
+            }
+        ));
+        let err = traversal
+            .shortest_path(root, NodeId::new())
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), ErrorCode::Cancelled);
+    }
+
+    #[tokio::test]
+    async fn test_bfs_and_dfs() {
+        let (backend, root, _dir) = tree(3).await;
+        let traversal = AsyncGraphTraversal::new(backend);
+
+        assert_eq!(traversal.bfs(root).await.unwrap().len(), 7);
+        // Children point at the root, so only the root is reachable downstream
+        assert_eq!(traversal.dfs(root).await.unwrap(), vec![root]);
+    }
+}
@@ -0,0 +1,120 @@
+//! Cycle detection for edge types that must form a DAG
+//!
+//! Ancestry lookups follow FOLLOWS and INHERITS edges until they run out, so a
+//! cycle of either type would never terminate. [`would_create_cycle`] is
+//! checked before such edges are written, and [`find_cycles`] reports cycles
+//! already in the graph.
+
+use crate::storage::AsyncStorageBackend;
+use crate::Result;
+use crate::{Edge, EdgeType, NodeId};
+use petgraph::algo::tarjan_scc;
+use petgraph::graph::{DiGraph, NodeIndex};
+use std::collections::{HashMap, HashSet};
+
+/// Nodes joined in a cycle by edges of one type
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EdgeCycle {
+    /// Type of the edges forming the cycle
+    pub edge_type: EdgeType,
+    /// Nodes on the cycle
+    pub nodes: Vec<NodeId>,
+}
+
+/// Whether storing `edge` would close a cycle of its edge type
+///
+/// Looks for a path of the same edge type from the new edge's target back to
+/// its source.
+///
+/// # Errors
+///
+/// Returns an error if fetching edges fails.
+pub async fn would_create_cycle(storage: &dyn AsyncStorageBackend, edge: &Edge) -> Result<bool> {
+    if edge.from == edge.to {
+        return Ok(true);
+    }
+    let mut visited = HashSet::from([edge.to]);
+    let mut frontier = vec![edge.to];
+    while let Some(node) = frontier.pop() {
+        for next in storage.get_outgoing_edges(&node).await? {
+            if next.edge_type != edge.edge_type {
+                continue;
+            }
+            if next.to == edge.from {
+                return Ok(true);
+            }
+            if visited.insert(next.to) {
+                frontier.push(next.to);
+            }
+        }
+    }
+    Ok(false)
+}
+
+/// Cycles among `edges` formed by edges of any of `edge_types`
+pub fn find_cycles(edges: &[Edge], edge_types: &[EdgeType]) -> Vec<EdgeCycle> {
+    let mut cycles = Vec::new();
+    for edge_type in edge_types {
+        let mut graph = DiGraph::<NodeId, ()>::new();
+        let mut indices: HashMap<NodeId, NodeIndex> = HashMap::new();
+        let mut self_loops = Vec::new();
+        for edge in edges.iter().filter(|edge| edge.edge_type == *edge_type) {
+            if edge.from == edge.to {
+                self_loops.push(edge.from);
+                continue;
+            }
+            let from = *indices
+                .entry(edge.from)
+                .or_insert_with(|| graph.add_node(edge.from));
+            let to = *indices
+                .entry(edge.to)
+                .or_insert_with(|| graph.add_node(edge.to));
+            graph.add_edge(from, to, ());
+        }
+
+        // Every strongly connected component with more than one node is a cycle
+        for component in tarjan_scc(&graph) {
+            if component.len() > 1 {
+                cycles.push(EdgeCycle {
+                    edge_type: edge_type.clone(),
+                    nodes: component.into_iter().map(|idx| graph[idx]).collect(),
+                });
+            }
+        }
+        cycles.extend(self_loops.into_iter().map(|node| EdgeCycle {
+            edge_type: edge_type.clone(),
+            nodes: vec![node],
+        }));
+    }
+    cycles
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_cycles() {
+        let [a, b, c, d] = [NodeId::new(), NodeId::new(), NodeId::new(), NodeId::new()];
+        let edges = vec![
+            Edge::new(a, b, EdgeType::Inherits),
+            Edge::new(b, c, EdgeType::Inherits),
+            Edge::new(c, a, EdgeType::Inherits),
+            // A cycle only when edge types are mixed does not count
+            Edge::new(c, d, EdgeType::Inherits),
+            Edge::new(d, c, EdgeType::Follows),
+            Edge::new(d, d, EdgeType::Follows),
+        ];
+
+        let cycles = find_cycles(&edges, &[EdgeType::Inherits, EdgeType::Follows]);
+        assert_eq!(cycles.len(), 2);
+        let inherits = &cycles[0];
+        assert_eq!(inherits.edge_type, EdgeType::Inherits);
+        assert_eq!(
+            inherits.nodes.iter().collect::<HashSet<_>>(),
+            [a, b, c].iter().collect()
+        );
+        assert_eq!(cycles[1].nodes, vec![d]);
+        assert!(find_cycles(&edges, &[EdgeType::PartOf]).is_empty());
+    }
+}
@@ -1,11 +1,21 @@
 //! Query interface for graph traversal and filtering
 
 pub mod async_query;
+pub mod async_traversal;
+pub mod cycles;
+pub mod property;
+pub mod watch;
+pub mod weighted;
 
 pub use async_query::AsyncQueryBuilder;
+pub use async_traversal::{AsyncGraphTraversal, Subgraph, DEFAULT_TRAVERSAL_CONCURRENCY};
+pub use cycles::{find_cycles, would_create_cycle, EdgeCycle};
+pub use property::PropertyPredicate;
+pub use watch::{WatchEvent, WatchStream};
+pub use weighted::WeightedPath;
 
-use crate::{Error, Result};
 use crate::{EdgeType, Node, NodeId, NodeType, SessionId};
+use crate::{Error, Result};
 use chrono::{DateTime, Utc};
 use petgraph::graph::{DiGraph, NodeIndex};
 use petgraph::visit::{Bfs, Dfs};
@@ -225,6 +235,7 @@ impl<'a> QueryBuilder<'a> {
                     Node::ToolInvocation(t) => t.timestamp,
                     Node::Agent(a) => a.created_at,
                     Node::Template(t) => t.created_at,
+                    Node::Custom(c) => c.created_at,
                 };
                 timestamp >= start_time
             });
@@ -239,6 +250,7 @@ impl<'a> QueryBuilder<'a> {
                     Node::ToolInvocation(t) => t.timestamp,
                     Node::Agent(a) => a.created_at,
                     Node::Template(t) => t.created_at,
+                    Node::Custom(c) => c.created_at,
                 };
                 timestamp <= end_time
             });
@@ -253,6 +265,7 @@ impl<'a> QueryBuilder<'a> {
                 Node::ToolInvocation(t) => t.timestamp,
                 Node::Agent(a) => a.created_at,
                 Node::Template(t) => t.created_at,
+                Node::Custom(c) => c.created_at,
             };
             let time_b = match b {
                 Node::Prompt(p) => p.timestamp,
@@ -261,6 +274,7 @@ impl<'a> QueryBuilder<'a> {
                 Node::ToolInvocation(t) => t.timestamp,
                 Node::Agent(a) => a.created_at,
                 Node::Template(t) => t.created_at,
+                Node::Custom(c) => c.created_at,
             };
             time_b.cmp(&time_a)
         });
@@ -431,6 +445,40 @@ impl<'a> GraphTraversal<'a> {
         Ok(result)
     }
 
+    /// Cheapest path from `start` to `target` along outgoing edges
+    ///
+    /// Each edge costs its [`weight`](crate::Edge::weight). Returns `None` if
+    /// the target cannot be reached.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if edge retrieval fails.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use llm_memory_graph::{MemoryGraph, Config, query::GraphTraversal};
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let graph = MemoryGraph::open(Config::default())?;
+    /// # let session = graph.create_session()?;
+    /// # let first = graph.add_prompt(session.id, "First".to_string(), None)?;
+    /// # let second = graph.add_prompt(session.id, "Second".to_string(), None)?;
+    /// let traversal = GraphTraversal::new(&graph);
+    /// if let Some(path) = traversal.shortest_path(second, first)? {
+    ///     println!("{} hops, weight {}", path.hops(), path.total_weight);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn shortest_path(&self, start: NodeId, target: NodeId) -> Result<Option<WeightedPath>> {
+        let mut search = weighted::PathSearch::new(start, target);
+        while let Some(node) = search.next_node() {
+            let edges = self.graph.get_outgoing_edges(node)?;
+            search.relax(node, edges);
+        }
+        Ok(search.into_path())
+    }
+
     /// Get the conversation thread for a prompt or response
     ///
     /// Returns nodes in chronological order (oldest to newest).
@@ -501,6 +549,11 @@ impl<'a> GraphTraversal<'a> {
                     "Cannot get conversation thread for template nodes".to_string(),
                 ));
             }
+            Node::Custom(c) => c.session_id.ok_or_else(|| {
+                Error::TraversalError(
+                    "Cannot get conversation thread for custom nodes without a session".to_string(),
+                )
+            })?,
         };
 
         // Get all nodes in the session
@@ -518,6 +571,7 @@ impl<'a> GraphTraversal<'a> {
                 Node::ToolInvocation(t) => t.timestamp,
                 Node::Agent(ag) => ag.created_at,
                 Node::Template(t) => t.created_at,
+                Node::Custom(c) => c.created_at,
             };
             let time_b = match b {
                 Node::Prompt(p) => p.timestamp,
@@ -526,6 +580,7 @@ impl<'a> GraphTraversal<'a> {
                 Node::ToolInvocation(t) => t.timestamp,
                 Node::Agent(ag) => ag.created_at,
                 Node::Template(t) => t.created_at,
+                Node::Custom(c) => c.created_at,
             };
             time_a.cmp(&time_b)
         });
@@ -642,6 +697,25 @@ mod tests {
         assert_eq!(nodes[0], prompt_id);
     }
 
+    #[test]
+    fn test_shortest_path_uses_weights() {
+        let dir = tempdir().unwrap();
+        let graph = MemoryGraph::open(Config::new(dir.path())).unwrap();
+
+        let [a, b, c] = [NodeId::new(), NodeId::new(), NodeId::new()];
+        for (from, to, weight) in [(a, c, 10.0), (a, b, 2.0), (b, c, 3.0)] {
+            graph
+                .add_weighted_edge(from, to, EdgeType::References, weight)
+                .unwrap();
+        }
+
+        let traversal = GraphTraversal::new(&graph);
+        let path = traversal.shortest_path(a, c).unwrap().unwrap();
+        assert_eq!(path.nodes, vec![a, b, c]);
+        assert_eq!(path.total_weight, 5.0);
+        assert!(traversal.shortest_path(c, a).unwrap().is_none());
+    }
+
     #[test]
     fn test_conversation_thread() {
         let dir = tempdir().unwrap();
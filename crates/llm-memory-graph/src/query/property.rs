@@ -0,0 +1,108 @@
+//! Predicates over user-defined node properties
+//!
+//! Used by [`AsyncQueryBuilder::where_property`](super::AsyncQueryBuilder::where_property)
+//! and its shorthands to filter nodes by the JSON values stored in
+//! [`Node::properties`](crate::Node::properties).
+
+use crate::Properties;
+use serde_json::Value;
+
+/// Condition on a single property
+#[derive(Debug, Clone, PartialEq)]
+pub enum PropertyPredicate {
+    /// The property is set, whatever its value
+    Exists(String),
+    /// The property equals the value
+    Equals(String, Value),
+    /// The property does not equal the value (unset properties match)
+    NotEquals(String, Value),
+    /// The property is a number greater than the bound
+    GreaterThan(String, f64),
+    /// The property is a number less than the bound
+    LessThan(String, f64),
+    /// The property is a number no greater than the bound
+    AtMost(String, f64),
+    /// The property is a string containing the substring, or an array
+    /// containing the string
+    Contains(String, String),
+}
+
+impl PropertyPredicate {
+    /// The property the predicate looks at
+    pub fn key(&self) -> &str {
+        match self {
+            Self::Exists(key)
+            | Self::Equals(key, _)
+            | Self::NotEquals(key, _)
+            | Self::GreaterThan(key, _)
+            | Self::LessThan(key, _)
+            | Self::AtMost(key, _)
+            | Self::Contains(key, _) => key,
+        }
+    }
+
+    /// Whether `properties` satisfy the predicate
+    pub fn matches(&self, properties: &Properties) -> bool {
+        let value = properties.get(self.key());
+        match self {
+            Self::Exists(_) => value.is_some(),
+            Self::Equals(_, expected) => value == Some(expected),
+            Self::NotEquals(_, expected) => value != Some(expected),
+            Self::GreaterThan(_, bound) => {
+                value.and_then(Value::as_f64).is_some_and(|v| v > *bound)
+            }
+            Self::LessThan(_, bound) => value.and_then(Value::as_f64).is_some_and(|v| v < *bound),
+            Self::AtMost(_, bound) => value.and_then(Value::as_f64).is_some_and(|v| v <= *bound),
+            Self::Contains(_, needle) => match value {
+                Some(Value::String(s)) => s.contains(needle.as_str()),
+                Some(Value::Array(items)) => items.iter().any(|item| item == needle.as_str()),
+                _ => false,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_predicates() {
+        let properties: Properties = [
+            ("customer".to_string(), json!("acme")),
+            ("score".to_string(), json!(0.8)),
+            ("labels".to_string(), json!(["billing", "urgent"])),
+        ]
+        .into_iter()
+        .collect();
+
+        let matches = |predicate: PropertyPredicate| predicate.matches(&properties);
+        assert!(matches(PropertyPredicate::Exists("customer".into())));
+        assert!(!matches(PropertyPredicate::Exists("missing".into())));
+        assert!(matches(PropertyPredicate::Equals(
+            "customer".into(),
+            json!("acme")
+        )));
+        assert!(matches(PropertyPredicate::NotEquals(
+            "missing".into(),
+            json!(1)
+        )));
+        assert!(matches(PropertyPredicate::GreaterThan("score".into(), 0.5)));
+        assert!(!matches(PropertyPredicate::LessThan("score".into(), 0.5)));
+        assert!(matches(PropertyPredicate::AtMost("score".into(), 0.8)));
+        assert!(!matches(PropertyPredicate::AtMost("missing".into(), 1.0)));
+        assert!(!matches(PropertyPredicate::GreaterThan(
+            "customer".into(),
+            0.0
+        )));
+        assert!(matches(PropertyPredicate::Contains(
+            "customer".into(),
+            "cm".into()
+        )));
+        assert!(matches(PropertyPredicate::Contains(
+            "labels".into(),
+            "urgent".into()
+        )));
+    }
+}
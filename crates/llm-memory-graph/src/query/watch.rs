@@ -0,0 +1,100 @@
+//! Live updates of query results
+//!
+//! [`AsyncMemoryGraph::watch`](crate::engine::AsyncMemoryGraph::watch) turns
+//! an [`AsyncQueryBuilder`] into a stream of [`WatchEvent`]s: every node
+//! mutation reported by the storage change feed is checked against the
+//! query's filters, so a UI can keep a session view current without polling.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use futures::StreamExt;
+//! use llm_memory_graph::engine::AsyncMemoryGraph;
+//! use llm_memory_graph::query::WatchEvent;
+//! use llm_memory_graph::{Config, NodeType};
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let graph = AsyncMemoryGraph::open(Config::default()).await?;
+//! # let session = graph.create_session().await?;
+//! let mut updates = graph.watch(graph.query().session(session.id).node_type(NodeType::Prompt))?;
+//! while let Some(event) = updates.next().await {
+//!     match event? {
+//!         WatchEvent::Created(node) | WatchEvent::Updated(node) => println!("show {}", node.id()),
+//!         WatchEvent::Removed(id) => println!("hide {id}"),
+//!         WatchEvent::Lagged(_) => println!("reload the view"),
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use super::AsyncQueryBuilder;
+use crate::changes::{ChangeEntity, ChangeKind, ChangeRecord};
+use crate::{Node, NodeId, Result};
+use futures::stream::Stream;
+use std::collections::HashSet;
+use std::pin::Pin;
+use tokio::sync::broadcast::{self, error::RecvError};
+
+/// Stream returned by [`AsyncMemoryGraph::watch`](crate::engine::AsyncMemoryGraph::watch)
+pub type WatchStream = Pin<Box<dyn Stream<Item = Result<WatchEvent>> + Send>>;
+
+/// A change to the results of a watched query
+#[derive(Debug, Clone)]
+pub enum WatchEvent {
+    /// A matching node was created
+    Created(Node),
+    /// A matching node was updated, possibly from one that did not match
+    Updated(Node),
+    /// A node that matched was deleted, or updated so it no longer matches
+    Removed(NodeId),
+    /// The watcher fell behind and this many changes were dropped; run the
+    /// query again to resynchronize
+    Lagged(u64),
+}
+
+/// Apply `query` to each node change received from `changes`
+///
+/// Nodes already matching when the stream is first polled are looked up so
+/// their deletion can be reported.
+pub(crate) fn watch(
+    query: AsyncQueryBuilder,
+    mut changes: broadcast::Receiver<ChangeRecord>,
+) -> WatchStream {
+    Box::pin(async_stream::try_stream! {
+        let mut matching: HashSet<NodeId> =
+            query.unpaged().execute().await?.iter().map(Node::id).collect();
+        loop {
+            let record = match changes.recv().await {
+                Ok(record) => record,
+                Err(RecvError::Lagged(missed)) => {
+                    yield WatchEvent::Lagged(missed);
+                    continue;
+                }
+                Err(RecvError::Closed) => break,
+            };
+            let ChangeEntity::Node(id) = record.entity else {
+                continue;
+            };
+            match record.node {
+                Some(node) if record.kind != ChangeKind::Deleted => {
+                    if query.matches(&node).await? {
+                        matching.insert(id);
+                        yield if record.kind == ChangeKind::Created {
+                            WatchEvent::Created(node)
+                        } else {
+                            WatchEvent::Updated(node)
+                        };
+                    } else if matching.remove(&id) {
+                        yield WatchEvent::Removed(id);
+                    }
+                }
+                _ => {
+                    if matching.remove(&id) {
+                        yield WatchEvent::Removed(id);
+                    }
+                }
+            }
+        }
+    })
+}
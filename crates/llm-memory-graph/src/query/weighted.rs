@@ -0,0 +1,166 @@
+//! Weighted shortest paths
+//!
+//! [`GraphTraversal::shortest_path`](super::GraphTraversal::shortest_path) and
+//! [`AsyncGraphTraversal::shortest_path`](super::AsyncGraphTraversal::shortest_path)
+//! run Dijkstra's algorithm over outgoing edges, costing each edge by
+//! [`Edge::weight`]. Edges are fetched as nodes are settled, so only the part
+//! of the graph closer to the start than the target is read.
+
+use crate::{Edge, NodeId};
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+
+/// Cheapest path between two nodes
+#[derive(Debug, Clone)]
+pub struct WeightedPath {
+    /// Nodes on the path, from start to target
+    pub nodes: Vec<NodeId>,
+    /// Edges followed, one fewer than the nodes
+    pub edges: Vec<Edge>,
+    /// Sum of the edge weights
+    pub total_weight: f64,
+}
+
+impl WeightedPath {
+    /// Number of edges on the path
+    pub fn hops(&self) -> usize {
+        self.edges.len()
+    }
+}
+
+/// Node waiting to be settled, ordered so the cheapest pops first
+struct Candidate {
+    cost: f64,
+    node: NodeId,
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.cost.total_cmp(&self.cost)
+    }
+}
+
+/// State of a Dijkstra search, driven by a caller that fetches edges
+///
+/// Shared by the sync and async traversals, which differ only in how they
+/// read edges.
+pub(crate) struct PathSearch {
+    target: NodeId,
+    heap: BinaryHeap<Candidate>,
+    costs: HashMap<NodeId, f64>,
+    via: HashMap<NodeId, Edge>,
+}
+
+impl PathSearch {
+    pub(crate) fn new(start: NodeId, target: NodeId) -> Self {
+        Self {
+            target,
+            heap: BinaryHeap::from([Candidate {
+                cost: 0.0,
+                node: start,
+            }]),
+            costs: HashMap::from([(start, 0.0)]),
+            via: HashMap::new(),
+        }
+    }
+
+    /// Next node whose edges should be relaxed, or `None` once the target is
+    /// settled or nothing is left to explore
+    pub(crate) fn next_node(&mut self) -> Option<NodeId> {
+        while let Some(Candidate { cost, node }) = self.heap.pop() {
+            // Skip entries superseded by a cheaper path
+            if cost > self.costs[&node] {
+                continue;
+            }
+            if node == self.target {
+                return None;
+            }
+            return Some(node);
+        }
+        None
+    }
+
+    /// Consider the outgoing edges of `node`
+    pub(crate) fn relax(&mut self, node: NodeId, edges: Vec<Edge>) {
+        let base = self.costs[&node];
+        for edge in edges {
+            let cost = base + edge.weight();
+            if self.costs.get(&edge.to).is_none_or(|known| cost < *known) {
+                self.costs.insert(edge.to, cost);
+                self.heap.push(Candidate {
+                    cost,
+                    node: edge.to,
+                });
+                self.via.insert(edge.to, edge);
+            }
+        }
+    }
+
+    /// The cheapest path to the target, if one was found
+    pub(crate) fn into_path(mut self) -> Option<WeightedPath> {
+        let total_weight = *self.costs.get(&self.target)?;
+        let mut nodes = vec![self.target];
+        let mut edges = Vec::new();
+        let mut current = self.target;
+        while let Some(edge) = self.via.remove(&current) {
+            current = edge.from;
+            nodes.push(current);
+            edges.push(edge);
+        }
+        nodes.reverse();
+        edges.reverse();
+        Some(WeightedPath {
+            nodes,
+            edges,
+            total_weight,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::EdgeType;
+
+    #[test]
+    fn test_prefers_lighter_path() {
+        let [a, b, c, d] = [NodeId::new(), NodeId::new(), NodeId::new(), NodeId::new()];
+        let mut graph: HashMap<NodeId, Vec<Edge>> = HashMap::new();
+        for (from, to, weight) in [(a, d, 5.0), (a, b, 1.0), (b, c, 1.0), (c, d, 1.0)] {
+            graph
+                .entry(from)
+                .or_default()
+                .push(Edge::new(from, to, EdgeType::Follows).with_weight(weight));
+        }
+
+        let mut search = PathSearch::new(a, d);
+        while let Some(node) = search.next_node() {
+            search.relax(node, graph.get(&node).cloned().unwrap_or_default());
+        }
+        let path = search.into_path().unwrap();
+
+        assert_eq!(path.nodes, vec![a, b, c, d]);
+        assert_eq!(path.hops(), 3);
+        assert_eq!(path.total_weight, 3.0);
+
+        let mut unreachable = PathSearch::new(d, a);
+        while let Some(node) = unreachable.next_node() {
+            unreachable.relax(node, graph.get(&node).cloned().unwrap_or_default());
+        }
+        assert!(unreachable.into_path().is_none());
+    }
+}
@@ -0,0 +1,356 @@
+//! Replication from a leader graph to read-only followers
+//!
+//! A [`Follower`] copies a leader's state with [`bootstrap`](Follower::bootstrap)
+//! and then keeps up by pulling the leader's captured changes (see
+//! [`changes`](crate::changes)) and applying them in order. The leader needs
+//! [`Config::change_capture`](crate::Config); the follower is usually opened
+//! with [`Config::read_only`](crate::Config) so that only replication writes
+//! to it.
+//!
+//! Leaders are reached through [`ReplicationSource`], implemented here for an
+//! in-process [`AsyncMemoryGraph`]; a network client exposing the same three
+//! calls can stand in for a remote leader.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use llm_memory_graph::engine::AsyncMemoryGraph;
+//! use llm_memory_graph::replication::Follower;
+//! use llm_memory_graph::Config;
+//! use std::sync::Arc;
+//! use std::time::Duration;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let leader = Arc::new(
+//!     AsyncMemoryGraph::open(Config::new("./leader").with_change_capture(true)).await?,
+//! );
+//! let replica = Arc::new(AsyncMemoryGraph::open(Config::new("./replica").with_read_only(true)).await?);
+//!
+//! let follower = Arc::new(Follower::new(Arc::clone(&replica), leader));
+//! follower.bootstrap().await?;
+//! let handle = Arc::clone(&follower).start(Duration::from_millis(500));
+//!
+//! // Serve reads from `replica`; check how far behind it is
+//! let status = follower.status();
+//! println!("caught up: {}, lag: {:?}", status.is_caught_up(), status.lag);
+//! # handle.stop().await;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::changes::{ChangeRecord, DEFAULT_CHANGE_PAGE_SIZE};
+use crate::engine::AsyncMemoryGraph;
+use crate::{Edge, Node, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+
+/// A leader's full state together with the change cursor it reflects
+#[derive(Debug, Clone, Default)]
+pub struct ReplicationSnapshot {
+    /// Last captured change included in the snapshot
+    pub cursor: Option<u64>,
+    /// Every node
+    pub nodes: Vec<Node>,
+    /// Every edge
+    pub edges: Vec<Edge>,
+}
+
+/// Where a follower reads the leader's state from
+#[async_trait]
+pub trait ReplicationSource: Send + Sync {
+    /// A consistent copy of the leader
+    async fn snapshot(&self) -> Result<ReplicationSnapshot>;
+
+    /// Up to `limit` changes after `cursor`, oldest first
+    async fn changes_since(&self, cursor: Option<u64>, limit: usize) -> Result<Vec<ChangeRecord>>;
+
+    /// Cursor of the leader's most recent change
+    async fn latest_cursor(&self) -> Result<Option<u64>>;
+}
+
+#[async_trait]
+impl ReplicationSource for AsyncMemoryGraph {
+    async fn snapshot(&self) -> Result<ReplicationSnapshot> {
+        let snapshot = AsyncMemoryGraph::snapshot(self).await?;
+        let storage = snapshot.storage();
+        Ok(ReplicationSnapshot {
+            cursor: snapshot.change_cursor(),
+            nodes: storage.all_nodes().await?,
+            edges: storage.all_edges().await?,
+        })
+    }
+
+    async fn changes_since(&self, cursor: Option<u64>, limit: usize) -> Result<Vec<ChangeRecord>> {
+        self.changes_page(cursor, limit).await
+    }
+
+    async fn latest_cursor(&self) -> Result<Option<u64>> {
+        self.latest_change_cursor().await
+    }
+}
+
+/// How far a follower has got
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReplicationStatus {
+    /// Cursor of the last change applied
+    pub applied_cursor: Option<u64>,
+    /// Cursor of the leader's latest change when last checked
+    pub leader_cursor: Option<u64>,
+    /// Changes applied since the follower was created
+    pub changes_applied: u64,
+    /// When the follower last finished a sync
+    pub last_sync: Option<DateTime<Utc>>,
+    /// Time between the leader capturing the last applied change and the
+    /// follower applying it
+    pub lag: Option<Duration>,
+}
+
+impl ReplicationStatus {
+    /// Whether every change the leader had at the last check is applied
+    pub fn is_caught_up(&self) -> bool {
+        self.leader_cursor <= self.applied_cursor
+    }
+}
+
+/// Keeps a graph in step with a leader
+pub struct Follower {
+    graph: Arc<AsyncMemoryGraph>,
+    source: Arc<dyn ReplicationSource>,
+    batch_size: usize,
+    status: Mutex<ReplicationStatus>,
+}
+
+impl Follower {
+    /// Replicate `source` into `graph`, starting from the beginning of the
+    /// leader's change log
+    pub fn new(graph: Arc<AsyncMemoryGraph>, source: Arc<dyn ReplicationSource>) -> Self {
+        Self {
+            graph,
+            source,
+            batch_size: DEFAULT_CHANGE_PAGE_SIZE,
+            status: Mutex::new(ReplicationStatus::default()),
+        }
+    }
+
+    /// Resume after `cursor`, for a follower that already applied changes up
+    /// to it
+    pub fn with_cursor(self, cursor: u64) -> Self {
+        self.status.lock().applied_cursor = Some(cursor);
+        self
+    }
+
+    /// Set how many changes are fetched per request
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// The replicated graph
+    pub fn graph(&self) -> &Arc<AsyncMemoryGraph> {
+        &self.graph
+    }
+
+    /// Current progress and lag
+    pub fn status(&self) -> ReplicationStatus {
+        self.status.lock().clone()
+    }
+
+    /// Copy the leader's current state into the follower
+    ///
+    /// Meant for an empty follower. Changes captured after the snapshot are
+    /// applied by the next [`sync`](Self::sync). Returns the number of nodes
+    /// and edges copied.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the leader cannot be snapshotted or the follower
+    /// cannot be written.
+    pub async fn bootstrap(&self) -> Result<usize> {
+        let snapshot = self.source.snapshot().await?;
+        self.graph
+            .load_replicated(&snapshot.nodes, &snapshot.edges)
+            .await?;
+
+        let mut status = self.status.lock();
+        status.applied_cursor = snapshot.cursor;
+        status.leader_cursor = status.leader_cursor.max(snapshot.cursor);
+        status.last_sync = Some(Utc::now());
+        Ok(snapshot.nodes.len() + snapshot.edges.len())
+    }
+
+    /// Apply every change the leader has captured since the last one applied
+    ///
+    /// Returns the number of changes applied. A failed change stops the sync;
+    /// the next sync retries it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the leader cannot be read or a change cannot be
+    /// applied.
+    pub async fn sync(&self) -> Result<usize> {
+        let mut applied = 0;
+        loop {
+            let cursor = self.status.lock().applied_cursor;
+            let page = self.source.changes_since(cursor, self.batch_size).await?;
+            let done = page.len() < self.batch_size;
+            for change in page {
+                self.graph.apply_replicated(&change).await?;
+                applied += 1;
+
+                let mut status = self.status.lock();
+                status.applied_cursor = Some(change.cursor);
+                status.changes_applied += 1;
+                status.lag = (Utc::now() - change.timestamp).to_std().ok();
+            }
+            if done {
+                break;
+            }
+        }
+
+        let leader_cursor = self.source.latest_cursor().await?;
+        let mut status = self.status.lock();
+        status.leader_cursor = leader_cursor;
+        status.last_sync = Some(Utc::now());
+        Ok(applied)
+    }
+
+    /// Sync every `interval` on a background task
+    ///
+    /// The loop stops when the handle is stopped or dropped, or when the
+    /// follower graph is closed. Failed syncs are logged and retried.
+    pub fn start(self: Arc<Self>, interval: Duration) -> ReplicationHandle {
+        let (shutdown, mut stop) = watch::channel(false);
+        let mut closing = self.graph.shutdown_signal();
+        let follower = Arc::downgrade(&self);
+        drop(self);
+
+        let task = tokio::spawn(async move {
+            while !*closing.borrow() {
+                let Some(follower) = Weak::upgrade(&follower) else {
+                    break;
+                };
+                match follower.sync().await {
+                    Ok(applied) => tracing::debug!("Replicated {} changes", applied),
+                    Err(e) => tracing::warn!("Replication sync failed: {}", e),
+                }
+                drop(follower);
+
+                tokio::select! {
+                    () = tokio::time::sleep(interval) => {}
+                    _ = stop.changed() => break,
+                    _ = closing.changed() => break,
+                }
+            }
+            tracing::info!("Replication stopped");
+        });
+
+        ReplicationHandle::new(shutdown, task)
+    }
+}
+
+/// Handle to a running replication loop
+///
+/// Dropping the handle stops the loop without waiting for it.
+pub struct ReplicationHandle {
+    shutdown: watch::Sender<bool>,
+    task: Option<JoinHandle<()>>,
+}
+
+impl ReplicationHandle {
+    pub(crate) fn new(shutdown: watch::Sender<bool>, task: JoinHandle<()>) -> Self {
+        Self {
+            shutdown,
+            task: Some(task),
+        }
+    }
+
+    /// Whether the loop is still running
+    pub fn is_running(&self) -> bool {
+        self.task.as_ref().is_some_and(|task| !task.is_finished())
+    }
+
+    /// Stop the loop and wait for the sync in progress, if any, to finish
+    pub async fn stop(mut self) {
+        let _ = self.shutdown.send(true);
+        if let Some(task) = self.task.take() {
+            let _ = task.await;
+        }
+    }
+}
+
+impl Drop for ReplicationHandle {
+    fn drop(&mut self) {
+        let _ = self.shutdown.send(true);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Config, EdgeType, TokenUsage};
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_follower_replicates_leader() {
+        let (leader_dir, follower_dir) = (tempdir().unwrap(), tempdir().unwrap());
+        let leader = Arc::new(
+            AsyncMemoryGraph::open(Config::new(leader_dir.path()).with_change_capture(true))
+                .await
+                .unwrap(),
+        );
+        let replica = Arc::new(
+            AsyncMemoryGraph::open(Config::new(follower_dir.path()).with_read_only(true))
+                .await
+                .unwrap(),
+        );
+        assert!(replica.create_session().await.is_err());
+
+        // State before the snapshot arrives through bootstrap
+        let session = leader.create_session().await.unwrap();
+        let prompt_id = leader
+            .add_prompt(session.id, "Before".to_string(), None)
+            .await
+            .unwrap();
+        let follower = Follower::new(
+            Arc::clone(&replica),
+            Arc::clone(&leader) as Arc<dyn ReplicationSource>,
+        )
+        .with_batch_size(2);
+        follower.bootstrap().await.unwrap();
+        assert!(replica.get_node(&prompt_id).await.unwrap().is_some());
+        assert!(follower.status().is_caught_up());
+
+        // Later changes arrive through sync
+        let response_id = leader
+            .add_response(prompt_id, "After".to_string(), TokenUsage::new(1, 1), None)
+            .await
+            .unwrap();
+        let second = leader
+            .add_prompt(session.id, "Second".to_string(), None)
+            .await
+            .unwrap();
+        leader.delete_node(second).await.unwrap();
+        assert!(follower.sync().await.unwrap() > 0);
+
+        assert!(replica.get_node(&response_id).await.unwrap().is_some());
+        assert!(replica.get_node(&second).await.unwrap().is_none());
+        let edges = replica.get_outgoing_edges(&response_id).await.unwrap();
+        assert!(edges
+            .iter()
+            .any(|edge| edge.edge_type == EdgeType::RespondsTo && edge.to == prompt_id));
+
+        let status = follower.status();
+        assert!(status.is_caught_up());
+        assert_eq!(
+            status.applied_cursor,
+            leader.latest_change_cursor().await.unwrap()
+        );
+        assert!(status.lag.is_some());
+        assert_eq!(follower.sync().await.unwrap(), 0);
+    }
+}
@@ -0,0 +1,599 @@
+//! Read replicas fed by shipped snapshot archives
+//!
+//! Change-log replication (see [`replication`](crate::replication)) needs the
+//! replica to reach the primary. Snapshot shipping only needs storage both can
+//! see: a [`SnapshotShipper`] on the primary periodically writes a consistent
+//! snapshot archive to a [`SnapshotStore`], and a [`SnapshotReplica`]
+//! elsewhere ingests the newest archive into a read-only graph, replacing
+//! its contents.
+//!
+//! Each archive is an [`.lmg` archive](crate::archive) of every node and
+//! edge, written before a [`SnapshotManifest`] recording its SHA-256, size and
+//! record counts. Replicas only see archives whose manifest exists and check the
+//! archive against it before applying anything, so a partially uploaded or
+//! corrupted archive is never ingested. [`DirectoryStore`] keeps archives in
+//! a local or mounted directory; object stores are plugged in by implementing
+//! [`SnapshotStore`].
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use llm_memory_graph::engine::AsyncMemoryGraph;
+//! use llm_memory_graph::shipping::{DirectoryStore, SnapshotReplica, SnapshotShipper};
+//! use llm_memory_graph::Config;
+//! use std::sync::Arc;
+//! use std::time::Duration;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let store = Arc::new(DirectoryStore::new("/mnt/shared/snapshots")?);
+//!
+//! // On the primary
+//! let primary = Arc::new(AsyncMemoryGraph::open(Config::new("./primary")).await?);
+//! let shipper = Arc::new(SnapshotShipper::new(primary, store.clone()).with_retention(3));
+//! let shipping = shipper.start(Duration::from_secs(300));
+//!
+//! // On the replica
+//! let replica = Arc::new(AsyncMemoryGraph::open(Config::new("./replica").with_read_only(true)).await?);
+//! let ingester = Arc::new(SnapshotReplica::new(Arc::clone(&replica), store));
+//! let ingesting = Arc::clone(&ingester).start(Duration::from_secs(60));
+//!
+//! // Serve reads from `replica`; check how stale it is
+//! println!("lag: {:?}", ingester.status().lag);
+//! # shipping.stop().await;
+//! # ingesting.stop().await;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::archive::{ArchiveKind, ArchiveReader};
+use crate::engine::AsyncMemoryGraph;
+use crate::export::sha256_hex;
+use crate::replication::ReplicationHandle;
+use crate::{Edge, Error, Node, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+use tokio::sync::watch;
+
+/// Version of the archive format written by this release
+///
+/// Version 1 archives were zstd-compressed JSON; version 2 archives are
+/// `.lmg` archives.
+pub const SNAPSHOT_ARCHIVE_VERSION: u32 = 2;
+
+/// Default number of archives a shipper keeps in the store
+pub const DEFAULT_SNAPSHOT_RETENTION: usize = 3;
+
+/// File name suffix of snapshot archives
+const ARCHIVE_SUFFIX: &str = ".snapshot.lmg";
+
+/// File name suffix of snapshot manifests
+const MANIFEST_SUFFIX: &str = ".manifest.json";
+
+/// Where shipped snapshots are kept
+///
+/// Names are plain file names without directories.
+#[async_trait]
+pub trait SnapshotStore: Send + Sync {
+    /// Store `data` under `name`, replacing any existing object
+    async fn put(&self, name: &str, data: Vec<u8>) -> Result<()>;
+
+    /// The object stored under `name`, if there is one
+    async fn get(&self, name: &str) -> Result<Option<Vec<u8>>>;
+
+    /// Names of every stored object
+    async fn list(&self) -> Result<Vec<String>>;
+
+    /// Remove the object stored under `name`, if there is one
+    async fn delete(&self, name: &str) -> Result<()>;
+}
+
+/// Snapshot store backed by a directory
+///
+/// Objects are written to a temporary file and renamed into place, so readers
+/// never see a partly written file.
+#[derive(Debug, Clone)]
+pub struct DirectoryStore {
+    dir: PathBuf,
+}
+
+impl DirectoryStore {
+    /// Store objects in `dir`, creating it if needed
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory cannot be created.
+    pub fn new(dir: impl Into<PathBuf>) -> Result<Self> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir)?;
+        Ok(Self { dir })
+    }
+
+    /// The directory holding the objects
+    pub fn path(&self) -> &Path {
+        &self.dir
+    }
+}
+
+#[async_trait]
+impl SnapshotStore for DirectoryStore {
+    async fn put(&self, name: &str, data: Vec<u8>) -> Result<()> {
+        let temp = self.dir.join(format!(".{name}.tmp"));
+        tokio::fs::write(&temp, data).await?;
+        tokio::fs::rename(&temp, self.dir.join(name)).await?;
+        Ok(())
+    }
+
+    async fn get(&self, name: &str) -> Result<Option<Vec<u8>>> {
+        match tokio::fs::read(self.dir.join(name)).await {
+            Ok(data) => Ok(Some(data)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn list(&self) -> Result<Vec<String>> {
+        let mut names = Vec::new();
+        let mut entries = tokio::fs::read_dir(&self.dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            if let Some(name) = entry.file_name().to_str() {
+                if !name.starts_with('.') {
+                    names.push(name.to_string());
+                }
+            }
+        }
+        Ok(names)
+    }
+
+    async fn delete(&self, name: &str) -> Result<()> {
+        match tokio::fs::remove_file(self.dir.join(name)).await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Description of a shipped archive, stored next to it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotManifest {
+    /// Archive format version
+    pub version: u32,
+    /// Name shared by the archive and manifest; later snapshots sort later
+    pub name: String,
+    /// When the snapshot was taken on the primary
+    pub taken_at: DateTime<Utc>,
+    /// Last captured change the snapshot reflects, if change capture is on
+    pub cursor: Option<u64>,
+    /// Nodes in the archive
+    pub node_count: usize,
+    /// Edges in the archive
+    pub edge_count: usize,
+    /// Size of the archive in bytes
+    pub size: u64,
+    /// SHA-256 of the archive, as hex
+    pub sha256: String,
+}
+
+impl SnapshotManifest {
+    /// Object name of the archive
+    pub fn archive_name(&self) -> String {
+        format!("{}{ARCHIVE_SUFFIX}", self.name)
+    }
+
+    /// Object name of the manifest
+    pub fn manifest_name(&self) -> String {
+        format!("{}{MANIFEST_SUFFIX}", self.name)
+    }
+}
+
+/// Check that `archive` is the one `manifest` describes and decode it
+fn open_archive(manifest: &SnapshotManifest, archive: Vec<u8>) -> Result<(Vec<Node>, Vec<Edge>)> {
+    let name = manifest.archive_name();
+    let corrupt = |reason: String| Error::corruption("snapshots", name.as_bytes(), reason);
+    if manifest.version != SNAPSHOT_ARCHIVE_VERSION {
+        return Err(corrupt(format!(
+            "unsupported archive version {}",
+            manifest.version
+        )));
+    }
+    if archive.len() as u64 != manifest.size {
+        return Err(corrupt(format!(
+            "archive is {} bytes, manifest says {}",
+            archive.len(),
+            manifest.size
+        )));
+    }
+    if sha256_hex(&archive) != manifest.sha256 {
+        return Err(corrupt(
+            "archive checksum does not match manifest".to_string(),
+        ));
+    }
+
+    let (nodes, edges) = ArchiveReader::new(archive)
+        .and_then(|reader| reader.read_all())
+        .map_err(|e| corrupt(e.to_string()))?;
+    if nodes.len() != manifest.node_count || edges.len() != manifest.edge_count {
+        return Err(corrupt(format!(
+            "archive holds {} nodes and {} edges, manifest says {} and {}",
+            nodes.len(),
+            edges.len(),
+            manifest.node_count,
+            manifest.edge_count
+        )));
+    }
+    Ok((nodes, edges))
+}
+
+/// Manifests in `store`, oldest first
+///
+/// # Errors
+///
+/// Returns an error if the store cannot be listed or a manifest cannot be
+/// read.
+pub async fn list_manifests(store: &dyn SnapshotStore) -> Result<Vec<SnapshotManifest>> {
+    let mut names: Vec<String> = store
+        .list()
+        .await?
+        .into_iter()
+        .filter(|name| name.ends_with(MANIFEST_SUFFIX))
+        .collect();
+    names.sort();
+
+    let mut manifests = Vec::with_capacity(names.len());
+    for name in names {
+        if let Some(data) = store.get(&name).await? {
+            let manifest = serde_json::from_slice(&data)
+                .map_err(|e| Error::corruption("snapshots", name.as_bytes(), e))?;
+            manifests.push(manifest);
+        }
+    }
+    Ok(manifests)
+}
+
+/// Check the archive `manifest` describes without ingesting it
+///
+/// # Errors
+///
+/// Returns a corruption error if the archive is missing, or its size,
+/// checksum or contents do not match the manifest.
+pub async fn verify_snapshot(store: &dyn SnapshotStore, manifest: &SnapshotManifest) -> Result<()> {
+    let name = manifest.archive_name();
+    let archive = store
+        .get(&name)
+        .await?
+        .ok_or_else(|| Error::corruption("snapshots", name.as_bytes(), "archive is missing"))?;
+    open_archive(manifest, archive).map(|_| ())
+}
+
+/// Periodically ships snapshots of a primary graph to a store
+pub struct SnapshotShipper {
+    graph: Arc<AsyncMemoryGraph>,
+    store: Arc<dyn SnapshotStore>,
+    retention: usize,
+}
+
+impl SnapshotShipper {
+    /// Ship snapshots of `graph` to `store`
+    pub fn new(graph: Arc<AsyncMemoryGraph>, store: Arc<dyn SnapshotStore>) -> Self {
+        Self {
+            graph,
+            store,
+            retention: DEFAULT_SNAPSHOT_RETENTION,
+        }
+    }
+
+    /// Keep the `retention` newest archives, deleting older ones after each
+    /// shipment
+    pub fn with_retention(mut self, retention: usize) -> Self {
+        self.retention = retention.max(1);
+        self
+    }
+
+    /// Take a snapshot and ship it now
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the graph cannot be snapshotted or the store cannot
+    /// be written.
+    pub async fn ship(&self) -> Result<SnapshotManifest> {
+        let archive = ArchiveReader::new(self.graph.archive_graph(ArchiveKind::Snapshot).await?)?;
+        let contents = archive.manifest();
+        let taken_at = contents.created_at;
+        let manifest = SnapshotManifest {
+            version: SNAPSHOT_ARCHIVE_VERSION,
+            name: format!("snapshot-{:020}", taken_at.timestamp_micros()),
+            taken_at,
+            cursor: contents.cursor,
+            node_count: contents.node_count,
+            edge_count: contents.edge_count,
+            size: archive.size(),
+            sha256: sha256_hex(archive.as_bytes()),
+        };
+        let archive = archive.as_bytes().to_vec();
+        // The manifest goes last: replicas ignore archives without one
+        self.store.put(&manifest.archive_name(), archive).await?;
+        self.store
+            .put(
+                &manifest.manifest_name(),
+                serde_json::to_vec_pretty(&manifest)?,
+            )
+            .await?;
+
+        let manifests = list_manifests(self.store.as_ref()).await?;
+        let expired = manifests.len().saturating_sub(self.retention);
+        for old in &manifests[..expired] {
+            self.store.delete(&old.manifest_name()).await?;
+            self.store.delete(&old.archive_name()).await?;
+        }
+
+        tracing::info!(
+            "Shipped snapshot {} with {} nodes and {} edges",
+            manifest.name,
+            manifest.node_count,
+            manifest.edge_count
+        );
+        Ok(manifest)
+    }
+
+    /// Ship a snapshot every `interval` on a background task
+    ///
+    /// The loop stops when the handle is stopped or dropped, or when the
+    /// graph is closed. Failed shipments are logged and retried.
+    pub fn start(self: Arc<Self>, interval: Duration) -> ReplicationHandle {
+        let closing = self.graph.shutdown_signal();
+        spawn_loop(
+            Arc::downgrade(&self),
+            closing,
+            interval,
+            |shipper| async move {
+                if let Err(e) = shipper.ship().await {
+                    tracing::warn!("Snapshot shipment failed: {}", e);
+                }
+            },
+        )
+    }
+}
+
+/// How far a snapshot replica has got
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SnapshotReplicaStatus {
+    /// Manifest of the snapshot the replica holds
+    pub applied: Option<SnapshotManifest>,
+    /// Name of the newest snapshot in the store at the last check
+    pub latest_available: Option<String>,
+    /// Snapshots ingested since the replica was created
+    pub snapshots_applied: u64,
+    /// Archives rejected because they did not match their manifest
+    pub verification_failures: u64,
+    /// When the replica last checked the store
+    pub last_check: Option<DateTime<Utc>>,
+    /// Age of the applied snapshot at the last check
+    pub lag: Option<Duration>,
+}
+
+impl SnapshotReplicaStatus {
+    /// Whether the newest snapshot in the store is the one applied
+    pub fn is_caught_up(&self) -> bool {
+        self.applied.as_ref().map(|manifest| &manifest.name) == self.latest_available.as_ref()
+    }
+}
+
+/// Keeps a read-only graph in step with snapshots shipped to a store
+pub struct SnapshotReplica {
+    graph: Arc<AsyncMemoryGraph>,
+    store: Arc<dyn SnapshotStore>,
+    status: Mutex<SnapshotReplicaStatus>,
+}
+
+impl SnapshotReplica {
+    /// Ingest snapshots from `store` into `graph`, usually opened with
+    /// [`Config::read_only`](crate::Config)
+    pub fn new(graph: Arc<AsyncMemoryGraph>, store: Arc<dyn SnapshotStore>) -> Self {
+        Self {
+            graph,
+            store,
+            status: Mutex::new(SnapshotReplicaStatus::default()),
+        }
+    }
+
+    /// The replica graph
+    pub fn graph(&self) -> &Arc<AsyncMemoryGraph> {
+        &self.graph
+    }
+
+    /// Current snapshot, lag and failure counts
+    pub fn status(&self) -> SnapshotReplicaStatus {
+        self.status.lock().clone()
+    }
+
+    /// Ingest the newest snapshot in the store if it is not the one applied
+    ///
+    /// The archive is checked against its manifest before the graph is
+    /// touched; the graph's nodes and edges are then replaced by the
+    /// archive's. Returns the manifest of the snapshot ingested, if any.
+    ///
+    /// # Errors
+    ///
+    /// Returns a corruption error if the archive does not match its
+    /// manifest, leaving the graph as it was, and any store or storage error.
+    pub async fn ingest(&self) -> Result<Option<SnapshotManifest>> {
+        let latest = list_manifests(self.store.as_ref()).await?.pop();
+        let already_applied = {
+            let mut status = self.status.lock();
+            status.last_check = Some(Utc::now());
+            status.latest_available = latest.as_ref().map(|manifest| manifest.name.clone());
+            status.lag = status.applied.as_ref().map(|applied| age(applied.taken_at));
+            status.applied.is_some() && status.applied == latest
+        };
+        let Some(manifest) = latest else {
+            return Ok(None);
+        };
+        if already_applied {
+            return Ok(None);
+        }
+
+        let name = manifest.archive_name();
+        let archive = self.store.get(&name).await?;
+        let records = archive
+            .ok_or_else(|| Error::corruption("snapshots", name.as_bytes(), "archive is missing"))
+            .and_then(|archive| open_archive(&manifest, archive));
+        let (nodes, edges) = match records {
+            Ok(records) => records,
+            Err(e) => {
+                self.status.lock().verification_failures += 1;
+                return Err(e);
+            }
+        };
+
+        self.graph.replace_replicated(&nodes, &edges).await?;
+
+        let mut status = self.status.lock();
+        status.lag = Some(age(manifest.taken_at));
+        status.applied = Some(manifest.clone());
+        status.snapshots_applied += 1;
+        tracing::info!("Ingested snapshot {}", manifest.name);
+        Ok(Some(manifest))
+    }
+
+    /// Check for a new snapshot every `interval` on a background task
+    ///
+    /// The loop stops when the handle is stopped or dropped, or when the
+    /// graph is closed. Failed ingests are logged and retried.
+    pub fn start(self: Arc<Self>, interval: Duration) -> ReplicationHandle {
+        let closing = self.graph.shutdown_signal();
+        spawn_loop(
+            Arc::downgrade(&self),
+            closing,
+            interval,
+            |replica| async move {
+                if let Err(e) = replica.ingest().await {
+                    tracing::warn!("Snapshot ingest failed: {}", e);
+                }
+            },
+        )
+    }
+}
+
+/// Time since `taken_at`, zero if it lies in the future
+fn age(taken_at: DateTime<Utc>) -> Duration {
+    (Utc::now() - taken_at).to_std().unwrap_or_default()
+}
+
+/// Run `tick` every `interval` while `owner` is alive and the graph open
+pub(crate) fn spawn_loop<T, F, Fut>(
+    owner: Weak<T>,
+    mut closing: watch::Receiver<bool>,
+    interval: Duration,
+    tick: F,
+) -> ReplicationHandle
+where
+    T: Send + Sync + 'static,
+    F: Fn(Arc<T>) -> Fut + Send + 'static,
+    Fut: std::future::Future<Output = ()> + Send,
+{
+    let (shutdown, mut stop) = watch::channel(false);
+    let task = tokio::spawn(async move {
+        while !*closing.borrow() {
+            let Some(owner) = Weak::upgrade(&owner) else {
+                break;
+            };
+            tick(owner).await;
+
+            tokio::select! {
+                () = tokio::time::sleep(interval) => {}
+                _ = stop.changed() => break,
+                _ = closing.changed() => break,
+            }
+        }
+    });
+    ReplicationHandle::new(shutdown, task)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Config, TokenUsage};
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_ship_and_ingest_snapshots() {
+        let (primary_dir, replica_dir, store_dir) =
+            (tempdir().unwrap(), tempdir().unwrap(), tempdir().unwrap());
+        let primary = Arc::new(
+            AsyncMemoryGraph::open(Config::new(primary_dir.path()))
+                .await
+                .unwrap(),
+        );
+        let replica = Arc::new(
+            AsyncMemoryGraph::open(Config::new(replica_dir.path()).with_read_only(true))
+                .await
+                .unwrap(),
+        );
+        let store = Arc::new(DirectoryStore::new(store_dir.path()).unwrap());
+        let shipper = SnapshotShipper::new(Arc::clone(&primary), store.clone()).with_retention(2);
+        let ingester = SnapshotReplica::new(Arc::clone(&replica), store.clone());
+        assert!(ingester.ingest().await.unwrap().is_none());
+
+        let session = primary.create_session().await.unwrap();
+        let removed = primary
+            .add_prompt(session.id, "First".to_string(), None)
+            .await
+            .unwrap();
+        let first = shipper.ship().await.unwrap();
+        assert_eq!(
+            ingester.ingest().await.unwrap().map(|m| m.name),
+            Some(first.name.clone())
+        );
+        assert!(replica.get_node(&removed).await.unwrap().is_some());
+        assert!(ingester.ingest().await.unwrap().is_none());
+
+        // A newer snapshot replaces the replica's contents
+        primary.delete_node(removed).await.unwrap();
+        let prompt = primary
+            .add_prompt(session.id, "Second".to_string(), None)
+            .await
+            .unwrap();
+        primary
+            .add_response(prompt, "Answer".to_string(), TokenUsage::new(1, 1), None)
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(2)).await;
+        let second = shipper.ship().await.unwrap();
+        ingester.ingest().await.unwrap();
+        assert!(replica.get_node(&removed).await.unwrap().is_none());
+        assert!(replica.get_node(&prompt).await.unwrap().is_some());
+        assert!(primary.diff(&replica).await.unwrap().is_empty());
+
+        let status = ingester.status();
+        assert!(status.is_caught_up());
+        assert_eq!(status.snapshots_applied, 2);
+        assert!(status.lag.is_some());
+
+        // Old archives are pruned and a corrupted archive is rejected
+        tokio::time::sleep(Duration::from_millis(2)).await;
+        let third = shipper.ship().await.unwrap();
+        let manifests = list_manifests(store.as_ref()).await.unwrap();
+        assert_eq!(
+            manifests.iter().map(|m| &m.name).collect::<Vec<_>>(),
+            vec![&second.name, &third.name]
+        );
+        verify_snapshot(store.as_ref(), &third).await.unwrap();
+        store
+            .put(&third.archive_name(), b"not an archive".to_vec())
+            .await
+            .unwrap();
+        assert!(matches!(
+            verify_snapshot(store.as_ref(), &third).await,
+            Err(Error::Corruption { .. })
+        ));
+        assert!(ingester.ingest().await.is_err());
+        let status = ingester.status();
+        assert_eq!(status.verification_failures, 1);
+        assert!(!status.is_caught_up());
+        assert_eq!(status.applied.map(|m| m.name), Some(second.name));
+    }
+}
@@ -0,0 +1,244 @@
+//! Logging of slow operations
+//!
+//! Storage operations, queries and traversals that take longer than the
+//! thresholds in [`SlowOpConfig`] are logged as a `tracing` warning carrying
+//! the operation, its duration, the node and session involved and the size of
+//! its result. The most recent ones are also kept in memory, so
+//! [`AsyncMemoryGraph::slow_ops`](crate::engine::AsyncMemoryGraph::slow_ops)
+//! shows what was slow without digging through logs.
+
+use crate::{NodeId, SessionId, SlowOpConfig};
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Kind of operation, each with its own threshold
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SlowOpKind {
+    /// Read or write of nodes and edges
+    Storage,
+    /// Query builder execution
+    Query,
+    /// Traversal or path search
+    Traversal,
+}
+
+impl fmt::Display for SlowOpKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Storage => "storage",
+            Self::Query => "query",
+            Self::Traversal => "traversal",
+        })
+    }
+}
+
+/// What a timed operation worked on and returned
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SlowOpDetails {
+    /// Node the operation read, wrote or started from
+    pub node_id: Option<NodeId>,
+    /// Session the operation worked in
+    pub session_id: Option<SessionId>,
+    /// Number of records returned
+    pub result_size: Option<usize>,
+}
+
+impl SlowOpDetails {
+    /// An operation on `node_id`
+    pub fn node(node_id: NodeId) -> Self {
+        Self {
+            node_id: Some(node_id),
+            ..Self::default()
+        }
+    }
+
+    /// An operation within `session_id`
+    pub fn session(session_id: SessionId) -> Self {
+        Self {
+            session_id: Some(session_id),
+            ..Self::default()
+        }
+    }
+
+    /// Set the session the operation worked in
+    #[must_use]
+    pub fn in_session(mut self, session_id: SessionId) -> Self {
+        self.session_id = Some(session_id);
+        self
+    }
+
+    /// Set the number of records returned
+    #[must_use]
+    pub fn with_result_size(mut self, result_size: usize) -> Self {
+        self.result_size = Some(result_size);
+        self
+    }
+}
+
+/// An operation that took longer than its threshold
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SlowOp {
+    /// Kind of operation
+    pub kind: SlowOpKind,
+    /// Name of the operation, such as `get_node`
+    pub operation: String,
+    /// How long it took
+    pub duration: Duration,
+    /// Node it read, wrote or started from
+    pub node_id: Option<NodeId>,
+    /// Session it worked in
+    pub session_id: Option<SessionId>,
+    /// Number of records returned
+    pub result_size: Option<usize>,
+    /// When it finished
+    pub finished_at: DateTime<Utc>,
+}
+
+/// Thresholds and the most recent slow operations, shared by a graph and the
+/// queries and traversals it creates
+#[derive(Clone)]
+pub struct SlowOpLog {
+    config: SlowOpConfig,
+    recent: Arc<Mutex<VecDeque<SlowOp>>>,
+}
+
+impl SlowOpLog {
+    /// Log operations slower than the thresholds in `config`
+    pub fn new(config: SlowOpConfig) -> Self {
+        Self {
+            recent: Arc::new(Mutex::new(VecDeque::with_capacity(config.capacity))),
+            config,
+        }
+    }
+
+    /// Threshold for `kind`, `None` if its operations are not logged
+    pub fn threshold(&self, kind: SlowOpKind) -> Option<Duration> {
+        let threshold_ms = match kind {
+            SlowOpKind::Storage => self.config.storage_ms,
+            SlowOpKind::Query => self.config.query_ms,
+            SlowOpKind::Traversal => self.config.traversal_ms,
+        };
+        (threshold_ms > 0).then(|| Duration::from_millis(threshold_ms))
+    }
+
+    /// Log `operation`, started at `started`, if it took longer than the
+    /// threshold for `kind`
+    pub fn observe(
+        &self,
+        kind: SlowOpKind,
+        operation: &str,
+        started: Instant,
+        details: SlowOpDetails,
+    ) {
+        let duration = started.elapsed();
+        if self
+            .threshold(kind)
+            .is_some_and(|threshold| duration > threshold)
+        {
+            self.record(SlowOp {
+                kind,
+                operation: operation.to_string(),
+                duration,
+                node_id: details.node_id,
+                session_id: details.session_id,
+                result_size: details.result_size,
+                finished_at: Utc::now(),
+            });
+        }
+    }
+
+    /// Log `op` and keep it among the most recent slow operations
+    pub fn record(&self, op: SlowOp) {
+        tracing::warn!(
+            kind = %op.kind,
+            operation = %op.operation,
+            duration_ms = op.duration.as_millis() as u64,
+            node_id = ?op.node_id,
+            session_id = ?op.session_id,
+            result_size = ?op.result_size,
+            "Slow operation",
+        );
+        if self.config.capacity == 0 {
+            return;
+        }
+        let mut recent = self.recent.lock();
+        if recent.len() == self.config.capacity {
+            recent.pop_front();
+        }
+        recent.push_back(op);
+    }
+
+    /// The most recent slow operations, oldest first
+    pub fn recent(&self) -> Vec<SlowOp> {
+        self.recent.lock().iter().cloned().collect()
+    }
+
+    /// Forget the recorded slow operations
+    pub fn clear(&self) {
+        self.recent.lock().clear();
+    }
+}
+
+impl Default for SlowOpLog {
+    fn default() -> Self {
+        Self::new(SlowOpConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slow_op_log() {
+        let log = SlowOpLog::new(
+            SlowOpConfig::new()
+                .with_storage_threshold(0)
+                .with_query_threshold(1)
+                .with_capacity(2),
+        );
+        assert_eq!(log.threshold(SlowOpKind::Storage), None);
+
+        let long_ago = Instant::now()
+            .checked_sub(Duration::from_millis(50))
+            .unwrap();
+        log.observe(
+            SlowOpKind::Storage,
+            "get_node",
+            long_ago,
+            SlowOpDetails::default(),
+        );
+        log.observe(
+            SlowOpKind::Query,
+            "fast",
+            Instant::now(),
+            SlowOpDetails::default(),
+        );
+        assert!(log.recent().is_empty());
+
+        let session_id = SessionId::new();
+        for operation in ["first", "second", "third"] {
+            log.observe(
+                SlowOpKind::Query,
+                operation,
+                long_ago,
+                SlowOpDetails::session(session_id).with_result_size(3),
+            );
+        }
+        let recent = log.recent();
+        assert_eq!(recent.len(), 2);
+        assert_eq!(recent[0].operation, "second");
+        assert_eq!(recent[1].session_id, Some(session_id));
+        assert_eq!(recent[1].result_size, Some(3));
+        assert!(recent[1].duration >= Duration::from_millis(50));
+
+        log.clear();
+        assert!(log.recent().is_empty());
+    }
+}
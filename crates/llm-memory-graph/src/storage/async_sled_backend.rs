@@ -4,12 +4,27 @@
 //! using `tokio::task::spawn_blocking` to run blocking operations on a dedicated
 //! thread pool without blocking the async runtime.
 
-use super::{AsyncStorageBackend, SerializationFormat, SledBackend, StorageBackend, StorageStats};
+use super::{
+    AsyncStorageBackend, ColdTierReport, EdgePage, IdempotencyRecord, IndexRebuildReport, KvEntry,
+    NodeDegree, NodeEmbedding, NodeVersion, SerializationFormat, SessionCheckpoint, SledBackend,
+    SnapshotBackend, StorageBackend, StorageStats, TrashedNode,
+};
+use crate::audit::{AuditEntry, AuditFilter};
+use crate::changes::ChangeRecord;
+use crate::deadline::Deadline;
+use crate::usage::{UsageBucket, UsageGranularity};
 use crate::Result;
-use crate::{Edge, EdgeId, Node, NodeId, SessionId};
+use crate::{
+    AliasTarget, Config, ConversationSession, Edge, EdgeId, Node, NodeId, PromptNode,
+    ReadConsistency, SessionId, ViewDefinition, ViewRow, ViewTotals,
+};
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::collections::HashSet;
 use std::path::Path;
 use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast;
 
 /// Async wrapper around Sled-based storage backend
 ///
@@ -48,6 +63,22 @@ impl AsyncSledBackend {
         })
     }
 
+    /// Open or create one namespace of the database at `path`; see
+    /// [`SledBackend::open_namespace`]
+    pub async fn open_namespace<P: AsRef<Path>>(path: P, namespace: &str) -> Result<Self> {
+        let path_buf = path.as_ref().to_path_buf();
+        let namespace = namespace.to_string();
+
+        let inner =
+            tokio::task::spawn_blocking(move || SledBackend::open_namespace(path_buf, &namespace))
+                .await
+                .map_err(|e| crate::Error::RuntimeError(e.to_string()))??;
+
+        Ok(Self {
+            inner: Arc::new(inner),
+        })
+    }
+
     /// Open with a custom serialization format
     ///
     /// # Examples
@@ -79,6 +110,68 @@ impl AsyncSledBackend {
             inner: Arc::new(inner),
         })
     }
+
+    /// Open one namespace of the database with a custom serialization format
+    pub async fn open_namespace_with_format<P: AsRef<Path>>(
+        path: P,
+        namespace: &str,
+        format: SerializationFormat,
+    ) -> Result<Self> {
+        let path_buf = path.as_ref().to_path_buf();
+        let namespace = namespace.to_string();
+
+        let inner = tokio::task::spawn_blocking(move || {
+            SledBackend::open_namespace(path_buf, &namespace).map(|db| db.with_format(format))
+        })
+        .await
+        .map_err(|e| crate::Error::RuntimeError(e.to_string()))??;
+
+        Ok(Self {
+            inner: Arc::new(inner),
+        })
+    }
+
+    /// Open the database, namespace, format, compression and cold storage set
+    /// in `config`, waiting for the lock as configured
+    pub async fn open_with_config(config: &Config) -> Result<Self> {
+        let config = config.clone();
+        let inner = tokio::task::spawn_blocking(move || SledBackend::open_with_config(&config))
+            .await
+            .map_err(|e| crate::Error::RuntimeError(e.to_string()))??;
+
+        Ok(Self {
+            inner: Arc::new(inner),
+        })
+    }
+
+    /// Open one namespace with a custom serialization format, waiting up to
+    /// `wait` for another process to release the database; see
+    /// [`SledBackend::open_namespace_waiting`]
+    pub async fn open_namespace_waiting<P: AsRef<Path>>(
+        path: P,
+        namespace: &str,
+        format: SerializationFormat,
+        wait: Option<Duration>,
+    ) -> Result<Self> {
+        let path_buf = path.as_ref().to_path_buf();
+        let namespace = namespace.to_string();
+
+        let inner = tokio::task::spawn_blocking(move || {
+            SledBackend::open_namespace_waiting(path_buf, &namespace, wait)
+                .map(|db| db.with_format(format))
+        })
+        .await
+        .map_err(|e| crate::Error::RuntimeError(e.to_string()))??;
+
+        Ok(Self {
+            inner: Arc::new(inner),
+        })
+    }
+
+    /// Start or stop capturing changes; see [`SledBackend::set_change_capture`]
+    pub fn set_change_capture(&self, enable: bool) {
+        self.inner.set_change_capture(enable);
+    }
 }
 
 #[async_trait]
@@ -146,6 +239,28 @@ impl AsyncStorageBackend for AsyncSledBackend {
             .map_err(|e| crate::Error::RuntimeError(e.to_string()))?
     }
 
+    async fn get_session_nodes_until(
+        &self,
+        session_id: &SessionId,
+        deadline: &Deadline,
+    ) -> Result<Vec<Node>> {
+        let inner = Arc::clone(&self.inner);
+        let session_id = *session_id;
+        let scan_deadline = deadline.clone();
+
+        // The scan checks the deadline itself, so the blocking thread stops
+        // along with the caller
+        let scan = tokio::task::spawn_blocking(move || {
+            inner.get_session_nodes_until(&session_id, &scan_deadline)
+        });
+        deadline
+            .run("get_session_nodes", None, async {
+                scan.await
+                    .map_err(|e| crate::Error::RuntimeError(e.to_string()))?
+            })
+            .await
+    }
+
     async fn get_outgoing_edges(&self, node_id: &NodeId) -> Result<Vec<Edge>> {
         let inner = Arc::clone(&self.inner);
         let node_id = *node_id;
@@ -172,6 +287,17 @@ impl AsyncStorageBackend for AsyncSledBackend {
             .map_err(|e| crate::Error::RuntimeError(e.to_string()))?
     }
 
+    async fn prepare_read(&self, consistency: ReadConsistency) -> Result<()> {
+        if consistency != ReadConsistency::RequireFlushed {
+            return Ok(());
+        }
+        let inner = Arc::clone(&self.inner);
+
+        tokio::task::spawn_blocking(move || inner.prepare_read(consistency))
+            .await
+            .map_err(|e| crate::Error::RuntimeError(e.to_string()))?
+    }
+
     async fn stats(&self) -> Result<StorageStats> {
         let inner = Arc::clone(&self.inner);
 
@@ -184,32 +310,18 @@ impl AsyncStorageBackend for AsyncSledBackend {
         let inner = Arc::clone(&self.inner);
         let nodes = nodes.to_vec();
 
-        tokio::task::spawn_blocking(move || {
-            let mut ids = Vec::with_capacity(nodes.len());
-            for node in &nodes {
-                inner.store_node(node)?;
-                ids.push(node.id());
-            }
-            Ok(ids)
-        })
-        .await
-        .map_err(|e| crate::Error::RuntimeError(e.to_string()))?
+        tokio::task::spawn_blocking(move || inner.store_nodes_batch(&nodes))
+            .await
+            .map_err(|e| crate::Error::RuntimeError(e.to_string()))?
     }
 
     async fn store_edges_batch(&self, edges: &[Edge]) -> Result<Vec<EdgeId>> {
         let inner = Arc::clone(&self.inner);
         let edges = edges.to_vec();
 
-        tokio::task::spawn_blocking(move || {
-            let mut ids = Vec::with_capacity(edges.len());
-            for edge in &edges {
-                inner.store_edge(edge)?;
-                ids.push(edge.id);
-            }
-            Ok(ids)
-        })
-        .await
-        .map_err(|e| crate::Error::RuntimeError(e.to_string()))?
+        tokio::task::spawn_blocking(move || inner.store_edges_batch(&edges))
+            .await
+            .map_err(|e| crate::Error::RuntimeError(e.to_string()))?
     }
 
     fn get_session_nodes_stream(
@@ -253,6 +365,441 @@ impl AsyncStorageBackend for AsyncSledBackend {
         .await
         .map_err(|e| crate::Error::RuntimeError(e.to_string()))?
     }
+
+    async fn append_audit_entry(&self, entry: AuditEntry) -> Result<AuditEntry> {
+        let inner = Arc::clone(&self.inner);
+
+        tokio::task::spawn_blocking(move || inner.append_audit_entry(entry))
+            .await
+            .map_err(|e| crate::Error::RuntimeError(e.to_string()))?
+    }
+
+    async fn audit_entries(&self, filter: &AuditFilter) -> Result<Vec<AuditEntry>> {
+        let inner = Arc::clone(&self.inner);
+        let filter = filter.clone();
+
+        tokio::task::spawn_blocking(move || inner.audit_entries(&filter))
+            .await
+            .map_err(|e| crate::Error::RuntimeError(e.to_string()))?
+    }
+
+    async fn trash_node(&self, id: &NodeId) -> Result<Option<TrashedNode>> {
+        let inner = Arc::clone(&self.inner);
+        let id = *id;
+
+        tokio::task::spawn_blocking(move || inner.trash_node(&id))
+            .await
+            .map_err(|e| crate::Error::RuntimeError(e.to_string()))?
+    }
+
+    async fn restore_node(&self, id: &NodeId) -> Result<Option<Node>> {
+        let inner = Arc::clone(&self.inner);
+        let id = *id;
+
+        tokio::task::spawn_blocking(move || inner.restore_node(&id))
+            .await
+            .map_err(|e| crate::Error::RuntimeError(e.to_string()))?
+    }
+
+    async fn trashed_nodes(&self) -> Result<Vec<TrashedNode>> {
+        let inner = Arc::clone(&self.inner);
+
+        tokio::task::spawn_blocking(move || inner.trashed_nodes())
+            .await
+            .map_err(|e| crate::Error::RuntimeError(e.to_string()))?
+    }
+
+    async fn purge_trash(
+        &self,
+        cutoff: DateTime<Utc>,
+        keep: &HashSet<SessionId>,
+    ) -> Result<Vec<NodeId>> {
+        let inner = Arc::clone(&self.inner);
+        let keep = keep.clone();
+
+        tokio::task::spawn_blocking(move || inner.purge_trash(cutoff, &keep))
+            .await
+            .map_err(|e| crate::Error::RuntimeError(e.to_string()))?
+    }
+
+    async fn list_sessions(&self) -> Result<Vec<ConversationSession>> {
+        let inner = Arc::clone(&self.inner);
+
+        tokio::task::spawn_blocking(move || inner.list_sessions())
+            .await
+            .map_err(|e| crate::Error::RuntimeError(e.to_string()))?
+    }
+
+    async fn find_prompts_by_content(
+        &self,
+        content: &str,
+        session_id: Option<&SessionId>,
+    ) -> Result<Vec<PromptNode>> {
+        let inner = Arc::clone(&self.inner);
+        let content = content.to_string();
+        let session_id = session_id.copied();
+
+        tokio::task::spawn_blocking(move || {
+            inner.find_prompts_by_content(&content, session_id.as_ref())
+        })
+        .await
+        .map_err(|e| crate::Error::RuntimeError(e.to_string()))?
+    }
+
+    async fn move_session_nodes(
+        &self,
+        source: &SessionId,
+        target: &SessionId,
+    ) -> Result<Vec<NodeId>> {
+        let inner = Arc::clone(&self.inner);
+        let (source, target) = (*source, *target);
+
+        tokio::task::spawn_blocking(move || inner.move_session_nodes(&source, &target))
+            .await
+            .map_err(|e| crate::Error::RuntimeError(e.to_string()))?
+    }
+
+    async fn compact_indexes(&self) -> Result<usize> {
+        let inner = Arc::clone(&self.inner);
+
+        tokio::task::spawn_blocking(move || inner.compact_indexes())
+            .await
+            .map_err(|e| crate::Error::RuntimeError(e.to_string()))?
+    }
+
+    async fn rebuild_indexes(&self) -> Result<IndexRebuildReport> {
+        let inner = Arc::clone(&self.inner);
+
+        tokio::task::spawn_blocking(move || inner.rebuild_indexes())
+            .await
+            .map_err(|e| crate::Error::RuntimeError(e.to_string()))?
+    }
+
+    async fn tier_cold(&self, cutoff: DateTime<Utc>) -> Result<ColdTierReport> {
+        let inner = Arc::clone(&self.inner);
+
+        tokio::task::spawn_blocking(move || inner.tier_cold(cutoff))
+            .await
+            .map_err(|e| crate::Error::RuntimeError(e.to_string()))?
+    }
+
+    async fn all_nodes(&self) -> Result<Vec<Node>> {
+        let inner = Arc::clone(&self.inner);
+
+        tokio::task::spawn_blocking(move || inner.all_nodes())
+            .await
+            .map_err(|e| crate::Error::RuntimeError(e.to_string()))?
+    }
+
+    async fn all_edges(&self) -> Result<Vec<Edge>> {
+        let inner = Arc::clone(&self.inner);
+
+        tokio::task::spawn_blocking(move || inner.all_edges())
+            .await
+            .map_err(|e| crate::Error::RuntimeError(e.to_string()))?
+    }
+
+    async fn changes_since(&self, cursor: Option<u64>, limit: usize) -> Result<Vec<ChangeRecord>> {
+        let inner = Arc::clone(&self.inner);
+
+        tokio::task::spawn_blocking(move || inner.changes_since(cursor, limit))
+            .await
+            .map_err(|e| crate::Error::RuntimeError(e.to_string()))?
+    }
+
+    async fn latest_change_cursor(&self) -> Result<Option<u64>> {
+        let inner = Arc::clone(&self.inner);
+
+        tokio::task::spawn_blocking(move || inner.latest_change_cursor())
+            .await
+            .map_err(|e| crate::Error::RuntimeError(e.to_string()))?
+    }
+
+    fn subscribe_changes(&self) -> Result<broadcast::Receiver<ChangeRecord>> {
+        Ok(self.inner.subscribe_changes())
+    }
+
+    async fn prune_changes(&self, cursor: u64) -> Result<usize> {
+        let inner = Arc::clone(&self.inner);
+
+        tokio::task::spawn_blocking(move || inner.prune_changes(cursor))
+            .await
+            .map_err(|e| crate::Error::RuntimeError(e.to_string()))?
+    }
+
+    async fn idempotency_record(&self, key: &str) -> Result<Option<IdempotencyRecord>> {
+        let inner = Arc::clone(&self.inner);
+        let key = key.to_string();
+
+        tokio::task::spawn_blocking(move || inner.idempotency_record(&key))
+            .await
+            .map_err(|e| crate::Error::RuntimeError(e.to_string()))?
+    }
+
+    async fn store_idempotency_record(&self, key: &str, record: &IdempotencyRecord) -> Result<()> {
+        let inner = Arc::clone(&self.inner);
+        let key = key.to_string();
+        let record = *record;
+
+        tokio::task::spawn_blocking(move || inner.store_idempotency_record(&key, &record))
+            .await
+            .map_err(|e| crate::Error::RuntimeError(e.to_string()))?
+    }
+
+    async fn store_checkpoint(&self, checkpoint: &SessionCheckpoint) -> Result<()> {
+        let inner = Arc::clone(&self.inner);
+        let checkpoint = checkpoint.clone();
+
+        tokio::task::spawn_blocking(move || inner.store_checkpoint(&checkpoint))
+            .await
+            .map_err(|e| crate::Error::RuntimeError(e.to_string()))?
+    }
+
+    async fn session_checkpoints(&self, session_id: &SessionId) -> Result<Vec<SessionCheckpoint>> {
+        let inner = Arc::clone(&self.inner);
+        let session_id = *session_id;
+
+        tokio::task::spawn_blocking(move || inner.session_checkpoints(&session_id))
+            .await
+            .map_err(|e| crate::Error::RuntimeError(e.to_string()))?
+    }
+
+    async fn store_node_version(&self, version: &NodeVersion) -> Result<()> {
+        let inner = Arc::clone(&self.inner);
+        let version = version.clone();
+
+        tokio::task::spawn_blocking(move || inner.store_node_version(&version))
+            .await
+            .map_err(|e| crate::Error::RuntimeError(e.to_string()))?
+    }
+
+    async fn node_versions(&self, node_id: &NodeId) -> Result<Vec<NodeVersion>> {
+        let inner = Arc::clone(&self.inner);
+        let node_id = *node_id;
+
+        tokio::task::spawn_blocking(move || inner.node_versions(&node_id))
+            .await
+            .map_err(|e| crate::Error::RuntimeError(e.to_string()))?
+    }
+
+    async fn node_version_ids(&self) -> Result<Vec<NodeId>> {
+        let inner = Arc::clone(&self.inner);
+
+        tokio::task::spawn_blocking(move || inner.node_version_ids())
+            .await
+            .map_err(|e| crate::Error::RuntimeError(e.to_string()))?
+    }
+
+    async fn usage_rollups(
+        &self,
+        granularity: UsageGranularity,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<UsageBucket>> {
+        let inner = Arc::clone(&self.inner);
+
+        tokio::task::spawn_blocking(move || inner.usage_rollups(granularity, from, to))
+            .await
+            .map_err(|e| crate::Error::RuntimeError(e.to_string()))?
+    }
+
+    async fn kv_get(&self, key: &str) -> Result<Option<KvEntry>> {
+        let inner = Arc::clone(&self.inner);
+        let key = key.to_string();
+
+        tokio::task::spawn_blocking(move || inner.kv_get(&key))
+            .await
+            .map_err(|e| crate::Error::RuntimeError(e.to_string()))?
+    }
+
+    async fn kv_set(&self, entry: &KvEntry) -> Result<()> {
+        let inner = Arc::clone(&self.inner);
+        let entry = entry.clone();
+
+        tokio::task::spawn_blocking(move || inner.kv_set(&entry))
+            .await
+            .map_err(|e| crate::Error::RuntimeError(e.to_string()))?
+    }
+
+    async fn kv_delete(&self, key: &str) -> Result<bool> {
+        let inner = Arc::clone(&self.inner);
+        let key = key.to_string();
+
+        tokio::task::spawn_blocking(move || inner.kv_delete(&key))
+            .await
+            .map_err(|e| crate::Error::RuntimeError(e.to_string()))?
+    }
+
+    async fn kv_list(&self, prefix: &str) -> Result<Vec<KvEntry>> {
+        let inner = Arc::clone(&self.inner);
+        let prefix = prefix.to_string();
+
+        tokio::task::spawn_blocking(move || inner.kv_list(&prefix))
+            .await
+            .map_err(|e| crate::Error::RuntimeError(e.to_string()))?
+    }
+
+    async fn store_embedding(&self, embedding: &NodeEmbedding) -> Result<()> {
+        let inner = Arc::clone(&self.inner);
+        let embedding = embedding.clone();
+
+        tokio::task::spawn_blocking(move || inner.store_embedding(&embedding))
+            .await
+            .map_err(|e| crate::Error::RuntimeError(e.to_string()))?
+    }
+
+    async fn get_embedding(&self, node_id: &NodeId) -> Result<Option<NodeEmbedding>> {
+        let inner = Arc::clone(&self.inner);
+        let node_id = *node_id;
+
+        tokio::task::spawn_blocking(move || inner.get_embedding(&node_id))
+            .await
+            .map_err(|e| crate::Error::RuntimeError(e.to_string()))?
+    }
+
+    async fn all_embeddings(&self) -> Result<Vec<NodeEmbedding>> {
+        let inner = Arc::clone(&self.inner);
+
+        tokio::task::spawn_blocking(move || inner.all_embeddings())
+            .await
+            .map_err(|e| crate::Error::RuntimeError(e.to_string()))?
+    }
+
+    async fn set_alias(&self, alias: &str, target: AliasTarget) -> Result<Option<AliasTarget>> {
+        let inner = Arc::clone(&self.inner);
+        let alias = alias.to_string();
+
+        tokio::task::spawn_blocking(move || inner.set_alias(&alias, target))
+            .await
+            .map_err(|e| crate::Error::RuntimeError(e.to_string()))?
+    }
+
+    async fn resolve_alias(&self, alias: &str) -> Result<Option<AliasTarget>> {
+        let inner = Arc::clone(&self.inner);
+        let alias = alias.to_string();
+
+        tokio::task::spawn_blocking(move || inner.resolve_alias(&alias))
+            .await
+            .map_err(|e| crate::Error::RuntimeError(e.to_string()))?
+    }
+
+    async fn remove_alias(&self, alias: &str) -> Result<Option<AliasTarget>> {
+        let inner = Arc::clone(&self.inner);
+        let alias = alias.to_string();
+
+        tokio::task::spawn_blocking(move || inner.remove_alias(&alias))
+            .await
+            .map_err(|e| crate::Error::RuntimeError(e.to_string()))?
+    }
+
+    async fn list_aliases(&self, prefix: &str) -> Result<Vec<(String, AliasTarget)>> {
+        let inner = Arc::clone(&self.inner);
+        let prefix = prefix.to_string();
+
+        tokio::task::spawn_blocking(move || inner.list_aliases(&prefix))
+            .await
+            .map_err(|e| crate::Error::RuntimeError(e.to_string()))?
+    }
+
+    async fn list_views(&self) -> Result<Vec<ViewDefinition>> {
+        Ok(self.inner.views())
+    }
+
+    async fn define_view(&self, view: &ViewDefinition) -> Result<bool> {
+        let inner = Arc::clone(&self.inner);
+        let view = view.clone();
+
+        tokio::task::spawn_blocking(move || inner.define_view(&view))
+            .await
+            .map_err(|e| crate::Error::RuntimeError(e.to_string()))?
+    }
+
+    async fn drop_view(&self, name: &str) -> Result<bool> {
+        let inner = Arc::clone(&self.inner);
+        let name = name.to_string();
+
+        tokio::task::spawn_blocking(move || inner.drop_view(&name))
+            .await
+            .map_err(|e| crate::Error::RuntimeError(e.to_string()))?
+    }
+
+    async fn view_totals(&self, name: &str, group: &[String]) -> Result<Option<ViewTotals>> {
+        let inner = Arc::clone(&self.inner);
+        let name = name.to_string();
+        let group = group.to_vec();
+
+        tokio::task::spawn_blocking(move || inner.view_totals(&name, &group))
+            .await
+            .map_err(|e| crate::Error::RuntimeError(e.to_string()))?
+    }
+
+    async fn view_rows(&self, name: &str) -> Result<Vec<ViewRow>> {
+        let inner = Arc::clone(&self.inner);
+        let name = name.to_string();
+
+        tokio::task::spawn_blocking(move || inner.view_rows(&name))
+            .await
+            .map_err(|e| crate::Error::RuntimeError(e.to_string()))?
+    }
+
+    async fn rebuild_views(&self) -> Result<usize> {
+        let inner = Arc::clone(&self.inner);
+
+        tokio::task::spawn_blocking(move || inner.rebuild_views())
+            .await
+            .map_err(|e| crate::Error::RuntimeError(e.to_string()))?
+    }
+
+    async fn snapshot(&self) -> Result<SnapshotBackend> {
+        let inner = Arc::clone(&self.inner);
+
+        tokio::task::spawn_blocking(move || inner.snapshot())
+            .await
+            .map_err(|e| crate::Error::RuntimeError(e.to_string()))?
+    }
+
+    async fn get_outgoing_edges_page(
+        &self,
+        node_id: &NodeId,
+        cursor: Option<&EdgeId>,
+        limit: usize,
+    ) -> Result<EdgePage> {
+        let inner = Arc::clone(&self.inner);
+        let node_id = *node_id;
+        let cursor = cursor.copied();
+
+        tokio::task::spawn_blocking(move || {
+            inner.get_outgoing_edges_page(&node_id, cursor.as_ref(), limit)
+        })
+        .await
+        .map_err(|e| crate::Error::RuntimeError(e.to_string()))?
+    }
+
+    async fn get_incoming_edges_page(
+        &self,
+        node_id: &NodeId,
+        cursor: Option<&EdgeId>,
+        limit: usize,
+    ) -> Result<EdgePage> {
+        let inner = Arc::clone(&self.inner);
+        let node_id = *node_id;
+        let cursor = cursor.copied();
+
+        tokio::task::spawn_blocking(move || {
+            inner.get_incoming_edges_page(&node_id, cursor.as_ref(), limit)
+        })
+        .await
+        .map_err(|e| crate::Error::RuntimeError(e.to_string()))?
+    }
+
+    async fn node_degree(&self, node_id: &NodeId) -> Result<NodeDegree> {
+        let inner = Arc::clone(&self.inner);
+        let node_id = *node_id;
+
+        tokio::task::spawn_blocking(move || inner.node_degree(&node_id))
+            .await
+            .map_err(|e| crate::Error::RuntimeError(e.to_string()))?
+    }
 }
 
 #[cfg(test)]
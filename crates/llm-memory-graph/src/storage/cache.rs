@@ -5,18 +5,39 @@
 
 use crate::{Edge, EdgeId, Node, NodeId};
 use moka::future::Cache;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
 use std::time::Duration;
 
 /// Multi-level cache for nodes and edges
 ///
 /// Provides LRU-based caching with automatic eviction and TTL support.
-/// All operations are async and thread-safe.
+/// All operations are async and thread-safe. Nodes are stored behind an
+/// [`Arc`], so a cache hit never copies node content.
 #[derive(Clone)]
 pub struct StorageCache {
     /// Cache for node lookups by ID
-    node_cache: Cache<NodeId, Node>,
+    node_cache: Cache<NodeId, Arc<Node>>,
     /// Cache for edge lookups by ID
     edge_cache: Cache<EdgeId, Edge>,
+    /// Lookup outcomes, shared by clones
+    counters: Arc<CacheCounters>,
+}
+
+/// Hits and misses of cache lookups
+#[derive(Default)]
+struct CacheCounters {
+    node_hits: AtomicU64,
+    node_misses: AtomicU64,
+    edge_hits: AtomicU64,
+    edge_misses: AtomicU64,
+}
+
+/// Count a lookup as a hit or a miss
+fn record<T>(found: Option<T>, hits: &AtomicU64, misses: &AtomicU64) -> Option<T> {
+    let counter = if found.is_some() { hits } else { misses };
+    counter.fetch_add(1, Ordering::Relaxed);
+    found
 }
 
 impl StorageCache {
@@ -34,17 +55,18 @@ impl StorageCache {
     pub fn with_capacity(node_capacity: u64, edge_capacity: u64) -> Self {
         let node_cache = Cache::builder()
             .max_capacity(node_capacity)
-            .time_to_live(Duration::from_secs(300)) // 5 minutes
+            .time_to_live(Duration::from_mins(5))
             .build();
 
         let edge_cache = Cache::builder()
             .max_capacity(edge_capacity)
-            .time_to_live(Duration::from_secs(300))
+            .time_to_live(Duration::from_mins(5))
             .build();
 
         Self {
             node_cache,
             edge_cache,
+            counters: Arc::default(),
         }
     }
 
@@ -63,17 +85,22 @@ impl StorageCache {
         Self {
             node_cache,
             edge_cache,
+            counters: Arc::default(),
         }
     }
 
-    /// Get a node from cache
-    pub async fn get_node(&self, id: &NodeId) -> Option<Node> {
-        self.node_cache.get(id).await
+    /// Get a shared reference to a node from cache
+    pub async fn get_node(&self, id: &NodeId) -> Option<Arc<Node>> {
+        record(
+            self.node_cache.get(id).await,
+            &self.counters.node_hits,
+            &self.counters.node_misses,
+        )
     }
 
     /// Insert a node into cache
-    pub async fn insert_node(&self, id: NodeId, node: Node) {
-        self.node_cache.insert(id, node).await;
+    pub async fn insert_node(&self, id: NodeId, node: impl Into<Arc<Node>>) {
+        self.node_cache.insert(id, node.into()).await;
     }
 
     /// Remove a node from cache
@@ -83,7 +110,11 @@ impl StorageCache {
 
     /// Get an edge from cache
     pub async fn get_edge(&self, id: &EdgeId) -> Option<Edge> {
-        self.edge_cache.get(id).await
+        record(
+            self.edge_cache.get(id).await,
+            &self.counters.edge_hits,
+            &self.counters.edge_misses,
+        )
     }
 
     /// Insert an edge into cache
@@ -98,8 +129,8 @@ impl StorageCache {
 
     /// Get cache statistics
     ///
-    /// Returns current cache sizes. Note that hit/miss rates are not tracked
-    /// by default in moka cache to minimize performance overhead.
+    /// Returns current cache sizes and the hits and misses of every lookup
+    /// since the cache was created.
     pub async fn stats(&self) -> CacheStats {
         // Sync pending tasks to get accurate counts
         self.node_cache.run_pending_tasks().await;
@@ -108,10 +139,10 @@ impl StorageCache {
         CacheStats {
             node_cache_size: self.node_cache.entry_count(),
             edge_cache_size: self.edge_cache.entry_count(),
-            node_cache_hits: 0, // Hit tracking not enabled by default
-            node_cache_misses: 0,
-            edge_cache_hits: 0,
-            edge_cache_misses: 0,
+            node_cache_hits: self.counters.node_hits.load(Ordering::Relaxed),
+            node_cache_misses: self.counters.node_misses.load(Ordering::Relaxed),
+            edge_cache_hits: self.counters.edge_hits.load(Ordering::Relaxed),
+            edge_cache_misses: self.counters.edge_misses.load(Ordering::Relaxed),
         }
     }
 
@@ -199,6 +230,10 @@ mod tests {
         let cached = cache.get_node(&node_id).await;
         assert!(cached.is_some());
         assert_eq!(cached.unwrap().id(), node_id);
+
+        let stats = cache.stats().await;
+        assert_eq!((stats.node_cache_hits, stats.node_cache_misses), (1, 1));
+        assert!((stats.node_hit_rate() - 0.5).abs() < f64::EPSILON);
     }
 
     #[tokio::test]
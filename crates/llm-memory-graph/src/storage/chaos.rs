@@ -0,0 +1,759 @@
+//! Failure injection for testing
+//!
+//! [`ChaosBackend`] wraps another backend and, as its [`ChaosConfig`]
+//! directs, delays operations, fails them, or applies a write and then
+//! reports it as failed. A batch write hit by such a partial failure stores
+//! only a prefix of its records. Faults are drawn from a seeded generator,
+//! so a test sees the same faults on every run; [`ChaosBackend::fail_next`]
+//! scripts them exactly.
+//!
+//! Built for tests, and for downstream crates with the `chaos` feature.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! # use llm_memory_graph::engine::AsyncMemoryGraph;
+//! # use llm_memory_graph::storage::{AsyncSledBackend, ChaosBackend, ChaosConfig};
+//! # use llm_memory_graph::Config;
+//! # use std::sync::Arc;
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let sled = Arc::new(AsyncSledBackend::open("./data/chaos.db").await?);
+//! let chaos = Arc::new(ChaosBackend::new(
+//!     sled,
+//!     ChaosConfig::new(42).with_error_rate(0.1),
+//! ));
+//! let graph = AsyncMemoryGraph::open_with_backend(Config::default(), chaos.clone());
+//! # Ok(())
+//! # }
+//! ```
+
+use super::{
+    AsyncStorageBackend, ColdTierReport, EdgePage, IdempotencyRecord, IndexRebuildReport, KvEntry,
+    NodeDegree, NodeEmbedding, NodeVersion, SessionCheckpoint, SnapshotBackend, StorageStats,
+    TrashedNode,
+};
+use crate::audit::{AuditEntry, AuditFilter};
+use crate::changes::ChangeRecord;
+use crate::usage::{UsageBucket, UsageGranularity};
+use crate::{
+    AliasTarget, ConversationSession, Edge, EdgeId, Error, Node, NodeId, PromptNode, Result,
+    SessionId, ViewDefinition, ViewRow, ViewTotals,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use parking_lot::{Mutex, RwLock};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast;
+
+/// Prefix of the message of every error a [`ChaosBackend`] injects
+const INJECTED: &str = "chaos: injected";
+
+/// Which faults a [`ChaosBackend`] injects, and how often
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChaosConfig {
+    /// Seed for the generator that decides which operations fail
+    pub seed: u64,
+    /// Delay added to every operation
+    pub latency: Duration,
+    /// Up to this much more delay, chosen at random per operation
+    pub jitter: Duration,
+    /// Chance, from 0 to 1, that an operation fails without taking effect
+    pub error_rate: f64,
+    /// Chance, from 0 to 1, that a write takes effect, in part for batches,
+    /// but is reported as failed
+    pub partial_write_rate: f64,
+    /// Leave reads alone and only fail writes
+    pub writes_only: bool,
+}
+
+impl ChaosConfig {
+    /// No faults, with `seed` for any enabled later
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            latency: Duration::ZERO,
+            jitter: Duration::ZERO,
+            error_rate: 0.0,
+            partial_write_rate: 0.0,
+            writes_only: false,
+        }
+    }
+
+    /// Delay every operation by `latency`
+    pub fn with_latency(mut self, latency: Duration) -> Self {
+        self.latency = latency;
+        self
+    }
+
+    /// Delay every operation by up to `jitter` more
+    pub fn with_jitter(mut self, jitter: Duration) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Fail this share of operations, clamped to 0..=1
+    pub fn with_error_rate(mut self, rate: f64) -> Self {
+        self.error_rate = rate.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Report this share of writes as failed after applying them, clamped
+    /// to 0..=1
+    pub fn with_partial_write_rate(mut self, rate: f64) -> Self {
+        self.partial_write_rate = rate.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Only fail writes
+    pub fn writes_only(mut self) -> Self {
+        self.writes_only = true;
+        self
+    }
+}
+
+impl Default for ChaosConfig {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+/// Counts of what a [`ChaosBackend`] has done
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ChaosStats {
+    /// Operations passed through the backend
+    pub operations: u64,
+    /// Operations failed without taking effect
+    pub injected_errors: u64,
+    /// Writes applied, at least in part, and then reported as failed
+    pub partial_writes: u64,
+}
+
+/// Backend that injects latency and failures into calls to `inner`
+pub struct ChaosBackend {
+    inner: Arc<dyn AsyncStorageBackend>,
+    config: RwLock<ChaosConfig>,
+    rng: Mutex<StdRng>,
+    enabled: AtomicBool,
+    scripted_failures: AtomicUsize,
+    operations: AtomicU64,
+    injected_errors: AtomicU64,
+    partial_writes: AtomicU64,
+}
+
+/// What happens to one operation
+struct Fault {
+    delay: Duration,
+    fail: bool,
+    partial: bool,
+}
+
+impl ChaosBackend {
+    /// Wrap `inner`, injecting the faults `config` describes
+    pub fn new(inner: Arc<dyn AsyncStorageBackend>, config: ChaosConfig) -> Self {
+        Self {
+            inner,
+            rng: Mutex::new(StdRng::seed_from_u64(config.seed)),
+            config: RwLock::new(config),
+            enabled: AtomicBool::new(true),
+            scripted_failures: AtomicUsize::new(0),
+            operations: AtomicU64::new(0),
+            injected_errors: AtomicU64::new(0),
+            partial_writes: AtomicU64::new(0),
+        }
+    }
+
+    /// The wrapped backend, which never injects faults
+    pub fn inner(&self) -> &Arc<dyn AsyncStorageBackend> {
+        &self.inner
+    }
+
+    /// Replace the configuration, reseeding the generator
+    pub fn set_config(&self, config: ChaosConfig) {
+        *self.rng.lock() = StdRng::seed_from_u64(config.seed);
+        *self.config.write() = config;
+    }
+
+    /// Turn fault injection on or off, for example while a test sets up
+    /// data; operations pass straight through while it is off
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::SeqCst);
+    }
+
+    /// Fail the next `count` operations, whatever the configuration says
+    pub fn fail_next(&self, count: usize) {
+        self.scripted_failures.store(count, Ordering::SeqCst);
+    }
+
+    /// What the backend has done so far
+    pub fn stats(&self) -> ChaosStats {
+        ChaosStats {
+            operations: self.operations.load(Ordering::Relaxed),
+            injected_errors: self.injected_errors.load(Ordering::Relaxed),
+            partial_writes: self.partial_writes.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Whether `error` was injected by a [`ChaosBackend`]
+    pub fn is_injected(error: &Error) -> bool {
+        matches!(error.root_cause(), Error::StorageError(message) if message.starts_with(INJECTED))
+    }
+
+    /// Decide the fate of one operation
+    fn draw(&self, write: bool) -> Fault {
+        self.operations.fetch_add(1, Ordering::Relaxed);
+        if !self.enabled.load(Ordering::SeqCst) {
+            return Fault {
+                delay: Duration::ZERO,
+                fail: false,
+                partial: false,
+            };
+        }
+
+        let config = self.config.read();
+        let mut rng = self.rng.lock();
+        let jitter = if config.jitter.is_zero() {
+            Duration::ZERO
+        } else {
+            config.jitter.mul_f64(rng.gen::<f64>())
+        };
+        let scripted = self
+            .scripted_failures
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+            .is_ok();
+        let faulty = write || !config.writes_only;
+        let fail = scripted || (faulty && rng.gen_bool(config.error_rate));
+        let partial = !fail && write && rng.gen_bool(config.partial_write_rate);
+        Fault {
+            delay: config.latency + jitter,
+            fail,
+            partial,
+        }
+    }
+
+    /// Delay, then fail if drawn to; returns whether a write should be
+    /// reported as failed after it is applied
+    async fn inject(&self, operation: &str, write: bool) -> Result<bool> {
+        let fault = self.draw(write);
+        if !fault.delay.is_zero() {
+            tokio::time::sleep(fault.delay).await;
+        }
+        if fault.fail {
+            self.injected_errors.fetch_add(1, Ordering::Relaxed);
+            return Err(Error::StorageError(format!(
+                "{INJECTED} failure in {operation}"
+            )));
+        }
+        Ok(fault.partial)
+    }
+
+    async fn read<T>(
+        &self,
+        operation: &str,
+        result: impl std::future::Future<Output = Result<T>>,
+    ) -> Result<T> {
+        self.inject(operation, false).await?;
+        result.await
+    }
+
+    /// Run a single write, reporting it as failed afterwards if drawn to
+    async fn write<T>(
+        &self,
+        operation: &str,
+        result: impl std::future::Future<Output = Result<T>>,
+    ) -> Result<T> {
+        let partial = self.inject(operation, true).await?;
+        let value = result.await?;
+        if partial {
+            self.partial_writes.fetch_add(1, Ordering::Relaxed);
+            return Err(Error::StorageError(format!(
+                "{INJECTED} failure in {operation} after it was applied"
+            )));
+        }
+        Ok(value)
+    }
+
+    /// How many of `len` records a batch write stores, if it is cut short
+    fn batch_cut(&self, len: usize) -> Option<usize> {
+        (len > 0).then(|| self.rng.lock().gen_range(0..len))
+    }
+
+    fn partial_batch_error(&self, operation: &str, stored: usize, len: usize) -> Error {
+        self.partial_writes.fetch_add(1, Ordering::Relaxed);
+        Error::StorageError(format!(
+            "{INJECTED} failure in {operation} after storing {stored} of {len} records"
+        ))
+    }
+}
+
+#[async_trait]
+impl AsyncStorageBackend for ChaosBackend {
+    async fn store_node(&self, node: &Node) -> Result<()> {
+        self.write("store_node", self.inner.store_node(node)).await
+    }
+
+    async fn get_node(&self, id: &NodeId) -> Result<Option<Node>> {
+        self.read("get_node", self.inner.get_node(id)).await
+    }
+
+    async fn delete_node(&self, id: &NodeId) -> Result<()> {
+        self.write("delete_node", self.inner.delete_node(id)).await
+    }
+
+    async fn store_edge(&self, edge: &Edge) -> Result<()> {
+        self.write("store_edge", self.inner.store_edge(edge)).await
+    }
+
+    async fn get_edge(&self, id: &EdgeId) -> Result<Option<Edge>> {
+        self.read("get_edge", self.inner.get_edge(id)).await
+    }
+
+    async fn delete_edge(&self, id: &EdgeId) -> Result<()> {
+        self.write("delete_edge", self.inner.delete_edge(id)).await
+    }
+
+    async fn get_session_nodes(&self, session_id: &SessionId) -> Result<Vec<Node>> {
+        self.read(
+            "get_session_nodes",
+            self.inner.get_session_nodes(session_id),
+        )
+        .await
+    }
+
+    async fn get_outgoing_edges(&self, node_id: &NodeId) -> Result<Vec<Edge>> {
+        self.read("get_outgoing_edges", self.inner.get_outgoing_edges(node_id))
+            .await
+    }
+
+    async fn get_incoming_edges(&self, node_id: &NodeId) -> Result<Vec<Edge>> {
+        self.read("get_incoming_edges", self.inner.get_incoming_edges(node_id))
+            .await
+    }
+
+    async fn flush(&self) -> Result<()> {
+        self.write("flush", self.inner.flush()).await
+    }
+
+    async fn stats(&self) -> Result<StorageStats> {
+        self.read("stats", self.inner.stats()).await
+    }
+
+    async fn store_nodes_batch(&self, nodes: &[Node]) -> Result<Vec<NodeId>> {
+        if !self.inject("store_nodes_batch", true).await? {
+            return self.inner.store_nodes_batch(nodes).await;
+        }
+        let stored = self.batch_cut(nodes.len()).unwrap_or(0);
+        self.inner.store_nodes_batch(&nodes[..stored]).await?;
+        Err(self.partial_batch_error("store_nodes_batch", stored, nodes.len()))
+    }
+
+    async fn store_edges_batch(&self, edges: &[Edge]) -> Result<Vec<EdgeId>> {
+        if !self.inject("store_edges_batch", true).await? {
+            return self.inner.store_edges_batch(edges).await;
+        }
+        let stored = self.batch_cut(edges.len()).unwrap_or(0);
+        self.inner.store_edges_batch(&edges[..stored]).await?;
+        Err(self.partial_batch_error("store_edges_batch", stored, edges.len()))
+    }
+
+    async fn count_session_nodes(&self, session_id: &SessionId) -> Result<usize> {
+        self.read(
+            "count_session_nodes",
+            self.inner.count_session_nodes(session_id),
+        )
+        .await
+    }
+
+    async fn append_audit_entry(&self, entry: AuditEntry) -> Result<AuditEntry> {
+        self.write("append_audit_entry", self.inner.append_audit_entry(entry))
+            .await
+    }
+
+    async fn audit_entries(&self, filter: &AuditFilter) -> Result<Vec<AuditEntry>> {
+        self.read("audit_entries", self.inner.audit_entries(filter))
+            .await
+    }
+
+    async fn trash_node(&self, id: &NodeId) -> Result<Option<TrashedNode>> {
+        self.write("trash_node", self.inner.trash_node(id)).await
+    }
+
+    async fn restore_node(&self, id: &NodeId) -> Result<Option<Node>> {
+        self.write("restore_node", self.inner.restore_node(id))
+            .await
+    }
+
+    async fn trashed_nodes(&self) -> Result<Vec<TrashedNode>> {
+        self.read("trashed_nodes", self.inner.trashed_nodes()).await
+    }
+
+    async fn purge_trash(
+        &self,
+        cutoff: DateTime<Utc>,
+        keep: &HashSet<SessionId>,
+    ) -> Result<Vec<NodeId>> {
+        self.write("purge_trash", self.inner.purge_trash(cutoff, keep))
+            .await
+    }
+
+    async fn list_sessions(&self) -> Result<Vec<ConversationSession>> {
+        self.read("list_sessions", self.inner.list_sessions()).await
+    }
+
+    async fn find_prompts_by_content(
+        &self,
+        content: &str,
+        session_id: Option<&SessionId>,
+    ) -> Result<Vec<PromptNode>> {
+        self.read(
+            "find_prompts_by_content",
+            self.inner.find_prompts_by_content(content, session_id),
+        )
+        .await
+    }
+
+    async fn move_session_nodes(
+        &self,
+        source: &SessionId,
+        target: &SessionId,
+    ) -> Result<Vec<NodeId>> {
+        self.write(
+            "move_session_nodes",
+            self.inner.move_session_nodes(source, target),
+        )
+        .await
+    }
+
+    async fn compact_indexes(&self) -> Result<usize> {
+        self.write("compact_indexes", self.inner.compact_indexes())
+            .await
+    }
+
+    async fn rebuild_indexes(&self) -> Result<IndexRebuildReport> {
+        self.write("rebuild_indexes", self.inner.rebuild_indexes())
+            .await
+    }
+
+    async fn tier_cold(&self, cutoff: DateTime<Utc>) -> Result<ColdTierReport> {
+        self.write("tier_cold", self.inner.tier_cold(cutoff)).await
+    }
+
+    async fn all_nodes(&self) -> Result<Vec<Node>> {
+        self.read("all_nodes", self.inner.all_nodes()).await
+    }
+
+    async fn all_edges(&self) -> Result<Vec<Edge>> {
+        self.read("all_edges", self.inner.all_edges()).await
+    }
+
+    async fn changes_since(&self, cursor: Option<u64>, limit: usize) -> Result<Vec<ChangeRecord>> {
+        self.read("changes_since", self.inner.changes_since(cursor, limit))
+            .await
+    }
+
+    async fn latest_change_cursor(&self) -> Result<Option<u64>> {
+        self.read("latest_change_cursor", self.inner.latest_change_cursor())
+            .await
+    }
+
+    fn subscribe_changes(&self) -> Result<broadcast::Receiver<ChangeRecord>> {
+        self.inner.subscribe_changes()
+    }
+
+    async fn prune_changes(&self, cursor: u64) -> Result<usize> {
+        self.write("prune_changes", self.inner.prune_changes(cursor))
+            .await
+    }
+
+    async fn idempotency_record(&self, key: &str) -> Result<Option<IdempotencyRecord>> {
+        self.read("idempotency_record", self.inner.idempotency_record(key))
+            .await
+    }
+
+    async fn store_idempotency_record(&self, key: &str, record: &IdempotencyRecord) -> Result<()> {
+        self.write(
+            "store_idempotency_record",
+            self.inner.store_idempotency_record(key, record),
+        )
+        .await
+    }
+
+    async fn store_checkpoint(&self, checkpoint: &SessionCheckpoint) -> Result<()> {
+        self.write("store_checkpoint", self.inner.store_checkpoint(checkpoint))
+            .await
+    }
+
+    async fn session_checkpoints(&self, session_id: &SessionId) -> Result<Vec<SessionCheckpoint>> {
+        self.read(
+            "session_checkpoints",
+            self.inner.session_checkpoints(session_id),
+        )
+        .await
+    }
+
+    async fn store_node_version(&self, version: &NodeVersion) -> Result<()> {
+        self.write("store_node_version", self.inner.store_node_version(version))
+            .await
+    }
+
+    async fn node_versions(&self, node_id: &NodeId) -> Result<Vec<NodeVersion>> {
+        self.read("node_versions", self.inner.node_versions(node_id))
+            .await
+    }
+
+    async fn node_version_ids(&self) -> Result<Vec<NodeId>> {
+        self.read("node_version_ids", self.inner.node_version_ids())
+            .await
+    }
+
+    async fn usage_rollups(
+        &self,
+        granularity: UsageGranularity,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<UsageBucket>> {
+        self.read(
+            "usage_rollups",
+            self.inner.usage_rollups(granularity, from, to),
+        )
+        .await
+    }
+
+    async fn kv_get(&self, key: &str) -> Result<Option<KvEntry>> {
+        self.read("kv_get", self.inner.kv_get(key)).await
+    }
+
+    async fn kv_set(&self, entry: &KvEntry) -> Result<()> {
+        self.write("kv_set", self.inner.kv_set(entry)).await
+    }
+
+    async fn kv_delete(&self, key: &str) -> Result<bool> {
+        self.write("kv_delete", self.inner.kv_delete(key)).await
+    }
+
+    async fn kv_list(&self, prefix: &str) -> Result<Vec<KvEntry>> {
+        self.read("kv_list", self.inner.kv_list(prefix)).await
+    }
+
+    async fn store_embedding(&self, embedding: &NodeEmbedding) -> Result<()> {
+        self.write("store_embedding", self.inner.store_embedding(embedding))
+            .await
+    }
+
+    async fn get_embedding(&self, node_id: &NodeId) -> Result<Option<NodeEmbedding>> {
+        self.read("get_embedding", self.inner.get_embedding(node_id))
+            .await
+    }
+
+    async fn all_embeddings(&self) -> Result<Vec<NodeEmbedding>> {
+        self.read("all_embeddings", self.inner.all_embeddings())
+            .await
+    }
+
+    async fn set_alias(&self, alias: &str, target: AliasTarget) -> Result<Option<AliasTarget>> {
+        self.write("set_alias", self.inner.set_alias(alias, target))
+            .await
+    }
+
+    async fn resolve_alias(&self, alias: &str) -> Result<Option<AliasTarget>> {
+        self.read("resolve_alias", self.inner.resolve_alias(alias))
+            .await
+    }
+
+    async fn remove_alias(&self, alias: &str) -> Result<Option<AliasTarget>> {
+        self.write("remove_alias", self.inner.remove_alias(alias))
+            .await
+    }
+
+    async fn list_aliases(&self, prefix: &str) -> Result<Vec<(String, AliasTarget)>> {
+        self.read("list_aliases", self.inner.list_aliases(prefix))
+            .await
+    }
+
+    async fn list_views(&self) -> Result<Vec<ViewDefinition>> {
+        self.read("list_views", self.inner.list_views()).await
+    }
+
+    async fn define_view(&self, view: &ViewDefinition) -> Result<bool> {
+        self.write("define_view", self.inner.define_view(view))
+            .await
+    }
+
+    async fn drop_view(&self, name: &str) -> Result<bool> {
+        self.write("drop_view", self.inner.drop_view(name)).await
+    }
+
+    async fn view_totals(&self, name: &str, group: &[String]) -> Result<Option<ViewTotals>> {
+        self.read("view_totals", self.inner.view_totals(name, group))
+            .await
+    }
+
+    async fn view_rows(&self, name: &str) -> Result<Vec<ViewRow>> {
+        self.read("view_rows", self.inner.view_rows(name)).await
+    }
+
+    async fn rebuild_views(&self) -> Result<usize> {
+        self.write("rebuild_views", self.inner.rebuild_views())
+            .await
+    }
+
+    async fn snapshot(&self) -> Result<SnapshotBackend> {
+        self.read("snapshot", self.inner.snapshot()).await
+    }
+
+    async fn get_outgoing_edges_page(
+        &self,
+        node_id: &NodeId,
+        cursor: Option<&EdgeId>,
+        limit: usize,
+    ) -> Result<EdgePage> {
+        self.read(
+            "get_outgoing_edges_page",
+            self.inner.get_outgoing_edges_page(node_id, cursor, limit),
+        )
+        .await
+    }
+
+    async fn get_incoming_edges_page(
+        &self,
+        node_id: &NodeId,
+        cursor: Option<&EdgeId>,
+        limit: usize,
+    ) -> Result<EdgePage> {
+        self.read(
+            "get_incoming_edges_page",
+            self.inner.get_incoming_edges_page(node_id, cursor, limit),
+        )
+        .await
+    }
+
+    async fn node_degree(&self, node_id: &NodeId) -> Result<NodeDegree> {
+        self.read("node_degree", self.inner.node_degree(node_id))
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::AsyncMemoryGraph;
+    use crate::storage::AsyncSledBackend;
+    use crate::{Config, ConversationSession, PromptNode};
+    use std::time::Instant;
+    use tempfile::tempdir;
+
+    async fn chaos(dir: &std::path::Path, config: ChaosConfig) -> Arc<ChaosBackend> {
+        let sled = AsyncSledBackend::open(dir.join("chaos.db")).await.unwrap();
+        Arc::new(ChaosBackend::new(Arc::new(sled), config))
+    }
+
+    #[tokio::test]
+    async fn test_seeded_faults_are_reproducible() {
+        let dir = tempdir().unwrap();
+        let mut outcomes = Vec::new();
+        for run in 0..2 {
+            let backend = chaos(
+                &dir.path().join(run.to_string()),
+                ChaosConfig::new(7).with_error_rate(0.3),
+            )
+            .await;
+            let session = ConversationSession::new();
+            let mut run_outcomes = Vec::new();
+            for i in 0..40 {
+                let prompt = PromptNode::new(session.id, format!("Prompt {i}"));
+                run_outcomes.push(backend.store_node(&Node::Prompt(prompt)).await.is_ok());
+            }
+            assert_eq!(
+                backend.stats().injected_errors,
+                run_outcomes.iter().filter(|ok| !**ok).count() as u64
+            );
+            outcomes.push(run_outcomes);
+        }
+        assert_eq!(outcomes[0], outcomes[1]);
+        assert!(outcomes[0].contains(&false) && outcomes[0].contains(&true));
+    }
+
+    #[tokio::test]
+    async fn test_partial_batch_write() {
+        let dir = tempdir().unwrap();
+        let backend = chaos(dir.path(), ChaosConfig::new(1).with_partial_write_rate(1.0)).await;
+        let session = ConversationSession::new();
+        let nodes: Vec<Node> = (0..10)
+            .map(|i| Node::Prompt(PromptNode::new(session.id, format!("Prompt {i}"))))
+            .collect();
+
+        let error = backend.store_nodes_batch(&nodes).await.unwrap_err();
+        assert!(ChaosBackend::is_injected(&error));
+        let stored = backend.inner().all_nodes().await.unwrap();
+        assert!(stored.len() < nodes.len());
+        for node in &stored {
+            assert!(nodes[..stored.len()].iter().any(|n| n.id() == node.id()));
+        }
+        assert_eq!(backend.stats().partial_writes, 1);
+
+        // A single write is applied before it is reported as failed
+        let node = Node::Prompt(PromptNode::new(session.id, "Applied".to_string()));
+        assert!(backend.store_node(&node).await.is_err());
+        assert!(backend
+            .inner()
+            .get_node(&node.id())
+            .await
+            .unwrap()
+            .is_some());
+    }
+
+    #[tokio::test]
+    async fn test_latency_and_writes_only() {
+        let dir = tempdir().unwrap();
+        let backend = chaos(
+            dir.path(),
+            ChaosConfig::new(3)
+                .with_latency(Duration::from_millis(20))
+                .with_error_rate(1.0)
+                .writes_only(),
+        )
+        .await;
+
+        let started = Instant::now();
+        assert!(backend.get_node(&NodeId::new()).await.unwrap().is_none());
+        assert!(started.elapsed() >= Duration::from_millis(20));
+        let node = Node::Session(ConversationSession::new());
+        assert!(backend.store_node(&node).await.is_err());
+
+        backend.set_enabled(false);
+        backend.store_node(&node).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_engine_retries_after_injected_failure() {
+        let dir = tempdir().unwrap();
+        let backend = chaos(dir.path(), ChaosConfig::new(5)).await;
+        let graph = AsyncMemoryGraph::open_with_backend(
+            Config::new(dir.path()),
+            Arc::clone(&backend) as Arc<dyn AsyncStorageBackend>,
+        );
+        let session = graph.create_session().await.unwrap();
+
+        backend.fail_next(1);
+        let error = graph
+            .add_prompt_idempotent("request-1", session.id, "Hi".to_string(), None)
+            .await
+            .unwrap_err();
+        assert!(ChaosBackend::is_injected(&error));
+
+        let first = graph
+            .add_prompt_idempotent("request-1", session.id, "Hi".to_string(), None)
+            .await
+            .unwrap();
+        let again = graph
+            .add_prompt_idempotent("request-1", session.id, "Hi".to_string(), None)
+            .await
+            .unwrap();
+        assert_eq!(first, again);
+        assert_eq!(graph.get_session_nodes(&session.id).await.unwrap().len(), 2);
+    }
+}
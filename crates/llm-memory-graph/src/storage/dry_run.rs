@@ -0,0 +1,828 @@
+//! In-memory overlay that records writes instead of making them
+//!
+//! A [`DryRunBackend`] sits over another backend. Writes go to an in-memory
+//! overlay and are appended to a [`Changeset`]; reads see the wrapped backend
+//! with the overlay applied, so code running against it behaves as if its
+//! writes had been made. Nothing reaches the wrapped backend.
+//!
+//! Session and edge lookups merge the overlay into the wrapped backend's
+//! results. Materialized views, usage rollups and the change log are read
+//! from the wrapped backend as they are, without the overlay's writes, and
+//! maintenance operations (purging the trash, moving nodes between sessions,
+//! index and view rebuilds, defining views, snapshots) are not supported.
+//! [`DryRunGraph`](crate::engine::DryRunGraph) runs the engine over one.
+
+use super::{
+    unsupported, AsyncStorageBackend, ColdTierReport, IdempotencyRecord, IndexRebuildReport,
+    KvEntry, NodeEmbedding, NodeVersion, SessionCheckpoint, SnapshotBackend, StorageStats,
+    TrashedNode,
+};
+use crate::audit::{AuditEntry, AuditFilter};
+use crate::changes::ChangeRecord;
+use crate::deadline::Deadline;
+use crate::usage::{UsageBucket, UsageGranularity};
+use crate::{
+    AliasTarget, ConversationSession, Edge, EdgeId, Node, NodeId, PromptNode, ReadConsistency,
+    Result, SessionId, ViewDefinition, ViewRow, ViewTotals,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use tokio::sync::broadcast;
+
+/// One write recorded by a [`DryRunBackend`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum Mutation {
+    /// A node was written, new if `created`
+    StoreNode {
+        /// The node as written
+        node: Node,
+        /// Whether the node did not exist before
+        created: bool,
+    },
+    /// A node was deleted
+    DeleteNode {
+        /// The deleted node
+        id: NodeId,
+    },
+    /// An edge was written, new if `created`
+    StoreEdge {
+        /// The edge as written
+        edge: Edge,
+        /// Whether the edge did not exist before
+        created: bool,
+    },
+    /// An edge was deleted
+    DeleteEdge {
+        /// The deleted edge
+        id: EdgeId,
+    },
+    /// A node was moved to the trash
+    TrashNode {
+        /// The trashed node
+        id: NodeId,
+    },
+    /// A node was restored from the trash
+    RestoreNode {
+        /// The restored node
+        id: NodeId,
+    },
+    /// An alias was pointed at a target
+    SetAlias {
+        /// The alias
+        alias: String,
+        /// What it now points at
+        target: AliasTarget,
+    },
+    /// An alias was removed
+    RemoveAlias {
+        /// The alias
+        alias: String,
+    },
+    /// A key-value entry was set
+    KvSet {
+        /// The entry as written
+        entry: KvEntry,
+    },
+    /// A key-value entry was deleted
+    KvDelete {
+        /// Key of the entry
+        key: String,
+    },
+    /// An embedding was stored for a node
+    StoreEmbedding {
+        /// The embedding
+        embedding: NodeEmbedding,
+    },
+    /// A session checkpoint was taken
+    StoreCheckpoint {
+        /// The checkpoint
+        checkpoint: SessionCheckpoint,
+    },
+    /// An earlier version of an edited node was kept
+    StoreNodeVersion {
+        /// The version
+        version: NodeVersion,
+    },
+}
+
+/// Writes recorded by a [`DryRunBackend`], in the order they were made
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Changeset {
+    /// Storage writes
+    pub mutations: Vec<Mutation>,
+    /// Audit entries of the operations that made them
+    pub operations: Vec<AuditEntry>,
+}
+
+impl Changeset {
+    /// Whether nothing was written
+    pub fn is_empty(&self) -> bool {
+        self.mutations.is_empty()
+    }
+
+    /// Number of writes
+    pub fn len(&self) -> usize {
+        self.mutations.len()
+    }
+
+    /// Nodes that would be created, as last written
+    pub fn created_nodes(&self) -> Vec<&Node> {
+        self.latest_nodes(true)
+    }
+
+    /// Existing nodes that would be changed, as last written
+    pub fn updated_nodes(&self) -> Vec<&Node> {
+        self.latest_nodes(false)
+    }
+
+    /// Edges that would be created
+    pub fn created_edges(&self) -> Vec<&Edge> {
+        self.mutations
+            .iter()
+            .filter_map(|mutation| match mutation {
+                Mutation::StoreEdge {
+                    edge,
+                    created: true,
+                } => Some(edge),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Nodes that would be deleted or trashed
+    pub fn deleted_nodes(&self) -> Vec<NodeId> {
+        self.mutations
+            .iter()
+            .filter_map(|mutation| match mutation {
+                Mutation::DeleteNode { id } | Mutation::TrashNode { id } => Some(*id),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// The last written state of each node whose first write did or did not
+    /// create it, in order of first write
+    fn latest_nodes(&self, created: bool) -> Vec<&Node> {
+        let mut first: HashMap<NodeId, bool> = HashMap::new();
+        let mut order = Vec::new();
+        let mut latest: HashMap<NodeId, &Node> = HashMap::new();
+        for mutation in &self.mutations {
+            if let Mutation::StoreNode { node, created } = mutation {
+                let id = node.id();
+                first.entry(id).or_insert_with(|| {
+                    order.push(id);
+                    *created
+                });
+                latest.insert(id, node);
+            }
+        }
+        order
+            .into_iter()
+            .filter(|id| first[id] == created)
+            .filter_map(|id| latest.get(&id).copied())
+            .collect()
+    }
+}
+
+/// What the overlay holds; `None` entries shadow deleted records
+#[derive(Default)]
+struct Overlay {
+    nodes: HashMap<NodeId, Option<Node>>,
+    edges: HashMap<EdgeId, Option<Edge>>,
+    trash: HashMap<NodeId, Option<TrashedNode>>,
+    aliases: HashMap<String, Option<AliasTarget>>,
+    kv: HashMap<String, Option<KvEntry>>,
+    embeddings: HashMap<NodeId, NodeEmbedding>,
+    idempotency: HashMap<String, IdempotencyRecord>,
+    checkpoints: Vec<SessionCheckpoint>,
+    versions: Vec<NodeVersion>,
+    changeset: Changeset,
+}
+
+/// Backend that records writes over `base` without making them, see the
+/// [module docs](self)
+pub struct DryRunBackend {
+    base: Arc<dyn AsyncStorageBackend>,
+    overlay: Mutex<Overlay>,
+}
+
+impl DryRunBackend {
+    /// Record writes over `base`
+    pub fn new(base: Arc<dyn AsyncStorageBackend>) -> Self {
+        Self {
+            base,
+            overlay: Mutex::new(Overlay::default()),
+        }
+    }
+
+    /// The wrapped backend, which the dry run never writes to
+    pub fn base(&self) -> &Arc<dyn AsyncStorageBackend> {
+        &self.base
+    }
+
+    /// Writes recorded so far
+    pub fn changeset(&self) -> Changeset {
+        self.overlay.lock().changeset.clone()
+    }
+
+    /// Writes recorded so far, clearing the record but keeping their effect
+    /// on reads
+    pub fn take_changeset(&self) -> Changeset {
+        std::mem::take(&mut self.overlay.lock().changeset)
+    }
+
+    /// Forget every write, recorded and applied
+    pub fn reset(&self) {
+        *self.overlay.lock() = Overlay::default();
+    }
+
+    fn record(&self, mutation: Mutation) {
+        self.overlay.lock().changeset.mutations.push(mutation);
+    }
+
+    /// Apply the overlay to nodes read from the base, adding the overlay's
+    /// own nodes that `keep` accepts
+    fn merge_nodes(&self, base: Vec<Node>, keep: impl Fn(&Node) -> bool) -> Vec<Node> {
+        let overlay = self.overlay.lock();
+        let seen: HashSet<NodeId> = base.iter().map(Node::id).collect();
+        let mut nodes: Vec<Node> = base
+            .into_iter()
+            .filter_map(|node| match overlay.nodes.get(&node.id()) {
+                Some(written) => written.clone(),
+                None => Some(node),
+            })
+            .filter(&keep)
+            .collect();
+        nodes.extend(
+            overlay
+                .nodes
+                .values()
+                .flatten()
+                .filter(|node| !seen.contains(&node.id()) && keep(node))
+                .cloned(),
+        );
+        nodes
+    }
+
+    /// Apply the overlay to edges read from the base, adding the overlay's
+    /// own edges that `keep` accepts
+    fn merge_edges(&self, base: Vec<Edge>, keep: impl Fn(&Edge) -> bool) -> Vec<Edge> {
+        let overlay = self.overlay.lock();
+        let seen: HashSet<EdgeId> = base.iter().map(|edge| edge.id).collect();
+        let mut edges: Vec<Edge> = base
+            .into_iter()
+            .filter_map(|edge| match overlay.edges.get(&edge.id) {
+                Some(written) => written.clone(),
+                None => Some(edge),
+            })
+            .filter(&keep)
+            .collect();
+        edges.extend(
+            overlay
+                .edges
+                .values()
+                .flatten()
+                .filter(|edge| !seen.contains(&edge.id) && keep(edge))
+                .cloned(),
+        );
+        edges
+    }
+
+    /// Session a node belongs to, through its prompt for a response
+    async fn session_of(&self, node: &Node) -> Result<Option<SessionId>> {
+        Ok(match node {
+            Node::Session(s) => Some(s.id),
+            Node::Prompt(p) => Some(p.session_id),
+            Node::Response(r) => match self.get_node(&r.prompt_id).await? {
+                Some(Node::Prompt(p)) => Some(p.session_id),
+                _ => None,
+            },
+            Node::Custom(c) => c.session_id,
+            Node::ToolInvocation(_) | Node::Agent(_) | Node::Template(_) => None,
+        })
+    }
+
+    /// Apply the overlay to the nodes of a session read from the base
+    ///
+    /// Responses belong to the session of their prompt, so a response
+    /// written to the overlay is kept if its prompt is in the session.
+    fn merge_session_nodes(&self, session_id: &SessionId, base: Vec<Node>) -> Vec<Node> {
+        let mut prompts: HashSet<NodeId> = base
+            .iter()
+            .filter(|node| matches!(node, Node::Prompt(_)))
+            .map(Node::id)
+            .collect();
+        prompts.extend(self.overlay.lock().nodes.values().flatten().filter_map(
+            |node| match node {
+                Node::Prompt(p) if p.session_id == *session_id => Some(p.id),
+                _ => None,
+            },
+        ));
+        self.merge_nodes(base, |node| match node {
+            Node::Session(s) => s.id == *session_id,
+            Node::Prompt(p) => p.session_id == *session_id,
+            Node::Response(r) => prompts.contains(&r.prompt_id),
+            Node::Custom(c) => c.session_id == Some(*session_id),
+            Node::ToolInvocation(_) | Node::Agent(_) | Node::Template(_) => false,
+        })
+    }
+}
+
+#[async_trait]
+impl AsyncStorageBackend for DryRunBackend {
+    async fn store_node(&self, node: &Node) -> Result<()> {
+        let created = self.get_node(&node.id()).await?.is_none();
+        let mut overlay = self.overlay.lock();
+        overlay.nodes.insert(node.id(), Some(node.clone()));
+        overlay.changeset.mutations.push(Mutation::StoreNode {
+            node: node.clone(),
+            created,
+        });
+        Ok(())
+    }
+
+    async fn get_node(&self, id: &NodeId) -> Result<Option<Node>> {
+        if let Some(written) = self.overlay.lock().nodes.get(id) {
+            return Ok(written.clone());
+        }
+        self.base.get_node(id).await
+    }
+
+    async fn delete_node(&self, id: &NodeId) -> Result<()> {
+        self.overlay.lock().nodes.insert(*id, None);
+        self.record(Mutation::DeleteNode { id: *id });
+        Ok(())
+    }
+
+    async fn store_edge(&self, edge: &Edge) -> Result<()> {
+        let created = self.get_edge(&edge.id).await?.is_none();
+        let mut overlay = self.overlay.lock();
+        overlay.edges.insert(edge.id, Some(edge.clone()));
+        overlay.changeset.mutations.push(Mutation::StoreEdge {
+            edge: edge.clone(),
+            created,
+        });
+        Ok(())
+    }
+
+    async fn get_edge(&self, id: &EdgeId) -> Result<Option<Edge>> {
+        if let Some(written) = self.overlay.lock().edges.get(id) {
+            return Ok(written.clone());
+        }
+        self.base.get_edge(id).await
+    }
+
+    async fn delete_edge(&self, id: &EdgeId) -> Result<()> {
+        self.overlay.lock().edges.insert(*id, None);
+        self.record(Mutation::DeleteEdge { id: *id });
+        Ok(())
+    }
+
+    async fn get_session_nodes(&self, session_id: &SessionId) -> Result<Vec<Node>> {
+        let base = self.base.get_session_nodes(session_id).await?;
+        Ok(self.merge_session_nodes(session_id, base))
+    }
+
+    async fn get_session_nodes_until(
+        &self,
+        session_id: &SessionId,
+        deadline: &Deadline,
+    ) -> Result<Vec<Node>> {
+        let base = self
+            .base
+            .get_session_nodes_until(session_id, deadline)
+            .await?;
+        Ok(self.merge_session_nodes(session_id, base))
+    }
+
+    async fn get_outgoing_edges(&self, node_id: &NodeId) -> Result<Vec<Edge>> {
+        let base = self.base.get_outgoing_edges(node_id).await?;
+        Ok(self.merge_edges(base, |edge| edge.from == *node_id))
+    }
+
+    async fn get_incoming_edges(&self, node_id: &NodeId) -> Result<Vec<Edge>> {
+        let base = self.base.get_incoming_edges(node_id).await?;
+        Ok(self.merge_edges(base, |edge| edge.to == *node_id))
+    }
+
+    async fn flush(&self) -> Result<()> {
+        Ok(())
+    }
+
+    async fn prepare_read(&self, consistency: ReadConsistency) -> Result<()> {
+        self.base.prepare_read(consistency).await
+    }
+
+    async fn stats(&self) -> Result<StorageStats> {
+        self.base.stats().await
+    }
+
+    async fn append_audit_entry(&self, entry: AuditEntry) -> Result<AuditEntry> {
+        self.overlay.lock().changeset.operations.push(entry.clone());
+        Ok(entry)
+    }
+
+    async fn audit_entries(&self, filter: &AuditFilter) -> Result<Vec<AuditEntry>> {
+        let mut entries = self.base.audit_entries(filter).await?;
+        let overlay = self.overlay.lock();
+        entries.extend(
+            overlay
+                .changeset
+                .operations
+                .iter()
+                .filter(|entry| filter.matches(entry))
+                .cloned(),
+        );
+        Ok(entries)
+    }
+
+    async fn trash_node(&self, id: &NodeId) -> Result<Option<TrashedNode>> {
+        let Some(node) = self.get_node(id).await? else {
+            return Ok(None);
+        };
+        let trashed = TrashedNode {
+            session_id: self.session_of(&node).await?,
+            node,
+            deleted_at: Utc::now(),
+        };
+        let mut overlay = self.overlay.lock();
+        overlay.nodes.insert(*id, None);
+        overlay.trash.insert(*id, Some(trashed.clone()));
+        overlay
+            .changeset
+            .mutations
+            .push(Mutation::TrashNode { id: *id });
+        Ok(Some(trashed))
+    }
+
+    async fn restore_node(&self, id: &NodeId) -> Result<Option<Node>> {
+        let trashed = match self.overlay.lock().trash.get(id) {
+            Some(trashed) => trashed.clone(),
+            None => None,
+        };
+        let trashed = match trashed {
+            Some(trashed) => Some(trashed),
+            None if self.overlay.lock().trash.contains_key(id) => None,
+            None => self
+                .base
+                .trashed_nodes()
+                .await?
+                .into_iter()
+                .find(|trashed| trashed.node.id() == *id),
+        };
+        let Some(trashed) = trashed else {
+            return Ok(None);
+        };
+        let mut overlay = self.overlay.lock();
+        overlay.trash.insert(*id, None);
+        overlay.nodes.insert(*id, Some(trashed.node.clone()));
+        overlay
+            .changeset
+            .mutations
+            .push(Mutation::RestoreNode { id: *id });
+        Ok(Some(trashed.node))
+    }
+
+    async fn trashed_nodes(&self) -> Result<Vec<TrashedNode>> {
+        let base = self.base.trashed_nodes().await?;
+        let overlay = self.overlay.lock();
+        let mut trashed: Vec<TrashedNode> = base
+            .into_iter()
+            .filter(|trashed| !overlay.trash.contains_key(&trashed.node.id()))
+            .collect();
+        trashed.extend(overlay.trash.values().flatten().cloned());
+        Ok(trashed)
+    }
+
+    async fn purge_trash(
+        &self,
+        _cutoff: DateTime<Utc>,
+        _keep: &HashSet<SessionId>,
+    ) -> Result<Vec<NodeId>> {
+        Err(unsupported("purging the trash in a dry run"))
+    }
+
+    async fn list_sessions(&self) -> Result<Vec<ConversationSession>> {
+        let base = self
+            .base
+            .list_sessions()
+            .await?
+            .into_iter()
+            .map(Node::Session)
+            .collect();
+        let nodes = self.merge_nodes(base, |node| matches!(node, Node::Session(_)));
+        Ok(nodes
+            .into_iter()
+            .filter_map(|node| match node {
+                Node::Session(session) => Some(session),
+                _ => None,
+            })
+            .collect())
+    }
+
+    async fn find_prompts_by_content(
+        &self,
+        content: &str,
+        session_id: Option<&SessionId>,
+    ) -> Result<Vec<PromptNode>> {
+        let base = self
+            .base
+            .find_prompts_by_content(content, session_id)
+            .await?
+            .into_iter()
+            .map(Node::Prompt)
+            .collect();
+        let nodes = self.merge_nodes(base, |node| {
+            matches!(node, Node::Prompt(p)
+                if p.content == content && session_id.is_none_or(|s| p.session_id == *s))
+        });
+        let mut prompts: Vec<PromptNode> = nodes
+            .into_iter()
+            .filter_map(|node| match node {
+                Node::Prompt(prompt) => Some(prompt),
+                _ => None,
+            })
+            .collect();
+        prompts.sort_by_key(|prompt| prompt.timestamp);
+        Ok(prompts)
+    }
+
+    async fn move_session_nodes(
+        &self,
+        _source: &SessionId,
+        _target: &SessionId,
+    ) -> Result<Vec<NodeId>> {
+        Err(unsupported("moving nodes between sessions in a dry run"))
+    }
+
+    async fn compact_indexes(&self) -> Result<usize> {
+        Err(unsupported("index compaction in a dry run"))
+    }
+
+    async fn rebuild_indexes(&self) -> Result<IndexRebuildReport> {
+        Err(unsupported("index rebuilds in a dry run"))
+    }
+
+    async fn tier_cold(&self, _cutoff: DateTime<Utc>) -> Result<ColdTierReport> {
+        Err(unsupported("cold storage tiering in a dry run"))
+    }
+
+    async fn usage_rollups(
+        &self,
+        granularity: UsageGranularity,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<UsageBucket>> {
+        self.base.usage_rollups(granularity, from, to).await
+    }
+
+    async fn all_nodes(&self) -> Result<Vec<Node>> {
+        let base = self.base.all_nodes().await?;
+        Ok(self.merge_nodes(base, |_| true))
+    }
+
+    async fn all_edges(&self) -> Result<Vec<Edge>> {
+        let base = self.base.all_edges().await?;
+        Ok(self.merge_edges(base, |_| true))
+    }
+
+    async fn changes_since(&self, cursor: Option<u64>, limit: usize) -> Result<Vec<ChangeRecord>> {
+        self.base.changes_since(cursor, limit).await
+    }
+
+    async fn latest_change_cursor(&self) -> Result<Option<u64>> {
+        self.base.latest_change_cursor().await
+    }
+
+    async fn prune_changes(&self, _cursor: u64) -> Result<usize> {
+        Err(unsupported("pruning the change log in a dry run"))
+    }
+
+    fn subscribe_changes(&self) -> Result<broadcast::Receiver<ChangeRecord>> {
+        self.base.subscribe_changes()
+    }
+
+    async fn idempotency_record(&self, key: &str) -> Result<Option<IdempotencyRecord>> {
+        if let Some(record) = self.overlay.lock().idempotency.get(key) {
+            return Ok(Some(*record));
+        }
+        self.base.idempotency_record(key).await
+    }
+
+    async fn store_idempotency_record(&self, key: &str, record: &IdempotencyRecord) -> Result<()> {
+        self.overlay
+            .lock()
+            .idempotency
+            .insert(key.to_string(), *record);
+        Ok(())
+    }
+
+    async fn store_checkpoint(&self, checkpoint: &SessionCheckpoint) -> Result<()> {
+        let mut overlay = self.overlay.lock();
+        overlay.checkpoints.push(checkpoint.clone());
+        overlay.changeset.mutations.push(Mutation::StoreCheckpoint {
+            checkpoint: checkpoint.clone(),
+        });
+        Ok(())
+    }
+
+    async fn session_checkpoints(&self, session_id: &SessionId) -> Result<Vec<SessionCheckpoint>> {
+        let mut checkpoints = self.base.session_checkpoints(session_id).await?;
+        checkpoints.extend(
+            self.overlay
+                .lock()
+                .checkpoints
+                .iter()
+                .filter(|checkpoint| checkpoint.session_id == *session_id)
+                .cloned(),
+        );
+        Ok(checkpoints)
+    }
+
+    async fn store_node_version(&self, version: &NodeVersion) -> Result<()> {
+        let mut overlay = self.overlay.lock();
+        overlay.versions.push(version.clone());
+        overlay
+            .changeset
+            .mutations
+            .push(Mutation::StoreNodeVersion {
+                version: version.clone(),
+            });
+        Ok(())
+    }
+
+    async fn node_versions(&self, node_id: &NodeId) -> Result<Vec<NodeVersion>> {
+        let mut versions = self.base.node_versions(node_id).await?;
+        versions.extend(
+            self.overlay
+                .lock()
+                .versions
+                .iter()
+                .filter(|version| version.node_id == *node_id)
+                .cloned(),
+        );
+        Ok(versions)
+    }
+
+    async fn node_version_ids(&self) -> Result<Vec<NodeId>> {
+        let mut ids = self.base.node_version_ids().await?;
+        for version in &self.overlay.lock().versions {
+            if !ids.contains(&version.node_id) {
+                ids.push(version.node_id);
+            }
+        }
+        Ok(ids)
+    }
+
+    async fn kv_get(&self, key: &str) -> Result<Option<KvEntry>> {
+        if let Some(entry) = self.overlay.lock().kv.get(key) {
+            return Ok(entry.clone());
+        }
+        self.base.kv_get(key).await
+    }
+
+    async fn kv_set(&self, entry: &KvEntry) -> Result<()> {
+        let mut overlay = self.overlay.lock();
+        overlay.kv.insert(entry.key.clone(), Some(entry.clone()));
+        overlay.changeset.mutations.push(Mutation::KvSet {
+            entry: entry.clone(),
+        });
+        Ok(())
+    }
+
+    async fn kv_delete(&self, key: &str) -> Result<bool> {
+        let existed = self.kv_get(key).await?.is_some();
+        let mut overlay = self.overlay.lock();
+        overlay.kv.insert(key.to_string(), None);
+        overlay.changeset.mutations.push(Mutation::KvDelete {
+            key: key.to_string(),
+        });
+        Ok(existed)
+    }
+
+    async fn kv_list(&self, prefix: &str) -> Result<Vec<KvEntry>> {
+        let base = self.base.kv_list(prefix).await?;
+        let overlay = self.overlay.lock();
+        let mut entries: Vec<KvEntry> = base
+            .into_iter()
+            .filter(|entry| !overlay.kv.contains_key(&entry.key))
+            .collect();
+        entries.extend(
+            overlay
+                .kv
+                .values()
+                .flatten()
+                .filter(|entry| entry.key.starts_with(prefix))
+                .cloned(),
+        );
+        entries.sort_by(|a, b| a.key.cmp(&b.key));
+        Ok(entries)
+    }
+
+    async fn store_embedding(&self, embedding: &NodeEmbedding) -> Result<()> {
+        let mut overlay = self.overlay.lock();
+        overlay
+            .embeddings
+            .insert(embedding.node_id, embedding.clone());
+        overlay.changeset.mutations.push(Mutation::StoreEmbedding {
+            embedding: embedding.clone(),
+        });
+        Ok(())
+    }
+
+    async fn get_embedding(&self, node_id: &NodeId) -> Result<Option<NodeEmbedding>> {
+        if let Some(embedding) = self.overlay.lock().embeddings.get(node_id) {
+            return Ok(Some(embedding.clone()));
+        }
+        self.base.get_embedding(node_id).await
+    }
+
+    async fn all_embeddings(&self) -> Result<Vec<NodeEmbedding>> {
+        let base = self.base.all_embeddings().await?;
+        let overlay = self.overlay.lock();
+        let mut embeddings: Vec<NodeEmbedding> = base
+            .into_iter()
+            .filter(|embedding| !overlay.embeddings.contains_key(&embedding.node_id))
+            .collect();
+        embeddings.extend(overlay.embeddings.values().cloned());
+        Ok(embeddings)
+    }
+
+    async fn set_alias(&self, alias: &str, target: AliasTarget) -> Result<Option<AliasTarget>> {
+        let previous = self.resolve_alias(alias).await?;
+        let mut overlay = self.overlay.lock();
+        overlay.aliases.insert(alias.to_string(), Some(target));
+        overlay.changeset.mutations.push(Mutation::SetAlias {
+            alias: alias.to_string(),
+            target,
+        });
+        Ok(previous)
+    }
+
+    async fn resolve_alias(&self, alias: &str) -> Result<Option<AliasTarget>> {
+        if let Some(target) = self.overlay.lock().aliases.get(alias) {
+            return Ok(*target);
+        }
+        self.base.resolve_alias(alias).await
+    }
+
+    async fn remove_alias(&self, alias: &str) -> Result<Option<AliasTarget>> {
+        let previous = self.resolve_alias(alias).await?;
+        if previous.is_some() {
+            let mut overlay = self.overlay.lock();
+            overlay.aliases.insert(alias.to_string(), None);
+            overlay.changeset.mutations.push(Mutation::RemoveAlias {
+                alias: alias.to_string(),
+            });
+        }
+        Ok(previous)
+    }
+
+    async fn list_aliases(&self, prefix: &str) -> Result<Vec<(String, AliasTarget)>> {
+        let base = self.base.list_aliases(prefix).await?;
+        let overlay = self.overlay.lock();
+        let mut aliases: Vec<(String, AliasTarget)> = base
+            .into_iter()
+            .filter(|(alias, _)| !overlay.aliases.contains_key(alias))
+            .collect();
+        aliases.extend(overlay.aliases.iter().filter_map(|(alias, target)| {
+            let target = (*target)?;
+            alias.starts_with(prefix).then(|| (alias.clone(), target))
+        }));
+        aliases.sort_by(|a, b| a.0.cmp(&b.0));
+        Ok(aliases)
+    }
+
+    async fn list_views(&self) -> Result<Vec<ViewDefinition>> {
+        self.base.list_views().await
+    }
+
+    async fn define_view(&self, _view: &ViewDefinition) -> Result<bool> {
+        Err(unsupported("defining views in a dry run"))
+    }
+
+    async fn drop_view(&self, _name: &str) -> Result<bool> {
+        Err(unsupported("dropping views in a dry run"))
+    }
+
+    async fn view_totals(&self, name: &str, group: &[String]) -> Result<Option<ViewTotals>> {
+        self.base.view_totals(name, group).await
+    }
+
+    async fn view_rows(&self, name: &str) -> Result<Vec<ViewRow>> {
+        self.base.view_rows(name).await
+    }
+
+    async fn rebuild_views(&self) -> Result<usize> {
+        Err(unsupported("view rebuilds in a dry run"))
+    }
+
+    async fn snapshot(&self) -> Result<SnapshotBackend> {
+        Err(unsupported("snapshots in a dry run"))
+    }
+}
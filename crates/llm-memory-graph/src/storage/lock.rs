@@ -0,0 +1,137 @@
+//! Advisory lock keeping a database to one process at a time
+//!
+//! Sled refuses a second open of the same path with a bare I/O error. Before
+//! opening sled, [`DatabaseLock::acquire`] takes an exclusive lock on a file
+//! inside the database directory and records the holder's process ID and
+//! start time in it, so a second process gets [`Error::DatabaseLocked`] naming
+//! the holder instead. The lock is released when the backend is dropped, or
+//! by the operating system if the process dies.
+
+use crate::{Error, Result};
+use chrono::{DateTime, Utc};
+use fs2::FileExt;
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// Name of the lock file inside the database directory
+pub const LOCK_FILE_NAME: &str = "lmg.lock";
+
+/// How often a waiting open checks whether the lock was released
+const LOCK_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Holder information written to the lock file
+#[derive(Serialize, Deserialize)]
+struct LockHolder {
+    pid: u32,
+    since: DateTime<Utc>,
+}
+
+/// An exclusive lock on a database directory, held until dropped
+#[derive(Debug)]
+pub(crate) struct DatabaseLock {
+    file: File,
+}
+
+impl DatabaseLock {
+    /// Lock the database at `dir`, creating the directory if needed
+    ///
+    /// With `wait` set, a lock held by another process is retried until it is
+    /// released or the wait runs out.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::DatabaseLocked`] if another process holds the lock,
+    /// and an I/O error if the lock file cannot be opened.
+    pub(crate) fn acquire(dir: &Path, wait: Option<Duration>) -> Result<Self> {
+        std::fs::create_dir_all(dir)?;
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(dir.join(LOCK_FILE_NAME))?;
+        let deadline = wait.map(|wait| Instant::now() + wait);
+
+        loop {
+            match file.try_lock_exclusive() {
+                Ok(()) => break,
+                Err(e) if e.kind() == fs2::lock_contended_error().kind() => {
+                    if deadline.is_some_and(|deadline| Instant::now() < deadline) {
+                        std::thread::sleep(LOCK_POLL_INTERVAL);
+                        continue;
+                    }
+                    let holder = Self::read_holder(&mut file);
+                    return Err(Error::DatabaseLocked {
+                        pid: holder.pid,
+                        since: holder.since,
+                    });
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        let holder = LockHolder {
+            pid: std::process::id(),
+            since: Utc::now(),
+        };
+        file.set_len(0)?;
+        file.seek(SeekFrom::Start(0))?;
+        serde_json::to_writer(&mut file, &holder)
+            .map_err(|e| Error::SerializationError(e.to_string()))?;
+        file.flush()?;
+        Ok(Self { file })
+    }
+
+    /// Holder recorded in the lock file, or an unknown one if it cannot be
+    /// read, such as while the holder is still writing it
+    fn read_holder(file: &mut File) -> LockHolder {
+        let mut contents = String::new();
+        let recorded = file
+            .seek(SeekFrom::Start(0))
+            .and_then(|_| file.read_to_string(&mut contents))
+            .ok()
+            .and_then(|_| serde_json::from_str(&contents).ok());
+        recorded.unwrap_or_else(|| LockHolder {
+            pid: 0,
+            since: file
+                .metadata()
+                .and_then(|metadata| metadata.modified())
+                .map_or_else(|_| Utc::now(), DateTime::from),
+        })
+    }
+}
+
+impl Drop for DatabaseLock {
+    fn drop(&mut self) {
+        // Closing the file releases the lock too; unlocking first makes the
+        // release immediate even if the handle is kept alive elsewhere
+        let _ = self.file.unlock();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_lock_reports_holder_and_waits() {
+        let dir = tempdir().unwrap();
+        let lock = DatabaseLock::acquire(dir.path(), None).unwrap();
+
+        match DatabaseLock::acquire(dir.path(), None) {
+            Err(Error::DatabaseLocked { pid, .. }) => assert_eq!(pid, std::process::id()),
+            other => panic!("expected DatabaseLocked, got {other:?}"),
+        }
+
+        let release = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(100));
+            drop(lock);
+        });
+        DatabaseLock::acquire(dir.path(), Some(Duration::from_secs(5))).unwrap();
+        release.join().unwrap();
+    }
+}
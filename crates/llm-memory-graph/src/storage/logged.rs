@@ -0,0 +1,738 @@
+//! Structured logging of mutations
+//!
+//! Every graph opens its storage through a [`LoggedBackend`], which emits one
+//! `tracing` event per write under the [`MUTATION_TARGET`] target, at the
+//! level set by [`Config::log_level`](crate::Config). Events carry
+//! machine-parsable fields:
+//!
+//! | Field | Value |
+//! |-------|-------|
+//! | `op` | Backend operation, such as `store_node` or `trash_node` |
+//! | `node_id` | Node written, or the source of an edge written |
+//! | `edge_id` | Edge written |
+//! | `session_id` | Session the node belongs to, when the node records it |
+//! | `key` | Alias, key-value key or view name written |
+//! | `records` | Number of records a batch wrote or an operation affected |
+//! | `duration_us` | How long the write took, in microseconds |
+//! | `result` | `ok` or `error` |
+//! | `error` | The error, if the write failed |
+//! | `content` | The node or edge as JSON, only with [`Config::log_content`](crate::Config) |
+//!
+//! Contents are left out by default so prompt and response text never
+//! reaches the logs unless asked for. Bookkeeping written alongside a
+//! mutation, its audit entry and idempotency record, is not logged
+//! separately.
+
+use super::{
+    AsyncStorageBackend, ColdTierReport, EdgePage, IdempotencyRecord, IndexRebuildReport, KvEntry,
+    NodeDegree, NodeEmbedding, NodeVersion, SessionCheckpoint, SnapshotBackend, StorageStats,
+    TrashedNode,
+};
+use crate::audit::{AuditEntry, AuditFilter};
+use crate::changes::ChangeRecord;
+use crate::deadline::Deadline;
+use crate::usage::{UsageBucket, UsageGranularity};
+use crate::{
+    AliasTarget, ConversationSession, Edge, EdgeId, LogLevel, Node, NodeId, PromptNode,
+    ReadConsistency, Result, SessionId, ViewDefinition, ViewRow, ViewTotals,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
+
+/// `tracing` target of mutation events
+pub const MUTATION_TARGET: &str = "llm_memory_graph::mutation";
+
+/// Emit a mutation event at a level chosen at runtime
+macro_rules! mutation_event {
+    ($level:expr, $($fields:tt)+) => {
+        match $level {
+            LogLevel::Off => {}
+            LogLevel::Error => tracing::error!(target: MUTATION_TARGET, $($fields)+),
+            LogLevel::Warn => tracing::warn!(target: MUTATION_TARGET, $($fields)+),
+            LogLevel::Info => tracing::info!(target: MUTATION_TARGET, $($fields)+),
+            LogLevel::Debug => tracing::debug!(target: MUTATION_TARGET, $($fields)+),
+            LogLevel::Trace => tracing::trace!(target: MUTATION_TARGET, $($fields)+),
+        }
+    };
+}
+
+/// What a write touched
+#[derive(Debug, Default)]
+struct Mutation {
+    node_id: Option<NodeId>,
+    edge_id: Option<EdgeId>,
+    session_id: Option<SessionId>,
+    key: Option<String>,
+    records: Option<usize>,
+    content: Option<String>,
+}
+
+impl Mutation {
+    fn node(node_id: NodeId) -> Self {
+        Self {
+            node_id: Some(node_id),
+            ..Self::default()
+        }
+    }
+
+    fn session(session_id: SessionId) -> Self {
+        Self {
+            session_id: Some(session_id),
+            ..Self::default()
+        }
+    }
+
+    fn key(key: &str) -> Self {
+        Self {
+            key: Some(key.to_string()),
+            ..Self::default()
+        }
+    }
+
+    fn records(records: usize) -> Self {
+        Self {
+            records: Some(records),
+            ..Self::default()
+        }
+    }
+}
+
+/// Session a node records itself as belonging to
+fn node_session(node: &Node) -> Option<SessionId> {
+    match node {
+        Node::Session(s) => Some(s.id),
+        Node::Prompt(p) => Some(p.session_id),
+        Node::Custom(c) => c.session_id,
+        Node::Response(_) | Node::ToolInvocation(_) | Node::Agent(_) | Node::Template(_) => None,
+    }
+}
+
+/// Backend that logs every write to `inner` and forwards reads untouched
+pub struct LoggedBackend {
+    inner: Arc<dyn AsyncStorageBackend>,
+    level: LogLevel,
+    content: bool,
+}
+
+impl LoggedBackend {
+    /// Log writes to `inner` at `level`, without their contents
+    pub fn new(inner: Arc<dyn AsyncStorageBackend>, level: LogLevel) -> Self {
+        Self {
+            inner,
+            level,
+            content: false,
+        }
+    }
+
+    /// Include written nodes and edges, as JSON, in the events
+    #[must_use]
+    pub fn with_content(mut self, enable: bool) -> Self {
+        self.content = enable;
+        self
+    }
+
+    /// The wrapped backend
+    pub fn inner(&self) -> &Arc<dyn AsyncStorageBackend> {
+        &self.inner
+    }
+
+    /// `value` as JSON if contents are logged
+    fn content_of(&self, value: &impl Serialize) -> Option<String> {
+        if self.content {
+            serde_json::to_string(value).ok()
+        } else {
+            None
+        }
+    }
+
+    fn node_mutation(&self, node: &Node) -> Mutation {
+        Mutation {
+            session_id: node_session(node),
+            content: self.content_of(node),
+            ..Mutation::node(node.id())
+        }
+    }
+
+    fn edge_mutation(&self, edge: &Edge) -> Mutation {
+        Mutation {
+            edge_id: Some(edge.id),
+            content: self.content_of(edge),
+            ..Mutation::node(edge.from)
+        }
+    }
+
+    /// Run a write and log it
+    async fn write<T>(
+        &self,
+        op: &'static str,
+        mutation: Mutation,
+        write: impl Future<Output = Result<T>>,
+    ) -> Result<T> {
+        self.write_counted(op, mutation, write, |_| None).await
+    }
+
+    /// Run a write and log it along with the number of records `affected`
+    /// says it touched
+    async fn write_counted<T>(
+        &self,
+        op: &'static str,
+        mut mutation: Mutation,
+        write: impl Future<Output = Result<T>>,
+        affected: impl FnOnce(&T) -> Option<usize>,
+    ) -> Result<T> {
+        if self.level == LogLevel::Off {
+            return write.await;
+        }
+        let start = Instant::now();
+        let result = write.await;
+        if let Ok(value) = &result {
+            mutation.records = affected(value).or(mutation.records);
+        }
+        self.log(op, &mutation, start.elapsed(), result.as_ref().err());
+        result
+    }
+
+    fn log(
+        &self,
+        op: &'static str,
+        mutation: &Mutation,
+        duration: Duration,
+        error: Option<&crate::Error>,
+    ) {
+        let duration_us = u64::try_from(duration.as_micros()).unwrap_or(u64::MAX);
+        mutation_event!(
+            self.level,
+            op,
+            node_id = mutation.node_id.map(tracing::field::display),
+            edge_id = mutation.edge_id.map(tracing::field::display),
+            session_id = mutation.session_id.map(tracing::field::display),
+            key = mutation.key.as_deref(),
+            records = mutation.records.map(|n| n as u64),
+            duration_us,
+            result = if error.is_some() { "error" } else { "ok" },
+            error = error.map(tracing::field::display),
+            content = mutation.content.as_deref(),
+            "Mutation",
+        );
+    }
+}
+
+#[async_trait]
+impl AsyncStorageBackend for LoggedBackend {
+    async fn store_node(&self, node: &Node) -> Result<()> {
+        self.write(
+            "store_node",
+            self.node_mutation(node),
+            self.inner.store_node(node),
+        )
+        .await
+    }
+
+    async fn get_node(&self, id: &NodeId) -> Result<Option<Node>> {
+        self.inner.get_node(id).await
+    }
+
+    async fn delete_node(&self, id: &NodeId) -> Result<()> {
+        self.write(
+            "delete_node",
+            Mutation::node(*id),
+            self.inner.delete_node(id),
+        )
+        .await
+    }
+
+    async fn store_edge(&self, edge: &Edge) -> Result<()> {
+        self.write(
+            "store_edge",
+            self.edge_mutation(edge),
+            self.inner.store_edge(edge),
+        )
+        .await
+    }
+
+    async fn get_edge(&self, id: &EdgeId) -> Result<Option<Edge>> {
+        self.inner.get_edge(id).await
+    }
+
+    async fn delete_edge(&self, id: &EdgeId) -> Result<()> {
+        let mutation = Mutation {
+            edge_id: Some(*id),
+            ..Mutation::default()
+        };
+        self.write("delete_edge", mutation, self.inner.delete_edge(id))
+            .await
+    }
+
+    async fn get_session_nodes(&self, session_id: &SessionId) -> Result<Vec<Node>> {
+        self.inner.get_session_nodes(session_id).await
+    }
+
+    async fn get_session_nodes_until(
+        &self,
+        session_id: &SessionId,
+        deadline: &Deadline,
+    ) -> Result<Vec<Node>> {
+        self.inner
+            .get_session_nodes_until(session_id, deadline)
+            .await
+    }
+
+    async fn get_outgoing_edges(&self, node_id: &NodeId) -> Result<Vec<Edge>> {
+        self.inner.get_outgoing_edges(node_id).await
+    }
+
+    async fn get_incoming_edges(&self, node_id: &NodeId) -> Result<Vec<Edge>> {
+        self.inner.get_incoming_edges(node_id).await
+    }
+
+    async fn flush(&self) -> Result<()> {
+        self.inner.flush().await
+    }
+
+    async fn prepare_read(&self, consistency: ReadConsistency) -> Result<()> {
+        self.inner.prepare_read(consistency).await
+    }
+
+    async fn stats(&self) -> Result<StorageStats> {
+        self.inner.stats().await
+    }
+
+    async fn store_nodes_batch(&self, nodes: &[Node]) -> Result<Vec<NodeId>> {
+        let mutation = Mutation {
+            content: self.content_of(&nodes),
+            ..Mutation::records(nodes.len())
+        };
+        self.write(
+            "store_nodes_batch",
+            mutation,
+            self.inner.store_nodes_batch(nodes),
+        )
+        .await
+    }
+
+    async fn store_edges_batch(&self, edges: &[Edge]) -> Result<Vec<EdgeId>> {
+        let mutation = Mutation {
+            content: self.content_of(&edges),
+            ..Mutation::records(edges.len())
+        };
+        self.write(
+            "store_edges_batch",
+            mutation,
+            self.inner.store_edges_batch(edges),
+        )
+        .await
+    }
+
+    async fn count_session_nodes(&self, session_id: &SessionId) -> Result<usize> {
+        self.inner.count_session_nodes(session_id).await
+    }
+
+    async fn append_audit_entry(&self, entry: AuditEntry) -> Result<AuditEntry> {
+        self.inner.append_audit_entry(entry).await
+    }
+
+    async fn audit_entries(&self, filter: &AuditFilter) -> Result<Vec<AuditEntry>> {
+        self.inner.audit_entries(filter).await
+    }
+
+    async fn trash_node(&self, id: &NodeId) -> Result<Option<TrashedNode>> {
+        self.write_counted(
+            "trash_node",
+            Mutation::node(*id),
+            self.inner.trash_node(id),
+            |trashed| Some(usize::from(trashed.is_some())),
+        )
+        .await
+    }
+
+    async fn restore_node(&self, id: &NodeId) -> Result<Option<Node>> {
+        self.write_counted(
+            "restore_node",
+            Mutation::node(*id),
+            self.inner.restore_node(id),
+            |restored| Some(usize::from(restored.is_some())),
+        )
+        .await
+    }
+
+    async fn trashed_nodes(&self) -> Result<Vec<TrashedNode>> {
+        self.inner.trashed_nodes().await
+    }
+
+    async fn purge_trash(
+        &self,
+        cutoff: DateTime<Utc>,
+        keep: &HashSet<SessionId>,
+    ) -> Result<Vec<NodeId>> {
+        self.write_counted(
+            "purge_trash",
+            Mutation::default(),
+            self.inner.purge_trash(cutoff, keep),
+            |purged| Some(purged.len()),
+        )
+        .await
+    }
+
+    async fn list_sessions(&self) -> Result<Vec<ConversationSession>> {
+        self.inner.list_sessions().await
+    }
+
+    async fn find_prompts_by_content(
+        &self,
+        content: &str,
+        session_id: Option<&SessionId>,
+    ) -> Result<Vec<PromptNode>> {
+        self.inner
+            .find_prompts_by_content(content, session_id)
+            .await
+    }
+
+    async fn move_session_nodes(
+        &self,
+        source: &SessionId,
+        target: &SessionId,
+    ) -> Result<Vec<NodeId>> {
+        self.write_counted(
+            "move_session_nodes",
+            Mutation::session(*target),
+            self.inner.move_session_nodes(source, target),
+            |moved| Some(moved.len()),
+        )
+        .await
+    }
+
+    async fn compact_indexes(&self) -> Result<usize> {
+        self.write_counted(
+            "compact_indexes",
+            Mutation::default(),
+            self.inner.compact_indexes(),
+            |removed| Some(*removed),
+        )
+        .await
+    }
+
+    async fn rebuild_indexes(&self) -> Result<IndexRebuildReport> {
+        self.write(
+            "rebuild_indexes",
+            Mutation::default(),
+            self.inner.rebuild_indexes(),
+        )
+        .await
+    }
+
+    async fn tier_cold(&self, cutoff: DateTime<Utc>) -> Result<ColdTierReport> {
+        self.write(
+            "tier_cold",
+            Mutation::default(),
+            self.inner.tier_cold(cutoff),
+        )
+        .await
+    }
+
+    async fn usage_rollups(
+        &self,
+        granularity: UsageGranularity,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<UsageBucket>> {
+        self.inner.usage_rollups(granularity, from, to).await
+    }
+
+    async fn all_nodes(&self) -> Result<Vec<Node>> {
+        self.inner.all_nodes().await
+    }
+
+    async fn all_edges(&self) -> Result<Vec<Edge>> {
+        self.inner.all_edges().await
+    }
+
+    async fn changes_since(&self, cursor: Option<u64>, limit: usize) -> Result<Vec<ChangeRecord>> {
+        self.inner.changes_since(cursor, limit).await
+    }
+
+    async fn latest_change_cursor(&self) -> Result<Option<u64>> {
+        self.inner.latest_change_cursor().await
+    }
+
+    fn subscribe_changes(&self) -> Result<broadcast::Receiver<ChangeRecord>> {
+        self.inner.subscribe_changes()
+    }
+
+    async fn prune_changes(&self, cursor: u64) -> Result<usize> {
+        self.write_counted(
+            "prune_changes",
+            Mutation::default(),
+            self.inner.prune_changes(cursor),
+            |pruned| Some(*pruned),
+        )
+        .await
+    }
+
+    async fn idempotency_record(&self, key: &str) -> Result<Option<IdempotencyRecord>> {
+        self.inner.idempotency_record(key).await
+    }
+
+    async fn store_idempotency_record(&self, key: &str, record: &IdempotencyRecord) -> Result<()> {
+        self.inner.store_idempotency_record(key, record).await
+    }
+
+    async fn store_checkpoint(&self, checkpoint: &SessionCheckpoint) -> Result<()> {
+        self.write(
+            "store_checkpoint",
+            Mutation::session(checkpoint.session_id),
+            self.inner.store_checkpoint(checkpoint),
+        )
+        .await
+    }
+
+    async fn session_checkpoints(&self, session_id: &SessionId) -> Result<Vec<SessionCheckpoint>> {
+        self.inner.session_checkpoints(session_id).await
+    }
+
+    async fn store_node_version(&self, version: &NodeVersion) -> Result<()> {
+        let mutation = Mutation {
+            content: self.content_of(version),
+            ..Mutation::node(version.node_id)
+        };
+        self.write(
+            "store_node_version",
+            mutation,
+            self.inner.store_node_version(version),
+        )
+        .await
+    }
+
+    async fn node_versions(&self, node_id: &NodeId) -> Result<Vec<NodeVersion>> {
+        self.inner.node_versions(node_id).await
+    }
+
+    async fn node_version_ids(&self) -> Result<Vec<NodeId>> {
+        self.inner.node_version_ids().await
+    }
+
+    async fn kv_get(&self, key: &str) -> Result<Option<KvEntry>> {
+        self.inner.kv_get(key).await
+    }
+
+    async fn kv_set(&self, entry: &KvEntry) -> Result<()> {
+        self.write(
+            "kv_set",
+            Mutation::key(&entry.key),
+            self.inner.kv_set(entry),
+        )
+        .await
+    }
+
+    async fn kv_delete(&self, key: &str) -> Result<bool> {
+        self.write_counted(
+            "kv_delete",
+            Mutation::key(key),
+            self.inner.kv_delete(key),
+            |removed| Some(usize::from(*removed)),
+        )
+        .await
+    }
+
+    async fn kv_list(&self, prefix: &str) -> Result<Vec<KvEntry>> {
+        self.inner.kv_list(prefix).await
+    }
+
+    async fn store_embedding(&self, embedding: &NodeEmbedding) -> Result<()> {
+        self.write(
+            "store_embedding",
+            Mutation::node(embedding.node_id),
+            self.inner.store_embedding(embedding),
+        )
+        .await
+    }
+
+    async fn get_embedding(&self, node_id: &NodeId) -> Result<Option<NodeEmbedding>> {
+        self.inner.get_embedding(node_id).await
+    }
+
+    async fn all_embeddings(&self) -> Result<Vec<NodeEmbedding>> {
+        self.inner.all_embeddings().await
+    }
+
+    async fn set_alias(&self, alias: &str, target: AliasTarget) -> Result<Option<AliasTarget>> {
+        self.write(
+            "set_alias",
+            Mutation::key(alias),
+            self.inner.set_alias(alias, target),
+        )
+        .await
+    }
+
+    async fn resolve_alias(&self, alias: &str) -> Result<Option<AliasTarget>> {
+        self.inner.resolve_alias(alias).await
+    }
+
+    async fn remove_alias(&self, alias: &str) -> Result<Option<AliasTarget>> {
+        self.write_counted(
+            "remove_alias",
+            Mutation::key(alias),
+            self.inner.remove_alias(alias),
+            |removed| Some(usize::from(removed.is_some())),
+        )
+        .await
+    }
+
+    async fn list_aliases(&self, prefix: &str) -> Result<Vec<(String, AliasTarget)>> {
+        self.inner.list_aliases(prefix).await
+    }
+
+    async fn list_views(&self) -> Result<Vec<ViewDefinition>> {
+        self.inner.list_views().await
+    }
+
+    async fn define_view(&self, view: &ViewDefinition) -> Result<bool> {
+        self.write(
+            "define_view",
+            Mutation::key(&view.name),
+            self.inner.define_view(view),
+        )
+        .await
+    }
+
+    async fn drop_view(&self, name: &str) -> Result<bool> {
+        self.write_counted(
+            "drop_view",
+            Mutation::key(name),
+            self.inner.drop_view(name),
+            |dropped| Some(usize::from(*dropped)),
+        )
+        .await
+    }
+
+    async fn view_totals(&self, name: &str, group: &[String]) -> Result<Option<ViewTotals>> {
+        self.inner.view_totals(name, group).await
+    }
+
+    async fn view_rows(&self, name: &str) -> Result<Vec<ViewRow>> {
+        self.inner.view_rows(name).await
+    }
+
+    async fn rebuild_views(&self) -> Result<usize> {
+        self.write_counted(
+            "rebuild_views",
+            Mutation::default(),
+            self.inner.rebuild_views(),
+            |rebuilt| Some(*rebuilt),
+        )
+        .await
+    }
+
+    async fn snapshot(&self) -> Result<SnapshotBackend> {
+        self.inner.snapshot().await
+    }
+
+    async fn get_outgoing_edges_page(
+        &self,
+        node_id: &NodeId,
+        cursor: Option<&EdgeId>,
+        limit: usize,
+    ) -> Result<EdgePage> {
+        self.inner
+            .get_outgoing_edges_page(node_id, cursor, limit)
+            .await
+    }
+
+    async fn get_incoming_edges_page(
+        &self,
+        node_id: &NodeId,
+        cursor: Option<&EdgeId>,
+        limit: usize,
+    ) -> Result<EdgePage> {
+        self.inner
+            .get_incoming_edges_page(node_id, cursor, limit)
+            .await
+    }
+
+    async fn node_degree(&self, node_id: &NodeId) -> Result<NodeDegree> {
+        self.inner.node_degree(node_id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::AsyncSledBackend;
+    use crate::{ConversationSession, PromptNode};
+    use parking_lot::Mutex;
+    use tempfile::tempdir;
+
+    /// Writer collecting formatted events
+    #[derive(Clone, Default)]
+    struct Capture(Arc<Mutex<Vec<u8>>>);
+
+    impl std::io::Write for Capture {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl Capture {
+        fn take(&self) -> String {
+            String::from_utf8(std::mem::take(&mut *self.0.lock())).unwrap()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_mutation_events() {
+        let capture = Capture::default();
+        let writer = capture.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_max_level(tracing::Level::INFO)
+            .with_ansi(false)
+            .with_writer(move || writer.clone())
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let dir = tempdir().unwrap();
+        let sled: Arc<dyn AsyncStorageBackend> =
+            Arc::new(AsyncSledBackend::open(dir.path()).await.unwrap());
+        let session = ConversationSession::new();
+        let prompt = Node::Prompt(PromptNode::new(session.id, "secret text".to_string()));
+
+        let backend = LoggedBackend::new(Arc::clone(&sled), LogLevel::Info);
+        backend.store_node(&prompt).await.unwrap();
+        backend.get_node(&prompt.id()).await.unwrap();
+        let logged = capture.take();
+        assert_eq!(logged.lines().count(), 1, "{logged}");
+        assert!(logged.contains(MUTATION_TARGET), "{logged}");
+        assert!(logged.contains("op=\"store_node\""), "{logged}");
+        assert!(
+            logged.contains(&format!("node_id={}", prompt.id())),
+            "{logged}"
+        );
+        assert!(
+            logged.contains(&format!("session_id={}", session.id)),
+            "{logged}"
+        );
+        assert!(logged.contains("duration_us="), "{logged}");
+        assert!(logged.contains("result=\"ok\""), "{logged}");
+        assert!(!logged.contains("secret text"), "{logged}");
+
+        let backend = LoggedBackend::new(Arc::clone(&sled), LogLevel::Info).with_content(true);
+        backend.store_node(&prompt).await.unwrap();
+        assert!(capture.take().contains("secret text"));
+
+        // Below the subscriber's level, or off, nothing is written
+        let backend = LoggedBackend::new(Arc::clone(&sled), LogLevel::Debug);
+        backend.delete_node(&prompt.id()).await.unwrap();
+        let backend = LoggedBackend::new(sled, LogLevel::Off);
+        backend.store_node(&prompt).await.unwrap();
+        assert!(capture.take().is_empty());
+    }
+}
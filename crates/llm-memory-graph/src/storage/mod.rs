@@ -2,19 +2,48 @@
 
 mod async_sled_backend;
 mod cache;
+#[cfg(any(test, feature = "chaos"))]
+mod chaos;
+mod dry_run;
+mod lock;
+mod logged;
 mod pooled_backend;
+#[cfg(test)]
+mod proptests;
+mod read_only;
 mod serialization;
 mod sled_backend;
+mod snapshot;
 
 pub use async_sled_backend::AsyncSledBackend;
 pub use cache::{CacheStats, StorageCache};
+#[cfg(any(test, feature = "chaos"))]
+pub use chaos::{ChaosBackend, ChaosConfig, ChaosStats};
+pub use dry_run::{Changeset, DryRunBackend, Mutation};
+pub use lock::LOCK_FILE_NAME;
+pub use logged::{LoggedBackend, MUTATION_TARGET};
 pub use pooled_backend::{PoolConfig, PoolMetrics, PoolMetricsSnapshot, PooledAsyncBackend};
-pub use serialization::{SerializationFormat, Serializer};
-pub use sled_backend::SledBackend;
+pub use read_only::ReadOnlyBackend;
+pub use serialization::{CompressionStats, SerializationFormat, Serializer};
+pub use sled_backend::{validate_namespace, SledBackend, DEFAULT_NAMESPACE, MAX_NAMESPACE_LEN};
+pub use snapshot::SnapshotBackend;
 
-use crate::Result;
-use crate::{Edge, EdgeId, Node, NodeId, SessionId};
+use crate::audit::{AuditEntry, AuditFilter};
+use crate::changes::ChangeRecord;
+use crate::deadline::Deadline;
+use crate::usage::{UsageBucket, UsageGranularity};
+use crate::{
+    AliasTarget, ConversationSession, Edge, EdgeId, Node, NodeId, PromptNode, ReadConsistency,
+    SessionId, ViewDefinition, ViewRow, ViewTotals,
+};
+use crate::{Error, Result};
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::time::Duration;
+use tokio::sync::broadcast;
+use uuid::Uuid;
 
 /// Trait defining storage backend operations
 pub trait StorageBackend: Send + Sync {
@@ -39,6 +68,17 @@ pub trait StorageBackend: Send + Sync {
     /// Get all nodes in a session
     fn get_session_nodes(&self, session_id: &SessionId) -> Result<Vec<Node>>;
 
+    /// As [`get_session_nodes`](Self::get_session_nodes), stopping with
+    /// [`Error::Interrupted`] once `deadline` passes
+    fn get_session_nodes_until(
+        &self,
+        session_id: &SessionId,
+        deadline: &Deadline,
+    ) -> Result<Vec<Node>> {
+        deadline.check("get_session_nodes", None)?;
+        self.get_session_nodes(session_id)
+    }
+
     /// Get all edges from a node
     fn get_outgoing_edges(&self, node_id: &NodeId) -> Result<Vec<Edge>>;
 
@@ -48,8 +88,142 @@ pub trait StorageBackend: Send + Sync {
     /// Flush any pending writes
     fn flush(&self) -> Result<()>;
 
+    /// Make storage ready for a read at `consistency`
+    ///
+    /// Flushes pending writes for [`ReadConsistency::RequireFlushed`]; the
+    /// other levels need nothing from storage.
+    fn prepare_read(&self, consistency: ReadConsistency) -> Result<()> {
+        match consistency {
+            ReadConsistency::RequireFlushed => self.flush(),
+            ReadConsistency::CacheOk | ReadConsistency::StorageOnly => Ok(()),
+        }
+    }
+
     /// Get storage statistics
     fn stats(&self) -> Result<StorageStats>;
+
+    /// Get up to `limit` edges from a node, starting after `cursor`
+    ///
+    /// Pass the previous page's [`EdgePage::next_cursor`] to continue. The
+    /// default implementation loads every edge and slices it; backends with an
+    /// ordered edge index should override it.
+    fn get_outgoing_edges_page(
+        &self,
+        node_id: &NodeId,
+        cursor: Option<&EdgeId>,
+        limit: usize,
+    ) -> Result<EdgePage> {
+        Ok(EdgePage::slice(
+            self.get_outgoing_edges(node_id)?,
+            cursor,
+            limit,
+        ))
+    }
+
+    /// Get up to `limit` edges to a node, starting after `cursor`
+    fn get_incoming_edges_page(
+        &self,
+        node_id: &NodeId,
+        cursor: Option<&EdgeId>,
+        limit: usize,
+    ) -> Result<EdgePage> {
+        Ok(EdgePage::slice(
+            self.get_incoming_edges(node_id)?,
+            cursor,
+            limit,
+        ))
+    }
+
+    /// Count the edges from and to a node without loading them
+    fn node_degree(&self, node_id: &NodeId) -> Result<NodeDegree> {
+        Ok(NodeDegree {
+            outgoing: self.get_outgoing_edges(node_id)?.len(),
+            incoming: self.get_incoming_edges(node_id)?.len(),
+        })
+    }
+}
+
+/// Default number of edges per page when streaming edges
+pub const DEFAULT_EDGE_PAGE_SIZE: usize = 1_000;
+
+/// One page of a node's edges
+#[derive(Debug, Clone, Default)]
+pub struct EdgePage {
+    /// Edges in this page, in index order
+    pub edges: Vec<Edge>,
+    /// Cursor for the next page, or `None` if this is the last page
+    pub next_cursor: Option<EdgeId>,
+}
+
+impl EdgePage {
+    /// Page of `edges` following `cursor`, for backends without an ordered index
+    ///
+    /// A `limit` of zero is treated as one.
+    pub(crate) fn slice(edges: Vec<Edge>, cursor: Option<&EdgeId>, limit: usize) -> Self {
+        let limit = limit.max(1);
+        let start = cursor
+            .and_then(|cursor| edges.iter().position(|edge| edge.id == *cursor))
+            .map_or(0, |position| position + 1);
+        let mut edges: Vec<Edge> = edges.into_iter().skip(start).collect();
+        let next_cursor = (edges.len() > limit).then(|| edges[limit - 1].id);
+        edges.truncate(limit);
+        Self { edges, next_cursor }
+    }
+}
+
+/// Number of edges touching a node
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NodeDegree {
+    /// Edges from the node
+    pub outgoing: usize,
+    /// Edges to the node
+    pub incoming: usize,
+}
+
+impl NodeDegree {
+    /// Edges in either direction
+    pub fn total(&self) -> usize {
+        self.outgoing + self.incoming
+    }
+}
+
+/// Entries written by an index rebuild
+///
+/// Returned by [`AsyncStorageBackend::rebuild_indexes`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexRebuildReport {
+    /// Nodes read from the node tree
+    pub nodes_scanned: usize,
+    /// Edges read from the edge tree
+    pub edges_scanned: usize,
+    /// Entries written to the session index
+    pub session_entries: usize,
+    /// Entries written to the prompt content index
+    pub content_entries: usize,
+    /// Entries written to the outgoing and incoming edge indexes, together
+    pub edge_entries: usize,
+    /// Nodes counted across all materialized views
+    pub view_members: usize,
+    /// Hourly and daily usage buckets written
+    pub usage_buckets: usize,
+}
+
+/// What a move to the cold tier did
+///
+/// Returned by [`AsyncStorageBackend::tier_cold`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ColdTierReport {
+    /// Nodes moved from the hot to the cold tier
+    pub nodes_moved: usize,
+    /// Edges moved from the hot to the cold tier
+    pub edges_moved: usize,
+}
+
+impl ColdTierReport {
+    /// Nodes and edges moved
+    pub fn total(&self) -> usize {
+        self.nodes_moved + self.edges_moved
+    }
 }
 
 /// Statistics about storage usage
@@ -63,6 +237,168 @@ pub struct StorageStats {
     pub storage_bytes: u64,
     /// Number of sessions
     pub session_count: u64,
+    /// Entries in each tree of the namespace, including its indexes
+    pub trees: Vec<TreeStats>,
+    /// How long the most recent flush took, if there has been one
+    pub last_flush_duration: Option<Duration>,
+    /// Writes made since the last flush
+    pub pending_writes: u64,
+    /// How well nodes written since the database was opened compressed
+    pub compression: CompressionStats,
+    /// Occupancy and hit rates of the graph's read cache, filled in by the
+    /// graph rather than by storage
+    pub cache: Option<CacheStats>,
+}
+
+/// Size of one storage tree
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TreeStats {
+    /// Name of the tree within its namespace, such as `nodes`
+    pub name: String,
+    /// Number of entries
+    pub entries: u64,
+}
+
+/// A soft-deleted node waiting in the trash
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrashedNode {
+    /// The node as it was when deleted
+    pub node: Node,
+    /// Session the node belonged to, if any
+    pub session_id: Option<SessionId>,
+    /// When the node was moved to the trash
+    pub deleted_at: DateTime<Utc>,
+}
+
+/// Write an idempotency key was first used for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IdempotentOperation {
+    /// [`add_prompt_idempotent`](crate::engine::AsyncMemoryGraph::add_prompt_idempotent)
+    AddPrompt,
+    /// [`add_response_idempotent`](crate::engine::AsyncMemoryGraph::add_response_idempotent)
+    AddResponse,
+    /// [`add_edge_idempotent`](crate::engine::AsyncMemoryGraph::add_edge_idempotent)
+    AddEdge,
+}
+
+/// Outcome of a write made under an idempotency key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IdempotencyRecord {
+    /// Write the key was used for
+    pub operation: IdempotentOperation,
+    /// Id of the node or edge the write created
+    pub id: Uuid,
+    /// When the key may be reused
+    pub expires_at: DateTime<Utc>,
+}
+
+impl IdempotencyRecord {
+    /// Whether the record has lapsed at `now`
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at <= now
+    }
+}
+
+/// The nodes and edges of a session at one point in time, see
+/// [`checkpoint_session`](crate::engine::AsyncMemoryGraph::checkpoint_session)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionCheckpoint {
+    /// Unique checkpoint identifier
+    pub id: Uuid,
+    /// Session the checkpoint was taken of
+    pub session_id: SessionId,
+    /// Caller-supplied name, such as the step an agent is about to start
+    pub label: String,
+    /// When the checkpoint was taken
+    pub created_at: DateTime<Utc>,
+    /// Nodes of the session, including the session node
+    pub node_ids: Vec<NodeId>,
+    /// Edges touching any of those nodes
+    pub edge_ids: Vec<EdgeId>,
+}
+
+/// An earlier state of a node whose content was edited, see
+/// [`edit_node_content`](crate::engine::AsyncMemoryGraph::edit_node_content)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeVersion {
+    /// Identifier of this version, the endpoint of its PREVIOUS_VERSION_OF
+    /// edges
+    pub id: NodeId,
+    /// The node this is a version of
+    pub node_id: NodeId,
+    /// Position in the node's history, starting at 1 for the original
+    pub version: u32,
+    /// The node as it was before the edit
+    pub node: Node,
+    /// Why the content was replaced
+    pub reason: String,
+    /// When the content was replaced
+    pub replaced_at: DateTime<Utc>,
+}
+
+impl NodeVersion {
+    /// The prompt or response content of this version
+    pub fn content(&self) -> Option<&str> {
+        match &self.node {
+            Node::Prompt(prompt) => Some(&prompt.content),
+            Node::Response(response) => Some(&response.content),
+            _ => None,
+        }
+    }
+}
+
+/// A value in the graph's key-value store, see
+/// [`kv`](crate::engine::AsyncMemoryGraph::kv)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KvEntry {
+    /// Key the value is stored under
+    pub key: String,
+    /// Stored bytes
+    pub value: Vec<u8>,
+    /// When the value was last set
+    pub updated_at: DateTime<Utc>,
+    /// When the value stops being visible, if it was set with a TTL
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl KvEntry {
+    /// Whether the entry has expired at `now`
+    pub fn is_expired_at(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at <= now)
+    }
+}
+
+/// Embedding vector computed for a node's text, see
+/// [`set_embedder`](crate::engine::AsyncMemoryGraph::set_embedder)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NodeEmbedding {
+    /// Node the vector was computed for
+    pub node_id: NodeId,
+    /// Model that produced the vector; vectors of different models are not
+    /// comparable
+    pub model: String,
+    /// The embedding
+    pub vector: Vec<f32>,
+    /// When the vector was computed
+    pub created_at: DateTime<Utc>,
+}
+
+/// Error returned by the default implementations of optional backend features
+pub(crate) fn unsupported(feature: &str) -> Error {
+    Error::Unsupported(format!(
+        "{feature} is not supported by this storage backend"
+    ))
+}
+
+/// Error returned by writes to a read-only backend
+pub(crate) fn read_only() -> Error {
+    Error::Storage("this storage backend is read-only".to_string())
+}
+
+/// Error returned by writes to a graph that has been closed
+pub(crate) fn closed() -> Error {
+    Error::Storage("the memory graph is closed".to_string())
 }
 
 /// Async trait defining storage backend operations
@@ -92,6 +428,22 @@ pub trait AsyncStorageBackend: Send + Sync {
     /// Get all nodes in a session asynchronously
     async fn get_session_nodes(&self, session_id: &SessionId) -> Result<Vec<Node>>;
 
+    /// As [`get_session_nodes`](Self::get_session_nodes), stopping with
+    /// [`Error::Interrupted`] once `deadline` passes
+    async fn get_session_nodes_until(
+        &self,
+        session_id: &SessionId,
+        deadline: &Deadline,
+    ) -> Result<Vec<Node>> {
+        deadline
+            .run(
+                "get_session_nodes",
+                None,
+                self.get_session_nodes(session_id),
+            )
+            .await
+    }
+
     /// Get all edges from a node asynchronously
     async fn get_outgoing_edges(&self, node_id: &NodeId) -> Result<Vec<Edge>>;
 
@@ -101,6 +453,17 @@ pub trait AsyncStorageBackend: Send + Sync {
     /// Flush any pending writes asynchronously
     async fn flush(&self) -> Result<()>;
 
+    /// Make storage ready for a read at `consistency` asynchronously
+    ///
+    /// Flushes pending writes for [`ReadConsistency::RequireFlushed`]; the
+    /// other levels need nothing from storage.
+    async fn prepare_read(&self, consistency: ReadConsistency) -> Result<()> {
+        match consistency {
+            ReadConsistency::RequireFlushed => self.flush().await,
+            ReadConsistency::CacheOk | ReadConsistency::StorageOnly => Ok(()),
+        }
+    }
+
     /// Get storage statistics asynchronously
     async fn stats(&self) -> Result<StorageStats>;
 
@@ -135,7 +498,7 @@ pub trait AsyncStorageBackend: Send + Sync {
     /// ```no_run
     /// use llm_memory_graph::storage::AsyncSledBackend;
     /// use llm_memory_graph::storage::AsyncStorageBackend;
-    /// use llm_memory_graph::types::SessionId;
+    /// use llm_memory_graph::SessionId;
     /// use futures::stream::StreamExt;
     ///
     /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
@@ -182,7 +545,7 @@ pub trait AsyncStorageBackend: Send + Sync {
     /// ```no_run
     /// use llm_memory_graph::storage::AsyncSledBackend;
     /// use llm_memory_graph::storage::AsyncStorageBackend;
-    /// use llm_memory_graph::types::SessionId;
+    /// use llm_memory_graph::SessionId;
     ///
     /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
     /// let backend = AsyncSledBackend::open("./data/graph.db").await?;
@@ -199,4 +562,388 @@ pub trait AsyncStorageBackend: Send + Sync {
         let nodes = self.get_session_nodes(session_id).await?;
         Ok(nodes.len())
     }
+
+    /// Append an entry to the append-only audit log
+    ///
+    /// Returns the entry with its storage-assigned sequence number. Backends
+    /// without an audit log return an error.
+    async fn append_audit_entry(&self, entry: AuditEntry) -> Result<AuditEntry> {
+        let _ = entry;
+        Err(unsupported("audit log"))
+    }
+
+    /// Audit entries matching `filter`, oldest first
+    async fn audit_entries(&self, filter: &AuditFilter) -> Result<Vec<AuditEntry>> {
+        let _ = filter;
+        Err(unsupported("audit log"))
+    }
+
+    /// Move a node to the trash, hiding it from reads and session listings
+    ///
+    /// Edges touching the node are kept so that a restore reconnects it.
+    /// Returns `None` if the node does not exist.
+    async fn trash_node(&self, id: &NodeId) -> Result<Option<TrashedNode>> {
+        let _ = id;
+        Err(unsupported("trash"))
+    }
+
+    /// Move a node from the trash back into the graph
+    ///
+    /// Returns `None` if the node is not in the trash.
+    async fn restore_node(&self, id: &NodeId) -> Result<Option<Node>> {
+        let _ = id;
+        Err(unsupported("trash"))
+    }
+
+    /// Every node currently in the trash
+    async fn trashed_nodes(&self) -> Result<Vec<TrashedNode>> {
+        Err(unsupported("trash"))
+    }
+
+    /// Permanently remove nodes trashed before `cutoff`, along with their
+    /// edges, except nodes of the sessions in `keep`
+    ///
+    /// Returns the IDs of the purged nodes.
+    async fn purge_trash(
+        &self,
+        cutoff: DateTime<Utc>,
+        keep: &HashSet<SessionId>,
+    ) -> Result<Vec<NodeId>> {
+        let _ = (cutoff, keep);
+        Err(unsupported("trash"))
+    }
+
+    /// Every session in the graph
+    async fn list_sessions(&self) -> Result<Vec<ConversationSession>> {
+        Err(unsupported("session listing"))
+    }
+
+    /// Prompts whose content is exactly `content`, oldest first, optionally
+    /// limited to one session
+    async fn find_prompts_by_content(
+        &self,
+        content: &str,
+        session_id: Option<&SessionId>,
+    ) -> Result<Vec<PromptNode>> {
+        let _ = (content, session_id);
+        Err(unsupported("content lookup"))
+    }
+
+    /// Move every node of session `source`, except its session node, to
+    /// session `target`
+    ///
+    /// Prompts and session-scoped custom nodes take `target` as their
+    /// session; responses follow their prompt. Returns the IDs of the moved
+    /// nodes.
+    async fn move_session_nodes(
+        &self,
+        source: &SessionId,
+        target: &SessionId,
+    ) -> Result<Vec<NodeId>> {
+        let _ = (source, target);
+        Err(unsupported("moving nodes between sessions"))
+    }
+
+    /// Remove index entries left dangling by hard deletes
+    ///
+    /// Returns the number of entries removed.
+    async fn compact_indexes(&self) -> Result<usize> {
+        Err(unsupported("index compaction"))
+    }
+
+    /// Rebuild every secondary index from the node and edge trees
+    ///
+    /// Existing index entries are discarded first, so entries that are
+    /// missing, stale or dangling are all repaired.
+    async fn rebuild_indexes(&self) -> Result<IndexRebuildReport> {
+        Err(unsupported("index rebuilds"))
+    }
+
+    /// Move nodes and edges created before `cutoff` to the cold tier
+    ///
+    /// Reads merge both tiers, so moved records stay visible; rewriting one
+    /// brings it back to the hot tier.
+    async fn tier_cold(&self, cutoff: DateTime<Utc>) -> Result<ColdTierReport> {
+        let _ = cutoff;
+        Err(unsupported("cold storage tiering"))
+    }
+
+    /// Stored usage buckets of `granularity` starting in `from..to`, oldest
+    /// first, see [`usage`](crate::usage)
+    async fn usage_rollups(
+        &self,
+        granularity: UsageGranularity,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<UsageBucket>> {
+        let _ = (granularity, from, to);
+        Err(unsupported("usage rollups"))
+    }
+
+    /// Every node in the graph, excluding the trash
+    async fn all_nodes(&self) -> Result<Vec<Node>> {
+        Err(unsupported("full scans"))
+    }
+
+    /// Every edge in the graph
+    async fn all_edges(&self) -> Result<Vec<Edge>> {
+        Err(unsupported("full scans"))
+    }
+
+    /// Up to `limit` captured changes after `cursor`, oldest first
+    ///
+    /// Pass `None` to start from the beginning of the change log.
+    async fn changes_since(
+        &self,
+        _cursor: Option<u64>,
+        _limit: usize,
+    ) -> Result<Vec<ChangeRecord>> {
+        Err(unsupported("change capture"))
+    }
+
+    /// Cursor of the most recent captured change, if any
+    async fn latest_change_cursor(&self) -> Result<Option<u64>> {
+        Err(unsupported("change capture"))
+    }
+
+    /// Remove captured changes up to and including `cursor`
+    async fn prune_changes(&self, _cursor: u64) -> Result<usize> {
+        Err(unsupported("change capture"))
+    }
+
+    /// Receive a record of every node and edge mutation from now on,
+    /// whether or not changes are captured
+    fn subscribe_changes(&self) -> Result<broadcast::Receiver<ChangeRecord>> {
+        Err(unsupported("change subscriptions"))
+    }
+
+    /// Unexpired record of the write made under idempotency `key`, if any
+    async fn idempotency_record(&self, _key: &str) -> Result<Option<IdempotencyRecord>> {
+        Err(unsupported("idempotency keys"))
+    }
+
+    /// Remember the outcome of the write made under idempotency `key`
+    async fn store_idempotency_record(
+        &self,
+        _key: &str,
+        _record: &IdempotencyRecord,
+    ) -> Result<()> {
+        Err(unsupported("idempotency keys"))
+    }
+
+    /// Save a checkpoint of a session
+    async fn store_checkpoint(&self, checkpoint: &SessionCheckpoint) -> Result<()> {
+        let _ = checkpoint;
+        Err(unsupported("session checkpoints"))
+    }
+
+    /// Checkpoints of a session, oldest first
+    async fn session_checkpoints(&self, session_id: &SessionId) -> Result<Vec<SessionCheckpoint>> {
+        let _ = session_id;
+        Err(unsupported("session checkpoints"))
+    }
+
+    /// Save an earlier version of a node
+    async fn store_node_version(&self, version: &NodeVersion) -> Result<()> {
+        let _ = version;
+        Err(unsupported("node versions"))
+    }
+
+    /// Earlier versions of a node, oldest first
+    async fn node_versions(&self, node_id: &NodeId) -> Result<Vec<NodeVersion>> {
+        let _ = node_id;
+        Err(unsupported("node versions"))
+    }
+
+    /// IDs of every stored node version, of any node
+    async fn node_version_ids(&self) -> Result<Vec<NodeId>> {
+        Err(unsupported("node versions"))
+    }
+
+    /// Key-value entry stored under `key`, expired or not
+    async fn kv_get(&self, key: &str) -> Result<Option<KvEntry>> {
+        let _ = key;
+        Err(unsupported("the key-value store"))
+    }
+
+    /// Store a key-value entry, replacing any entry under the same key
+    async fn kv_set(&self, entry: &KvEntry) -> Result<()> {
+        let _ = entry;
+        Err(unsupported("the key-value store"))
+    }
+
+    /// Remove the key-value entry under `key`, returning whether one existed
+    async fn kv_delete(&self, key: &str) -> Result<bool> {
+        let _ = key;
+        Err(unsupported("the key-value store"))
+    }
+
+    /// Key-value entries whose key starts with `prefix`, sorted by key
+    async fn kv_list(&self, prefix: &str) -> Result<Vec<KvEntry>> {
+        let _ = prefix;
+        Err(unsupported("the key-value store"))
+    }
+
+    /// Store the embedding of a node, replacing any earlier one
+    async fn store_embedding(&self, embedding: &NodeEmbedding) -> Result<()> {
+        let _ = embedding;
+        Err(unsupported("embeddings"))
+    }
+
+    /// Embedding stored for a node, if any
+    async fn get_embedding(&self, node_id: &NodeId) -> Result<Option<NodeEmbedding>> {
+        let _ = node_id;
+        Err(unsupported("embeddings"))
+    }
+
+    /// Every stored embedding
+    async fn all_embeddings(&self) -> Result<Vec<NodeEmbedding>> {
+        Err(unsupported("embeddings"))
+    }
+
+    /// Point `alias` at `target`, returning what it pointed at before
+    async fn set_alias(&self, alias: &str, target: AliasTarget) -> Result<Option<AliasTarget>> {
+        let _ = (alias, target);
+        Err(unsupported("aliases"))
+    }
+
+    /// What `alias` points at, if it is set
+    async fn resolve_alias(&self, alias: &str) -> Result<Option<AliasTarget>> {
+        let _ = alias;
+        Err(unsupported("aliases"))
+    }
+
+    /// Remove `alias`, returning what it pointed at
+    async fn remove_alias(&self, alias: &str) -> Result<Option<AliasTarget>> {
+        let _ = alias;
+        Err(unsupported("aliases"))
+    }
+
+    /// Every alias starting with `prefix`, sorted, with its target
+    async fn list_aliases(&self, prefix: &str) -> Result<Vec<(String, AliasTarget)>> {
+        let _ = prefix;
+        Err(unsupported("aliases"))
+    }
+
+    /// Materialized views defined in the graph
+    async fn list_views(&self) -> Result<Vec<ViewDefinition>> {
+        Err(unsupported("materialized views"))
+    }
+
+    /// Define a materialized view, replacing any view with the same name;
+    /// returns whether the view was built
+    async fn define_view(&self, view: &ViewDefinition) -> Result<bool> {
+        let _ = view;
+        Err(unsupported("materialized views"))
+    }
+
+    /// Remove a materialized view; returns `false` if no view has that name
+    async fn drop_view(&self, name: &str) -> Result<bool> {
+        let _ = name;
+        Err(unsupported("materialized views"))
+    }
+
+    /// Totals of one group of a view, `None` if no node falls in it
+    async fn view_totals(&self, name: &str, group: &[String]) -> Result<Option<ViewTotals>> {
+        let _ = (name, group);
+        Err(unsupported("materialized views"))
+    }
+
+    /// Every non-empty group of a view with its totals
+    async fn view_rows(&self, name: &str) -> Result<Vec<ViewRow>> {
+        let _ = name;
+        Err(unsupported("materialized views"))
+    }
+
+    /// Rebuild every view from the stored nodes, returning the number of
+    /// nodes counted
+    async fn rebuild_views(&self) -> Result<usize> {
+        Err(unsupported("materialized views"))
+    }
+
+    /// Copy the graph into a read-only backend pinned to the current moment
+    async fn snapshot(&self) -> Result<SnapshotBackend> {
+        Err(unsupported("snapshots"))
+    }
+
+    /// Get up to `limit` edges from a node, starting after `cursor`
+    ///
+    /// Pass the previous page's [`EdgePage::next_cursor`] to continue. The
+    /// default implementation loads every edge and slices it.
+    async fn get_outgoing_edges_page(
+        &self,
+        node_id: &NodeId,
+        cursor: Option<&EdgeId>,
+        limit: usize,
+    ) -> Result<EdgePage> {
+        let edges = self.get_outgoing_edges(node_id).await?;
+        Ok(EdgePage::slice(edges, cursor, limit))
+    }
+
+    /// Get up to `limit` edges to a node, starting after `cursor`
+    async fn get_incoming_edges_page(
+        &self,
+        node_id: &NodeId,
+        cursor: Option<&EdgeId>,
+        limit: usize,
+    ) -> Result<EdgePage> {
+        let edges = self.get_incoming_edges(node_id).await?;
+        Ok(EdgePage::slice(edges, cursor, limit))
+    }
+
+    /// Count the edges from and to a node without loading them
+    async fn node_degree(&self, node_id: &NodeId) -> Result<NodeDegree> {
+        Ok(NodeDegree {
+            outgoing: self.get_outgoing_edges(node_id).await?.len(),
+            incoming: self.get_incoming_edges(node_id).await?.len(),
+        })
+    }
+
+    /// Stream the edges from a node, fetching `page_size` edges at a time
+    fn get_outgoing_edges_stream(
+        &self,
+        node_id: &NodeId,
+        page_size: usize,
+    ) -> std::pin::Pin<Box<dyn futures::stream::Stream<Item = Result<Edge>> + Send + '_>> {
+        let node_id = *node_id;
+        Box::pin(async_stream::try_stream! {
+            let mut cursor = None;
+            loop {
+                let page = self
+                    .get_outgoing_edges_page(&node_id, cursor.as_ref(), page_size)
+                    .await?;
+                for edge in page.edges {
+                    yield edge;
+                }
+                match page.next_cursor {
+                    Some(next) => cursor = Some(next),
+                    None => break,
+                }
+            }
+        })
+    }
+
+    /// Stream the edges to a node, fetching `page_size` edges at a time
+    fn get_incoming_edges_stream(
+        &self,
+        node_id: &NodeId,
+        page_size: usize,
+    ) -> std::pin::Pin<Box<dyn futures::stream::Stream<Item = Result<Edge>> + Send + '_>> {
+        let node_id = *node_id;
+        Box::pin(async_stream::try_stream! {
+            let mut cursor = None;
+            loop {
+                let page = self
+                    .get_incoming_edges_page(&node_id, cursor.as_ref(), page_size)
+                    .await?;
+                for edge in page.edges {
+                    yield edge;
+                }
+                match page.next_cursor {
+                    Some(next) => cursor = Some(next),
+                    None => break,
+                }
+            }
+        })
+    }
 }
@@ -30,14 +30,27 @@
 //! └─────────────────────────────────────────┘
 //! ```
 
+use crate::audit::{AuditEntry, AuditFilter};
+use crate::changes::ChangeRecord;
+use crate::deadline::Deadline;
+use crate::storage::{
+    AsyncSledBackend, AsyncStorageBackend, ColdTierReport, EdgePage, IdempotencyRecord,
+    IndexRebuildReport, KvEntry, NodeDegree, NodeEmbedding, NodeVersion, SessionCheckpoint,
+    SnapshotBackend, StorageStats, TrashedNode,
+};
+use crate::usage::{UsageBucket, UsageGranularity};
+use crate::{
+    AliasTarget, ConversationSession, Edge, EdgeId, Node, NodeId, PromptNode, ReadConsistency,
+    SessionId, ViewDefinition, ViewRow, ViewTotals,
+};
 use crate::{Error, Result};
-use crate::storage::{AsyncSledBackend, AsyncStorageBackend, StorageStats};
-use crate::{Edge, EdgeId, Node, NodeId, SessionId};
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::collections::HashSet;
 use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
-use tokio::sync::Semaphore;
+use tokio::sync::{broadcast, Semaphore};
 use tokio::time::timeout;
 
 /// Configuration for the connection pool
@@ -384,6 +397,15 @@ impl AsyncStorageBackend for PooledAsyncBackend {
             .await
     }
 
+    async fn get_session_nodes_until(
+        &self,
+        session_id: &SessionId,
+        deadline: &Deadline,
+    ) -> Result<Vec<Node>> {
+        self.with_permit(self.backend.get_session_nodes_until(session_id, deadline))
+            .await
+    }
+
     async fn get_outgoing_edges(&self, node_id: &NodeId) -> Result<Vec<Edge>> {
         self.with_permit(self.backend.get_outgoing_edges(node_id))
             .await
@@ -398,6 +420,11 @@ impl AsyncStorageBackend for PooledAsyncBackend {
         self.with_permit(self.backend.flush()).await
     }
 
+    async fn prepare_read(&self, consistency: ReadConsistency) -> Result<()> {
+        self.with_permit(self.backend.prepare_read(consistency))
+            .await
+    }
+
     async fn stats(&self) -> Result<StorageStats> {
         self.with_permit(self.backend.stats()).await
     }
@@ -411,6 +438,236 @@ impl AsyncStorageBackend for PooledAsyncBackend {
         self.with_permit(self.backend.store_edges_batch(edges))
             .await
     }
+
+    async fn append_audit_entry(&self, entry: AuditEntry) -> Result<AuditEntry> {
+        self.with_permit(self.backend.append_audit_entry(entry))
+            .await
+    }
+
+    async fn audit_entries(&self, filter: &AuditFilter) -> Result<Vec<AuditEntry>> {
+        self.with_permit(self.backend.audit_entries(filter)).await
+    }
+
+    async fn trash_node(&self, id: &NodeId) -> Result<Option<TrashedNode>> {
+        self.with_permit(self.backend.trash_node(id)).await
+    }
+
+    async fn restore_node(&self, id: &NodeId) -> Result<Option<Node>> {
+        self.with_permit(self.backend.restore_node(id)).await
+    }
+
+    async fn trashed_nodes(&self) -> Result<Vec<TrashedNode>> {
+        self.with_permit(self.backend.trashed_nodes()).await
+    }
+
+    async fn purge_trash(
+        &self,
+        cutoff: DateTime<Utc>,
+        keep: &HashSet<SessionId>,
+    ) -> Result<Vec<NodeId>> {
+        self.with_permit(self.backend.purge_trash(cutoff, keep))
+            .await
+    }
+
+    async fn list_sessions(&self) -> Result<Vec<ConversationSession>> {
+        self.with_permit(self.backend.list_sessions()).await
+    }
+
+    async fn find_prompts_by_content(
+        &self,
+        content: &str,
+        session_id: Option<&SessionId>,
+    ) -> Result<Vec<PromptNode>> {
+        self.with_permit(self.backend.find_prompts_by_content(content, session_id))
+            .await
+    }
+
+    async fn move_session_nodes(
+        &self,
+        source: &SessionId,
+        target: &SessionId,
+    ) -> Result<Vec<NodeId>> {
+        self.with_permit(self.backend.move_session_nodes(source, target))
+            .await
+    }
+
+    async fn compact_indexes(&self) -> Result<usize> {
+        self.with_permit(self.backend.compact_indexes()).await
+    }
+
+    async fn rebuild_indexes(&self) -> Result<IndexRebuildReport> {
+        self.with_permit(self.backend.rebuild_indexes()).await
+    }
+
+    async fn tier_cold(&self, cutoff: DateTime<Utc>) -> Result<ColdTierReport> {
+        self.with_permit(self.backend.tier_cold(cutoff)).await
+    }
+
+    async fn all_nodes(&self) -> Result<Vec<Node>> {
+        self.with_permit(self.backend.all_nodes()).await
+    }
+
+    async fn all_edges(&self) -> Result<Vec<Edge>> {
+        self.with_permit(self.backend.all_edges()).await
+    }
+
+    async fn changes_since(&self, cursor: Option<u64>, limit: usize) -> Result<Vec<ChangeRecord>> {
+        self.with_permit(self.backend.changes_since(cursor, limit))
+            .await
+    }
+
+    async fn latest_change_cursor(&self) -> Result<Option<u64>> {
+        self.with_permit(self.backend.latest_change_cursor()).await
+    }
+
+    fn subscribe_changes(&self) -> Result<broadcast::Receiver<ChangeRecord>> {
+        self.backend.subscribe_changes()
+    }
+
+    async fn prune_changes(&self, cursor: u64) -> Result<usize> {
+        self.with_permit(self.backend.prune_changes(cursor)).await
+    }
+
+    async fn idempotency_record(&self, key: &str) -> Result<Option<IdempotencyRecord>> {
+        self.with_permit(self.backend.idempotency_record(key)).await
+    }
+
+    async fn store_idempotency_record(&self, key: &str, record: &IdempotencyRecord) -> Result<()> {
+        self.with_permit(self.backend.store_idempotency_record(key, record))
+            .await
+    }
+
+    async fn kv_get(&self, key: &str) -> Result<Option<KvEntry>> {
+        self.with_permit(self.backend.kv_get(key)).await
+    }
+
+    async fn kv_set(&self, entry: &KvEntry) -> Result<()> {
+        self.with_permit(self.backend.kv_set(entry)).await
+    }
+
+    async fn kv_delete(&self, key: &str) -> Result<bool> {
+        self.with_permit(self.backend.kv_delete(key)).await
+    }
+
+    async fn kv_list(&self, prefix: &str) -> Result<Vec<KvEntry>> {
+        self.with_permit(self.backend.kv_list(prefix)).await
+    }
+
+    async fn store_embedding(&self, embedding: &NodeEmbedding) -> Result<()> {
+        self.with_permit(self.backend.store_embedding(embedding))
+            .await
+    }
+
+    async fn get_embedding(&self, node_id: &NodeId) -> Result<Option<NodeEmbedding>> {
+        self.with_permit(self.backend.get_embedding(node_id)).await
+    }
+
+    async fn all_embeddings(&self) -> Result<Vec<NodeEmbedding>> {
+        self.with_permit(self.backend.all_embeddings()).await
+    }
+
+    async fn set_alias(&self, alias: &str, target: AliasTarget) -> Result<Option<AliasTarget>> {
+        self.with_permit(self.backend.set_alias(alias, target))
+            .await
+    }
+
+    async fn resolve_alias(&self, alias: &str) -> Result<Option<AliasTarget>> {
+        self.with_permit(self.backend.resolve_alias(alias)).await
+    }
+
+    async fn remove_alias(&self, alias: &str) -> Result<Option<AliasTarget>> {
+        self.with_permit(self.backend.remove_alias(alias)).await
+    }
+
+    async fn list_aliases(&self, prefix: &str) -> Result<Vec<(String, AliasTarget)>> {
+        self.with_permit(self.backend.list_aliases(prefix)).await
+    }
+
+    async fn store_checkpoint(&self, checkpoint: &SessionCheckpoint) -> Result<()> {
+        self.with_permit(self.backend.store_checkpoint(checkpoint))
+            .await
+    }
+
+    async fn session_checkpoints(&self, session_id: &SessionId) -> Result<Vec<SessionCheckpoint>> {
+        self.with_permit(self.backend.session_checkpoints(session_id))
+            .await
+    }
+
+    async fn store_node_version(&self, version: &NodeVersion) -> Result<()> {
+        self.with_permit(self.backend.store_node_version(version))
+            .await
+    }
+
+    async fn node_versions(&self, node_id: &NodeId) -> Result<Vec<NodeVersion>> {
+        self.with_permit(self.backend.node_versions(node_id)).await
+    }
+
+    async fn node_version_ids(&self) -> Result<Vec<NodeId>> {
+        self.with_permit(self.backend.node_version_ids()).await
+    }
+
+    async fn usage_rollups(
+        &self,
+        granularity: UsageGranularity,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<UsageBucket>> {
+        self.with_permit(self.backend.usage_rollups(granularity, from, to))
+            .await
+    }
+
+    async fn list_views(&self) -> Result<Vec<ViewDefinition>> {
+        self.with_permit(self.backend.list_views()).await
+    }
+
+    async fn define_view(&self, view: &ViewDefinition) -> Result<bool> {
+        self.with_permit(self.backend.define_view(view)).await
+    }
+
+    async fn drop_view(&self, name: &str) -> Result<bool> {
+        self.with_permit(self.backend.drop_view(name)).await
+    }
+
+    async fn view_totals(&self, name: &str, group: &[String]) -> Result<Option<ViewTotals>> {
+        self.with_permit(self.backend.view_totals(name, group))
+            .await
+    }
+
+    async fn view_rows(&self, name: &str) -> Result<Vec<ViewRow>> {
+        self.with_permit(self.backend.view_rows(name)).await
+    }
+
+    async fn rebuild_views(&self) -> Result<usize> {
+        self.with_permit(self.backend.rebuild_views()).await
+    }
+
+    async fn snapshot(&self) -> Result<SnapshotBackend> {
+        self.with_permit(self.backend.snapshot()).await
+    }
+
+    async fn get_outgoing_edges_page(
+        &self,
+        node_id: &NodeId,
+        cursor: Option<&EdgeId>,
+        limit: usize,
+    ) -> Result<EdgePage> {
+        self.with_permit(self.backend.get_outgoing_edges_page(node_id, cursor, limit))
+            .await
+    }
+
+    async fn get_incoming_edges_page(
+        &self,
+        node_id: &NodeId,
+        cursor: Option<&EdgeId>,
+        limit: usize,
+    ) -> Result<EdgePage> {
+        self.with_permit(self.backend.get_incoming_edges_page(node_id, cursor, limit))
+            .await
+    }
+
+    async fn node_degree(&self, node_id: &NodeId) -> Result<NodeDegree> {
+        self.with_permit(self.backend.node_degree(node_id)).await
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,483 @@
+//! Property tests for serialization round-trips
+//!
+//! Arbitrary nodes and edges must come back unchanged from every
+//! [`SerializationFormat`] that accepts them, and an arbitrary graph must
+//! survive being loaded into one database and copied into another that uses
+//! a different format. Values are compared through their JSON form, which
+//! covers every field.
+
+use super::{SerializationFormat, Serializer, SledBackend};
+use crate::{
+    AgentConfig, AgentId, AgentMetrics, AgentNode, AgentStatus, ConversationSession, CustomNode,
+    Edge, EdgeId, EdgeType, Node, NodeId, PromptMetadata, PromptNode, PromptTemplate, Properties,
+    ResponseMetadata, ResponseNode, SessionId, SessionStatus, TemplateId, TokenUsage,
+    ToolInvocation, VariableSpec, Version,
+};
+use chrono::{DateTime, Utc};
+use proptest::collection::{hash_map, vec};
+use proptest::option;
+use proptest::prelude::*;
+use serde_json::Value;
+use std::collections::HashMap;
+use tempfile::tempdir;
+use uuid::Uuid;
+
+const FORMATS: [SerializationFormat; 3] = [
+    SerializationFormat::Json,
+    SerializationFormat::MessagePack,
+    SerializationFormat::Bincode,
+];
+
+fn node_id() -> impl Strategy<Value = NodeId> {
+    any::<u128>().prop_map(|n| NodeId::from_uuid(Uuid::from_u128(n)))
+}
+
+fn session_id() -> impl Strategy<Value = SessionId> {
+    any::<u128>().prop_map(|n| SessionId::from_uuid(Uuid::from_u128(n)))
+}
+
+fn timestamp() -> impl Strategy<Value = DateTime<Utc>> {
+    (0_i64..4_102_444_800, 0_u32..1_000_000_000)
+        .prop_map(|(secs, nanos)| DateTime::from_timestamp(secs, nanos).unwrap())
+}
+
+fn text() -> impl Strategy<Value = String> {
+    ".{0,24}"
+}
+
+fn strings() -> impl Strategy<Value = HashMap<String, String>> {
+    hash_map("[a-z_]{1,8}", text(), 0..4)
+}
+
+fn json() -> impl Strategy<Value = Value> {
+    let leaf = prop_oneof![
+        Just(Value::Null),
+        any::<bool>().prop_map(Value::from),
+        any::<i64>().prop_map(Value::from),
+        (-1e12_f64..1e12).prop_map(Value::from),
+        text().prop_map(Value::from),
+    ];
+    leaf.prop_recursive(3, 16, 4, |inner| {
+        prop_oneof![
+            vec(inner.clone(), 0..4).prop_map(Value::Array),
+            hash_map("[a-z]{1,6}", inner, 0..4)
+                .prop_map(|map| Value::Object(map.into_iter().collect())),
+        ]
+    })
+}
+
+fn properties() -> impl Strategy<Value = Properties> {
+    hash_map("[a-z_]{1,8}", json(), 0..3)
+}
+
+fn tags() -> impl Strategy<Value = Vec<String>> {
+    vec("[a-z-]{1,10}", 0..3)
+}
+
+prop_compose! {
+    fn session()(
+        ids in (node_id(), session_id()),
+        created_at in timestamp(),
+        updated_at in timestamp(),
+        metadata in strings(),
+        tags in tags(),
+        properties in properties(),
+        title in option::of(text()),
+        status in prop_oneof![
+            Just(SessionStatus::Open),
+            Just(SessionStatus::Closed),
+            Just(SessionStatus::Archived),
+        ],
+    ) -> ConversationSession {
+        ConversationSession {
+            node_id: ids.0,
+            id: ids.1,
+            created_at,
+            updated_at,
+            metadata,
+            tags,
+            properties,
+            title,
+            status,
+        }
+    }
+}
+
+prop_compose! {
+    fn prompt()(
+        id in node_id(),
+        session_id in session_id(),
+        timestamp in timestamp(),
+        template_id in option::of(any::<u128>()),
+        content in text(),
+        variables in strings(),
+        model in text(),
+        temperature in -2.0_f32..2.0,
+        max_tokens in option::of(0_usize..1 << 20),
+        tools_available in tags(),
+        custom in strings(),
+        properties in properties(),
+    ) -> PromptNode {
+        PromptNode {
+            id,
+            session_id,
+            timestamp,
+            template_id: template_id
+                .map(|n| TemplateId::from_uuid(Uuid::from_u128(n))),
+            content,
+            variables,
+            metadata: PromptMetadata {
+                model,
+                temperature,
+                max_tokens,
+                tools_available,
+                custom,
+            },
+            properties,
+        }
+    }
+}
+
+prop_compose! {
+    fn response()(
+        id in node_id(),
+        prompt_id in node_id(),
+        timestamp in timestamp(),
+        content in text(),
+        tokens in (any::<u16>(), any::<u16>()),
+        model in text(),
+        finish_reason in text(),
+        latency_ms in any::<u64>(),
+        custom in strings(),
+        properties in properties(),
+    ) -> ResponseNode {
+        ResponseNode {
+            id,
+            prompt_id,
+            timestamp,
+            content,
+            usage: TokenUsage::new(u32::from(tokens.0), u32::from(tokens.1)),
+            metadata: ResponseMetadata {
+                model,
+                finish_reason,
+                latency_ms,
+                custom,
+            },
+            properties,
+        }
+    }
+}
+
+prop_compose! {
+    fn tool()(
+        ids in (node_id(), node_id()),
+        tool_name in text(),
+        parameters in json(),
+        result in option::of(json()),
+        error in option::of(text()),
+        duration_ms in any::<u64>(),
+        timestamp in timestamp(),
+        success in any::<bool>(),
+        retry_count in any::<u32>(),
+        metadata in strings(),
+        properties in properties(),
+    ) -> ToolInvocation {
+        ToolInvocation {
+            id: ids.0,
+            response_id: ids.1,
+            tool_name,
+            parameters,
+            result,
+            error,
+            duration_ms,
+            timestamp,
+            success,
+            retry_count,
+            metadata,
+            properties,
+        }
+    }
+}
+
+prop_compose! {
+    fn agent()(
+        ids in (any::<u128>(), node_id()),
+        names in (text(), text(), text()),
+        capabilities in tags(),
+        times in (timestamp(), timestamp()),
+        status in prop_oneof![
+            Just(AgentStatus::Active),
+            Just(AgentStatus::Idle),
+            Just(AgentStatus::Busy),
+            Just(AgentStatus::Paused),
+            Just(AgentStatus::Terminated),
+        ],
+        config in (-2.0_f32..2.0, 0_usize..1 << 20, any::<u64>(), any::<u32>(), tags()),
+        metrics in (any::<u64>(), any::<u64>(), any::<u64>(), 0.0_f64..1e9, any::<u64>()),
+        tags in tags(),
+        properties in properties(),
+    ) -> AgentNode {
+        AgentNode {
+            id: AgentId::from_uuid(Uuid::from_u128(ids.0)),
+            node_id: ids.1,
+            name: names.0,
+            role: names.1,
+            capabilities,
+            model: names.2,
+            created_at: times.0,
+            last_active: times.1,
+            status,
+            config: AgentConfig {
+                temperature: config.0,
+                max_tokens: config.1,
+                timeout_seconds: config.2,
+                max_retries: config.3,
+                tools_enabled: config.4,
+            },
+            metrics: AgentMetrics {
+                total_prompts: metrics.0,
+                successful_tasks: metrics.1,
+                failed_tasks: metrics.2,
+                average_latency_ms: metrics.3,
+                total_tokens_used: metrics.4,
+            },
+            tags,
+            properties,
+        }
+    }
+}
+
+prop_compose! {
+    fn variable()(
+        name in "[a-z_]{1,8}",
+        type_hint in text(),
+        required in any::<bool>(),
+        default in option::of(text()),
+        validation_pattern in option::of(text()),
+        description in text(),
+    ) -> VariableSpec {
+        VariableSpec {
+            name,
+            type_hint,
+            required,
+            default,
+            validation_pattern,
+            description,
+        }
+    }
+}
+
+prop_compose! {
+    fn template()(
+        ids in (any::<u128>(), node_id(), option::of(any::<u128>())),
+        version in (any::<u16>(), any::<u16>(), any::<u16>()),
+        texts in (text(), text(), text(), text()),
+        variables in vec(variable(), 0..3),
+        times in (timestamp(), timestamp()),
+        usage_count in any::<u64>(),
+        tags in tags(),
+        metadata in strings(),
+        properties in properties(),
+    ) -> PromptTemplate {
+        PromptTemplate {
+            id: TemplateId::from_uuid(Uuid::from_u128(ids.0)),
+            node_id: ids.1,
+            version: Version::new(version.0, version.1, version.2),
+            name: texts.0,
+            description: texts.1,
+            template: texts.2,
+            variables,
+            parent_id: ids.2.map(|n| TemplateId::from_uuid(Uuid::from_u128(n))),
+            created_at: times.0,
+            updated_at: times.1,
+            author: texts.3,
+            usage_count,
+            tags,
+            metadata,
+            properties,
+        }
+    }
+}
+
+prop_compose! {
+    fn custom()(
+        id in node_id(),
+        type_name in "[a-z_]{1,12}",
+        session_id in option::of(session_id()),
+        created_at in timestamp(),
+        payload in json(),
+        properties in properties(),
+    ) -> CustomNode {
+        CustomNode {
+            id,
+            type_name,
+            session_id,
+            created_at,
+            payload,
+            properties,
+        }
+    }
+}
+
+fn node() -> impl Strategy<Value = Node> {
+    prop_oneof![
+        session().prop_map(Node::Session),
+        prompt().prop_map(Node::Prompt),
+        response().prop_map(Node::Response),
+        tool().prop_map(Node::ToolInvocation),
+        agent().prop_map(Node::Agent),
+        template().prop_map(Node::Template),
+        custom().prop_map(Node::Custom),
+    ]
+}
+
+fn edge_type() -> impl Strategy<Value = EdgeType> {
+    prop_oneof![
+        Just(EdgeType::Follows),
+        Just(EdgeType::RespondsTo),
+        Just(EdgeType::HandledBy),
+        Just(EdgeType::PartOf),
+        Just(EdgeType::Invokes),
+        Just(EdgeType::TransfersTo),
+        Just(EdgeType::Instantiates),
+        Just(EdgeType::Inherits),
+        Just(EdgeType::References),
+        Just(EdgeType::MentionedIn),
+        Just(EdgeType::Supersedes),
+        Just(EdgeType::PreviousVersionOf),
+    ]
+}
+
+prop_compose! {
+    fn edge()(
+        id in any::<u128>(),
+        ends in (node_id(), node_id()),
+        edge_type in edge_type(),
+        created_at in timestamp(),
+        properties in strings(),
+        attributes in properties(),
+    ) -> Edge {
+        Edge {
+            id: EdgeId::from_bytes(Uuid::from_u128(id).into_bytes()),
+            from: ends.0,
+            to: ends.1,
+            edge_type,
+            created_at,
+            properties,
+            attributes,
+        }
+    }
+}
+
+/// Nodes plus edges between them
+fn graph() -> impl Strategy<Value = (Vec<Node>, Vec<Edge>)> {
+    vec(node(), 1..12).prop_flat_map(|nodes| {
+        let count = nodes.len();
+        let edges = vec((edge(), 0..count, 0..count), 0..16);
+        (Just(nodes), edges).prop_map(|(nodes, edges)| {
+            let edges = edges
+                .into_iter()
+                .map(|(mut edge, from, to)| {
+                    edge.from = nodes[from].id();
+                    edge.to = nodes[to].id();
+                    edge
+                })
+                .collect();
+            (nodes, edges)
+        })
+    })
+}
+
+fn json_of(value: &impl serde::Serialize) -> Value {
+    serde_json::to_value(value).unwrap()
+}
+
+/// JSON form of `items`, sorted so that storage order does not matter
+fn sorted_json<T: serde::Serialize>(items: &[T]) -> Vec<String> {
+    let mut json: Vec<String> = items.iter().map(|item| json_of(item).to_string()).collect();
+    json.sort();
+    json
+}
+
+proptest! {
+    #[test]
+    fn prop_node_roundtrip(node in node()) {
+        for format in FORMATS {
+            let serializer = Serializer::new(format);
+            match serializer.serialize_node(&node) {
+                Ok(bytes) => {
+                    let decoded = serializer.deserialize_node(&bytes).unwrap();
+                    prop_assert_eq!(json_of(&decoded), json_of(&node), "{:?}", format);
+                }
+                // Bincode refuses JSON values up front rather than writing
+                // records it cannot read back
+                Err(err) => prop_assert!(
+                    format == SerializationFormat::Bincode,
+                    "{:?}: {}",
+                    format,
+                    err
+                ),
+            }
+        }
+    }
+
+    #[test]
+    fn prop_edge_roundtrip(edge in edge()) {
+        for format in FORMATS {
+            let serializer = Serializer::new(format);
+            match serializer.serialize_edge(&edge) {
+                Ok(bytes) => {
+                    let decoded = serializer.deserialize_edge(&bytes).unwrap();
+                    prop_assert_eq!(json_of(&decoded), json_of(&edge), "{:?}", format);
+                }
+                Err(err) => prop_assert!(
+                    format == SerializationFormat::Bincode,
+                    "{:?}: {}",
+                    format,
+                    err
+                ),
+            }
+        }
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(24))]
+
+    #[test]
+    fn prop_graph_survives_copy_between_formats((nodes, edges) in graph()) {
+        let dir = tempdir().unwrap();
+        let source = SledBackend::open(dir.path().join("source"))
+            .unwrap()
+            .with_format(SerializationFormat::MessagePack);
+        source.store_nodes_batch(&nodes).unwrap();
+        source.store_edges_batch(&edges).unwrap();
+
+        let target = SledBackend::open(dir.path().join("target"))
+            .unwrap()
+            .with_format(SerializationFormat::Json);
+        target.store_nodes_batch(&source.all_nodes().unwrap()).unwrap();
+        target.store_edges_batch(&source.all_edges().unwrap()).unwrap();
+
+        prop_assert_eq!(sorted_json(&target.all_nodes().unwrap()), sorted_json(&nodes));
+        prop_assert_eq!(sorted_json(&target.all_edges().unwrap()), sorted_json(&edges));
+    }
+}
+
+#[test]
+fn test_bincode_rejects_json_values() {
+    let serializer = Serializer::new(SerializationFormat::Bincode);
+    let prompt = Node::Prompt(PromptNode::new(SessionId::new(), "Hi".to_string()));
+    let bytes = serializer.serialize_node(&prompt).unwrap();
+    assert_eq!(
+        json_of(&serializer.deserialize_node(&bytes).unwrap()),
+        json_of(&prompt)
+    );
+
+    let tool = Node::ToolInvocation(ToolInvocation::new(
+        NodeId::new(),
+        "search".to_string(),
+        serde_json::json!({"query": "rust"}),
+    ));
+    assert!(serializer.serialize_node(&tool).is_err());
+}
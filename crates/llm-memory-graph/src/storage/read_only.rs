@@ -0,0 +1,361 @@
+//! Read-only wrapper around another backend
+//!
+//! A graph opened with [`Config::read_only`](crate::Config) wraps its storage in
+//! a [`ReadOnlyBackend`], so every write made through the graph fails while
+//! reads pass through. Replication applies changes to the wrapped backend
+//! directly. Other graphs wrap theirs with
+//! [`ReadOnlyBackend::until_closed`], which lets writes through until the
+//! graph is closed.
+
+use super::{
+    read_only, AsyncStorageBackend, ColdTierReport, EdgePage, IdempotencyRecord,
+    IndexRebuildReport, KvEntry, NodeDegree, NodeEmbedding, NodeVersion, SessionCheckpoint,
+    SnapshotBackend, StorageStats, TrashedNode,
+};
+use crate::audit::{AuditEntry, AuditFilter};
+use crate::changes::ChangeRecord;
+use crate::deadline::Deadline;
+use crate::usage::{UsageBucket, UsageGranularity};
+use crate::{
+    AliasTarget, ConversationSession, Edge, EdgeId, Node, NodeId, PromptNode, ReadConsistency,
+    Result, SessionId, ViewDefinition, ViewRow, ViewTotals,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tokio::sync::broadcast;
+
+/// Backend that rejects writes and forwards reads to `inner`
+pub struct ReadOnlyBackend {
+    inner: Arc<dyn AsyncStorageBackend>,
+    /// Flag that makes the backend read-only once set (`None` = always)
+    closed: Option<Arc<AtomicBool>>,
+}
+
+impl ReadOnlyBackend {
+    /// Wrap `inner`
+    pub fn new(inner: Arc<dyn AsyncStorageBackend>) -> Self {
+        Self {
+            inner,
+            closed: None,
+        }
+    }
+
+    /// Wrap `inner`, forwarding writes until `closed` is set
+    pub fn until_closed(inner: Arc<dyn AsyncStorageBackend>, closed: Arc<AtomicBool>) -> Self {
+        Self {
+            inner,
+            closed: Some(closed),
+        }
+    }
+
+    /// The wrapped backend if writes are still accepted
+    fn writable(&self) -> Result<&Arc<dyn AsyncStorageBackend>> {
+        match &self.closed {
+            None => Err(read_only()),
+            Some(closed) if closed.load(Ordering::Acquire) => Err(super::closed()),
+            Some(_) => Ok(&self.inner),
+        }
+    }
+
+    /// The wrapped backend, which still accepts writes
+    pub fn inner(&self) -> &Arc<dyn AsyncStorageBackend> {
+        &self.inner
+    }
+}
+
+#[async_trait]
+impl AsyncStorageBackend for ReadOnlyBackend {
+    async fn store_node(&self, node: &Node) -> Result<()> {
+        self.writable()?.store_node(node).await
+    }
+
+    async fn get_node(&self, id: &NodeId) -> Result<Option<Node>> {
+        self.inner.get_node(id).await
+    }
+
+    async fn delete_node(&self, id: &NodeId) -> Result<()> {
+        self.writable()?.delete_node(id).await
+    }
+
+    async fn store_edge(&self, edge: &Edge) -> Result<()> {
+        self.writable()?.store_edge(edge).await
+    }
+
+    async fn get_edge(&self, id: &EdgeId) -> Result<Option<Edge>> {
+        self.inner.get_edge(id).await
+    }
+
+    async fn delete_edge(&self, id: &EdgeId) -> Result<()> {
+        self.writable()?.delete_edge(id).await
+    }
+
+    async fn get_session_nodes(&self, session_id: &SessionId) -> Result<Vec<Node>> {
+        self.inner.get_session_nodes(session_id).await
+    }
+
+    async fn get_session_nodes_until(
+        &self,
+        session_id: &SessionId,
+        deadline: &Deadline,
+    ) -> Result<Vec<Node>> {
+        self.inner
+            .get_session_nodes_until(session_id, deadline)
+            .await
+    }
+
+    async fn get_outgoing_edges(&self, node_id: &NodeId) -> Result<Vec<Edge>> {
+        self.inner.get_outgoing_edges(node_id).await
+    }
+
+    async fn get_incoming_edges(&self, node_id: &NodeId) -> Result<Vec<Edge>> {
+        self.inner.get_incoming_edges(node_id).await
+    }
+
+    async fn flush(&self) -> Result<()> {
+        self.inner.flush().await
+    }
+
+    async fn prepare_read(&self, consistency: ReadConsistency) -> Result<()> {
+        self.inner.prepare_read(consistency).await
+    }
+
+    async fn stats(&self) -> Result<StorageStats> {
+        self.inner.stats().await
+    }
+
+    async fn store_nodes_batch(&self, nodes: &[Node]) -> Result<Vec<NodeId>> {
+        self.writable()?.store_nodes_batch(nodes).await
+    }
+
+    async fn store_edges_batch(&self, edges: &[Edge]) -> Result<Vec<EdgeId>> {
+        self.writable()?.store_edges_batch(edges).await
+    }
+
+    async fn count_session_nodes(&self, session_id: &SessionId) -> Result<usize> {
+        self.inner.count_session_nodes(session_id).await
+    }
+
+    async fn append_audit_entry(&self, entry: AuditEntry) -> Result<AuditEntry> {
+        self.writable()?.append_audit_entry(entry).await
+    }
+
+    async fn audit_entries(&self, filter: &AuditFilter) -> Result<Vec<AuditEntry>> {
+        self.inner.audit_entries(filter).await
+    }
+
+    async fn trash_node(&self, id: &NodeId) -> Result<Option<TrashedNode>> {
+        self.writable()?.trash_node(id).await
+    }
+
+    async fn restore_node(&self, id: &NodeId) -> Result<Option<Node>> {
+        self.writable()?.restore_node(id).await
+    }
+
+    async fn trashed_nodes(&self) -> Result<Vec<TrashedNode>> {
+        self.inner.trashed_nodes().await
+    }
+
+    async fn purge_trash(
+        &self,
+        cutoff: DateTime<Utc>,
+        keep: &HashSet<SessionId>,
+    ) -> Result<Vec<NodeId>> {
+        self.writable()?.purge_trash(cutoff, keep).await
+    }
+
+    async fn list_sessions(&self) -> Result<Vec<ConversationSession>> {
+        self.inner.list_sessions().await
+    }
+
+    async fn find_prompts_by_content(
+        &self,
+        content: &str,
+        session_id: Option<&SessionId>,
+    ) -> Result<Vec<PromptNode>> {
+        self.inner
+            .find_prompts_by_content(content, session_id)
+            .await
+    }
+
+    async fn move_session_nodes(
+        &self,
+        source: &SessionId,
+        target: &SessionId,
+    ) -> Result<Vec<NodeId>> {
+        self.writable()?.move_session_nodes(source, target).await
+    }
+
+    async fn compact_indexes(&self) -> Result<usize> {
+        self.writable()?.compact_indexes().await
+    }
+
+    async fn rebuild_indexes(&self) -> Result<IndexRebuildReport> {
+        self.writable()?.rebuild_indexes().await
+    }
+
+    async fn tier_cold(&self, cutoff: DateTime<Utc>) -> Result<ColdTierReport> {
+        self.writable()?.tier_cold(cutoff).await
+    }
+
+    async fn all_nodes(&self) -> Result<Vec<Node>> {
+        self.inner.all_nodes().await
+    }
+
+    async fn all_edges(&self) -> Result<Vec<Edge>> {
+        self.inner.all_edges().await
+    }
+
+    async fn changes_since(&self, cursor: Option<u64>, limit: usize) -> Result<Vec<ChangeRecord>> {
+        self.inner.changes_since(cursor, limit).await
+    }
+
+    async fn latest_change_cursor(&self) -> Result<Option<u64>> {
+        self.inner.latest_change_cursor().await
+    }
+
+    fn subscribe_changes(&self) -> Result<broadcast::Receiver<ChangeRecord>> {
+        self.inner.subscribe_changes()
+    }
+
+    async fn prune_changes(&self, cursor: u64) -> Result<usize> {
+        self.writable()?.prune_changes(cursor).await
+    }
+
+    async fn idempotency_record(&self, key: &str) -> Result<Option<IdempotencyRecord>> {
+        self.inner.idempotency_record(key).await
+    }
+
+    async fn store_idempotency_record(&self, key: &str, record: &IdempotencyRecord) -> Result<()> {
+        self.writable()?.store_idempotency_record(key, record).await
+    }
+
+    async fn store_checkpoint(&self, checkpoint: &SessionCheckpoint) -> Result<()> {
+        self.writable()?.store_checkpoint(checkpoint).await
+    }
+
+    async fn session_checkpoints(&self, session_id: &SessionId) -> Result<Vec<SessionCheckpoint>> {
+        self.inner.session_checkpoints(session_id).await
+    }
+
+    async fn store_node_version(&self, version: &NodeVersion) -> Result<()> {
+        self.writable()?.store_node_version(version).await
+    }
+
+    async fn node_versions(&self, node_id: &NodeId) -> Result<Vec<NodeVersion>> {
+        self.inner.node_versions(node_id).await
+    }
+
+    async fn node_version_ids(&self) -> Result<Vec<NodeId>> {
+        self.inner.node_version_ids().await
+    }
+
+    async fn usage_rollups(
+        &self,
+        granularity: UsageGranularity,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<UsageBucket>> {
+        self.inner.usage_rollups(granularity, from, to).await
+    }
+
+    async fn kv_get(&self, key: &str) -> Result<Option<KvEntry>> {
+        self.inner.kv_get(key).await
+    }
+
+    async fn kv_set(&self, entry: &KvEntry) -> Result<()> {
+        self.writable()?.kv_set(entry).await
+    }
+
+    async fn kv_delete(&self, key: &str) -> Result<bool> {
+        self.writable()?.kv_delete(key).await
+    }
+
+    async fn kv_list(&self, prefix: &str) -> Result<Vec<KvEntry>> {
+        self.inner.kv_list(prefix).await
+    }
+
+    async fn store_embedding(&self, embedding: &NodeEmbedding) -> Result<()> {
+        self.writable()?.store_embedding(embedding).await
+    }
+
+    async fn get_embedding(&self, node_id: &NodeId) -> Result<Option<NodeEmbedding>> {
+        self.inner.get_embedding(node_id).await
+    }
+
+    async fn all_embeddings(&self) -> Result<Vec<NodeEmbedding>> {
+        self.inner.all_embeddings().await
+    }
+
+    async fn set_alias(&self, alias: &str, target: AliasTarget) -> Result<Option<AliasTarget>> {
+        self.writable()?.set_alias(alias, target).await
+    }
+
+    async fn resolve_alias(&self, alias: &str) -> Result<Option<AliasTarget>> {
+        self.inner.resolve_alias(alias).await
+    }
+
+    async fn remove_alias(&self, alias: &str) -> Result<Option<AliasTarget>> {
+        self.writable()?.remove_alias(alias).await
+    }
+
+    async fn list_aliases(&self, prefix: &str) -> Result<Vec<(String, AliasTarget)>> {
+        self.inner.list_aliases(prefix).await
+    }
+
+    async fn list_views(&self) -> Result<Vec<ViewDefinition>> {
+        self.inner.list_views().await
+    }
+
+    async fn define_view(&self, view: &ViewDefinition) -> Result<bool> {
+        self.writable()?.define_view(view).await
+    }
+
+    async fn drop_view(&self, name: &str) -> Result<bool> {
+        self.writable()?.drop_view(name).await
+    }
+
+    async fn view_totals(&self, name: &str, group: &[String]) -> Result<Option<ViewTotals>> {
+        self.inner.view_totals(name, group).await
+    }
+
+    async fn view_rows(&self, name: &str) -> Result<Vec<ViewRow>> {
+        self.inner.view_rows(name).await
+    }
+
+    async fn rebuild_views(&self) -> Result<usize> {
+        self.writable()?.rebuild_views().await
+    }
+
+    async fn snapshot(&self) -> Result<SnapshotBackend> {
+        self.inner.snapshot().await
+    }
+
+    async fn get_outgoing_edges_page(
+        &self,
+        node_id: &NodeId,
+        cursor: Option<&EdgeId>,
+        limit: usize,
+    ) -> Result<EdgePage> {
+        self.inner
+            .get_outgoing_edges_page(node_id, cursor, limit)
+            .await
+    }
+
+    async fn get_incoming_edges_page(
+        &self,
+        node_id: &NodeId,
+        cursor: Option<&EdgeId>,
+        limit: usize,
+    ) -> Result<EdgePage> {
+        self.inner
+            .get_incoming_edges_page(node_id, cursor, limit)
+            .await
+    }
+
+    async fn node_degree(&self, node_id: &NodeId) -> Result<NodeDegree> {
+        self.inner.node_degree(node_id).await
+    }
+}
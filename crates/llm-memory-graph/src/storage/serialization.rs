@@ -1,33 +1,145 @@
 //! Serialization utilities for storage
+//!
+//! Nodes can be compressed after encoding, according to a
+//! [`CompressionPolicy`]. A compressed node starts with
+//! [`COMPRESSED_MARKER`], a byte that begins no JSON, MessagePack or bincode
+//! node, followed by a byte naming the algorithm, so compressed and plain
+//! nodes are told apart on read whatever the current policy.
 
+use crate::{CompressionAlgorithm, CompressionPolicy, Edge, Node};
 use crate::{Error, Result};
-use crate::{Edge, Node};
-
-/// Serialization format options
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum SerializationFormat {
-    /// JSON format (human-readable, slower)
-    Json,
-    /// MessagePack format (binary, faster)
-    MessagePack,
-    /// Bincode format (binary, fastest)
-    Bincode,
-}
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+pub use crate::SerializationFormat;
+
+/// First byte of a compressed node (reserved, never used, in MessagePack)
+const COMPRESSED_MARKER: u8 = 0xC1;
+
+/// Second byte of a node compressed with zstd
+const ZSTD_TAG: u8 = 1;
+
+/// Second byte of a node compressed with LZ4
+const LZ4_TAG: u8 = 2;
 
 /// Handles serialization and deserialization of graph entities
+#[derive(Clone)]
 pub struct Serializer {
     format: SerializationFormat,
+    compression: Option<Compression>,
+}
+
+/// Compression applied to nodes as they are written
+#[derive(Clone)]
+struct Compression {
+    policy: CompressionPolicy,
+    level: i32,
+    counters: Arc<CompressionCounters>,
+}
+
+#[derive(Default)]
+struct CompressionCounters {
+    nodes: AtomicU64,
+    bytes_before: AtomicU64,
+    bytes_after: AtomicU64,
+}
+
+/// How well nodes written since the database was opened compressed
+///
+/// Only nodes the policy selected, and that got smaller, are counted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CompressionStats {
+    /// Nodes stored compressed
+    pub nodes_compressed: u64,
+    /// Encoded size of those nodes before compression
+    pub bytes_before: u64,
+    /// Size of those nodes as stored
+    pub bytes_after: u64,
+}
+
+impl CompressionStats {
+    /// Stored size as a fraction of the original size, 1.0 if nothing was
+    /// compressed
+    pub fn ratio(&self) -> f64 {
+        if self.bytes_before == 0 {
+            1.0
+        } else {
+            self.bytes_after as f64 / self.bytes_before as f64
+        }
+    }
 }
 
 impl Serializer {
     /// Create a new serializer with the specified format
     #[must_use]
     pub const fn new(format: SerializationFormat) -> Self {
-        Self { format }
+        Self {
+            format,
+            compression: None,
+        }
+    }
+
+    /// Encoding used for nodes and edges
+    pub const fn format(&self) -> SerializationFormat {
+        self.format
+    }
+
+    /// Compress nodes selected by `policy` from now on
+    ///
+    /// `level` is the zstd level, 1 to 9; 0 turns compression off. LZ4 has
+    /// no levels.
+    #[must_use]
+    pub fn with_compression(mut self, policy: CompressionPolicy, level: u8) -> Self {
+        self.compression =
+            (policy.algorithm != CompressionAlgorithm::None && level > 0).then(|| Compression {
+                policy,
+                level: i32::from(level),
+                counters: Arc::default(),
+            });
+        self
     }
 
-    /// Serialize a node to bytes
+    /// Compression achieved so far
+    pub fn compression_stats(&self) -> CompressionStats {
+        self.compression
+            .as_ref()
+            .map(|compression| {
+                let counters = &compression.counters;
+                CompressionStats {
+                    nodes_compressed: counters.nodes.load(Ordering::Relaxed),
+                    bytes_before: counters.bytes_before.load(Ordering::Relaxed),
+                    bytes_after: counters.bytes_after.load(Ordering::Relaxed),
+                }
+            })
+            .unwrap_or_default()
+    }
+
+    /// Serialize a node to bytes, compressed if the policy selects it
     pub fn serialize_node(&self, node: &Node) -> Result<Vec<u8>> {
+        let bytes = self.encode_node(node)?;
+        match &self.compression {
+            Some(compression)
+                if compression
+                    .policy
+                    .applies_to(&node.node_type(), bytes.len()) =>
+            {
+                compression.compress(bytes)
+            }
+            _ => Ok(bytes),
+        }
+    }
+
+    /// Deserialize a node from bytes, compressed or not
+    pub fn deserialize_node(&self, bytes: &[u8]) -> Result<Node> {
+        match bytes {
+            [COMPRESSED_MARKER, tag, compressed @ ..] => {
+                self.decode_node(&decompress(*tag, compressed)?)
+            }
+            _ => self.decode_node(bytes),
+        }
+    }
+
+    fn encode_node(&self, node: &Node) -> Result<Vec<u8>> {
         match self.format {
             SerializationFormat::Json => {
                 serde_json::to_vec(node).map_err(|e| Error::SerializationError(e.to_string()))
@@ -36,13 +148,15 @@ impl Serializer {
                 rmp_serde::to_vec(node).map_err(|e| Error::SerializationError(e.to_string()))
             }
             SerializationFormat::Bincode => {
-                bincode::serialize(node).map_err(|e| Error::SerializationError(e.to_string()))
+                let bytes = bincode::serialize(node)
+                    .map_err(|e| Error::SerializationError(e.to_string()))?;
+                verify_bincode::<Node>(&bytes)?;
+                Ok(bytes)
             }
         }
     }
 
-    /// Deserialize a node from bytes
-    pub fn deserialize_node(&self, bytes: &[u8]) -> Result<Node> {
+    fn decode_node(&self, bytes: &[u8]) -> Result<Node> {
         match self.format {
             SerializationFormat::Json => {
                 serde_json::from_slice(bytes).map_err(|e| Error::SerializationError(e.to_string()))
@@ -66,7 +180,10 @@ impl Serializer {
                 rmp_serde::to_vec(edge).map_err(|e| Error::SerializationError(e.to_string()))
             }
             SerializationFormat::Bincode => {
-                bincode::serialize(edge).map_err(|e| Error::SerializationError(e.to_string()))
+                let bytes = bincode::serialize(edge)
+                    .map_err(|e| Error::SerializationError(e.to_string()))?;
+                verify_bincode::<Edge>(&bytes)?;
+                Ok(bytes)
             }
         }
     }
@@ -87,6 +204,63 @@ impl Serializer {
     }
 }
 
+impl Compression {
+    /// `bytes` with the compressed-node header, or unchanged if compression
+    /// does not make them smaller
+    fn compress(&self, bytes: Vec<u8>) -> Result<Vec<u8>> {
+        let (tag, compressed) = match self.policy.algorithm {
+            CompressionAlgorithm::None => return Ok(bytes),
+            CompressionAlgorithm::Zstd => (
+                ZSTD_TAG,
+                zstd::bulk::compress(&bytes, self.level)
+                    .map_err(|e| Error::SerializationError(format!("zstd: {e}")))?,
+            ),
+            CompressionAlgorithm::Lz4 => (LZ4_TAG, lz4_flex::compress_prepend_size(&bytes)),
+        };
+        if compressed.len() + 2 >= bytes.len() {
+            return Ok(bytes);
+        }
+
+        let mut stored = Vec::with_capacity(compressed.len() + 2);
+        stored.extend_from_slice(&[COMPRESSED_MARKER, tag]);
+        stored.extend_from_slice(&compressed);
+        self.counters.nodes.fetch_add(1, Ordering::Relaxed);
+        self.counters
+            .bytes_before
+            .fetch_add(bytes.len() as u64, Ordering::Relaxed);
+        self.counters
+            .bytes_after
+            .fetch_add(stored.len() as u64, Ordering::Relaxed);
+        Ok(stored)
+    }
+}
+
+/// Decompress the body of a compressed node
+fn decompress(tag: u8, compressed: &[u8]) -> Result<Vec<u8>> {
+    match tag {
+        ZSTD_TAG => zstd::stream::decode_all(compressed)
+            .map_err(|e| Error::SerializationError(format!("zstd: {e}"))),
+        LZ4_TAG => lz4_flex::decompress_size_prepended(compressed)
+            .map_err(|e| Error::SerializationError(format!("lz4: {e}"))),
+        other => Err(Error::SerializationError(format!(
+            "unknown compression tag {other}"
+        ))),
+    }
+}
+
+/// Check that bincode can read `bytes` back
+///
+/// Bincode cannot decode JSON values such as properties, tool parameters and
+/// custom payloads even though it encodes them, so records holding any are
+/// refused rather than written unreadable.
+fn verify_bincode<T: serde::de::DeserializeOwned>(bytes: &[u8]) -> Result<()> {
+    bincode::deserialize::<T>(bytes).map(|_| ()).map_err(|e| {
+        Error::SerializationError(format!(
+            "bincode cannot store this record ({e}); use JSON or MessagePack"
+        ))
+    })
+}
+
 impl Default for Serializer {
     fn default() -> Self {
         Self::new(SerializationFormat::MessagePack)
@@ -139,4 +313,49 @@ mod tests {
         assert_eq!(edge.id, deserialized.id);
         assert_eq!(edge.edge_type, deserialized.edge_type);
     }
+
+    #[test]
+    fn test_node_compression() {
+        use crate::{NodeType, ResponseNode, TokenUsage};
+
+        let prompt = Node::Prompt(PromptNode::new(SessionId::new(), "Short".to_string()));
+        let response = Node::Response(ResponseNode::new(
+            NodeId::new(),
+            "All work and no play. ".repeat(200),
+            TokenUsage::new(10, 1000),
+        ));
+
+        for algorithm in [CompressionAlgorithm::Zstd, CompressionAlgorithm::Lz4] {
+            let serializer = Serializer::default().with_compression(
+                CompressionPolicy::new(algorithm)
+                    .with_min_bytes(256)
+                    .with_node_type(NodeType::Response),
+                3,
+            );
+
+            let bytes = serializer.serialize_node(&response).unwrap();
+            assert_eq!(bytes[0], COMPRESSED_MARKER);
+            let restored = serializer.deserialize_node(&bytes).unwrap();
+            assert_eq!(restored.id(), response.id());
+            match (&restored, &response) {
+                (Node::Response(restored), Node::Response(original)) => {
+                    assert_eq!(restored.content, original.content);
+                }
+                _ => panic!("expected a response"),
+            }
+
+            // Small nodes and other types are stored as they are
+            let bytes = serializer.serialize_node(&prompt).unwrap();
+            assert_ne!(bytes[0], COMPRESSED_MARKER);
+
+            let stats = serializer.compression_stats();
+            assert_eq!(stats.nodes_compressed, 1);
+            assert!(stats.ratio() < 0.5);
+
+            // A serializer without compression still reads compressed nodes
+            let bytes = serializer.serialize_node(&response).unwrap();
+            let plain = Serializer::default().deserialize_node(&bytes).unwrap();
+            assert_eq!(plain.id(), response.id());
+        }
+    }
 }
@@ -1,81 +1,1959 @@
 //! Sled-based storage backend implementation
 
-use super::{SerializationFormat, Serializer, StorageBackend, StorageStats};
+use super::lock::DatabaseLock;
+use super::{
+    ColdTierReport, EdgePage, IdempotencyRecord, IndexRebuildReport, KvEntry, NodeDegree,
+    NodeEmbedding, NodeVersion, SerializationFormat, Serializer, SessionCheckpoint,
+    SnapshotBackend, StorageBackend, StorageStats, TrashedNode, TreeStats,
+};
+use crate::audit::{AuditEntry, AuditFilter};
+use crate::changes::{ChangeKind, ChangeRecord};
+use crate::deadline::Deadline;
+use crate::usage::{self, UsageBucket, UsageGranularity, UsageTotals};
+use crate::{
+    validate_alias, AliasTarget, ColdStorageConfig, CompressionPolicy, Config, ConversationSession,
+    Edge, EdgeId, Node, NodeId, PromptNode, ReadConsistency, SessionId, ViewDefinition, ViewRow,
+    ViewTotals,
+};
 use crate::{Error, Result};
-use crate::{Edge, EdgeId, Node, NodeId, SessionId};
-use sled::{Db, Tree};
+use chrono::{DateTime, Utc};
+use parking_lot::{Mutex, RwLock, RwLockReadGuard};
+use sha2::{Digest, Sha256};
+use sled::{Batch, Db, IVec, Tree};
+use std::collections::{HashMap, HashSet};
+use std::ops::{Bound, Range};
 use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
+
+/// Namespace used when none is given, stored under the original tree names
+pub const DEFAULT_NAMESPACE: &str = "default";
+
+/// Longest accepted namespace name
+pub const MAX_NAMESPACE_LEN: usize = 64;
+
+/// Prefix of the trees of every namespace other than the default one
+const NAMESPACE_TREE_PREFIX: &str = "ns/";
+
+/// Changes buffered for each subscriber of
+/// [`subscribe_changes`](SledBackend::subscribe_changes) before it lags
+const CHANGE_FEED_CAPACITY: usize = 1024;
+
+/// Marker in the content index recording that prompts stored before the
+/// index existed have been added to it
+const CONTENT_INDEX_READY: &[u8] = b"ready";
+
+/// Marker in the metrics tree recording that nodes stored before usage
+/// rollups existed have been counted
+const USAGE_ROLLUPS_READY: &[u8] = b"ready";
+
+/// Records [`tier_cold`](SledBackend::tier_cold) moves while holding writes
+/// off
+const COLD_TIER_CHUNK: usize = 1000;
+
+/// How long to keep retrying sled's own lock after taking the database lock,
+/// while a handle dropped by this process finishes closing in the background
+const SLED_LOCK_GRACE: Duration = Duration::from_secs(2);
+
+/// Check that `name` can be used as a namespace name
+///
+/// Names are 1 to [`MAX_NAMESPACE_LEN`] characters of lowercase ASCII letters,
+/// digits, `_`, `-` and `.`, starting with a letter or digit.
+///
+/// # Errors
+///
+/// Returns a validation error for any other name.
+pub fn validate_namespace(name: &str) -> Result<()> {
+    let valid = name.len() <= MAX_NAMESPACE_LEN
+        && name.starts_with(|c: char| c.is_ascii_lowercase() || c.is_ascii_digit())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '_' | '-' | '.'));
+    if valid {
+        Ok(())
+    } else {
+        Err(Error::ValidationError(format!(
+            "invalid namespace name '{name}'"
+        )))
+    }
+}
+
+/// Name of the tree holding `name` for `namespace`
+fn tree_name(namespace: &str, name: &str) -> String {
+    if namespace == DEFAULT_NAMESPACE {
+        name.to_string()
+    } else {
+        format!("{NAMESPACE_TREE_PREFIX}{namespace}/{name}")
+    }
+}
+
+/// Database of a cold tier kept apart from the main one
+#[derive(Clone)]
+struct ColdDatabase {
+    db: Db,
+    /// Held for as long as any namespace uses the cold database
+    _lock: Arc<DatabaseLock>,
+}
 
 /// Sled-based storage backend
 pub struct SledBackend {
+    namespace: String,
     db: Db,
     nodes: Tree,
     edges: Tree,
     session_index: Tree,
+    /// Prompts keyed by content hash, session and prompt ID
+    content_index: Tree,
     outgoing_edges_index: Tree,
     incoming_edges_index: Tree,
+    audit_log: Tree,
+    trash: Tree,
+    changes: Tree,
+    idempotency: Tree,
+    checkpoints: Tree,
+    /// Earlier versions of edited nodes, keyed by node and version number
+    versions: Tree,
+    /// Hourly and daily usage totals, keyed by granularity and bucket start
+    metrics: Tree,
+    /// External keys and what they refer to
+    aliases: Tree,
+    /// Embedder key-value entries
+    kv: Tree,
+    /// Embedding vectors keyed by node ID
+    embeddings: Tree,
+    /// Materialized view definitions keyed by name
+    view_definitions: Tree,
+    /// Totals of each group of each view
+    view_rows: Tree,
+    /// Group and contribution of each node counted by each view
+    view_members: Tree,
+    /// Nodes moved to the cold tier, see [`tier_cold`](Self::tier_cold)
+    cold_nodes: Tree,
+    /// Edges moved to the cold tier
+    cold_edges: Tree,
+    /// Separate database holding the cold trees, if they are not in `db`
+    cold_db: Option<ColdDatabase>,
+    /// Encodes nodes moved to the cold tier
+    cold_serializer: Serializer,
+    /// Views maintained on every write, as stored in `view_definitions`
+    views: RwLock<Vec<ViewDefinition>>,
     serializer: Serializer,
+    /// Whether mutations are appended to the change log
+    change_capture: AtomicBool,
+    /// Keeps change records in cursor order when writes race
+    change_lock: Mutex<()>,
+    /// Live change records for subscribers
+    change_feed: broadcast::Sender<ChangeRecord>,
+    /// Held shared by writes and exclusively while a snapshot is copied
+    write_gate: RwLock<()>,
+    /// Lock on the database directory, shared with other namespaces
+    lock: Arc<DatabaseLock>,
+    /// Writes made since the last flush
+    pending_writes: AtomicU64,
+    /// Duration of the most recent flush
+    last_flush: Mutex<Option<Duration>>,
 }
 
-impl SledBackend {
-    /// Open or create a new Sled backend at the specified path
-    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
-        let db = sled::open(path)?;
+impl SledBackend {
+    /// Open or create a new Sled backend at the specified path
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::open_namespace(path, DEFAULT_NAMESPACE)
+    }
+
+    /// Open or create one namespace of the database at `path`
+    ///
+    /// Each namespace is an independent graph stored in its own set of trees.
+    ///
+    /// # Errors
+    ///
+    /// Returns a validation error if the name is not a valid namespace name,
+    /// and [`Error::DatabaseLocked`] if another process has the database open.
+    pub fn open_namespace<P: AsRef<Path>>(path: P, namespace: &str) -> Result<Self> {
+        Self::open_namespace_waiting(path, namespace, None)
+    }
+
+    /// Open one namespace, waiting up to `wait` for another process to
+    /// release the database
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::DatabaseLocked`] if the database is still locked when
+    /// the wait runs out, or immediately when `wait` is `None`.
+    pub fn open_namespace_waiting<P: AsRef<Path>>(
+        path: P,
+        namespace: &str,
+        wait: Option<Duration>,
+    ) -> Result<Self> {
+        validate_namespace(namespace)?;
+        let path = path.as_ref();
+        let lock = DatabaseLock::acquire(path, wait)?;
+        let grace = Instant::now() + SLED_LOCK_GRACE;
+        let db = loop {
+            match sled::open(path) {
+                Err(sled::Error::Io(e))
+                    if e.kind() == std::io::ErrorKind::WouldBlock && Instant::now() < grace =>
+                {
+                    std::thread::sleep(Duration::from_millis(20));
+                }
+                result => break result?,
+            }
+        };
+        let backend = Self::with_db(db, namespace, Arc::new(lock))?;
+        backend.backfill_content_index()?;
+        backend.backfill_usage_rollups()?;
+        Ok(backend)
+    }
+
+    /// Open the namespace `config` names with its serialization format,
+    /// compression, cold tier and lock wait
+    ///
+    /// # Errors
+    ///
+    /// As [`open_namespace_waiting`](Self::open_namespace_waiting) and
+    /// [`with_cold_storage`](Self::with_cold_storage).
+    pub fn open_with_config(config: &Config) -> Result<Self> {
+        let namespace = config.namespace.as_deref().unwrap_or(DEFAULT_NAMESPACE);
+        let wait = config.wait_for_lock_ms.map(Duration::from_millis);
+        Self::open_namespace_waiting(&config.path, namespace, wait)?
+            .with_format(config.serialization_format)
+            .with_compression(config.compression.clone(), config.compression_level)
+            .with_cold_storage(&config.cold_storage)
+    }
+
+    /// Another namespace of the same database
+    ///
+    /// Use this rather than opening the path again to work with several
+    /// namespaces at once; sled allows one open handle per database.
+    pub fn in_namespace(&self, namespace: &str) -> Result<Self> {
+        validate_namespace(namespace)?;
+        let mut backend = Self::with_db(self.db.clone(), namespace, Arc::clone(&self.lock))?;
+        backend.serializer = self.serializer.clone();
+        backend.cold_serializer = self.cold_serializer.clone();
+        if let Some(cold) = &self.cold_db {
+            backend.cold_nodes = cold.db.open_tree(tree_name(namespace, "cold_nodes"))?;
+            backend.cold_edges = cold.db.open_tree(tree_name(namespace, "cold_edges"))?;
+            backend.cold_db = Some(cold.clone());
+        }
+        backend.backfill_content_index()?;
+        backend.backfill_usage_rollups()?;
+        Ok(backend)
+    }
+
+    fn with_db(db: Db, namespace: &str, lock: Arc<DatabaseLock>) -> Result<Self> {
+        let tree = |name: &str| db.open_tree(tree_name(namespace, name));
+
+        let nodes = tree("nodes")?;
+        let edges = tree("edges")?;
+        let session_index = tree("session_index")?;
+        let content_index = tree("content_index")?;
+        let outgoing_edges_index = tree("outgoing_edges")?;
+        let incoming_edges_index = tree("incoming_edges")?;
+        let audit_log = tree("audit_log")?;
+        let trash = tree("trash")?;
+        let changes = tree("changes")?;
+        let idempotency = tree("idempotency")?;
+        let checkpoints = tree("checkpoints")?;
+        let versions = tree("versions")?;
+        let metrics = tree("metrics")?;
+        let aliases = tree("aliases")?;
+        let kv = tree("kv")?;
+        let embeddings = tree("embeddings")?;
+        let view_definitions = tree("view_definitions")?;
+        let view_rows = tree("view_rows")?;
+        let view_members = tree("view_members")?;
+        let cold_nodes = tree("cold_nodes")?;
+        let cold_edges = tree("cold_edges")?;
+        let views = view_definitions
+            .iter()
+            .values()
+            .map(|bytes| {
+                serde_json::from_slice(&bytes?)
+                    .map_err(|e| Error::DeserializationError(e.to_string()))
+            })
+            .collect::<Result<Vec<ViewDefinition>>>()?;
+
+        Ok(Self {
+            namespace: namespace.to_string(),
+            db,
+            nodes,
+            edges,
+            session_index,
+            content_index,
+            outgoing_edges_index,
+            incoming_edges_index,
+            audit_log,
+            trash,
+            changes,
+            idempotency,
+            checkpoints,
+            versions,
+            metrics,
+            aliases,
+            kv,
+            embeddings,
+            view_definitions,
+            view_rows,
+            view_members,
+            cold_nodes,
+            cold_edges,
+            cold_db: None,
+            cold_serializer: Serializer::new(SerializationFormat::MessagePack),
+            views: RwLock::new(views),
+            serializer: Serializer::new(SerializationFormat::MessagePack),
+            change_capture: AtomicBool::new(false),
+            change_lock: Mutex::new(()),
+            change_feed: broadcast::channel(CHANGE_FEED_CAPACITY).0,
+            write_gate: RwLock::new(()),
+            lock,
+            pending_writes: AtomicU64::new(0),
+            last_flush: Mutex::new(None),
+        })
+    }
+
+    /// Add prompts written before the content index existed to it
+    ///
+    /// Runs once per database: the scan is skipped when the index is marked
+    /// ready.
+    fn backfill_content_index(&self) -> Result<()> {
+        if self.content_index.contains_key(CONTENT_INDEX_READY)? {
+            return Ok(());
+        }
+        let mut batch = Batch::default();
+        for result in self.node_records() {
+            let (_, bytes) = result?;
+            if let Ok(Node::Prompt(prompt)) = self.serializer.deserialize_node(&bytes) {
+                batch.insert(Self::content_key(&prompt), &[]);
+            }
+        }
+        batch.insert(CONTENT_INDEX_READY, &[]);
+        self.content_index.apply_batch(batch)?;
+        Ok(())
+    }
+
+    /// Count nodes written before usage rollups existed, and those in the
+    /// trash
+    ///
+    /// Runs once per database: the scan is skipped when the rollups are
+    /// marked ready.
+    fn backfill_usage_rollups(&self) -> Result<()> {
+        if self.metrics.contains_key(USAGE_ROLLUPS_READY)? {
+            return Ok(());
+        }
+        self.build_usage_rollups()?;
+        Ok(())
+    }
+
+    /// Recount the usage rollups from the node tree and the trash, returning
+    /// the number of buckets written
+    fn build_usage_rollups(&self) -> Result<usize> {
+        let mut buckets: HashMap<[u8; 9], UsageTotals> = HashMap::new();
+        let mut count = |node: &Node| {
+            let totals = UsageTotals::of(node);
+            for granularity in UsageGranularity::ALL {
+                let start = granularity.bucket_start(node.created_at());
+                buckets
+                    .entry(usage::bucket_key(granularity, start))
+                    .or_default()
+                    .add(&totals);
+            }
+        };
+        for result in self.node_records() {
+            let (_, bytes) = result?;
+            count(&self.serializer.deserialize_node(&bytes)?);
+        }
+        for trashed in self.trashed_nodes()? {
+            count(&trashed.node);
+        }
+
+        let mut batch = Batch::default();
+        for (key, totals) in &buckets {
+            batch.insert(key, serde_json::to_vec(totals)?);
+        }
+        batch.insert(USAGE_ROLLUPS_READY, &[]);
+        self.metrics.clear()?;
+        self.metrics.apply_batch(batch)?;
+        Ok(buckets.len())
+    }
+
+    /// Replace `previous`'s contribution to the usage rollups with `node`'s
+    ///
+    /// Buckets are updated atomically so concurrent writes do not lose
+    /// counts.
+    fn update_usage(&self, previous: Option<&Node>, node: &Node) -> Result<()> {
+        let totals = UsageTotals::of(node);
+        let previous = previous.map(|previous| (previous.created_at(), UsageTotals::of(previous)));
+        for granularity in UsageGranularity::ALL {
+            if let Some((at, previous)) = &previous {
+                let key = usage::bucket_key(granularity, granularity.bucket_start(*at));
+                self.adjust_usage_bucket(&key, |current| current.subtract(previous))?;
+            }
+            let key = usage::bucket_key(granularity, granularity.bucket_start(node.created_at()));
+            self.adjust_usage_bucket(&key, |current| current.add(&totals))?;
+        }
+        Ok(())
+    }
+
+    fn adjust_usage_bucket(&self, key: &[u8], adjust: impl Fn(&mut UsageTotals)) -> Result<()> {
+        self.metrics.fetch_and_update(key, |current| {
+            let mut totals: UsageTotals = current
+                .and_then(|bytes| serde_json::from_slice(bytes).ok())
+                .unwrap_or_default();
+            adjust(&mut totals);
+            (!totals.is_empty())
+                .then(|| serde_json::to_vec(&totals).ok())
+                .flatten()
+        })?;
+        Ok(())
+    }
+
+    /// Stored usage buckets of `granularity` starting in `from..to`, oldest
+    /// first; buckets with no usage are not stored
+    pub fn usage_rollups(
+        &self,
+        granularity: UsageGranularity,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<UsageBucket>> {
+        if from >= to {
+            return Ok(Vec::new());
+        }
+        self.metrics
+            .range(usage::bucket_key(granularity, from)..usage::bucket_key(granularity, to))
+            .map(|result| {
+                let (key, bytes) = result?;
+                let start = usage::bucket_start_of(&key).ok_or_else(|| {
+                    Error::corruption(
+                        String::from_utf8_lossy(&self.metrics.name()),
+                        &key,
+                        "malformed bucket key",
+                    )
+                })?;
+                let totals = serde_json::from_slice(&bytes)
+                    .map_err(|e| Error::DeserializationError(e.to_string()))?;
+                Ok(UsageBucket { start, totals })
+            })
+            .collect()
+    }
+
+    /// Namespace this backend reads and writes
+    pub fn namespace(&self) -> &str {
+        &self.namespace
+    }
+
+    /// Every namespace in the database, sorted, always including
+    /// [`DEFAULT_NAMESPACE`]
+    pub fn list_namespaces(&self) -> Vec<String> {
+        let mut namespaces: Vec<String> = self
+            .db
+            .tree_names()
+            .iter()
+            .filter_map(|name| {
+                let name = std::str::from_utf8(name).ok()?;
+                let rest = name.strip_prefix(NAMESPACE_TREE_PREFIX)?;
+                rest.split_once('/')
+                    .map(|(namespace, _)| namespace.to_string())
+            })
+            .collect();
+        namespaces.push(DEFAULT_NAMESPACE.to_string());
+        namespaces.sort();
+        namespaces.dedup();
+        namespaces
+    }
+
+    /// Delete a namespace and everything in it
+    ///
+    /// Returns `false` if the namespace did not exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns a validation error for the default namespace and for the
+    /// namespace this backend is using.
+    pub fn drop_namespace(&self, namespace: &str) -> Result<bool> {
+        validate_namespace(namespace)?;
+        if namespace == DEFAULT_NAMESPACE || namespace == self.namespace {
+            return Err(Error::ValidationError(format!(
+                "namespace '{namespace}' is in use and cannot be dropped"
+            )));
+        }
+
+        let prefix = format!("{NAMESPACE_TREE_PREFIX}{namespace}/");
+        let mut dropped = false;
+        for name in self.db.tree_names() {
+            if name.starts_with(prefix.as_bytes()) {
+                dropped |= self.db.drop_tree(&name)?;
+            }
+        }
+        self.db.flush()?;
+        Ok(dropped)
+    }
+
+    /// Open with a custom serialization format
+    pub fn open_with_format<P: AsRef<Path>>(path: P, format: SerializationFormat) -> Result<Self> {
+        Ok(Self::open(path)?.with_format(format))
+    }
+
+    /// Encode nodes and edges written from now on with `format`
+    ///
+    /// Data already stored must use the same format to remain readable.
+    #[must_use]
+    pub fn with_format(mut self, format: SerializationFormat) -> Self {
+        self.serializer = Serializer::new(format);
+        self.cold_serializer = Serializer::new(format);
+        self
+    }
+
+    /// Compress nodes selected by `policy` when they are written; see
+    /// [`Serializer::with_compression`]
+    ///
+    /// Nodes already stored, compressed or not, stay readable. Set the
+    /// format first: [`with_format`](Self::with_format) resets compression.
+    #[must_use]
+    pub fn with_compression(mut self, policy: CompressionPolicy, level: u8) -> Self {
+        self.serializer = self.serializer.with_compression(policy, level);
+        self
+    }
+
+    /// Store the cold tier as `config` describes
+    ///
+    /// Without a path the cold trees stay in this database; with one they
+    /// move to a database of their own there, taking along anything already
+    /// moved to the cold tier of this database. Dropping the path later
+    /// leaves that database unread. Set the format first:
+    /// [`with_format`](Self::with_format) resets cold compression.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::DatabaseLocked`] if another process has the cold
+    /// database open, or an error if it cannot be opened.
+    pub fn with_cold_storage(mut self, config: &ColdStorageConfig) -> Result<Self> {
+        let policy = CompressionPolicy::new(config.compression).with_min_bytes(0);
+        self.cold_serializer = Serializer::new(self.serializer.format())
+            .with_compression(policy, config.compression_level);
+        let Some(path) = &config.path else {
+            return Ok(self);
+        };
+
+        let lock = Arc::new(DatabaseLock::acquire(path, None)?);
+        let db = sled::open(path)?;
+        let cold_nodes = db.open_tree(tree_name(&self.namespace, "cold_nodes"))?;
+        let cold_edges = db.open_tree(tree_name(&self.namespace, "cold_edges"))?;
+        for (from, to) in [
+            (&self.cold_nodes, &cold_nodes),
+            (&self.cold_edges, &cold_edges),
+        ] {
+            if from.is_empty() {
+                continue;
+            }
+            let mut batch = Batch::default();
+            for result in from.iter() {
+                let (key, bytes) = result?;
+                batch.insert(key, bytes);
+            }
+            to.apply_batch(batch)?;
+            db.flush()?;
+            from.clear()?;
+        }
+        self.cold_nodes = cold_nodes;
+        self.cold_edges = cold_edges;
+        self.cold_db = Some(ColdDatabase { db, _lock: lock });
+        Ok(self)
+    }
+
+    /// Move nodes and edges created before `cutoff` from the hot trees to the
+    /// cold ones, re-encoding nodes with the cold tier's compression
+    ///
+    /// Records are written to the cold tier before they leave the hot one, so
+    /// an interrupted move never loses data; a record briefly in both tiers
+    /// is read from the hot one.
+    pub fn tier_cold(&self, cutoff: DateTime<Utc>) -> Result<ColdTierReport> {
+        let nodes_moved = self.tier_tree(&self.nodes, &self.cold_nodes, |bytes| {
+            let node = self.serializer.deserialize_node(bytes)?;
+            if node.created_at() < cutoff {
+                Ok(Some(self.cold_serializer.serialize_node(&node)?))
+            } else {
+                Ok(None)
+            }
+        })?;
+        let edges_moved = self.tier_tree(&self.edges, &self.cold_edges, |bytes| {
+            let edge = self.serializer.deserialize_edge(bytes)?;
+            Ok((edge.created_at < cutoff).then(|| bytes.to_vec()))
+        })?;
+        self.db.flush()?;
+        Ok(ColdTierReport {
+            nodes_moved,
+            edges_moved,
+        })
+    }
+
+    /// Move the records of `hot` that `cold_copy` encodes for the cold tier
+    /// to `cold`, [`COLD_TIER_CHUNK`] records at a time
+    ///
+    /// Each chunk holds the write gate exclusively, so no write can land
+    /// between a record's copy and its removal from the hot tier, while
+    /// writers wait for at most one chunk.
+    fn tier_tree(
+        &self,
+        hot: &Tree,
+        cold: &Tree,
+        cold_copy: impl Fn(&[u8]) -> Result<Option<Vec<u8>>>,
+    ) -> Result<usize> {
+        let mut moved = 0;
+        let mut after: Option<IVec> = None;
+        loop {
+            let _gate = self.write_gate.write();
+            let records = match &after {
+                Some(key) => {
+                    hot.range::<&[u8], _>((Bound::Excluded(key.as_ref()), Bound::Unbounded))
+                }
+                None => hot.iter(),
+            };
+            let mut cold_batch = Batch::default();
+            let mut hot_batch = Batch::default();
+            let mut scanned = 0;
+            for result in records.take(COLD_TIER_CHUNK) {
+                let (key, bytes) = result?;
+                if let Some(copy) = cold_copy(&bytes)? {
+                    cold_batch.insert(&key, copy);
+                    hot_batch.remove(&key);
+                    moved += 1;
+                }
+                after = Some(key);
+                scanned += 1;
+            }
+            cold.apply_batch(cold_batch)?;
+            self.flush_cold()?;
+            hot.apply_batch(hot_batch)?;
+            if scanned < COLD_TIER_CHUNK {
+                return Ok(moved);
+            }
+        }
+    }
+
+    /// Flush the cold database, if the cold tier has one of its own
+    fn flush_cold(&self) -> Result<()> {
+        if let Some(cold) = &self.cold_db {
+            cold.db.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Every stored node, hot tier first, skipping cold copies of nodes an
+    /// interrupted move left in both tiers
+    fn node_records(&self) -> impl Iterator<Item = sled::Result<(IVec, IVec)>> + '_ {
+        Self::tier_records(&self.nodes, &self.cold_nodes)
+    }
+
+    /// Every stored edge, as [`node_records`](Self::node_records)
+    fn edge_records(&self) -> impl Iterator<Item = sled::Result<(IVec, IVec)>> + '_ {
+        Self::tier_records(&self.edges, &self.cold_edges)
+    }
+
+    fn tier_records<'a>(
+        hot: &'a Tree,
+        cold: &'a Tree,
+    ) -> impl Iterator<Item = sled::Result<(IVec, IVec)>> + 'a {
+        hot.iter().chain(cold.iter().filter(move |result| {
+            result
+                .as_ref()
+                .map_or(true, |(key, _)| !hot.contains_key(key).unwrap_or(false))
+        }))
+    }
+
+    /// Whether a node with ID bytes `key` is stored in either tier
+    fn contains_node(&self, key: &[u8]) -> Result<bool> {
+        Ok(self.nodes.contains_key(key)? || self.cold_nodes.contains_key(key)?)
+    }
+
+    /// Whether an edge with ID bytes `key` is stored in either tier
+    fn contains_edge(&self, key: &[u8]) -> Result<bool> {
+        Ok(self.edges.contains_key(key)? || self.cold_edges.contains_key(key)?)
+    }
+
+    /// Build a composite key for indexing
+    fn build_index_key(prefix: &[u8], id: &[u8]) -> Vec<u8> {
+        let mut key = Vec::with_capacity(prefix.len() + id.len());
+        key.extend_from_slice(prefix);
+        key.extend_from_slice(id);
+        key
+    }
+
+    /// Key of a prompt in the content index: the SHA-256 of its content,
+    /// then its session and its ID
+    fn content_key(prompt: &PromptNode) -> Vec<u8> {
+        let hash = Sha256::digest(prompt.content.as_bytes());
+        let prefix = Self::build_index_key(&hash, &prompt.session_id.to_bytes());
+        Self::build_index_key(&prefix, &prompt.id.to_bytes())
+    }
+
+    /// Prompts whose content is exactly `content`, oldest first, optionally
+    /// limited to one session
+    ///
+    /// Looks the content hash up in the content index, so the cost depends
+    /// on the number of matches rather than the size of the session or graph.
+    /// Entries left behind by deletes and edits are skipped.
+    pub fn find_prompts_by_content(
+        &self,
+        content: &str,
+        session_id: Option<&SessionId>,
+    ) -> Result<Vec<PromptNode>> {
+        let hash = Sha256::digest(content.as_bytes());
+        let prefix = match session_id {
+            Some(session_id) => Self::build_index_key(&hash, &session_id.to_bytes()),
+            None => hash.to_vec(),
+        };
+
+        let mut prompts = Vec::new();
+        for result in self.content_index.scan_prefix(prefix) {
+            let (key, _) = result?;
+            if key.len() < 64 {
+                continue;
+            }
+            let node_id = NodeId::from_bytes(Self::index_id(&self.content_index, &key, 48..64)?);
+            let session_id =
+                SessionId::from_bytes(Self::index_id(&self.content_index, &key, 32..48)?);
+            if let Some(Node::Prompt(prompt)) = self.get_node(&node_id)? {
+                if prompt.content == content && prompt.session_id == session_id {
+                    prompts.push(prompt);
+                }
+            }
+        }
+        prompts.sort_by_key(|prompt| prompt.timestamp);
+        Ok(prompts)
+    }
+
+    /// Start or stop appending mutations to the change log
+    pub fn set_change_capture(&self, enable: bool) {
+        self.change_capture.store(enable, Ordering::Relaxed);
+    }
+
+    /// Append a record to the change log if capture is enabled, assigning
+    /// its cursor
+    ///
+    /// Records are stored as JSON, like audit entries.
+    ///
+    /// The record is also sent to subscribers of
+    /// [`subscribe_changes`](Self::subscribe_changes), with a cursor of 0
+    /// when capture is disabled.
+    fn record_change(&self, record: impl FnOnce() -> ChangeRecord) -> Result<()> {
+        let capture = self.change_capture.load(Ordering::Relaxed);
+        if !capture && self.change_feed.receiver_count() == 0 {
+            return Ok(());
+        }
+        let mut record = record();
+        // Assign and insert under one lock so readers never see a later
+        // cursor before an earlier one
+        let _order = self.change_lock.lock();
+        if capture {
+            record.cursor = self.db.generate_id()?;
+            self.changes
+                .insert(record.cursor.to_be_bytes(), serde_json::to_vec(&record)?)?;
+        }
+        // Fails only when every subscriber has gone away
+        let _ = self.change_feed.send(record);
+        Ok(())
+    }
+
+    /// Receive a record of every node and edge mutation from now on
+    ///
+    /// Unlike the change log, this works whether or not changes are
+    /// captured, and records are not persisted: a subscriber that falls more
+    /// than 1,024 records behind misses the oldest ones and is told how many
+    /// by [`broadcast::error::RecvError::Lagged`].
+    pub fn subscribe_changes(&self) -> broadcast::Receiver<ChangeRecord> {
+        self.change_feed.subscribe()
+    }
+
+    /// Whether mutations currently produce change records
+    fn tracks_changes(&self) -> bool {
+        self.change_capture.load(Ordering::Relaxed) || self.change_feed.receiver_count() > 0
+    }
+
+    /// Change kind of a write, given whether the key already existed
+    fn write_kind(existed: bool) -> ChangeKind {
+        if existed {
+            ChangeKind::Updated
+        } else {
+            ChangeKind::Created
+        }
+    }
+
+    /// Which of `keys` are present in `tree`, or all `false` when no change
+    /// records are produced
+    fn existing_keys(
+        &self,
+        [hot, cold]: [&Tree; 2],
+        keys: impl Iterator<Item = [u8; 16]>,
+    ) -> Result<Vec<bool>> {
+        let tracked = self.tracks_changes();
+        keys.map(|key| Ok(tracked && (hot.contains_key(key)? || cold.contains_key(key)?)))
+            .collect()
+    }
+
+    /// Up to `limit` change records after `cursor`, oldest first
+    ///
+    /// Pass `None` to start from the beginning of the log.
+    pub fn changes_since(&self, cursor: Option<u64>, limit: usize) -> Result<Vec<ChangeRecord>> {
+        let start = match cursor {
+            Some(cursor) => Bound::Excluded(cursor.to_be_bytes()),
+            None => Bound::Unbounded,
+        };
+        self.changes
+            .range((start, Bound::Unbounded))
+            .take(limit)
+            .map(|result| {
+                let (_, bytes) = result?;
+                serde_json::from_slice(&bytes)
+                    .map_err(|e| Error::DeserializationError(e.to_string()))
+            })
+            .collect()
+    }
+
+    /// Cursor of the most recent change record, if any
+    pub fn latest_change_cursor(&self) -> Result<Option<u64>> {
+        Ok(match self.changes.last()? {
+            Some((key, _)) => {
+                Some(u64::from_be_bytes(key.as_ref().try_into().map_err(
+                    |_| Error::Storage("Invalid change cursor".to_string()),
+                )?))
+            }
+            None => None,
+        })
+    }
+
+    /// Remove change records up to and including `cursor`
+    ///
+    /// Returns the number of records removed.
+    pub fn prune_changes(&self, cursor: u64) -> Result<usize> {
+        let mut removed = 0;
+        for result in self.changes.range(..=cursor.to_be_bytes()) {
+            let (key, _) = result?;
+            self.changes.remove(key)?;
+            removed += 1;
+        }
+        self.db.flush()?;
+        Ok(removed)
+    }
+
+    /// Hold off snapshots for the duration of a write
+    ///
+    /// Recursive so writes that call other writes, such as
+    /// [`restore_node`](Self::restore_node), cannot deadlock behind a waiting
+    /// snapshot.
+    fn write_guard(&self) -> RwLockReadGuard<'_, ()> {
+        self.pending_writes.fetch_add(1, Ordering::Relaxed);
+        self.write_gate.read_recursive()
+    }
+
+    /// Copy the node and edge trees and their indexes into a read-only
+    /// [`SnapshotBackend`]
+    ///
+    /// Writes wait only while the raw records are collected, so the snapshot
+    /// reflects every write that finished before it and none that started
+    /// after; decoding happens once writes have resumed. The audit log and
+    /// trash are not copied; the snapshot records the latest change cursor so
+    /// a reader can continue from the change log.
+    pub fn snapshot(&self) -> Result<SnapshotBackend> {
+        let (node_records, edge_records, index_keys, change_cursor) = {
+            let _gate = self.write_gate.write();
+            let node_records = self
+                .node_records()
+                .map(|result| result.map(|(_, bytes)| bytes))
+                .collect::<sled::Result<Vec<IVec>>>()?;
+            let edge_records = self
+                .edge_records()
+                .map(|result| result.map(|(_, bytes)| bytes))
+                .collect::<sled::Result<Vec<IVec>>>()?;
+            let index_keys = self
+                .session_index
+                .iter()
+                .keys()
+                .collect::<sled::Result<Vec<IVec>>>()?;
+            (
+                node_records,
+                edge_records,
+                index_keys,
+                self.latest_change_cursor()?,
+            )
+        };
+
+        let nodes = node_records
+            .iter()
+            .map(|bytes| self.serializer.deserialize_node(bytes))
+            .collect::<Result<Vec<_>>>()?;
+        let edges = edge_records
+            .iter()
+            .map(|bytes| self.serializer.deserialize_edge(bytes))
+            .collect::<Result<Vec<_>>>()?;
+        let mut session_index = Vec::with_capacity(index_keys.len());
+        for key in index_keys.iter().filter(|key| key.len() >= 32) {
+            let session_bytes: [u8; 16] = Self::index_id(&self.session_index, key, 0..16)?;
+            let node_id_bytes: [u8; 16] = Self::index_id(&self.session_index, key, 16..32)?;
+            session_index.push((
+                SessionId::from_bytes(session_bytes),
+                NodeId::from_bytes(node_id_bytes),
+            ));
+        }
+
+        // Counted from the copy, so they agree with what the snapshot holds
+        let mut stats = self.stats()?;
+        stats.node_count = nodes.len() as u64;
+        stats.edge_count = edges.len() as u64;
+        stats.session_count = session_index.chunk_by(|a, b| a.0 == b.0).count() as u64;
+
+        Ok(SnapshotBackend::new(
+            nodes,
+            edges,
+            session_index,
+            stats,
+            change_cursor,
+        ))
+    }
+
+    /// Append an entry to the audit log, assigning its sequence number
+    ///
+    /// Entries are keyed by a monotonically increasing id and stored as JSON,
+    /// independent of the node serialization format, so exported logs stay
+    /// readable across format changes.
+    pub fn append_audit_entry(&self, mut entry: AuditEntry) -> Result<AuditEntry> {
+        entry.sequence = self.db.generate_id()?;
+        let bytes = serde_json::to_vec(&entry)?;
+        self.audit_log.insert(entry.sequence.to_be_bytes(), bytes)?;
+        Ok(entry)
+    }
+
+    /// Audit entries matching `filter`, oldest first
+    pub fn audit_entries(&self, filter: &AuditFilter) -> Result<Vec<AuditEntry>> {
+        let mut entries = Vec::new();
+        for result in self.audit_log.iter() {
+            if filter.limit.is_some_and(|limit| entries.len() >= limit) {
+                break;
+            }
+            let (_, bytes) = result?;
+            let entry: AuditEntry = serde_json::from_slice(&bytes)
+                .map_err(|e| Error::DeserializationError(e.to_string()))?;
+            if filter.matches(&entry) {
+                entries.push(entry);
+            }
+        }
+        Ok(entries)
+    }
+
+    /// Move a node to the trash tree
+    ///
+    /// The node is removed from the node tree and the session index, so reads
+    /// and session listings no longer see it. Its edges are left in place.
+    pub fn trash_node(&self, id: &NodeId) -> Result<Option<TrashedNode>> {
+        let _gate = self.write_guard();
+        let Some(node) = self.get_node(id)? else {
+            return Ok(None);
+        };
+        let session_id = self.session_of(&node)?;
+        let trashed = TrashedNode {
+            node,
+            session_id,
+            deleted_at: Utc::now(),
+        };
+
+        // Write the trash entry first so a crash never loses the node
+        self.trash
+            .insert(id.to_bytes(), serde_json::to_vec(&trashed)?)?;
+        self.nodes.remove(id.to_bytes())?;
+        self.cold_nodes.remove(id.to_bytes())?;
+        if let Some(session_id) = session_id {
+            let key = Self::build_index_key(&session_id.to_bytes(), &id.to_bytes());
+            self.session_index.remove(key)?;
+        }
+        self.update_views(id, None)?;
+        self.record_change(|| ChangeRecord::node_deleted(*id))?;
+
+        self.flush_cold()?;
+        self.db.flush()?;
+        Ok(Some(trashed))
+    }
+
+    /// Move a node from the trash back into the node tree
+    pub fn restore_node(&self, id: &NodeId) -> Result<Option<Node>> {
+        let _gate = self.write_guard();
+        let Some(trashed) = self.get_trashed(id)? else {
+            return Ok(None);
+        };
+
+        self.store_node(&trashed.node)?;
+        // A response whose prompt is still trashed is not re-indexed by
+        // store_node, so restore the index entry explicitly
+        if let Some(session_id) = trashed.session_id {
+            let key = Self::build_index_key(&session_id.to_bytes(), &id.to_bytes());
+            self.session_index.insert(key, &[])?;
+        }
+        self.trash.remove(id.to_bytes())?;
+
+        self.db.flush()?;
+        Ok(Some(trashed.node))
+    }
+
+    /// Every node in the graph, in both tiers, excluding the trash
+    pub fn all_nodes(&self) -> Result<Vec<Node>> {
+        self.node_records()
+            .map(|result| {
+                let (_, bytes) = result?;
+                self.serializer.deserialize_node(&bytes)
+            })
+            .collect()
+    }
+
+    /// Every edge in the graph, in both tiers
+    pub fn all_edges(&self) -> Result<Vec<Edge>> {
+        self.edge_records()
+            .map(|result| {
+                let (_, bytes) = result?;
+                self.serializer.deserialize_edge(&bytes)
+            })
+            .collect()
+    }
+
+    /// Every node in the trash
+    pub fn trashed_nodes(&self) -> Result<Vec<TrashedNode>> {
+        self.trash
+            .iter()
+            .map(|result| {
+                let (_, bytes) = result?;
+                Self::decode_trashed(&bytes)
+            })
+            .collect()
+    }
+
+    /// Permanently remove nodes trashed before `cutoff` and every edge
+    /// touching them, except nodes of the sessions in `keep`
+    pub fn purge_trash(
+        &self,
+        cutoff: DateTime<Utc>,
+        keep: &HashSet<SessionId>,
+    ) -> Result<Vec<NodeId>> {
+        let _gate = self.write_guard();
+        let mut purged = Vec::new();
+
+        for trashed in self.trashed_nodes()? {
+            if trashed.deleted_at >= cutoff
+                || trashed.session_id.is_some_and(|id| keep.contains(&id))
+            {
+                continue;
+            }
+            let id = trashed.node.id();
+
+            // The node's earlier versions go with it
+            let versions = self.node_versions(&id)?;
+            let mut edge_ids = Vec::new();
+            for endpoint in std::iter::once(id).chain(versions.iter().map(|version| version.id)) {
+                for index in [&self.outgoing_edges_index, &self.incoming_edges_index] {
+                    for result in index.scan_prefix(endpoint.to_bytes()) {
+                        let (key, _) = result?;
+                        if key.len() >= 32 {
+                            let edge_id_bytes: [u8; 16] = Self::index_id(index, &key, 16..32)?;
+                            edge_ids.push(EdgeId::from_bytes(edge_id_bytes));
+                        }
+                    }
+                }
+            }
+            for edge_id in edge_ids {
+                self.remove_edge_and_indexes(&edge_id)?;
+            }
+            for version in &versions {
+                self.versions.remove(Self::build_index_key(
+                    &id.to_bytes(),
+                    &version.version.to_be_bytes(),
+                ))?;
+            }
+
+            self.trash.remove(id.to_bytes())?;
+            self.embeddings.remove(id.to_bytes())?;
+            purged.push(id);
+        }
+
+        self.flush_cold()?;
+        self.db.flush()?;
+        Ok(purged)
+    }
+
+    /// Every session in the graph
+    ///
+    /// Walks the session index and loads each indexed node, so the cost grows
+    /// with the number of prompts and responses as well as sessions.
+    pub fn list_sessions(&self) -> Result<Vec<ConversationSession>> {
+        let mut sessions = Vec::new();
+        for result in self.session_index.iter() {
+            let (key, _) = result?;
+            if key.len() < 32 {
+                continue;
+            }
+            let node_id_bytes: [u8; 16] = Self::index_id(&self.session_index, &key, 16..32)?;
+            if let Some(Node::Session(session)) =
+                self.get_node(&NodeId::from_bytes(node_id_bytes))?
+            {
+                sessions.push(session);
+            }
+        }
+        Ok(sessions)
+    }
+
+    /// Move every node indexed under session `source`, except the session
+    /// node itself, to session `target`
+    ///
+    /// Prompts and session-scoped custom nodes are rewritten with `target` as
+    /// their session; responses keep pointing at their prompt. Index entries
+    /// are re-keyed in one batch. Returns the IDs of the moved nodes.
+    pub fn move_session_nodes(
+        &self,
+        source: &SessionId,
+        target: &SessionId,
+    ) -> Result<Vec<NodeId>> {
+        let _gate = self.write_guard();
+        let mut node_batch = Batch::default();
+        let mut index_batch = Batch::default();
+        let mut content_batch = Batch::default();
+        let mut rewritten = Vec::new();
+        let mut moved = Vec::new();
+
+        for result in self.session_index.scan_prefix(source.to_bytes()) {
+            let (key, _) = result?;
+            if key.len() < 32 {
+                continue;
+            }
+            let node_id = NodeId::from_bytes(Self::index_id(&self.session_index, &key, 16..32)?);
+            let Some(mut node) = self.get_node(&node_id)? else {
+                // Left behind by a hard delete
+                index_batch.remove(key);
+                continue;
+            };
+            match &mut node {
+                Node::Session(session) if session.id == *source => continue,
+                Node::Prompt(prompt) => {
+                    content_batch.remove(Self::content_key(prompt));
+                    prompt.session_id = *target;
+                    content_batch.insert(Self::content_key(prompt), &[]);
+                }
+                Node::Custom(custom) => custom.session_id = Some(*target),
+                _ => {}
+            }
+            if matches!(node, Node::Prompt(_) | Node::Custom(_)) {
+                node_batch.insert(&node_id.to_bytes(), self.serializer.serialize_node(&node)?);
+                rewritten.push(node);
+            }
+            index_batch.remove(key);
+            index_batch.insert(
+                Self::build_index_key(&target.to_bytes(), &node_id.to_bytes()),
+                &[],
+            );
+            moved.push(node_id);
+        }
+
+        self.nodes.apply_batch(node_batch)?;
+        for node in &rewritten {
+            self.cold_nodes.remove(node.id().to_bytes())?;
+        }
+        self.session_index.apply_batch(index_batch)?;
+        self.content_index.apply_batch(content_batch)?;
+        // Responses follow their prompts, so every moved node may change group
+        if !self.views.read().is_empty() {
+            for id in &moved {
+                if let Some(node) = self.get_node(id)? {
+                    self.update_views(id, Some(&node))?;
+                }
+            }
+        }
+        for node in &rewritten {
+            self.record_change(|| ChangeRecord::node_written(ChangeKind::Updated, node))?;
+        }
+        self.db.flush()?;
+        Ok(moved)
+    }
+
+    /// Unexpired record of the write made under idempotency `key`, if any
+    ///
+    /// An expired record is removed when it is looked up.
+    pub fn idempotency_record(&self, key: &str) -> Result<Option<IdempotencyRecord>> {
+        let Some(bytes) = self.idempotency.get(key.as_bytes())? else {
+            return Ok(None);
+        };
+        let record: IdempotencyRecord = serde_json::from_slice(&bytes)
+            .map_err(|e| Error::DeserializationError(e.to_string()))?;
+        if record.is_expired(Utc::now()) {
+            self.idempotency.remove(key.as_bytes())?;
+            return Ok(None);
+        }
+        Ok(Some(record))
+    }
+
+    /// Remember the outcome of the write made under idempotency `key`
+    pub fn store_idempotency_record(&self, key: &str, record: &IdempotencyRecord) -> Result<()> {
+        let _gate = self.write_guard();
+        self.idempotency
+            .insert(key.as_bytes(), serde_json::to_vec(record)?)?;
+        self.db.flush()?;
+        Ok(())
+    }
+
+    /// Save a checkpoint of a session
+    ///
+    /// Checkpoints are keyed by session and stored as JSON, like audit
+    /// entries.
+    pub fn store_checkpoint(&self, checkpoint: &SessionCheckpoint) -> Result<()> {
+        let _gate = self.write_guard();
+        let key =
+            Self::build_index_key(&checkpoint.session_id.to_bytes(), checkpoint.id.as_bytes());
+        self.checkpoints
+            .insert(key, serde_json::to_vec(checkpoint)?)?;
+        self.db.flush()?;
+        Ok(())
+    }
+
+    /// Checkpoints of a session, oldest first
+    pub fn session_checkpoints(&self, session_id: &SessionId) -> Result<Vec<SessionCheckpoint>> {
+        let mut checkpoints = self
+            .checkpoints
+            .scan_prefix(session_id.to_bytes())
+            .map(|result| {
+                let (_, bytes) = result?;
+                serde_json::from_slice::<SessionCheckpoint>(&bytes)
+                    .map_err(|e| Error::DeserializationError(e.to_string()))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        checkpoints.sort_by_key(|checkpoint| checkpoint.created_at);
+        Ok(checkpoints)
+    }
+
+    /// Save an earlier version of a node
+    ///
+    /// Versions are keyed by node and version number and stored as JSON,
+    /// like checkpoints.
+    pub fn store_node_version(&self, version: &NodeVersion) -> Result<()> {
+        let _gate = self.write_guard();
+        let key =
+            Self::build_index_key(&version.node_id.to_bytes(), &version.version.to_be_bytes());
+        self.versions.insert(key, serde_json::to_vec(version)?)?;
+        self.db.flush()?;
+        Ok(())
+    }
+
+    /// Earlier versions of a node, oldest first
+    pub fn node_versions(&self, node_id: &NodeId) -> Result<Vec<NodeVersion>> {
+        self.versions
+            .scan_prefix(node_id.to_bytes())
+            .map(|result| {
+                let (_, bytes) = result?;
+                serde_json::from_slice::<NodeVersion>(&bytes)
+                    .map_err(|e| Error::DeserializationError(e.to_string()))
+            })
+            .collect()
+    }
+
+    /// IDs of every stored node version
+    pub fn node_version_ids(&self) -> Result<Vec<NodeId>> {
+        self.versions
+            .iter()
+            .map(|result| {
+                let (_, bytes) = result?;
+                serde_json::from_slice::<NodeVersion>(&bytes)
+                    .map(|version| version.id)
+                    .map_err(|e| Error::DeserializationError(e.to_string()))
+            })
+            .collect()
+    }
+
+    /// Key-value entry stored under `key`, expired or not
+    pub fn kv_get(&self, key: &str) -> Result<Option<KvEntry>> {
+        self.kv
+            .get(key.as_bytes())?
+            .map(|bytes| Self::decode_kv(key.as_bytes(), &bytes))
+            .transpose()
+    }
+
+    /// Store a key-value entry, replacing any entry under the same key
+    pub fn kv_set(&self, entry: &KvEntry) -> Result<()> {
+        let _gate = self.write_guard();
+        self.kv
+            .insert(entry.key.as_bytes(), serde_json::to_vec(entry)?)?;
+        self.db.flush()?;
+        Ok(())
+    }
+
+    /// Remove the key-value entry under `key`, returning whether one existed
+    pub fn kv_delete(&self, key: &str) -> Result<bool> {
+        let _gate = self.write_guard();
+        let existed = self.kv.remove(key.as_bytes())?.is_some();
+        self.db.flush()?;
+        Ok(existed)
+    }
+
+    /// Key-value entries whose key starts with `prefix`, sorted by key
+    pub fn kv_list(&self, prefix: &str) -> Result<Vec<KvEntry>> {
+        self.kv
+            .scan_prefix(prefix.as_bytes())
+            .map(|result| {
+                let (key, bytes) = result?;
+                Self::decode_kv(&key, &bytes)
+            })
+            .collect()
+    }
+
+    fn decode_kv(key: &[u8], bytes: &[u8]) -> Result<KvEntry> {
+        serde_json::from_slice(bytes).map_err(|e| Error::corruption("kv", key, e))
+    }
+
+    /// Store the embedding of a node, replacing any earlier one
+    pub fn store_embedding(&self, embedding: &NodeEmbedding) -> Result<()> {
+        let _gate = self.write_guard();
+        self.embeddings
+            .insert(embedding.node_id.to_bytes(), bincode::serialize(embedding)?)?;
+        self.db.flush()?;
+        Ok(())
+    }
+
+    /// Embedding stored for a node, if any
+    pub fn get_embedding(&self, node_id: &NodeId) -> Result<Option<NodeEmbedding>> {
+        let key = node_id.to_bytes();
+        self.embeddings
+            .get(key)?
+            .map(|bytes| Self::decode_embedding(&key, &bytes))
+            .transpose()
+    }
+
+    /// Every stored embedding, in node ID order
+    pub fn all_embeddings(&self) -> Result<Vec<NodeEmbedding>> {
+        self.embeddings
+            .iter()
+            .map(|result| {
+                let (key, bytes) = result?;
+                Self::decode_embedding(&key, &bytes)
+            })
+            .collect()
+    }
+
+    fn decode_embedding(key: &[u8], bytes: &[u8]) -> Result<NodeEmbedding> {
+        bincode::deserialize(bytes).map_err(|e| Error::corruption("embeddings", key, e))
+    }
+
+    /// Point `alias` at `target`, returning what it pointed at before
+    ///
+    /// # Errors
+    ///
+    /// Returns a validation error if the alias is empty, too long or holds a
+    /// control character.
+    pub fn set_alias(&self, alias: &str, target: AliasTarget) -> Result<Option<AliasTarget>> {
+        validate_alias(alias)?;
+        let _gate = self.write_guard();
+        let previous = self
+            .aliases
+            .insert(alias.as_bytes(), serde_json::to_vec(&target)?)?;
+        self.db.flush()?;
+        previous
+            .map(|bytes| Self::decode_alias(alias, &bytes))
+            .transpose()
+    }
+
+    /// What `alias` points at, if it is set
+    pub fn resolve_alias(&self, alias: &str) -> Result<Option<AliasTarget>> {
+        self.aliases
+            .get(alias.as_bytes())?
+            .map(|bytes| Self::decode_alias(alias, &bytes))
+            .transpose()
+    }
+
+    /// Remove `alias`, returning what it pointed at
+    pub fn remove_alias(&self, alias: &str) -> Result<Option<AliasTarget>> {
+        let _gate = self.write_guard();
+        let previous = self.aliases.remove(alias.as_bytes())?;
+        self.db.flush()?;
+        previous
+            .map(|bytes| Self::decode_alias(alias, &bytes))
+            .transpose()
+    }
+
+    /// Every alias starting with `prefix`, sorted, with its target
+    pub fn list_aliases(&self, prefix: &str) -> Result<Vec<(String, AliasTarget)>> {
+        self.aliases
+            .scan_prefix(prefix.as_bytes())
+            .map(|result| {
+                let (key, bytes) = result?;
+                let alias = String::from_utf8_lossy(&key).into_owned();
+                let target = Self::decode_alias(&alias, &bytes)?;
+                Ok((alias, target))
+            })
+            .collect()
+    }
+
+    fn decode_alias(alias: &str, bytes: &[u8]) -> Result<AliasTarget> {
+        serde_json::from_slice(bytes).map_err(|e| Error::corruption("aliases", alias.as_bytes(), e))
+    }
+
+    /// Materialized views defined in this namespace
+    pub fn views(&self) -> Vec<ViewDefinition> {
+        self.views.read().clone()
+    }
+
+    /// Define a materialized view, replacing any view with the same name
+    ///
+    /// A new or changed view is built from the nodes already stored, holding
+    /// off writes meanwhile; defining an identical view again does nothing.
+    /// Returns whether the view was built.
+    ///
+    /// # Errors
+    ///
+    /// Returns a validation error if the view name is invalid.
+    pub fn define_view(&self, view: &ViewDefinition) -> Result<bool> {
+        view.validate()?;
+        let _gate = self.write_gate.write();
+        if self.views.read().contains(view) {
+            return Ok(false);
+        }
+        self.view_definitions
+            .insert(view.name.as_bytes(), serde_json::to_vec(view)?)?;
+        {
+            let mut views = self.views.write();
+            views.retain(|existing| existing.name != view.name);
+            views.push(view.clone());
+        }
+        self.build_view(view)?;
+        self.db.flush()?;
+        Ok(true)
+    }
+
+    /// Remove a materialized view and its totals
+    ///
+    /// Returns `false` if no view has that name.
+    pub fn drop_view(&self, name: &str) -> Result<bool> {
+        let _gate = self.write_gate.write();
+        if self.view_definitions.remove(name.as_bytes())?.is_none() {
+            return Ok(false);
+        }
+        self.views.write().retain(|view| view.name != name);
+        self.clear_view(name)?;
+        self.db.flush()?;
+        Ok(true)
+    }
+
+    /// Totals of one group of a view, `None` if no node falls in it
+    ///
+    /// # Errors
+    ///
+    /// Returns a not-found error if no view has that name.
+    pub fn view_totals(&self, name: &str, group: &[String]) -> Result<Option<ViewTotals>> {
+        self.ensure_view(name)?;
+        let key = Self::view_key(name, &serde_json::to_vec(group)?);
+        self.view_rows
+            .get(key)?
+            .map(|bytes| Self::decode_view_row(&bytes).map(|row| row.totals))
+            .transpose()
+    }
+
+    /// Every non-empty group of a view with its totals
+    ///
+    /// # Errors
+    ///
+    /// Returns a not-found error if no view has that name.
+    pub fn view_rows(&self, name: &str) -> Result<Vec<ViewRow>> {
+        self.ensure_view(name)?;
+        self.view_rows
+            .scan_prefix(Self::view_key(name, &[]))
+            .values()
+            .map(|bytes| Self::decode_view_row(&bytes?))
+            .collect()
+    }
+
+    /// Rebuild every view from the stored nodes, holding off writes meanwhile
+    ///
+    /// Views are kept current on every write, so this is only needed to
+    /// repair them, for example after restoring the node tree from elsewhere.
+    /// Returns the number of nodes counted across all views.
+    pub fn rebuild_views(&self) -> Result<usize> {
+        let _gate = self.write_gate.write();
+        let views = self.views();
+        let mut counted = 0;
+        for view in &views {
+            counted += self.build_view(view)?;
+        }
+        self.db.flush()?;
+        Ok(counted)
+    }
+
+    fn ensure_view(&self, name: &str) -> Result<()> {
+        if self.views.read().iter().any(|view| view.name == name) {
+            Ok(())
+        } else {
+            Err(Error::not_found("view", name))
+        }
+    }
+
+    /// Key of a view's row or member entry: the name, a NUL, then `suffix`
+    fn view_key(name: &str, suffix: &[u8]) -> Vec<u8> {
+        let mut key = Vec::with_capacity(name.len() + 1 + suffix.len());
+        key.extend_from_slice(name.as_bytes());
+        key.push(0);
+        key.extend_from_slice(suffix);
+        key
+    }
+
+    fn decode_view_row(bytes: &[u8]) -> Result<ViewRow> {
+        serde_json::from_slice(bytes).map_err(|e| Error::DeserializationError(e.to_string()))
+    }
+
+    /// Remove the rows and members of a view
+    fn clear_view(&self, name: &str) -> Result<()> {
+        let prefix = Self::view_key(name, &[]);
+        for tree in [&self.view_rows, &self.view_members] {
+            let mut batch = Batch::default();
+            for key in tree.scan_prefix(&prefix).keys() {
+                batch.remove(key?);
+            }
+            tree.apply_batch(batch)?;
+        }
+        Ok(())
+    }
+
+    /// Recompute a view from scratch, returning the number of nodes counted
+    fn build_view(&self, view: &ViewDefinition) -> Result<usize> {
+        self.clear_view(&view.name)?;
+        let mut rows: HashMap<Vec<String>, ViewTotals> = HashMap::new();
+        let mut members = Batch::default();
+        let mut counted = 0;
+        for node in self.all_nodes()? {
+            let Some(group) = view.group_of(&node, self.session_of(&node)?) else {
+                continue;
+            };
+            let member = ViewRow {
+                group,
+                totals: ViewTotals::of(&node),
+            };
+            members.insert(
+                Self::view_key(&view.name, &node.id().to_bytes()),
+                serde_json::to_vec(&member)?,
+            );
+            rows.entry(member.group).or_default().add(&member.totals);
+            counted += 1;
+        }
+        let mut batch = Batch::default();
+        for (group, totals) in rows {
+            let key = Self::view_key(&view.name, &serde_json::to_vec(&group)?);
+            batch.insert(key, serde_json::to_vec(&ViewRow { group, totals })?);
+        }
+        self.view_members.apply_batch(members)?;
+        self.view_rows.apply_batch(batch)?;
+        Ok(counted)
+    }
+
+    /// Move a node's contribution to every view from its old group to the
+    /// group of `node`, or out of the views when it was removed
+    fn update_views(&self, id: &NodeId, node: Option<&Node>) -> Result<()> {
+        let views = self.views.read();
+        if views.is_empty() {
+            return Ok(());
+        }
+        let session_id = match node {
+            Some(node) => self.session_of(node)?,
+            None => None,
+        };
+        for view in views.iter() {
+            let key = Self::view_key(&view.name, &id.to_bytes());
+            let member = node.and_then(|node| {
+                view.group_of(node, session_id).map(|group| ViewRow {
+                    group,
+                    totals: ViewTotals::of(node),
+                })
+            });
+            let previous = match &member {
+                Some(member) => self.view_members.insert(key, serde_json::to_vec(member)?)?,
+                None => self.view_members.remove(key)?,
+            };
+            if let Some(previous) = previous {
+                self.adjust_view_row(&view.name, &Self::decode_view_row(&previous)?, false)?;
+            }
+            if let Some(member) = &member {
+                self.adjust_view_row(&view.name, member, true)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Add a node's contribution to its group's row, or take it away
+    ///
+    /// Rows are updated atomically so concurrent writes do not lose counts,
+    /// and removed once their count reaches zero.
+    fn adjust_view_row(&self, name: &str, member: &ViewRow, add: bool) -> Result<()> {
+        let key = Self::view_key(name, &serde_json::to_vec(&member.group)?);
+        self.view_rows.fetch_and_update(key, |current| {
+            let mut totals = current
+                .and_then(|bytes| Self::decode_view_row(bytes).ok())
+                .map(|row| row.totals)
+                .unwrap_or_default();
+            if add {
+                totals.add(&member.totals);
+            } else {
+                totals.subtract(&member.totals);
+            }
+            (totals.count > 0)
+                .then(|| ViewRow {
+                    group: member.group.clone(),
+                    totals,
+                })
+                .and_then(|row| serde_json::to_vec(&row).ok())
+        })?;
+        Ok(())
+    }
+
+    /// Remove index entries that point at nodes or edges that no longer exist
+    ///
+    /// Hard deletes leave such entries behind, as do edits that change a
+    /// prompt's content. Expired idempotency records are dropped too. Returns
+    /// the number removed.
+    pub fn compact_indexes(&self) -> Result<usize> {
+        let _gate = self.write_guard();
+        let mut removed = 0;
 
-        let nodes = db.open_tree(b"nodes")?;
-        let edges = db.open_tree(b"edges")?;
-        let session_index = db.open_tree(b"session_index")?;
-        let outgoing_edges_index = db.open_tree(b"outgoing_edges")?;
-        let incoming_edges_index = db.open_tree(b"incoming_edges")?;
+        for result in self.session_index.iter() {
+            let (key, _) = result?;
+            if key.len() >= 32 && !self.contains_node(&key[16..32])? {
+                self.session_index.remove(&key)?;
+                removed += 1;
+            }
+        }
+        for result in self.content_index.iter() {
+            let (key, _) = result?;
+            if key.len() < 64 {
+                continue;
+            }
+            let node_id = NodeId::from_bytes(Self::index_id(&self.content_index, &key, 48..64)?);
+            let current = match self.get_node(&node_id)? {
+                Some(Node::Prompt(prompt)) => Self::content_key(&prompt) == *key,
+                _ => false,
+            };
+            if !current {
+                self.content_index.remove(&key)?;
+                removed += 1;
+            }
+        }
+        for index in [&self.outgoing_edges_index, &self.incoming_edges_index] {
+            for result in index.iter() {
+                let (key, _) = result?;
+                if key.len() >= 32 && !self.contains_edge(&key[16..32])? {
+                    index.remove(&key)?;
+                    removed += 1;
+                }
+            }
+        }
+        let now = Utc::now();
+        for result in self.idempotency.iter() {
+            let (key, bytes) = result?;
+            let expired = serde_json::from_slice::<IdempotencyRecord>(&bytes)
+                .map_or(true, |record| record.is_expired(now));
+            if expired {
+                self.idempotency.remove(&key)?;
+                removed += 1;
+            }
+        }
 
-        Ok(Self {
-            db,
-            nodes,
-            edges,
-            session_index,
-            outgoing_edges_index,
-            incoming_edges_index,
-            serializer: Serializer::new(SerializationFormat::MessagePack),
-        })
+        self.db.flush()?;
+        Ok(removed)
     }
 
-    /// Open with a custom serialization format
-    pub fn open_with_format<P: AsRef<Path>>(path: P, format: SerializationFormat) -> Result<Self> {
-        let mut backend = Self::open(path)?;
-        backend.serializer = Serializer::new(format);
-        Ok(backend)
+    /// Rebuild the session, content and edge indexes, every view and the usage
+    /// rollups from the node and edge trees, holding off writes meanwhile
+    ///
+    /// Unlike [`compact_indexes`](Self::compact_indexes), which only drops
+    /// dangling entries, this also restores entries that are missing, such as
+    /// after a crash between writing a record and indexing it, or when a
+    /// database predates an index.
+    pub fn rebuild_indexes(&self) -> Result<IndexRebuildReport> {
+        let _gate = self.write_gate.write();
+        let mut report = IndexRebuildReport::default();
+
+        let mut session_batch = Batch::default();
+        let mut content_batch = Batch::default();
+        for result in self.node_records() {
+            let (_, bytes) = result?;
+            let node = self.serializer.deserialize_node(&bytes)?;
+            report.nodes_scanned += 1;
+            if let Some(session_id) = self.session_of(&node)? {
+                let key = Self::build_index_key(&session_id.to_bytes(), &node.id().to_bytes());
+                session_batch.insert(key, &[]);
+                report.session_entries += 1;
+            }
+            if let Node::Prompt(prompt) = &node {
+                content_batch.insert(Self::content_key(prompt), &[]);
+                report.content_entries += 1;
+            }
+        }
+        content_batch.insert(CONTENT_INDEX_READY, &[]);
+
+        let mut outgoing_batch = Batch::default();
+        let mut incoming_batch = Batch::default();
+        for result in self.edge_records() {
+            let (_, bytes) = result?;
+            let edge = self.serializer.deserialize_edge(&bytes)?;
+            report.edges_scanned += 1;
+            let id = edge.id.to_bytes();
+            outgoing_batch.insert(Self::build_index_key(&edge.from.to_bytes(), &id), &[]);
+            incoming_batch.insert(Self::build_index_key(&edge.to.to_bytes(), &id), &[]);
+            report.edge_entries += 2;
+        }
+
+        for (index, batch) in [
+            (&self.session_index, session_batch),
+            (&self.content_index, content_batch),
+            (&self.outgoing_edges_index, outgoing_batch),
+            (&self.incoming_edges_index, incoming_batch),
+        ] {
+            index.clear()?;
+            index.apply_batch(batch)?;
+        }
+        for view in &self.views() {
+            report.view_members += self.build_view(view)?;
+        }
+        report.usage_buckets = self.build_usage_rollups()?;
+
+        self.db.flush()?;
+        Ok(report)
     }
 
-    /// Build a composite key for indexing
-    fn build_index_key(prefix: &[u8], id: &[u8]) -> Vec<u8> {
-        let mut key = Vec::with_capacity(prefix.len() + id.len());
-        key.extend_from_slice(prefix);
-        key.extend_from_slice(id);
-        key
+    /// Store many nodes with one write batch per tree and a single flush
+    ///
+    /// Each tree's batch is applied atomically. Responses are indexed under the
+    /// session of their prompt, which may be anywhere in the same batch.
+    pub fn store_nodes_batch(&self, nodes: &[Node]) -> Result<Vec<NodeId>> {
+        let _gate = self.write_guard();
+        let mut node_batch = Batch::default();
+        let mut session_batch = Batch::default();
+        let mut content_batch = Batch::default();
+        let prompt_sessions: HashMap<NodeId, SessionId> = nodes
+            .iter()
+            .filter_map(|node| match node {
+                Node::Prompt(p) => Some((p.id, p.session_id)),
+                _ => None,
+            })
+            .collect();
+        let mut ids = Vec::with_capacity(nodes.len());
+
+        for node in nodes {
+            let id = node.id();
+            node_batch.insert(&id.to_bytes(), self.serializer.serialize_node(node)?);
+
+            if let Node::Prompt(p) = node {
+                content_batch.insert(Self::content_key(p), &[]);
+            }
+            let session_id = match node {
+                Node::Prompt(p) => Some(p.session_id),
+                Node::Response(r) => match prompt_sessions.get(&r.prompt_id) {
+                    Some(session_id) => Some(*session_id),
+                    None => match self.get_node(&r.prompt_id)? {
+                        Some(Node::Prompt(p)) => Some(p.session_id),
+                        _ => None,
+                    },
+                },
+                Node::Session(s) => Some(s.id),
+                Node::Custom(c) => c.session_id,
+                // Tool invocations, agents and templates are not indexed by session
+                _ => None,
+            };
+            if let Some(session_id) = session_id {
+                let key = Self::build_index_key(&session_id.to_bytes(), &id.to_bytes());
+                session_batch.insert(key, &[]);
+            }
+            ids.push(id);
+        }
+
+        let existing = self.existing_keys(
+            [&self.nodes, &self.cold_nodes],
+            ids.iter().map(NodeId::to_bytes),
+        )?;
+        // Overwritten nodes give back their share of the usage rollups
+        let mut previous = Vec::with_capacity(nodes.len());
+        for id in &ids {
+            previous.push(self.get_node(id)?);
+        }
+        self.nodes.apply_batch(node_batch)?;
+        for id in &ids {
+            self.cold_nodes.remove(id.to_bytes())?;
+        }
+        self.session_index.apply_batch(session_batch)?;
+        self.content_index.apply_batch(content_batch)?;
+        for (node, previous) in nodes.iter().zip(&previous) {
+            self.update_views(&node.id(), Some(node))?;
+            self.update_usage(previous.as_ref(), node)?;
+        }
+        for (node, existed) in nodes.iter().zip(existing) {
+            self.record_change(|| ChangeRecord::node_written(Self::write_kind(existed), node))?;
+        }
+        self.db.flush()?;
+        Ok(ids)
+    }
+
+    /// Store many edges with one write batch per tree and a single flush
+    pub fn store_edges_batch(&self, edges: &[Edge]) -> Result<Vec<EdgeId>> {
+        let _gate = self.write_guard();
+        let mut edge_batch = Batch::default();
+        let mut outgoing_batch = Batch::default();
+        let mut incoming_batch = Batch::default();
+        let mut ids = Vec::with_capacity(edges.len());
+
+        for edge in edges {
+            let id = edge.id.to_bytes();
+            edge_batch.insert(&id, self.serializer.serialize_edge(edge)?);
+            outgoing_batch.insert(Self::build_index_key(&edge.from.to_bytes(), &id), &[]);
+            incoming_batch.insert(Self::build_index_key(&edge.to.to_bytes(), &id), &[]);
+            ids.push(edge.id);
+        }
+
+        let existing = self.existing_keys(
+            [&self.edges, &self.cold_edges],
+            ids.iter().map(EdgeId::to_bytes),
+        )?;
+        self.edges.apply_batch(edge_batch)?;
+        for id in &ids {
+            self.cold_edges.remove(id.to_bytes())?;
+        }
+        self.outgoing_edges_index.apply_batch(outgoing_batch)?;
+        self.incoming_edges_index.apply_batch(incoming_batch)?;
+        for (edge, existed) in edges.iter().zip(existing) {
+            self.record_change(|| ChangeRecord::edge_written(Self::write_kind(existed), edge))?;
+        }
+        self.db.flush()?;
+        Ok(ids)
+    }
+
+    /// Read one page of an edge index, starting after the `cursor` edge
+    fn edges_page(
+        &self,
+        index: &Tree,
+        node_id: &NodeId,
+        cursor: Option<&EdgeId>,
+        limit: usize,
+    ) -> Result<EdgePage> {
+        let limit = limit.max(1);
+        let prefix = node_id.to_bytes();
+        let start = match cursor {
+            Some(cursor) => Bound::Excluded(Self::build_index_key(&prefix, &cursor.to_bytes())),
+            None => Bound::Included(prefix.to_vec()),
+        };
+
+        let mut page = EdgePage::default();
+        let mut last = None;
+        for result in index.range::<Vec<u8>, _>((start, Bound::Unbounded)) {
+            let (key, _) = result?;
+            if !key.starts_with(&prefix) {
+                break;
+            }
+            if key.len() < 32 {
+                continue;
+            }
+            if page.edges.len() == limit {
+                page.next_cursor = last;
+                break;
+            }
+            let edge_id_bytes: [u8; 16] = Self::index_id(index, &key, 16..32)?;
+            let edge_id = EdgeId::from_bytes(edge_id_bytes);
+            last = Some(edge_id);
+
+            if let Some(edge) = self.get_edge(&edge_id)? {
+                page.edges.push(edge);
+            }
+        }
+
+        Ok(page)
+    }
+
+    /// Count the live edges in an edge index for a node
+    fn count_edges(&self, index: &Tree, node_id: &NodeId) -> Result<usize> {
+        let mut count = 0;
+        for result in index.scan_prefix(node_id.to_bytes()) {
+            let (key, _) = result?;
+            // Skip entries left dangling by hard deletes
+            if key.len() >= 32 && self.contains_edge(&key[16..32])? {
+                count += 1;
+            }
+        }
+        Ok(count)
+    }
+
+    fn get_trashed(&self, id: &NodeId) -> Result<Option<TrashedNode>> {
+        self.trash
+            .get(id.to_bytes())?
+            .map(|bytes| Self::decode_trashed(&bytes))
+            .transpose()
+    }
+
+    /// The ID stored at `range` of a key of `index`
+    fn index_id(index: &Tree, key: &[u8], range: Range<usize>) -> Result<[u8; 16]> {
+        key.get(range)
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or_else(|| {
+                Error::corruption(
+                    String::from_utf8_lossy(&index.name()),
+                    key,
+                    "index key is too short",
+                )
+            })
+    }
+
+    fn decode_trashed(bytes: &[u8]) -> Result<TrashedNode> {
+        serde_json::from_slice(bytes).map_err(|e| Error::DeserializationError(e.to_string()))
+    }
+
+    /// Session a node is indexed under, looking through the trash for a
+    /// response's prompt if needed
+    fn session_of(&self, node: &Node) -> Result<Option<SessionId>> {
+        Ok(match node {
+            Node::Session(s) => Some(s.id),
+            Node::Prompt(p) => Some(p.session_id),
+            Node::Response(r) => match self.get_node(&r.prompt_id)? {
+                Some(Node::Prompt(p)) => Some(p.session_id),
+                _ => self
+                    .get_trashed(&r.prompt_id)?
+                    .and_then(|trashed| trashed.session_id),
+            },
+            Node::Custom(c) => c.session_id,
+            Node::ToolInvocation(_) | Node::Agent(_) | Node::Template(_) => None,
+        })
+    }
+
+    /// Remove an edge along with both of its index entries
+    fn remove_edge_and_indexes(&self, id: &EdgeId) -> Result<()> {
+        if let Some(edge) = self.get_edge(id)? {
+            self.outgoing_edges_index
+                .remove(Self::build_index_key(&edge.from.to_bytes(), &id.to_bytes()))?;
+            self.incoming_edges_index
+                .remove(Self::build_index_key(&edge.to.to_bytes(), &id.to_bytes()))?;
+            self.edges.remove(id.to_bytes())?;
+            self.cold_edges.remove(id.to_bytes())?;
+            self.record_change(|| ChangeRecord::edge_deleted(*id))?;
+        }
+        Ok(())
     }
 }
 
 impl StorageBackend for SledBackend {
     fn store_node(&self, node: &Node) -> Result<()> {
+        let _gate = self.write_guard();
         let id = node.id();
         let bytes = self.serializer.serialize_node(node)?;
 
-        // Store the node
-        self.nodes.insert(id.to_bytes(), bytes)?;
+        // Store the node, bringing it back to the hot tier if it was cold
+        let previous = match self.nodes.insert(id.to_bytes(), bytes)? {
+            Some(previous) => Some(previous),
+            None => self.cold_nodes.remove(id.to_bytes())?,
+        };
 
         // Update session index for prompts and responses
         match node {
             Node::Prompt(p) => {
                 let key = Self::build_index_key(&p.session_id.to_bytes(), &id.to_bytes());
                 self.session_index.insert(key, &[])?;
+                self.content_index.insert(Self::content_key(p), &[])?;
             }
             Node::Response(r) => {
                 // Find the prompt to get session_id
-                if let Some(prompt_bytes) = self.nodes.get(r.prompt_id.to_bytes())? {
-                    if let Ok(Node::Prompt(p)) = self.serializer.deserialize_node(&prompt_bytes) {
-                        let key = Self::build_index_key(&p.session_id.to_bytes(), &id.to_bytes());
-                        self.session_index.insert(key, &[])?;
-                    }
+                if let Some(Node::Prompt(p)) = self.get_node(&r.prompt_id)? {
+                    let key = Self::build_index_key(&p.session_id.to_bytes(), &id.to_bytes());
+                    self.session_index.insert(key, &[])?;
                 }
             }
             Node::Session(s) => {
@@ -94,33 +1972,66 @@ impl StorageBackend for SledBackend {
                 // Templates are global entities, not tied to specific sessions
                 // They're accessed via template ID or Instantiates/Inherits edges
             }
+            Node::Custom(c) => {
+                // Custom nodes are indexed only when attached to a session
+                if let Some(session_id) = c.session_id {
+                    let key = Self::build_index_key(&session_id.to_bytes(), &id.to_bytes());
+                    self.session_index.insert(key, &[])?;
+                }
+            }
         }
+        self.update_views(&id, Some(node))?;
+        let previous_node = previous
+            .as_ref()
+            .and_then(|bytes| self.serializer.deserialize_node(bytes).ok());
+        self.update_usage(previous_node.as_ref(), node)?;
+
+        self.record_change(|| {
+            ChangeRecord::node_written(Self::write_kind(previous.is_some()), node)
+        })?;
 
         self.db.flush()?;
         Ok(())
     }
 
     fn get_node(&self, id: &NodeId) -> Result<Option<Node>> {
-        match self.nodes.get(id.to_bytes())? {
-            Some(bytes) => {
-                let node = self.serializer.deserialize_node(&bytes)?;
-                Ok(Some(node))
+        for tree in [&self.nodes, &self.cold_nodes] {
+            if let Some(bytes) = tree.get(id.to_bytes())? {
+                let node = self.serializer.deserialize_node(&bytes).map_err(|e| {
+                    Error::corruption(
+                        String::from_utf8_lossy(&tree.name()),
+                        id.to_string().as_bytes(),
+                        e,
+                    )
+                })?;
+                return Ok(Some(node));
             }
-            None => Ok(None),
         }
+        Ok(None)
     }
 
     fn delete_node(&self, id: &NodeId) -> Result<()> {
-        self.nodes.remove(id.to_bytes())?;
+        let _gate = self.write_guard();
+        let hot = self.nodes.remove(id.to_bytes())?.is_some();
+        if self.cold_nodes.remove(id.to_bytes())?.is_some() || hot {
+            self.embeddings.remove(id.to_bytes())?;
+            self.update_views(id, None)?;
+            self.record_change(|| ChangeRecord::node_deleted(*id))?;
+        }
+        self.flush_cold()?;
         self.db.flush()?;
         Ok(())
     }
 
     fn store_edge(&self, edge: &Edge) -> Result<()> {
+        let _gate = self.write_guard();
         let bytes = self.serializer.serialize_edge(edge)?;
 
-        // Store the edge
-        self.edges.insert(edge.id.to_bytes(), bytes)?;
+        // Store the edge, bringing it back to the hot tier if it was cold
+        let previous = match self.edges.insert(edge.id.to_bytes(), bytes)? {
+            Some(previous) => Some(previous),
+            None => self.cold_edges.remove(edge.id.to_bytes())?,
+        };
 
         // Update outgoing edges index
         let outgoing_key = Self::build_index_key(&edge.from.to_bytes(), &edge.id.to_bytes());
@@ -130,37 +2041,59 @@ impl StorageBackend for SledBackend {
         let incoming_key = Self::build_index_key(&edge.to.to_bytes(), &edge.id.to_bytes());
         self.incoming_edges_index.insert(incoming_key, &[])?;
 
+        self.record_change(|| {
+            ChangeRecord::edge_written(Self::write_kind(previous.is_some()), edge)
+        })?;
+
         self.db.flush()?;
         Ok(())
     }
 
     fn get_edge(&self, id: &EdgeId) -> Result<Option<Edge>> {
-        match self.edges.get(id.to_bytes())? {
-            Some(bytes) => {
-                let edge = self.serializer.deserialize_edge(&bytes)?;
-                Ok(Some(edge))
+        for tree in [&self.edges, &self.cold_edges] {
+            if let Some(bytes) = tree.get(id.to_bytes())? {
+                let edge = self.serializer.deserialize_edge(&bytes).map_err(|e| {
+                    Error::corruption(
+                        String::from_utf8_lossy(&tree.name()),
+                        id.to_string().as_bytes(),
+                        e,
+                    )
+                })?;
+                return Ok(Some(edge));
             }
-            None => Ok(None),
         }
+        Ok(None)
     }
 
     fn delete_edge(&self, id: &EdgeId) -> Result<()> {
-        self.edges.remove(id.to_bytes())?;
+        let _gate = self.write_guard();
+        let hot = self.edges.remove(id.to_bytes())?.is_some();
+        if self.cold_edges.remove(id.to_bytes())?.is_some() || hot {
+            self.record_change(|| ChangeRecord::edge_deleted(*id))?;
+        }
+        self.flush_cold()?;
         self.db.flush()?;
         Ok(())
     }
 
     fn get_session_nodes(&self, session_id: &SessionId) -> Result<Vec<Node>> {
+        self.get_session_nodes_until(session_id, &Deadline::none())
+    }
+
+    fn get_session_nodes_until(
+        &self,
+        session_id: &SessionId,
+        deadline: &Deadline,
+    ) -> Result<Vec<Node>> {
         let prefix = session_id.to_bytes();
         let mut nodes = Vec::new();
 
         for result in self.session_index.scan_prefix(prefix) {
+            deadline.check("get_session_nodes", Some(nodes.len() as u64))?;
             let (key, _) = result?;
             // Extract node ID from composite key (skip session_id bytes)
             if key.len() >= 32 {
-                let node_id_bytes: [u8; 16] = key[16..32]
-                    .try_into()
-                    .map_err(|_| Error::Storage("Invalid node ID in index".to_string()))?;
+                let node_id_bytes: [u8; 16] = Self::index_id(&self.session_index, &key, 16..32)?;
                 let node_id = NodeId::from_bytes(node_id_bytes);
 
                 if let Some(node) = self.get_node(&node_id)? {
@@ -180,9 +2113,8 @@ impl StorageBackend for SledBackend {
             let (key, _) = result?;
             // Extract edge ID from composite key
             if key.len() >= 32 {
-                let edge_id_bytes: [u8; 16] = key[16..32]
-                    .try_into()
-                    .map_err(|_| Error::Storage("Invalid edge ID in index".to_string()))?;
+                let edge_id_bytes: [u8; 16] =
+                    Self::index_id(&self.outgoing_edges_index, &key, 16..32)?;
                 let edge_id = EdgeId::from_bytes(edge_id_bytes);
 
                 if let Some(edge) = self.get_edge(&edge_id)? {
@@ -202,9 +2134,8 @@ impl StorageBackend for SledBackend {
             let (key, _) = result?;
             // Extract edge ID from composite key
             if key.len() >= 32 {
-                let edge_id_bytes: [u8; 16] = key[16..32]
-                    .try_into()
-                    .map_err(|_| Error::Storage("Invalid edge ID in index".to_string()))?;
+                let edge_id_bytes: [u8; 16] =
+                    Self::index_id(&self.incoming_edges_index, &key, 16..32)?;
                 let edge_id = EdgeId::from_bytes(edge_id_bytes);
 
                 if let Some(edge) = self.get_edge(&edge_id)? {
@@ -216,15 +2147,59 @@ impl StorageBackend for SledBackend {
         Ok(edges)
     }
 
+    fn get_outgoing_edges_page(
+        &self,
+        node_id: &NodeId,
+        cursor: Option<&EdgeId>,
+        limit: usize,
+    ) -> Result<EdgePage> {
+        self.edges_page(&self.outgoing_edges_index, node_id, cursor, limit)
+    }
+
+    fn get_incoming_edges_page(
+        &self,
+        node_id: &NodeId,
+        cursor: Option<&EdgeId>,
+        limit: usize,
+    ) -> Result<EdgePage> {
+        self.edges_page(&self.incoming_edges_index, node_id, cursor, limit)
+    }
+
+    fn node_degree(&self, node_id: &NodeId) -> Result<NodeDegree> {
+        Ok(NodeDegree {
+            outgoing: self.count_edges(&self.outgoing_edges_index, node_id)?,
+            incoming: self.count_edges(&self.incoming_edges_index, node_id)?,
+        })
+    }
+
     fn flush(&self) -> Result<()> {
-        self.db.flush()?;
+        let pending = self.pending_writes.swap(0, Ordering::Relaxed);
+        let started = Instant::now();
+        if let Err(e) = self.flush_cold().and_then(|()| Ok(self.db.flush()?)) {
+            self.pending_writes.fetch_add(pending, Ordering::Relaxed);
+            return Err(e);
+        }
+        *self.last_flush.lock() = Some(started.elapsed());
+        Ok(())
+    }
+
+    fn prepare_read(&self, consistency: ReadConsistency) -> Result<()> {
+        // Nothing written since the last flush means nothing to make durable
+        if consistency == ReadConsistency::RequireFlushed
+            && self.pending_writes.load(Ordering::Relaxed) > 0
+        {
+            self.flush()?;
+        }
         Ok(())
     }
 
     fn stats(&self) -> Result<StorageStats> {
-        let node_count = self.nodes.len() as u64;
-        let edge_count = self.edges.len() as u64;
-        let storage_bytes = self.db.size_on_disk()?;
+        let node_count = (self.nodes.len() + self.cold_nodes.len()) as u64;
+        let edge_count = (self.edges.len() + self.cold_edges.len()) as u64;
+        let mut storage_bytes = self.db.size_on_disk()?;
+        if let Some(cold) = &self.cold_db {
+            storage_bytes += cold.db.size_on_disk()?;
+        }
 
         // Count unique sessions
         let mut session_count = 0u64;
@@ -241,11 +2216,45 @@ impl StorageBackend for SledBackend {
             }
         }
 
+        let trees = [
+            ("nodes", &self.nodes),
+            ("edges", &self.edges),
+            ("session_index", &self.session_index),
+            ("content_index", &self.content_index),
+            ("outgoing_edges", &self.outgoing_edges_index),
+            ("incoming_edges", &self.incoming_edges_index),
+            ("audit_log", &self.audit_log),
+            ("trash", &self.trash),
+            ("changes", &self.changes),
+            ("idempotency", &self.idempotency),
+            ("checkpoints", &self.checkpoints),
+            ("versions", &self.versions),
+            ("metrics", &self.metrics),
+            ("aliases", &self.aliases),
+            ("kv", &self.kv),
+            ("embeddings", &self.embeddings),
+            ("view_rows", &self.view_rows),
+            ("view_members", &self.view_members),
+            ("cold_nodes", &self.cold_nodes),
+            ("cold_edges", &self.cold_edges),
+        ]
+        .into_iter()
+        .map(|(name, tree)| TreeStats {
+            name: name.to_string(),
+            entries: tree.len() as u64,
+        })
+        .collect();
+
         Ok(StorageStats {
             node_count,
             edge_count,
             storage_bytes,
             session_count,
+            trees,
+            last_flush_duration: *self.last_flush.lock(),
+            pending_writes: self.pending_writes.load(Ordering::Relaxed),
+            compression: self.serializer.compression_stats(),
+            cache: None,
         })
     }
 }
@@ -253,7 +2262,7 @@ impl StorageBackend for SledBackend {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{ConversationSession, EdgeType, PromptNode};
+    use crate::{EdgeType, PromptNode, ResponseNode, TokenUsage};
     use tempfile::tempdir;
 
     #[test]
@@ -271,6 +2280,23 @@ mod tests {
         assert_eq!(retrieved.unwrap().id(), session.node_id);
     }
 
+    #[test]
+    fn test_corrupt_node_reported_with_location() {
+        let dir = tempdir().unwrap();
+        let backend = SledBackend::open(dir.path()).unwrap();
+        let id = NodeId::new();
+        backend
+            .nodes
+            .insert(id.to_bytes(), &b"garbage"[..])
+            .unwrap();
+
+        let err = backend.get_node(&id).unwrap_err();
+        assert_eq!(err.code(), crate::ErrorCode::Corruption);
+        assert!(
+            matches!(&err, Error::Corruption { tree, key, .. } if tree == "nodes" && *key == id.to_string())
+        );
+    }
+
     #[test]
     fn test_store_and_retrieve_edge() {
         let dir = tempdir().unwrap();
@@ -302,6 +2328,150 @@ mod tests {
 
         let nodes = backend.get_session_nodes(&session.id).unwrap();
         assert_eq!(nodes.len(), 2);
+
+        let token = crate::deadline::CancellationToken::new();
+        let deadline = Deadline::cancelled_by(token.clone());
+        assert_eq!(
+            backend
+                .get_session_nodes_until(&session.id, &deadline)
+                .unwrap()
+                .len(),
+            2
+        );
+        token.cancel();
+        let err = backend
+            .get_session_nodes_until(&session.id, &deadline)
+            .unwrap_err();
+        assert!(
+            matches!(
+                err,
+                Error::Interrupted {
+                    completed: Some(0),
+                    ..
+                }
+            ),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn test_content_index() {
+        let dir = tempdir().unwrap();
+        let session = ConversationSession::new();
+        let prompt = PromptNode::new(session.id, "Hello".to_string());
+        {
+            let backend = SledBackend::open(dir.path()).unwrap();
+            backend.store_node(&Node::Prompt(prompt.clone())).unwrap();
+            // Simulate a database written before the content index existed
+            backend.content_index.clear().unwrap();
+        }
+
+        // Prompts already stored are indexed when the database is opened
+        let backend = SledBackend::open(dir.path()).unwrap();
+        let found = backend
+            .find_prompts_by_content("Hello", Some(&session.id))
+            .unwrap();
+        assert_eq!(found.len(), 1);
+        assert!(backend
+            .find_prompts_by_content("Hello", Some(&SessionId::new()))
+            .unwrap()
+            .is_empty());
+
+        // An edited prompt is found by its new content only, and compaction
+        // drops the stale entry
+        let mut edited = prompt.clone();
+        edited.content = "Hello there".to_string();
+        backend.store_node(&Node::Prompt(edited)).unwrap();
+        assert!(backend
+            .find_prompts_by_content("Hello", None)
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            backend
+                .find_prompts_by_content("Hello there", None)
+                .unwrap()
+                .len(),
+            1
+        );
+        assert_eq!(backend.compact_indexes().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_materialized_views() {
+        let dir = tempdir().unwrap();
+        let session = ConversationSession::new();
+        let prompt = PromptNode::new(session.id, "Hello".to_string());
+        let response = |tokens| {
+            let mut response =
+                ResponseNode::new(prompt.id, "Hi".to_string(), TokenUsage::new(tokens, tokens));
+            response.metadata.model = "gpt-4".to_string();
+            response
+        };
+        let first = response(10);
+        let group = vec![session.id.to_string()];
+        let backend = SledBackend::open(dir.path()).unwrap();
+        backend
+            .store_nodes_batch(&[
+                Node::Session(session.clone()),
+                Node::Prompt(prompt.clone()),
+                Node::Response(first.clone()),
+            ])
+            .unwrap();
+
+        // Nodes already stored are counted when the view is defined
+        assert!(backend
+            .define_view(&ViewDefinition::session_usage())
+            .unwrap());
+        assert!(!backend
+            .define_view(&ViewDefinition::session_usage())
+            .unwrap());
+        let totals = backend
+            .view_totals("session_usage", &group)
+            .unwrap()
+            .unwrap();
+        assert_eq!((totals.count, totals.total_tokens), (1, 20));
+
+        // Definitions are loaded from storage, and totals follow later writes
+        let backend = backend.in_namespace(DEFAULT_NAMESPACE).unwrap();
+        assert_eq!(backend.views(), vec![ViewDefinition::session_usage()]);
+        let second = response(5);
+        backend.store_node(&Node::Response(second.clone())).unwrap();
+        let mut edited = first.clone();
+        edited.usage = TokenUsage::new(1, 1);
+        backend.store_node(&Node::Response(edited)).unwrap();
+        let totals = backend
+            .view_totals("session_usage", &group)
+            .unwrap()
+            .unwrap();
+        assert_eq!((totals.count, totals.total_tokens), (2, 12));
+
+        // Moving the prompt moves its responses to the other session's group
+        let other = SessionId::new();
+        backend.move_session_nodes(&session.id, &other).unwrap();
+        assert_eq!(backend.view_totals("session_usage", &group).unwrap(), None);
+        let rows = backend.view_rows("session_usage").unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].group, vec![other.to_string()]);
+
+        backend.trash_node(&first.id).unwrap();
+        backend.delete_node(&second.id).unwrap();
+        assert!(backend.view_rows("session_usage").unwrap().is_empty());
+
+        // Rebuilding repairs totals that no longer match the nodes
+        backend.restore_node(&first.id).unwrap();
+        backend.view_rows.clear().unwrap();
+        assert_eq!(backend.rebuild_views().unwrap(), 1);
+        let totals = backend
+            .view_totals("session_usage", &[other.to_string()])
+            .unwrap()
+            .unwrap();
+        assert_eq!((totals.count, totals.total_tokens), (1, 2));
+
+        assert!(backend.drop_view("session_usage").unwrap());
+        assert!(backend
+            .view_totals("session_usage", &group)
+            .unwrap_err()
+            .is_not_found());
     }
 
     #[test]
@@ -321,6 +2491,86 @@ mod tests {
         assert_eq!(incoming.len(), 1);
     }
 
+    #[test]
+    fn test_batch_writes() {
+        let dir = tempdir().unwrap();
+        let backend = SledBackend::open(dir.path()).unwrap();
+
+        let session = ConversationSession::new();
+        let prompt = PromptNode::new(session.id, "Batched".to_string());
+        let response = ResponseNode::new(prompt.id, "Reply".to_string(), TokenUsage::new(1, 1));
+        let nodes = vec![
+            Node::Session(session.clone()),
+            Node::Prompt(prompt.clone()),
+            Node::Response(response.clone()),
+        ];
+        let ids = backend.store_nodes_batch(&nodes).unwrap();
+        assert_eq!(ids, vec![session.node_id, prompt.id, response.id]);
+        // The response is indexed via a prompt from the same batch
+        assert_eq!(backend.get_session_nodes(&session.id).unwrap().len(), 3);
+
+        let edges = vec![
+            Edge::new(prompt.id, session.node_id, EdgeType::PartOf),
+            Edge::new(response.id, prompt.id, EdgeType::RespondsTo),
+        ];
+        backend.store_edges_batch(&edges).unwrap();
+        assert_eq!(backend.node_degree(&prompt.id).unwrap().total(), 2);
+        assert_eq!(
+            backend.get_edge(&edges[1].id).unwrap().unwrap().to,
+            prompt.id
+        );
+    }
+
+    #[test]
+    fn test_edge_pages_and_degree() {
+        let dir = tempdir().unwrap();
+        let backend = SledBackend::open(dir.path()).unwrap();
+
+        let hub = NodeId::new();
+        let mut stored = Vec::new();
+        for _ in 0..25 {
+            let edge = Edge::new(NodeId::new(), hub, EdgeType::PartOf);
+            backend.store_edge(&edge).unwrap();
+            stored.push(edge.id);
+        }
+
+        let mut seen = Vec::new();
+        let mut cursor = None;
+        let mut pages = 0;
+        loop {
+            let page = backend
+                .get_incoming_edges_page(&hub, cursor.as_ref(), 10)
+                .unwrap();
+            pages += 1;
+            seen.extend(page.edges.iter().map(|e| e.id));
+            match page.next_cursor {
+                Some(next) => cursor = Some(next),
+                None => break,
+            }
+        }
+        assert_eq!(pages, 3);
+        assert_eq!(seen.len(), 25);
+        stored.sort_by_key(EdgeId::to_bytes);
+        assert_eq!(seen, stored);
+
+        assert!(backend
+            .get_outgoing_edges_page(&hub, None, 10)
+            .unwrap()
+            .edges
+            .is_empty());
+        assert_eq!(
+            backend.node_degree(&hub).unwrap(),
+            NodeDegree {
+                outgoing: 0,
+                incoming: 25
+            }
+        );
+
+        // Hard-deleted edges leave index entries behind but are not counted
+        backend.delete_edge(&stored[0]).unwrap();
+        assert_eq!(backend.node_degree(&hub).unwrap().total(), 24);
+    }
+
     #[test]
     fn test_stats() {
         let dir = tempdir().unwrap();
@@ -332,5 +2582,331 @@ mod tests {
         let stats = backend.stats().unwrap();
         assert_eq!(stats.node_count, 1);
         assert!(stats.storage_bytes > 0);
+        assert_eq!(stats.pending_writes, 1);
+        assert!(stats.last_flush_duration.is_none());
+        let nodes = stats.trees.iter().find(|t| t.name == "nodes").unwrap();
+        assert_eq!(nodes.entries, 1);
+        assert!(stats.trees.iter().any(|t| t.name == "session_index"));
+
+        backend.flush().unwrap();
+        let stats = backend.stats().unwrap();
+        assert_eq!(stats.pending_writes, 0);
+        assert!(stats.last_flush_duration.is_some());
+    }
+
+    #[test]
+    fn test_trash_restore_and_purge() {
+        let dir = tempdir().unwrap();
+        let backend = SledBackend::open(dir.path()).unwrap();
+
+        let session = ConversationSession::new();
+        backend.store_node(&Node::Session(session.clone())).unwrap();
+        let prompt = PromptNode::new(session.id, "Remember me".to_string());
+        let prompt_id = prompt.id;
+        backend.store_node(&Node::Prompt(prompt)).unwrap();
+        let edge = Edge::new(prompt_id, session.node_id, EdgeType::PartOf);
+        backend.store_edge(&edge).unwrap();
+
+        let trashed = backend.trash_node(&prompt_id).unwrap().unwrap();
+        assert_eq!(trashed.session_id, Some(session.id));
+        assert!(backend.get_node(&prompt_id).unwrap().is_none());
+        assert_eq!(backend.get_session_nodes(&session.id).unwrap().len(), 1);
+        assert_eq!(backend.trashed_nodes().unwrap().len(), 1);
+        assert!(backend.trash_node(&prompt_id).unwrap().is_none());
+
+        backend.restore_node(&prompt_id).unwrap().unwrap();
+        assert!(backend.get_node(&prompt_id).unwrap().is_some());
+        assert_eq!(backend.get_session_nodes(&session.id).unwrap().len(), 2);
+        assert!(backend.trashed_nodes().unwrap().is_empty());
+
+        backend.trash_node(&prompt_id).unwrap();
+        let cutoff = trashed.deleted_at;
+        assert!(backend
+            .purge_trash(cutoff, &HashSet::new())
+            .unwrap()
+            .is_empty());
+        let purged = backend.purge_trash(Utc::now(), &HashSet::new()).unwrap();
+        assert_eq!(purged, vec![prompt_id]);
+        assert!(backend.restore_node(&prompt_id).unwrap().is_none());
+        assert!(backend.get_edge(&edge.id).unwrap().is_none());
+        assert!(backend
+            .get_incoming_edges(&session.node_id)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_node_versions_purged_with_node() {
+        let dir = tempdir().unwrap();
+        let backend = SledBackend::open(dir.path()).unwrap();
+
+        let session = ConversationSession::new();
+        let prompt = PromptNode::new(session.id, "First draft".to_string());
+        let prompt_id = prompt.id;
+        backend.store_node(&Node::Prompt(prompt.clone())).unwrap();
+        let versions: Vec<NodeVersion> = (1..=2)
+            .map(|version| NodeVersion {
+                id: NodeId::new(),
+                node_id: prompt_id,
+                version,
+                node: Node::Prompt(prompt.clone()),
+                reason: format!("edit {version}"),
+                replaced_at: Utc::now(),
+            })
+            .collect();
+        for version in versions.iter().rev() {
+            backend.store_node_version(version).unwrap();
+        }
+        let edge = Edge::new(versions[1].id, prompt_id, EdgeType::PreviousVersionOf);
+        backend.store_edge(&edge).unwrap();
+
+        let stored = backend.node_versions(&prompt_id).unwrap();
+        assert_eq!(stored.iter().map(|v| v.version).collect::<Vec<_>>(), [1, 2]);
+        assert_eq!(backend.node_version_ids().unwrap().len(), 2);
+
+        backend.trash_node(&prompt_id).unwrap();
+        backend.purge_trash(Utc::now(), &HashSet::new()).unwrap();
+        assert!(backend.node_versions(&prompt_id).unwrap().is_empty());
+        assert!(backend.get_edge(&edge.id).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_list_sessions_and_compact_indexes() {
+        let dir = tempdir().unwrap();
+        let backend = SledBackend::open(dir.path()).unwrap();
+
+        let session = ConversationSession::new();
+        backend.store_node(&Node::Session(session.clone())).unwrap();
+        let prompt = PromptNode::new(session.id, "Hello".to_string());
+        let prompt_id = prompt.id;
+        backend.store_node(&Node::Prompt(prompt)).unwrap();
+        let edge = Edge::new(prompt_id, session.node_id, EdgeType::PartOf);
+        backend.store_edge(&edge).unwrap();
+
+        let sessions = backend.list_sessions().unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].id, session.id);
+
+        assert_eq!(backend.compact_indexes().unwrap(), 0);
+        backend.delete_node(&prompt_id).unwrap();
+        backend.delete_edge(&edge.id).unwrap();
+        // The prompt's session and content index entries plus the edge's two
+        // index entries
+        assert_eq!(backend.compact_indexes().unwrap(), 4);
+        assert_eq!(backend.compact_indexes().unwrap(), 0);
+        assert_eq!(backend.list_sessions().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_rebuild_indexes() {
+        let dir = tempdir().unwrap();
+        let backend = SledBackend::open(dir.path()).unwrap();
+
+        let session = ConversationSession::new();
+        backend.store_node(&Node::Session(session.clone())).unwrap();
+        let prompt = PromptNode::new(session.id, "Hello".to_string());
+        backend.store_node(&Node::Prompt(prompt.clone())).unwrap();
+        let response = ResponseNode::new(prompt.id, "Hi".to_string(), TokenUsage::new(1, 1));
+        backend.store_node(&Node::Response(response)).unwrap();
+        let edge = Edge::new(prompt.id, session.node_id, EdgeType::PartOf);
+        backend.store_edge(&edge).unwrap();
+
+        // Lose every index, as if writes had crashed before indexing, and
+        // leave a dangling entry behind
+        for index in [
+            &backend.session_index,
+            &backend.content_index,
+            &backend.outgoing_edges_index,
+            &backend.incoming_edges_index,
+        ] {
+            index.clear().unwrap();
+        }
+        let stray = SledBackend::build_index_key(&session.id.to_bytes(), &NodeId::new().to_bytes());
+        backend.session_index.insert(stray, &[]).unwrap();
+        assert!(backend.get_outgoing_edges(&prompt.id).unwrap().is_empty());
+
+        let report = backend.rebuild_indexes().unwrap();
+        assert_eq!(report.nodes_scanned, 3);
+        assert_eq!(report.edges_scanned, 1);
+        assert_eq!(report.session_entries, 3);
+        assert_eq!(report.content_entries, 1);
+        assert_eq!(report.edge_entries, 2);
+
+        assert_eq!(backend.session_index.len(), 3);
+        assert_eq!(backend.get_session_nodes(&session.id).unwrap().len(), 3);
+        assert_eq!(
+            backend
+                .find_prompts_by_content("Hello", Some(&session.id))
+                .unwrap()[0]
+                .id,
+            prompt.id
+        );
+        assert_eq!(backend.get_outgoing_edges(&prompt.id).unwrap().len(), 1);
+        assert_eq!(
+            backend.get_incoming_edges(&session.node_id).unwrap().len(),
+            1
+        );
+        assert_eq!(backend.compact_indexes().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_cold_tier_keeps_concurrent_writes() {
+        let dir = tempdir().unwrap();
+        let backend = Arc::new(SledBackend::open(dir.path()).unwrap());
+        let session = ConversationSession::new();
+        let prompts: Vec<_> = (0..COLD_TIER_CHUNK + 500)
+            .map(|i| PromptNode::new(session.id, format!("Prompt {i}")))
+            .collect();
+        backend
+            .store_nodes_batch(
+                &prompts
+                    .iter()
+                    .cloned()
+                    .map(Node::Prompt)
+                    .collect::<Vec<_>>(),
+            )
+            .unwrap();
+        let cutoff = Utc::now() + chrono::Duration::milliseconds(1);
+        std::thread::sleep(std::time::Duration::from_millis(2));
+
+        // Rewrite every prompt while tiering runs; each rewrite must survive
+        let writer = {
+            let backend = Arc::clone(&backend);
+            let prompts = prompts.clone();
+            std::thread::spawn(move || {
+                for mut prompt in prompts {
+                    prompt.content.push_str(" (edited)");
+                    backend.store_node(&Node::Prompt(prompt)).unwrap();
+                }
+            })
+        };
+        backend.tier_cold(cutoff).unwrap();
+        writer.join().unwrap();
+
+        for prompt in &prompts {
+            let Some(Node::Prompt(stored)) = backend.get_node(&prompt.id).unwrap() else {
+                panic!("prompt {} lost", prompt.id);
+            };
+            assert_eq!(stored.content, format!("{} (edited)", prompt.content));
+        }
+        assert_eq!(
+            backend.nodes.len() + backend.cold_nodes.len(),
+            prompts.len()
+        );
+    }
+
+    #[test]
+    fn test_cold_tier() {
+        let dir = tempdir().unwrap();
+        let cold_dir = tempdir().unwrap();
+        let backend = SledBackend::open(dir.path()).unwrap();
+
+        let session = ConversationSession::new();
+        backend.store_node(&Node::Session(session.clone())).unwrap();
+        let old = PromptNode::new(session.id, "Old".to_string());
+        backend.store_node(&Node::Prompt(old.clone())).unwrap();
+        let edge = Edge::new(old.id, session.node_id, EdgeType::PartOf);
+        backend.store_edge(&edge).unwrap();
+        let cutoff = Utc::now() + chrono::Duration::milliseconds(1);
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        let recent = PromptNode::new(session.id, "Recent".to_string());
+        backend.store_node(&Node::Prompt(recent.clone())).unwrap();
+
+        let report = backend.tier_cold(cutoff).unwrap();
+        assert_eq!((report.nodes_moved, report.edges_moved), (2, 1));
+        assert_eq!((backend.nodes.len(), backend.cold_nodes.len()), (1, 2));
+
+        // Moving the cold tier to its own database takes what it holds along
+        let backend = backend
+            .with_cold_storage(&ColdStorageConfig::new().with_path(cold_dir.path()))
+            .unwrap();
+        assert!(backend.db.open_tree("cold_nodes").unwrap().is_empty());
+        assert_eq!(backend.cold_nodes.len(), 2);
+
+        // Cold records are read as if they never moved
+        assert_eq!(backend.get_node(&old.id).unwrap().unwrap().id(), old.id);
+        assert_eq!(backend.get_edge(&edge.id).unwrap().unwrap().id, edge.id);
+        assert_eq!(backend.get_session_nodes(&session.id).unwrap().len(), 3);
+        assert_eq!(backend.get_outgoing_edges(&old.id).unwrap().len(), 1);
+        assert_eq!(backend.all_nodes().unwrap().len(), 3);
+        assert_eq!(backend.all_edges().unwrap().len(), 1);
+        let stats = backend.stats().unwrap();
+        assert_eq!((stats.node_count, stats.edge_count), (3, 1));
+        assert_eq!(backend.compact_indexes().unwrap(), 0);
+
+        // Rewriting a cold record brings it back, deleting one removes it
+        backend.store_node(&Node::Prompt(old.clone())).unwrap();
+        assert!(backend.nodes.contains_key(old.id.to_bytes()).unwrap());
+        assert_eq!(backend.cold_nodes.len(), 1);
+        backend.delete_edge(&edge.id).unwrap();
+        assert!(backend.get_edge(&edge.id).unwrap().is_none());
+        assert!(backend.cold_edges.is_empty());
+
+        // Tiering is by age, so the promoted node goes back
+        assert_eq!(backend.tier_cold(cutoff).unwrap().total(), 1);
+        assert_eq!(backend.nodes.len(), 1);
+    }
+
+    #[test]
+    fn test_namespaces() {
+        let dir = tempdir().unwrap();
+        let default = SledBackend::open(dir.path()).unwrap();
+        let project = default.in_namespace("project-a").unwrap();
+        assert_eq!(project.namespace(), "project-a");
+        assert!(default.in_namespace("Project A").is_err());
+
+        let session = ConversationSession::new();
+        project.store_node(&Node::Session(session.clone())).unwrap();
+        assert!(project.get_node(&session.node_id).unwrap().is_some());
+        assert!(default.get_node(&session.node_id).unwrap().is_none());
+        assert_eq!(project.stats().unwrap().node_count, 1);
+        assert_eq!(default.stats().unwrap().node_count, 0);
+
+        assert_eq!(default.list_namespaces(), vec!["default", "project-a"]);
+        assert!(project.drop_namespace("project-a").is_err());
+        assert!(project.drop_namespace(DEFAULT_NAMESPACE).is_err());
+        drop(project);
+        assert!(default.drop_namespace("project-a").unwrap());
+        assert!(!default.drop_namespace("project-a").unwrap());
+        assert_eq!(default.list_namespaces(), vec!["default"]);
+    }
+
+    #[test]
+    fn test_aliases() {
+        let dir = tempdir().unwrap();
+        let backend = SledBackend::open(dir.path()).unwrap();
+        let session = SessionId::new();
+        let node = NodeId::new();
+
+        assert!(backend.resolve_alias("ext:conv-42").unwrap().is_none());
+        assert!(backend
+            .set_alias("ext:conv-42", AliasTarget::Session(session))
+            .unwrap()
+            .is_none());
+        backend
+            .set_alias("ext:msg-1", AliasTarget::Node(node))
+            .unwrap();
+        assert!(backend.set_alias("", AliasTarget::Node(node)).is_err());
+        assert_eq!(
+            backend.resolve_alias("ext:conv-42").unwrap(),
+            Some(AliasTarget::Session(session))
+        );
+
+        let aliases = backend.list_aliases("ext:").unwrap();
+        assert_eq!(aliases.len(), 2);
+        assert_eq!(aliases[0].0, "ext:conv-42");
+        assert!(backend.list_aliases("other:").unwrap().is_empty());
+
+        assert_eq!(
+            backend
+                .set_alias("ext:conv-42", AliasTarget::Node(node))
+                .unwrap(),
+            Some(AliasTarget::Session(session))
+        );
+        assert_eq!(
+            backend.remove_alias("ext:conv-42").unwrap(),
+            Some(AliasTarget::Node(node))
+        );
+        assert!(backend.resolve_alias("ext:conv-42").unwrap().is_none());
     }
 }
@@ -0,0 +1,219 @@
+//! Read-only copy of a graph pinned to a point in time
+//!
+//! Sled has no point-in-time reads, so [`SledBackend::snapshot`](super::SledBackend::snapshot)
+//! collects the raw node and edge records and the session index while writes
+//! are held off, then decodes and indexes them in memory. Readers of the resulting [`SnapshotBackend`] never observe a
+//! write that finished after the snapshot was taken, nor half of a batch.
+
+use super::{read_only, AsyncStorageBackend, StorageStats};
+use crate::{ConversationSession, Edge, EdgeId, Node, NodeId, PromptNode, Result, SessionId};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+#[derive(Debug, Default)]
+struct SnapshotData {
+    nodes: HashMap<NodeId, Node>,
+    edges: HashMap<EdgeId, Edge>,
+    /// Nodes of each session, in the source backend's index order
+    session_index: HashMap<SessionId, Vec<NodeId>>,
+    /// Sessions in the source backend's index order
+    sessions: Vec<SessionId>,
+    outgoing: HashMap<NodeId, Vec<EdgeId>>,
+    incoming: HashMap<NodeId, Vec<EdgeId>>,
+}
+
+/// In-memory, read-only storage backend holding a copy of the graph
+///
+/// Cloning is cheap: clones share the copied data.
+#[derive(Debug, Clone)]
+pub struct SnapshotBackend {
+    taken_at: DateTime<Utc>,
+    stats: StorageStats,
+    change_cursor: Option<u64>,
+    data: Arc<SnapshotData>,
+}
+
+impl SnapshotBackend {
+    /// Build a snapshot from the contents of a backend
+    ///
+    /// `edges` are indexed in the order given; `session_index` lists the
+    /// nodes of each session as the source backend indexed them.
+    pub(crate) fn new(
+        nodes: Vec<Node>,
+        edges: Vec<Edge>,
+        session_index: Vec<(SessionId, NodeId)>,
+        stats: StorageStats,
+        change_cursor: Option<u64>,
+    ) -> Self {
+        let mut data = SnapshotData {
+            nodes: nodes.into_iter().map(|node| (node.id(), node)).collect(),
+            ..SnapshotData::default()
+        };
+        for (session_id, node_id) in session_index {
+            data.session_index
+                .entry(session_id)
+                .or_insert_with(|| {
+                    data.sessions.push(session_id);
+                    Vec::new()
+                })
+                .push(node_id);
+        }
+        for edge in edges {
+            data.outgoing.entry(edge.from).or_default().push(edge.id);
+            data.incoming.entry(edge.to).or_default().push(edge.id);
+            data.edges.insert(edge.id, edge);
+        }
+        Self {
+            taken_at: Utc::now(),
+            stats,
+            change_cursor,
+            data: Arc::new(data),
+        }
+    }
+
+    /// When the snapshot was taken
+    pub fn taken_at(&self) -> DateTime<Utc> {
+        self.taken_at
+    }
+
+    /// Cursor of the last captured change included in the snapshot
+    pub fn change_cursor(&self) -> Option<u64> {
+        self.change_cursor
+    }
+
+    fn session_node_ids(&self, session_id: &SessionId) -> impl Iterator<Item = &NodeId> + '_ {
+        self.data
+            .session_index
+            .get(session_id)
+            .into_iter()
+            .flatten()
+    }
+
+    fn edges_in(&self, index: &HashMap<NodeId, Vec<EdgeId>>, node_id: &NodeId) -> Vec<Edge> {
+        index
+            .get(node_id)
+            .into_iter()
+            .flatten()
+            .filter_map(|id| self.data.edges.get(id).cloned())
+            .collect()
+    }
+}
+
+#[async_trait]
+impl AsyncStorageBackend for SnapshotBackend {
+    async fn store_node(&self, _node: &Node) -> Result<()> {
+        Err(read_only())
+    }
+
+    async fn get_node(&self, id: &NodeId) -> Result<Option<Node>> {
+        Ok(self.data.nodes.get(id).cloned())
+    }
+
+    async fn delete_node(&self, _id: &NodeId) -> Result<()> {
+        Err(read_only())
+    }
+
+    async fn store_edge(&self, _edge: &Edge) -> Result<()> {
+        Err(read_only())
+    }
+
+    async fn get_edge(&self, id: &EdgeId) -> Result<Option<Edge>> {
+        Ok(self.data.edges.get(id).cloned())
+    }
+
+    async fn delete_edge(&self, _id: &EdgeId) -> Result<()> {
+        Err(read_only())
+    }
+
+    async fn get_session_nodes(&self, session_id: &SessionId) -> Result<Vec<Node>> {
+        Ok(self
+            .session_node_ids(session_id)
+            .filter_map(|id| self.data.nodes.get(id).cloned())
+            .collect())
+    }
+
+    async fn get_outgoing_edges(&self, node_id: &NodeId) -> Result<Vec<Edge>> {
+        Ok(self.edges_in(&self.data.outgoing, node_id))
+    }
+
+    async fn get_incoming_edges(&self, node_id: &NodeId) -> Result<Vec<Edge>> {
+        Ok(self.edges_in(&self.data.incoming, node_id))
+    }
+
+    async fn flush(&self) -> Result<()> {
+        Ok(())
+    }
+
+    async fn stats(&self) -> Result<StorageStats> {
+        Ok(self.stats.clone())
+    }
+
+    async fn store_nodes_batch(&self, _nodes: &[Node]) -> Result<Vec<NodeId>> {
+        Err(read_only())
+    }
+
+    async fn store_edges_batch(&self, _edges: &[Edge]) -> Result<Vec<EdgeId>> {
+        Err(read_only())
+    }
+
+    async fn count_session_nodes(&self, session_id: &SessionId) -> Result<usize> {
+        Ok(self
+            .session_node_ids(session_id)
+            .filter(|id| self.data.nodes.contains_key(id))
+            .count())
+    }
+
+    async fn list_sessions(&self) -> Result<Vec<ConversationSession>> {
+        Ok(self
+            .data
+            .sessions
+            .iter()
+            .flat_map(|session_id| self.session_node_ids(session_id))
+            .filter_map(|id| match self.data.nodes.get(id) {
+                Some(Node::Session(session)) => Some(session.clone()),
+                _ => None,
+            })
+            .collect())
+    }
+
+    async fn find_prompts_by_content(
+        &self,
+        content: &str,
+        session_id: Option<&SessionId>,
+    ) -> Result<Vec<PromptNode>> {
+        let mut prompts: Vec<PromptNode> = self
+            .data
+            .nodes
+            .values()
+            .filter_map(|node| match node {
+                Node::Prompt(prompt)
+                    if prompt.content == content
+                        && session_id.is_none_or(|id| prompt.session_id == *id) =>
+                {
+                    Some(prompt.clone())
+                }
+                _ => None,
+            })
+            .collect();
+        prompts.sort_by_key(|prompt| prompt.timestamp);
+        Ok(prompts)
+    }
+
+    async fn all_nodes(&self) -> Result<Vec<Node>> {
+        Ok(self.data.nodes.values().cloned().collect())
+    }
+
+    async fn all_edges(&self) -> Result<Vec<Edge>> {
+        Ok(self.data.edges.values().cloned().collect())
+    }
+
+    async fn latest_change_cursor(&self) -> Result<Option<u64>> {
+        Ok(self.change_cursor)
+    }
+
+    async fn snapshot(&self) -> Result<SnapshotBackend> {
+        Ok(self.clone())
+    }
+}
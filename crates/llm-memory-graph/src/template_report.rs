@@ -0,0 +1,434 @@
+//! Periodic usage reports per prompt template
+//!
+//! A [`TemplateReporter`] summarizes, for every template, the prompts
+//! instantiated from it during a period: how many there were and how that
+//! compares with the previous report, and the average completion tokens,
+//! latency and cost of their responses, the share of responses that failed
+//! (finish reasons in [`FAILED_FINISH_REASONS`]) and the share of prompts
+//! carrying an annotation, such as a prompt injection finding.
+//!
+//! Each report covers the time since the previous one and is stored as a
+//! [`TEMPLATE_REPORT_TYPE`] custom node referencing the templates it covers,
+//! see [`AsyncMemoryGraph::add_template_usage_report`]. With a
+//! [`WebhookPlugin`] it is also posted as a [`TEMPLATE_REPORT_TYPE`] event.
+//! [`TemplateReporter::start`] runs it on a schedule.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use llm_memory_graph::engine::AsyncMemoryGraph;
+//! use llm_memory_graph::plugin::WebhookPlugin;
+//! use llm_memory_graph::template_report::TemplateReporter;
+//! use llm_memory_graph::transcript::TokenPricing;
+//! use llm_memory_graph::Config;
+//! use std::sync::Arc;
+//! use std::time::Duration;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let graph = Arc::new(AsyncMemoryGraph::open(Config::default()).await?);
+//! let reporter = Arc::new(
+//!     TemplateReporter::new(Arc::clone(&graph))
+//!         .with_pricing(TokenPricing::per_1k_tokens(0.003, 0.015))
+//!         .with_webhook(Arc::new(WebhookPlugin::new("https://hooks.example.com/prompts")?)),
+//! );
+//! let handle = Arc::clone(&reporter).start(Duration::from_hours(24));
+//! # handle.stop().await;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::engine::AsyncMemoryGraph;
+use crate::finetune::FAILED_FINISH_REASONS;
+use crate::plugin::WebhookPlugin;
+use crate::replication::ReplicationHandle;
+use crate::shipping::spawn_loop;
+use crate::transcript::TokenPricing;
+use crate::{AliasTarget, EdgeType, Node, NodeId, PromptTemplate, Result, TemplateId};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::ops::Range;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Custom type name of template usage report nodes, and event name of the
+/// reports posted to webhooks
+pub const TEMPLATE_REPORT_TYPE: &str = "template_usage_report";
+
+/// Alias pointing at the latest template usage report node
+pub const LATEST_TEMPLATE_REPORT_ALIAS: &str = "template_usage_report:latest";
+
+/// Period the first report covers when there is no earlier one
+pub const DEFAULT_FIRST_PERIOD: Duration = Duration::from_hours(24);
+
+/// How many templates are listed per page
+const LIST_PAGE_SIZE: usize = 500;
+
+/// Usage of one template during a report's period
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TemplateUsage {
+    /// The template
+    pub template_id: TemplateId,
+    /// Node of the template
+    pub node_id: NodeId,
+    /// Template name
+    pub name: String,
+    /// Template version when the report was made
+    pub version: String,
+    /// Prompts instantiated from the template during the period
+    pub uses: u64,
+    /// Change in `uses` since the previous report
+    pub uses_change: i64,
+    /// Prompts instantiated from the template ever
+    pub total_uses: u64,
+    /// Responses to the period's prompts
+    pub responses: u64,
+    /// Average completion tokens per response
+    pub avg_response_tokens: f64,
+    /// Average response latency in milliseconds
+    pub avg_latency_ms: f64,
+    /// Average cost per response, if the reporter has pricing
+    pub avg_cost: Option<f64>,
+    /// Share of responses that failed
+    pub failure_rate: f64,
+    /// Share of prompts annotated, on the prompt or one of its responses
+    pub annotation_rate: f64,
+}
+
+/// Template usage over one period
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TemplateUsageReport {
+    /// Start of the period, inclusive
+    pub period_start: DateTime<Utc>,
+    /// End of the period, exclusive
+    pub period_end: DateTime<Utc>,
+    /// Templates used during the period or the previous one, most used first
+    pub templates: Vec<TemplateUsage>,
+}
+
+impl TemplateUsageReport {
+    /// Usage of a template, if the report covers it
+    pub fn template(&self, template_id: TemplateId) -> Option<&TemplateUsage> {
+        self.templates.iter().find(|t| t.template_id == template_id)
+    }
+}
+
+/// Makes template usage reports, see the [module docs](self)
+pub struct TemplateReporter {
+    graph: Arc<AsyncMemoryGraph>,
+    pricing: Option<TokenPricing>,
+    webhook: Option<Arc<WebhookPlugin>>,
+    first_period: Duration,
+}
+
+impl TemplateReporter {
+    /// Report on the templates of `graph`
+    pub fn new(graph: Arc<AsyncMemoryGraph>) -> Self {
+        Self {
+            graph,
+            pricing: None,
+            webhook: None,
+            first_period: DEFAULT_FIRST_PERIOD,
+        }
+    }
+
+    /// Price responses with `pricing` to report average costs
+    pub fn with_pricing(mut self, pricing: TokenPricing) -> Self {
+        self.pricing = Some(pricing);
+        self
+    }
+
+    /// Post every stored report to `webhook`
+    pub fn with_webhook(mut self, webhook: Arc<WebhookPlugin>) -> Self {
+        self.webhook = Some(webhook);
+        self
+    }
+
+    /// Cover `period` in the first report, when there is no earlier one
+    pub fn with_first_period(mut self, period: Duration) -> Self {
+        self.first_period = period;
+        self
+    }
+
+    /// The latest stored report, if any
+    pub async fn latest(&self) -> Result<Option<TemplateUsageReport>> {
+        let Some(AliasTarget::Node(node_id)) = self
+            .graph
+            .resolve_alias(LATEST_TEMPLATE_REPORT_ALIAS)
+            .await?
+        else {
+            return Ok(None);
+        };
+        match self.graph.get_node(&node_id).await? {
+            Some(Node::Custom(node)) if node.type_name == TEMPLATE_REPORT_TYPE => {
+                Ok(Some(serde_json::from_value(node.payload)?))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Report on the time since the previous report, store the report and
+    /// post it to the webhook
+    ///
+    /// A failed webhook delivery is logged; the report is stored regardless.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the graph cannot be read or the report cannot be
+    /// stored.
+    pub async fn run(&self) -> Result<(NodeId, TemplateUsageReport)> {
+        let previous = self.latest().await?;
+        let end = Utc::now();
+        let start = previous.as_ref().map_or_else(
+            || end - chrono::Duration::from_std(self.first_period).unwrap_or_default(),
+            |previous| previous.period_end,
+        );
+        let report = self.generate(start..end, previous.as_ref()).await?;
+        let node_id = self.graph.add_template_usage_report(&report).await?;
+
+        if let Some(webhook) = &self.webhook {
+            let payload = serde_json::to_value(&report)?;
+            if let Err(e) = webhook.post(TEMPLATE_REPORT_TYPE, &payload).await {
+                tracing::warn!("Template usage report delivery failed: {}", e);
+            }
+        }
+        tracing::info!(
+            "Reported usage of {} templates from {} to {}",
+            report.templates.len(),
+            report.period_start,
+            report.period_end
+        );
+        Ok((node_id, report))
+    }
+
+    /// Make a report on `period` without storing it, comparing with
+    /// `previous` if given
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the graph cannot be read.
+    pub async fn generate(
+        &self,
+        period: Range<DateTime<Utc>>,
+        previous: Option<&TemplateUsageReport>,
+    ) -> Result<TemplateUsageReport> {
+        let previous_uses: HashMap<TemplateId, u64> = previous
+            .map(|report| {
+                report
+                    .templates
+                    .iter()
+                    .map(|t| (t.template_id, t.uses))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let mut templates = Vec::new();
+        for template in self.templates().await? {
+            let before = previous_uses.get(&template.id).copied().unwrap_or(0);
+            let usage = self.usage(&template, &period, before).await?;
+            if usage.uses > 0 || before > 0 {
+                templates.push(usage);
+            }
+        }
+        templates.sort_by(|a, b| b.uses.cmp(&a.uses).then_with(|| a.name.cmp(&b.name)));
+        Ok(TemplateUsageReport {
+            period_start: period.start,
+            period_end: period.end,
+            templates,
+        })
+    }
+
+    /// Usage of one template during `period`
+    async fn usage(
+        &self,
+        template: &PromptTemplate,
+        period: &Range<DateTime<Utc>>,
+        previous_uses: u64,
+    ) -> Result<TemplateUsage> {
+        let mut usage = TemplateUsage {
+            template_id: template.id,
+            node_id: template.node_id,
+            name: template.name.clone(),
+            version: template.version.to_string(),
+            uses: 0,
+            uses_change: 0,
+            total_uses: 0,
+            responses: 0,
+            avg_response_tokens: 0.0,
+            avg_latency_ms: 0.0,
+            avg_cost: None,
+            failure_rate: 0.0,
+            annotation_rate: 0.0,
+        };
+        let (mut tokens, mut latency, mut cost, mut failed, mut annotated) =
+            (0u64, 0u64, 0.0, 0u64, 0u64);
+
+        for edge in self.graph.get_incoming_edges(&template.node_id).await? {
+            if edge.edge_type != EdgeType::Instantiates {
+                continue;
+            }
+            let Some(Node::Prompt(prompt)) = self.graph.get_node(&edge.from).await? else {
+                continue;
+            };
+            usage.total_uses += 1;
+            if !period.contains(&prompt.timestamp) {
+                continue;
+            }
+            usage.uses += 1;
+
+            let mut is_annotated = !self.graph.get_annotations(&prompt.id).await?.is_empty();
+            for edge in self.graph.get_incoming_edges(&prompt.id).await? {
+                if edge.edge_type != EdgeType::RespondsTo {
+                    continue;
+                }
+                let Some(Node::Response(response)) = self.graph.get_node(&edge.from).await? else {
+                    continue;
+                };
+                usage.responses += 1;
+                tokens += u64::from(response.usage.completion_tokens);
+                latency += response.metadata.latency_ms;
+                if let Some(pricing) = &self.pricing {
+                    cost += pricing.cost(
+                        u64::from(response.usage.prompt_tokens),
+                        u64::from(response.usage.completion_tokens),
+                    );
+                }
+                if FAILED_FINISH_REASONS.contains(&response.metadata.finish_reason.as_str()) {
+                    failed += 1;
+                }
+                if !is_annotated {
+                    is_annotated = !self.graph.get_annotations(&response.id).await?.is_empty();
+                }
+            }
+            annotated += u64::from(is_annotated);
+        }
+
+        usage.uses_change = usage.uses.cast_signed() - previous_uses.cast_signed();
+        if usage.responses > 0 {
+            let responses = usage.responses as f64;
+            usage.avg_response_tokens = tokens as f64 / responses;
+            usage.avg_latency_ms = latency as f64 / responses;
+            usage.failure_rate = failed as f64 / responses;
+            usage.avg_cost = self.pricing.as_ref().map(|_| cost / responses);
+        }
+        if usage.uses > 0 {
+            usage.annotation_rate = annotated as f64 / usage.uses as f64;
+        }
+        Ok(usage)
+    }
+
+    /// Every template in the graph
+    async fn templates(&self) -> Result<Vec<PromptTemplate>> {
+        let mut templates = Vec::new();
+        let mut cursor = None;
+        loop {
+            let page = self
+                .graph
+                .list_templates_page(cursor.as_ref(), LIST_PAGE_SIZE)
+                .await?;
+            templates.extend(page.items);
+            match page.next_cursor {
+                Some(next) => cursor = Some(next),
+                None => return Ok(templates),
+            }
+        }
+    }
+
+    /// Report every `interval` on a background task
+    ///
+    /// The loop stops when the handle is stopped or dropped, or when the
+    /// graph is closed. Failed reports are logged and retried.
+    pub fn start(self: Arc<Self>, interval: Duration) -> ReplicationHandle {
+        let closing = self.graph.shutdown_signal();
+        spawn_loop(
+            Arc::downgrade(&self),
+            closing,
+            interval,
+            |reporter| async move {
+                if let Err(e) = reporter.run().await {
+                    tracing::warn!("Template usage report failed: {}", e);
+                }
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugin::webhook::tests::serve;
+    use crate::{Config, ResponseMetadata, TokenUsage};
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_report_and_deliver() {
+        let dir = tempdir().unwrap();
+        let graph = Arc::new(
+            AsyncMemoryGraph::open(Config::new(dir.path()))
+                .await
+                .unwrap(),
+        );
+        let template = PromptTemplate::new("greet".to_string(), "Hi {{name}}".to_string(), vec![]);
+        let (template_id, template_node) = (template.id, template.node_id);
+        graph.create_template(template).await.unwrap();
+        let idle = PromptTemplate::new("idle".to_string(), "Unused".to_string(), vec![]);
+        graph.create_template(idle).await.unwrap();
+
+        let session = graph.create_session().await.unwrap();
+        for (i, finish_reason) in ["stop", "error"].into_iter().enumerate() {
+            let prompt = graph
+                .add_prompt(session.id, format!("Hi {i}"), None)
+                .await
+                .unwrap();
+            graph
+                .link_prompt_to_template(prompt, template_node)
+                .await
+                .unwrap();
+            let metadata = ResponseMetadata {
+                finish_reason: finish_reason.to_string(),
+                latency_ms: 100 * (i as u64 + 1),
+                ..ResponseMetadata::default()
+            };
+            graph
+                .add_response(
+                    prompt,
+                    "Hello".to_string(),
+                    TokenUsage::new(1000, 10 * (i as u32 + 1)),
+                    Some(metadata),
+                )
+                .await
+                .unwrap();
+        }
+
+        let (url, mut requests) = serve(200).await;
+        let reporter = TemplateReporter::new(Arc::clone(&graph))
+            .with_pricing(TokenPricing::per_1k_tokens(1.0, 0.0))
+            .with_webhook(Arc::new(WebhookPlugin::new(url).unwrap()));
+        let (node_id, report) = reporter.run().await.unwrap();
+
+        assert_eq!(report.templates.len(), 1);
+        let usage = report.template(template_id).unwrap();
+        assert_eq!((usage.uses, usage.uses_change, usage.responses), (2, 2, 2));
+        assert!((usage.avg_response_tokens - 15.0).abs() < 1e-9);
+        assert!((usage.avg_latency_ms - 150.0).abs() < 1e-9);
+        assert!((usage.avg_cost.unwrap() - 1.0).abs() < 1e-9);
+        assert!((usage.failure_rate - 0.5).abs() < 1e-9);
+        assert!(usage.annotation_rate.abs() < 1e-9);
+
+        let (_, body) = requests.recv().await.unwrap();
+        assert_eq!(body["event"], TEMPLATE_REPORT_TYPE);
+        assert_eq!(body["payload"]["templates"][0]["name"], "greet");
+        assert_eq!(reporter.latest().await.unwrap(), Some(report.clone()));
+        let references = graph.get_outgoing_edges(&node_id).await.unwrap();
+        assert_eq!(references[0].to, template_node);
+
+        // The next report starts where this one ended and sees the drop
+        let (_, next) = reporter.run().await.unwrap();
+        assert_eq!(next.period_start, report.period_end);
+        let usage = next.template(template_id).unwrap();
+        assert_eq!(
+            (usage.uses, usage.uses_change, usage.total_uses),
+            (0, -2, 2)
+        );
+    }
+}
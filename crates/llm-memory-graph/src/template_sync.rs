@@ -0,0 +1,892 @@
+//! Prompt templates synced from a git repository
+//!
+//! Teams that keep prompts as code can make their repository the source of
+//! truth for the graph's [`PromptTemplate`]s. [`TemplateSync`] pulls a
+//! checkout (cloning it first if needed), reads every Markdown and YAML file
+//! under it, and creates or updates one template per file:
+//!
+//! - a Markdown file (`.md`) is the template text, optionally preceded by YAML
+//!   front-matter between `---` lines
+//! - a YAML file (`.yaml`, `.yml`) holds the same fields, with the text under
+//!   `template`
+//!
+//! ```text
+//! ---
+//! name: summarize
+//! description: Summarize a document
+//! author: docs-team
+//! tags: [summaries]
+//! parent: base-instructions
+//! bump: minor
+//! variables:
+//!   - name: document
+//!     description: Text to summarize
+//!   - name: length
+//!     required: false
+//!     default: short
+//! ---
+//! Summarize {{document}} in a {{length}} paragraph.
+//! ```
+//!
+//! Every field is optional: `name` defaults to the file name, undeclared
+//! `{{variables}}` are required strings, and any other scalar field is kept in
+//! the template's metadata. A file is tied to its template by its path in the
+//! repository. When the text or variables of a file change, its template's
+//! version is bumped at the `bump` level (a patch by default); description,
+//! author or tag changes update it in place. A `parent` names the template it
+//! inherits from, linked with an INHERITS edge when the template is created.
+//! Templates whose files are removed are left in the graph.
+//!
+//! Lineage goes back to the repository as annotated tags
+//! `<prefix>/<name>/v<version>` on the synced commit, whose message records
+//! the template and node ids, and is pushed to `origin` if asked to.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use llm_memory_graph::engine::AsyncMemoryGraph;
+//! use llm_memory_graph::template_sync::TemplateSync;
+//! use llm_memory_graph::Config;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let graph = AsyncMemoryGraph::open(Config::default()).await?;
+//! let report = TemplateSync::new("./prompts")
+//!     .with_remote("git@github.com:acme/prompts.git")
+//!     .with_push_tags(true)
+//!     .sync(&graph)
+//!     .await?;
+//! println!(
+//!     "{} created, {} updated at {:?}",
+//!     report.created.len(),
+//!     report.updated.len(),
+//!     report.commit
+//! );
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::engine::AsyncMemoryGraph;
+use crate::{Error, NodeId, PromptTemplate, Result, TemplateId, VariableSpec, VersionLevel};
+use chrono::Utc;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+/// Template metadata key holding the path of the file it is synced from
+pub const SYNC_PATH_KEY: &str = "sync_path";
+
+/// Template metadata key holding the repository it is synced from
+pub const SYNC_REPO_KEY: &str = "sync_repo";
+
+/// Template metadata key holding the commit it was last synced at
+pub const SYNC_COMMIT_KEY: &str = "sync_commit";
+
+/// Template metadata key holding the git tag recording its current version
+pub const SYNC_TAG_KEY: &str = "sync_tag";
+
+/// Default prefix of the lineage tags
+pub const DEFAULT_TAG_PREFIX: &str = "prompts";
+
+/// How many templates are listed per page while matching files to them
+const LIST_PAGE_SIZE: usize = 500;
+
+static VARIABLE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\{\{\s*([A-Za-z_][A-Za-z0-9_]*)\s*\}\}").expect("valid regex"));
+
+/// Syncs prompt templates from a git checkout into a graph
+#[derive(Debug, Clone)]
+pub struct TemplateSync {
+    checkout: PathBuf,
+    remote: Option<String>,
+    branch: Option<String>,
+    subdirectory: Option<PathBuf>,
+    tag_prefix: String,
+    push_tags: bool,
+    tagger: Option<(String, String)>,
+}
+
+impl TemplateSync {
+    /// Sync from the git checkout at `checkout`
+    pub fn new(checkout: impl Into<PathBuf>) -> Self {
+        Self {
+            checkout: checkout.into(),
+            remote: None,
+            branch: None,
+            subdirectory: None,
+            tag_prefix: DEFAULT_TAG_PREFIX.to_string(),
+            push_tags: false,
+            tagger: None,
+        }
+    }
+
+    /// Clone the checkout from `url` if it does not exist yet, and pull from
+    /// it before every sync
+    #[must_use]
+    pub fn with_remote(mut self, url: impl Into<String>) -> Self {
+        self.remote = Some(url.into());
+        self
+    }
+
+    /// Sync `branch` instead of the checkout's current branch
+    #[must_use]
+    pub fn with_branch(mut self, branch: impl Into<String>) -> Self {
+        self.branch = Some(branch.into());
+        self
+    }
+
+    /// Only read templates under `path`, relative to the repository root
+    #[must_use]
+    pub fn with_subdirectory(mut self, path: impl Into<PathBuf>) -> Self {
+        self.subdirectory = Some(path.into());
+        self
+    }
+
+    /// Name lineage tags `<prefix>/<name>/v<version>`
+    #[must_use]
+    pub fn with_tag_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.tag_prefix = prefix.into();
+        self
+    }
+
+    /// Push new lineage tags to `origin`
+    #[must_use]
+    pub fn with_push_tags(mut self, enable: bool) -> Self {
+        self.push_tags = enable;
+        self
+    }
+
+    /// Sign lineage tags as `name <email>` instead of the identity git is
+    /// configured with
+    #[must_use]
+    pub fn with_tagger(mut self, name: impl Into<String>, email: impl Into<String>) -> Self {
+        self.tagger = Some((name.into(), email.into()));
+        self
+    }
+
+    /// Pull the repository and bring the graph's templates in line with it
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a git command fails, the files cannot be read, or
+    /// writing to the graph fails. Files that cannot be parsed, or whose
+    /// parent is unknown, are reported in [`TemplateSyncReport::skipped`]
+    /// instead.
+    pub async fn sync(&self, graph: &AsyncMemoryGraph) -> Result<TemplateSyncReport> {
+        self.pull().await?;
+        let commit = self.git(&["rev-parse", "HEAD"]).await?;
+        let repo = self
+            .remote
+            .clone()
+            .unwrap_or_else(|| self.checkout.display().to_string());
+
+        let root = match &self.subdirectory {
+            Some(sub) => self.checkout.join(sub),
+            None => self.checkout.clone(),
+        };
+        let checkout = self.checkout.clone();
+        let files = tokio::task::spawn_blocking(move || read_files(&checkout, &root))
+            .await
+            .map_err(|e| Error::RuntimeError(e.to_string()))??;
+
+        let mut report = TemplateSyncReport {
+            commit: Some(commit.clone()),
+            ..TemplateSyncReport::default()
+        };
+        let mut parsed = Vec::new();
+        for (path, text) in files {
+            match TemplateFile::parse(&path, &text) {
+                Ok(file) => parsed.push(file),
+                Err(reason) => report.skipped.push(SkippedTemplate { path, reason }),
+            }
+        }
+
+        let mut by_path = HashMap::new();
+        let mut by_name = HashMap::new();
+        for template in list_templates(graph).await? {
+            by_name.insert(template.name.clone(), template.node_id);
+            if let Some(path) = template.metadata.get(SYNC_PATH_KEY) {
+                by_path.insert(path.clone(), template);
+            }
+        }
+
+        let mut changed = Vec::new();
+        // Parents before children; each round syncs the files whose parent is
+        // known by then
+        while !parsed.is_empty() {
+            let (ready, waiting): (Vec<_>, Vec<_>) = parsed.into_iter().partition(|file| {
+                file.parent
+                    .as_ref()
+                    .is_none_or(|parent| by_name.contains_key(parent))
+            });
+            if ready.is_empty() {
+                for file in waiting {
+                    let reason = format!(
+                        "unknown parent template {:?}",
+                        file.parent.as_deref().unwrap_or_default()
+                    );
+                    report.skipped.push(SkippedTemplate {
+                        path: file.path,
+                        reason,
+                    });
+                }
+                break;
+            }
+            for file in ready {
+                let parent = file.parent.as_ref().and_then(|name| by_name.get(name));
+                let existing = by_path.remove(&file.path_key());
+                let outcome = self
+                    .apply(graph, file, existing, parent.copied(), &repo, &commit)
+                    .await?;
+                match outcome {
+                    Outcome::Created(template) => {
+                        report.created.push(template.id);
+                        by_name.insert(template.name.clone(), template.node_id);
+                        changed.push(template);
+                    }
+                    Outcome::Updated(template) => {
+                        report.updated.push(template.id);
+                        by_name.insert(template.name.clone(), template.node_id);
+                        changed.push(template);
+                    }
+                    Outcome::Unchanged => report.unchanged += 1,
+                }
+            }
+            parsed = waiting;
+        }
+
+        for template in &changed {
+            if let Some(tag) = self.tag(template).await? {
+                report.tags.push(tag);
+            }
+        }
+        if self.push_tags && !report.tags.is_empty() {
+            let mut args = vec!["push", "origin"];
+            args.extend(report.tags.iter().map(String::as_str));
+            self.git(&args).await?;
+        }
+        Ok(report)
+    }
+
+    /// Create, update or leave alone the template synced from `file`
+    async fn apply(
+        &self,
+        graph: &AsyncMemoryGraph,
+        file: TemplateFile,
+        existing: Option<PromptTemplate>,
+        parent: Option<NodeId>,
+        repo: &str,
+        commit: &str,
+    ) -> Result<Outcome> {
+        let path = file.path_key();
+        let Some(mut template) = existing else {
+            let mut template = PromptTemplate::new(file.name.clone(), String::new(), Vec::new());
+            file.fill(&mut template);
+            template.add_metadata(SYNC_PATH_KEY.to_string(), path);
+            template.add_metadata(SYNC_REPO_KEY.to_string(), repo.to_string());
+            template.add_metadata(SYNC_COMMIT_KEY.to_string(), commit.to_string());
+            template.add_metadata(SYNC_TAG_KEY.to_string(), self.tag_name(&template));
+            match parent {
+                Some(parent) => {
+                    graph
+                        .create_template_from_parent(template.clone(), parent)
+                        .await?;
+                }
+                None => {
+                    graph.create_template(template.clone()).await?;
+                }
+            }
+            return Ok(Outcome::Created(template));
+        };
+
+        let before = Snapshot::of(&template);
+        file.fill(&mut template);
+        let after = Snapshot::of(&template);
+        if before == after {
+            return Ok(Outcome::Unchanged);
+        }
+        if before.content != after.content {
+            template.bump_version(file.bump);
+        }
+        template.updated_at = Utc::now();
+        template.add_metadata(SYNC_REPO_KEY.to_string(), repo.to_string());
+        template.add_metadata(SYNC_COMMIT_KEY.to_string(), commit.to_string());
+        template.add_metadata(SYNC_TAG_KEY.to_string(), self.tag_name(&template));
+        graph.update_template(template.clone()).await?;
+        Ok(Outcome::Updated(template))
+    }
+
+    /// Name of the lineage tag for the template's current version
+    fn tag_name(&self, template: &PromptTemplate) -> String {
+        let name: String = template
+            .name
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                    c
+                } else {
+                    '-'
+                }
+            })
+            .collect();
+        format!("{}/{name}/v{}", self.tag_prefix, template.version)
+    }
+
+    /// Tag the synced commit with the template's lineage, unless already
+    /// tagged; returns the new tag
+    async fn tag(&self, template: &PromptTemplate) -> Result<Option<String>> {
+        let name = self.tag_name(template);
+        if !self.git(&["tag", "--list", &name]).await?.is_empty() {
+            return Ok(None);
+        }
+        let mut message = format!(
+            "Prompt template {} v{}\n\ntemplate_id: {}\nnode_id: {}\n",
+            template.name, template.version, template.id, template.node_id
+        );
+        if let Some(parent) = template.parent_id {
+            message.push_str(&format!("parent_template_id: {parent}\n"));
+        }
+        self.git(&["tag", "--annotate", &name, "--message", &message])
+            .await?;
+        Ok(Some(name))
+    }
+
+    /// Clone the remote if the checkout is missing, otherwise pull it
+    async fn pull(&self) -> Result<()> {
+        let Some(remote) = &self.remote else {
+            if let Some(branch) = &self.branch {
+                self.git(&["checkout", branch]).await?;
+            }
+            return Ok(());
+        };
+        if !self.checkout.join(".git").exists() {
+            let checkout = self.checkout.display().to_string();
+            let mut args = vec!["clone"];
+            if let Some(branch) = &self.branch {
+                args.extend(["--branch", branch]);
+            }
+            args.extend([remote.as_str(), checkout.as_str()]);
+            run_git(None, &args).await?;
+            return Ok(());
+        }
+        if let Some(branch) = &self.branch {
+            self.git(&["fetch", "origin", branch]).await?;
+            self.git(&["checkout", branch]).await?;
+        }
+        self.git(&["pull", "--ff-only", "--tags"]).await?;
+        Ok(())
+    }
+
+    /// Run git in the checkout
+    async fn git(&self, args: &[&str]) -> Result<String> {
+        let mut full = Vec::with_capacity(args.len() + 6);
+        let identity;
+        if let Some((name, email)) = &self.tagger {
+            identity = [format!("user.name={name}"), format!("user.email={email}")];
+            full.extend(["-c", identity[0].as_str(), "-c", identity[1].as_str()]);
+        }
+        full.extend_from_slice(args);
+        run_git(Some(&self.checkout), &full).await
+    }
+}
+
+/// Run git with `args`, in `dir` if given, returning its trimmed output
+async fn run_git(dir: Option<&Path>, args: &[&str]) -> Result<String> {
+    let mut command = tokio::process::Command::new("git");
+    if let Some(dir) = dir {
+        command.arg("-C").arg(dir);
+    }
+    let output = command
+        .args(args)
+        .output()
+        .await
+        .map_err(|e| Error::IntegrationError(format!("cannot run git: {e}")))?;
+    if !output.status.success() {
+        return Err(Error::IntegrationError(format!(
+            "git {} failed: {}",
+            args.first().copied().unwrap_or_default(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Every template file under `root`, with its path relative to `checkout`
+fn read_files(checkout: &Path, root: &Path) -> Result<Vec<(PathBuf, String)>> {
+    let mut files = Vec::new();
+    let mut dirs = vec![root.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        for entry in std::fs::read_dir(&dir)? {
+            let path = entry?.path();
+            let hidden = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with('.'));
+            if hidden {
+                continue;
+            }
+            if path.is_dir() {
+                dirs.push(path);
+            } else if TemplateFile::kind(&path).is_some() {
+                let text = std::fs::read_to_string(&path)?;
+                let relative = path.strip_prefix(checkout).unwrap_or(&path).to_path_buf();
+                files.push((relative, text));
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Templates in the graph, listed page by page
+async fn list_templates(graph: &AsyncMemoryGraph) -> Result<Vec<PromptTemplate>> {
+    let mut templates = Vec::new();
+    let mut cursor = None;
+    loop {
+        let page = graph
+            .list_templates_page(cursor.as_ref(), LIST_PAGE_SIZE)
+            .await?;
+        templates.extend(page.items);
+        match page.next_cursor {
+            Some(next) => cursor = Some(next),
+            None => return Ok(templates),
+        }
+    }
+}
+
+/// What a sync did
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TemplateSyncReport {
+    /// Commit the templates were synced at
+    pub commit: Option<String>,
+    /// Templates created from new files
+    pub created: Vec<TemplateId>,
+    /// Templates whose files changed
+    pub updated: Vec<TemplateId>,
+    /// Files whose templates were already up to date
+    pub unchanged: usize,
+    /// Lineage tags created
+    pub tags: Vec<String>,
+    /// Files left out, and why
+    pub skipped: Vec<SkippedTemplate>,
+}
+
+/// A file that could not be synced
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SkippedTemplate {
+    /// Path of the file in the repository
+    pub path: PathBuf,
+    /// Why it was left out
+    pub reason: String,
+}
+
+enum Outcome {
+    Created(PromptTemplate),
+    Updated(PromptTemplate),
+    Unchanged,
+}
+
+/// The parts of a template a file controls, split into what bumps the
+/// version and what does not
+#[derive(PartialEq)]
+struct Snapshot {
+    content: (String, String),
+    details: (String, String, Vec<String>, Vec<(String, String)>),
+}
+
+impl Snapshot {
+    fn of(template: &PromptTemplate) -> Self {
+        let mut metadata: Vec<(String, String)> = template
+            .metadata
+            .iter()
+            .filter(|(key, _)| {
+                ![SYNC_REPO_KEY, SYNC_COMMIT_KEY, SYNC_TAG_KEY].contains(&key.as_str())
+            })
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+        metadata.sort();
+        Self {
+            content: (
+                template.template.clone(),
+                serde_json::to_string(&template.variables).unwrap_or_default(),
+            ),
+            details: (
+                template.description.clone(),
+                template.author.clone(),
+                template.tags.clone(),
+                metadata,
+            ),
+        }
+    }
+}
+
+/// Fields a template file may set
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct FrontMatter {
+    name: Option<String>,
+    description: Option<String>,
+    author: Option<String>,
+    tags: Vec<String>,
+    variables: Vec<VariableEntry>,
+    parent: Option<String>,
+    bump: Option<String>,
+    template: Option<String>,
+    #[serde(flatten)]
+    extra: BTreeMap<String, serde_yaml::Value>,
+}
+
+/// A declared variable: just its name, or a full specification
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum VariableEntry {
+    Name(String),
+    Spec {
+        name: String,
+        #[serde(rename = "type", default = "string_type")]
+        type_hint: String,
+        #[serde(default = "required")]
+        required: bool,
+        #[serde(default)]
+        default: Option<String>,
+        #[serde(default)]
+        pattern: Option<String>,
+        #[serde(default)]
+        description: String,
+    },
+}
+
+fn string_type() -> String {
+    "string".to_string()
+}
+
+const fn required() -> bool {
+    true
+}
+
+impl VariableEntry {
+    fn into_spec(self) -> VariableSpec {
+        match self {
+            Self::Name(name) => VariableSpec::new(name, string_type(), true, String::new()),
+            Self::Spec {
+                name,
+                type_hint,
+                required,
+                default,
+                pattern,
+                description,
+            } => VariableSpec {
+                name,
+                type_hint,
+                required,
+                default,
+                validation_pattern: pattern,
+                description,
+            },
+        }
+    }
+}
+
+/// A template file read from the repository
+struct TemplateFile {
+    path: PathBuf,
+    name: String,
+    description: String,
+    author: Option<String>,
+    tags: Vec<String>,
+    variables: Vec<VariableSpec>,
+    parent: Option<String>,
+    bump: VersionLevel,
+    text: String,
+    metadata: BTreeMap<String, String>,
+}
+
+#[derive(Clone, Copy)]
+enum FileKind {
+    Markdown,
+    Yaml,
+}
+
+impl TemplateFile {
+    fn kind(path: &Path) -> Option<FileKind> {
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "md" | "markdown" => Some(FileKind::Markdown),
+            "yaml" | "yml" => Some(FileKind::Yaml),
+            _ => None,
+        }
+    }
+
+    fn parse(path: &Path, text: &str) -> std::result::Result<Self, String> {
+        let yaml_error = |e: serde_yaml::Error| format!("invalid YAML: {e}");
+        let (front, body) = match Self::kind(path) {
+            Some(FileKind::Markdown) => match split_front_matter(text) {
+                Some((front, body)) => (
+                    serde_yaml::from_str::<Option<FrontMatter>>(front)
+                        .map_err(yaml_error)?
+                        .unwrap_or_default(),
+                    body.to_string(),
+                ),
+                None => (FrontMatter::default(), text.to_string()),
+            },
+            Some(FileKind::Yaml) => {
+                let mut front: FrontMatter = serde_yaml::from_str(text).map_err(yaml_error)?;
+                let body = front
+                    .template
+                    .take()
+                    .ok_or_else(|| "no `template` field".to_string())?;
+                (front, body)
+            }
+            None => return Err("not a Markdown or YAML file".to_string()),
+        };
+        let body = body.trim().to_string();
+        if body.is_empty() {
+            return Err("empty template".to_string());
+        }
+
+        let bump = match front
+            .bump
+            .as_deref()
+            .map(str::to_ascii_lowercase)
+            .as_deref()
+        {
+            None | Some("patch") => VersionLevel::Patch,
+            Some("minor") => VersionLevel::Minor,
+            Some("major") => VersionLevel::Major,
+            Some(other) => return Err(format!("unknown bump level {other:?}")),
+        };
+        let mut variables: Vec<VariableSpec> = front
+            .variables
+            .into_iter()
+            .map(VariableEntry::into_spec)
+            .collect();
+        if variables.is_empty() {
+            for name in VARIABLE.captures_iter(&body).map(|c| c[1].to_string()) {
+                if !variables.iter().any(|v| v.name == name) {
+                    variables.push(VariableSpec::new(name, string_type(), true, String::new()));
+                }
+            }
+        }
+        let metadata = front
+            .extra
+            .into_iter()
+            .filter_map(|(key, value)| {
+                let value = match value {
+                    serde_yaml::Value::String(s) => s,
+                    serde_yaml::Value::Number(n) => n.to_string(),
+                    serde_yaml::Value::Bool(b) => b.to_string(),
+                    _ => return None,
+                };
+                Some((key, value))
+            })
+            .collect();
+        let name = front.name.unwrap_or_else(|| {
+            path.file_stem()
+                .and_then(|stem| stem.to_str())
+                .unwrap_or_default()
+                .to_string()
+        });
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            name,
+            description: front.description.unwrap_or_default(),
+            author: front.author,
+            tags: front.tags,
+            variables,
+            parent: front.parent,
+            bump,
+            text: body,
+            metadata,
+        })
+    }
+
+    /// Path as stored under [`SYNC_PATH_KEY`], with `/` separators
+    fn path_key(&self) -> String {
+        self.path
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
+    /// Set the fields of `template` the file controls
+    fn fill(&self, template: &mut PromptTemplate) {
+        template.name.clone_from(&self.name);
+        template.description.clone_from(&self.description);
+        if let Some(author) = &self.author {
+            template.author.clone_from(author);
+        }
+        template.tags.clone_from(&self.tags);
+        template.template.clone_from(&self.text);
+        template.variables.clone_from(&self.variables);
+        for (key, value) in &self.metadata {
+            template.add_metadata(key.clone(), value.clone());
+        }
+    }
+}
+
+/// Split Markdown into its front-matter and body, if it has front-matter
+fn split_front_matter(text: &str) -> Option<(&str, &str)> {
+    let rest = text
+        .strip_prefix("---\n")
+        .or_else(|| text.strip_prefix("---\r\n"))?;
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == "---" {
+            return Some((&rest[..offset], &rest[offset + line.len()..]));
+        }
+        offset += line.len();
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Config, Version};
+    use tempfile::tempdir;
+
+    async fn git(dir: &Path, args: &[&str]) -> String {
+        let mut full = vec!["-c", "user.name=Test", "-c", "user.email=test@example.com"];
+        full.extend_from_slice(args);
+        run_git(Some(dir), &full).await.unwrap()
+    }
+
+    async fn template(graph: &AsyncMemoryGraph, id: TemplateId) -> PromptTemplate {
+        list_templates(graph)
+            .await
+            .unwrap()
+            .into_iter()
+            .find(|template| template.id == id)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_parse_template_files() {
+        let file = TemplateFile::parse(
+            Path::new("prompts/summarize.md"),
+            "---\nname: summarize\nbump: minor\nowner: docs\nvariables:\n  - document\n  - name: length\n    required: false\n    default: short\n---\nSummarize {{document}} in a {{length}} paragraph.\n",
+        )
+        .unwrap();
+        assert_eq!(file.name, "summarize");
+        assert_eq!(file.bump, VersionLevel::Minor);
+        assert_eq!(
+            file.text,
+            "Summarize {{document}} in a {{length}} paragraph."
+        );
+        assert_eq!(file.variables.len(), 2);
+        assert!(file.variables[0].required);
+        assert_eq!(file.variables[1].default.as_deref(), Some("short"));
+        assert_eq!(file.metadata.get("owner").map(String::as_str), Some("docs"));
+        assert_eq!(file.path_key(), "prompts/summarize.md");
+
+        let file = TemplateFile::parse(
+            Path::new("greet.yaml"),
+            "description: Greeting\ntemplate: Hello {{ name }}!\n",
+        )
+        .unwrap();
+        assert_eq!(file.name, "greet");
+        assert_eq!(file.variables[0].name, "name");
+
+        let plain = TemplateFile::parse(Path::new("plain.md"), "Just text").unwrap();
+        assert_eq!(plain.text, "Just text");
+        assert!(TemplateFile::parse(Path::new("bad.yaml"), "name: x\n").is_err());
+        assert!(TemplateFile::parse(Path::new("bad.md"), "---\nbump: huge\n---\nx").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_sync_from_remote() {
+        let dir = tempdir().unwrap();
+        let remote = dir.path().join("remote.git");
+        let work = dir.path().join("work");
+        run_git(None, &["init", "--bare", remote.to_str().unwrap()])
+            .await
+            .unwrap();
+        run_git(
+            None,
+            &["clone", remote.to_str().unwrap(), work.to_str().unwrap()],
+        )
+        .await
+        .unwrap();
+        std::fs::create_dir_all(work.join("prompts")).unwrap();
+        std::fs::write(
+            work.join("prompts/base.md"),
+            "---\nname: base\n---\nYou are helpful.",
+        )
+        .unwrap();
+        std::fs::write(
+            work.join("prompts/answer.md"),
+            "---\nname: answer\nparent: base\ntags: [qa]\n---\nAnswer {{question}}.",
+        )
+        .unwrap();
+        std::fs::write(work.join("README.txt"), "not a template").unwrap();
+        git(&work, &["add", "-A"]).await;
+        git(&work, &["commit", "-m", "Add prompts"]).await;
+        git(&work, &["push", "origin", "HEAD"]).await;
+
+        let graph = AsyncMemoryGraph::open(Config::new(dir.path().join("graph")))
+            .await
+            .unwrap();
+        let sync = TemplateSync::new(dir.path().join("checkout"))
+            .with_remote(remote.to_str().unwrap())
+            .with_push_tags(true)
+            .with_tagger("Sync", "sync@example.com");
+
+        let report = sync.sync(&graph).await.unwrap();
+        assert_eq!(report.created.len(), 2);
+        assert!(report.skipped.is_empty());
+        assert_eq!(
+            report.tags,
+            vec!["prompts/base/v1.0.0", "prompts/answer/v1.0.0"]
+        );
+        let answer = template(&graph, report.created[1]).await;
+        assert_eq!(answer.variables[0].name, "question");
+        assert_eq!(answer.tags, vec!["qa"]);
+        assert_eq!(
+            answer.metadata.get(SYNC_PATH_KEY).map(String::as_str),
+            Some("prompts/answer.md")
+        );
+        let base = template(&graph, report.created[0]).await;
+        let inherits = graph.get_outgoing_edges(&answer.node_id).await.unwrap();
+        assert_eq!(inherits[0].to, base.node_id);
+
+        // Tags reach the remote and record the template
+        let message = git(
+            &remote,
+            &["tag", "-l", "--format=%(contents)", "prompts/answer/v1.0.0"],
+        )
+        .await;
+        assert!(message.contains(&answer.id.to_string()), "{message}");
+
+        // Nothing changed: nothing to do
+        let report = sync.sync(&graph).await.unwrap();
+        assert_eq!(report.unchanged, 2);
+        assert!(report.tags.is_empty());
+
+        // A content change bumps the version; a tag-only change does not
+        std::fs::write(
+            work.join("prompts/answer.md"),
+            "---\nname: answer\nparent: base\ntags: [qa]\nbump: minor\n---\nAnswer {{question}} briefly.",
+        )
+        .unwrap();
+        std::fs::write(
+            work.join("prompts/base.md"),
+            "---\nname: base\ntags: [core]\n---\nYou are helpful.",
+        )
+        .unwrap();
+        git(&work, &["commit", "-am", "Edit prompts"]).await;
+        git(&work, &["push", "origin", "HEAD"]).await;
+
+        let report = sync.sync(&graph).await.unwrap();
+        assert_eq!(report.updated.len(), 2);
+        let answer = template(&graph, answer.id).await;
+        assert_eq!(answer.version, Version::new(1, 1, 0));
+        assert_eq!(answer.template, "Answer {{question}} briefly.");
+        assert_eq!(answer.metadata.get(SYNC_COMMIT_KEY), report.commit.as_ref());
+        let base = template(&graph, base.id).await;
+        assert_eq!(base.version, Version::new(1, 0, 0));
+        assert_eq!(base.tags, vec!["core"]);
+        assert_eq!(report.tags, vec!["prompts/answer/v1.1.0"]);
+    }
+}
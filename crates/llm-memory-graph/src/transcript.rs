@@ -0,0 +1,426 @@
+//! Human-readable conversation transcripts
+//!
+//! [`AsyncMemoryGraph::export_transcript`](crate::engine::AsyncMemoryGraph::export_transcript)
+//! renders a session as Markdown or HTML for sharing agent runs with people who
+//! will not read raw graph data. Each prompt is followed by its responses, tool
+//! calls are collapsed into `<details>` blocks and a footer totals token usage
+//! (and cost, when [`TokenPricing`] is supplied).
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use llm_memory_graph::engine::AsyncMemoryGraph;
+//! use llm_memory_graph::transcript::{TokenPricing, TranscriptFormat, TranscriptOptions};
+//! use llm_memory_graph::Config;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let graph = AsyncMemoryGraph::open(Config::default()).await?;
+//! # let session = graph.create_session().await?;
+//! let markdown = graph.export_transcript(session.id, TranscriptFormat::Markdown).await?;
+//!
+//! let options = TranscriptOptions::new(TranscriptFormat::Html)
+//!     .with_pricing(TokenPricing::per_1k_tokens(0.003, 0.015));
+//! let html = graph.export_transcript_with(session.id, &options).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::storage::AsyncStorageBackend;
+use crate::{
+    ConversationSession, EdgeType, Node, NodeId, PromptNode, ResponseNode, Result, ToolInvocation,
+};
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// Output format of a transcript
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TranscriptFormat {
+    /// GitHub-flavoured Markdown
+    Markdown,
+    /// Standalone HTML document
+    Html,
+}
+
+/// Price of tokens, used to estimate the cost of a session
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TokenPricing {
+    /// Price per 1,000 prompt tokens
+    pub prompt_per_1k: f64,
+    /// Price per 1,000 completion tokens
+    pub completion_per_1k: f64,
+}
+
+impl TokenPricing {
+    /// Pricing from per-1,000-token prices
+    pub fn per_1k_tokens(prompt_per_1k: f64, completion_per_1k: f64) -> Self {
+        Self {
+            prompt_per_1k,
+            completion_per_1k,
+        }
+    }
+
+    /// Cost of the given token counts
+    pub fn cost(&self, prompt_tokens: u64, completion_tokens: u64) -> f64 {
+        (prompt_tokens as f64 * self.prompt_per_1k
+            + completion_tokens as f64 * self.completion_per_1k)
+            / 1000.0
+    }
+}
+
+/// How to render a transcript
+#[derive(Debug, Clone, PartialEq)]
+pub struct TranscriptOptions {
+    /// Output format
+    pub format: TranscriptFormat,
+    /// Token prices for the cost footer; no cost is shown when unset
+    pub pricing: Option<TokenPricing>,
+}
+
+impl TranscriptOptions {
+    /// Options for `format` without pricing
+    pub fn new(format: TranscriptFormat) -> Self {
+        Self {
+            format,
+            pricing: None,
+        }
+    }
+
+    /// Show an estimated cost in the footer
+    pub fn with_pricing(mut self, pricing: TokenPricing) -> Self {
+        self.pricing = Some(pricing);
+        self
+    }
+}
+
+/// A response together with the tools it invoked
+#[derive(Debug, Clone)]
+pub struct TranscriptResponse {
+    /// The response
+    pub response: ResponseNode,
+    /// Tools invoked by the response, oldest first
+    pub tools: Vec<ToolInvocation>,
+}
+
+/// A prompt and everything that answered it
+#[derive(Debug, Clone)]
+pub struct TranscriptTurn {
+    /// The prompt
+    pub prompt: PromptNode,
+    /// Responses to the prompt, oldest first
+    pub responses: Vec<TranscriptResponse>,
+}
+
+/// A session's conversation, ready to render
+#[derive(Debug, Clone)]
+pub struct Transcript {
+    /// The session
+    pub session: ConversationSession,
+    /// Turns in chronological order
+    pub turns: Vec<TranscriptTurn>,
+}
+
+impl Transcript {
+    /// Read a session's prompts, responses and tool calls from `storage`
+    pub(crate) async fn collect(
+        storage: &dyn AsyncStorageBackend,
+        session: ConversationSession,
+    ) -> Result<Self> {
+        let mut prompts = Vec::new();
+        let mut responses: HashMap<NodeId, Vec<ResponseNode>> = HashMap::new();
+        for node in storage.get_session_nodes(&session.id).await? {
+            match node {
+                Node::Prompt(prompt) => prompts.push(prompt),
+                Node::Response(response) => {
+                    responses
+                        .entry(response.prompt_id)
+                        .or_default()
+                        .push(response);
+                }
+                _ => {}
+            }
+        }
+        prompts.sort_by_key(|p| p.timestamp);
+
+        let mut turns = Vec::with_capacity(prompts.len());
+        for prompt in prompts {
+            let mut replies = responses.remove(&prompt.id).unwrap_or_default();
+            replies.sort_by_key(|r| r.timestamp);
+
+            let mut entries = Vec::with_capacity(replies.len());
+            for response in replies {
+                let mut tools = Vec::new();
+                for edge in storage.get_outgoing_edges(&response.id).await? {
+                    if edge.edge_type == EdgeType::Invokes {
+                        if let Some(Node::ToolInvocation(tool)) = storage.get_node(&edge.to).await?
+                        {
+                            tools.push(tool);
+                        }
+                    }
+                }
+                tools.sort_by_key(|t| t.timestamp);
+                entries.push(TranscriptResponse { response, tools });
+            }
+            turns.push(TranscriptTurn {
+                prompt,
+                responses: entries,
+            });
+        }
+
+        Ok(Self { session, turns })
+    }
+
+    /// Total prompt and completion tokens across all responses
+    pub fn token_totals(&self) -> (u64, u64) {
+        self.responses().fold((0, 0), |(prompt, completion), r| {
+            (
+                prompt + u64::from(r.response.usage.prompt_tokens),
+                completion + u64::from(r.response.usage.completion_tokens),
+            )
+        })
+    }
+
+    /// Render the transcript
+    pub fn render(&self, options: &TranscriptOptions) -> String {
+        match options.format {
+            TranscriptFormat::Markdown => self.render_markdown(options),
+            TranscriptFormat::Html => self.render_html(options),
+        }
+    }
+
+    fn responses(&self) -> impl Iterator<Item = &TranscriptResponse> {
+        self.turns.iter().flat_map(|turn| &turn.responses)
+    }
+
+    fn title(&self) -> String {
+        self.session
+            .metadata
+            .get("title")
+            .cloned()
+            .unwrap_or_else(|| format!("Session {}", self.session.id))
+    }
+
+    fn footer(&self, options: &TranscriptOptions) -> String {
+        let (prompt, completion) = self.token_totals();
+        let mut footer = format!(
+            "Tokens: {} prompt + {} completion = {} total",
+            prompt,
+            completion,
+            prompt + completion
+        );
+        if let Some(pricing) = options.pricing {
+            let _ = write!(
+                footer,
+                " · Estimated cost: ${:.4}",
+                pricing.cost(prompt, completion)
+            );
+        }
+        footer
+    }
+
+    fn render_markdown(&self, options: &TranscriptOptions) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "# {}\n", self.title());
+        let _ = writeln!(out, "- **Session:** `{}`", self.session.id);
+        let _ = writeln!(
+            out,
+            "- **Started:** {}",
+            format_time(self.session.created_at)
+        );
+        let _ = writeln!(out, "- **Turns:** {}", self.turns.len());
+
+        for turn in &self.turns {
+            let _ = writeln!(
+                out,
+                "\n---\n\n### User — {}\n\n{}",
+                format_time(turn.prompt.timestamp),
+                turn.prompt.content
+            );
+            for entry in &turn.responses {
+                let _ = writeln!(
+                    out,
+                    "\n### Assistant ({}) — {}\n\n{}",
+                    entry.response.metadata.model,
+                    format_time(entry.response.timestamp),
+                    entry.response.content
+                );
+                for tool in &entry.tools {
+                    let _ = writeln!(
+                        out,
+                        "\n<details>\n<summary>{}</summary>\n",
+                        tool_summary(tool)
+                    );
+                    let _ = writeln!(
+                        out,
+                        "**Parameters**\n\n```json\n{}\n```",
+                        pretty(&tool.parameters)
+                    );
+                    if let Some(result) = &tool.result {
+                        let _ = writeln!(out, "\n**Result**\n\n```json\n{}\n```", pretty(result));
+                    }
+                    if let Some(error) = &tool.error {
+                        let _ = writeln!(out, "\n**Error:** {}", error);
+                    }
+                    out.push_str("\n</details>\n");
+                }
+            }
+        }
+
+        let _ = writeln!(out, "\n---\n\n**{}**", self.footer(options));
+        out
+    }
+
+    fn render_html(&self, options: &TranscriptOptions) -> String {
+        let title = escape_html(&self.title());
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n</head>\n<body>"
+        );
+        let _ = writeln!(out, "<h1>{title}</h1>");
+        let _ = writeln!(
+            out,
+            "<ul>\n<li><strong>Session:</strong> <code>{}</code></li>\n<li><strong>Started:</strong> {}</li>\n<li><strong>Turns:</strong> {}</li>\n</ul>",
+            self.session.id,
+            format_time(self.session.created_at),
+            self.turns.len()
+        );
+
+        for turn in &self.turns {
+            let _ = writeln!(
+                out,
+                "<hr>\n<section class=\"user\">\n<h3>User — {}</h3>\n<pre>{}</pre>\n</section>",
+                format_time(turn.prompt.timestamp),
+                escape_html(&turn.prompt.content)
+            );
+            for entry in &turn.responses {
+                let _ = writeln!(
+                    out,
+                    "<section class=\"assistant\">\n<h3>Assistant ({}) — {}</h3>\n<pre>{}</pre>",
+                    escape_html(&entry.response.metadata.model),
+                    format_time(entry.response.timestamp),
+                    escape_html(&entry.response.content)
+                );
+                for tool in &entry.tools {
+                    let _ = writeln!(
+                        out,
+                        "<details>\n<summary>{}</summary>\n<p><strong>Parameters</strong></p>\n<pre>{}</pre>",
+                        escape_html(&tool_summary(tool)),
+                        escape_html(&pretty(&tool.parameters))
+                    );
+                    if let Some(result) = &tool.result {
+                        let _ = writeln!(
+                            out,
+                            "<p><strong>Result</strong></p>\n<pre>{}</pre>",
+                            escape_html(&pretty(result))
+                        );
+                    }
+                    if let Some(error) = &tool.error {
+                        let _ =
+                            writeln!(out, "<p><strong>Error:</strong> {}</p>", escape_html(error));
+                    }
+                    out.push_str("</details>\n");
+                }
+                out.push_str("</section>\n");
+            }
+        }
+
+        let _ = writeln!(
+            out,
+            "<hr>\n<footer><strong>{}</strong></footer>\n</body>\n</html>",
+            escape_html(&self.footer(options))
+        );
+        out
+    }
+}
+
+fn format_time(timestamp: DateTime<Utc>) -> String {
+    timestamp.format("%Y-%m-%d %H:%M:%S UTC").to_string()
+}
+
+fn tool_summary(tool: &ToolInvocation) -> String {
+    format!(
+        "Tool call: {} ({}, {} ms)",
+        tool.tool_name,
+        tool.status(),
+        tool.duration_ms
+    )
+}
+
+fn pretty(value: &serde_json::Value) -> String {
+    serde_json::to_string_pretty(value).unwrap_or_else(|_| value.to_string())
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TokenUsage;
+
+    fn transcript() -> Transcript {
+        let session = ConversationSession::new();
+        let prompt = PromptNode::new(session.id, "What is 2 + 2?".to_string());
+        let response = ResponseNode::new(
+            prompt.id,
+            "It is <b>4</b>".to_string(),
+            TokenUsage::new(1000, 2000),
+        );
+        let mut tool = ToolInvocation::new(
+            response.id,
+            "calculator".to_string(),
+            serde_json::json!({"a": 2, "b": 2}),
+        );
+        tool.mark_success(serde_json::json!(4), 12);
+
+        Transcript {
+            session,
+            turns: vec![TranscriptTurn {
+                prompt,
+                responses: vec![TranscriptResponse {
+                    response,
+                    tools: vec![tool],
+                }],
+            }],
+        }
+    }
+
+    #[test]
+    fn test_markdown_transcript() {
+        let options = TranscriptOptions::new(TranscriptFormat::Markdown)
+            .with_pricing(TokenPricing::per_1k_tokens(0.01, 0.03));
+        let markdown = transcript().render(&options);
+
+        assert!(markdown.contains("### User — "));
+        assert!(markdown.contains("What is 2 + 2?"));
+        assert!(markdown.contains("### Assistant (unknown)"));
+        assert!(markdown.contains("<summary>Tool call: calculator (success, 12 ms)</summary>"));
+        assert!(markdown.contains("\"a\": 2"));
+        assert!(markdown.contains("Tokens: 1000 prompt + 2000 completion = 3000 total"));
+        assert!(markdown.contains("Estimated cost: $0.0700"));
+    }
+
+    #[test]
+    fn test_html_transcript_escapes_content() {
+        let html = transcript().render(&TranscriptOptions::new(TranscriptFormat::Html));
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("It is &lt;b&gt;4&lt;/b&gt;"));
+        assert!(!html.contains("<b>4</b>"));
+        assert!(html.contains("<details>"));
+        assert!(!html.contains("Estimated cost"));
+    }
+}
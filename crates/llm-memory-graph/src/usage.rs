@@ -0,0 +1,394 @@
+//! Time-series rollups of usage
+//!
+//! Reporting how many tokens were spent last month should not mean reading
+//! every response written last month. The storage backend keeps running
+//! totals of nodes written and tokens used in hourly and daily buckets, in a
+//! `metrics` tree updated with every node write, and
+//! [`AsyncMemoryGraph::usage_series`](crate::engine::AsyncMemoryGraph::usage_series)
+//! reads a range of buckets back as a [`UsageSeries`].
+//!
+//! Nodes count in the bucket of their creation time. Rewriting a node
+//! replaces its contribution, so edits do not count twice, but deleting one
+//! leaves it counted: the rollups record what was used, not what is still
+//! stored. Databases written before rollups existed are backfilled from the
+//! nodes and trash they hold when first opened.
+//!
+//! Costs are not stored, since prices change; apply a [`TokenPricing`] to a
+//! bucket or series instead, per model if the models are priced differently.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use chrono::{Duration, Utc};
+//! use llm_memory_graph::engine::AsyncMemoryGraph;
+//! use llm_memory_graph::transcript::TokenPricing;
+//! use llm_memory_graph::usage::UsageGranularity;
+//! use llm_memory_graph::Config;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let graph = AsyncMemoryGraph::open(Config::default()).await?;
+//!
+//! let now = Utc::now();
+//! let series = graph
+//!     .usage_series(now - Duration::days(30)..now, UsageGranularity::Day)
+//!     .await?;
+//! let pricing = TokenPricing::per_1k_tokens(0.003, 0.015);
+//! for bucket in &series.buckets {
+//!     let totals = &bucket.totals;
+//!     println!("{}: {} tokens, ${:.2}", bucket.start.date_naive(), totals.total_tokens, totals.cost(&pricing));
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::transcript::TokenPricing;
+use crate::{Error, Node};
+use chrono::{DateTime, Duration, DurationRound, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::str::FromStr;
+
+/// Width of the buckets in a usage series
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UsageGranularity {
+    /// One bucket per hour
+    Hour,
+    /// One bucket per day (UTC)
+    Day,
+}
+
+impl UsageGranularity {
+    /// Every granularity rollups are kept at
+    pub const ALL: [Self; 2] = [Self::Hour, Self::Day];
+
+    /// Width of one bucket
+    pub fn step(self) -> Duration {
+        match self {
+            Self::Hour => Duration::hours(1),
+            Self::Day => Duration::days(1),
+        }
+    }
+
+    /// Start of the bucket holding `at`
+    pub fn bucket_start(self, at: DateTime<Utc>) -> DateTime<Utc> {
+        at.duration_trunc(self.step()).unwrap_or(at)
+    }
+
+    /// Byte identifying the granularity in storage keys
+    pub(crate) fn tag(self) -> u8 {
+        match self {
+            Self::Hour => 0,
+            Self::Day => 1,
+        }
+    }
+}
+
+impl fmt::Display for UsageGranularity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Hour => "hour",
+            Self::Day => "day",
+        })
+    }
+}
+
+impl FromStr for UsageGranularity {
+    type Err = Error;
+
+    fn from_str(s: &str) -> crate::Result<Self> {
+        match s.to_lowercase().as_str() {
+            "hour" | "hourly" => Ok(Self::Hour),
+            "day" | "daily" => Ok(Self::Day),
+            _ => Err(Error::ValidationError(format!(
+                "unknown granularity '{s}', expected 'hour' or 'day'"
+            ))),
+        }
+    }
+}
+
+/// Tokens used by the responses of one model
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModelUsage {
+    /// Responses from the model
+    pub responses: u64,
+    /// Prompt tokens used
+    pub prompt_tokens: u64,
+    /// Completion tokens used
+    pub completion_tokens: u64,
+}
+
+/// Nodes written and tokens used in one bucket
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UsageTotals {
+    /// Nodes of every type
+    pub nodes: u64,
+    /// Sessions started
+    pub sessions: u64,
+    /// Prompts sent
+    pub prompts: u64,
+    /// Responses received
+    pub responses: u64,
+    /// Tool invocations made
+    pub tool_invocations: u64,
+    /// Prompt tokens used
+    pub prompt_tokens: u64,
+    /// Completion tokens used
+    pub completion_tokens: u64,
+    /// Total tokens used
+    pub total_tokens: u64,
+    /// Responses and tokens by the model that answered
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub models: BTreeMap<String, ModelUsage>,
+}
+
+impl UsageTotals {
+    /// Contribution of one node
+    pub fn of(node: &Node) -> Self {
+        let mut totals = Self {
+            nodes: 1,
+            ..Self::default()
+        };
+        match node {
+            Node::Session(_) => totals.sessions = 1,
+            Node::Prompt(_) => totals.prompts = 1,
+            Node::Response(response) => {
+                let usage = &response.usage;
+                totals.responses = 1;
+                totals.prompt_tokens = u64::from(usage.prompt_tokens);
+                totals.completion_tokens = u64::from(usage.completion_tokens);
+                totals.total_tokens = u64::from(usage.total_tokens);
+                totals.models.insert(
+                    response.metadata.model.clone(),
+                    ModelUsage {
+                        responses: 1,
+                        prompt_tokens: totals.prompt_tokens,
+                        completion_tokens: totals.completion_tokens,
+                    },
+                );
+            }
+            Node::ToolInvocation(_) => totals.tool_invocations = 1,
+            Node::Agent(_) | Node::Template(_) | Node::Custom(_) => {}
+        }
+        totals
+    }
+
+    /// Add `other` to these totals
+    pub fn add(&mut self, other: &Self) {
+        self.nodes += other.nodes;
+        self.sessions += other.sessions;
+        self.prompts += other.prompts;
+        self.responses += other.responses;
+        self.tool_invocations += other.tool_invocations;
+        self.prompt_tokens += other.prompt_tokens;
+        self.completion_tokens += other.completion_tokens;
+        self.total_tokens += other.total_tokens;
+        for (model, usage) in &other.models {
+            let entry = self.models.entry(model.clone()).or_default();
+            entry.responses += usage.responses;
+            entry.prompt_tokens += usage.prompt_tokens;
+            entry.completion_tokens += usage.completion_tokens;
+        }
+    }
+
+    /// Take `other` away from these totals, dropping models left with no
+    /// responses
+    pub fn subtract(&mut self, other: &Self) {
+        self.nodes = self.nodes.saturating_sub(other.nodes);
+        self.sessions = self.sessions.saturating_sub(other.sessions);
+        self.prompts = self.prompts.saturating_sub(other.prompts);
+        self.responses = self.responses.saturating_sub(other.responses);
+        self.tool_invocations = self.tool_invocations.saturating_sub(other.tool_invocations);
+        self.prompt_tokens = self.prompt_tokens.saturating_sub(other.prompt_tokens);
+        self.completion_tokens = self
+            .completion_tokens
+            .saturating_sub(other.completion_tokens);
+        self.total_tokens = self.total_tokens.saturating_sub(other.total_tokens);
+        for (model, usage) in &other.models {
+            if let Some(entry) = self.models.get_mut(model) {
+                entry.responses = entry.responses.saturating_sub(usage.responses);
+                entry.prompt_tokens = entry.prompt_tokens.saturating_sub(usage.prompt_tokens);
+                entry.completion_tokens = entry
+                    .completion_tokens
+                    .saturating_sub(usage.completion_tokens);
+                if entry.responses == 0 {
+                    self.models.remove(model);
+                }
+            }
+        }
+    }
+
+    /// Whether nothing is counted
+    pub fn is_empty(&self) -> bool {
+        self.nodes == 0
+    }
+
+    /// Cost of the tokens used, at the same price for every model
+    pub fn cost(&self, pricing: &TokenPricing) -> f64 {
+        pricing.cost(self.prompt_tokens, self.completion_tokens)
+    }
+
+    /// Cost of the tokens used, priced per model; models without a price
+    /// cost nothing
+    pub fn cost_by_model(&self, prices: &HashMap<String, TokenPricing>) -> f64 {
+        self.models
+            .iter()
+            .filter_map(|(model, usage)| {
+                prices
+                    .get(model)
+                    .map(|pricing| pricing.cost(usage.prompt_tokens, usage.completion_tokens))
+            })
+            .sum()
+    }
+}
+
+/// Totals for the bucket starting at `start`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UsageBucket {
+    /// Start of the bucket
+    pub start: DateTime<Utc>,
+    /// What was written and used in the bucket
+    pub totals: UsageTotals,
+}
+
+/// Consecutive usage buckets over a time range, see the [module docs](self)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UsageSeries {
+    /// Width of each bucket
+    pub granularity: UsageGranularity,
+    /// Every bucket in the range, oldest first; buckets with no usage are
+    /// included with empty totals
+    pub buckets: Vec<UsageBucket>,
+}
+
+impl UsageSeries {
+    /// Fill the range `from..to` with `stored` buckets, adding empty ones
+    /// for the gaps
+    pub(crate) fn fill(
+        granularity: UsageGranularity,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        stored: Vec<UsageBucket>,
+    ) -> Self {
+        let mut stored: BTreeMap<DateTime<Utc>, UsageTotals> = stored
+            .into_iter()
+            .map(|bucket| (bucket.start, bucket.totals))
+            .collect();
+        let mut buckets = Vec::new();
+        let mut start = granularity.bucket_start(from);
+        while start < to {
+            buckets.push(UsageBucket {
+                start,
+                totals: stored.remove(&start).unwrap_or_default(),
+            });
+            start += granularity.step();
+        }
+        Self {
+            granularity,
+            buckets,
+        }
+    }
+
+    /// Totals over the whole series
+    pub fn totals(&self) -> UsageTotals {
+        let mut totals = UsageTotals::default();
+        for bucket in &self.buckets {
+            totals.add(&bucket.totals);
+        }
+        totals
+    }
+}
+
+/// Key of a bucket in the metrics tree: the granularity, then the bucket's
+/// start in seconds with the sign bit flipped so keys sort by time
+pub(crate) fn bucket_key(granularity: UsageGranularity, start: DateTime<Utc>) -> [u8; 9] {
+    let mut key = [0; 9];
+    key[0] = granularity.tag();
+    key[1..].copy_from_slice(&(start.timestamp().cast_unsigned() ^ (1 << 63)).to_be_bytes());
+    key
+}
+
+/// Start of the bucket stored under `key`, if it is a bucket key
+pub(crate) fn bucket_start_of(key: &[u8]) -> Option<DateTime<Utc>> {
+    let seconds: [u8; 8] = key.get(1..9)?.try_into().ok()?;
+    let seconds = (u64::from_be_bytes(seconds) ^ (1 << 63)).cast_signed();
+    Utc.timestamp_opt(seconds, 0).single()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{PromptNode, ResponseMetadata, ResponseNode, SessionId, TokenUsage};
+
+    #[test]
+    fn test_usage_totals() {
+        let prompt = PromptNode::new(SessionId::new(), "Hi".to_string());
+        let metadata = ResponseMetadata {
+            model: "gpt-4".to_string(),
+            ..ResponseMetadata::default()
+        };
+        let response = Node::Response(ResponseNode::with_metadata(
+            prompt.id,
+            "Hello".to_string(),
+            TokenUsage::new(1000, 2000),
+            metadata,
+        ));
+
+        let mut totals = UsageTotals::of(&Node::Prompt(prompt));
+        totals.add(&UsageTotals::of(&response));
+        assert_eq!((totals.nodes, totals.prompts, totals.responses), (2, 1, 1));
+        assert_eq!(totals.total_tokens, 3000);
+        assert_eq!(totals.models["gpt-4"].completion_tokens, 2000);
+
+        let pricing = TokenPricing::per_1k_tokens(1.0, 2.0);
+        assert!((totals.cost(&pricing) - 5.0).abs() < 1e-9);
+        let prices = HashMap::from([("gpt-4".to_string(), pricing)]);
+        assert!((totals.cost_by_model(&prices) - 5.0).abs() < 1e-9);
+        assert_eq!(totals.cost_by_model(&HashMap::new()), 0.0);
+
+        totals.subtract(&UsageTotals::of(&response));
+        assert_eq!((totals.nodes, totals.total_tokens), (1, 0));
+        assert!(totals.models.is_empty());
+    }
+
+    #[test]
+    fn test_series_fills_gaps() {
+        let from = Utc.with_ymd_and_hms(2024, 3, 1, 10, 30, 0).unwrap();
+        let to = from + Duration::hours(3);
+        let stored = vec![UsageBucket {
+            start: Utc.with_ymd_and_hms(2024, 3, 1, 11, 0, 0).unwrap(),
+            totals: UsageTotals {
+                nodes: 2,
+                ..UsageTotals::default()
+            },
+        }];
+
+        let series = UsageSeries::fill(UsageGranularity::Hour, from, to, stored);
+        let starts: Vec<_> = series
+            .buckets
+            .iter()
+            .map(|b| b.start.format("%H").to_string())
+            .collect();
+        assert_eq!(starts, ["10", "11", "12", "13"]);
+        assert_eq!(series.buckets[1].totals.nodes, 2);
+        assert_eq!(series.totals().nodes, 2);
+    }
+
+    #[test]
+    fn test_bucket_keys_sort_by_time() {
+        let early = Utc.with_ymd_and_hms(1960, 1, 1, 0, 0, 0).unwrap();
+        let late = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let (a, b) = (
+            bucket_key(UsageGranularity::Day, early),
+            bucket_key(UsageGranularity::Day, late),
+        );
+        assert!(a < b);
+        assert_eq!(bucket_start_of(&a), Some(early));
+        assert!(bucket_key(UsageGranularity::Hour, late) < a);
+        assert_eq!(
+            "daily".parse::<UsageGranularity>().unwrap(),
+            UsageGranularity::Day
+        );
+    }
+}
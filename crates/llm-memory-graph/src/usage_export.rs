@@ -0,0 +1,482 @@
+//! Usage rollups packaged for sharing outside the system
+//!
+//! A [`UsageExport`] is a [`UsageSeries`] with a manifest describing what it
+//! covers, serializable as JSON. Usage statistics shared externally, with a
+//! vendor or in a public dashboard, can give away more than intended: a bucket
+//! counting three sessions says a lot about the three users behind them. An
+//! export can therefore be made with [`PrivacyOptions`], which apply two
+//! protections to every count:
+//!
+//! - **Laplace noise**: every count gets noise drawn from a Laplace
+//!   distribution, rounded to a whole number and clamped at zero. One record
+//!   falls in one bucket and changes at most [`RELEASED_COUNTS_PER_BUCKET`] of
+//!   its counts, so each count is noised with an equal share of `epsilon` and
+//!   the whole export is `epsilon`-differentially private
+//! - **k-anonymity thresholds**: noisy counts below `k` are reported as zero,
+//!   and models whose noisy response count in a bucket is below `k` are left
+//!   out. Token counts follow the response count they belong to. Thresholds
+//!   look only at noisy counts, so suppression spends no extra budget
+//!
+//! The parameters used are recorded in the [`UsageExportManifest`], so readers
+//! know how much to trust small numbers. The noise seed, if one was set, is
+//! not: it would let anyone holding the export take the noise back out.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use chrono::{Duration, Utc};
+//! use llm_memory_graph::engine::AsyncMemoryGraph;
+//! use llm_memory_graph::usage::UsageGranularity;
+//! use llm_memory_graph::usage_export::PrivacyOptions;
+//! use llm_memory_graph::Config;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let graph = AsyncMemoryGraph::open(Config::default()).await?;
+//!
+//! let now = Utc::now();
+//! let privacy = PrivacyOptions::new(0.5).with_k_anonymity(10);
+//! let export = graph
+//!     .export_usage(now - Duration::days(30)..now, UsageGranularity::Day, Some(&privacy))
+//!     .await?;
+//! std::fs::write("usage.json", export.to_json()?)?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::usage::{ModelUsage, UsageBucket, UsageGranularity, UsageSeries, UsageTotals};
+use crate::{Error, Result};
+use chrono::{DateTime, Utc};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+/// Current usage export format version
+pub const USAGE_EXPORT_VERSION: u32 = 1;
+
+/// Noise mechanism recorded in manifests
+pub const LAPLACE_MECHANISM: &str = "laplace";
+
+/// Counts in a bucket one record can change, which share the budget: five
+/// record counts, two token sums, and one model's responses and token sums
+pub const RELEASED_COUNTS_PER_BUCKET: u32 = 10;
+
+/// Token sensitivity used unless one is set: roughly the tokens one response
+/// can add to a count
+pub const DEFAULT_TOKEN_SENSITIVITY: f64 = 4096.0;
+
+/// How counts are protected in an export, see the [module docs](self)
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrivacyOptions {
+    /// Privacy budget of the whole export; smaller is more private and noisier
+    pub epsilon: f64,
+    /// Most one record changes a node, session or response count
+    pub count_sensitivity: f64,
+    /// Most one record changes a token count
+    pub token_sensitivity: f64,
+    /// Counts below this are suppressed; 0 or 1 suppresses nothing
+    pub k_anonymity: u64,
+    /// Seed for the noise, for reproducible exports; random if unset
+    pub seed: Option<u64>,
+}
+
+impl PrivacyOptions {
+    /// Laplace noise with budget `epsilon`, with no suppression
+    pub fn new(epsilon: f64) -> Self {
+        Self {
+            epsilon,
+            count_sensitivity: 1.0,
+            token_sensitivity: DEFAULT_TOKEN_SENSITIVITY,
+            k_anonymity: 0,
+            seed: None,
+        }
+    }
+
+    /// Suppress counts whose noisy value is below `k`
+    pub fn with_k_anonymity(mut self, k: u64) -> Self {
+        self.k_anonymity = k;
+        self
+    }
+
+    /// Scale token noise for records adding at most `sensitivity` tokens
+    pub fn with_token_sensitivity(mut self, sensitivity: f64) -> Self {
+        self.token_sensitivity = sensitivity;
+        self
+    }
+
+    /// Draw noise from a generator seeded with `seed`
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Check the parameters describe a usable mechanism
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::ValidationError`] if `epsilon` is not positive or a
+    /// sensitivity is negative or not finite.
+    pub fn validate(&self) -> Result<()> {
+        if !(self.epsilon.is_finite() && self.epsilon > 0.0) {
+            return Err(Error::ValidationError(format!(
+                "epsilon must be positive, got {}",
+                self.epsilon
+            )));
+        }
+        for (name, value) in [
+            ("count_sensitivity", self.count_sensitivity),
+            ("token_sensitivity", self.token_sensitivity),
+        ] {
+            if !(value.is_finite() && value >= 0.0) {
+                return Err(Error::ValidationError(format!(
+                    "{name} must be a non-negative number, got {value}"
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Privacy parameters an export was made with
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PrivacyManifest {
+    /// Noise mechanism, `laplace`
+    pub mechanism: String,
+    /// Privacy budget of the whole export
+    pub epsilon: f64,
+    /// Share of the budget each released count was noised with
+    pub epsilon_per_count: f64,
+    /// Sensitivity node, session and response counts were noised for
+    pub count_sensitivity: f64,
+    /// Sensitivity token counts were noised for
+    pub token_sensitivity: f64,
+    /// Counts whose noisy value was below this were suppressed
+    pub k_anonymity: u64,
+    /// Number of counts and model breakdowns suppressed
+    pub suppressed: usize,
+}
+
+/// What a usage export covers, and how it was protected
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UsageExportManifest {
+    /// Export format version
+    pub version: u32,
+    /// When the export was made
+    pub exported_at: DateTime<Utc>,
+    /// Width of each bucket
+    pub granularity: UsageGranularity,
+    /// Start of the first bucket
+    pub period_start: Option<DateTime<Utc>>,
+    /// End of the last bucket
+    pub period_end: Option<DateTime<Utc>>,
+    /// Number of buckets
+    pub bucket_count: usize,
+    /// Privacy protections applied, if any
+    pub privacy: Option<PrivacyManifest>,
+}
+
+/// Usage rollups ready to share, serializable as JSON
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UsageExport {
+    /// Coverage and privacy parameters
+    pub manifest: UsageExportManifest,
+    /// Buckets, oldest first, with protected counts if privacy was applied
+    pub buckets: Vec<UsageBucket>,
+}
+
+impl UsageExport {
+    /// Package `series`, protecting its counts with `privacy` if given
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::ValidationError`] if the privacy options are invalid.
+    pub fn new(series: UsageSeries, privacy: Option<&PrivacyOptions>) -> Result<Self> {
+        let step = series.granularity.step();
+        let mut manifest = UsageExportManifest {
+            version: USAGE_EXPORT_VERSION,
+            exported_at: Utc::now(),
+            granularity: series.granularity,
+            period_start: series.buckets.first().map(|bucket| bucket.start),
+            period_end: series.buckets.last().map(|bucket| bucket.start + step),
+            bucket_count: series.buckets.len(),
+            privacy: None,
+        };
+        let mut buckets = series.buckets;
+        if let Some(options) = privacy {
+            options.validate()?;
+            let mut noise = Noise::new(options);
+            for bucket in &mut buckets {
+                bucket.totals = noise.protect(&bucket.totals);
+            }
+            manifest.privacy = Some(PrivacyManifest {
+                mechanism: LAPLACE_MECHANISM.to_string(),
+                epsilon: options.epsilon,
+                epsilon_per_count: noise.epsilon,
+                count_sensitivity: options.count_sensitivity,
+                token_sensitivity: options.token_sensitivity,
+                k_anonymity: options.k_anonymity,
+                suppressed: noise.suppressed,
+            });
+        }
+        Ok(Self { manifest, buckets })
+    }
+
+    /// Totals over every bucket
+    pub fn totals(&self) -> UsageTotals {
+        let mut totals = UsageTotals::default();
+        for bucket in &self.buckets {
+            totals.add(&bucket.totals);
+        }
+        totals
+    }
+
+    /// Serialize the export as pretty-printed JSON
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if serialization fails.
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Parse an export from JSON
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `json` is not a usage export.
+    pub fn from_json(json: &str) -> Result<Self> {
+        Ok(serde_json::from_str(json)?)
+    }
+}
+
+/// Suppression and noise for one export
+struct Noise<'a> {
+    options: &'a PrivacyOptions,
+    /// Budget share of each released count
+    epsilon: f64,
+    rng: StdRng,
+    suppressed: usize,
+}
+
+impl<'a> Noise<'a> {
+    fn new(options: &'a PrivacyOptions) -> Self {
+        let rng = match options.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        Self {
+            options,
+            epsilon: options.epsilon / f64::from(RELEASED_COUNTS_PER_BUCKET),
+            rng,
+            suppressed: 0,
+        }
+    }
+
+    /// Protected copy of `totals`
+    fn protect(&mut self, totals: &UsageTotals) -> UsageTotals {
+        let counts = self.options.count_sensitivity;
+        let tokens = self.options.token_sensitivity;
+        let responses = self.noised(totals.responses, counts);
+        let mut protected = UsageTotals {
+            nodes: self.release(totals.nodes, counts),
+            sessions: self.release(totals.sessions, counts),
+            prompts: self.release(totals.prompts, counts),
+            responses: self.threshold(responses, responses),
+            tool_invocations: self.release(totals.tool_invocations, counts),
+            ..UsageTotals::default()
+        };
+        let prompt_tokens = self.noised(totals.prompt_tokens, tokens);
+        let completion_tokens = self.noised(totals.completion_tokens, tokens);
+        protected.prompt_tokens = self.threshold(prompt_tokens, responses);
+        protected.completion_tokens = self.threshold(completion_tokens, responses);
+        protected.total_tokens = protected.prompt_tokens + protected.completion_tokens;
+        for (model, usage) in &totals.models {
+            let usage = ModelUsage {
+                responses: self.noised(usage.responses, counts),
+                prompt_tokens: self.noised(usage.prompt_tokens, tokens),
+                completion_tokens: self.noised(usage.completion_tokens, tokens),
+            };
+            if usage.responses < self.options.k_anonymity {
+                if usage.responses > 0 {
+                    self.suppressed += 1;
+                }
+                continue;
+            }
+            protected.models.insert(model.clone(), usage);
+        }
+        protected
+    }
+
+    /// `value` noised, then suppressed if the noisy value is below k
+    fn release(&mut self, value: u64, sensitivity: f64) -> u64 {
+        let noisy = self.noised(value, sensitivity);
+        self.threshold(noisy, noisy)
+    }
+
+    /// Noisy `value`, or zero if the noisy size of the `group` it counts is
+    /// below k. Zeros are not counted as suppressed.
+    fn threshold(&mut self, value: u64, group: u64) -> u64 {
+        if group < self.options.k_anonymity {
+            if value > 0 {
+                self.suppressed += 1;
+            }
+            return 0;
+        }
+        value
+    }
+
+    fn noised(&mut self, value: u64, sensitivity: f64) -> u64 {
+        let scale = sensitivity / self.epsilon;
+        (value as f64 + laplace(&mut self.rng, scale))
+            .round()
+            .max(0.0) as u64
+    }
+}
+
+/// A draw from the Laplace distribution centred on zero with `scale`, by
+/// inverting its CDF
+fn laplace(rng: &mut StdRng, scale: f64) -> f64 {
+    if scale == 0.0 {
+        return 0.0;
+    }
+    // Open interval, so the logarithm stays finite
+    let u: f64 = rng.gen_range(f64::EPSILON..1.0) - 0.5;
+    -scale * u.signum() * (1.0 - 2.0 * u.abs()).ln()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use std::collections::BTreeMap;
+
+    fn series() -> UsageSeries {
+        let start = Utc.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap();
+        let busy = UsageTotals {
+            nodes: 500,
+            sessions: 100,
+            prompts: 200,
+            responses: 200,
+            tool_invocations: 0,
+            prompt_tokens: 2_000_000,
+            completion_tokens: 4_000_000,
+            total_tokens: 6_000_000,
+            models: BTreeMap::from([
+                (
+                    "gpt-4".to_string(),
+                    ModelUsage {
+                        responses: 198,
+                        prompt_tokens: 1_980_000,
+                        completion_tokens: 3_960_000,
+                    },
+                ),
+                (
+                    "rare-model".to_string(),
+                    ModelUsage {
+                        responses: 2,
+                        prompt_tokens: 20_000,
+                        completion_tokens: 40_000,
+                    },
+                ),
+            ]),
+        };
+        let quiet = UsageTotals {
+            nodes: 3,
+            sessions: 1,
+            prompts: 1,
+            responses: 1,
+            prompt_tokens: 10,
+            completion_tokens: 20,
+            total_tokens: 30,
+            ..UsageTotals::default()
+        };
+        UsageSeries {
+            granularity: UsageGranularity::Day,
+            buckets: vec![
+                UsageBucket {
+                    start,
+                    totals: busy,
+                },
+                UsageBucket {
+                    start: start + chrono::Duration::days(1),
+                    totals: quiet,
+                },
+                UsageBucket {
+                    start: start + chrono::Duration::days(2),
+                    totals: UsageTotals::default(),
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_export_without_privacy_is_exact() {
+        let export = UsageExport::new(series(), None).unwrap();
+        assert_eq!(export.buckets, series().buckets);
+        assert_eq!(export.manifest.bucket_count, 3);
+        assert_eq!(
+            export.manifest.period_end,
+            Some(Utc.with_ymd_and_hms(2024, 3, 4, 0, 0, 0).unwrap())
+        );
+        assert!(export.manifest.privacy.is_none());
+        let parsed = UsageExport::from_json(&export.to_json().unwrap()).unwrap();
+        assert_eq!(parsed, export);
+    }
+
+    #[test]
+    fn test_privacy_suppresses_and_noises() {
+        let privacy = PrivacyOptions::new(1.0).with_k_anonymity(5).with_seed(7);
+        let export = UsageExport::new(series(), Some(&privacy)).unwrap();
+
+        // The busy bucket is noised within what each count's budget share allows
+        let busy = &export.buckets[0].totals;
+        assert_ne!(busy, &series().buckets[0].totals);
+        assert!(busy.nodes.abs_diff(500) < 200);
+        assert!(busy.total_tokens.abs_diff(6_000_000) < 2_000_000);
+        assert_eq!(
+            busy.total_tokens,
+            busy.prompt_tokens + busy.completion_tokens
+        );
+        assert!(busy.models.contains_key("gpt-4"));
+
+        let recorded = export.manifest.privacy.as_ref().unwrap();
+        assert_eq!(recorded.mechanism, LAPLACE_MECHANISM);
+        assert_eq!((recorded.epsilon, recorded.k_anonymity), (1.0, 5));
+        assert!(
+            (recorded.epsilon_per_count * f64::from(RELEASED_COUNTS_PER_BUCKET) - 1.0).abs() < 1e-9
+        );
+        assert!(!export.to_json().unwrap().contains("seed"));
+
+        // The same seed gives the same noise
+        let again = UsageExport::new(series(), Some(&privacy)).unwrap();
+        assert_eq!(again.buckets, export.buckets);
+
+        assert!(UsageExport::new(series(), Some(&PrivacyOptions::new(0.0))).is_err());
+        assert!(UsageExport::new(
+            series(),
+            Some(&PrivacyOptions::new(1.0).with_token_sensitivity(-1.0))
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_privacy_thresholds_noisy_counts() {
+        // With a budget this large the noise rounds away, leaving suppression
+        let privacy = PrivacyOptions::new(1000.0)
+            .with_token_sensitivity(1.0)
+            .with_k_anonymity(5)
+            .with_seed(7);
+        let export = UsageExport::new(series(), Some(&privacy)).unwrap();
+
+        // The quiet bucket is below k everywhere, the empty one stays empty
+        assert!(export.buckets[1].totals.is_empty());
+        assert_eq!(export.buckets[1].totals.total_tokens, 0);
+        assert_eq!(export.buckets[2].totals, UsageTotals::default());
+
+        // The busy bucket keeps its counts but loses the rare model
+        let busy = &export.buckets[0].totals;
+        assert_eq!(busy.nodes, 500);
+        assert!(busy.models.contains_key("gpt-4"));
+        assert!(!busy.models.contains_key("rare-model"));
+        assert_eq!(export.manifest.privacy.unwrap().suppressed, 7);
+    }
+}
@@ -0,0 +1,478 @@
+//! Validation of requests made to the service
+//!
+//! Requests are checked before they reach the graph, so an oversized prompt,
+//! a malformed ID or a huge page is rejected as the caller's mistake instead
+//! of surfacing as a storage error. A [`RequestValidator`] collects every
+//! problem with a request, each tied to the field it concerns, and reports
+//! them together as an [`InvalidRequest`].
+//!
+//! Over gRPC an [`InvalidRequest`] becomes an `INVALID_ARGUMENT` status whose
+//! details carry a `google.rpc.BadRequest` listing the field violations, the
+//! form `tonic-types` and other gRPC clients decode; over HTTP it serializes
+//! to JSON.
+//!
+//! # Examples
+//!
+//! ```
+//! use llm_memory_graph::validation::{RequestLimits, RequestValidator};
+//!
+//! let limits = RequestLimits::default().with_max_content_bytes(16);
+//! let mut request = RequestValidator::new(&limits);
+//! request.uuid("session_id", "not-a-uuid");
+//! request.content("content", "far more than sixteen bytes");
+//!
+//! let err = request.finish().unwrap_err();
+//! assert_eq!(err.violations.len(), 2);
+//! assert_eq!(err.violations[0].field, "session_id");
+//! ```
+
+use crate::engine::ImportRecord;
+use crate::pagination::PageCursor;
+use crate::Node;
+use futures::{Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use tonic::codegen::Bytes;
+use tonic::{Code, Status};
+use uuid::Uuid;
+
+/// Default largest prompt or response content accepted, in bytes
+pub const DEFAULT_MAX_CONTENT_BYTES: usize = 1024 * 1024;
+
+/// Default largest number of items in one batch request
+pub const DEFAULT_MAX_BATCH_SIZE: usize = 1000;
+
+/// Default largest page of results returned by one request
+pub const DEFAULT_MAX_PAGE_SIZE: usize = 10_000;
+
+/// Type URL of the `google.rpc.BadRequest` status detail
+const BAD_REQUEST_TYPE_URL: &str = "type.googleapis.com/google.rpc.BadRequest";
+
+/// Size limits applied to requests
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RequestLimits {
+    /// Largest content string accepted, in bytes
+    pub max_content_bytes: usize,
+    /// Most items accepted in one batch
+    pub max_batch_size: usize,
+    /// Largest page size a request may ask for
+    pub max_page_size: usize,
+}
+
+impl Default for RequestLimits {
+    fn default() -> Self {
+        Self {
+            max_content_bytes: DEFAULT_MAX_CONTENT_BYTES,
+            max_batch_size: DEFAULT_MAX_BATCH_SIZE,
+            max_page_size: DEFAULT_MAX_PAGE_SIZE,
+        }
+    }
+}
+
+impl RequestLimits {
+    /// Set the largest content accepted, in bytes
+    #[must_use]
+    pub fn with_max_content_bytes(mut self, bytes: usize) -> Self {
+        self.max_content_bytes = bytes;
+        self
+    }
+
+    /// Set the most items accepted in one batch
+    #[must_use]
+    pub fn with_max_batch_size(mut self, size: usize) -> Self {
+        self.max_batch_size = size;
+        self
+    }
+
+    /// Set the largest page size a request may ask for
+    #[must_use]
+    pub fn with_max_page_size(mut self, size: usize) -> Self {
+        self.max_page_size = size;
+        self
+    }
+}
+
+/// A problem with one field of a request
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldViolation {
+    /// Path of the field, such as `metadata.model` or `node_ids[3]`
+    pub field: String,
+    /// What is wrong with it
+    pub description: String,
+}
+
+/// A request rejected because some of its fields are invalid
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InvalidRequest {
+    /// Every problem found, in the order the fields were checked
+    pub violations: Vec<FieldViolation>,
+}
+
+impl fmt::Display for InvalidRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("invalid request: ")?;
+        for (i, violation) in self.violations.iter().enumerate() {
+            if i > 0 {
+                f.write_str("; ")?;
+            }
+            write!(f, "{}: {}", violation.field, violation.description)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for InvalidRequest {}
+
+impl InvalidRequest {
+    /// The field violations carried by `status`, if it has any
+    pub fn from_status(status: &Status) -> Option<Self> {
+        use prost::Message;
+
+        let details = proto::RpcStatus::decode(status.details()).ok()?;
+        let bad_request = details
+            .details
+            .iter()
+            .find(|any| any.type_url == BAD_REQUEST_TYPE_URL)?;
+        let bad_request = proto::BadRequest::decode(bad_request.value.as_slice()).ok()?;
+        Some(Self {
+            violations: bad_request
+                .field_violations
+                .into_iter()
+                .map(|violation| FieldViolation {
+                    field: violation.field,
+                    description: violation.description,
+                })
+                .collect(),
+        })
+    }
+}
+
+impl From<InvalidRequest> for Status {
+    fn from(err: InvalidRequest) -> Self {
+        use prost::Message;
+
+        let message = err.to_string();
+        let bad_request = proto::BadRequest {
+            field_violations: err
+                .violations
+                .into_iter()
+                .map(|violation| proto::FieldViolation {
+                    field: violation.field,
+                    description: violation.description,
+                })
+                .collect(),
+        };
+        let details = proto::RpcStatus {
+            code: Code::InvalidArgument as i32,
+            message: message.clone(),
+            details: vec![prost_types::Any {
+                type_url: BAD_REQUEST_TYPE_URL.to_string(),
+                value: bad_request.encode_to_vec(),
+            }],
+        };
+        Status::with_details(
+            Code::InvalidArgument,
+            message,
+            Bytes::from(details.encode_to_vec()),
+        )
+    }
+}
+
+/// Collects the field violations of one request
+pub struct RequestValidator<'a> {
+    limits: &'a RequestLimits,
+    violations: Vec<FieldViolation>,
+}
+
+impl<'a> RequestValidator<'a> {
+    /// Check a request against `limits`
+    pub fn new(limits: &'a RequestLimits) -> Self {
+        Self {
+            limits,
+            violations: Vec::new(),
+        }
+    }
+
+    /// Record a problem with `field`
+    pub fn violation(&mut self, field: impl Into<String>, description: impl Into<String>) {
+        self.violations.push(FieldViolation {
+            field: field.into(),
+            description: description.into(),
+        });
+    }
+
+    /// Require `value` to be non-empty
+    pub fn required(&mut self, field: &str, value: &str) -> bool {
+        if value.is_empty() {
+            self.violation(field, "is required");
+            return false;
+        }
+        true
+    }
+
+    /// Require `value` to be a UUID, returning it if it is one
+    pub fn uuid(&mut self, field: &str, value: &str) -> Option<Uuid> {
+        if !self.required(field, value) {
+            return None;
+        }
+        let id = Uuid::parse_str(value).ok();
+        if id.is_none() {
+            self.violation(field, format!("{value:?} is not a valid UUID"));
+        }
+        id
+    }
+
+    /// Require every ID in `values` to be a UUID, and the list to fit in a
+    /// batch
+    pub fn uuids(&mut self, field: &str, values: &[String]) -> Vec<Uuid> {
+        self.batch(field, values.len());
+        values
+            .iter()
+            .enumerate()
+            .filter_map(|(i, value)| self.uuid(&format!("{field}[{i}]"), value))
+            .collect()
+    }
+
+    /// Require `value` to be non-empty and no larger than the content limit
+    pub fn content(&mut self, field: &str, value: &str) {
+        if self.required(field, value) {
+            self.content_size(field, value);
+        }
+    }
+
+    /// Require `value` to be no larger than the content limit
+    pub fn content_size(&mut self, field: &str, value: &str) {
+        if value.len() > self.limits.max_content_bytes {
+            self.violation(
+                field,
+                format!(
+                    "is {} bytes, more than the limit of {} bytes",
+                    value.len(),
+                    self.limits.max_content_bytes
+                ),
+            );
+        }
+    }
+
+    /// Require an imported prompt's or response's content to be within the
+    /// content limit; empty content is kept as exported
+    pub fn import_record(&mut self, field: &str, record: &ImportRecord) {
+        match record {
+            ImportRecord::Node(Node::Prompt(prompt)) => {
+                self.content_size(&format!("{field}.content"), &prompt.content);
+            }
+            ImportRecord::Node(Node::Response(response)) => {
+                self.content_size(&format!("{field}.content"), &response.content);
+            }
+            ImportRecord::Node(_) | ImportRecord::Edge(_) => {}
+        }
+    }
+
+    /// Require a batch of `len` items to be non-empty and within the batch
+    /// limit
+    pub fn batch(&mut self, field: &str, len: usize) {
+        if len == 0 {
+            self.violation(field, "must not be empty");
+        } else if len > self.limits.max_batch_size {
+            self.violation(
+                field,
+                format!(
+                    "has {len} items, more than the limit of {}",
+                    self.limits.max_batch_size
+                ),
+            );
+        }
+    }
+
+    /// Require a page size to be within the page limit; 0 asks for the
+    /// default page size
+    pub fn page_size(&mut self, field: &str, size: i64) {
+        if size < 0 {
+            self.violation(field, "must not be negative");
+        } else if size.unsigned_abs() > self.limits.max_page_size as u64 {
+            self.violation(
+                field,
+                format!(
+                    "is {size}, more than the limit of {}",
+                    self.limits.max_page_size
+                ),
+            );
+        }
+    }
+
+    /// Require `value`, if given, to be a page cursor, returning it if it is
+    /// one
+    pub fn cursor(&mut self, field: &str, value: &str) -> Option<PageCursor> {
+        if value.is_empty() {
+            return None;
+        }
+        let cursor = value.parse().ok();
+        if cursor.is_none() {
+            self.violation(field, "is not a valid page cursor");
+        }
+        cursor
+    }
+
+    /// Require an offset to be non-negative
+    pub fn offset(&mut self, field: &str, offset: i64) {
+        if offset < 0 {
+            self.violation(field, "must not be negative");
+        }
+    }
+
+    /// Whether any violation has been found so far
+    pub fn has_violations(&self) -> bool {
+        !self.violations.is_empty()
+    }
+
+    /// Succeed if no violation was found
+    ///
+    /// # Errors
+    ///
+    /// Returns every violation found as an [`InvalidRequest`].
+    pub fn finish(self) -> Result<(), InvalidRequest> {
+        if self.violations.is_empty() {
+            Ok(())
+        } else {
+            Err(InvalidRequest {
+                violations: self.violations,
+            })
+        }
+    }
+}
+
+/// Check each record of an import as it arrives, failing the import at the
+/// first record that breaks a limit
+pub fn validate_import_records<S>(
+    records: S,
+    limits: RequestLimits,
+) -> impl Stream<Item = crate::Result<ImportRecord>>
+where
+    S: Stream<Item = crate::Result<ImportRecord>>,
+{
+    let mut index = 0;
+    records.map(move |record| {
+        let record = record?;
+        index += 1;
+        let mut validator = RequestValidator::new(&limits);
+        validator.import_record(&format!("record[{index}]"), &record);
+        validator
+            .finish()
+            .map_err(|e| crate::Error::ValidationError(e.to_string()))?;
+        Ok(record)
+    })
+}
+
+/// The `google.rpc` messages carried in gRPC status details
+mod proto {
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub(super) struct RpcStatus {
+        #[prost(int32, tag = "1")]
+        pub code: i32,
+        #[prost(string, tag = "2")]
+        pub message: String,
+        #[prost(message, repeated, tag = "3")]
+        pub details: Vec<prost_types::Any>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub(super) struct BadRequest {
+        #[prost(message, repeated, tag = "1")]
+        pub field_violations: Vec<FieldViolation>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub(super) struct FieldViolation {
+        #[prost(string, tag = "1")]
+        pub field: String,
+        #[prost(string, tag = "2")]
+        pub description: String,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_validator() {
+        let limits = RequestLimits::default()
+            .with_max_content_bytes(8)
+            .with_max_batch_size(2)
+            .with_max_page_size(100);
+        let id = Uuid::new_v4();
+
+        let mut request = RequestValidator::new(&limits);
+        assert_eq!(request.uuid("session_id", &id.to_string()), Some(id));
+        request.content("content", "short");
+        request.page_size("limit", 100);
+        request.offset("offset", 0);
+        assert!(!request.has_violations());
+        assert!(request.finish().is_ok());
+
+        let mut request = RequestValidator::new(&limits);
+        request.uuid("session_id", "");
+        request.content("content", "more than eight bytes");
+        let ids = request.uuids(
+            "node_ids",
+            &[id.to_string(), "bad".to_string(), id.to_string()],
+        );
+        assert_eq!(ids, vec![id, id]);
+        request.page_size("limit", 101);
+        request.offset("offset", -1);
+        assert_eq!(request.cursor("cursor", ""), None);
+        assert_eq!(request.cursor("cursor", "bogus"), None);
+        let err = request.finish().unwrap_err();
+        let fields: Vec<_> = err.violations.iter().map(|v| v.field.as_str()).collect();
+        assert_eq!(
+            fields,
+            vec![
+                "session_id",
+                "content",
+                "node_ids",
+                "node_ids[1]",
+                "limit",
+                "offset",
+                "cursor"
+            ]
+        );
+        assert_eq!(err.violations[0].description, "is required");
+    }
+
+    #[test]
+    fn test_import_record_validation() {
+        let limits = RequestLimits::default().with_max_content_bytes(8);
+        let session = crate::ConversationSession::new();
+        let short = crate::PromptNode::new(session.id, "short".to_string());
+        let empty = crate::PromptNode::new(session.id, String::new());
+        let long = crate::ResponseNode::new(
+            short.id,
+            "more than eight bytes".to_string(),
+            crate::TokenUsage::new(1, 1),
+        );
+
+        let mut request = RequestValidator::new(&limits);
+        request.import_record("record[1]", &ImportRecord::Node(Node::Prompt(short)));
+        request.import_record("record[2]", &ImportRecord::Node(Node::Prompt(empty)));
+        request.import_record("record[3]", &ImportRecord::Node(Node::Session(session)));
+        request.import_record("record[4]", &ImportRecord::Node(Node::Response(long)));
+        let err = request.finish().unwrap_err();
+        assert_eq!(err.violations.len(), 1);
+        assert_eq!(err.violations[0].field, "record[4].content");
+    }
+
+    #[test]
+    fn test_invalid_request_status() {
+        let err = InvalidRequest {
+            violations: vec![FieldViolation {
+                field: "prompt_id".to_string(),
+                description: "\"x\" is not a valid UUID".to_string(),
+            }],
+        };
+        let status = Status::from(err.clone());
+        assert_eq!(status.code(), Code::InvalidArgument);
+        assert!(status.message().contains("prompt_id"));
+        assert_eq!(InvalidRequest::from_status(&status), Some(err));
+        assert_eq!(InvalidRequest::from_status(&Status::internal("boom")), None);
+    }
+}